@@ -0,0 +1,57 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The statistics of the persistent cache of verified procedures. This module
+//! only depends on `serde`, because it is also compiled into `prusti-launch`,
+//! which reads the statistics from the summaries of the crates.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The lookups of verification results in a cache.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct CacheStats {
+    /// The lookups that found a result.
+    pub hits: usize,
+    /// The lookups that did not find a result.
+    pub misses: usize,
+    /// The results that were discarded, because they were produced by
+    /// another backend or by another version of it.
+    pub invalidated: usize,
+}
+
+impl CacheStats {
+    /// The fraction of the lookups that found a result, or `None` if there
+    /// were no lookups.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            None
+        } else {
+            Some(self.hits as f64 / lookups as f64)
+        }
+    }
+
+    pub fn add(&mut self, other: &CacheStats) {
+        self.hits += other.hits;
+        self.misses += other.misses;
+        self.invalidated += other.invalidated;
+    }
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hit_rate = match self.hit_rate() {
+            Some(hit_rate) => format!("{:.0}%", 100.0 * hit_rate),
+            None => "-".to_string(),
+        };
+        write!(
+            f,
+            "{} hits, {} misses ({} hit rate), {} invalidated by a backend change",
+            self.hits, self.misses, hit_rate, self.invalidated,
+        )
+    }
+}
@@ -5,12 +5,16 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 mod commandline;
+mod settings;
 
-use config_crate::{Config, Environment, File};
+use config_crate::{Config, Environment, File, Source, Value};
 use self::commandline::CommandLine;
+use self::settings::OPTIONAL_SETTINGS;
+pub use self::settings::{ModuleSettings, Settings};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
-use std::sync::RwLock;
-use serde::Deserialize;
+use std::sync::{Mutex, MutexGuard, RwLock};
 
 
 #[derive(Debug, PartialEq, Eq)]
@@ -56,9 +60,146 @@ impl Optimizations {
     }
 }
 
+/// The kinds of obligations that are checked.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Obligations {
+    /// Arithmetic overflows, if `CHECK_OVERFLOWS` is enabled.
+    pub overflow: bool,
+    /// Out-of-bounds accesses to arrays and slices.
+    pub bounds: bool,
+    /// Other panics, e.g. `panic!`, `unreachable!` and divisions by zero.
+    pub panics: bool,
+    /// The preconditions of calls.
+    pub pre: bool,
+    /// The postconditions of functions.
+    pub post: bool,
+    /// Loop invariants.
+    pub invariants: bool,
+    /// The termination of recursive pure functions, and the termination
+    /// measures of `#[decreases]` annotations and `body_variant!`.
+    pub termination: bool,
+}
+
+impl Obligations {
+    fn all_disabled() -> Self {
+        Obligations {
+            overflow: false,
+            bounds: false,
+            panics: false,
+            pre: false,
+            post: false,
+            invariants: false,
+            termination: false,
+        }
+    }
+
+    fn all_enabled() -> Self {
+        Obligations {
+            overflow: true,
+            bounds: true,
+            panics: true,
+            pre: true,
+            post: true,
+            invariants: true,
+            termination: true,
+        }
+    }
+}
+
+/// A source of settings that is merged over the default values.
+#[derive(PartialEq)]
+enum SourceKind {
+    File(String),
+    Environment,
+    CommandLine,
+}
+
+impl SourceKind {
+    /// Describe how this source sets the setting `key`.
+    fn describe(&self, key: &str) -> String {
+        match self {
+            SourceKind::File(path) => format!("the file {}", path),
+            SourceKind::Environment => {
+                format!("the environment variable PRUSTI_{}", key.to_uppercase())
+            }
+            SourceKind::CommandLine => format!("the command-line argument -P{}", key),
+        }
+    }
+}
+
+/// The settings of Prusti, together with where they come from.
+#[derive(Clone)]
+struct State {
+    config: Config,
+    /// The typed view of `config`, which is rebuilt when a setting is
+    /// overridden. It is an error if a value does not have the type of its
+    /// setting.
+    settings: Result<Settings, String>,
+    /// The typed view of the default values, which is read instead of an
+    /// erroneous `settings` until `validate` reports the error.
+    defaults: Settings,
+    /// The names of the settings that Prusti knows.
+    known_settings: HashSet<String>,
+    /// The source of each setting that does not have its default value.
+    provenance: HashMap<String, String>,
+    /// The errors reported by `validate`.
+    errors: Vec<String>,
+}
+
+impl State {
+    fn new(defaults: Config) -> Self {
+        let mut known_settings: HashSet<String> = OPTIONAL_SETTINGS
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        if let Ok(values) = defaults.clone().try_into::<HashMap<String, Value>>() {
+            known_settings.extend(values.into_iter().map(|(name, _)| name));
+        }
+        let settings = typed_settings(&defaults);
+        State {
+            defaults: settings.clone().expect("invalid default value of a setting"),
+            config: defaults,
+            settings,
+            known_settings,
+            provenance: HashMap::new(),
+            errors: vec![],
+        }
+    }
+
+    /// Merge a source over the current settings. Unknown settings are errors,
+    /// except for environment variables, because their `PRUSTI_` prefix is
+    /// shared with other variables such as `PRUSTI_LOG`.
+    fn merge(&mut self, kind: SourceKind, source: Box<dyn Source + Send + Sync>) {
+        let values = match source.collect() {
+            Ok(values) => values,
+            Err(error) => {
+                self.errors.push(format!("could not read the settings: {}", error));
+                return;
+            }
+        };
+        for name in values.keys() {
+            let name = name.to_lowercase();
+            let description = kind.describe(&name);
+            if kind != SourceKind::Environment && !self.known_settings.contains(&name) {
+                self.errors.push(format!("unknown setting '{}' (set by {})", name, description));
+            }
+            self.provenance.insert(name, description);
+        }
+        if let Err(error) = self.config.merge(vec![source]) {
+            self.errors.push(format!("could not read the settings: {}", error));
+        }
+        self.settings = typed_settings(&self.config);
+    }
+}
+
+fn typed_settings(config: &Config) -> Result<Settings, String> {
+    config.clone()
+        .try_into()
+        .map_err(|error| format!("invalid value of a setting: {}", error))
+}
+
 lazy_static! {
-    // Is this RwLock<..> necessary?
-    static ref SETTINGS: RwLock<Config> = RwLock::new({
+    static ref STATE: RwLock<State> = RwLock::new({
         let mut settings = Config::default();
 
         // 1. Default values
@@ -67,7 +208,14 @@ lazy_static! {
         settings.set_default("CHECK_FOLDUNFOLD_STATE", false).unwrap();
         settings.set_default("CHECK_OVERFLOWS", false).unwrap();
         settings.set_default("CHECK_PANICS", true).unwrap();
+        settings.set_default("TRUNCATE_CASTS", false).unwrap();
+        settings.set_default("BIT_PRECISE", false).unwrap();
         settings.set_default("ENCODE_UNSIGNED_NUM_CONSTRAINT", false).unwrap();
+        settings.set_default("ENCODE_FLOATS_AS_REALS", false).unwrap();
+        settings.set_default("CHECK_RAW_POINTER_DEREFS", false).unwrap();
+        settings.set_default("RELAXED_PURITY", false).unwrap();
+        settings.set_default("COUNTEREXAMPLE", false).unwrap();
+        settings.set_default("INFER_LOOP_INVARIANTS", false).unwrap();
         settings.set_default("SIMPLIFY_ENCODING", true).unwrap();
         settings.set_default("LOG_DIR", "./log/").unwrap();
         settings.set_default("DUMP_DEBUG_INFO", false).unwrap();
@@ -77,19 +225,40 @@ lazy_static! {
         settings.set_default("DUMP_REBORROWING_DAG_IN_DEBUG_INFO", false).unwrap();
         settings.set_default("DUMP_BORROWCK_INFO", false).unwrap();
         settings.set_default("DUMP_VIPER_PROGRAM", false).unwrap();
+        settings.set_default("DUMP_VIPER_PROGRAM_JSON", false).unwrap();
+        settings.set_default("DUMP_VIR_ITEMS", false).unwrap();
+        settings.set_default("TRACE_PROVENANCE", "").unwrap();
         settings.set_default("FOLDUNFOLD_STATE_FILTER", "").unwrap();
         settings.set_default("CONTRACTS_LIB", "").unwrap();
         settings.set_default::<Vec<String>>("EXTRA_JVM_ARGS", vec![]).unwrap();
         settings.set_default::<Vec<String>>("EXTRA_VERIFIER_ARGS", vec![]).unwrap();
         settings.set_default("QUIET", false).unwrap();
+        settings.set_default("MESSAGE_FORMAT", "human").unwrap();
         settings.set_default("ASSERT_TIMEOUT", 10_000).unwrap();
         settings.set_default("USE_MORE_COMPLETE_EXHALE", true).unwrap();
         settings.set_default("SKIP_UNSUPPORTED_FEATURES", false).unwrap();
         settings.set_default("ALLOW_UNREACHABLE_UNSUPPORTED_CODE", false).unwrap();
         settings.set_default("NO_VERIFY", false).unwrap();
+        settings.set_default("ENCODE_ONLY", false).unwrap();
         settings.set_default("FULL_COMPILATION", false).unwrap();
+        settings.set_default("VERIFY_ONLY_TESTS", false).unwrap();
+        settings.set_default("SKIP_EXPANSIONS_OF", "").unwrap();
+        settings.set_default("WHOLE_PROGRAM", false).unwrap();
+        settings.set_default("REQUIRE_DECREASES", false).unwrap();
+        settings.set_default("CHECK_SEND_SYNC_IMPLS", false).unwrap();
+        settings.set_default("EXTERN_SPECS_DIR", "").unwrap();
+        settings.set_default("HAVOC_MUTABLE_GLOBAL_STATE", false).unwrap();
+        settings.set_default("CHECK", "all").unwrap();
+        settings.set_default("CHECKED_OBLIGATIONS", "all").unwrap();
         settings.set_default("JSON_COMMUNICATION", false).unwrap();
         settings.set_default("JSON_COMMUNICATION", false).unwrap();
+        settings.set_default("SERVER_CACHE_RESULTS", true).unwrap();
+        settings.set_default("CACHE_PATH", "target/prusti-cache").unwrap();
+        settings.set_default("FORCE_VERIFY", false).unwrap();
+        settings.set_default("CACHE_STATS", false).unwrap();
+        settings.set_default("JOBS", 1).unwrap();
+        settings.set_default("DETERMINISTIC", false).unwrap();
+        settings.set_default("COMPRESS_SERVER_REQUESTS", false).unwrap();
         settings.set_default("OPTIMIZATIONS","all").unwrap();
         settings.set_default("INTERN_NAMES", true).unwrap();
         settings.set_default("ENABLE_PURIFICATION_OPTIMIZATION", false).unwrap();
@@ -107,27 +276,34 @@ lazy_static! {
         settings.set_default::<Vec<String>>("DELETE_BASIC_BLOCKS", vec![]).unwrap();
 
 
+        let mut state = State::new(settings);
+
         // 2. Override with the optional TOML file "Prusti.toml" (if there is any)
-        settings.merge(
-            File::with_name("Prusti.toml").required(false)
-        ).unwrap();
+        state.merge(
+            SourceKind::File("Prusti.toml".to_string()),
+            Box::new(File::with_name("Prusti.toml").required(false)),
+        );
 
         // 3. Override with an optional TOML file specified by the `PRUSTI_CONFIG` env variable
-        settings.merge(
-            File::with_name(&env::var("PRUSTI_CONFIG").unwrap_or("".to_string())).required(false)
-        ).unwrap();
+        let config_file = env::var("PRUSTI_CONFIG").unwrap_or("".to_string());
+        state.merge(
+            SourceKind::File(config_file.clone()),
+            Box::new(File::with_name(&config_file).required(false)),
+        );
 
         // 4. Override with env variables (`PRUSTI_VIPER_BACKEND`, ...)
-        settings.merge(
-            Environment::with_prefix("PRUSTI").ignore_empty(true)
-        ).unwrap();
+        state.merge(
+            SourceKind::Environment,
+            Box::new(Environment::with_prefix("PRUSTI").ignore_empty(true)),
+        );
 
         // 5. Override with command-line arguments -P<arg>=<val>
-        settings.merge(
-            CommandLine::with_prefix("-P").ignore_invalid(true)
-        ).unwrap();
+        state.merge(
+            SourceKind::CommandLine,
+            Box::new(CommandLine::with_prefix("-P").ignore_invalid(true)),
+        );
 
-        settings
+        state
     });
 }
 
@@ -138,157 +314,371 @@ pub fn get_filtered_args() -> Vec<String> {
         .collect::<Vec<String>>()
 }
 
+lazy_static! {
+    /// Held by the scope of the overrides, see `override_settings`.
+    static ref OVERRIDE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Restores the settings that were overridden by `override_settings` when it
+/// is dropped.
+pub struct OverrideScope {
+    previous: Option<State>,
+    /// Released after the settings are restored.
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Drop for OverrideScope {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            *STATE.write().unwrap() = previous;
+        }
+    }
+}
+
+/// Override the given settings, with precedence over all other sources of
+/// settings, until the returned scope is dropped. The settings are global to
+/// the process, so the overrides are seen by all threads.
+///
+/// Only one scope exists at a time: if another thread holds one, this call
+/// blocks until it is dropped. Thus the scopes must not be nested, and
+/// dropping a scope restores exactly the settings that it replaced.
+pub fn override_settings(overrides: &[(String, String)]) -> OverrideScope {
+    // A run that panicked still restored the settings when its scope was
+    // dropped, so a poisoned lock can be reused.
+    let lock = OVERRIDE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut state = STATE.write().unwrap();
+    let previous = state.clone();
+    for (name, value) in overrides {
+        let name = name.to_lowercase();
+        if !state.known_settings.contains(&name) {
+            state.errors.push(format!("unknown setting '{}' (set by an override)", name));
+        }
+        state.config.set(&name, value.clone()).unwrap();
+        state.provenance.insert(name, "an override".to_string());
+    }
+    state.settings = typed_settings(&state.config);
+    OverrideScope {
+        previous: Some(previous),
+        _lock: lock,
+    }
+}
+
+/// Check the settings, returning the settings of `Prusti.toml`, of the
+/// `PRUSTI_CONFIG` file, of `-P` arguments and of overrides that Prusti does
+/// not know, and the values that do not have the type of their setting.
+pub fn validate() -> Result<(), Vec<String>> {
+    let state = STATE.read().unwrap();
+    let mut errors = state.errors.clone();
+    if let Err(error) = &state.settings {
+        errors.push(error.clone());
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// A copy of the typed settings.
+pub fn settings() -> Settings {
+    read(Settings::clone)
+}
+
+/// Describe where the setting `name` was set: a file, an environment
+/// variable, a command-line argument or an override. Returns `None` if the
+/// setting has its default value.
+pub fn provenance(name: &str) -> Option<String> {
+    STATE.read().unwrap().provenance.get(&name.to_lowercase()).cloned()
+}
+
 /// Generate a dump of the settings
 pub fn dump() -> String {
-    format!("{:?}", SETTINGS.read().unwrap())
+    let state = STATE.read().unwrap();
+    let provenance: BTreeMap<_, _> = state.provenance.iter().collect();
+    format!("{:?}\nprovenance: {:?}", state.config, provenance)
+}
+
+/// Read a value of the typed settings. If a value does not have the type of
+/// its setting, the default values are read, so that settings such as
+/// `be_rustc` can be read before `validate` reports the error.
+fn read<T>(get: impl FnOnce(&Settings) -> T) -> T {
+    let state = STATE.read().unwrap();
+    match &state.settings {
+        Ok(settings) => get(settings),
+        Err(_) => get(&state.defaults),
+    }
 }
 
-fn read_optional_setting<T>(name: &'static str) -> Option<T>
-where
-    T: Deserialize<'static>,
-{
-    SETTINGS.read().unwrap().get(name).ok()
+thread_local! {
+    /// The settings of the module of the item that this thread processes,
+    /// see `enter_module`.
+    static MODULE_SETTINGS: RefCell<Option<ModuleSettings>> = RefCell::new(None);
 }
 
-fn read_setting<T>(name: &'static str) -> T
-where
-    T: Deserialize<'static>,
-{
-    read_optional_setting(name).unwrap()
+/// Restores the module settings of the thread when it is dropped.
+pub struct ModuleScope {
+    previous: Option<ModuleSettings>,
+}
+
+impl Drop for ModuleScope {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        MODULE_SETTINGS.with(|settings| *settings.borrow_mut() = previous);
+    }
+}
+
+/// Apply the `[module."<path>"]` tables of the settings that contain the item
+/// with the path `item_path` (relative to the crate root, e.g.
+/// `parser::parse`) to the settings read by this thread, until the returned
+/// scope is dropped.
+pub fn enter_module(item_path: &str) -> ModuleScope {
+    let settings = read(|settings| ModuleSettings::of_item(&settings.module, item_path));
+    let previous = MODULE_SETTINGS.with(|current| current.replace(Some(settings)));
+    ModuleScope { previous }
+}
+
+/// Read a setting of the module entered by this thread, if it sets it.
+fn read_module<T>(get: impl FnOnce(&ModuleSettings) -> Option<T>) -> Option<T> {
+    MODULE_SETTINGS.with(|settings| settings.borrow().as_ref().and_then(get))
+}
+
+/// Whether a `[module."<path>"]` table overrides the timeout of its methods,
+/// which are then verified as separate programs.
+pub fn has_module_timeouts() -> bool {
+    read(|settings| settings.module.values().any(|module| module.assert_timeout.is_some()))
 }
 
 /// Should Prusti behave exactly like rustc?
 pub fn be_rustc() -> bool {
-    read_setting("BE_RUSTC")
+    read(|settings| settings.be_rustc)
 }
 
 /// Generate additional, *slow*, checks for the foldunfold algorithm
 pub fn check_foldunfold_state() -> bool {
-    read_setting("CHECK_FOLDUNFOLD_STATE")
+    read(|settings| settings.check_foldunfold_state)
 }
 
 /// The Viper backend that should be used for the verification
 pub fn viper_backend() -> String {
-    read_setting::<String>("VIPER_BACKEND")
+    read(|settings| settings.viper_backend.clone())
         .to_lowercase()
         .trim()
         .to_string()
 }
 
-/// Should we check absence of panics?
+/// Should we check absence of panics? A `[module."<path>"]` table can
+/// override this for the items of a module.
 pub fn check_panics() -> bool {
-    read_setting("CHECK_PANICS")
+    read_module(|module| module.check_panics)
+        .unwrap_or_else(|| read(|settings| settings.check_panics))
+}
+
+/// Encode the integer casts that might not fit into the target type as a
+/// truncation, as done by Rust, instead of checking that the value fits
+/// (if `CHECK_OVERFLOWS` is enabled) or keeping the value unchanged.
+pub fn truncate_casts() -> bool {
+    read(|settings| settings.truncate_casts)
+}
+
+/// Encode the integers of all functions as machine integers, as if every
+/// function was marked with `#[bit_precise]`: arithmetic wraps around the
+/// range of the integer type and bitwise operations are encoded bit by bit.
+pub fn bit_precise() -> bool {
+    read(|settings| settings.bit_precise)
 }
 
 /// Should we simplify the encoding before passing it to Viper?
 pub fn simplify_encoding() -> bool {
-    read_setting("SIMPLIFY_ENCODING")
+    read(|settings| settings.simplify_encoding)
 }
 
 /// Should we dump debug files?
 pub fn dump_debug_info() -> bool {
-    read_setting("DUMP_DEBUG_INFO")
+    read(|settings| settings.dump_debug_info)
 }
 
 /// Should we dump debug files for fold/unfold generation?
 pub fn dump_debug_info_during_fold() -> bool {
-    read_setting("DUMP_DEBUG_INFO_DURING_FOLD")
+    read(|settings| settings.dump_debug_info_during_fold)
 }
 
 /// What is the longest allowed length of a log file name? If this is exceeded,
 /// the file name is truncated.
 pub fn max_log_file_name_length() -> usize {
-    read_setting("MAX_LOG_FILE_NAME_LENGTH")
+    read(|settings| settings.max_log_file_name_length)
 }
 
 /// Should we dump the branch context state in debug files?
 pub fn dump_path_ctxt_in_debug_info() -> bool {
-    read_setting("DUMP_PATH_CTXT_IN_DEBUG_INFO")
+    read(|settings| settings.dump_path_ctxt_in_debug_info)
 }
 
 /// Should we dump the reborrowing DAGs in debug files?
 pub fn dump_reborrowing_dag_in_debug_info() -> bool {
-    read_setting("DUMP_REBORROWING_DAG_IN_DEBUG_INFO")
+    read(|settings| settings.dump_reborrowing_dag_in_debug_info)
 }
 
 /// Should we dump borrowck info?
 pub fn dump_borrowck_info() -> bool {
-    read_setting("DUMP_BORROWCK_INFO")
+    read(|settings| settings.dump_borrowck_info)
 }
 
-/// Should we dump the Viper program?
+/// Should we dump the Viper program? Next to each dumped `<name>.vpr` file,
+/// `<name>.names.json` maps the identifiers of the program to the Rust items
+/// and types that they encode, with their location in the source code.
 pub fn dump_viper_program() -> bool {
-    read_setting("DUMP_VIPER_PROGRAM")
+    read(|settings| settings.dump_viper_program)
+}
+
+/// Should we dump each verified program as `<name>.vpr.json`, the JSON form
+/// of its Silver AST (see `AstUtils::to_json`), including the imported Viper
+/// sources? Unlike the `.vpr` dump, it can be read without a Viper parser.
+pub fn dump_viper_program_json() -> bool {
+    read(|settings| settings.dump_viper_program_json)
+}
+
+/// Should we dump the encoding of each item of the final VIR program, as a
+/// JSON map from the item to its text, to `vir_items/<crate name>.json`? Comments
+/// are omitted, so that the dumps of two runs can be compared.
+pub fn dump_vir_items() -> bool {
+    read(|settings| settings.dump_vir_items)
+}
+
+/// Print how the verification errors whose message contains the given text
+/// were produced: the failing Viper assertion, the VIR statement, the encoder
+/// code that registered its position and the MIR statement being encoded.
+/// The text `all` selects all errors; an empty text disables the tracing.
+pub fn trace_provenance() -> Option<String> {
+    let filter = read(|settings| settings.trace_provenance.clone());
+    if filter.is_empty() {
+        None
+    } else {
+        Some(filter)
+    }
 }
 
-/// The Viper backend that should be used for the verification
 pub fn foldunfold_state_filter() -> String {
-    read_setting("FOLDUNFOLD_STATE_FILTER")
+    read(|settings| settings.foldunfold_state_filter.clone())
 }
 
 /// In which folder should we sore log/dumps?
 pub fn log_dir() -> String {
-    read_setting("LOG_DIR")
+    read(|settings| settings.log_dir.clone())
 }
 
-/// Check binary operations for overflows
+/// Check binary operations for overflows. A `[module."<path>"]` table can
+/// disable the checks for the items of a module, whose arithmetic then wraps
+/// around the range of the integer type (see `integer_bounds`).
 pub fn check_overflows() -> bool {
-    read_setting("CHECK_OVERFLOWS")
+    integer_bounds() && read_module(|module| module.check_overflows) != Some(false)
+}
+
+/// Encode the range of the integer types, which is done if overflows are
+/// checked globally. The ranges are part of the encoding of the types, so
+/// they are not changed by a `[module."<path>"]` table.
+pub fn integer_bounds() -> bool {
+    read(|settings| settings.check_overflows)
 }
 
 /// Encode (and check) that unsigned integers are non-negative.
 pub fn encode_unsigned_num_constraint() -> bool {
-    read_setting("ENCODE_UNSIGNED_NUM_CONSTRAINT")
+    read(|settings| settings.encode_unsigned_num_constraint)
+}
+
+/// Approximate `f32` and `f64` values by real numbers, ignoring rounding,
+/// infinities and NaN. When disabled, the floating point operations are
+/// uninterpreted and only the equality of values is known to the verifier.
+pub fn encode_floats_as_reals() -> bool {
+    read(|settings| settings.encode_floats_as_reals)
+}
+
+/// Check that each dereferenced raw pointer is valid, which has to be stated
+/// with `ptr_valid` in the specifications. When disabled, raw pointers are
+/// dereferenced like references.
+pub fn check_raw_pointer_derefs() -> bool {
+    read(|settings| settings.check_raw_pointer_derefs)
+}
+
+/// Allow pure functions to allocate boxes and to move the owned data, which
+/// are encoded by the values that they contain.
+pub fn relaxed_purity() -> bool {
+    read(|settings| settings.relaxed_purity)
+}
+
+/// Ask the backend for a model of each verification failure, and report the
+/// values of the arguments of the failing procedure. For a failing loop
+/// invariant, the values at the loop head are also reported. Only supported
+/// by Silicon.
+pub fn counterexample() -> bool {
+    read(|settings| settings.counterexample)
+}
+
+/// Infer the invariants of simple numeric loops that have no
+/// `body_invariant!`: the loop guard, and lower or upper bounds of counters
+/// and accumulators that the loop only increases or only decreases. Loops
+/// that need other invariants still require an explicit `body_invariant!`.
+pub fn infer_loop_invariants() -> bool {
+    read(|settings| settings.infer_loop_invariants)
 }
 
 /// Location of 'libprusti_contracts*.rlib'
 pub fn contracts_lib() -> String {
-    read_setting("CONTRACTS_LIB")
+    read(|settings| settings.contracts_lib.clone())
 }
 
 /// Get extra JVM arguments
 pub fn extra_jvm_args() -> Vec<String> {
-    read_setting("EXTRA_JVM_ARGS")
+    read(|settings| settings.extra_jvm_args.clone())
 }
 
 /// Get extra arguments for the verifier
 pub fn extra_verifier_args() -> Vec<String> {
-    read_setting("EXTRA_VERIFIER_ARGS")
+    read(|settings| settings.extra_verifier_args.clone())
 }
 
 /// Should we hide user messages?
 pub fn quiet() -> bool {
-    read_setting("QUIET")
+    read(|settings| settings.quiet)
 }
 
-/// The assert timeout (in milliseconds) passed to Silicon.
+/// Whether the diagnostics are emitted as rustc's JSON diagnostics, with the
+/// details of the Prusti errors (see `PrustiError::emit`). This is the case
+/// if `MESSAGE_FORMAT` is `json`, which `prusti-rustc --message-format=json`
+/// and `--error-format=json` set.
+pub fn json_messages() -> bool {
+    read(|settings| settings.message_format.trim().eq_ignore_ascii_case("json"))
+}
+
+/// The assert timeout (in milliseconds) passed to Silicon. A
+/// `[module."<path>"]` table can override this for the items of a module.
 pub fn assert_timeout() -> u64 {
-    read_setting("ASSERT_TIMEOUT")
+    read_module(|module| module.assert_timeout)
+        .unwrap_or_else(|| read(|settings| settings.assert_timeout))
 }
 
 /// Use the Silicon configuration option `--enableMoreCompleteExhale`.
 pub fn use_more_complete_exhale() -> bool {
-    read_setting("USE_MORE_COMPLETE_EXHALE")
+    read(|settings| settings.use_more_complete_exhale)
 }
 
 /// Should Prusti print the items collected for verification.
 pub fn print_collected_verification_items() -> bool {
-    read_setting("PRINT_COLLECTED_VERIFICATION_ITEMS")
+    read(|settings| settings.print_collected_verification_items)
 }
 
 /// Should Prusti print the AST with desugared specifications.
 pub fn print_desugared_specs() -> bool {
-    read_setting("PRINT_DESUGARED_SPECS")
+    read(|settings| settings.print_desugared_specs)
 }
 
 /// Should Prusti print the type-checked specifications.
 pub fn print_typeckd_specs() -> bool {
-    read_setting("PRINT_TYPECKD_SPECS")
+    read(|settings| settings.print_typeckd_specs)
 }
 
 /// Should Prusti hide the UUIDs of expressions and specifications.
 pub fn hide_uuids() -> bool {
-    read_setting("HIDE_UUIDS")
+    read(|settings| settings.hide_uuids)
 }
 
 /**
@@ -300,26 +690,45 @@ It also doesn't make much sense to set this to less than that, since then the se
 pub fn server_max_stored_verifiers() -> Option<usize> {
     // TODO: default to below in prusti-server
     // TODO: warn if lower than below
-    read_optional_setting("SERVER_MAX_STORED_VERIFIERS")
+    read(|settings| settings.server_max_stored_verifiers)
+}
+
+/// The number of methods of a crate that are verified concurrently. With
+/// more than one job, each method is verified as a separate Viper program.
+/// `cargo-prusti --jobs` sets this.
+pub fn jobs() -> usize {
+    read(|settings| settings.jobs)
 }
 
 /// The maximum amount of verification requests the server will work on concurrently.
 ///
 /// If not set, this defaults to the number of (logical) cores on the system
 pub fn server_max_concurrency() -> Option<usize> {
-    read_optional_setting("SERVER_MAX_CONCURRENCY")
+    read(|settings| settings.server_max_concurrency)
 }
 
 /// When set, Prusti will connect to this server and use it for its verification backend (i.e. the things using the JVM/Viper).
 /// Set to "MOCK" to run the server off-thread, effectively mocking connecting to a server without having to start it up separately.
 /// e.g. "127.0.0.1:2468"
 pub fn server_address() -> Option<String> {
-    read_optional_setting("SERVER_ADDRESS")
+    read(|settings| settings.server_address.clone())
+}
+
+/// If true, the server reuses the result of a previous request with the same
+/// crate, program name, program and backend configuration.
+pub fn server_cache_results() -> bool {
+    read(|settings| settings.server_cache_results)
 }
 
 /// If true, communication with the server will be encoded as json and not the default of bincode.
 pub fn json_communication() -> bool {
-    read_setting("JSON_COMMUNICATION")
+    read(|settings| settings.json_communication)
+}
+
+/// If true, verification requests sent to the server are compressed with gzip.
+/// This is worth it only when the server runs on a remote machine.
+pub fn compress_server_requests() -> bool {
+    read(|settings| settings.compress_server_requests)
 }
 
 /// Disable mangling of generated Viper names.
@@ -327,21 +736,21 @@ pub fn json_communication() -> bool {
 /// **Note:** This is very likely to result in invalid programs being
 /// generated because of name collisions.
 pub fn disable_name_mangling() -> bool {
-    read_setting("DISABLE_NAME_MANGLING")
+    read(|settings| settings.disable_name_mangling)
 }
 
 /// Verify only the preamble: domains, functions, and predicates.
 ///
 /// **Note:** With this flag enabled, no methods are verified!
 pub fn verify_only_preamble() -> bool {
-    read_setting("VERIFY_ONLY_PREAMBLE")
+    read(|settings| settings.verify_only_preamble)
 }
 
 /// Verify only the path given in ``VERIFY_ONLY_BASIC_BLOCK_PATH``.
 ///
 /// **Note:** This flag is only for debugging Prusti!
 pub fn enable_verify_only_basic_block_path() -> bool {
-    read_setting("ENABLE_VERIFY_ONLY_BASIC_BLOCK_PATH")
+    read(|settings| settings.enable_verify_only_basic_block_path)
 }
 
 /// Verify only the single execution path goes through the given basic blocks.
@@ -351,12 +760,12 @@ pub fn enable_verify_only_basic_block_path() -> bool {
 ///
 /// **Note:** This flag is only for debugging Prusti!
 pub fn verify_only_basic_block_path() -> Vec<String> {
-    read_setting("VERIFY_ONLY_BASIC_BLOCK_PATH")
+    read(|settings| settings.verify_only_basic_block_path.clone())
 }
 
 /// Which optimizations should be enabled
 pub fn optimizations() -> Optimizations {
-    let optimizations_string = read_setting::<String>("OPTIMIZATIONS");
+    let optimizations_string = read(|settings| settings.optimizations.clone());
 
     let mut opt = Optimizations::all_disabled();
 
@@ -381,36 +790,260 @@ pub fn optimizations() -> Optimizations {
 }
 
 pub fn enable_purification_optimization() -> bool {
-    read_setting("ENABLE_PURIFICATION_OPTIMIZATION")
+    read(|settings| settings.enable_purification_optimization)
 }
 
 /// Replace the given basic blocks with ``assume false``.
 pub fn delete_basic_blocks() -> Vec<String> {
-    read_setting("DELETE_BASIC_BLOCKS")
+    read(|settings| settings.delete_basic_blocks.clone())
+}
+
+/// A JSON file listing the verification failures that are accepted, which
+/// are then reported as warnings. `cargo-prusti --baseline` sets this.
+pub fn baseline() -> Option<String> {
+    read(|settings| settings.baseline.clone())
 }
 
-/// Skip features that are unsupported or partially supported
+/// The directory of the persistent cache of the procedures that were verified
+/// successfully. An unchanged procedure in the cache is not verified again.
+/// An empty path disables the cache.
+pub fn cache_path() -> String {
+    read(|settings| settings.cache_path.clone())
+}
+
+/// Verify all procedures, including the ones that are unchanged since they
+/// were last verified successfully.
+pub fn force_verify() -> bool {
+    read(|settings| settings.force_verify)
+}
+
+/// Make the outcome of a verification reproducible across runs: the seeds of
+/// the solver are pinned, the backend verifies the members of a program one
+/// after another, and the declarations of the Viper program and the reported
+/// verification errors are sorted by name and position. Timeouts still depend
+/// on the speed of the machine.
+pub fn deterministic() -> bool {
+    read(|settings| settings.deterministic)
+}
+
+/// Report how many procedures were found in the verification caches, and how
+/// many cached results were invalidated by a change of the backend.
+/// `cargo-prusti --cache-stats` reports the totals of all crates instead.
+pub fn cache_stats() -> bool {
+    read(|settings| settings.cache_stats)
+}
+
+/// Skip features that are unsupported or partially supported. A
+/// `[module."<path>"]` table can override this for the items of a module.
 pub fn skip_unsupported_features() -> bool {
-    read_setting("SKIP_UNSUPPORTED_FEATURES")
+    read_module(|module| module.skip_unsupported_features)
+        .unwrap_or_else(|| read(|settings| settings.skip_unsupported_features))
+}
+
+/// The comma-separated kinds of problems that are reported as errors, and
+/// thus make Prusti exit with a non-zero exit code: `verification`
+/// (verification failures), `timeout` (the verifier timed out) and
+/// `unsupported` (unsupported features). Problems of the other kinds are
+/// reported as warnings. Invalid specifications and internal errors are
+/// always errors.
+///
+/// If not set, all kinds are errors, except for unsupported features when
+/// `SKIP_UNSUPPORTED_FEATURES` is enabled.
+pub fn fail_on() -> Option<Vec<String>> {
+    read(|settings| settings.fail_on.clone()).map(|kinds| {
+        kinds.split(',')
+            .map(|kind| kind.trim())
+            .filter(|kind| !kind.is_empty())
+            .filter(|kind| match *kind {
+                "verification" | "timeout" | "unsupported" => true,
+                _ => {
+                    warn!("Ignoring unknown kind of problem '{}' in FAIL_ON", kind);
+                    false
+                }
+            })
+            .map(|kind| kind.to_string())
+            .collect()
+    })
 }
 
 /// Encode unsupported code as `assert false`, so that we report error messages
 /// only for unsupported code that is actually reachable.
 pub fn allow_unreachable_unsupported_code() -> bool {
-    read_setting("ALLOW_UNREACHABLE_UNSUPPORTED_CODE")
+    read(|settings| settings.allow_unreachable_unsupported_code)
 }
 
 /// Skip the verification
 pub fn no_verify() -> bool {
-    read_setting("NO_VERIFY")
+    read(|settings| settings.no_verify)
+}
+
+/// Encode the crate to Viper, but skip the verification of the encoding
+pub fn encode_only() -> bool {
+    read(|settings| settings.encode_only)
 }
 
 /// Continue the compilation and generate the binary after Prusti terminates
 pub fn full_compilation() -> bool {
-    read_setting("FULL_COMPILATION")
+    read(|settings| settings.full_compilation)
+}
+
+/// Verify only the functions annotated with `#[test]` (e.g. with
+/// `cargo prusti --tests`). Since tests take no inputs, this statically
+/// proves their assertions; the other functions are used only through
+/// their contracts.
+pub fn verify_only_tests() -> bool {
+    read(|settings| settings.verify_only_tests)
+}
+
+/// Do not verify the functions generated by the macros whose path matches one
+/// of these comma-separated patterns, e.g. `serde_derive::*`. A `*` matches
+/// any sequence of characters. Crates can also list macros with
+/// `#![prusti::skip_expansion_of(path, ...)]`.
+pub fn skip_expansions_of() -> Vec<String> {
+    read(|settings| settings.skip_expansions_of.clone())
+        .split(',')
+        .map(|pattern| pattern.trim().to_string())
+        .filter(|pattern| !pattern.is_empty())
+        .collect()
+}
+
+/// Assume that all the implementations of a trait are known (closed world),
+/// instead of verifying each crate modularly. Trait objects of a trait with
+/// a single implementation are then encoded as the implementing type, so
+/// that calls on them use the contracts of the implementation.
+///
+/// This is sound only if no other crate can implement the traits, e.g. when
+/// verifying a binary.
+pub fn whole_program() -> bool {
+    read(|settings| settings.whole_program)
+}
+
+/// Require a `#[decreases]` annotation also on self-recursive pure functions.
+/// Mutually recursive pure functions always require one.
+pub fn require_decreases() -> bool {
+    read(|settings| settings.require_decreases)
+}
+
+/// Require every `unsafe impl Send` and `unsafe impl Sync` of a local type to
+/// be justified by a `#[thread_safety_invariant]` on an impl block of the type.
+pub fn check_send_sync_impls() -> bool {
+    read(|settings| settings.check_send_sync_impls)
+}
+
+/// Check only the absence of panics (and of overflows, if enabled) and the
+/// preconditions of calls, but not the postconditions of functions. This is
+/// enabled by setting `CHECK` to `panics` (e.g. with `cargo prusti
+/// --check=panics`); the default is `all`. Postconditions are still assumed
+/// at call sites.
+pub fn check_only_panics() -> bool {
+    let check = read(|settings| settings.check.clone());
+    match check.trim() {
+        "all" => false,
+        "panics" => true,
+        other => {
+            warn!("Ignoring unknown value '{}' of CHECK", other);
+            false
+        }
+    }
+}
+
+/// The directory of the external specification files of the crate, relative
+/// to the directory of its `Cargo.toml` (or to the working directory, outside
+/// of Cargo). Each `.rs` file in it is compiled as a module of the crate, so
+/// that its `#[extern_spec]` blocks provide contracts for the functions of
+/// other crates. The loading is disabled by default, with an empty value,
+/// so that no directory of the crate is compiled unless it is requested.
+pub fn extern_specs_dir() -> String {
+    read(|settings| settings.extern_specs_dir.clone())
+}
+
+/// Model every access of mutable global state (`static mut` and
+/// `thread_local!`) as returning a fresh location with an arbitrary value,
+/// instead of reporting it as unsupported. The model is trusted: it does not
+/// account for aliasing between the accesses, so the code using it is verified
+/// only under the assumption that no other code modifies the state meanwhile.
+pub fn havoc_mutable_global_state() -> bool {
+    read(|settings| settings.havoc_mutable_global_state)
+}
+
+/// The comma-separated kinds of obligations that are checked: `overflow`,
+/// `bounds`, `panics`, `pre`, `post`, `invariants` and `termination`, or
+/// `all`. Obligations that are not checked are assumed to hold. With
+/// `CHECK=panics`, postconditions are never checked.
+pub fn checked_obligations() -> Obligations {
+    let obligations_string = read(|settings| settings.checked_obligations.clone());
+
+    let mut obligations = Obligations::all_disabled();
+
+    for s in obligations_string.split(",") {
+        let trimmed = s.trim();
+        match trimmed {
+            "all" => obligations = Obligations::all_enabled(),
+            "overflow" => obligations.overflow = true,
+            "bounds" => obligations.bounds = true,
+            "panics" => obligations.panics = true,
+            "pre" => obligations.pre = true,
+            "post" => obligations.post = true,
+            "invariants" => obligations.invariants = true,
+            "termination" => obligations.termination = true,
+            "" => {}
+            _ => warn!("Ignoring unknown kind of obligation '{}' in CHECKED_OBLIGATIONS", trimmed),
+        }
+    }
+    if check_only_panics() {
+        obligations.post = false;
+    }
+    obligations
 }
 
 /// Intern Viper identifiers to shorten them when possible.
 pub fn intern_names() -> bool {
-    read_setting("INTERN_NAMES")
+    read(|settings| settings.intern_names)
+}
+
+/// When set, Prusti writes a JSON summary of the verification of each crate
+/// into this directory. `cargo-prusti` uses it to report a summary of the run.
+pub fn summary_dir() -> Option<String> {
+    read(|settings| settings.summary_dir.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_are_typed_and_tracked() {
+        {
+            let _scope = override_settings(&[("JOBS".to_string(), "3".to_string())]);
+            assert_eq!(jobs(), 3);
+            assert_eq!(provenance("jobs"), Some("an override".to_string()));
+            assert_eq!(provenance("cache_path"), None);
+        }
+        assert_eq!(provenance("jobs"), None);
+
+        {
+            let _scope = override_settings(&[("JOBS".to_string(), "abc".to_string())]);
+            assert_eq!(jobs(), 1);
+            let errors = validate().unwrap_err();
+            assert!(errors.iter().any(|error| error.contains("invalid value of a setting")));
+        }
+
+        let _scope = override_settings(&[("no_such_setting".to_string(), "true".to_string())]);
+        let errors = validate().unwrap_err();
+        assert!(errors.iter().any(|error| error.contains("'no_such_setting'")));
+    }
+
+    #[test]
+    fn overrides_of_threads_apply_one_at_a_time() {
+        let scope = override_settings(&[("ASSERT_TIMEOUT".to_string(), "3000".to_string())]);
+        let other = std::thread::spawn(|| {
+            let _scope = override_settings(&[("ASSERT_TIMEOUT".to_string(), "5000".to_string())]);
+            assert_timeout()
+        });
+        // The other thread waits until this scope is dropped.
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert_eq!(assert_timeout(), 3000);
+        drop(scope);
+        assert_eq!(other.join().unwrap(), 5000);
+    }
 }
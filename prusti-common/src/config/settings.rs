@@ -0,0 +1,165 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// The typed settings of Prusti, deserialized from the merged sources of
+/// settings. Each field is named like its setting in lowercase, and is
+/// documented by the function of the `config` module that reads it. The
+/// settings without a default value are optional.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Settings {
+    pub be_rustc: bool,
+    pub viper_backend: String,
+    pub check_foldunfold_state: bool,
+    pub check_overflows: bool,
+    pub check_panics: bool,
+    pub truncate_casts: bool,
+    pub bit_precise: bool,
+    pub encode_unsigned_num_constraint: bool,
+    pub encode_floats_as_reals: bool,
+    pub check_raw_pointer_derefs: bool,
+    pub relaxed_purity: bool,
+    pub counterexample: bool,
+    pub infer_loop_invariants: bool,
+    pub simplify_encoding: bool,
+    pub log_dir: String,
+    pub dump_debug_info: bool,
+    pub dump_debug_info_during_fold: bool,
+    pub max_log_file_name_length: usize,
+    pub dump_path_ctxt_in_debug_info: bool,
+    pub dump_reborrowing_dag_in_debug_info: bool,
+    pub dump_borrowck_info: bool,
+    pub dump_viper_program: bool,
+    pub dump_viper_program_json: bool,
+    pub dump_vir_items: bool,
+    pub trace_provenance: String,
+    pub foldunfold_state_filter: String,
+    pub contracts_lib: String,
+    pub extra_jvm_args: Vec<String>,
+    pub extra_verifier_args: Vec<String>,
+    pub quiet: bool,
+    pub message_format: String,
+    pub assert_timeout: u64,
+    pub use_more_complete_exhale: bool,
+    pub skip_unsupported_features: bool,
+    pub allow_unreachable_unsupported_code: bool,
+    pub no_verify: bool,
+    pub encode_only: bool,
+    pub full_compilation: bool,
+    pub verify_only_tests: bool,
+    pub skip_expansions_of: String,
+    pub whole_program: bool,
+    pub require_decreases: bool,
+    pub check_send_sync_impls: bool,
+    pub extern_specs_dir: String,
+    pub havoc_mutable_global_state: bool,
+    pub check: String,
+    pub checked_obligations: String,
+    pub json_communication: bool,
+    pub server_cache_results: bool,
+    pub cache_path: String,
+    pub force_verify: bool,
+    pub cache_stats: bool,
+    pub jobs: usize,
+    pub deterministic: bool,
+    pub compress_server_requests: bool,
+    pub optimizations: String,
+    pub intern_names: bool,
+    pub enable_purification_optimization: bool,
+    pub print_desugared_specs: bool,
+    pub print_typeckd_specs: bool,
+    pub print_collected_verification_items: bool,
+    pub hide_uuids: bool,
+    pub disable_name_mangling: bool,
+    pub verify_only_preamble: bool,
+    pub enable_verify_only_basic_block_path: bool,
+    pub verify_only_basic_block_path: Vec<String>,
+    pub delete_basic_blocks: Vec<String>,
+    pub server_max_stored_verifiers: Option<usize>,
+    pub server_max_concurrency: Option<usize>,
+    pub server_address: Option<String>,
+    pub baseline: Option<String>,
+    pub fail_on: Option<String>,
+    pub summary_dir: Option<String>,
+    /// The settings of the `[module."<path>"]` tables, by module path.
+    #[serde(default)]
+    pub module: HashMap<String, ModuleSettings>,
+}
+
+/// The settings that can be overridden for the items of a module, e.g. with
+///
+/// ```toml
+/// [module."crate::parser"]
+/// check_overflows = false
+/// assert_timeout = 30000
+/// ```
+///
+/// The settings are applied while the items of the module are encoded and
+/// verified, see `config::enter_module`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModuleSettings {
+    /// Overflows are not checked if this is `false`, and the arithmetic of
+    /// the module wraps around instead. Overflow checks cannot be enabled for
+    /// a module if they are disabled globally.
+    pub check_overflows: Option<bool>,
+    pub check_panics: Option<bool>,
+    pub skip_unsupported_features: Option<bool>,
+    /// The timeout applies only if the methods of the crate are verified as
+    /// separate programs, which the verifier then does.
+    pub assert_timeout: Option<u64>,
+}
+
+impl ModuleSettings {
+    /// Override the settings of `self` with the ones set by `other`.
+    fn merge(&mut self, other: &ModuleSettings) {
+        self.check_overflows = other.check_overflows.or(self.check_overflows);
+        self.check_panics = other.check_panics.or(self.check_panics);
+        self.skip_unsupported_features =
+            other.skip_unsupported_features.or(self.skip_unsupported_features);
+        self.assert_timeout = other.assert_timeout.or(self.assert_timeout);
+    }
+
+    /// The settings of the tables of `modules` that apply to the item with
+    /// the path `item_path`, relative to the crate root. The tables of all the
+    /// enclosing modules apply, and the innermost one takes precedence.
+    pub(super) fn of_item(modules: &HashMap<String, ModuleSettings>, item_path: &str) -> Self {
+        let mut matching: Vec<_> = modules
+            .iter()
+            .filter_map(|(path, settings)| {
+                let path = path.strip_prefix("crate::").unwrap_or(path);
+                let contains_item = path == "crate"
+                    || item_path == path
+                    || item_path.starts_with(&format!("{}::", path));
+                if contains_item {
+                    Some((path.len(), settings))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        matching.sort_by_key(|(length, _)| *length);
+        let mut result = ModuleSettings::default();
+        for (_, settings) in matching {
+            result.merge(settings);
+        }
+        result
+    }
+}
+
+/// The names of the optional settings, which are known even though they have
+/// no default value.
+pub(super) const OPTIONAL_SETTINGS: &[&str] = &[
+    "server_max_stored_verifiers",
+    "server_max_concurrency",
+    "server_address",
+    "baseline",
+    "fail_on",
+    "summary_dir",
+    "module",
+];
@@ -22,6 +22,7 @@ extern crate serde;
 extern crate uuid;
 extern crate viper;
 
+pub mod cache_stats;
 pub mod config;
 pub mod report;
 mod stopwatch;
@@ -1,18 +1,48 @@
 use config;
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::SystemTime;
 use viper::{self, VerificationBackend};
 use vir::Program;
 
+pub use cache_stats::CacheStats;
+
 pub trait VerificationService {
     fn verify(&self, request: VerificationRequest) -> viper::VerificationResult;
+
+    /// Verifies several requests, returning the results in the same order.
+    fn verify_batch(&self, requests: Vec<VerificationRequest>) -> Vec<viper::VerificationResult> {
+        requests.into_iter().map(|request| self.verify(request)).collect()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VerificationRequest {
     pub program: Program,
     pub program_name: String,
+    /// The crate the program belongs to. Servers keep the programs of
+    /// different crates in separate namespaces.
+    #[serde(default)]
+    pub crate_name: String,
     pub backend_config: ViperBackendConfig,
 }
 
+impl VerificationRequest {
+    /// The name of the program, qualified by the crate it belongs to.
+    pub fn qualified_program_name(&self) -> String {
+        if self.crate_name.is_empty() {
+            self.program_name.clone()
+        } else {
+            format!("{}.{}", self.crate_name, self.program_name)
+        }
+    }
+}
+
 /**
 The configuration for the viper backend, (i.e. verifier).
 Expresses which backend (silicon or carbon) should be used, and provides command-line arguments to the viper verifier.
@@ -38,11 +68,31 @@ impl Default for ViperBackendConfig {
                     "--logLevel".to_string(),
                     "ERROR".to_string(),
                 ]);
+                if config::counterexample() {
+                    verifier_args.extend(vec![
+                        "--counterexample".to_string(),
+                        "variables".to_string(),
+                    ]);
+                }
+                if config::deterministic() {
+                    verifier_args.extend(vec![
+                        "--numberOfParallelVerifiers".to_string(),
+                        "1".to_string(),
+                        "--z3Args".to_string(),
+                        "smt.random_seed=0 sat.random_seed=0".to_string(),
+                    ]);
+                }
             }
             VerificationBackend::Carbon => {
                 verifier_args.extend(vec![
                     "--disableAllocEncoding".to_string(),
                 ]);
+                if config::deterministic() {
+                    verifier_args.extend(vec![
+                        "--boogieOpt".to_string(),
+                        "/proverOpt:O:smt.random_seed=0 /proverOpt:O:sat.random_seed=0".to_string(),
+                    ]);
+                }
             }
         }
         Self {
@@ -51,3 +101,139 @@ impl Default for ViperBackendConfig {
         }
     }
 }
+
+/// The identifier of a verification job, which a server assigns when the job
+/// is submitted.
+pub type JobId = u64;
+
+/// The progress of a verification job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum JobState {
+    /// The job waits for earlier jobs to finish.
+    Queued,
+    /// Some of the programs of the job are being verified.
+    Running,
+    /// All the programs of the job have been verified.
+    Finished,
+}
+
+/// The status of a verification job, as reported by a server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobStatus {
+    pub id: JobId,
+    pub state: JobState,
+    /// The number of programs of the job.
+    pub programs: usize,
+    /// The number of programs whose verification has finished.
+    pub verified_programs: usize,
+}
+
+/// The outcome of the verification of a program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProgramOutcome {
+    Success,
+    Failure,
+    ConsistencyErrors,
+    JavaException,
+    /// The verifier panicked.
+    Panicked,
+}
+
+/// The result of the verification of a program of a job, in a structure that
+/// does not require the client to know about Viper.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgramResult {
+    pub program_name: String,
+    pub crate_name: String,
+    pub outcome: ProgramOutcome,
+    pub errors: Vec<ProgramError>,
+}
+
+/// An error reported by the verification of a program.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgramError {
+    /// The Viper method or function in which the error occurred, if known.
+    pub method: Option<String>,
+    /// The identifier of the kind of error, e.g. `assert.failed:assertion.false`.
+    pub full_id: Option<String>,
+    pub message: String,
+    /// The source line of the error, starting at 1, if known.
+    pub line: Option<i32>,
+    /// The source column of the error, starting at 1, if known.
+    pub column: Option<i32>,
+}
+
+impl ViperBackendConfig {
+    /// The hash that identifies the results of this backend in caches: the
+    /// name of the backend, its arguments (which include the flags of the
+    /// solver), and the installed versions of Viper and of the solvers. A new
+    /// version of the backend thus invalidates the cached results.
+    pub fn cache_key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        backend_fingerprint_hash(self.backend).hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+lazy_static! {
+    /// The hashes of the fingerprints of the backends, which are computed
+    /// once per process: the loaded Viper does not change while it runs.
+    static ref BACKEND_FINGERPRINT_HASHES: Mutex<HashMap<VerificationBackend, u64>> =
+        Mutex::new(HashMap::new());
+}
+
+fn backend_fingerprint_hash(backend: VerificationBackend) -> u64 {
+    *BACKEND_FINGERPRINT_HASHES
+        .lock()
+        .unwrap()
+        .entry(backend)
+        .or_insert_with(|| {
+            let mut hasher = DefaultHasher::new();
+            backend_fingerprint(backend).hash(&mut hasher);
+            hasher.finish()
+        })
+}
+
+/// Identify a version of a file by its path, size and modification time.
+#[derive(Debug, Hash)]
+pub struct FileFingerprint(String, u64, Option<SystemTime>);
+
+impl FileFingerprint {
+    pub fn of(path: &Path) -> Self {
+        let metadata = fs::metadata(path).ok();
+        FileFingerprint(
+            path.to_string_lossy().to_string(),
+            metadata.as_ref().map_or(0, |metadata| metadata.len()),
+            metadata.and_then(|metadata| metadata.modified().ok()),
+        )
+    }
+}
+
+/// Identify the installed Viper, by the files in `VIPER_HOME`, and the
+/// solvers used by `backend`, by their executables.
+fn backend_fingerprint(backend: VerificationBackend) -> Vec<FileFingerprint> {
+    let mut fingerprint = vec![];
+    if let Ok(viper_home) = env::var("VIPER_HOME") {
+        if let Ok(entries) = fs::read_dir(&viper_home) {
+            let mut paths: Vec<_> = entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .collect();
+            paths.sort();
+            fingerprint.extend(paths.iter().map(|path| FileFingerprint::of(path)));
+        }
+    }
+    let mut solvers = vec!["Z3_EXE"];
+    if backend == VerificationBackend::Carbon {
+        solvers.push("BOOGIE_EXE");
+    }
+    for solver in solvers {
+        if let Ok(path) = env::var(solver) {
+            fingerprint.push(FileFingerprint::of(Path::new(&path)));
+        }
+    }
+    fingerprint
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use vir::{ast::*, cfg::CfgMethod};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,4 +9,211 @@ pub struct Program {
     pub methods: Vec<CfgMethod>,
     pub functions: Vec<Function>,
     pub viper_predicates: Vec<Predicate>,
+    /// Handwritten Viper declarations, as source code, imported with
+    /// `#[viper_import]`. They are merged into the program before verification.
+    pub viper_imports: Vec<String>,
+}
+
+impl Program {
+    /// Split the program into one program per method, each of which keeps all
+    /// the other declarations. Since a method never calls another method, the
+    /// programs can be verified independently. The programs are sorted by the
+    /// name of their method.
+    pub fn split_methods(self) -> Vec<Program> {
+        let Program {
+            domains,
+            fields,
+            builtin_methods,
+            mut methods,
+            functions,
+            viper_predicates,
+            viper_imports,
+        } = self;
+        methods.sort_by_key(|method| method.name());
+        methods
+            .into_iter()
+            .map(|method| Program {
+                domains: domains.clone(),
+                fields: fields.clone(),
+                builtin_methods: builtin_methods.clone(),
+                methods: vec![method],
+                functions: functions.clone(),
+                viper_predicates: viper_predicates.clone(),
+                viper_imports: viper_imports.clone(),
+            })
+            .collect()
+    }
+
+    /// Sort the declarations of the program by name, so that the text of the
+    /// program does not depend on the order in which they were encoded.
+    pub fn sort_declarations(&mut self) {
+        self.domains.sort_by(|a, b| a.name.cmp(&b.name));
+        self.fields.sort_by(|a, b| a.name.cmp(&b.name));
+        self.builtin_methods.sort_by(|a, b| a.name.cmp(&b.name));
+        self.methods.sort_by_key(|method| method.name());
+        self.functions.sort_by(|a, b| a.name.cmp(&b.name));
+        self.viper_predicates.sort_by(|a, b| a.name().cmp(b.name()));
+        self.viper_imports.sort();
+    }
+
+    /// The text of each method, function, predicate and domain of the program,
+    /// without comments, keyed by the kind and the name of the item.
+    pub fn item_encodings(&self) -> BTreeMap<String, String> {
+        fn without_comments(text: String) -> String {
+            text.lines()
+                .filter(|line| !line.trim_start().starts_with("//"))
+                .map(|line| format!("{}\n", line.trim_end()))
+                .collect()
+        }
+        let mut items = BTreeMap::new();
+        for domain in &self.domains {
+            items.insert(format!("domain {}", domain.name), without_comments(domain.to_string()));
+        }
+        for method in &self.builtin_methods {
+            items.insert(format!("method {}", method.name), without_comments(method.to_string()));
+        }
+        for method in &self.methods {
+            items.insert(format!("method {}", method.name()), without_comments(method.to_string()));
+        }
+        for function in &self.functions {
+            items.insert(format!("function {}", function.name), without_comments(function.to_string()));
+        }
+        for predicate in &self.viper_predicates {
+            items.insert(
+                format!("predicate {}", predicate.name()),
+                without_comments(predicate.to_string()),
+            );
+        }
+        items
+    }
+
+    /// Describe the statement or expression of the program whose position has
+    /// the given id, together with the method or function that contains it.
+    pub fn find_position(&self, pos_id: u64) -> Option<String> {
+        self.find(pos_id)
+            .map(|(found, _, kind, name)| format!("{} (in {} {})", found, kind, name))
+    }
+
+    /// The source position with the given id, together with the name of the
+    /// method or function that contains it.
+    pub fn locate_position(&self, pos_id: u64) -> Option<(String, Position)> {
+        self.find(pos_id).map(|(_, position, _, name)| (name, position))
+    }
+
+    /// The statement or expression with the position `pos_id`, its position,
+    /// and the kind and name of the method or function that contains it.
+    fn find(&self, pos_id: u64) -> Option<(String, Position, &'static str, String)> {
+        let mut finder = PositionFinder { pos_id, found: None };
+        for method in &self.methods {
+            for block in &method.basic_blocks {
+                for stmt in &block.stmts {
+                    match stmt_position(stmt) {
+                        Some(pos) if pos.id() == pos_id => {
+                            finder.found = Some((stmt.to_string(), *pos));
+                        }
+                        _ => StmtWalker::walk(&mut finder, stmt),
+                    }
+                    if let Some((found, position)) = finder.found.take() {
+                        return Some((found, position, "method", method.name()));
+                    }
+                }
+            }
+        }
+        for function in &self.functions {
+            for expr in function.pres.iter().chain(&function.posts).chain(&function.body) {
+                ExprWalker::walk(&mut finder, expr);
+                if let Some((found, position)) = finder.found.take() {
+                    return Some((found, position, "function", function.name.clone()));
+                }
+            }
+        }
+        None
+    }
+}
+
+fn stmt_position(stmt: &Stmt) -> Option<&Position> {
+    match stmt {
+        Stmt::Exhale(_, pos)
+        | Stmt::Assert(_, _, pos)
+        | Stmt::Fold(_, _, _, _, pos)
+        | Stmt::Obtain(_, pos)
+        | Stmt::PackageMagicWand(_, _, _, _, pos)
+        | Stmt::ApplyMagicWand(_, pos) => Some(pos),
+        _ => None,
+    }
+}
+
+/// Finds the first expression with the position `pos_id`.
+struct PositionFinder {
+    pos_id: u64,
+    found: Option<(String, Position)>,
+}
+
+impl ExprWalker for PositionFinder {
+    fn walk(&mut self, expr: &Expr) {
+        if self.found.is_none() && expr.pos().id() == self.pos_id {
+            self.found = Some((expr.to_string(), expr.pos()));
+        }
+        default_walk_expr(self, expr);
+    }
+}
+
+impl StmtWalker for PositionFinder {
+    fn walk_expr(&mut self, expr: &Expr) {
+        ExprWalker::walk(self, expr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A program whose declarations are added in the order of `names`.
+    fn program_with(names: &[&str]) -> Program {
+        Program {
+            domains: vec![],
+            fields: names.iter().map(|name| Field::new(format!("f_{}", name), Type::Int)).collect(),
+            builtin_methods: names.iter()
+                .map(|name| BodylessMethod {
+                    name: format!("builtin_{}", name),
+                    formal_args: vec![],
+                    formal_returns: vec![],
+                })
+                .collect(),
+            methods: names.iter()
+                .map(|name| CfgMethod::new(format!("m_{}", name), 0, vec![], vec![], vec![]))
+                .collect(),
+            functions: vec![],
+            viper_predicates: vec![],
+            viper_imports: names.iter().map(|name| format!("// import {}", name)).collect(),
+        }
+    }
+
+    /// The declarations of the program, in order.
+    fn declarations(program: &Program) -> Vec<String> {
+        program.fields.iter().map(|field| field.name.clone())
+            .chain(program.builtin_methods.iter().map(|method| method.name.clone()))
+            .chain(program.methods.iter().map(|method| method.name()))
+            .chain(program.viper_imports.iter().cloned())
+            .collect()
+    }
+
+    #[test]
+    fn sorted_declarations_do_not_depend_on_the_encoding_order() {
+        let mut first = program_with(&["b", "c", "a"]);
+        let mut second = program_with(&["c", "a", "b"]);
+        assert_ne!(declarations(&first), declarations(&second));
+        first.sort_declarations();
+        second.sort_declarations();
+        assert_eq!(declarations(&first), declarations(&second));
+        assert_eq!(
+            declarations(&first),
+            vec![
+                "f_a", "f_b", "f_c",
+                "builtin_a", "builtin_b", "builtin_c",
+                "m_a", "m_b", "m_c",
+                "// import a", "// import b", "// import c",
+            ],
+        );
+    }
 }
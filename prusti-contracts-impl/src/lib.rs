@@ -34,17 +34,88 @@ pub fn trusted(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
     tokens
 }
 
+#[proc_macro_attribute]
+pub fn decreases(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
+#[proc_macro_attribute]
+pub fn bitvector(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
+#[proc_macro_attribute]
+pub fn bit_precise(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
+#[proc_macro_attribute]
+pub fn in_state(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
+#[proc_macro_attribute]
+pub fn complexity(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
+#[proc_macro_attribute]
+pub fn viper_import(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
+#[proc_macro_attribute]
+pub fn lemma(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
+#[proc_macro_attribute]
+pub fn trusted_proof(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
+#[proc_macro_attribute]
+pub fn thread_safety_invariant(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
 #[proc_macro]
 pub fn body_invariant(_tokens: TokenStream) -> TokenStream {
     let callsite_span = Span::call_site();
     (quote_spanned!(callsite_span=> ())).into()
 }
 
+#[proc_macro]
+pub fn body_variant(_tokens: TokenStream) -> TokenStream {
+    let callsite_span = Span::call_site();
+    (quote_spanned!(callsite_span=> ())).into()
+}
+
+#[proc_macro]
+pub fn ghost(tokens: TokenStream) -> TokenStream {
+    prusti_specs::ghost(tokens.into(), true).into()
+}
+
 #[proc_macro]
 pub fn closure(tokens: TokenStream) -> TokenStream {
     prusti_specs::closure(tokens.into(), true).into()
 }
 
+#[proc_macro]
+pub fn predicate(tokens: TokenStream) -> TokenStream {
+    prusti_specs::predicate(tokens.into(), true).into()
+}
+
+#[proc_macro]
+pub fn state_machine(tokens: TokenStream) -> TokenStream {
+    prusti_specs::state_machine(tokens.into()).into()
+}
+
+#[proc_macro_attribute]
+pub fn invariant(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
 #[proc_macro_attribute]
 pub fn refine_trait_spec(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
     tokens
@@ -33,16 +33,90 @@ pub fn trusted(attr: TokenStream, tokens: TokenStream) -> TokenStream {
     rewrite_prusti_attributes(SpecAttributeKind::Trusted, attr.into(), tokens.into()).into()
 }
 
+#[proc_macro_attribute]
+pub fn decreases(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    rewrite_prusti_attributes(SpecAttributeKind::Decreases, attr.into(), tokens.into()).into()
+}
+
+#[proc_macro_attribute]
+pub fn bitvector(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    rewrite_prusti_attributes(SpecAttributeKind::Bitvector, attr.into(), tokens.into()).into()
+}
+
+#[proc_macro_attribute]
+pub fn bit_precise(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    rewrite_prusti_attributes(SpecAttributeKind::BitPrecise, attr.into(), tokens.into()).into()
+}
+
+#[proc_macro_attribute]
+pub fn in_state(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    rewrite_prusti_attributes(SpecAttributeKind::InState, attr.into(), tokens.into()).into()
+}
+
+#[proc_macro_attribute]
+pub fn complexity(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    rewrite_prusti_attributes(SpecAttributeKind::Complexity, attr.into(), tokens.into()).into()
+}
+
+#[proc_macro_attribute]
+pub fn viper_import(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    rewrite_prusti_attributes(SpecAttributeKind::ViperImport, attr.into(), tokens.into()).into()
+}
+
+#[proc_macro_attribute]
+pub fn lemma(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    rewrite_prusti_attributes(SpecAttributeKind::Lemma, attr.into(), tokens.into()).into()
+}
+
+#[proc_macro_attribute]
+pub fn trusted_proof(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    rewrite_prusti_attributes(SpecAttributeKind::TrustedProof, attr.into(), tokens.into()).into()
+}
+
+#[proc_macro_attribute]
+pub fn thread_safety_invariant(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    rewrite_prusti_attributes(
+        SpecAttributeKind::ThreadSafetyInvariant,
+        attr.into(),
+        tokens.into(),
+    ).into()
+}
+
 #[proc_macro]
 pub fn body_invariant(tokens: TokenStream) -> TokenStream {
     prusti_specs::body_invariant(tokens.into()).into()
 }
 
+#[proc_macro]
+pub fn body_variant(tokens: TokenStream) -> TokenStream {
+    prusti_specs::body_variant(tokens.into()).into()
+}
+
+#[proc_macro]
+pub fn ghost(tokens: TokenStream) -> TokenStream {
+    prusti_specs::ghost(tokens.into(), false).into()
+}
+
 #[proc_macro]
 pub fn closure(tokens: TokenStream) -> TokenStream {
     prusti_specs::closure(tokens.into(), false).into()
 }
 
+#[proc_macro]
+pub fn predicate(tokens: TokenStream) -> TokenStream {
+    prusti_specs::predicate(tokens.into(), false).into()
+}
+
+#[proc_macro]
+pub fn state_machine(tokens: TokenStream) -> TokenStream {
+    prusti_specs::state_machine(tokens.into()).into()
+}
+
+#[proc_macro_attribute]
+pub fn invariant(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    prusti_specs::invariant(attr.into(), tokens.into()).into()
+}
+
 #[proc_macro_attribute]
 pub fn refine_trait_spec(attr: TokenStream, tokens: TokenStream) -> TokenStream {
     prusti_specs::refine_trait_spec(attr.into(), tokens.into()).into()
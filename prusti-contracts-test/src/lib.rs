@@ -19,3 +19,11 @@ pub fn test4() {
         body_invariant!(true)
     }
 }
+
+pub fn test5() -> u32 {
+    ghost! {
+        let ghost_value = 1;
+        assert!(ghost_value == 1);
+    }
+    0
+}
@@ -20,12 +20,62 @@ mod private {
     /// A macro for marking a function as trusted.
     pub use prusti_contracts_impl::trusted;
 
+    /// A macro for writing a termination measure of a function.
+    pub use prusti_contracts_impl::decreases;
+
+    /// A macro for writing a bound on the number of loop iterations and
+    /// calls performed by a function.
+    pub use prusti_contracts_impl::complexity;
+
+    /// A macro for encoding the bitwise operations of a function precisely.
+    pub use prusti_contracts_impl::bitvector;
+
+    /// A macro for encoding the integers of a function as machine integers.
+    pub use prusti_contracts_impl::bit_precise;
+
+    /// A macro for implementing a pure function by a function of a
+    /// handwritten Viper file.
+    pub use prusti_contracts_impl::viper_import;
+
+    /// A macro for declaring a lemma, a pure function whose calls
+    /// instantiate its postcondition.
+    pub use prusti_contracts_impl::lemma;
+
+    /// A macro for declaring a lemma whose proof is not verified.
+    pub use prusti_contracts_impl::trusted_proof;
+
+    /// A macro for restricting a method to a state of a ghost state machine.
+    pub use prusti_contracts_impl::in_state;
+
+    /// A macro for writing the invariant that justifies an `unsafe impl` of
+    /// `Send` or `Sync`.
+    pub use prusti_contracts_impl::thread_safety_invariant;
+
     /// A macro for writing a loop body invariant.
     pub use prusti_contracts_impl::body_invariant;
 
+    /// A macro for writing a termination measure of a loop.
+    pub use prusti_contracts_impl::body_variant;
+
+    /// A macro for writing ghost code, which is verified but erased from
+    /// the compiled program.
+    pub use prusti_contracts_impl::ghost;
+
     /// A macro for defining a closure with a specification.
     pub use prusti_contracts_impl::closure;
 
+    /// A macro for defining a predicate, which can be used in specifications.
+    pub use prusti_contracts_impl::predicate;
+
+    /// A macro for declaring a ghost state machine, whose transitions are
+    /// checked by the verifier.
+    pub use prusti_contracts_impl::state_machine;
+
+    /// A macro for writing the invariant of a type. It is required of the
+    /// arguments of every call and ensured of every returned value, and has
+    /// to hold when a value of the type is put into a cell.
+    pub use prusti_contracts_impl::invariant;
+
     /// A macro for impl blocks that refine trait specifications.
     pub use prusti_contracts_impl::refine_trait_spec;
 
@@ -53,12 +103,62 @@ mod private {
     /// A macro for marking a function as trusted.
     pub use prusti_contracts_internal::trusted;
 
+    /// A macro for writing a termination measure of a function.
+    pub use prusti_contracts_internal::decreases;
+
+    /// A macro for writing a bound on the number of loop iterations and
+    /// calls performed by a function.
+    pub use prusti_contracts_internal::complexity;
+
+    /// A macro for encoding the bitwise operations of a function precisely.
+    pub use prusti_contracts_internal::bitvector;
+
+    /// A macro for encoding the integers of a function as machine integers.
+    pub use prusti_contracts_internal::bit_precise;
+
+    /// A macro for implementing a pure function by a function of a
+    /// handwritten Viper file.
+    pub use prusti_contracts_internal::viper_import;
+
+    /// A macro for declaring a lemma, a pure function whose calls
+    /// instantiate its postcondition.
+    pub use prusti_contracts_internal::lemma;
+
+    /// A macro for declaring a lemma whose proof is not verified.
+    pub use prusti_contracts_internal::trusted_proof;
+
+    /// A macro for restricting a method to a state of a ghost state machine.
+    pub use prusti_contracts_internal::in_state;
+
+    /// A macro for writing the invariant that justifies an `unsafe impl` of
+    /// `Send` or `Sync`.
+    pub use prusti_contracts_internal::thread_safety_invariant;
+
     /// A macro for writing a loop body invariant.
     pub use prusti_contracts_internal::body_invariant;
 
+    /// A macro for writing a termination measure of a loop.
+    pub use prusti_contracts_internal::body_variant;
+
+    /// A macro for writing ghost code, which is verified but erased from
+    /// the compiled program.
+    pub use prusti_contracts_internal::ghost;
+
     /// A macro for defining a closure with a specification.
     pub use prusti_contracts_internal::closure;
 
+    /// A macro for defining a predicate, which can be used in specifications.
+    pub use prusti_contracts_internal::predicate;
+
+    /// A macro for declaring a ghost state machine, whose transitions are
+    /// checked by the verifier.
+    pub use prusti_contracts_internal::state_machine;
+
+    /// A macro for writing the invariant of a type. It is required of the
+    /// arguments of every call and ensured of every returned value, and has
+    /// to hold when a value of the type is put into a cell.
+    pub use prusti_contracts_internal::invariant;
+
     /// A macro for impl blocks that refine trait specifications.
     pub use prusti_contracts_internal::refine_trait_spec;
 
@@ -79,4 +179,88 @@ pub fn old<T>(arg: T) -> T {
     arg
 }
 
+/// The number of credits left to the current function, which has a
+/// `#[complexity]` annotation. Each loop iteration consumes one credit, and
+/// each call of a function with a `#[complexity]` annotation consumes one
+/// credit plus the bound of the callee.
+pub fn credits() -> usize {
+    unimplemented!("credits can only be used in specifications")
+}
+
+/// The termination measure of the current function or loop, which is defined
+/// by a `#[decreases]` annotation or a `body_variant!`.
+pub fn termination_measure() -> i128 {
+    unimplemented!("termination_measure can only be used in specifications")
+}
+
+/// Whether `left` and `right` have the same snapshot, which is written
+/// `left === right` in specifications. Snapshots are structural: references
+/// and boxes are compared by the values that they point to, never by their
+/// addresses. The types whose snapshot contains a raw pointer are rejected.
+pub fn snapshot_equality<T>(_left: T, _right: T) -> bool {
+    unimplemented!("snapshot_equality can only be used in specifications")
+}
+
+/// The raw pointer types, whose validity can be stated with `ptr_valid`.
+pub trait RawPtr: Copy {}
+
+impl<T: ?Sized> RawPtr for *const T {}
+impl<T: ?Sized> RawPtr for *mut T {}
+
+/// The permission to dereference `ptr`, which is non-null and points to an
+/// allocated, initialized value. With `CHECK_RAW_POINTER_DEREFS`, each
+/// dereference of a raw pointer, as well as `ptr::read` and `ptr::write`,
+/// requires this permission, which is transferred by the pre- and
+/// postconditions like the permission to a place. The contracts of foreign
+/// functions, given in an `#[extern_spec]` module, state with it which
+/// pointers the foreign code reads, writes or keeps.
+pub fn ptr_valid<P: RawPtr>(_ptr: P) -> bool {
+    unimplemented!("ptr_valid can only be used in specifications")
+}
+
+/// The integer types, on which the modular arithmetic functions are defined.
+pub trait Int: Copy {
+    #[doc(hidden)]
+    fn to_bits(self) -> u128;
+    #[doc(hidden)]
+    fn from_bits(bits: u128) -> Self;
+}
+
+macro_rules! impl_int {
+    ($($ty:ty)*) => {
+        $(
+            impl Int for $ty {
+                fn to_bits(self) -> u128 {
+                    self as u128
+                }
+                fn from_bits(bits: u128) -> Self {
+                    bits as Self
+                }
+            }
+        )*
+    }
+}
+
+impl_int!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+
+/// The result of `a + b`, wrapped around the range of `T`.
+pub fn wrap_add<T: Int>(a: T, b: T) -> T {
+    T::from_bits(a.to_bits().wrapping_add(b.to_bits()))
+}
+
+/// The result of `a - b`, wrapped around the range of `T`.
+pub fn wrap_sub<T: Int>(a: T, b: T) -> T {
+    T::from_bits(a.to_bits().wrapping_sub(b.to_bits()))
+}
+
+/// The result of `a * b`, wrapped around the range of `T`.
+pub fn wrap_mul<T: Int>(a: T, b: T) -> T {
+    T::from_bits(a.to_bits().wrapping_mul(b.to_bits()))
+}
+
+/// The value of `x`, truncated to the bits of `T` as done by `x as T`.
+pub fn trunc<T: Int, S: Int>(x: S) -> T {
+    T::from_bits(x.to_bits())
+}
+
 pub use private::*;
@@ -1,16 +1,42 @@
 use prusti_interface::{specs, environment::Environment};
 use rustc_driver::Compilation;
 use rustc_hir::intravisit;
-use rustc_interface::interface::Compiler;
+use rustc_interface::interface::{Compiler, Config};
 use rustc_interface::Queries;
+use rustc_session::config::Input;
 use regex::Regex;
 use prusti_common::config;
 use crate::verifier::verify;
+use crate::extern_specs::ExternSpecsFileLoader;
+use crate::{Callbacks, VerificationResults};
 
-#[derive(Default)]
-pub struct PrustiCompilerCalls;
+pub struct PrustiCompilerCalls<'a> {
+    callbacks: &'a mut (dyn Callbacks + Send),
+    results: Option<VerificationResults>,
+}
 
-impl rustc_driver::Callbacks for PrustiCompilerCalls {
+impl<'a> PrustiCompilerCalls<'a> {
+    pub fn new(callbacks: &'a mut (dyn Callbacks + Send)) -> Self {
+        PrustiCompilerCalls {
+            callbacks,
+            results: None,
+        }
+    }
+
+    /// The results of the verification, if the crate was verified.
+    pub fn take_results(&mut self) -> Option<VerificationResults> {
+        self.results.take()
+    }
+}
+
+impl<'a> rustc_driver::Callbacks for PrustiCompilerCalls<'a> {
+    fn config(&mut self, config: &mut Config) {
+        if let Input::File(ref crate_root) = config.input {
+            if let Some(file_loader) = ExternSpecsFileLoader::new(crate_root) {
+                config.file_loader = Some(Box::new(file_loader));
+            }
+        }
+    }
     fn after_expansion<'tcx>(
         &mut self,
         compiler: &Compiler,
@@ -71,7 +97,7 @@ impl rustc_driver::Callbacks for PrustiCompilerCalls {
                 }
             }
             if !config::no_verify() {
-                verify(env, def_spec);
+                self.results = Some(verify(env, def_spec, &mut *self.callbacks));
             }
         });
 
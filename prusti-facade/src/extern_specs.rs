@@ -0,0 +1,94 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Loading of external specification files. The `.rs` files of the
+//! `EXTERN_SPECS_DIR` directory contain `#[extern_spec]` blocks for the
+//! functions, generic functions and trait impls of other crates, so that
+//! specifications of common dependencies can be shared between crates. The
+//! files are compiled as modules of the verified crate, which are declared at
+//! the end of its root file while the compiler reads it.
+
+use log::debug;
+use prusti_common::config;
+use rustc_span::source_map::{FileLoader, RealFileLoader};
+use std::{env, fs, io, path::{Path, PathBuf}};
+
+/// Reads the source files like the compiler, but declares the external
+/// specification files as modules of the crate root.
+pub struct ExternSpecsFileLoader {
+    crate_root: PathBuf,
+    spec_files: Vec<PathBuf>,
+}
+
+impl ExternSpecsFileLoader {
+    /// Create a loader for the crate whose root file is `crate_root`, if the
+    /// crate has external specification files.
+    pub fn new(crate_root: &Path) -> Option<Self> {
+        let spec_files = find_spec_files()?;
+        if spec_files.is_empty() {
+            return None;
+        }
+        Some(ExternSpecsFileLoader {
+            crate_root: crate_root.to_path_buf(),
+            spec_files,
+        })
+    }
+
+    fn module_declarations(&self) -> String {
+        let mut declarations = String::new();
+        for spec_file in &self.spec_files {
+            let stem = spec_file.file_stem().unwrap().to_string_lossy();
+            let module_name: String = stem.chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+            declarations.push_str(&format!(
+                "\n#[allow(dead_code, unused_imports)]\n#[path = {:?}]\nmod prusti_extern_specs_{};\n",
+                spec_file,
+                module_name,
+            ));
+        }
+        declarations
+    }
+}
+
+impl FileLoader for ExternSpecsFileLoader {
+    fn file_exists(&self, path: &Path) -> bool {
+        RealFileLoader.file_exists(path)
+    }
+
+    fn read_file(&self, path: &Path) -> io::Result<String> {
+        let mut source = RealFileLoader.read_file(path)?;
+        if path == self.crate_root {
+            source.push_str(&self.module_declarations());
+        }
+        Ok(source)
+    }
+}
+
+/// The `.rs` files of the external specification directory, sorted by name.
+/// Returns `None` if the directory is disabled or does not exist.
+fn find_spec_files() -> Option<Vec<PathBuf>> {
+    let specs_dir = config::extern_specs_dir();
+    if specs_dir.is_empty() {
+        return None;
+    }
+    let mut specs_dir = PathBuf::from(specs_dir);
+    if specs_dir.is_relative() {
+        if let Some(manifest_dir) = env::var_os("CARGO_MANIFEST_DIR") {
+            specs_dir = PathBuf::from(manifest_dir).join(specs_dir);
+        }
+    }
+    let entries = fs::read_dir(&specs_dir).ok()?;
+    let mut spec_files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().map_or(false, |ext| ext == "rs"))
+        .filter_map(|path| path.canonicalize().ok())
+        .collect();
+    spec_files.sort();
+    debug!("External specification files: {:?}", spec_files);
+    Some(spec_files)
+}
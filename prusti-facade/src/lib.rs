@@ -0,0 +1,192 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A stable API for embedding Prusti as a library, used by `prusti-driver`
+//! and by custom drivers and research tools.
+//!
+//! ```ignore
+//! let config = PrustiConfig::new(rustc_args).set("check_overflows", "true");
+//! let results = prusti_facade::verify_crate(config, &mut DefaultCallbacks);
+//! std::process::exit(results.exit_code);
+//! ```
+//!
+//! Passes registered with `register_vir_pass` transform the encoded Viper
+//! program of the crate before it is verified.
+//!
+//! The settings of Prusti are global to the process, so `verify_crate`
+//! verifies one crate at a time: a call waits until the runs of the other
+//! threads have finished. The settings of a `PrustiConfig` only apply to the
+//! run of `verify_crate` that it configures.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_session;
+extern crate rustc_span;
+
+mod callbacks;
+mod extern_specs;
+mod summary;
+mod verifier;
+
+use callbacks::PrustiCompilerCalls;
+use prusti_common::config;
+use std::{env, path::PathBuf, time::Duration};
+
+pub use prusti_interface::data::{ProcedureDefId, ProcedureStatus};
+pub use prusti_interface::environment::Environment;
+pub use prusti_viper::vir_passes::{register_vir_pass, VirPass};
+
+/// The configuration of a verification run.
+#[derive(Debug, Clone)]
+pub struct PrustiConfig {
+    rustc_args: Vec<String>,
+    settings: Vec<(String, String)>,
+}
+
+impl PrustiConfig {
+    /// Verify the crate compiled by the given `rustc` arguments. As in
+    /// `rustc`, the first argument is the name of the executable and is ignored.
+    pub fn new(rustc_args: Vec<String>) -> Self {
+        PrustiConfig {
+            rustc_args,
+            settings: vec![],
+        }
+    }
+
+    /// Set a Prusti flag, such as `check_overflows`. The value takes
+    /// precedence over `Prusti.toml`, `PRUSTI_*` environment variables and
+    /// `-P` arguments, during this run only.
+    pub fn set(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.settings.push((name.into(), value.into()));
+        self
+    }
+}
+
+/// Hooks that allow a driver to customize a verification run.
+pub trait Callbacks {
+    /// Select the procedures to verify among the procedures of the crate that
+    /// Prusti would verify by default.
+    fn select_procedures<'tcx>(
+        &mut self,
+        _env: &Environment<'tcx>,
+        procedures: Vec<ProcedureDefId>,
+    ) -> Vec<ProcedureDefId> {
+        procedures
+    }
+
+    /// Called once the crate has been verified, while the compiler state is
+    /// still available through `env`.
+    fn after_verification<'tcx>(
+        &mut self,
+        _env: &Environment<'tcx>,
+        _results: &VerificationResults,
+    ) {}
+}
+
+/// Callbacks that keep the default behaviour of Prusti.
+pub struct DefaultCallbacks;
+
+impl Callbacks for DefaultCallbacks {}
+
+/// The outcome of verifying a single procedure.
+#[derive(Debug, Clone)]
+pub struct ProcedureResult {
+    /// The absolute path of the procedure.
+    pub name: String,
+    pub status: ProcedureStatus,
+    /// The time spent encoding the procedure to Viper.
+    pub encoding_time: Duration,
+    /// The time spent verifying the Viper program that contains the method
+    /// of the procedure.
+    pub verification_time: Duration,
+}
+
+/// The outcome of verifying a crate.
+#[derive(Debug, Clone)]
+pub struct VerificationResults {
+    /// Whether the crate compiled and all its procedures were verified.
+    pub success: bool,
+    pub procedures: Vec<ProcedureResult>,
+    /// The exit code that `prusti-rustc` reports for this run.
+    pub exit_code: i32,
+}
+
+/// Compile and verify a crate. Verification errors are emitted as compiler
+/// diagnostics, and are summarized in the returned results. Invalid settings
+/// (see `config::validate`) are reported before the crate is compiled.
+///
+/// The call blocks while another thread verifies a crate, because the
+/// settings of the run are applied to the whole process. It must not be
+/// called from the callbacks of another run.
+pub fn verify_crate(
+    config: PrustiConfig,
+    callbacks: &mut (dyn Callbacks + Send),
+) -> VerificationResults {
+    let _overrides = config::override_settings(&config.settings);
+    if let Err(errors) = config::validate() {
+        for error in errors {
+            eprintln!("error: {}", error);
+        }
+        return VerificationResults {
+            success: false,
+            procedures: vec![],
+            exit_code: 1,
+        };
+    }
+    let rustc_args = prusti_rustc_args(config.rustc_args);
+    let mut compiler_calls = PrustiCompilerCalls::new(callbacks);
+    let exit_code = rustc_driver::catch_with_exit_code(|| {
+        rustc_driver::RunCompiler::new(&rustc_args, &mut compiler_calls).run()
+    });
+    // Without results, the crate compiled but was not verified (`NO_VERIFY`).
+    let mut results = compiler_calls.take_results().unwrap_or(VerificationResults {
+        success: true,
+        procedures: vec![],
+        exit_code,
+    });
+    results.success &= exit_code == 0;
+    results.exit_code = exit_code;
+    results
+}
+
+/// Extend the `rustc` arguments with the flags that Prusti needs.
+fn prusti_rustc_args(mut rustc_args: Vec<String>) -> Vec<String> {
+    env::set_var("POLONIUS_ALGORITHM", "Naive");
+    rustc_args.push("-Zborrowck=mir".to_owned());
+    rustc_args.push("-Zpolonius".to_owned());
+    rustc_args.push("-Znll-facts".to_owned());
+    rustc_args.push(format!(
+        "-Znll-facts-dir={}",
+        PathBuf::from(config::log_dir()).join("nll-facts").to_str()
+            .expect("failed to configure nll-facts-dir")
+    ));
+    rustc_args.push("-Zidentify-regions".to_owned());
+    rustc_args.push(format!(
+        "-Zdump-mir-dir={}",
+        PathBuf::from(config::log_dir()).join("mir").to_str()
+            .expect("failed to configure dump-mir-dir")
+    ));
+    rustc_args.push("-Zdump-mir=renumber".to_owned());
+    rustc_args.push("-Zalways-encode-mir".to_owned());
+    rustc_args.push("-Zcrate-attr=feature(register_tool)".to_owned());
+    rustc_args.push("-Zcrate-attr=register_tool(prusti)".to_owned());
+    rustc_args.push("--cfg=prusti".to_owned());
+
+    if config::check_overflows() {
+        // Some crates might have a `overflow-checks = false` in their `Cargo.toml` to
+        // disable integer overflow checks, but we want to ignore that.
+        rustc_args.push("-Zforce-overflow-checks=yes".to_owned());
+    }
+
+    if config::dump_debug_info() {
+        rustc_args.push("-Zdump-mir=all".to_owned());
+        rustc_args.push("-Zdump-mir-graphviz".to_owned());
+    }
+    rustc_args
+}
@@ -0,0 +1,85 @@
+//! A machine-readable summary of the verification of a crate, which is
+//! aggregated by `cargo-prusti` at the end of a run.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    path::PathBuf,
+    time::Duration,
+};
+use log::warn;
+use serde::Serialize;
+use prusti_common::verification_service::CacheStats;
+use prusti_interface::{
+    data::{ProcedureReport, ProcedureStatus},
+    environment::Environment,
+};
+
+#[derive(Serialize)]
+struct ItemSummary {
+    name: String,
+    status: &'static str,
+    /// The time spent encoding the item to Viper.
+    encoding_secs: f64,
+    /// The time spent verifying the Viper program that contains the item.
+    verification_secs: f64,
+}
+
+#[derive(Serialize)]
+pub struct CrateSummary {
+    crate_name: String,
+    success: bool,
+    duration_secs: f64,
+    items: Vec<ItemSummary>,
+    /// The lookups in the persistent cache of verified procedures.
+    cache: CacheStats,
+}
+
+impl CrateSummary {
+    pub fn new(
+        env: &Environment,
+        success: bool,
+        duration: Duration,
+        reports: &[ProcedureReport],
+        cache: CacheStats,
+    ) -> Self {
+        let items = reports
+            .iter()
+            .map(|report| ItemSummary {
+                name: env.get_absolute_item_name(report.def_id),
+                status: match report.status {
+                    ProcedureStatus::Verified => "verified",
+                    ProcedureStatus::Failed => "failed",
+                    ProcedureStatus::Trusted => "trusted",
+                    ProcedureStatus::Unsupported => "unsupported",
+                    ProcedureStatus::DependencyFailed => "dependency_failed",
+                },
+                encoding_secs: report.encoding_time.as_secs_f64(),
+                verification_secs: report.verification_time.as_secs_f64(),
+            })
+            .collect();
+        CrateSummary {
+            crate_name: env.crate_name(),
+            success,
+            duration_secs: duration.as_secs_f64(),
+            items,
+            cache,
+        }
+    }
+
+    /// Write the summary to `dir`. The name of the file depends on the source
+    /// path, because the library and the binaries of a package can share the
+    /// same crate name.
+    pub fn write(&self, env: &Environment, dir: &str) {
+        let mut hasher = DefaultHasher::new();
+        env.source_path().hash(&mut hasher);
+        let path = PathBuf::from(dir)
+            .join(format!("{}-{:016x}.json", self.crate_name, hasher.finish()));
+        let result = fs::create_dir_all(dir)
+            .and_then(|_| fs::write(&path, serde_json::to_string_pretty(self).unwrap()));
+        if let Err(error) = result {
+            warn!("Failed to write the verification summary to {:?}: {}", path, error);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! A module that invokes the verifier `prusti-viper`
+
+use prusti_interface::specs::typed;
+use log::{debug, trace, warn};
+use prusti_interface::{
+    data::{VerificationResult, VerificationTask},
+    environment::Environment,
+};
+use prusti_viper::verifier::Verifier;
+use prusti_common::config;
+use prusti_common::verification_service::CacheStats;
+use prusti_common::report::user;
+use std::time::Instant;
+use crate::summary::CrateSummary;
+use crate::{Callbacks, ProcedureResult, VerificationResults};
+
+pub fn verify<'tcx>(
+    env: Environment<'tcx>,
+    def_spec: typed::DefSpecificationMap<'tcx>,
+    callbacks: &mut dyn Callbacks,
+) -> VerificationResults {
+    trace!("[verify] enter");
+
+    let start = Instant::now();
+    let results = if env.has_errors() {
+        warn!("The compiler reported an error, so the program will not be verified.");
+        if let Some(summary_dir) = config::summary_dir() {
+            CrateSummary::new(&env, false, start.elapsed(), &[], CacheStats::default())
+                .write(&env, &summary_dir);
+        }
+        VerificationResults {
+            success: false,
+            procedures: vec![],
+            exit_code: 0,
+        }
+    } else {
+        debug!("Prepare verification task...");
+        let annotated_procedures = callbacks.select_procedures(
+            &env,
+            env.get_annotated_procedures(),
+        );
+        let verification_task = VerificationTask {
+            procedures: annotated_procedures,
+        };
+        debug!("Verification task: {:?}", &verification_task);
+
+        user::message(format!(
+            "Verification of {} items...",
+            verification_task.procedures.len()
+        ));
+
+        if config::print_collected_verification_items() {
+            println!("Collected verification items {}:", verification_task.procedures.len());
+            for procedure in &verification_task.procedures {
+                println!("procedure: {} at {:?}", env.get_item_def_path(*procedure), env.get_item_span(*procedure));
+            }
+        }
+
+        let (verification_result, procedure_reports, cache_stats) = if verification_task.procedures.is_empty() {
+            (VerificationResult::Success, vec![], CacheStats::default())
+        } else {
+            debug!("Dump borrow checker info...");
+            env.dump_borrowck_info(&verification_task.procedures);
+
+            let mut verifier = Verifier::new(&env, &def_spec);
+            let verification_result = verifier.verify(&verification_task);
+            debug!("Verifier returned {:?}", verification_result);
+
+            (verification_result, verifier.procedure_reports().to_vec(), verifier.cache_stats())
+        };
+
+        if let Some(summary_dir) = config::summary_dir() {
+            let success = verification_result == VerificationResult::Success;
+            CrateSummary::new(&env, success, start.elapsed(), &procedure_reports, cache_stats)
+                .write(&env, &summary_dir);
+        }
+
+        match verification_result {
+            VerificationResult::Success => {
+                user::message(format!(
+                    "Successful verification of {} items",
+                    verification_task.procedures.len()
+                ));
+            }
+            VerificationResult::Failure => {
+                user::message("Verification failed");
+                debug_assert!(env.has_errors());
+            }
+        };
+
+        VerificationResults {
+            success: verification_result == VerificationResult::Success,
+            procedures: procedure_reports
+                .iter()
+                .map(|report| ProcedureResult {
+                    name: env.get_absolute_item_name(report.def_id),
+                    status: report.status,
+                    encoding_time: report.encoding_time,
+                    verification_time: report.verification_time,
+                })
+                .collect(),
+            // Set by `verify_crate` once the compiler returns
+            exit_code: 0,
+        }
+    };
+    callbacks.after_verification(&env, &results);
+
+    trace!("[verify] exit");
+    results
+}
\ No newline at end of file
@@ -0,0 +1,97 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use prusti_facade::{
+    verify_crate, Callbacks, Environment, PrustiConfig, ProcedureDefId, ProcedureStatus,
+    VerificationResults,
+};
+use std::{env, fs, path::PathBuf, process::Command};
+
+const PROGRAM: &str = "
+fn verified(x: u32) -> u32 { x }
+fn failing() { assert!(false); }
+fn deselected() { assert!(false); }
+fn main() {}
+";
+
+/// Verifies all the procedures but `deselected`, and keeps the results.
+#[derive(Default)]
+struct RecordingCallbacks {
+    selected: Vec<String>,
+    results: Option<VerificationResults>,
+}
+
+impl Callbacks for RecordingCallbacks {
+    fn select_procedures<'tcx>(
+        &mut self,
+        env: &Environment<'tcx>,
+        procedures: Vec<ProcedureDefId>,
+    ) -> Vec<ProcedureDefId> {
+        let procedures: Vec<_> = procedures
+            .into_iter()
+            .filter(|&def_id| env.get_absolute_item_name(def_id) != "deselected")
+            .collect();
+        self.selected = procedures.iter()
+            .map(|&def_id| env.get_absolute_item_name(def_id))
+            .collect();
+        procedures
+    }
+
+    fn after_verification<'tcx>(
+        &mut self,
+        _env: &Environment<'tcx>,
+        results: &VerificationResults,
+    ) {
+        self.results = Some(results.clone());
+    }
+}
+
+fn rust_sysroot() -> String {
+    let output = Command::new("rustc")
+        .args(&["--print", "sysroot"])
+        .output()
+        .expect("failed to execute rustc");
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+#[test]
+fn verify_crate_with_callbacks() {
+    let work_dir: PathBuf = env::temp_dir()
+        .join(format!("prusti-facade-test-{}", std::process::id()));
+    fs::create_dir_all(&work_dir).unwrap();
+    let source_path = work_dir.join("program.rs");
+    fs::write(&source_path, PROGRAM).unwrap();
+
+    let config = PrustiConfig::new(vec![
+        "rustc".to_string(),
+        source_path.to_str().unwrap().to_string(),
+        "--edition=2018".to_string(),
+        "--sysroot".to_string(),
+        rust_sysroot(),
+        "--out-dir".to_string(),
+        work_dir.to_str().unwrap().to_string(),
+    ]).set("quiet", "true");
+    let mut callbacks = RecordingCallbacks::default();
+    let results = verify_crate(config, &mut callbacks);
+    fs::remove_dir_all(&work_dir).unwrap();
+
+    assert!(!results.success);
+    assert_ne!(results.exit_code, 0);
+    assert!(callbacks.selected.contains(&"verified".to_string()));
+    assert!(!callbacks.selected.contains(&"deselected".to_string()));
+    let status_of = |name: &str| {
+        results.procedures.iter()
+            .find(|procedure| procedure.name == name)
+            .map(|procedure| procedure.status)
+    };
+    assert_eq!(status_of("verified"), Some(ProcedureStatus::Verified));
+    assert_eq!(status_of("failing"), Some(ProcedureStatus::Failed));
+    assert_eq!(status_of("deselected"), None);
+
+    // The callback sees the results before the exit code is known.
+    let reported = callbacks.results.expect("after_verification was not called");
+    assert_eq!(reported.procedures.len(), results.procedures.len());
+}
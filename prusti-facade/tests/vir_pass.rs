@@ -0,0 +1,80 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use prusti_common::vir::{
+    CfgMethod, FoldingBehaviour, Position, Program, Stmt, Successor,
+};
+use prusti_facade::{register_vir_pass, verify_crate, DefaultCallbacks, PrustiConfig, VirPass};
+use std::{
+    env, fs,
+    path::PathBuf,
+    process::Command,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// A program that verifies without the pass.
+const PROGRAM: &str = "
+fn identity(x: u32) -> u32 { x }
+fn main() {}
+";
+
+static PASS_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+/// Adds a method that asserts `false`, so that the program fails to verify.
+struct InjectFailure;
+
+impl VirPass for InjectFailure {
+    fn name(&self) -> &str {
+        "inject-failure"
+    }
+
+    fn run(&self, mut program: Program) -> Program {
+        PASS_RUNS.fetch_add(1, Ordering::SeqCst);
+        let mut method = CfgMethod::new("injected_failure".to_string(), 0, vec![], vec![], vec![]);
+        let block = method.add_block(
+            "start",
+            vec![Stmt::Assert(false.into(), FoldingBehaviour::Stmt, Position::new(0, 0, 999_999))],
+        );
+        method.set_successor(block, Successor::Return);
+        program.methods.push(method);
+        program
+    }
+}
+
+fn rust_sysroot() -> String {
+    let output = Command::new("rustc")
+        .args(&["--print", "sysroot"])
+        .output()
+        .expect("failed to execute rustc");
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+#[test]
+fn registered_pass_transforms_the_verified_program() {
+    let work_dir: PathBuf = env::temp_dir()
+        .join(format!("prusti-vir-pass-test-{}", std::process::id()));
+    fs::create_dir_all(&work_dir).unwrap();
+    let source_path = work_dir.join("program.rs");
+    fs::write(&source_path, PROGRAM).unwrap();
+
+    register_vir_pass(Box::new(InjectFailure));
+    let config = PrustiConfig::new(vec![
+        "rustc".to_string(),
+        source_path.to_str().unwrap().to_string(),
+        "--edition=2018".to_string(),
+        "--sysroot".to_string(),
+        rust_sysroot(),
+        "--out-dir".to_string(),
+        work_dir.to_str().unwrap().to_string(),
+    ]).set("quiet", "true");
+    let results = verify_crate(config, &mut DefaultCallbacks);
+    fs::remove_dir_all(&work_dir).unwrap();
+
+    assert_eq!(PASS_RUNS.load(Ordering::SeqCst), 1);
+    // The failure can only come from the method added by the pass.
+    assert!(!results.success);
+    assert_ne!(results.exit_code, 0);
+}
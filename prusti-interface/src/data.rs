@@ -8,6 +8,8 @@
 //! its environment.
 
 use rustc_hir::def_id::DefId;
+use rustc_span::Span;
+use std::time::Duration;
 
 /// A unique identifier of the Rust procedure.
 pub type ProcedureDefId = DefId;
@@ -28,3 +30,34 @@ pub enum VerificationResult {
     /// the verifier.
     Failure,
 }
+
+/// The outcome of verifying a single procedure.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ProcedureStatus {
+    /// The procedure was encoded and no verification error was attributed to it.
+    Verified,
+    /// Encoding or verification of the procedure reported an error.
+    Failed,
+    /// The procedure is trusted, so its body was not verified.
+    Trusted,
+    /// The procedure uses a feature that is not supported and was skipped.
+    Unsupported,
+    /// The procedure was not verified because a procedure that it calls
+    /// failed, and its errors would likely be a consequence of that failure.
+    DependencyFailed,
+}
+
+/// Per-procedure information collected while verifying a crate.
+#[derive(Debug, Clone)]
+pub struct ProcedureReport {
+    pub def_id: ProcedureDefId,
+    /// The span used to attribute verification errors to the procedure.
+    pub span: Span,
+    pub status: ProcedureStatus,
+    /// The time spent encoding the procedure to Viper.
+    pub encoding_time: Duration,
+    /// The time spent verifying the Viper program that contains the method
+    /// of the procedure. Unless the methods are verified separately (see
+    /// `config::jobs`), the program contains all the methods of the crate.
+    pub verification_time: Duration,
+}
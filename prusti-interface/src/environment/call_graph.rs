@@ -0,0 +1,158 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::data::ProcedureDefId;
+use crate::environment::Environment;
+use rustc_middle::mir;
+use rustc_middle::ty;
+use std::collections::{HashMap, HashSet};
+use log::trace;
+
+/// The calls between the procedures of a verification task.
+pub struct CallGraph {
+    /// The procedures, ordered such that callees come before their callers.
+    /// The order of (mutually) recursive procedures is arbitrary.
+    order: Vec<ProcedureDefId>,
+    callees: HashMap<ProcedureDefId, Vec<ProcedureDefId>>,
+}
+
+impl CallGraph {
+    pub fn new(env: &Environment, procedures: &[ProcedureDefId]) -> Self {
+        let procedure_set: HashSet<_> = procedures.iter().cloned().collect();
+        let callees = procedures
+            .iter()
+            .map(|&def_id| (def_id, collect_callees(env, def_id, &procedure_set)))
+            .collect();
+        let mut call_graph = CallGraph {
+            order: Vec::with_capacity(procedures.len()),
+            callees,
+        };
+        let mut visited = HashSet::new();
+        for &def_id in procedures {
+            call_graph.visit(def_id, &mut visited);
+        }
+        call_graph
+    }
+
+    fn visit(&mut self, def_id: ProcedureDefId, visited: &mut HashSet<ProcedureDefId>) {
+        if !visited.insert(def_id) {
+            return;
+        }
+        for callee in self.callees[&def_id].clone() {
+            self.visit(callee, visited);
+        }
+        self.order.push(def_id);
+    }
+
+    /// The procedures, ordered such that callees come before their callers.
+    pub fn topological_order(&self) -> &[ProcedureDefId] {
+        &self.order
+    }
+
+    /// The procedures of the task that are called by `def_id`, excluding
+    /// `def_id` itself.
+    pub fn callees(&self, def_id: ProcedureDefId) -> impl Iterator<Item = ProcedureDefId> + '_ {
+        self.callees
+            .get(&def_id)
+            .into_iter()
+            .flatten()
+            .cloned()
+            .filter(move |&callee| callee != def_id)
+    }
+
+    /// The groups of (mutually) recursive procedures among the procedures
+    /// that satisfy `filter`, considering only calls between such procedures.
+    pub fn recursive_components<F>(&self, filter: F) -> Vec<Vec<ProcedureDefId>>
+        where F: Fn(ProcedureDefId) -> bool
+    {
+        let nodes: Vec<_> = self.order.iter().cloned().filter(|&def_id| filter(def_id)).collect();
+        let reachable: HashMap<_, _> = nodes.iter()
+            .map(|&def_id| (def_id, self.reachable_from(def_id, &filter)))
+            .collect();
+        let mut components = vec![];
+        let mut visited = HashSet::new();
+        for &def_id in &nodes {
+            if visited.contains(&def_id) || !reachable[&def_id].contains(&def_id) {
+                continue;
+            }
+            let component: Vec<_> = nodes.iter()
+                .cloned()
+                .filter(|other| {
+                    reachable[&def_id].contains(other) && reachable[other].contains(&def_id)
+                })
+                .collect();
+            visited.extend(component.iter().cloned());
+            components.push(component);
+        }
+        components
+    }
+
+    /// The procedures that satisfy `filter` and that are reachable from
+    /// `def_id` with at least one call through such procedures.
+    fn reachable_from<F>(&self, def_id: ProcedureDefId, filter: &F) -> HashSet<ProcedureDefId>
+        where F: Fn(ProcedureDefId) -> bool
+    {
+        let mut reachable = HashSet::new();
+        let mut stack = vec![def_id];
+        while let Some(caller) = stack.pop() {
+            for &callee in &self.callees[&caller] {
+                if filter(callee) && reachable.insert(callee) {
+                    stack.push(callee);
+                }
+            }
+        }
+        reachable
+    }
+}
+
+/// Collect the procedures in `procedures` that are directly called by the
+/// body of `caller`. Calls of trait methods are resolved to the implementation
+/// when possible, and calls of closures through the `Fn*` traits are
+/// attributed to the closure.
+fn collect_callees(
+    env: &Environment,
+    caller: ProcedureDefId,
+    procedures: &HashSet<ProcedureDefId>,
+) -> Vec<ProcedureDefId> {
+    let local_caller = match caller.as_local() {
+        Some(local_caller) => local_caller,
+        None => return vec![],
+    };
+    let tcx = env.tcx();
+    let param_env = tcx.param_env(caller);
+    let mir = env.local_mir(local_caller);
+    let mut callees = vec![];
+    for bb_data in mir.basic_blocks() {
+        let func = match bb_data.terminator().kind {
+            mir::TerminatorKind::Call { ref func, .. } => func,
+            _ => continue,
+        };
+        let constant = match func {
+            mir::Operand::Constant(constant) => constant,
+            _ => continue,
+        };
+        let (def_id, substs) = match constant.literal.ty.kind() {
+            ty::TyKind::FnDef(def_id, substs) => (*def_id, *substs),
+            _ => continue,
+        };
+        let mut candidates = vec![def_id];
+        if let Ok(Some(instance)) = ty::Instance::resolve(tcx, param_env, def_id, substs) {
+            candidates.push(instance.def_id());
+        }
+        if let Some(self_ty) = substs.types().next() {
+            if let ty::TyKind::Closure(closure_def_id, _) = self_ty.kind() {
+                candidates.push(*closure_def_id);
+            }
+        }
+        for callee in candidates {
+            if procedures.contains(&callee) && !callees.contains(&callee) {
+                callees.push(callee);
+            }
+        }
+    }
+    trace!("callees of {:?}: {:?}", caller, callees);
+    callees
+}
@@ -1,4 +1,4 @@
-use rustc_hir::intravisit::{Visitor, NestedVisitorMap, ErasedMap, walk_expr, FnKind};
+use rustc_hir::intravisit::{Visitor, NestedVisitorMap, ErasedMap, walk_expr, walk_item, FnKind};
 use rustc_hir as hir;
 use rustc_middle::hir::map::Map;
 use crate::environment::Environment;
@@ -6,7 +6,7 @@ use log::{trace, debug};
 use rustc_hir::def_id::DefId;
 use rustc_span::Span;
 use rustc_middle::ty::TypeckResults;
-use crate::utils::has_spec_only_attr;
+use crate::utils::{has_spec_only_attr, is_test_harness_item};
 
 pub struct CollectClosureDefsVisitor<'env, 'tcx: 'env> {
     env: &'env Environment<'tcx>,
@@ -34,6 +34,13 @@ impl<'env, 'tcx> Visitor<'tcx> for CollectClosureDefsVisitor<'env, 'tcx> {
         NestedVisitorMap::OnlyBodies (self.map)
     }
 
+    fn visit_item(&mut self, item: &'tcx hir::Item<'tcx>) {
+        // The closures of the test harness are not written by the user.
+        if !is_test_harness_item(self.env.tcx(), &item.attrs) {
+            walk_item(self, item)
+        }
+    }
+
     fn visit_expr(&mut self, expr: &'tcx hir::Expr<'tcx>) {
         match expr.kind {
             hir::ExprKind::Closure(_, _, _, _, _) => {
@@ -14,12 +14,17 @@ use std::collections::HashSet;
 use std::iter::FromIterator;
 use log::{trace, debug};
 use rustc_ast::ast;
-use crate::utils::{has_spec_only_attr, has_extern_spec_attr};
+use rustc_span::symbol::{sym, Symbol};
+use crate::utils::{has_spec_only_attr, has_extern_spec_attr, is_test_harness_item};
 
 pub struct CollectPrustiSpecVisitor<'a, 'tcx: 'a> {
     env: &'a Environment<'tcx>,
     tcx: TyCtxt<'tcx>,
     result: Vec<DefId>,
+    /// The functions declared as items, with their parent module and name.
+    fn_items: Vec<(DefId, hir::HirId, Symbol)>,
+    /// The parent module and name of the functions annotated with `#[test]`.
+    test_markers: HashSet<(hir::HirId, Symbol)>,
 }
 
 impl<'a, 'tcx> CollectPrustiSpecVisitor<'a, 'tcx> {
@@ -28,16 +33,33 @@ impl<'a, 'tcx> CollectPrustiSpecVisitor<'a, 'tcx> {
             env,
             tcx: env.tcx(),
             result: Vec::new(),
+            fn_items: Vec::new(),
+            test_markers: HashSet::new(),
         }
     }
     pub fn get_annotated_procedures(self) -> Vec<DefId> {
         self.result
     }
+    /// The functions annotated with `#[test]`. For each of them, the test
+    /// harness generates a constant with the same name in the same module.
+    pub fn get_test_functions(&self) -> Vec<DefId> {
+        self.fn_items
+            .iter()
+            .filter(|(_, parent, name)| self.test_markers.contains(&(*parent, *name)))
+            .map(|(def_id, _, _)| *def_id)
+            .collect()
+    }
 }
 
 impl<'a, 'tcx> ItemLikeVisitor<'tcx> for CollectPrustiSpecVisitor<'a, 'tcx> {
     fn visit_item(&mut self, item: &hir::Item) {
-        if has_spec_only_attr(&item.attrs) || has_extern_spec_attr(&item.attrs) {
+        if self.tcx.sess.contains_name(&item.attrs, sym::rustc_test_marker) {
+            let parent = self.tcx.hir().get_parent_item(item.hir_id);
+            self.test_markers.insert((parent, item.ident.name));
+            return;
+        }
+        if has_spec_only_attr(&item.attrs) || has_extern_spec_attr(&item.attrs)
+            || is_test_harness_item(self.tcx, &item.attrs) {
             return;
         }
         if let hir::ItemKind::Fn(..) = item.kind {
@@ -45,6 +67,8 @@ impl<'a, 'tcx> ItemLikeVisitor<'tcx> for CollectPrustiSpecVisitor<'a, 'tcx> {
             let item_def_path = self.env.get_item_def_path(def_id);
             trace!("Add {} to result", item_def_path);
             self.result.push(def_id);
+            let parent = self.tcx.hir().get_parent_item(item.hir_id);
+            self.fn_items.push((def_id, parent, item.ident.name));
         }
     }
 
@@ -0,0 +1,177 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::data::ProcedureDefId;
+use crate::environment::Environment;
+use crate::utils::has_prusti_attr;
+use crate::PrustiError;
+use rustc_hir::def_id::DefId;
+use rustc_middle::mir::{self, visit::Visitor};
+use rustc_middle::ty;
+use rustc_span::{BytePos, Span};
+use log::trace;
+
+/// Check that the ghost code of the procedure `def_id` does not modify the
+/// rest of the program, because it is erased from the compiled program. The
+/// ghost code can only write to the variables declared by ghost code and can
+/// only call the functions for which `is_pure` holds, constructors and the
+/// functions that panic. Returns an error for each offending statement.
+pub fn check_ghost_code<F>(
+    env: &Environment<'_>,
+    def_id: ProcedureDefId,
+    is_pure: F,
+) -> Vec<PrustiError>
+    where F: Fn(DefId) -> bool
+{
+    let local_def_id = match def_id.as_local() {
+        Some(local_def_id) => local_def_id,
+        None => return vec![],
+    };
+    let mir = env.local_mir(local_def_id);
+    let regions = collect_ghost_regions(env, &mir);
+    if regions.is_empty() {
+        return vec![];
+    }
+    let mut checker = GhostChecker {
+        env,
+        mir: &mir,
+        regions,
+        is_pure,
+        errors: vec![],
+    };
+    checker.visit_body(&mir);
+    trace!("ghost code errors of {:?}: {:?}", def_id, checker.errors);
+    checker.errors
+}
+
+/// The source regions of the `ghost!` blocks of `mir`, delimited by the
+/// `prusti::ghost_begin` and `prusti::ghost_end` closures.
+fn collect_ghost_regions(env: &Environment<'_>, mir: &mir::Body<'_>) -> Vec<(BytePos, BytePos)> {
+    let tcx = env.tcx();
+    let mut begins = vec![];
+    let mut ends = vec![];
+    for bb_data in mir.basic_blocks() {
+        for stmt in &bb_data.statements {
+            if let mir::StatementKind::Assign(box (_, mir::Rvalue::Aggregate(box aggr, _))) = &stmt.kind {
+                if let mir::AggregateKind::Closure(cl_def_id, _) = aggr {
+                    let attrs = tcx.get_attrs(*cl_def_id);
+                    if has_prusti_attr(attrs, "ghost_begin") {
+                        begins.push(stmt.source_info.span.lo());
+                    } else if has_prusti_attr(attrs, "ghost_end") {
+                        ends.push(stmt.source_info.span.hi());
+                    }
+                }
+            }
+        }
+    }
+    begins.sort();
+    ends.sort();
+    // Ghost blocks cannot be nested, so the n-th end closes the n-th begin.
+    begins.into_iter().zip(ends.into_iter()).collect()
+}
+
+struct GhostChecker<'a, 'tcx, F> {
+    env: &'a Environment<'tcx>,
+    mir: &'a mir::Body<'tcx>,
+    regions: Vec<(BytePos, BytePos)>,
+    is_pure: F,
+    errors: Vec<PrustiError>,
+}
+
+impl<'a, 'tcx, F> GhostChecker<'a, 'tcx, F>
+    where F: Fn(DefId) -> bool
+{
+    /// Whether `span`, or the macro call that generated it, is ghost code.
+    fn is_ghost(&self, span: Span) -> bool {
+        let span = span.source_callsite();
+        self.regions.iter().any(|&(lo, hi)| lo <= span.lo() && span.hi() <= hi)
+    }
+
+    /// Whether the variable of `place` is declared outside of ghost code.
+    fn is_non_ghost_variable(&self, place: mir::Place<'tcx>) -> bool {
+        let local_decl = &self.mir.local_decls[place.local];
+        local_decl.is_user_variable() && !self.is_ghost(local_decl.source_info.span)
+    }
+
+    fn report<S: ToString>(&mut self, message: S, help: &str, span: Span) {
+        self.errors.push(
+            PrustiError::incorrect(message, span.into()).set_help(help)
+        );
+    }
+
+    fn check_write(&mut self, place: mir::Place<'tcx>, span: Span) {
+        if self.is_ghost(span) && self.is_non_ghost_variable(place) {
+            self.report(
+                "ghost code cannot modify a variable that is not declared by ghost code",
+                "Ghost code is erased from the compiled program, which would then behave differently.",
+                span,
+            );
+        }
+    }
+
+    fn check_call(&mut self, func: &mir::Operand<'tcx>, span: Span) {
+        if !self.is_ghost(span) {
+            return;
+        }
+        let tcx = self.env.tcx();
+        match func.ty(self.mir, tcx).kind() {
+            ty::TyKind::FnDef(def_id, _) => {
+                let def_id = *def_id;
+                let is_panic = matches!(
+                    tcx.def_path_str(def_id).as_str(),
+                    "std::rt::begin_panic" | "core::panicking::panic" | "core::panicking::panic_fmt"
+                );
+                if !is_panic && !tcx.is_constructor(def_id) && !(self.is_pure)(def_id) {
+                    self.report(
+                        format!(
+                            "use of impure function '{}' is not allowed in ghost code",
+                            self.env.get_item_name(def_id),
+                        ),
+                        "Only functions marked with #[pure] can be called by ghost code.",
+                        span,
+                    );
+                }
+            }
+            _ => {
+                self.report(
+                    "calling a function pointer is not allowed in ghost code",
+                    "The called function is not known statically.",
+                    span,
+                );
+            }
+        }
+    }
+}
+
+impl<'a, 'tcx, F> Visitor<'tcx> for GhostChecker<'a, 'tcx, F>
+    where F: Fn(DefId) -> bool
+{
+    fn visit_statement(&mut self, statement: &mir::Statement<'tcx>, location: mir::Location) {
+        let span = statement.source_info.span;
+        if let mir::StatementKind::Assign(box (place, rvalue)) = &statement.kind {
+            self.check_write(*place, span);
+            match rvalue {
+                mir::Rvalue::Ref(_, mir::BorrowKind::Mut { .. }, borrowed_place)
+                | mir::Rvalue::AddressOf(mir::Mutability::Mut, borrowed_place) => {
+                    self.check_write(*borrowed_place, span);
+                }
+                _ => {}
+            }
+        }
+        self.super_statement(statement, location);
+    }
+
+    fn visit_terminator(&mut self, terminator: &mir::Terminator<'tcx>, location: mir::Location) {
+        let span = terminator.source_info.span;
+        if let mir::TerminatorKind::Call { ref func, ref destination, .. } = terminator.kind {
+            if let Some((place, _)) = destination {
+                self.check_write(*place, span);
+            }
+            self.check_call(func, span);
+        }
+        self.super_terminator(terminator, location);
+    }
+}
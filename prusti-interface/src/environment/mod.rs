@@ -15,13 +15,16 @@ use rustc_middle::ty::{self, TyCtxt, ParamEnv};
 use std::path::PathBuf;
 use std::cell::Ref;
 use rustc_span::{Span, MultiSpan, symbol::Symbol};
+use rustc_errors::DiagnosticBuilder;
 use std::collections::HashSet;
 use log::debug;
 
 pub mod borrowck;
+mod call_graph;
 mod collect_prusti_spec_visitor;
 mod collect_closure_defs_visitor;
 mod dump_borrowck_info;
+mod ghost_checker;
 mod loops;
 mod loops_utils;
 pub mod mir_analyses;
@@ -29,13 +32,17 @@ pub mod mir_utils;
 pub mod place_set;
 pub mod polonius_info;
 mod procedure;
+mod purity_checker;
 
 use self::collect_prusti_spec_visitor::CollectPrustiSpecVisitor;
 use self::collect_closure_defs_visitor::CollectClosureDefsVisitor;
 use rustc_hir::intravisit::Visitor;
+pub use self::call_graph::CallGraph;
 pub use self::loops::{PlaceAccess, PlaceAccessKind, ProcedureLoops};
 pub use self::loops_utils::*;
 pub use self::procedure::{BasicBlockIndex, Procedure};
+pub use self::ghost_checker::check_ghost_code;
+pub use self::purity_checker::check_purity;
 // use config;
 use crate::data::ProcedureDefId;
 // use syntax::codemap::CodeMap;
@@ -109,14 +116,15 @@ impl<'tcx> Environment<'tcx> {
     //     self.state.session.span_err(sp, msg);
     // }
 
-    /// Emits an error message.
-    pub fn span_err_with_help_and_note<S: Into<MultiSpan> + Clone>(
+    /// Builds an error message, to be emitted with `emit()`.
+    pub fn struct_span_err_with_help_and_note<S: Into<MultiSpan> + Clone>(
         &self,
         sp: S,
         msg: &str,
         help: &Option<String>,
-        note: &Option<(String, S)>
-    ) {
+        note: &Option<(String, S)>,
+        plain_note: &Option<String>,
+    ) -> DiagnosticBuilder<'tcx> {
         let mut diagnostic = self.tcx.sess.struct_err(msg);
         diagnostic.set_span(sp);
         if let Some(help_msg) = help {
@@ -125,17 +133,21 @@ impl<'tcx> Environment<'tcx> {
         if let Some((note_msg, note_sp)) = note {
             diagnostic.span_note(note_sp.clone(), note_msg);
         }
-        diagnostic.emit();
+        if let Some(note_msg) = plain_note {
+            diagnostic.note(note_msg);
+        }
+        diagnostic
     }
 
-    /// Emits an error message.
-    pub fn span_warn_with_help_and_note<S: Into<MultiSpan> + Clone>(
+    /// Builds a warning message, to be emitted with `emit()`.
+    pub fn struct_span_warn_with_help_and_note<S: Into<MultiSpan> + Clone>(
         &self,
         sp: S,
         msg: &str,
         help: &Option<String>,
-        note: &Option<(String, S)>
-    ) {
+        note: &Option<(String, S)>,
+        plain_note: &Option<String>,
+    ) -> DiagnosticBuilder<'tcx> {
         let mut diagnostic = self.tcx.sess.struct_warn(msg);
         diagnostic.set_span(sp);
         if let Some(help_msg) = help {
@@ -144,7 +156,10 @@ impl<'tcx> Environment<'tcx> {
         if let Some((note_msg, note_sp)) = note {
             diagnostic.span_note(note_sp.clone(), note_msg);
         }
-        diagnostic.emit();
+        if let Some(note_msg) = plain_note {
+            diagnostic.note(note_msg);
+        }
+        diagnostic
     }
 
     /// Returns true if an error has been emitted
@@ -161,11 +176,81 @@ impl<'tcx> Environment<'tcx> {
         let mut cl_visitor = CollectClosureDefsVisitor::new(self);
         tcx.hir().krate().visit_all_item_likes(&mut cl_visitor.as_deep_visitor());
 
-        let mut result: Vec<_> = visitor.get_annotated_procedures();
-        result.extend(cl_visitor.get_closure_defs());
+        let mut result = if prusti_common::config::verify_only_tests() {
+            let tests = visitor.get_test_functions();
+            let closures = cl_visitor.get_closure_defs()
+                .into_iter()
+                .filter(|&def_id| tests.contains(&tcx.closure_base_def_id(def_id)));
+            let mut result = tests.clone();
+            result.extend(closures);
+            result
+        } else {
+            let mut result: Vec<_> = visitor.get_annotated_procedures();
+            result.extend(cl_visitor.get_closure_defs());
+            result
+        };
+
+        result.retain(|&def_id| !self.is_skipped(def_id));
+
+        let skipped_macros = self.get_skipped_macros();
+        if !skipped_macros.is_empty() {
+            result.retain(|&def_id| {
+                let base_def_id = tcx.closure_base_def_id(def_id);
+                match self.get_expanding_macro(self.get_item_span(base_def_id), &skipped_macros) {
+                    Some(macro_path) => {
+                        debug!(
+                            "Skipping {} generated by the macro {}",
+                            self.get_item_def_path(def_id),
+                            macro_path,
+                        );
+                        false
+                    }
+                    None => true,
+                }
+            });
+        }
         result
     }
 
+    /// The patterns of the paths of the macros whose expansions should not be
+    /// verified, from the `SKIP_EXPANSIONS_OF` setting and from the
+    /// `#![prusti::skip_expansion_of(...)]` attributes of the crate.
+    fn get_skipped_macros(&self) -> Vec<String> {
+        let mut patterns = prusti_common::config::skip_expansions_of();
+        patterns.extend(crate::utils::read_prusti_path_list_attrs(
+            "skip_expansion_of",
+            self.tcx.hir().krate_attrs(),
+        ));
+        patterns
+    }
+
+    /// Return the path of the first macro in the backtrace of `span` whose
+    /// path or name matches one of `patterns`.
+    fn get_expanding_macro(&self, span: Span, patterns: &[String]) -> Option<String> {
+        for expn_data in span.macro_backtrace() {
+            let mut names = vec![];
+            if let Some(macro_def_id) = expn_data.macro_def_id {
+                names.push(self.tcx.def_path_str(macro_def_id));
+            }
+            if let rustc_span::hygiene::ExpnKind::Macro(_, name) = expn_data.kind {
+                names.push(name.to_string());
+            }
+            let matches = names.iter().any(|name| {
+                patterns.iter().any(|pattern| crate::utils::matches_glob(pattern, name))
+            });
+            if matches {
+                return names.into_iter().next();
+            }
+        }
+        None
+    }
+
+    /// Find whether the item is excluded from the verification with
+    /// `prusti::skip`, either directly or through an enclosing item.
+    pub fn is_skipped(&self, def_id: DefId) -> bool {
+        crate::utils::is_skipped_item(self.tcx, def_id)
+    }
+
     /// Find whether the procedure has a particular `prusti::<name>` attribute.
     pub fn has_prusti_attribute(&self, def_id: ProcedureDefId, name: &str) -> bool {
         let tcx = self.tcx();
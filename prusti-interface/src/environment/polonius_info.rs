@@ -525,6 +525,27 @@ fn add_fake_facts<'a, 'tcx: 'a>(
     Ok((reference_moves, argument_moves, incompatible_loans))
 }
 
+/// Remove the loans of the shallow borrows that the compiler introduces for
+/// match guards. These borrows only prevent the guard from modifying the
+/// matched place and are never dereferenced, so they are not encoded.
+fn remove_shallow_borrows<'tcx>(
+    all_facts: &mut facts::AllInputFacts,
+    interner: &facts::Interner,
+    mir: &mir::Body<'tcx>,
+) {
+    all_facts.borrow_region.retain(|&(_, _, point_index)| {
+        let location = interner.get_point(point_index).location;
+        let is_shallow_borrow = mir
+            .statement_at(location)
+            .and_then(|statement| statement.as_assign())
+            .map_or(false, |(_, rhs)| match rhs {
+                mir::Rvalue::Ref(_, mir::BorrowKind::Shallow, _) => true,
+                _ => false,
+            });
+        !is_shallow_borrow
+    });
+}
+
 /// Remove back edges to make MIR uncyclic so that we can compute reborrowing dags at the end of
 /// the loop body.
 fn remove_back_edges(
@@ -700,6 +721,7 @@ impl<'a, 'tcx: 'a> PoloniusInfo<'a, 'tcx> {
             PoloniusInfoError::PlaceRegionsError(err, loc)
         )?;
 
+        remove_shallow_borrows(&mut all_facts, &facts_loader.interner, &mir);
         Self::disconnect_universal_regions(tcx, mir, &place_regions, &mut all_facts);
 
         let output = Output::compute(&all_facts, Algorithm::Naive, true);
@@ -0,0 +1,172 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::data::ProcedureDefId;
+use crate::environment::Environment;
+use crate::PrustiError;
+use prusti_common::config;
+use rustc_hir::def_id::DefId;
+use rustc_middle::mir::{self, visit::Visitor};
+use rustc_middle::ty;
+use rustc_span::Span;
+use log::trace;
+
+/// Check that the body of the pure function `def_id` contains only
+/// constructs that can be encoded as a deterministic function of its
+/// arguments. Calls are allowed to the functions for which `is_pure` holds,
+/// to constructors and to functions of other crates, whose support is decided
+/// by the encoder. Returns an error for each offending statement.
+pub fn check_purity<F>(
+    env: &Environment<'_>,
+    def_id: ProcedureDefId,
+    is_pure: F,
+) -> Vec<PrustiError>
+    where F: Fn(DefId) -> bool
+{
+    let local_def_id = match def_id.as_local() {
+        Some(local_def_id) => local_def_id,
+        None => return vec![],
+    };
+    let mir = env.local_mir(local_def_id);
+    let mut checker = PurityChecker {
+        env,
+        mir: &mir,
+        is_pure,
+        errors: vec![],
+    };
+    checker.visit_body(&mir);
+    trace!("purity errors of {:?}: {:?}", def_id, checker.errors);
+    checker.errors
+}
+
+struct PurityChecker<'a, 'tcx, F> {
+    env: &'a Environment<'tcx>,
+    mir: &'a mir::Body<'tcx>,
+    is_pure: F,
+    errors: Vec<PrustiError>,
+}
+
+impl<'a, 'tcx, F> PurityChecker<'a, 'tcx, F>
+    where F: Fn(DefId) -> bool
+{
+    fn report<S: ToString>(&mut self, message: S, help: &str, span: Span) {
+        self.errors.push(
+            PrustiError::incorrect(message, span.into()).set_help(help)
+        );
+    }
+
+    fn report_static(&mut self, static_def_id: DefId, span: Span) {
+        self.report(
+            format!(
+                "reading the static '{}' is not allowed in a pure function",
+                self.env.get_item_name(static_def_id),
+            ),
+            "The value of a static might change between calls. Pass the value as an argument.",
+            span,
+        );
+    }
+
+    fn report_allocation(&mut self, span: Span) {
+        if !config::relaxed_purity() {
+            self.report(
+                "heap allocation is not allowed in a pure function",
+                "Enable RELAXED_PURITY to allow pure functions to allocate boxes.",
+                span,
+            );
+        }
+    }
+
+    fn check_call(&mut self, func: &mir::Operand<'tcx>, span: Span) {
+        let tcx = self.env.tcx();
+        match func.ty(self.mir, tcx).kind() {
+            ty::TyKind::FnDef(def_id, _) => {
+                let def_id = *def_id;
+                if tcx.def_path_str(def_id) == "std::boxed::Box::<T>::new" {
+                    self.report_allocation(span);
+                } else if def_id.is_local() && !tcx.is_constructor(def_id) && !(self.is_pure)(def_id) {
+                    self.report(
+                        format!(
+                            "use of impure function '{}' is not allowed in a pure function",
+                            self.env.get_item_name(def_id),
+                        ),
+                        "Only functions marked with #[pure] can be called by a pure function.",
+                        span,
+                    );
+                }
+            }
+            _ => {
+                self.report(
+                    "calling a function pointer is not allowed in a pure function",
+                    "The called function is not known statically.",
+                    span,
+                );
+            }
+        }
+    }
+}
+
+impl<'a, 'tcx, F> Visitor<'tcx> for PurityChecker<'a, 'tcx, F>
+    where F: Fn(DefId) -> bool
+{
+    fn visit_constant(&mut self, constant: &mir::Constant<'tcx>, location: mir::Location) {
+        if let Some(static_def_id) = constant.check_static_ptr(self.env.tcx()) {
+            self.report_static(static_def_id, constant.span);
+        }
+        self.super_constant(constant, location);
+    }
+
+    fn visit_rvalue(&mut self, rvalue: &mir::Rvalue<'tcx>, location: mir::Location) {
+        let span = self.mir.source_info(location).span;
+        match rvalue {
+            mir::Rvalue::ThreadLocalRef(static_def_id) => {
+                self.report_static(*static_def_id, span);
+            }
+            mir::Rvalue::NullaryOp(mir::NullOp::Box, _) => {
+                self.report(
+                    "the box syntax is not supported in a pure function",
+                    "Allocate the box with Box::new and enable RELAXED_PURITY.",
+                    span,
+                );
+            }
+            mir::Rvalue::AddressOf(..) => {
+                self.report(
+                    "creating a raw pointer is not allowed in a pure function",
+                    "The address of a place is not a deterministic function of the arguments.",
+                    span,
+                );
+            }
+            _ => {}
+        }
+        self.super_rvalue(rvalue, location);
+    }
+
+    fn visit_statement(&mut self, statement: &mir::Statement<'tcx>, location: mir::Location) {
+        if let mir::StatementKind::LlvmInlineAsm(..) = statement.kind {
+            self.report(
+                "inline assembly is not allowed in a pure function",
+                "Pure functions cannot have side effects.",
+                statement.source_info.span,
+            );
+        }
+        self.super_statement(statement, location);
+    }
+
+    fn visit_terminator(&mut self, terminator: &mir::Terminator<'tcx>, location: mir::Location) {
+        let span = terminator.source_info.span;
+        match terminator.kind {
+            mir::TerminatorKind::Call { ref func, .. } => self.check_call(func, span),
+            mir::TerminatorKind::InlineAsm { .. } => {
+                self.report(
+                    "inline assembly is not allowed in a pure function",
+                    "Pure functions cannot have side effects.",
+                    span,
+                );
+            }
+            _ => {}
+        }
+        self.super_terminator(terminator, location);
+    }
+}
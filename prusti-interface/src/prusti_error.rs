@@ -4,7 +4,8 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use rustc_span::MultiSpan;
+use rustc_errors::{Applicability, DiagnosticId};
+use rustc_span::{MultiSpan, Span};
 use crate::environment::Environment;
 use prusti_common::config;
 use ::log::warn;
@@ -21,41 +22,79 @@ use ::log::warn;
 #[derive(Clone, Debug)]
 pub struct PrustiError {
     is_error: bool,
+    /// The kind of problem, e.g. `verification` or `internal`
+    kind: &'static str,
     message: String,
     span: MultiSpan,
     help: Option<String>,
     note: Option<(String, MultiSpan)>,
+    counterexample: Option<String>,
+    /// A message, the span to replace, its replacement and whether the
+    /// replacement can be applied as is
+    suggestion: Option<(String, Span, String, Applicability)>,
+    /// The procedure in which the problem was found
+    method: Option<String>,
+    /// The kind of specification that failed, e.g. `postcondition`
+    spec_kind: Option<String>,
+    /// The error context of the encoding that failed, e.g. `ExhaleMethodPostcondition`
+    error_ctxt: Option<String>,
 }
 
 impl PrustiError {
     /// Private constructor. Use one of the following methods.
-    fn new(message: String, span: MultiSpan) -> Self {
+    fn new(kind: &'static str, message: String, span: MultiSpan) -> Self {
         PrustiError {
             is_error: true,
+            kind,
             message,
             span,
             help: None,
             note: None,
+            counterexample: None,
+            suggestion: None,
+            method: None,
+            spec_kind: None,
+            error_ctxt: None,
         }
     }
 
     /// Report a verification error of the verified Rust code
     pub fn verification<S: ToString>(message: S, span: MultiSpan) -> Self {
         check_message(message.to_string());
-        PrustiError::new(
+        let mut error = PrustiError::new(
+            "verification",
             format!("[Prusti: verification error] {}", message.to_string()),
             span
-        )
+        );
+        if !fails_on("verification", true) {
+            error.set_warning();
+        }
+        error
+    }
+
+    /// Report that the verifier timed out
+    pub fn timeout<S: ToString>(message: S, span: MultiSpan) -> Self {
+        check_message(message.to_string());
+        let mut error = PrustiError::new(
+            "timeout",
+            format!("[Prusti: verification timeout] {}", message.to_string()),
+            span
+        );
+        if !fails_on("timeout", true) {
+            error.set_warning();
+        }
+        error
     }
 
     /// Report an unsupported feature of the verified Rust code (e.g. dereferencing raw pointers)
     pub fn unsupported<S: ToString>(message: S, span: MultiSpan) -> Self {
         check_message(message.to_string());
         let mut error = PrustiError::new(
+            "unsupported",
             format!("[Prusti: unsupported feature] {}", message.to_string()),
             span
         );
-        if config::skip_unsupported_features() {
+        if !fails_on("unsupported", !config::skip_unsupported_features()) {
             error.set_warning();
         }
         error
@@ -65,6 +104,7 @@ impl PrustiError {
     pub fn incorrect<S: ToString>(message: S, span: MultiSpan) -> Self {
         check_message(message.to_string());
         PrustiError::new(
+            "invalid-specification",
             format!("[Prusti: invalid specification] {}", message.to_string()),
             span
         )
@@ -74,11 +114,24 @@ impl PrustiError {
     pub fn internal<S: ToString>(message: S, span: MultiSpan) -> Self {
         check_message(message.to_string());
         PrustiError::new(
+            "internal",
             format!("[Prusti internal error] {}", message.to_string()),
             span
         )
     }
 
+    /// Report a problem that does not make the verification fail (e.g. a skipped procedure)
+    pub fn warning<S: ToString>(message: S, span: MultiSpan) -> Self {
+        check_message(message.to_string());
+        let mut warning = PrustiError::new(
+            "warning",
+            format!("[Prusti: warning] {}", message.to_string()),
+            span
+        );
+        warning.set_warning();
+        warning
+    }
+
     /// Set that this Prusti error should be reported as a warning to the user
     pub fn set_warning(&mut self) {
         self.is_error = false;
@@ -88,28 +141,100 @@ impl PrustiError {
         self.is_error
     }
 
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn span(&self) -> &MultiSpan {
+        &self.span
+    }
+
     pub fn set_help<S: ToString>(mut self, message: S) -> Self {
         self.help = Some(message.to_string());
         self
     }
 
-    /// Report the encoding error using the compiler's interface
+    /// Suggest to replace the code at `span` with `replacement`, e.g. to
+    /// let editors apply the fix.
+    pub fn set_suggestion<S: ToString, R: ToString>(
+        mut self,
+        message: S,
+        span: Span,
+        replacement: R,
+        applicability: Applicability,
+    ) -> Self {
+        self.suggestion = Some((message.to_string(), span, replacement.to_string(), applicability));
+        self
+    }
+
+    /// Set the procedure in which the problem was found.
+    pub fn set_method<S: ToString>(mut self, method: S) -> Self {
+        self.method = Some(method.to_string());
+        self
+    }
+
+    /// Set the error context of the encoding that failed and, if the failure
+    /// is that of a specification, its kind.
+    pub fn set_error_ctxt<S: ToString>(mut self, error_ctxt: S, spec_kind: Option<&str>) -> Self {
+        self.error_ctxt = Some(error_ctxt.to_string());
+        self.spec_kind = spec_kind.map(|spec_kind| spec_kind.to_string());
+        self
+    }
+
+    /// Set the values of the variables for which the verification fails,
+    /// e.g. `x = 255, y = 1`.
+    pub fn set_counterexample<S: ToString>(mut self, counterexample: S) -> Self {
+        self.counterexample = Some(counterexample.to_string());
+        self
+    }
+
+    /// Report the encoding error using the compiler's interface.
+    ///
+    /// With `MESSAGE_FORMAT=json`, the diagnostics are emitted as the JSON
+    /// diagnostics of rustc. The `code.code` of the diagnostic is
+    /// `prusti::<kind>`, and one of its `children` is a note whose `message`
+    /// is `prusti: ` followed by a JSON object with the fields:
+    /// * `kind`: the kind of problem, e.g. `verification`;
+    /// * `method`: the path of the procedure in which the problem was found,
+    ///   or `null`;
+    /// * `spec_kind`: the kind of specification that failed, e.g.
+    ///   `postcondition`, or `null`;
+    /// * `error_ctxt`: the error context of the encoding that failed, e.g.
+    ///   `ExhaleMethodPostcondition`, or `null`.
     pub fn emit(self, env: &Environment) {
-        if self.is_error {
-            env.span_err_with_help_and_note(
+        let counterexample = self.counterexample
+            .map(|counterexample| format!("counterexample: {}", counterexample));
+        let mut diagnostic = if self.is_error {
+            env.struct_span_err_with_help_and_note(
                 self.span,
                 &self.message,
                 &self.help,
                 &self.note,
-            );
+                &counterexample,
+            )
         } else {
-            env.span_warn_with_help_and_note(
+            env.struct_span_warn_with_help_and_note(
                 self.span,
                 &self.message,
                 &self.help,
                 &self.note,
-            );
+                &counterexample,
+            )
+        };
+        if let Some((message, span, replacement, applicability)) = self.suggestion {
+            diagnostic.span_suggestion(span, &message, replacement, applicability);
+        }
+        if config::json_messages() {
+            let details = serde_json::json!({
+                "kind": self.kind,
+                "method": self.method,
+                "spec_kind": self.spec_kind,
+                "error_ctxt": self.error_ctxt,
+            });
+            diagnostic.code(DiagnosticId::Error(format!("prusti::{}", self.kind)));
+            diagnostic.note(&format!("prusti: {}", details));
         }
+        diagnostic.emit();
     }
 
     /// Set the span of the failing assertion expression.
@@ -134,6 +259,12 @@ impl PrustiError {
     }
 }
 
+/// Whether problems of the given kind should be reported as errors, according
+/// to the `FAIL_ON` configuration flag.
+fn fails_on(kind: &str, default: bool) -> bool {
+    config::fail_on().map_or(default, |kinds| kinds.iter().any(|k| k == kind))
+}
+
 fn check_message(message: String) {
     debug_assert!(
         message.len() >= 3,
@@ -2,16 +2,18 @@ use prusti_specs::specifications::{json::Assertion as JsonAssertion, SpecType};
 use rustc_ast::ast;
 use rustc_hir::{intravisit, ItemKind};
 use rustc_middle::hir::map::Map;
-use rustc_middle::ty::TyCtxt;
+use rustc_middle::ty::{self, TyCtxt};
 use rustc_span::{Span, MultiSpan};
-use rustc_span::symbol::Symbol;
+use rustc_span::symbol::{sym, Symbol};
+use rustc_hir::def::DefKind;
 use rustc_hir::def_id::{DefId, LocalDefId};
 use std::collections::HashMap;
 use std::convert::TryInto;
 use crate::environment::Environment;
 use crate::PrustiError;
 use crate::utils::{
-    has_spec_only_attr, has_extern_spec_attr, read_prusti_attr, read_prusti_attrs, has_prusti_attr
+    has_spec_only_attr, has_extern_spec_attr, read_prusti_attr, read_prusti_attrs, has_prusti_attr,
+    is_skipped_item,
 };
 use log::debug;
 
@@ -63,6 +65,13 @@ pub struct SpecCollector<'tcx> {
     /// Resolved specifications.
     procedure_specs: HashMap<LocalDefId, ProcedureSpecRef>,
     loop_specs: HashMap<LocalDefId, Vec<SpecificationId>>,
+    loop_variants: HashMap<LocalDefId, SpecificationId>,
+
+    /// The `unsafe impl`s of `Send` and `Sync`.
+    send_sync_impls: Vec<LocalDefId>,
+    /// The types with a `#[thread_safety_invariant]` on one of their impl
+    /// blocks, and the fields that their invariants constrain.
+    thread_safe_types: HashMap<DefId, Vec<String>>,
 }
 
 impl<'tcx> SpecCollector<'tcx> {
@@ -73,8 +82,11 @@ impl<'tcx> SpecCollector<'tcx> {
             typed_specs: HashMap::new(),
             procedure_specs: HashMap::new(),
             loop_specs: HashMap::new(),
+            loop_variants: HashMap::new(),
             typed_expressions: HashMap::new(),
             extern_resolver: ExternSpecResolver::new(tcx),
+            send_sync_impls: Vec::new(),
+            thread_safe_types: HashMap::new(),
         }
     }
 
@@ -101,9 +113,97 @@ impl<'tcx> SpecCollector<'tcx> {
         self.determine_extern_specs(&mut def_spec, env);
         self.determine_loop_specs(&mut def_spec);
         self.determine_struct_specs(&mut def_spec);
+        if prusti_common::config::check_send_sync_impls() {
+            self.check_send_sync_impls(env);
+        }
         def_spec
     }
 
+    /// Report the `unsafe impl`s of `Send` and `Sync` whose type does not
+    /// declare a `#[thread_safety_invariant]`, or whose invariant does not
+    /// constrain every field through which the value can be mutated while it
+    /// is shared, i.e. the fields with interior mutability or raw pointers.
+    fn check_send_sync_impls(&self, env: &Environment<'tcx>) {
+        for &impl_id in &self.send_sync_impls {
+            let impl_def_id = impl_id.to_def_id();
+            let span = env.get_item_span(impl_def_id);
+            let self_ty = self.tcx.type_of(impl_def_id);
+            let trait_ref = self.tcx.impl_trait_ref(impl_def_id).unwrap();
+            let justification = match self_ty.kind() {
+                ty::TyKind::Adt(adt_def, substs) => self.thread_safe_types
+                    .get(&adt_def.did)
+                    .map(|constrained_fields| (adt_def, substs, constrained_fields)),
+                _ => None,
+            };
+            let (adt_def, substs, constrained_fields) = match justification {
+                Some(justification) => justification,
+                None => {
+                    self.report_unjustified_send_sync_impl(env, trait_ref.def_id, self_ty, span);
+                    continue;
+                }
+            };
+            if constrained_fields.is_empty() {
+                PrustiError::incorrect(
+                    format!(
+                        "the thread-safety invariant of `{}` does not constrain any of its fields",
+                        self_ty,
+                    ),
+                    MultiSpan::from_span(span),
+                ).set_help(
+                    "the invariant has to constrain the fields of the type through `self`"
+                ).emit(env);
+                continue;
+            }
+            let param_env = self.tcx.param_env(impl_def_id);
+            for field in adt_def.all_fields() {
+                let field_name = field.ident.to_string();
+                let field_ty = field.ty(self.tcx, substs);
+                let allows_shared_mutation = !field_ty.is_freeze(self.tcx.at(span), param_env)
+                    || field_ty.walk().any(|arg| match arg.unpack() {
+                        ty::subst::GenericArgKind::Type(ty) => ty.is_unsafe_ptr(),
+                        _ => false,
+                    });
+                if allows_shared_mutation && !constrained_fields.contains(&field_name) {
+                    PrustiError::incorrect(
+                        format!(
+                            "the implementation of `{}` for `{}` is not justified, because the \
+                            thread-safety invariant does not constrain the field `{}`",
+                            self.tcx.def_path_str(trait_ref.def_id),
+                            self_ty,
+                            field_name,
+                        ),
+                        MultiSpan::from_span(span),
+                    ).set_help(format!(
+                        "the type `{}` of the field allows mutation through shared references; \
+                        state in the invariant how the accesses to `self.{}` are synchronized",
+                        field_ty,
+                        field_name,
+                    )).emit(env);
+                }
+            }
+        }
+    }
+
+    fn report_unjustified_send_sync_impl(
+        &self,
+        env: &Environment<'tcx>,
+        trait_def_id: DefId,
+        self_ty: ty::Ty<'tcx>,
+        span: Span,
+    ) {
+        PrustiError::incorrect(
+            format!(
+                "the implementation of `{}` for `{}` is not justified by a \
+                thread-safety invariant",
+                self.tcx.def_path_str(trait_def_id),
+                self_ty,
+            ),
+            MultiSpan::from_span(span),
+        ).set_help(
+            "add a #[thread_safety_invariant(...)] to an inherent impl block of the type"
+        ).emit(env);
+    }
+
     fn determine_extern_specs(&self, def_spec: &mut typed::DefSpecificationMap<'tcx>, env: &Environment<'tcx>) {
         self.extern_resolver.check_duplicates(env);
         // TODO: do something with the traits
@@ -128,6 +228,9 @@ impl<'tcx> SpecCollector<'tcx> {
             let mut pres = Vec::new();
             let mut posts = Vec::new();
             let mut pledges = Vec::new();
+            let mut predicate_body = None;
+            let mut complexity = None;
+            let mut decreases = None;
             for spec_id_ref in &refs.spec_id_refs {
                 match spec_id_ref {
                     SpecIdRef::Precondition(spec_id) => {
@@ -143,6 +246,15 @@ impl<'tcx> SpecCollector<'tcx> {
                             rhs: self.typed_specs.get(&rhs).unwrap().clone(),
                         })
                     }
+                    SpecIdRef::Predicate(spec_id) => {
+                        predicate_body = Some(self.typed_specs.get(&spec_id).unwrap().clone());
+                    }
+                    SpecIdRef::Complexity(spec_id) => {
+                        complexity = Some(self.typed_specs.get(&spec_id).unwrap().clone());
+                    }
+                    SpecIdRef::Decreases(spec_id) => {
+                        decreases = Some(self.typed_specs.get(&spec_id).unwrap().clone());
+                    }
                 }
             }
             def_spec.specs.insert(
@@ -151,6 +263,9 @@ impl<'tcx> SpecCollector<'tcx> {
                     pres,
                     posts,
                     pledges,
+                    predicate_body,
+                    complexity,
+                    decreases,
                     pure: refs.pure,
                     trusted: refs.trusted,
                 })
@@ -164,7 +279,14 @@ impl<'tcx> SpecCollector<'tcx> {
                 .map(|spec_id| self.typed_specs.get(&spec_id).unwrap().clone())
                 .collect();
             def_spec.specs.insert(*local_id, typed::SpecificationSet::Loop(typed::LoopSpecification {
-                invariant: specs
+                invariant: specs,
+                variant: None,
+            }));
+        }
+        for (local_id, spec_id) in self.loop_variants.iter() {
+            def_spec.specs.insert(*local_id, typed::SpecificationSet::Loop(typed::LoopSpecification {
+                invariant: vec![],
+                variant: Some(self.typed_specs.get(&spec_id).unwrap().clone()),
             }));
         }
     }
@@ -208,6 +330,21 @@ fn get_procedure_spec_ids(def_id: DefId, attrs: &[ast::Attribute]) -> Option<Pro
             }
         )
     );
+    spec_id_refs.extend(
+        read_prusti_attrs("pred_spec_id_ref", attrs).into_iter().map(
+            |raw_spec_id| SpecIdRef::Predicate(parse_spec_id(raw_spec_id))
+        )
+    );
+    spec_id_refs.extend(
+        read_prusti_attrs("complexity_spec_id_ref", attrs).into_iter().map(
+            |raw_spec_id| SpecIdRef::Complexity(parse_spec_id(raw_spec_id))
+        )
+    );
+    spec_id_refs.extend(
+        read_prusti_attrs("decreases_spec_id_ref", attrs).into_iter().map(
+            |raw_spec_id| SpecIdRef::Decreases(parse_spec_id(raw_spec_id))
+        )
+    );
     debug!("Function {:?} has specification ids {:?}", def_id, spec_id_refs);
 
     let pure = has_prusti_attr(attrs, "pure");
@@ -246,6 +383,35 @@ impl<'tcx> intravisit::Visitor<'tcx> for SpecCollector<'tcx> {
         intravisit::NestedVisitorMap::All(map)
     }
 
+    fn visit_item(&mut self, item: &'tcx rustc_hir::Item) {
+        intravisit::walk_item(self, item);
+
+        let local_id = self.tcx.hir().local_def_id(item.hir_id);
+        let def_id = local_id.to_def_id();
+        if self.tcx.def_kind(def_id) != DefKind::Impl {
+            return;
+        }
+        if let Some(trait_ref) = self.tcx.impl_trait_ref(def_id) {
+            let is_send_or_sync = Some(trait_ref.def_id) == self.tcx.lang_items().sync_trait()
+                || self.tcx.is_diagnostic_item(sym::send_trait, trait_ref.def_id);
+            if is_send_or_sync
+                && self.tcx.impl_polarity(def_id) == ty::ImplPolarity::Positive {
+                self.send_sync_impls.push(local_id);
+            }
+        } else if has_prusti_attr(item.attrs, "thread_safety_invariant") {
+            if let ty::TyKind::Adt(adt_def, _) = self.tcx.type_of(def_id).kind() {
+                let constrained_fields = read_prusti_attr("thread_safety_invariant_fields", item.attrs)
+                    .unwrap_or_default();
+                self.thread_safe_types.entry(adt_def.did).or_default().extend(
+                    constrained_fields
+                        .split(',')
+                        .filter(|field| !field.is_empty())
+                        .map(|field| field.to_string())
+                );
+            }
+        }
+    }
+
     fn visit_trait_item(
         &mut self,
         ti: &'tcx rustc_hir::TraitItem,
@@ -257,9 +423,13 @@ impl<'tcx> intravisit::Visitor<'tcx> for SpecCollector<'tcx> {
         let def_id = local_id.to_def_id();
         let attrs = ti.attrs;
 
-        // Collect procedure specifications
+        // Collect procedure specifications, ignoring the ones of skipped
+        // items so that their calls are encoded like calls to unspecified
+        // functions.
         if let Some(procedure_spec_ref) = get_procedure_spec_ids(def_id, attrs) {
-            self.procedure_specs.insert(local_id, procedure_spec_ref);
+            if !is_skipped_item(self.tcx, def_id) {
+                self.procedure_specs.insert(local_id, procedure_spec_ref);
+            }
         }
     }
 
@@ -282,9 +452,13 @@ impl<'tcx> intravisit::Visitor<'tcx> for SpecCollector<'tcx> {
             self.extern_resolver.add_extern_fn(fn_kind, fn_decl, body_id, span, id);
         }
 
-        // Collect procedure specifications
+        // Collect procedure specifications, ignoring the ones of skipped
+        // items so that their calls are encoded like calls to unspecified
+        // functions.
         if let Some(procedure_spec_ref) = get_procedure_spec_ids(def_id, attrs) {
-            self.procedure_specs.insert(local_id, procedure_spec_ref);
+            if !is_skipped_item(self.tcx, def_id) {
+                self.procedure_specs.insert(local_id, procedure_spec_ref);
+            }
         }
 
         // Collect a typed expression
@@ -306,13 +480,15 @@ impl<'tcx> intravisit::Visitor<'tcx> for SpecCollector<'tcx> {
             // for postconditions and invariants.
             let spec_type = if has_prusti_attr(attrs, "loop_body_invariant_spec") {
                 SpecType::Invariant
+            } else if has_prusti_attr(attrs, "loop_body_variant_spec") {
+                SpecType::Variant
             } else {
                 let fn_name = match fn_kind {
                     intravisit::FnKind::ItemFn(ref ident, ..) |
                     intravisit::FnKind::Method(ref ident, ..) => ident.name.to_ident_string(),
                     intravisit::FnKind::Closure(..) => unreachable!(
                         "a closure is annotated with prusti::spec_id but not with \
-                        prusti::loop_body_invariant_spec"
+                        prusti::loop_body_invariant_spec or prusti::loop_body_variant_spec"
                     ),
                 };
                 if fn_name.starts_with("prusti_pre_item_")
@@ -321,6 +497,12 @@ impl<'tcx> intravisit::Visitor<'tcx> for SpecCollector<'tcx> {
                 } else if fn_name.starts_with("prusti_post_item_")
                     || fn_name.starts_with("prusti_post_closure_") {
                     SpecType::Postcondition
+                } else if fn_name.starts_with("prusti_pred_item_") {
+                    SpecType::Predicate
+                } else if fn_name.starts_with("prusti_complexity_item_") {
+                    SpecType::Complexity
+                } else if fn_name.starts_with("prusti_decreases_item_") {
+                    SpecType::Decreases
                 } else {
                     unreachable!()
                 }
@@ -336,6 +518,10 @@ impl<'tcx> intravisit::Visitor<'tcx> for SpecCollector<'tcx> {
                     .or_insert(vec![])
                     .push(spec_id);
             }
+            // Collect loop variant
+            if spec_type == SpecType::Variant {
+                self.loop_variants.insert(local_id, spec_id);
+            }
         }
     }
 
@@ -9,8 +9,10 @@
 use rustc_middle::mir;
 use rustc_middle::ty::{self, TyCtxt};
 use rustc_index::vec::Idx;
+use rustc_hir::def_id::DefId;
 use std::collections::HashSet;
 use rustc_ast::ast;
+use rustc_span::symbol::sym;
 use log::trace;
 
 /// Check if the place `potential_prefix` is a prefix of `place`. For example:
@@ -281,11 +283,32 @@ pub fn has_prusti_attr(attrs: &[ast::Attribute], name: &str) -> bool {
     })
 }
 
+/// Check if the item, or one of the items (e.g. impl blocks or modules) that
+/// contain it, is annotated with `prusti::skip`.
+pub fn is_skipped_item(tcx: TyCtxt, def_id: DefId) -> bool {
+    let mut current = Some(def_id);
+    while let Some(def_id) = current {
+        if has_prusti_attr(tcx.get_attrs(def_id), "skip") {
+            return true;
+        }
+        current = tcx.parent(def_id);
+    }
+    false
+}
+
 /// Check if `prusti::spec_only` is among the attributes.
 pub fn has_spec_only_attr(attrs: &[ast::Attribute]) -> bool {
     has_prusti_attr(attrs, "spec_only")
 }
 
+/// Check if the item is generated by the test harness of `rustc --test`.
+/// The generated `main` function is marked with `#[main]` and the descriptors
+/// of the tests with `#[rustc_test_marker]`.
+pub fn is_test_harness_item(tcx: TyCtxt, attrs: &[ast::Attribute]) -> bool {
+    tcx.sess.contains_name(attrs, sym::main)
+        || tcx.sess.contains_name(attrs, sym::rustc_test_marker)
+}
+
 /// Check if `prusti::extern_spec` is among the attributes.
 pub fn has_extern_spec_attr(attrs: &[ast::Attribute]) -> bool {
     has_prusti_attr(attrs, "extern_spec")
@@ -337,3 +360,61 @@ pub fn read_prusti_attrs(attr_name: &str, attrs: &[ast::Attribute]) -> Vec<Strin
 pub fn read_prusti_attr(attr_name: &str, attrs: &[ast::Attribute]) -> Option<String> {
     read_prusti_attrs(attr_name, attrs).pop()
 }
+
+/// Read the paths listed in Prusti attributes of the form
+/// `prusti::<attr_name>(path, ...)`.
+pub fn read_prusti_path_list_attrs(attr_name: &str, attrs: &[ast::Attribute]) -> Vec<String> {
+    use rustc_ast::token::{BinOpToken, Token, TokenKind};
+    use rustc_ast::tokenstream::TokenTree;
+    let mut paths = vec![];
+    for attr in attrs {
+        if let ast::AttrKind::Normal(ast::AttrItem {
+                                         path: ast::Path { span: _, segments, tokens: _ },
+                                         args: ast::MacArgs::Delimited(_, _, tokens),
+                                         tokens: _,
+                                     }, _) = &attr.kind {
+            if !(
+                segments.len() == 2
+                    && segments[0].ident.as_str() == "prusti"
+                    && segments[1].ident.as_str() == attr_name
+            ) {
+                continue;
+            }
+            let mut path = String::new();
+            for tree in tokens.trees() {
+                match tree {
+                    TokenTree::Token(Token { kind: TokenKind::Comma, .. }) => {
+                        paths.push(std::mem::take(&mut path));
+                    }
+                    TokenTree::Token(Token { kind: TokenKind::Ident(name, _), .. }) => {
+                        path.push_str(&name.as_str());
+                    }
+                    TokenTree::Token(Token { kind: TokenKind::ModSep, .. }) => {
+                        path.push_str("::");
+                    }
+                    TokenTree::Token(Token { kind: TokenKind::BinOp(BinOpToken::Star), .. }) => {
+                        path.push('*');
+                    }
+                    x => trace!("Ignoring token {:?} in prusti::{}", x, attr_name),
+                }
+            }
+            if !path.is_empty() {
+                paths.push(path);
+            }
+        }
+    }
+    paths
+}
+
+/// Check if `text` matches `pattern`, in which a `*` matches any sequence of
+/// characters.
+pub fn matches_glob(pattern: &str, text: &str) -> bool {
+    match pattern.find('*') {
+        None => pattern == text,
+        Some(index) => {
+            let (prefix, rest) = (&pattern[..index], &pattern[index + 1..]);
+            text.starts_with(prefix) && (prefix.len()..=text.len())
+                .any(|start| text.is_char_boundary(start) && matches_glob(rest, &text[start..]))
+        }
+    }
+}
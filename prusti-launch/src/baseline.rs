@@ -0,0 +1,85 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A baseline file lists the verification failures that are accepted, which
+//! Prusti then reports as warnings. The file is read by `prusti-viper` (see
+//! `prusti-viper/src/baseline.rs`) when `PRUSTI_BASELINE` is set.
+
+use std::{fs, io, path::Path};
+use serde::{Deserialize, Serialize};
+use crate::summary::RunSummary;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BaselineFailure {
+    pub crate_name: String,
+    pub item: String,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Baseline {
+    pub failures: Vec<BaselineFailure>,
+}
+
+impl Baseline {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    /// The baseline that accepts all the failures of the run.
+    pub fn from_summary(summary: &RunSummary) -> Self {
+        Baseline {
+            failures: failures(summary),
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+        fs::write(path, content)
+    }
+
+    /// Print how the failures of the run compare to the baseline.
+    pub fn print_comparison(&self, summary: &RunSummary) {
+        let failures = failures(summary);
+        let known = failures.iter()
+            .filter(|failure| self.failures.contains(failure))
+            .count();
+        let new = failures.len() - known;
+        let crates: Vec<_> = summary.crates.iter()
+            .map(|krate| &krate.crate_name)
+            .collect();
+        // Failures of crates that are not part of the run are not fixed.
+        let fixed: Vec<_> = self.failures.iter()
+            .filter(|failure| crates.contains(&&failure.crate_name))
+            .filter(|failure| !failures.contains(failure))
+            .collect();
+        eprintln!(
+            "baseline: {} known failures; {} new failures; {} fixed failures",
+            known, new, fixed.len(),
+        );
+        for failure in fixed {
+            eprintln!(
+                "    {} ({}) now verifies and can be removed from the baseline",
+                failure.item, failure.crate_name,
+            );
+        }
+    }
+}
+
+fn failures(summary: &RunSummary) -> Vec<BaselineFailure> {
+    summary.crates.iter()
+        .flat_map(|krate| {
+            krate.items.iter()
+                .filter(|item| item.status == "failed")
+                .map(move |item| BaselineFailure {
+                    crate_name: krate.crate_name.clone(),
+                    item: item.name.clone(),
+                })
+        })
+        .collect()
+}
@@ -0,0 +1,238 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Benchmarking of Prusti on a corpus of files and crates. Each run records
+//! the wall time of each entry of the corpus and the encoding time of each
+//! item, as reported in the summaries written by `prusti-driver` (see
+//! `summary.rs`), and is appended to a JSON history with which later runs are
+//! compared.
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+use serde::{Deserialize, Serialize};
+use crate::summary::CrateSummary;
+
+/// Timings below this number of seconds are too noisy to be compared.
+const MIN_COMPARED_SECS: f64 = 0.1;
+
+/// An entry of the corpus: a single file verified with `prusti-rustc`, or a
+/// crate verified with `cargo-prusti`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CorpusEntry {
+    File(PathBuf),
+    Crate(PathBuf),
+}
+
+impl CorpusEntry {
+    pub fn new(path: PathBuf) -> Self {
+        if path.join("Cargo.toml").is_file() {
+            CorpusEntry::Crate(path)
+        } else {
+            CorpusEntry::File(path)
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            CorpusEntry::File(path) | CorpusEntry::Crate(path) => path,
+        }
+    }
+}
+
+/// Read the corpus listed in `path`, with one file or crate directory per
+/// line, as the first column of a CSV file such as `benchmarks.csv`.
+pub fn read_corpus(path: &Path) -> io::Result<Vec<CorpusEntry>> {
+    let content = fs::read_to_string(path)?;
+    Ok(content.lines()
+        .map(|line| line.split(',').next().unwrap().trim())
+        .filter(|entry| !entry.is_empty() && !entry.starts_with('#'))
+        .map(|entry| CorpusEntry::new(PathBuf::from(entry)))
+        .collect())
+}
+
+/// The files of the test suite that are expected to verify.
+pub fn test_suite_corpus(tests_dir: &Path) -> io::Result<Vec<CorpusEntry>> {
+    let mut files = vec![];
+    for entry in walkdir::WalkDir::new(tests_dir.join("verify").join("pass")) {
+        let entry = entry.map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+        if entry.path().extension().map_or(false, |ext| ext == "rs") {
+            files.push(entry.path().to_path_buf());
+        }
+    }
+    files.sort();
+    Ok(files.into_iter().map(CorpusEntry::File).collect())
+}
+
+/// The executables with which the corpus is verified.
+#[derive(Debug, Clone)]
+pub struct BenchRunner {
+    pub prusti_rustc: PathBuf,
+    pub cargo_prusti: PathBuf,
+    /// The directory in which the summaries of the items are written.
+    pub work_dir: PathBuf,
+}
+
+impl BenchRunner {
+    /// Verify `entry` `iterations` times and record the median of each timing.
+    pub fn measure(&self, entry: &CorpusEntry, iterations: usize) -> io::Result<Timings> {
+        let mut samples: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+        for _ in 0..iterations {
+            for (name, secs) in self.measure_once(entry)? {
+                samples.entry(name).or_default().push(secs);
+            }
+        }
+        Ok(samples.into_iter().map(|(name, secs)| (name, median(secs))).collect())
+    }
+
+    fn measure_once(&self, entry: &CorpusEntry) -> io::Result<Timings> {
+        let summary_dir = self.work_dir.join("summaries");
+        if summary_dir.exists() {
+            fs::remove_dir_all(&summary_dir)?;
+        }
+        let mut cmd = match entry {
+            CorpusEntry::File(path) => {
+                let mut cmd = Command::new(&self.prusti_rustc);
+                cmd.arg("--edition=2018")
+                    .arg(path)
+                    .arg("--out-dir")
+                    .arg(&self.work_dir);
+                cmd
+            }
+            CorpusEntry::Crate(path) => {
+                let mut cmd = Command::new(&self.cargo_prusti);
+                cmd.current_dir(path)
+                    .env("CARGO_TARGET_DIR", self.work_dir.join("target"));
+                cmd
+            }
+        };
+        cmd.env("PRUSTI_SUMMARY_DIR", &summary_dir)
+            .env("PRUSTI_QUIET", "true");
+        let stopwatch = Instant::now();
+        let status = cmd.status()?;
+        let duration = stopwatch.elapsed();
+        if !status.success() {
+            eprintln!("warning: verification of {:?} failed", entry.path());
+        }
+
+        let entry_name = entry.path().to_string_lossy().to_string();
+        let mut timings = Timings::new();
+        timings.insert(entry_name.clone(), duration.as_secs_f64());
+        if summary_dir.exists() {
+            for summary_entry in fs::read_dir(&summary_dir)? {
+                let content = fs::read_to_string(summary_entry?.path())?;
+                let summary: CrateSummary = serde_json::from_str(&content)
+                    .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+                for item in summary.items {
+                    timings.insert(
+                        format!("{}: {}", entry_name, item.name),
+                        item.encoding_secs,
+                    );
+                }
+            }
+        }
+        Ok(timings)
+    }
+}
+
+fn median(mut samples: Vec<f64>) -> f64 {
+    samples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    samples[samples.len() / 2]
+}
+
+/// The timings in seconds of a run, keyed by the corpus entry, or by the
+/// corpus entry and the name of the item for the encoding time of an item.
+pub type Timings = BTreeMap<String, f64>;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BenchRun {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The git revision of Prusti, if known.
+    pub revision: Option<String>,
+    pub timings: Timings,
+}
+
+impl BenchRun {
+    pub fn new(revision: Option<String>, timings: Timings) -> Self {
+        BenchRun {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs()),
+            revision,
+            timings,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct BenchHistory {
+    pub runs: Vec<BenchRun>,
+}
+
+impl BenchHistory {
+    /// Load the history at `path`, which is empty if the file does not exist.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        if !path.exists() {
+            return Ok(BenchHistory::default());
+        }
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+        fs::write(path, content)
+    }
+
+    /// The timings that got slower by more than `threshold` (e.g. `0.1` for
+    /// 10%) with respect to the last recorded run.
+    pub fn regressions(&self, timings: &Timings, threshold: f64) -> Vec<Regression> {
+        let previous = match self.runs.last() {
+            Some(run) => &run.timings,
+            None => return vec![],
+        };
+        timings.iter()
+            .filter_map(|(name, &new_secs)| {
+                let old_secs = *previous.get(name)?;
+                if new_secs.max(old_secs) < MIN_COMPARED_SECS {
+                    return None;
+                }
+                if new_secs > old_secs * (1.0 + threshold) {
+                    Some(Regression {
+                        name: name.clone(),
+                        old_secs,
+                        new_secs,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    pub name: String,
+    pub old_secs: f64,
+    pub new_secs: f64,
+}
+
+impl Regression {
+    /// The relative slowdown, e.g. `0.25` for 25%.
+    pub fn slowdown(&self) -> f64 {
+        self.new_secs / self.old_secs - 1.0
+    }
+}
@@ -4,8 +4,13 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::process::Command;
-use prusti_launch::get_rust_toolchain_channel;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+    time::Instant,
+};
+use prusti_launch::{baseline::Baseline, daemon, get_rust_toolchain_channel, summary::RunSummary};
 
 fn main(){
     if let Err(code) = process(std::env::args().skip(1)) {
@@ -26,19 +31,81 @@ fn process<I>(args: I) -> Result<(), i32>
 
     // Remove the leading "prusti" argument when `cargo-prusti` is invocated
     // as `cargo prusti` (note the space)
-    let clean_args = args.skip_while(|x| x == "prusti");
+    let mut clean_args = args.skip_while(|x| x == "prusti").peekable();
+    if clean_args.peek().map(|arg| arg == "daemon").unwrap_or(false) {
+        clean_args.next();
+        return process_daemon_command(clean_args.next());
+    }
+    let (cargo_args, prusti_args) = extract_prusti_args(clean_args);
 
     let cargo_path = std::env::var("CARGO_PATH").unwrap_or("cargo".to_string());
 
-    let exit_status = Command::new(cargo_path)
-        .arg("check")
-        .args(clean_args)
+    // The summaries of previous runs are removed, so that only the crates
+    // verified by this run are reported. Crates for which Cargo reuses the
+    // result of a previous run are not verified again, and not reported.
+    let target_dir = env::var("CARGO_TARGET_DIR").unwrap_or("target".to_string());
+    let summary_dir = env::current_dir()
+        .expect("current directory invalid")
+        .join(target_dir)
+        .join("prusti-summary");
+    if summary_dir.exists() {
+        if let Err(error) = fs::remove_dir_all(&summary_dir) {
+            eprintln!("could not remove the previous verification summaries: {}", error);
+        }
+    }
+
+    let baseline_path = prusti_args.baseline.map(|path| {
+        env::current_dir().expect("current directory invalid").join(path)
+    });
+
+    let stopwatch = Instant::now();
+    let mut cmd = Command::new(cargo_path);
+    cmd.arg("check")
+        .args(cargo_args)
         .env("RUST_TOOLCHAIN", get_rust_toolchain_channel())
         .env("PRUSTI_QUIET", "true")
         .env("PRUSTI_FULL_COMPILATION", "true")
-        .env("RUSTC_WRAPPER", prusti_rustc_path)
-        .status()
-        .expect("could not run cargo");
+        .env("PRUSTI_SUMMARY_DIR", &summary_dir)
+        .env("RUSTC_WRAPPER", prusti_rustc_path);
+    if let Some(ref path) = baseline_path {
+        cmd.env("PRUSTI_BASELINE", path);
+    }
+    if let Some(ref check) = prusti_args.check {
+        cmd.env("PRUSTI_CHECK", check);
+    }
+    if let Some(ref jobs) = prusti_args.jobs {
+        cmd.env("PRUSTI_JOBS", jobs);
+    }
+    if env::var_os("PRUSTI_SERVER_ADDRESS").is_none() {
+        if let Some(info) = daemon::running() {
+            cmd.env("PRUSTI_SERVER_ADDRESS", &info.address);
+        }
+    }
+    let exit_status = cmd.status().expect("could not run cargo");
+
+    let summary = if summary_dir.exists() {
+        Some(RunSummary::collect(&summary_dir, stopwatch.elapsed()))
+    } else {
+        None
+    };
+    match summary {
+        None => {}
+        Some(Ok(summary)) => {
+            summary.print();
+            if prusti_args.cache_stats {
+                summary.print_cache_stats();
+            }
+            if let Some(path) = prusti_args.summary_json {
+                summary.write_json(&path).unwrap_or_else(|error| {
+                    eprintln!("could not write the verification summary to {:?}: {}", path, error)
+                });
+            }
+            if let Some(path) = baseline_path {
+                report_baseline(&path, &summary);
+            }
+        }
+        Some(Err(error)) => eprintln!("could not collect the verification summary: {}", error),
+    }
 
     if exit_status.success() {
         Ok(())
@@ -46,3 +113,112 @@ fn process<I>(args: I) -> Result<(), i32>
         Err(exit_status.code().unwrap_or(-1))
     }
 }
+
+/// Handle `cargo prusti daemon <command>`, see the `daemon` module.
+fn process_daemon_command(command: Option<String>) -> Result<(), i32> {
+    let result = match command.as_deref() {
+        Some("start") => {
+            let mut prusti_server_path = env::current_exe()
+                .expect("current executable path invalid")
+                .with_file_name("prusti-server");
+            if cfg!(windows) {
+                prusti_server_path.set_extension("exe");
+            }
+            daemon::start(&prusti_server_path)
+                .map(|info| eprintln!("started the Prusti daemon at {}", info.address))
+        }
+        Some("stop") => daemon::stop()
+            .map(|info| eprintln!("stopped the Prusti daemon at {}", info.address)),
+        Some("status") => {
+            match daemon::running() {
+                Some(info) => eprintln!("the Prusti daemon is running at {}", info.address),
+                None => eprintln!("no Prusti daemon is running"),
+            }
+            Ok(())
+        }
+        _ => Err("usage: cargo prusti daemon <start|stop|status>".to_string()),
+    };
+    result.map_err(|error| {
+        eprintln!("error: {}", error);
+        1
+    })
+}
+
+/// Report how the run compares to the baseline. If the baseline file does
+/// not exist yet, it is created with the failures of the run.
+fn report_baseline(path: &Path, summary: &RunSummary) {
+    if path.exists() {
+        match Baseline::load(path) {
+            Ok(baseline) => baseline.print_comparison(summary),
+            Err(error) => eprintln!("could not read the baseline {:?}: {}", path, error),
+        }
+    } else {
+        let baseline = Baseline::from_summary(summary);
+        match baseline.write(path) {
+            Ok(()) => eprintln!(
+                "created the baseline {:?} with {} accepted failures",
+                path, baseline.failures.len(),
+            ),
+            Err(error) => eprintln!("could not write the baseline {:?}: {}", path, error),
+        }
+    }
+}
+
+/// The arguments of `cargo-prusti` that are not understood by Cargo.
+#[derive(Default)]
+struct PrustiArgs {
+    /// `--summary-json <path>`: write the summary of the run to a JSON file.
+    summary_json: Option<PathBuf>,
+    /// `--baseline <path>`: report the failures listed in the file as warnings.
+    baseline: Option<PathBuf>,
+    /// `--check <obligations>`: the obligations to check, e.g. `panics`.
+    check: Option<String>,
+    /// `--jobs <n>`: the number of methods of a crate that are verified
+    /// concurrently. It is also passed to Cargo.
+    jobs: Option<String>,
+    /// `--cache-stats`: report the hits and misses of the verification cache.
+    cache_stats: bool,
+}
+
+/// Split the arguments of `cargo-prusti` from the ones to be passed to Cargo.
+/// Both `--option <value>` and `--option=<value>` are accepted.
+fn extract_prusti_args<I>(args: I) -> (Vec<String>, PrustiArgs)
+    where
+        I: Iterator<Item = String>,
+{
+    let mut cargo_args = vec![];
+    let mut prusti_args = PrustiArgs::default();
+    let mut args = args;
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            cargo_args.push(arg);
+            cargo_args.extend(args.by_ref());
+            break;
+        }
+        if arg == "--cache-stats" {
+            prusti_args.cache_stats = true;
+            continue;
+        }
+        let (name, value) = match arg.find('=') {
+            Some(index) => (arg[..index].to_string(), Some(arg[index + 1..].to_string())),
+            None => (arg.clone(), None),
+        };
+        if !["--summary-json", "--baseline", "--check", "--jobs"].contains(&name.as_str()) {
+            cargo_args.push(arg);
+            continue;
+        }
+        let value = value.or_else(|| args.next())
+            .unwrap_or_else(|| panic!("{} requires a value", name));
+        match name.as_str() {
+            "--summary-json" => prusti_args.summary_json = Some(PathBuf::from(value)),
+            "--baseline" => prusti_args.baseline = Some(PathBuf::from(value)),
+            "--check" => prusti_args.check = Some(value),
+            "--jobs" => {
+                cargo_args.push(format!("--jobs={}", value));
+                prusti_args.jobs = Some(value);
+            }
+            _ => unreachable!(),
+        }
+    }
+    (cargo_args, prusti_args)
+}
@@ -0,0 +1,128 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Verify a corpus of files and crates, record the timings in a JSON history
+//! and report the timings that regressed with respect to the previous run:
+//!
+//! ```text
+//! prusti-bench [--corpus <file>]... [--test-suite] [--history <file>]
+//!     [--threshold <percent>] [--iterations <n>] [--revision <rev>] [--no-record]
+//! ```
+//!
+//! Without `--corpus` and `--test-suite`, the corpus is `benchmarks.csv` of
+//! the repository. The exit code is 1 if some timing regressed.
+
+use std::{env, path::PathBuf};
+use prusti_launch::bench::{
+    read_corpus, test_suite_corpus, BenchHistory, BenchRun, BenchRunner, Timings,
+};
+
+fn main() {
+    if let Err(code) = process(env::args().skip(1).collect()) {
+        std::process::exit(code);
+    }
+}
+
+fn process(args: Vec<String>) -> Result<(), i32> {
+    let current_executable = env::current_exe().expect("current executable path invalid");
+    // The executable is in `<repo>/target/<profile>`.
+    let repo = current_executable.ancestors().nth(3)
+        .expect("failed to obtain the repository of the current executable")
+        .to_path_buf();
+    let executable = |name: &str| {
+        let mut path = current_executable.with_file_name(name);
+        if cfg!(windows) {
+            path.set_extension("exe");
+        }
+        path
+    };
+
+    let mut corpus_files: Vec<PathBuf> = vec![];
+    let mut test_suite = false;
+    let mut history_path = repo.join("benchmark-output").join("history.json");
+    let mut threshold = 10.0;
+    let mut iterations = 3;
+    let mut revision = None;
+    let mut record = true;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| panic!("{} requires a value", arg));
+        match arg.as_str() {
+            "--corpus" => corpus_files.push(PathBuf::from(value())),
+            "--history" => history_path = PathBuf::from(value()),
+            "--threshold" => {
+                threshold = value().parse().expect("--threshold requires a percentage");
+            }
+            "--iterations" => {
+                iterations = value().parse().expect("--iterations requires a number");
+            }
+            "--revision" => revision = Some(value()),
+            "--test-suite" => test_suite = true,
+            "--no-record" => record = false,
+            _ => panic!("unknown argument {}", arg),
+        }
+    }
+    if corpus_files.is_empty() && !test_suite {
+        corpus_files.push(repo.join("benchmarks.csv"));
+    }
+    assert!(iterations > 0, "--iterations must be positive");
+
+    let mut corpus = vec![];
+    for path in &corpus_files {
+        corpus.extend(read_corpus(path).unwrap_or_else(|error| {
+            panic!("could not read the corpus {:?}: {}", path, error)
+        }));
+    }
+    if test_suite {
+        corpus.extend(test_suite_corpus(&repo.join("prusti-tests").join("tests"))
+            .expect("could not list the test suite"));
+    }
+
+    let runner = BenchRunner {
+        prusti_rustc: executable("prusti-rustc"),
+        cargo_prusti: executable("cargo-prusti"),
+        work_dir: repo.join("target").join("prusti-bench"),
+    };
+    let mut timings = Timings::new();
+    for entry in &corpus {
+        eprintln!("benchmarking {:?}", entry.path());
+        let entry_timings = runner.measure(entry, iterations).unwrap_or_else(|error| {
+            panic!("could not benchmark {:?}: {}", entry.path(), error)
+        });
+        timings.extend(entry_timings);
+    }
+
+    let mut history = BenchHistory::load(&history_path).unwrap_or_else(|error| {
+        panic!("could not read the history {:?}: {}", history_path, error)
+    });
+    let regressions = history.regressions(&timings, threshold / 100.0);
+    for regression in &regressions {
+        println!(
+            "regression: {} took {:.2}s instead of {:.2}s (+{:.0}%)",
+            regression.name,
+            regression.new_secs,
+            regression.old_secs,
+            regression.slowdown() * 100.0,
+        );
+    }
+    eprintln!(
+        "benchmark: {} corpus entries; {} timings; {} regressions above {}%",
+        corpus.len(), timings.len(), regressions.len(), threshold,
+    );
+
+    if record {
+        history.runs.push(BenchRun::new(revision, timings));
+        history.write(&history_path).unwrap_or_else(|error| {
+            panic!("could not write the history {:?}: {}", history_path, error)
+        });
+    }
+
+    if regressions.is_empty() {
+        Ok(())
+    } else {
+        Err(1)
+    }
+}
@@ -0,0 +1,112 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Encode a crate with two versions of Prusti, or with two sets of flags,
+//! without verifying it, and report the items whose encoding changed:
+//!
+//! ```text
+//! prusti-encoding-diff [--old-rev <rev>] [--new-rev <rev>]
+//!     [--old-flag <NAME>=<value>]... [--new-flag <NAME>=<value>]... [--lines]
+//!     -- <prusti-rustc arguments>
+//! ```
+//!
+//! A side without a revision uses the `prusti-rustc` next to this executable.
+//! The exit code is 1 if some encodings differ, as for `diff`.
+
+use std::{env, path::PathBuf};
+use prusti_launch::encoding_diff::{build_revision, EncodingDiff, EncodingSide};
+
+fn main() {
+    if let Err(code) = process(env::args().skip(1).collect()) {
+        std::process::exit(code);
+    }
+}
+
+/// The arguments that configure one side of the comparison.
+#[derive(Default)]
+struct SideArgs {
+    /// `--<side>-rev <rev>`: the git revision of Prusti to build and use.
+    rev: Option<String>,
+    /// `--<side>-flag <NAME>=<value>`: a flag of Prusti, repeatable.
+    flags: Vec<(String, String)>,
+}
+
+fn process(args: Vec<String>) -> Result<(), i32> {
+    let current_executable = env::current_exe().expect("current executable path invalid");
+    // The executable is in `<repo>/target/<profile>`.
+    let repo = current_executable.ancestors().nth(3)
+        .expect("failed to obtain the repository of the current executable")
+        .to_path_buf();
+    let mut default_prusti_rustc = current_executable.with_file_name("prusti-rustc");
+    if cfg!(windows) {
+        default_prusti_rustc.set_extension("exe");
+    }
+
+    let mut old_args = SideArgs::default();
+    let mut new_args = SideArgs::default();
+    let mut show_lines = false;
+    let mut rustc_args = vec![];
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            rustc_args.extend(args.by_ref());
+            break;
+        }
+        if arg == "--lines" {
+            show_lines = true;
+            continue;
+        }
+        let (side, option) = if let Some(option) = arg.strip_prefix("--old-") {
+            (&mut old_args, option)
+        } else if let Some(option) = arg.strip_prefix("--new-") {
+            (&mut new_args, option)
+        } else {
+            rustc_args.push(arg);
+            continue;
+        };
+        let value = args.next().unwrap_or_else(|| panic!("{} requires a value", arg));
+        match option {
+            "rev" => side.rev = Some(value),
+            "flag" => {
+                let index = value.find('=')
+                    .unwrap_or_else(|| panic!("{} requires a value of the form NAME=value", arg));
+                side.flags.push((value[..index].to_string(), value[index + 1..].to_string()));
+            }
+            _ => panic!("unknown option {}", arg),
+        }
+    }
+
+    let work_dir = repo.join("target").join("encoding-diff");
+    let log_dir = env::current_dir().expect("current directory invalid")
+        .join("log")
+        .join("encoding-diff");
+    let mut encodings = vec![];
+    for (name, side_args) in vec![("old", old_args), ("new", new_args)] {
+        let prusti_rustc = match side_args.rev {
+            Some(rev) => build_revision(&repo, &rev, &work_dir).unwrap_or_else(|error| {
+                panic!("could not build Prusti at revision {}: {}", rev, error)
+            }),
+            None => default_prusti_rustc.clone(),
+        };
+        let side = EncodingSide {
+            prusti_rustc,
+            flags: side_args.flags,
+        };
+        let side_log_dir: PathBuf = log_dir.join(name);
+        let items = side.encode(&rustc_args, &side_log_dir).unwrap_or_else(|error| {
+            panic!("could not encode the crate with the {} version: {}", name, error)
+        });
+        encodings.push(items);
+    }
+
+    let diff = EncodingDiff::compute(&encodings[0], &encodings[1]);
+    diff.print(show_lines);
+    if diff.is_empty() {
+        Ok(())
+    } else {
+        Err(1)
+    }
+}
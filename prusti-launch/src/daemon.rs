@@ -0,0 +1,191 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A Prusti server that keeps running in the background between runs of
+//! `cargo-prusti`. It is started with `cargo prusti daemon start` and stopped
+//! with `cargo prusti daemon stop`. While it is running, `cargo-prusti` sends
+//! the verification requests to it, so that the JVM, the Viper classes and
+//! the solver are started only once instead of on every build.
+//!
+//! The address and the process of the daemon are recorded in a file of the
+//! directory given by `PRUSTI_DAEMON_DIR`, or else of a directory in the
+//! temporary directory of the system.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    fs,
+    io::{BufRead, BufReader},
+    net::{SocketAddr, TcpStream},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+/// The running daemon, as recorded in its state file.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DaemonInfo {
+    /// The process of the `prusti-server` launcher, which leads the process
+    /// group of the server.
+    pub pid: u32,
+    /// The address on which the server accepts verification requests.
+    pub address: String,
+}
+
+impl DaemonInfo {
+    /// Whether the server still accepts connections on its address.
+    pub fn is_running(&self) -> bool {
+        match self.address.parse::<SocketAddr>() {
+            Ok(address) => TcpStream::connect_timeout(&address, Duration::from_secs(1)).is_ok(),
+            Err(_) => false,
+        }
+    }
+}
+
+/// The directory of the state file and of the log of the daemon.
+pub fn daemon_dir() -> PathBuf {
+    match env::var_os("PRUSTI_DAEMON_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            let user = env::var("USER").or_else(|_| env::var("USERNAME")).unwrap_or_default();
+            env::temp_dir().join(format!("prusti-daemon-{}", user))
+        }
+    }
+}
+
+fn info_path() -> PathBuf {
+    daemon_dir().join("daemon.json")
+}
+
+fn log_path() -> PathBuf {
+    daemon_dir().join("daemon.log")
+}
+
+/// The daemon, if one is running. A state file left behind by a daemon that
+/// is no longer running is removed.
+pub fn running() -> Option<DaemonInfo> {
+    let content = fs::read_to_string(info_path()).ok()?;
+    let info: Option<DaemonInfo> = serde_json::from_str(&content).ok();
+    match info {
+        Some(info) if info.is_running() => Some(info),
+        _ => {
+            let _ = fs::remove_file(info_path());
+            None
+        }
+    }
+}
+
+/// Start the daemon with the `prusti-server` executable at `server_path`.
+/// Returns once the server is warmed up and accepts requests.
+pub fn start(server_path: &Path) -> Result<DaemonInfo, String> {
+    if let Some(info) = running() {
+        return Err(format!("a Prusti daemon is already running at {}", info.address));
+    }
+    fs::create_dir_all(daemon_dir())
+        .map_err(|error| format!("could not create {:?}: {}", daemon_dir(), error))?;
+    let log = fs::File::create(log_path())
+        .map_err(|error| format!("could not create {:?}: {}", log_path(), error))?;
+
+    // Both outputs of the server go to the log, which outlives
+    // `cargo-prusti`, so that the server can keep writing to them.
+    let stdout = log.try_clone()
+        .map_err(|error| format!("could not open {:?}: {}", log_path(), error))?;
+    let mut cmd = Command::new(server_path);
+    cmd.args(&["--port", "0", "--warm-up"])
+        .stdin(Stdio::null())
+        .stdout(stdout)
+        .stderr(log);
+    detach(&mut cmd);
+    let mut child = cmd.spawn()
+        .map_err(|error| format!("could not run {:?}: {}", server_path, error))?;
+
+    // The server reports its port on stdout once it accepts requests.
+    let port = loop {
+        if let Some(port) = read_port(&log_path()) {
+            break port;
+        }
+        if let Ok(Some(_)) = child.try_wait() {
+            return Err(format!(
+                "the Prusti server stopped during its startup, see {:?}",
+                log_path(),
+            ));
+        }
+        thread::sleep(Duration::from_millis(100));
+    };
+
+    let info = DaemonInfo {
+        pid: child.id(),
+        address: format!("127.0.0.1:{}", port),
+    };
+    let content = serde_json::to_string(&info).unwrap();
+    fs::write(info_path(), content)
+        .map_err(|error| format!("could not write {:?}: {}", info_path(), error))?;
+    Ok(info)
+}
+
+/// The port reported by the server in its log, if it already accepts
+/// requests.
+fn read_port(log_path: &Path) -> Option<String> {
+    let log = fs::File::open(log_path).ok()?;
+    BufReader::new(log)
+        .lines()
+        .filter_map(|line| line.ok())
+        .find_map(|line| line.strip_prefix("port: ").map(|port| port.trim().to_string()))
+}
+
+/// Stop the running daemon.
+pub fn stop() -> Result<DaemonInfo, String> {
+    let info = running().ok_or_else(|| "no Prusti daemon is running".to_string())?;
+    kill(info.pid)?;
+    let _ = fs::remove_file(info_path());
+    Ok(info)
+}
+
+/// Run the server in a session of its own, so that it is not terminated
+/// together with the terminal of `cargo-prusti`.
+#[cfg(target_family = "unix")]
+fn detach(cmd: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    unsafe {
+        cmd.pre_exec(|| {
+            nix::unistd::setsid()
+                .map(|_| ())
+                .map_err(|_| std::io::Error::last_os_error())
+        });
+    }
+}
+
+#[cfg(target_family = "windows")]
+fn detach(cmd: &mut Command) {
+    use std::os::windows::process::CommandExt;
+    const DETACHED_PROCESS: u32 = 0x00000008;
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x00000200;
+    cmd.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+}
+
+#[cfg(target_family = "unix")]
+fn kill(pid: u32) -> Result<(), String> {
+    use nix::{sys::signal::{killpg, Signal}, unistd::Pid};
+    // The launcher leads the process group of the server and its JVM.
+    killpg(Pid::from_raw(pid as i32), Signal::SIGTERM)
+        .map_err(|error| format!("could not stop the Prusti daemon: {}", error))
+}
+
+#[cfg(target_family = "windows")]
+fn kill(pid: u32) -> Result<(), String> {
+    let status = Command::new("TASKKILL")
+        .args(&["/PID", &pid.to_string(), "/T", "/F"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|error| format!("could not stop the Prusti daemon: {}", error))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err("could not stop the Prusti daemon".to_string())
+    }
+}
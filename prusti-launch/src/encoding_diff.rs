@@ -0,0 +1,211 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Comparison of the encodings of a crate produced by two versions of Prusti,
+//! or by two sets of flags. Each side runs `prusti-rustc` with `ENCODE_ONLY`
+//! and `DUMP_VIR_ITEMS`, which dumps the VIR of each item of the crate (see
+//! `prusti-common/src/vir/program.rs`), and the dumps are compared per item.
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The encoding of each item, keyed by the kind and the name of the item.
+pub type ItemEncodings = BTreeMap<String, String>;
+
+/// A version of Prusti and the flags with which it encodes the crate.
+#[derive(Debug, Clone)]
+pub struct EncodingSide {
+    pub prusti_rustc: PathBuf,
+    /// Flags given as `(name, value)`, e.g. `("CHECK_OVERFLOWS", "true")`.
+    pub flags: Vec<(String, String)>,
+}
+
+impl EncodingSide {
+    /// Encode the crate compiled by `rustc_args`, using `log_dir` as the log
+    /// directory of Prusti.
+    pub fn encode(&self, rustc_args: &[String], log_dir: &Path) -> io::Result<ItemEncodings> {
+        let items_dir = log_dir.join("vir_items");
+        if items_dir.exists() {
+            fs::remove_dir_all(&items_dir)?;
+        }
+        let mut cmd = Command::new(&self.prusti_rustc);
+        cmd.args(rustc_args)
+            .env("PRUSTI_ENCODE_ONLY", "true")
+            .env("PRUSTI_DUMP_VIR_ITEMS", "true")
+            .env("PRUSTI_INTERN_NAMES", "false")
+            .env("PRUSTI_LOG_DIR", log_dir);
+        for (name, value) in &self.flags {
+            cmd.env(format!("PRUSTI_{}", name.to_uppercase()), value);
+        }
+        let status = cmd.status()?;
+        if !status.success() {
+            eprintln!(
+                "warning: {:?} reported errors; the encoding might be incomplete",
+                self.prusti_rustc,
+            );
+        }
+        let mut items = ItemEncodings::new();
+        for entry in fs::read_dir(&items_dir)? {
+            let path = entry?.path();
+            let crate_name = path.file_stem().unwrap().to_string_lossy().to_string();
+            let content = fs::read_to_string(&path)?;
+            let crate_items: ItemEncodings = serde_json::from_str(&content)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+            items.extend(crate_items.into_iter().map(|(name, encoding)| {
+                (format!("{}: {}", crate_name, name), encoding)
+            }));
+        }
+        Ok(items)
+    }
+}
+
+/// Build `prusti-rustc` at the git revision `rev` of the repository `repo`,
+/// in a worktree below `work_dir`, and return the path of the executable.
+pub fn build_revision(repo: &Path, rev: &str, work_dir: &Path) -> io::Result<PathBuf> {
+    let worktree = work_dir.join(rev.replace(|c: char| !c.is_alphanumeric(), "_"));
+    if !worktree.exists() {
+        run(Command::new("git")
+            .arg("-C").arg(repo)
+            .args(&["worktree", "add", "--detach"])
+            .arg(&worktree)
+            .arg(rev))?;
+    } else {
+        run(Command::new("git")
+            .arg("-C").arg(&worktree)
+            .args(&["checkout", "--detach", rev]))?;
+    }
+    run(Command::new("cargo").arg("build").current_dir(&worktree))?;
+    let mut prusti_rustc = worktree.join("target").join("debug").join("prusti-rustc");
+    if cfg!(windows) {
+        prusti_rustc.set_extension("exe");
+    }
+    Ok(prusti_rustc)
+}
+
+fn run(cmd: &mut Command) -> io::Result<()> {
+    let status = cmd.status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::new(io::ErrorKind::Other, format!("{:?} failed with {}", cmd, status)))
+    }
+}
+
+/// The items whose encoding differs between two runs.
+#[derive(Debug, Default)]
+pub struct EncodingDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<(String, Vec<DiffLine>)>,
+    pub unchanged: usize,
+}
+
+/// A line of the difference between two encodings of an item.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DiffLine {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+impl EncodingDiff {
+    pub fn compute(old: &ItemEncodings, new: &ItemEncodings) -> Self {
+        let mut diff = EncodingDiff::default();
+        for (name, old_encoding) in old {
+            match new.get(name) {
+                None => diff.removed.push(name.clone()),
+                Some(new_encoding) if new_encoding == old_encoding => diff.unchanged += 1,
+                Some(new_encoding) => {
+                    diff.changed.push((name.clone(), diff_lines(old_encoding, new_encoding)));
+                }
+            }
+        }
+        diff.added = new.keys()
+            .filter(|name| !old.contains_key(*name))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Print the changed items with their differences, followed by a summary.
+    pub fn print(&self, show_lines: bool) {
+        for name in &self.removed {
+            println!("removed: {}", name);
+        }
+        for name in &self.added {
+            println!("added: {}", name);
+        }
+        for (name, lines) in &self.changed {
+            println!("changed: {}", name);
+            if !show_lines {
+                continue;
+            }
+            for line in lines {
+                match line {
+                    DiffLine::Same(_) => {}
+                    DiffLine::Removed(text) => println!("    - {}", text),
+                    DiffLine::Added(text) => println!("    + {}", text),
+                }
+            }
+        }
+        eprintln!(
+            "encoding diff: {} changed; {} added; {} removed; {} unchanged",
+            self.changed.len(), self.added.len(), self.removed.len(), self.unchanged,
+        );
+    }
+}
+
+/// A line-based difference of two texts, computed on the longest common
+/// subsequence of the lines that remain after the common prefix and suffix.
+fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<_> = old.lines().collect();
+    let new: Vec<_> = new.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    // lcs[i][j] is the length of the common subsequence of old_middle[i..]
+    // and new_middle[j..].
+    let mut lcs = vec![vec![0usize; new_middle.len() + 1]; old_middle.len() + 1];
+    for i in (0..old_middle.len()).rev() {
+        for j in (0..new_middle.len()).rev() {
+            lcs[i][j] = if old_middle[i] == new_middle[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines: Vec<_> = old[..prefix].iter().map(|line| DiffLine::Same(line.to_string())).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < old_middle.len() || j < new_middle.len() {
+        if i < old_middle.len() && j < new_middle.len() && old_middle[i] == new_middle[j] {
+            lines.push(DiffLine::Same(old_middle[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if j < new_middle.len() && (i == old_middle.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            lines.push(DiffLine::Added(new_middle[j].to_string()));
+            j += 1;
+        } else {
+            lines.push(DiffLine::Removed(old_middle[i].to_string()));
+            i += 1;
+        }
+    }
+    lines.extend(old[old.len() - suffix..].iter().map(|line| DiffLine::Same(line.to_string())));
+    lines
+}
@@ -13,6 +13,14 @@ use serde::Deserialize;
 #[cfg(target_family = "unix")]
 use nix::{sys::signal::{Signal, killpg}, unistd::getpgrp};
 
+pub mod baseline;
+#[path = "../../prusti-common/src/cache_stats.rs"]
+pub mod cache_stats;
+pub mod bench;
+pub mod daemon;
+pub mod encoding_diff;
+pub mod summary;
+
 /// Append paths to the loader environment variable
 pub fn add_to_loader_path(paths: Vec<PathBuf>, cmd: &mut Command) {
     #[cfg(target_os = "windows")]
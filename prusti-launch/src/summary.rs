@@ -0,0 +1,157 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Aggregation of the per-crate summaries written by `prusti-driver` when
+//! `PRUSTI_SUMMARY_DIR` is set. The format of the files is defined in
+//! `prusti/src/summary.rs`.
+
+use std::{
+    fs, io,
+    path::Path,
+    time::Duration,
+};
+use serde::{Deserialize, Serialize};
+pub use crate::cache_stats::CacheStats;
+
+/// The number of items listed as the slowest ones.
+const SLOWEST_ITEMS: usize = 5;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ItemSummary {
+    pub name: String,
+    pub status: String,
+    pub encoding_secs: f64,
+    /// The time spent verifying the Viper program that contains the item.
+    #[serde(default)]
+    pub verification_secs: f64,
+}
+
+impl ItemSummary {
+    /// The time spent encoding and verifying the item.
+    pub fn total_secs(&self) -> f64 {
+        self.encoding_secs + self.verification_secs
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CrateSummary {
+    pub crate_name: String,
+    pub success: bool,
+    pub duration_secs: f64,
+    pub items: Vec<ItemSummary>,
+    #[serde(default)]
+    pub cache: CacheStats,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunSummary {
+    pub success: bool,
+    pub verified: usize,
+    pub failed: usize,
+    pub trusted: usize,
+    pub unsupported: usize,
+    pub dependency_failed: usize,
+    /// The lookups in the persistent caches of verified procedures of the
+    /// crates that were verified by this run.
+    pub procedure_cache: CacheStats,
+    pub duration_secs: f64,
+    pub slowest_items: Vec<ItemSummary>,
+    pub crates: Vec<CrateSummary>,
+}
+
+impl RunSummary {
+    /// Collect the summaries from `dir`, which has to contain only the
+    /// summaries of the crates verified by the run.
+    pub fn collect(dir: &Path, duration: Duration) -> io::Result<Self> {
+        let mut crates = vec![];
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().map_or(true, |ext| ext != "json") {
+                continue;
+            }
+            let content = fs::read_to_string(&path)?;
+            let summary: CrateSummary = serde_json::from_str(&content)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+            crates.push(summary);
+        }
+        crates.sort_by(|a, b| a.crate_name.cmp(&b.crate_name));
+
+        let count = |status: &str| {
+            crates.iter()
+                .flat_map(|krate| &krate.items)
+                .filter(|item| item.status == status)
+                .count()
+        };
+        let mut slowest_items: Vec<ItemSummary> = crates.iter()
+            .flat_map(|krate| krate.items.iter().cloned())
+            .collect();
+        slowest_items.sort_by(|a, b| b.total_secs().partial_cmp(&a.total_secs()).unwrap());
+        slowest_items.truncate(SLOWEST_ITEMS);
+        let mut procedure_cache = CacheStats::default();
+        for krate in &crates {
+            procedure_cache.add(&krate.cache);
+        }
+
+        Ok(RunSummary {
+            success: crates.iter().all(|krate| krate.success),
+            verified: count("verified"),
+            failed: count("failed"),
+            trusted: count("trusted"),
+            unsupported: count("unsupported"),
+            dependency_failed: count("dependency_failed"),
+            procedure_cache,
+            duration_secs: duration.as_secs_f64(),
+            slowest_items,
+            crates,
+        })
+    }
+
+    /// Print the summary in a format similar to the one of `cargo test`.
+    pub fn print(&self) {
+        eprintln!();
+        eprintln!(
+            "verification result: {}. {} verified; {} failed; {} trusted; {} skipped (unsupported); \
+            {} skipped (dependency failed); {} procedures cached; finished in {:.2}s",
+            if self.success { "ok" } else { "FAILED" },
+            self.verified,
+            self.failed,
+            self.trusted,
+            self.unsupported,
+            self.dependency_failed,
+            self.procedure_cache.hits,
+            self.duration_secs,
+        );
+        if !self.slowest_items.is_empty() {
+            eprintln!("slowest items (encoding and verification time):");
+            for item in &self.slowest_items {
+                eprintln!(
+                    "    {:>8.2}s  {} ({}; encoding {:.2}s, verification {:.2}s)",
+                    item.total_secs(),
+                    item.name,
+                    item.status,
+                    item.encoding_secs,
+                    item.verification_secs,
+                );
+            }
+        }
+    }
+
+    /// Print the lookups in the caches of verified procedures, per crate and
+    /// in total.
+    pub fn print_cache_stats(&self) {
+        eprintln!("verification cache:");
+        for krate in &self.crates {
+            eprintln!("    {}: {}", krate.crate_name, krate.cache);
+        }
+        eprintln!("    total: {}", self.procedure_cache);
+    }
+
+    pub fn write_json(&self, path: &Path) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+        fs::write(path, content)
+    }
+}
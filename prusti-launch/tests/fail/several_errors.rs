@@ -0,0 +1,29 @@
+use prusti_contracts::*;
+
+#[pure]
+fn double(x: u32) -> u32 {
+    x + x
+}
+
+#[pure]
+fn is_small(x: u32) -> bool {
+    x < 100
+}
+
+#[requires(is_small(x))]
+#[ensures(result == double(x) + 1)]
+fn wrong_double(x: u32) -> u32 {
+    x * 2
+}
+
+#[ensures(is_small(result))]
+fn too_big() -> u32 {
+    1000
+}
+
+fn failing_assertions(x: u32) {
+    assert!(x > 0);
+    assert!(double(x) > x);
+}
+
+fn main() {}
@@ -10,7 +10,12 @@ use std::path::PathBuf;
 use std::io::{BufReader, BufRead};
 use std::env;
 use prusti_launch::find_java_home;
+use prusti_launch::bench::BenchHistory;
+use prusti_launch::daemon::DaemonInfo;
+use prusti_launch::summary::RunSummary;
 use std::collections::HashMap;
+use std::fs;
+use std::time::Duration;
 
 fn find_executable_path(base_name: &str) -> PathBuf {
     let target_directory = if cfg!(debug_assertions) {
@@ -166,3 +171,287 @@ fn test_prusti_rustc_with_server() {
             .expect("failed to execute prusti-rustc")
     });
 }
+
+#[test]
+fn test_cargo_prusti_daemon() {
+    let cargo_prusti = find_executable_path("cargo-prusti");
+    let prusti_rustc = find_executable_path("prusti-rustc");
+    let java_home = find_java_home().expect("Failed to find Java home directory.");
+    let daemon_dir = env::temp_dir()
+        .join(format!("prusti-daemon-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&daemon_dir);
+
+    let run_daemon_command = |command: &str| -> ExitStatus {
+        Command::new(&cargo_prusti)
+            .args(&["prusti", "daemon", command])
+            .env("RUST_BACKTRACE", "1")
+            .env("JAVA_HOME", &java_home)
+            .env("PRUSTI_DAEMON_DIR", &daemon_dir)
+            .status()
+            .expect("failed to execute cargo-prusti")
+    };
+
+    assert!(run_daemon_command("start").success(), "the daemon did not start");
+    let info = fs::read_to_string(daemon_dir.join("daemon.json"))
+        .expect("the daemon did not record its address");
+    let info: DaemonInfo = serde_json::from_str(&info).unwrap();
+
+    // The daemon keeps serving requests once `cargo-prusti` has exited, so
+    // its output must not go to a pipe that nobody reads anymore.
+    run_on_test_files(|program: &PathBuf| -> ExitStatus {
+        println!("Running {:?} on {:?}...", prusti_rustc.display(), program.display());
+        Command::new(&prusti_rustc)
+            .arg("--edition=2018")
+            .arg(program)
+            .env_clear()
+            .env("RUST_BACKTRACE", "1")
+            .env("PRUSTI_SERVER_ADDRESS", &info.address)
+            .status()
+            .expect("failed to execute prusti-rustc")
+    });
+
+    assert!(run_daemon_command("stop").success(), "the daemon did not stop");
+    let log = fs::read_to_string(daemon_dir.join("daemon.log")).unwrap();
+    fs::remove_dir_all(&daemon_dir).unwrap();
+    assert!(log.contains("port: "), "the output of the daemon is not in its log");
+}
+
+#[test]
+fn test_prusti_rustc_summary() {
+    let prusti_rustc = find_executable_path("prusti-rustc");
+
+    let run_with_summary = |program: &str| -> RunSummary {
+        let summary_dir = env::temp_dir()
+            .join(format!("prusti-summary-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&summary_dir);
+        Command::new(&prusti_rustc)
+            .arg("--edition=2018")
+            .arg(program)
+            .env_clear()
+            .env("RUST_BACKTRACE", "1")
+            .env("PRUSTI_SUMMARY_DIR", &summary_dir)
+            .status()
+            .expect("failed to execute prusti-rustc");
+        let summary = RunSummary::collect(&summary_dir, Duration::default())
+            .expect("failed to collect the verification summary");
+        fs::remove_dir_all(&summary_dir).unwrap();
+        summary
+    };
+
+    let summary = run_with_summary("tests/pass/with_specs.rs");
+    assert!(summary.success);
+    assert_eq!(summary.crates.len(), 1);
+    assert_eq!(summary.trusted, 1);
+    assert_eq!(summary.failed, 0);
+    assert!(summary.verified > 0);
+
+    let summary = run_with_summary("tests/fail/wrong_ensures.rs");
+    assert!(!summary.success);
+    assert_eq!(summary.trusted, 1);
+    assert_eq!(summary.failed, 1);
+    let failing_item = summary.slowest_items.iter().find(|item| item.name == "test").unwrap();
+    assert!(failing_item.verification_secs > 0.0);
+}
+
+#[test]
+fn test_cargo_prusti_summary() {
+    let cargo_prusti = fs::canonicalize(find_executable_path("cargo-prusti")).unwrap();
+    let crate_dir = env::temp_dir()
+        .join(format!("prusti-cargo-summary-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&crate_dir);
+    fs::create_dir_all(crate_dir.join("src")).unwrap();
+    fs::write(
+        crate_dir.join("Cargo.toml"),
+        "[package]\nname = \"summary_test\"\nversion = \"0.1.0\"\nedition = \"2018\"\n",
+    ).unwrap();
+    fs::write(
+        crate_dir.join("src/lib.rs"),
+        "pub fn identity(x: u32) -> u32 { x }\npub fn fails() { assert!(false); }\n",
+    ).unwrap();
+    // The summary of a crate of an earlier run must not be reported.
+    let summary_dir = crate_dir.join("target/prusti-summary");
+    fs::create_dir_all(&summary_dir).unwrap();
+    fs::write(
+        summary_dir.join("stale-0000000000000000.json"),
+        r#"{"crate_name": "stale", "success": false, "duration_secs": 0.0, "items": []}"#,
+    ).unwrap();
+
+    let summary_path = crate_dir.join("summary.json");
+    let status = Command::new(&cargo_prusti)
+        .arg("--summary-json")
+        .arg(&summary_path)
+        .current_dir(&crate_dir)
+        .env("RUST_BACKTRACE", "1")
+        .status()
+        .expect("failed to execute cargo-prusti");
+    assert!(!status.success());
+    let summary: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&summary_path).unwrap()).unwrap();
+    fs::remove_dir_all(&crate_dir).unwrap();
+
+    let crates = summary["crates"].as_array().unwrap();
+    assert_eq!(crates.len(), 1);
+    assert_eq!(crates[0]["crate_name"], "summary_test");
+    assert_eq!(summary["failed"], 1);
+    let slowest = &summary["slowest_items"][0];
+    assert!(slowest["verification_secs"].as_f64().unwrap() > 0.0);
+}
+
+#[test]
+fn test_prusti_rustc_deterministic() {
+    let prusti_rustc = find_executable_path("prusti-rustc");
+    let log_dir = env::temp_dir()
+        .join(format!("prusti-deterministic-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&log_dir);
+
+    // Each run is a new process, with other seeds of its hash maps, and thus
+    // encodes the items in another order.
+    let run = |index: usize| -> (String, Vec<String>) {
+        let run_log_dir = log_dir.join(index.to_string());
+        let output = Command::new(&prusti_rustc)
+            .arg("--edition=2018")
+            .arg("tests/fail/several_errors.rs")
+            .env_clear()
+            .env("RUST_BACKTRACE", "1")
+            .env("PRUSTI_DETERMINISTIC", "true")
+            .env("PRUSTI_DUMP_VIPER_PROGRAM", "true")
+            .env("PRUSTI_LOG_DIR", &run_log_dir)
+            .output()
+            .expect("failed to execute prusti-rustc");
+        assert!(!output.status.success());
+        let program = fs::read_to_string(run_log_dir.join("viper_program/several_errors.rs.vpr"))
+            .expect("the Viper program was not dumped");
+        let errors = String::from_utf8(output.stderr).unwrap()
+            .lines()
+            .filter(|line| line.starts_with("error") || line.trim_start().starts_with("-->"))
+            .map(|line| line.to_string())
+            .collect();
+        (program, errors)
+    };
+
+    let (first_program, first_errors) = run(0);
+    let (second_program, second_errors) = run(1);
+    fs::remove_dir_all(&log_dir).unwrap();
+    assert!(first_errors.len() > 2, "the errors were not reported: {:?}", first_errors);
+    assert_eq!(first_errors, second_errors, "the errors are reported in another order");
+    assert!(first_program == second_program, "the dumped Viper programs differ");
+}
+
+#[test]
+fn test_prusti_rustc_json_messages() {
+    let prusti_rustc = find_executable_path("prusti-rustc");
+
+    let output = Command::new(&prusti_rustc)
+        .arg("--edition=2018")
+        .arg("tests/fail/wrong_ensures.rs")
+        .env_clear()
+        .env("RUST_BACKTRACE", "1")
+        .env("PRUSTI_MESSAGE_FORMAT", "json")
+        .output()
+        .expect("failed to execute prusti-rustc");
+    assert!(!output.status.success());
+
+    // The details of the error are in a note of the JSON diagnostic.
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let diagnostic = stderr
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .find(|diagnostic| diagnostic["code"]["code"] == "prusti::verification")
+        .unwrap_or_else(|| panic!("no JSON diagnostic of a verification error in {}", stderr));
+    let details = diagnostic["children"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|child| child["message"].as_str()?.strip_prefix("prusti: "))
+        .next()
+        .expect("the diagnostic has no note with the details of the error");
+    let details: serde_json::Value = serde_json::from_str(details).unwrap();
+    assert_eq!(details["kind"], "verification");
+    assert_eq!(details["method"], "test");
+    assert_eq!(details["spec_kind"], "postcondition");
+    assert_eq!(details["error_ctxt"], "AssertMethodPostcondition");
+}
+
+#[test]
+fn test_prusti_rustc_baseline() {
+    let prusti_rustc = find_executable_path("prusti-rustc");
+    let baseline_path = env::temp_dir()
+        .join(format!("prusti-baseline-test-{}.json", std::process::id()));
+
+    let run_with_baseline = |baseline: &str| -> ExitStatus {
+        fs::write(&baseline_path, baseline).unwrap();
+        Command::new(&prusti_rustc)
+            .arg("--edition=2018")
+            .arg("tests/fail/wrong_ensures.rs")
+            .env_clear()
+            .env("RUST_BACKTRACE", "1")
+            .env("PRUSTI_BASELINE", &baseline_path)
+            .status()
+            .expect("failed to execute prusti-rustc")
+    };
+
+    let accepted = run_with_baseline(
+        r#"{ "failures": [ { "crate_name": "wrong_ensures", "item": "test" } ] }"#
+    );
+    let not_accepted = run_with_baseline(
+        r#"{ "failures": [ { "crate_name": "wrong_ensures", "item": "main" } ] }"#
+    );
+    fs::remove_file(&baseline_path).unwrap();
+    assert!(accepted.success(), "the failure accepted by the baseline was reported as an error");
+    assert!(!not_accepted.success(), "the failure not in the baseline was not reported");
+}
+
+#[test]
+fn test_prusti_encoding_diff() {
+    let prusti_encoding_diff = find_executable_path("prusti-encoding-diff");
+
+    let run_with_flags = |flags: &[&str]| -> ExitStatus {
+        Command::new(&prusti_encoding_diff)
+            .args(flags)
+            .arg("--")
+            .arg("--edition=2018")
+            .arg("tests/pass/with_specs.rs")
+            .env_clear()
+            .env("RUST_BACKTRACE", "1")
+            .status()
+            .expect("failed to execute prusti-encoding-diff")
+    };
+
+    let same = run_with_flags(&[]);
+    let different = run_with_flags(&["--new-flag", "CHECK_PANICS=false"]);
+    assert!(same.success(), "the encodings of the same flags were reported as different");
+    assert_eq!(different.code(), Some(1), "the change of the encoding was not reported");
+}
+
+#[test]
+fn test_prusti_bench() {
+    let prusti_bench = find_executable_path("prusti-bench");
+    let corpus_path = env::temp_dir()
+        .join(format!("prusti-bench-corpus-{}.csv", std::process::id()));
+    let history_path = env::temp_dir()
+        .join(format!("prusti-bench-history-{}.json", std::process::id()));
+    fs::write(&corpus_path, "tests/pass/with_specs.rs\n").unwrap();
+
+    let run_with_threshold = |threshold: &str| -> ExitStatus {
+        Command::new(&prusti_bench)
+            .arg("--corpus")
+            .arg(&corpus_path)
+            .arg("--history")
+            .arg(&history_path)
+            .args(&["--iterations", "1", "--threshold", threshold])
+            .env_clear()
+            .env("RUST_BACKTRACE", "1")
+            .status()
+            .expect("failed to execute prusti-bench")
+    };
+
+    let first = run_with_threshold("10");
+    let second = run_with_threshold("100000");
+    let history = BenchHistory::load(&history_path).unwrap();
+    fs::remove_file(&corpus_path).unwrap();
+    fs::remove_file(&history_path).unwrap();
+    assert!(first.success(), "a run without history reported a regression");
+    assert!(second.success(), "a regression was reported above the threshold");
+    assert_eq!(history.runs.len(), 2);
+    assert!(history.runs[1].timings.contains_key("tests/pass/with_specs.rs"));
+}
@@ -27,6 +27,11 @@ fn main() {
                 .takes_value(true)
                 .value_name("PORT"),
         )
+        .arg(
+            Arg::with_name("warm-up")
+                .long("warm-up")
+                .help("Starts a verifier for the default backend before accepting requests, so that the first request is not slowed down by the startup of Viper and the solver."),
+        )
         .get_matches();
 
     let port = matches
@@ -36,5 +41,8 @@ fn main() {
         .expect("Invalid port provided");
 
     let service = ServerSideService::new();
+    if matches.is_present("warm-up") {
+        service.warm_up();
+    }
     service.listen_on_port(port);
 }
@@ -0,0 +1,195 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Verification jobs, which let clients submit programs without waiting for
+//! their results: a job is queued, verified in the background, and its status
+//! and results are fetched later by its id.
+
+use super::{PrustiServer, RemoteVerificationResult};
+use prusti_common::verification_service::*;
+use std::{
+    collections::BTreeMap,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+use viper::VerificationResult;
+
+/// The number of finished jobs whose results are kept. When more jobs
+/// finish, the results of the oldest ones are discarded.
+const MAX_FINISHED_JOBS: usize = 100;
+
+struct Job {
+    state: JobState,
+    programs: usize,
+    results: Vec<ProgramResult>,
+}
+
+#[derive(Default)]
+struct Jobs {
+    next_id: JobId,
+    jobs: BTreeMap<JobId, Job>,
+}
+
+pub struct JobQueue {
+    jobs: Arc<Mutex<Jobs>>,
+    sender: Mutex<mpsc::Sender<(JobId, Vec<VerificationRequest>)>>,
+}
+
+impl JobQueue {
+    /// Create a queue whose jobs are verified one after the other by
+    /// `server`, on a thread of their own.
+    pub fn new(server: Arc<PrustiServer>) -> Self {
+        let jobs = Arc::new(Mutex::new(Jobs::default()));
+        let (sender, receiver) = mpsc::channel::<(JobId, Vec<VerificationRequest>)>();
+        let worker_jobs = jobs.clone();
+        thread::Builder::new()
+            .name("prusti-server-jobs".to_string())
+            .spawn(move || {
+                for (id, requests) in receiver {
+                    info!("Running verification job {}", id);
+                    worker_jobs.lock().unwrap().set_state(id, JobState::Running);
+                    for request in requests {
+                        let result = server.run_verifier(request.clone());
+                        let mut jobs = worker_jobs.lock().unwrap();
+                        if let Some(job) = jobs.jobs.get_mut(&id) {
+                            job.results.push(program_result(&request, result));
+                        }
+                    }
+                    worker_jobs.lock().unwrap().set_state(id, JobState::Finished);
+                }
+            })
+            .expect("could not spawn the thread of the verification jobs");
+        JobQueue {
+            jobs,
+            sender: Mutex::new(sender),
+        }
+    }
+
+    /// Queue a job that verifies `requests`, and return its id.
+    pub fn submit(&self, requests: Vec<VerificationRequest>) -> JobId {
+        let id = {
+            let mut jobs = self.jobs.lock().unwrap();
+            let id = jobs.next_id;
+            jobs.next_id += 1;
+            jobs.jobs.insert(id, Job {
+                state: JobState::Queued,
+                programs: requests.len(),
+                results: vec![],
+            });
+            id
+        };
+        info!("Queueing verification job {} with {} programs", id, requests.len());
+        self.sender
+            .lock()
+            .unwrap()
+            .send((id, requests))
+            .expect("the thread of the verification jobs stopped");
+        id
+    }
+
+    /// The status of the job `id`, or `None` if there is no such job.
+    pub fn status(&self, id: JobId) -> Option<JobStatus> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.jobs.get(&id).map(|job| JobStatus {
+            id,
+            state: job.state,
+            programs: job.programs,
+            verified_programs: job.results.len(),
+        })
+    }
+
+    /// The results of the job `id`, in the order of its programs, or its
+    /// status if it has not finished yet. Returns `None` if there is no such
+    /// job.
+    pub fn results(&self, id: JobId) -> Option<Result<Vec<ProgramResult>, JobStatus>> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.jobs.get(&id).map(|job| {
+            if job.state == JobState::Finished {
+                Ok(job.results.clone())
+            } else {
+                Err(JobStatus {
+                    id,
+                    state: job.state,
+                    programs: job.programs,
+                    verified_programs: job.results.len(),
+                })
+            }
+        })
+    }
+}
+
+impl Jobs {
+    fn set_state(&mut self, id: JobId, state: JobState) {
+        if let Some(job) = self.jobs.get_mut(&id) {
+            job.state = state;
+        }
+        if state == JobState::Finished {
+            let finished: Vec<JobId> = self.jobs
+                .iter()
+                .filter(|(_, job)| job.state == JobState::Finished)
+                .map(|(&id, _)| id)
+                .collect();
+            // The ids grow over time, so the first ones are the oldest.
+            for id in finished.iter().take(finished.len().saturating_sub(MAX_FINISHED_JOBS)) {
+                self.jobs.remove(id);
+            }
+        }
+    }
+}
+
+/// Describe the result of `request`, locating the errors in the source code
+/// with the positions of the program.
+fn program_result(request: &VerificationRequest, result: RemoteVerificationResult) -> ProgramResult {
+    let message_error = |message: String| ProgramError {
+        method: None,
+        full_id: None,
+        message,
+        line: None,
+        column: None,
+    };
+    let (outcome, errors) = match result {
+        Ok(VerificationResult::Success()) => (ProgramOutcome::Success, vec![]),
+        Ok(VerificationResult::Failure(errors)) => {
+            let errors = errors
+                .into_iter()
+                .map(|error| {
+                    let location = error.pos_id
+                        .as_ref()
+                        .and_then(|pos_id| pos_id.parse::<u64>().ok())
+                        .and_then(|pos_id| request.program.locate_position(pos_id));
+                    // Positions without a source location have line 0.
+                    let position = location
+                        .as_ref()
+                        .map(|(_, position)| *position)
+                        .filter(|position| position.line() > 0);
+                    ProgramError {
+                        method: location.map(|(method, _)| method),
+                        full_id: Some(error.full_id),
+                        message: error.message,
+                        line: position.map(|position| position.line()),
+                        column: position.map(|position| position.column()),
+                    }
+                })
+                .collect();
+            (ProgramOutcome::Failure, errors)
+        }
+        Ok(VerificationResult::ConsistencyErrors(errors)) => (
+            ProgramOutcome::ConsistencyErrors,
+            errors.into_iter().map(message_error).collect(),
+        ),
+        Ok(VerificationResult::JavaException(exception)) => (
+            ProgramOutcome::JavaException,
+            vec![message_error(exception.to_string())],
+        ),
+        Err(panicked) => (ProgramOutcome::Panicked, vec![message_error(panicked.message)]),
+    };
+    ProgramResult {
+        program_name: request.program_name.clone(),
+        crate_name: request.crate_name.clone(),
+        outcome,
+        errors,
+    }
+}
@@ -10,6 +10,7 @@ extern crate warp;
 #[macro_use]
 extern crate log;
 extern crate bincode;
+extern crate flate2;
 extern crate futures;
 extern crate num_cpus;
 extern crate prusti_common;
@@ -17,29 +18,53 @@ extern crate tokio;
 #[macro_use]
 extern crate serde;
 
+mod jobs;
+mod parallel_verifier;
 mod service;
 mod verifier_runner;
 mod verifier_thread;
 
 use futures::Future;
-use prusti_common::{verification_context::VerifierBuilder, verification_service::*, Stopwatch};
+use prusti_common::{
+    config, verification_context::VerifierBuilder, verification_service::*, vir::Program,
+    Stopwatch,
+};
+pub use jobs::*;
+pub use parallel_verifier::*;
 pub use service::*;
 use std::{
-    collections::VecDeque,
-    sync::{Arc, RwLock},
+    collections::{hash_map::DefaultHasher, HashMap, VecDeque},
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex, RwLock},
 };
 pub use verifier_runner::*;
 use verifier_thread::*;
 use viper::VerificationResult;
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct VerifierPanicked;
+pub struct VerifierPanicked {
+    /// The message of the panic.
+    pub message: String,
+}
 pub type RemoteVerificationResult = Result<VerificationResult, VerifierPanicked>;
 
+/// The state that the server keeps for a single crate. Each crate has its own
+/// namespace, so that crates of a workspace can be verified concurrently
+/// without their programs clashing.
+#[derive(Default)]
+struct CrateSession {
+    /// The results of earlier requests, keyed by program name, together with
+    /// the hash of the request that produced them.
+    results: HashMap<String, (u64, VerificationResult)>,
+}
+
 pub struct PrustiServer {
     verifier_builder: Arc<VerifierBuilder>,
     threads: RwLock<VecDeque<VerifierThread>>,
     cache_size: usize,
+    sessions: Mutex<HashMap<String, CrateSession>>,
+    /// The lookups in the cache of results, see `config::server_cache_results`.
+    cache_stats: Mutex<CacheStats>,
 }
 
 impl PrustiServer {
@@ -52,10 +77,96 @@ impl PrustiServer {
             verifier_builder,
             threads: RwLock::new(VecDeque::with_capacity(cache_size)),
             cache_size,
+            sessions: Mutex::new(HashMap::new()),
+            cache_stats: Mutex::new(CacheStats::default()),
+        }
+    }
+
+    /// Start a verifier for the default backend and verify an empty program,
+    /// so that the first request does not pay for loading the Viper classes
+    /// and starting the solver.
+    pub fn warm_up(&self) {
+        let stopwatch = Stopwatch::start("prusti-server", "warm-up");
+        let backend_config = ViperBackendConfig::default();
+        let thread = VerifierThread::new(self.verifier_builder.clone(), backend_config);
+        let program = Program {
+            domains: vec![],
+            fields: vec![],
+            builtin_methods: vec![],
+            methods: vec![],
+            functions: vec![],
+            viper_predicates: vec![],
+            viper_imports: vec![],
+        };
+        match thread.verify(program, "warm-up".to_string()).wait() {
+            Ok(_) => self.threads.write().unwrap().push_front(thread),
+            Err(_) => error!("Panic while warming up the verifier"),
         }
+        stopwatch.finish();
+    }
+
+    pub fn cache_stats(&self) -> CacheStats {
+        *self.cache_stats.lock().unwrap()
     }
 
     pub fn run_verifier(&self, request: VerificationRequest) -> RemoteVerificationResult {
+        let request_hash = hash_request(&request);
+        if let Some(result) = self.cached_result(&request, request_hash) {
+            info!(
+                "Reusing the cached result for {}",
+                request.qualified_program_name()
+            );
+            return Ok(result);
+        }
+        let result = self.run_verifier_uncached(&request)?;
+        self.cache_result(&request, request_hash, &result);
+        Ok(result)
+    }
+
+    fn cached_result(&self, request: &VerificationRequest, request_hash: u64)
+        -> Option<VerificationResult>
+    {
+        if !config::server_cache_results() {
+            return None;
+        }
+        let sessions = self.sessions.lock().unwrap();
+        let result = sessions
+            .get(&request.crate_name)
+            .and_then(|session| session.results.get(&request.program_name))
+            .filter(|(hash, _)| *hash == request_hash)
+            .map(|(_, result)| result.clone());
+        let mut cache_stats = self.cache_stats.lock().unwrap();
+        if result.is_some() {
+            cache_stats.hits += 1;
+        } else {
+            cache_stats.misses += 1;
+        }
+        result
+    }
+
+    fn cache_result(
+        &self,
+        request: &VerificationRequest,
+        request_hash: u64,
+        result: &VerificationResult,
+    ) {
+        // Only cache results that do not depend on the state of the verifier.
+        let is_cacheable = match result {
+            VerificationResult::Success() | VerificationResult::Failure(_) => true,
+            _ => false,
+        };
+        if !config::server_cache_results() || !is_cacheable {
+            return;
+        }
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions
+            .entry(request.crate_name.clone())
+            .or_default()
+            .results
+            .insert(request.program_name.clone(), (request_hash, result.clone()));
+    }
+
+    fn run_verifier_uncached(&self, request: &VerificationRequest) -> RemoteVerificationResult {
         // try to find and take out an existing threads from our cache
         let existing_thread = {
             let mut threads = self.threads.write().unwrap();
@@ -74,10 +185,10 @@ impl PrustiServer {
         });
 
         match thread
-            .verify(request.program, request.program_name.clone())
+            .verify(request.program.clone(), request.qualified_program_name())
             .wait()
         {
-            Ok(result) => {
+            Ok(Ok(result)) => {
                 // put back the thread for later reuse
                 let mut threads = self.threads.write().unwrap();
                 if threads.len() >= self.cache_size {
@@ -87,14 +198,34 @@ impl PrustiServer {
                 threads.push_front(thread);
                 Ok(result)
             }
+            Ok(Err(message)) => {
+                error!(
+                    "Panic while handling verification request {}: {}",
+                    request.qualified_program_name(),
+                    message
+                );
+                Err(VerifierPanicked { message })
+            }
             Err(_) => {
-                // canceled—the verifier thread panicked
+                // canceled—the verifier thread stopped without an answer
                 error!(
                     "Panic while handling verification request {}",
-                    request.program_name
+                    request.qualified_program_name()
                 );
-                Err(VerifierPanicked)
+                Err(VerifierPanicked {
+                    message: "the verifier thread stopped".to_string(),
+                })
             }
         }
     }
 }
+
+/// Hashes everything that influences the result of a verification request.
+fn hash_request(request: &VerificationRequest) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bincode::serialize(&request.program)
+        .expect("could not encode program")
+        .hash(&mut hasher);
+    request.backend_config.cache_key().hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,116 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::VerifierRunner;
+use prusti_common::{
+    verification_context::VerifierBuilder, verification_service::ViperBackendConfig,
+    vir::Program,
+};
+use std::{
+    collections::{HashSet, VecDeque},
+    panic,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+use viper::VerificationResult;
+
+/// The programs that are not verified yet. An idle worker takes the next
+/// program, so that the work stays balanced even if some programs take much
+/// longer to verify than the others.
+struct JobQueue {
+    jobs: Mutex<VecDeque<(usize, String, Program)>>,
+}
+
+impl JobQueue {
+    fn next(&self) -> Option<(usize, String, Program)> {
+        self.jobs.lock().unwrap().pop_front()
+    }
+}
+
+/// Verify the programs, given with their name, with `jobs` verifiers running
+/// concurrently. The results, with the time spent verifying each program, are
+/// in the order of the programs.
+pub fn verify_in_parallel(
+    verifier_builder: Arc<VerifierBuilder>,
+    backend_config: &ViperBackendConfig,
+    programs: Vec<(String, Program)>,
+    jobs: usize,
+) -> Vec<(VerificationResult, Duration)> {
+    let programs_count = programs.len();
+    let queue = Arc::new(JobQueue {
+        jobs: Mutex::new(
+            programs
+                .into_iter()
+                .enumerate()
+                .map(|(index, (name, program))| (index, name, program))
+                .collect(),
+        ),
+    });
+    let (sender, receiver) = mpsc::channel();
+    let workers: Vec<_> = (0..jobs.min(programs_count))
+        .map(|job| {
+            let queue = queue.clone();
+            let sender = sender.clone();
+            let verifier_builder = verifier_builder.clone();
+            let backend_config = backend_config.clone();
+            thread::Builder::new()
+                .name(format!("Verifier job {}", job))
+                .spawn(move || {
+                    VerifierRunner::with_runner(&verifier_builder, &backend_config, |runner| {
+                        while let Some((index, name, program)) = queue.next() {
+                            let start = Instant::now();
+                            let result = runner.verify(program, &name);
+                            sender.send((index, (result, start.elapsed()))).unwrap();
+                        }
+                    })
+                })
+                .unwrap()
+        })
+        .collect();
+    drop(sender);
+
+    let mut results: Vec<Option<(VerificationResult, Duration)>> = vec![None; programs_count];
+    for (index, result) in receiver {
+        results[index] = Some(result);
+    }
+    for worker in workers {
+        if let Err(payload) = worker.join() {
+            panic::resume_unwind(payload);
+        }
+    }
+    results
+        .into_iter()
+        .map(|result| result.expect("a program was not verified"))
+        .collect()
+}
+
+/// Merge the results of the programs obtained by splitting a program, in
+/// their order. The declarations that are shared by the programs are
+/// verified with each of them, so their errors are reported only once.
+pub fn merge_verification_results(results: Vec<VerificationResult>) -> VerificationResult {
+    let mut verification_errors = vec![];
+    let mut consistency_errors = vec![];
+    for result in results {
+        match result {
+            VerificationResult::Success() => {}
+            VerificationResult::Failure(errors) => verification_errors.extend(errors),
+            VerificationResult::ConsistencyErrors(errors) => consistency_errors.extend(errors),
+            exception @ VerificationResult::JavaException(_) => return exception,
+        }
+    }
+    if !consistency_errors.is_empty() {
+        let mut seen = HashSet::new();
+        consistency_errors.retain(|error| seen.insert(error.clone()));
+        VerificationResult::ConsistencyErrors(consistency_errors)
+    } else if !verification_errors.is_empty() {
+        let mut seen = HashSet::new();
+        verification_errors.retain(|error| seen.insert(error.clone()));
+        VerificationResult::Failure(verification_errors)
+    } else {
+        VerificationResult::Success()
+    }
+}
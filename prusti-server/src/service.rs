@@ -4,14 +4,17 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use super::{PrustiServer, RemoteVerificationResult};
+use super::{JobQueue, PrustiServer, RemoteVerificationResult};
 use prusti_common::{config, verification_service::*};
 
 use bincode;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 use futures::{self};
 use num_cpus;
-use reqwest::{self, Client, Url, UrlError};
+use reqwest::{self, header, Client, RequestBuilder, Url, UrlError};
+use serde::{de::DeserializeOwned, Serialize};
 use std::{
+    io::{self, Read, Write},
     net::{Ipv4Addr, SocketAddr},
     sync::{mpsc, Arc},
     thread,
@@ -23,6 +26,7 @@ use warp::{self, Buf, Filter};
 #[derive(Clone)]
 pub struct ServerSideService {
     server: Arc<PrustiServer>,
+    jobs: Arc<JobQueue>,
     max_concurrency: usize,
 }
 
@@ -38,12 +42,19 @@ impl ServerSideService {
             warn!("PRUSTI_SERVER_MAX_STORED_VERIFIERS is lower than PRUSTI_SERVER_MAX_CONCURRENCY—you probably don't want to do this, since it means the server will likely have to keep creating new verifiers, reducing the performance gained from reuse.");
         }
 
+        let server = Arc::new(PrustiServer::new(cache_size));
         Self {
             max_concurrency,
-            server: Arc::new(PrustiServer::new(cache_size)),
+            jobs: Arc::new(JobQueue::new(server.clone())),
+            server,
         }
     }
 
+    /// Prepare a verifier, see `PrustiServer::warm_up`.
+    pub fn warm_up(&self) {
+        self.server.warm_up();
+    }
+
     pub fn spawn_off_thread() -> SocketAddr {
         let (sender, receiver) = mpsc::channel();
         thread::spawn(move || {
@@ -85,13 +96,9 @@ impl ServerSideService {
         let bincode_verify = warp::path("bincode")
             .and(warp::path("verify"))
             .and(warp::path::end())
+            .and(warp::header::optional::<String>("content-encoding"))
             .and(warp::body::concat())
-            .and_then(|buf: warp::body::FullBody| {
-                bincode::deserialize(&buf.bytes()).map_err(|err| {
-                    info!("request bincode body error: {}", err);
-                    warp::reject::custom(err)
-                })
-            })
+            .and_then(decode_bincode_body::<VerificationRequest>)
             .map(move |request: VerificationRequest| clone.verify(request))
             .map(|result| {
                 warp::http::Response::new(
@@ -99,7 +106,81 @@ impl ServerSideService {
                 )
             });
 
-        let endpoints = json_verify.or(bincode_verify);
+        let clone = self.clone();
+        let json_verify_batch = warp::path("json")
+            .and(warp::path("verify-batch"))
+            .and(warp::path::end())
+            .and(warp::body::json())
+            .map(move |requests: Vec<VerificationRequest>| clone.verify_batch(requests))
+            .map(|response| warp::reply::json(&response));
+
+        let clone = self.clone();
+        let bincode_verify_batch = warp::path("bincode")
+            .and(warp::path("verify-batch"))
+            .and(warp::path::end())
+            .and(warp::header::optional::<String>("content-encoding"))
+            .and(warp::body::concat())
+            .and_then(decode_bincode_body::<Vec<VerificationRequest>>)
+            .map(move |requests: Vec<VerificationRequest>| clone.verify_batch(requests))
+            .map(|results| {
+                warp::http::Response::new(
+                    bincode::serialize(&results).expect("could not encode verification results"),
+                )
+            });
+
+        let clone = self.clone();
+        let json_cache_stats = warp::path("json")
+            .and(warp::path("cache-stats"))
+            .and(warp::path::end())
+            .and(warp::get2())
+            .map(move || warp::reply::json(&clone.server.cache_stats()));
+
+        let clone = self.clone();
+        let json_submit_job = warp::path("json")
+            .and(warp::path("jobs"))
+            .and(warp::path::end())
+            .and(warp::post2())
+            .and(warp::body::json())
+            .map(move |requests: Vec<VerificationRequest>| clone.jobs.submit(requests))
+            .map(|id| warp::reply::json(&id));
+
+        let clone = self.clone();
+        let json_job_status = warp::path("json")
+            .and(warp::path("jobs"))
+            .and(warp::path::param::<JobId>())
+            .and(warp::path::end())
+            .and(warp::get2())
+            .and_then(move |id| clone.jobs.status(id).ok_or_else(warp::reject::not_found))
+            .map(|status| warp::reply::json(&status));
+
+        let clone = self.clone();
+        let json_job_results = warp::path("json")
+            .and(warp::path("jobs"))
+            .and(warp::path::param::<JobId>())
+            .and(warp::path("results"))
+            .and(warp::path::end())
+            .and(warp::get2())
+            .and_then(move |id| clone.jobs.results(id).ok_or_else(warp::reject::not_found))
+            .map(|results| match results {
+                Ok(results) => warp::reply::with_status(
+                    warp::reply::json(&results),
+                    warp::http::StatusCode::OK,
+                ),
+                // The job is known, but its results are not available yet.
+                Err(status) => warp::reply::with_status(
+                    warp::reply::json(&status),
+                    warp::http::StatusCode::ACCEPTED,
+                ),
+            });
+
+        let endpoints = json_verify
+            .or(bincode_verify)
+            .or(json_verify_batch)
+            .or(bincode_verify_batch)
+            .or(json_cache_stats)
+            .or(json_submit_job)
+            .or(json_job_status)
+            .or(json_job_results);
 
         info!("Prusti Server binding to port {}", port);
         let (address, server_handle) =
@@ -123,8 +204,19 @@ impl ServerSideService {
         thread::park();
     }
 
+    fn verify_batch(&self, requests: Vec<VerificationRequest>) -> Vec<RemoteVerificationResult> {
+        info!("Handling a batch of {} verification requests", requests.len());
+        requests
+            .into_iter()
+            .map(|request| self.verify(request))
+            .collect()
+    }
+
     fn verify(&self, request: VerificationRequest) -> RemoteVerificationResult {
-        info!("Handling verification request for {}", request.program_name);
+        info!(
+            "Handling verification request for {}",
+            request.qualified_program_name()
+        );
         self.server.run_verifier(request)
     }
 }
@@ -150,25 +242,96 @@ impl PrustiServerConnection {
         &self,
         request: VerificationRequest,
     ) -> reqwest::Result<RemoteVerificationResult> {
+        self.post("verify/", &request)
+    }
+
+    /// Sends several verification requests in a single round trip.
+    pub fn verify_batch_checked(
+        &self,
+        requests: Vec<VerificationRequest>,
+    ) -> reqwest::Result<Vec<RemoteVerificationResult>> {
+        self.post("verify-batch/", &requests)
+    }
+
+    /// The lookups in the cache of results of the server.
+    pub fn cache_stats(&self) -> reqwest::Result<CacheStats> {
+        self.get_json("json/cache-stats")
+    }
+
+    /// Queues a job that verifies `requests`, without waiting for it.
+    pub fn submit_job(&self, requests: &[VerificationRequest]) -> reqwest::Result<JobId> {
+        self.client
+            .post(self.server_url.join("json/jobs").unwrap())
+            .json(requests)
+            .send()?
+            .error_for_status()?
+            .json()
+    }
+
+    /// The status of a job submitted with `submit_job`.
+    pub fn job_status(&self, id: JobId) -> reqwest::Result<JobStatus> {
+        self.get_json(&format!("json/jobs/{}", id))
+    }
+
+    /// The results of a job, or `None` if it has not finished yet. Fails with
+    /// a 404 status if the job is unknown.
+    pub fn job_results(&self, id: JobId) -> reqwest::Result<Option<Vec<ProgramResult>>> {
+        let mut response = self.client
+            .get(self.server_url.join(&format!("json/jobs/{}/results", id)).unwrap())
+            .send()?
+            .error_for_status()?;
+        if response.status() == reqwest::StatusCode::ACCEPTED {
+            Ok(None)
+        } else {
+            response.json().map(Some)
+        }
+    }
+
+    fn get_json<Res: DeserializeOwned>(&self, endpoint: &str) -> reqwest::Result<Res> {
+        self.client
+            .get(self.server_url.join(endpoint).unwrap())
+            .send()?
+            .error_for_status()?
+            .json()
+    }
+
+    fn post<Req, Res>(&self, endpoint: &str, request: &Req) -> reqwest::Result<Res>
+    where
+        Req: Serialize,
+        Res: DeserializeOwned,
+    {
         let use_json = config::json_communication();
         let base = self.client.post(
             self.server_url
                 .join(if use_json { "json/" } else { "bincode/" })
                 .unwrap()
-                .join("verify/")
+                .join(endpoint)
                 .unwrap(),
         );
         let response = if use_json {
-            base.json(&request).send()?.error_for_status()?.json()?
+            base.json(request).send()?.error_for_status()?.json()?
         } else {
-            let raw = base
-                .body(bincode::serialize(&request).expect("error encoding verification request"))
+            let body = bincode::serialize(request).expect("error encoding verification request");
+            let raw = Self::with_bincode_body(base, body)
                 .send()?
                 .error_for_status()?;
             bincode::deserialize_from(raw).expect("error decoding verification result")
         };
         Ok(response)
     }
+
+    fn with_bincode_body(base: RequestBuilder, body: Vec<u8>) -> RequestBuilder {
+        if config::compress_server_requests() {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder
+                .write_all(&body)
+                .and_then(|_| encoder.finish())
+                .map(|compressed| base.header(header::CONTENT_ENCODING, "gzip").body(compressed))
+                .expect("error compressing verification request")
+        } else {
+            base.body(body)
+        }
+    }
 }
 
 impl VerificationService for PrustiServerConnection {
@@ -178,4 +341,44 @@ impl VerificationService for PrustiServerConnection {
             .expect("Verification request to server failed!")
             .expect("Server panicked while processing request!")
     }
+
+    /// panics if the verification request fails
+    fn verify_batch(&self, requests: Vec<VerificationRequest>) -> Vec<VerificationResult> {
+        self.verify_batch_checked(requests)
+            .expect("Verification request to server failed!")
+            .into_iter()
+            .map(|result| result.expect("Server panicked while processing request!"))
+            .collect()
+    }
+}
+
+/// Decodes a bincode request body, which the client may have compressed.
+fn decode_bincode_body<T: DeserializeOwned>(
+    content_encoding: Option<String>,
+    buf: warp::body::FullBody,
+) -> Result<T, warp::Rejection> {
+    let bytes = match content_encoding.as_ref().map(String::as_str) {
+        Some("gzip") => {
+            let mut decompressed = vec![];
+            GzDecoder::new(buf.bytes())
+                .read_to_end(&mut decompressed)
+                .map_err(|err| {
+                    info!("request gzip body error: {}", err);
+                    warp::reject::custom(err)
+                })?;
+            decompressed
+        }
+        None => buf.bytes().to_vec(),
+        Some(encoding) => {
+            info!("request has unsupported content encoding: {}", encoding);
+            return Err(warp::reject::custom(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported content encoding: {}", encoding),
+            )));
+        }
+    };
+    bincode::deserialize(&bytes).map_err(|err| {
+        info!("request bincode body error: {}", err);
+        warp::reject::custom(err)
+    })
 }
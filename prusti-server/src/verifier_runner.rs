@@ -57,20 +57,51 @@ impl<'v> VerifierRunner<'v> {
 
     pub fn verify(&self, program: Program, program_name: &str) -> VerificationResult {
         let mut stopwatch = Stopwatch::start("prusti-server", "construction of JVM objects");
-        let viper_program = program.to_viper(&self.ast_factory);
+        let mut viper_program = program.to_viper(&self.ast_factory);
+        if !program.viper_imports.is_empty() {
+            stopwatch.start_next("parsing of imported Viper sources");
+            viper_program = match self.import_viper_sources(viper_program, &program.viper_imports) {
+                Ok(viper_program) => viper_program,
+                Err(errors) => return VerificationResult::ConsistencyErrors(errors),
+            };
+        }
         if config::dump_viper_program() {
             stopwatch.start_next("dumping viper program");
             self.dump(viper_program, program_name);
         }
+        if config::dump_viper_program_json() {
+            stopwatch.start_next("dumping viper program as JSON");
+            self.dump_json(viper_program, program_name);
+        }
 
         stopwatch.start_next("verification");
         self.verifier.verify(viper_program)
     }
 
+    /// Merge the declarations of the handwritten Viper sources into the program.
+    fn import_viper_sources(
+        &self,
+        mut viper_program: viper::Program<'v>,
+        sources: &[String],
+    ) -> Result<viper::Program<'v>, Vec<String>> {
+        for source in sources {
+            let imported_program = self.ast_utils.parse_program(source)?;
+            viper_program = self.ast_factory.merge_programs(viper_program, imported_program);
+        }
+        Ok(viper_program)
+    }
+
     fn dump(&self, program: viper::Program, program_name: &str) {
         let namespace = "viper_program";
         let filename = format!("{}.vpr", program_name);
         info!("Dumping Viper program to '{}/{}'", namespace, filename);
         log::report(namespace, filename, self.ast_utils.pretty_print(program));
     }
+
+    fn dump_json(&self, program: viper::Program, program_name: &str) {
+        let namespace = "viper_program";
+        let filename = format!("{}.vpr.json", program_name);
+        info!("Dumping Viper program as JSON to '{}/{}'", namespace, filename);
+        log::report(namespace, filename, self.ast_utils.to_json(program).to_string());
+    }
 }
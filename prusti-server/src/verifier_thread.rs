@@ -10,17 +10,21 @@ use prusti_common::{
     verification_context::VerifierBuilder, verification_service::ViperBackendConfig, vir::Program,
 };
 use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
     sync::{mpsc, Arc, Mutex},
     thread,
 };
 use viper::VerificationResult;
 
-pub type FutVerificationResult = Box<dyn Future<Item = VerificationResult, Error = Canceled>>;
+/// The result of a verification, or the message of the panic of the verifier.
+pub type FutVerificationResult =
+    Box<dyn Future<Item = Result<VerificationResult, String>, Error = Canceled>>;
 
 struct VerificationRequest {
     pub program: Program,
     pub program_name: String,
-    pub sender: oneshot::Sender<VerificationResult>,
+    pub sender: oneshot::Sender<Result<VerificationResult, String>>,
 }
 
 pub struct VerifierThread {
@@ -57,13 +61,23 @@ impl VerifierThread {
         request_receiver: mpsc::Receiver<VerificationRequest>,
     ) {
         while let Ok(request) = request_receiver.recv() {
-            let result = runner.verify(request.program, request.program_name.as_str());
+            let program = request.program;
+            let program_name = request.program_name;
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                runner.verify(program, program_name.as_str())
+            }))
+            .map_err(|payload| panic_message(&*payload));
+            let panicked = result.is_err();
             request.sender.send(result).unwrap_or_else(|err| {
                 error!(
                     "verifier thread attempting to send result to dropped receiver: {:?}",
                     err
                 );
             });
+            // The verifier might be left in an inconsistent state.
+            if panicked {
+                break;
+            }
         }
     }
 
@@ -81,3 +95,14 @@ impl VerifierThread {
         Box::new(rx)
     }
 }
+
+/// The message given to `panic!`, if it is a string.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "the verifier panicked".to_string()
+    }
+}
@@ -5,10 +5,17 @@ extern crate viper;
 extern crate lazy_static;
 
 use prusti_common::{
-    verification_service::{VerificationRequest, VerificationService},
+    config,
+    verification_service::{
+        JobState, ProgramOutcome, VerificationRequest, VerificationService,
+    },
     vir::*,
 };
-use prusti_server::{PrustiServerConnection, ServerSideService};
+use prusti_server::{merge_verification_results, PrustiServerConnection, ServerSideService};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
 use viper::VerificationResult;
 
 lazy_static! {
@@ -34,6 +41,40 @@ fn consistency_error() {
     }
 }
 
+#[test]
+fn viper_import() {
+    let result = process_program(|program| {
+        program.viper_imports.push(
+            "domain Pair { function first(x: Int, y: Int): Int \
+             axiom { forall x: Int, y: Int :: first(x, y) == x } }"
+                .to_string(),
+        );
+    });
+
+    match result {
+        VerificationResult::Success() => (),
+        other => panic!(
+            "program with a Viper import not verified successfully, instead found {:?}",
+            other
+        ),
+    }
+}
+
+#[test]
+fn invalid_viper_import() {
+    let result = process_program(|program| {
+        program.viper_imports.push("function f(): Int { undefined }".to_string());
+    });
+
+    match result {
+        VerificationResult::ConsistencyErrors(errors) => println!("errors: {:?}", errors),
+        other => panic!(
+            "invalid Viper import not identified, instead found {:?}",
+            other
+        ),
+    }
+}
+
 #[test]
 fn empty_program() {
     let result = process_program(|_| ());
@@ -47,26 +88,207 @@ fn empty_program() {
     }
 }
 
-fn process_program<F>(configure: F) -> VerificationResult
-where
-    F: FnOnce(&mut Program),
-{
+#[test]
+fn crate_namespaces() {
+    // Programs with the same name but from different crates are given
+    // distinct names in the backend.
+    let request_in_crate = |crate_name: &str| VerificationRequest {
+        program: empty_program_definition(),
+        program_name: "dummy".to_string(),
+        crate_name: crate_name.to_string(),
+        backend_config: Default::default(),
+    };
+    let name_a = request_in_crate("crate_a").qualified_program_name();
+    let name_b = request_in_crate("crate_b").qualified_program_name();
+    assert_ne!(name_a, name_b);
+    assert_eq!(name_a, "crate_a.dummy");
+
+    // They must not share cached results either.
+    let invalid = process_program_in_crate("crate_a", |program| {
+        program.fields.push(Field {
+            name: "field".to_string(), // reserved identifier
+            typ: Type::Int,
+        });
+    });
+    let valid = process_program_in_crate("crate_b", |_| ());
+
+    match (invalid, valid) {
+        (VerificationResult::ConsistencyErrors(_), VerificationResult::Success()) => (),
+        other => panic!(
+            "programs of different crates were not kept apart, instead found {:?}",
+            other
+        ),
+    }
+}
+
+#[test]
+fn batched_requests() {
     let service =
         PrustiServerConnection::new(SERVER_ADDRESS.clone()).expect("Could not connect to server!");
 
-    let mut program = Program {
+    let mut invalid_program = empty_program_definition();
+    invalid_program.fields.push(Field {
+        name: "field".to_string(), // reserved identifier
+        typ: Type::Int,
+    });
+    // A method that fails to verify at a known source position.
+    let mut failing_program = empty_program_definition();
+    let mut method = CfgMethod::new("failing".to_string(), 0, vec![], vec![], vec![]);
+    let block = method.add_block(
+        "start",
+        vec![Stmt::Assert(false.into(), FoldingBehaviour::Stmt, Position::new(3, 7, 42))],
+    );
+    method.set_successor(block, Successor::Return);
+    failing_program.methods.push(method);
+    let requests = vec![
+        VerificationRequest {
+            program: empty_program_definition(),
+            program_name: "valid".to_string(),
+            crate_name: "jobs_crate".to_string(),
+            backend_config: Default::default(),
+        },
+        VerificationRequest {
+            program: invalid_program,
+            program_name: "invalid".to_string(),
+            crate_name: "jobs_crate".to_string(),
+            backend_config: Default::default(),
+        },
+        VerificationRequest {
+            program: failing_program,
+            program_name: "failing".to_string(),
+            crate_name: "jobs_crate".to_string(),
+            backend_config: Default::default(),
+        },
+    ];
+
+    let id = service.submit_job(&requests).expect("Could not submit the job!");
+    let deadline = Instant::now() + Duration::from_secs(300);
+    let status = loop {
+        let status = service.job_status(id).expect("Could not read the status of the job!");
+        if status.state == JobState::Finished {
+            break status;
+        }
+        assert!(Instant::now() < deadline, "the job did not finish in time: {:?}", status);
+        thread::sleep(Duration::from_millis(100));
+    };
+    assert_eq!((status.programs, status.verified_programs), (3, 3));
+
+    let results = service.job_results(id)
+        .expect("Could not read the results of the job!")
+        .expect("The results of the finished job are not available!");
+    let outcomes: Vec<_> = results
+        .iter()
+        .map(|result| (result.program_name.as_str(), result.outcome))
+        .collect();
+    assert_eq!(
+        outcomes,
+        vec![
+            ("valid", ProgramOutcome::Success),
+            ("invalid", ProgramOutcome::ConsistencyErrors),
+            ("failing", ProgramOutcome::Failure),
+        ]
+    );
+    let errors = &results[2].errors;
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].method.as_deref(), Some("failing"));
+    assert_eq!((errors[0].line, errors[0].column), (Some(3), Some(7)));
+
+    assert!(service.job_status(id + 1).is_err(), "unknown jobs are reported as missing");
+    assert!(service.job_results(id + 1).is_err(), "unknown jobs are reported as missing");
+}
+
+#[test]
+fn compressed_requests() {
+    // The requests of the tests that run meanwhile are compressed as well,
+    // which does not change their results.
+    let _compression = config::override_settings(&[
+        ("COMPRESS_SERVER_REQUESTS".to_string(), "true".to_string()),
+    ]);
+    assert!(config::compress_server_requests() && !config::json_communication());
+    let service =
+        PrustiServerConnection::new(SERVER_ADDRESS.clone()).expect("Could not connect to server!");
+
+    let mut invalid_program = empty_program_definition();
+    invalid_program.fields.push(Field {
+        name: "field".to_string(), // reserved identifier
+        typ: Type::Int,
+    });
+    let request = |program_name: &str, program: Program| VerificationRequest {
+        program,
+        program_name: program_name.to_string(),
+        crate_name: "compressed_crate".to_string(),
+        backend_config: Default::default(),
+    };
+
+    match service.verify(request("single", invalid_program.clone())) {
+        VerificationResult::ConsistencyErrors(_) => (),
+        other => panic!(
+            "compressed request not decoded by the server, instead found {:?}",
+            other
+        ),
+    }
+
+    let results = service.verify_batch(vec![
+        request("batch_valid", empty_program_definition()),
+        request("batch_invalid", invalid_program),
+    ]);
+    match &results[..] {
+        [VerificationResult::Success(), VerificationResult::ConsistencyErrors(_)] => (),
+        other => panic!(
+            "compressed batch request not decoded by the server, instead found {:?}",
+            other
+        ),
+    }
+}
+
+#[test]
+fn merged_results() {
+    // The errors of the declarations shared by split programs are reported once.
+    let merged = merge_verification_results(vec![
+        VerificationResult::Success(),
+        VerificationResult::ConsistencyErrors(vec!["shared".to_string()]),
+        VerificationResult::ConsistencyErrors(vec!["shared".to_string(), "other".to_string()]),
+    ]);
+
+    assert_eq!(
+        merged,
+        VerificationResult::ConsistencyErrors(vec!["shared".to_string(), "other".to_string()])
+    );
+}
+
+fn empty_program_definition() -> Program {
+    Program {
         domains: vec![],
         fields: vec![],
         builtin_methods: vec![],
         methods: vec![],
         functions: vec![],
         viper_predicates: vec![],
-    };
+        viper_imports: vec![],
+    }
+}
+
+fn process_program<F>(configure: F) -> VerificationResult
+where
+    F: FnOnce(&mut Program),
+{
+    process_program_in_crate("dummy_crate", configure)
+}
+
+fn process_program_in_crate<F>(crate_name: &str, configure: F) -> VerificationResult
+where
+    F: FnOnce(&mut Program),
+{
+    let service =
+        PrustiServerConnection::new(SERVER_ADDRESS.clone()).expect("Could not connect to server!");
+
+    let mut program = empty_program_definition();
     configure(&mut program);
 
     let request = VerificationRequest {
         program,
         program_name: "dummy".to_string(),
+        crate_name: crate_name.to_string(),
         backend_config: Default::default(),
     };
 
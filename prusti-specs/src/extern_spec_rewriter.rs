@@ -8,7 +8,8 @@ use crate::span_overrider::SpanOverrider;
 
 /// Process external specifications in Rust modules marked with the
 /// #[extern_spec] attribute. Nested modules are processed recursively.
-/// Specifications are collected from functions and function stubs.
+/// Specifications are collected from functions, function stubs and the
+/// functions declared in `extern` blocks.
 ///
 /// Modules are rewritten so that their name does not clash with the module
 /// they are specifying.
@@ -22,8 +23,16 @@ pub fn rewrite_mod(item_mod: &mut syn::ItemMod, path: &mut syn::Path) -> syn::Re
     item_mod.ident = syn::Ident::new(&name_generator.generate_mod_name(&item_mod.ident),
                                     item_mod.span());
 
-    for item in item_mod.content.as_mut().unwrap().1.iter_mut() {
-        match item {
+    let items = std::mem::replace(&mut item_mod.content.as_mut().unwrap().1, vec![]);
+    let mut new_items = Vec::with_capacity(items.len());
+    for mut item in items {
+        match &mut item {
+            syn::Item::ForeignMod(foreign_mod) => {
+                for foreign_item in foreign_mod.items.drain(..) {
+                    new_items.push(syn::Item::Fn(rewrite_foreign_fn(foreign_item, path)?));
+                }
+                continue;
+            }
             syn::Item::Fn(item_fn) => {
                 rewrite_fn(item_fn, path);
             },
@@ -64,10 +73,45 @@ pub fn rewrite_mod(item_mod: &mut syn::ItemMod, path: &mut syn::Path) -> syn::Re
                 "unexpected item",
             ))
         }
+        new_items.push(item);
     }
+    item_mod.content.as_mut().unwrap().1 = new_items;
     Ok(())
 }
 
+/// Rewrite a function declared in an `extern` block to a specification
+/// function. Since calling a foreign function is unsafe, the call to the
+/// specified function is wrapped in an `unsafe` block.
+fn rewrite_foreign_fn(
+    foreign_item: syn::ForeignItem,
+    path: &mut syn::Path,
+) -> syn::Result<syn::ItemFn> {
+    let foreign_fn = match foreign_item {
+        syn::ForeignItem::Fn(foreign_fn) => foreign_fn,
+        other => return Err(syn::Error::new(
+            other.span(),
+            "expected a function declaration",
+        )),
+    };
+    let ident = &foreign_fn.sig.ident;
+    let args = &foreign_fn.sig.inputs;
+    let item_span = foreign_fn.span();
+    let mut item_fn = syn::ItemFn {
+        attrs: foreign_fn.attrs.clone(),
+        vis: foreign_fn.vis.clone(),
+        sig: foreign_fn.sig.clone(),
+        block: parse_quote_spanned! {item_span=>
+            {
+                unsafe { #path :: #ident (#args); }
+                unimplemented!()
+            }
+        },
+    };
+    item_fn.attrs.push(parse_quote_spanned!(item_span=> #[prusti::extern_spec]));
+    item_fn.attrs.push(parse_quote_spanned!(item_span=> #[trusted]));
+    Ok(item_fn)
+}
+
 /// Rewrite a specification function to a call to the specified function.
 /// The result of this rewriting is then parsed in `ExternSpecResolver`.
 fn rewrite_fn(item_fn: &mut syn::ItemFn, path: &mut syn::Path) {
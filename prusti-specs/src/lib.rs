@@ -8,16 +8,26 @@ mod span_overrider;
 mod extern_spec_rewriter;
 mod rewriter;
 mod parse_closure_macro;
+mod parse_predicate_macro;
+mod parse_ghost_macro;
+mod parse_in_state_attr;
+mod parse_viper_import_attr;
+mod parse_state_machine_macro;
 mod spec_attribute_kind;
 pub mod specifications;
 
-use proc_macro2::{Span, TokenStream};
-use quote::{quote, quote_spanned, ToTokens};
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{quote, quote_spanned, format_ident, ToTokens};
 use syn::spanned::Spanned;
 use std::convert::{TryFrom, TryInto};
 
 use specifications::untyped;
 use parse_closure_macro::ClosureWithSpec;
+use parse_predicate_macro::PredicateWithBody;
+use parse_ghost_macro::GhostBlock;
+use parse_in_state_attr::InState;
+use parse_viper_import_attr::ViperImport;
+use parse_state_machine_macro::StateMachine;
 pub use spec_attribute_kind::SpecAttributeKind;
 
 macro_rules! handle_result {
@@ -30,7 +40,11 @@ macro_rules! handle_result {
 }
 
 fn extract_prusti_attributes<'a>(item: &'a mut untyped::AnyFnItem) -> impl Iterator<Item=(SpecAttributeKind, TokenStream)> + 'a {
-    item.attrs_mut().drain_filter(
+    extract_prusti_attributes_from(item.attrs_mut())
+}
+
+fn extract_prusti_attributes_from<'a>(attrs: &'a mut Vec<syn::Attribute>) -> impl Iterator<Item=(SpecAttributeKind, TokenStream)> + 'a {
+    attrs.drain_filter(
         |attr|
             attr.path.segments.len() == 1
                 && SpecAttributeKind::try_from(attr.path.segments[0].ident.to_string()).is_ok()
@@ -50,6 +64,13 @@ pub fn rewrite_prusti_attributes(
     outer_attr_tokens: TokenStream,
     item_tokens: TokenStream,
 ) -> TokenStream {
+    if let Ok(impl_block) = syn::parse2::<syn::ItemImpl>(item_tokens.clone()) {
+        return handle_result!(rewrite_impl_block(
+            vec![(outer_attr_kind, outer_attr_tokens)],
+            impl_block,
+        ));
+    }
+
     let mut item: untyped::AnyFnItem = handle_result!(syn::parse2(item_tokens));
 
     // Start with the outer attribute
@@ -80,6 +101,14 @@ fn generate_spec_and_assertions(
 ) -> GeneratedResult {
     let mut generated_items = vec![];
     let mut generated_attributes = vec![];
+    let is_bitvector = prusti_attributes.iter().any(|(attr_kind, _)| match attr_kind {
+        SpecAttributeKind::Bitvector => true,
+        _ => false,
+    });
+    let is_bit_precise = prusti_attributes.iter().any(|(attr_kind, _)| match attr_kind {
+        SpecAttributeKind::BitPrecise => true,
+        _ => false,
+    });
 
     for (attr_kind, attr_tokens) in prusti_attributes.drain(..) {
         let rewriting_result = match attr_kind {
@@ -89,21 +118,102 @@ fn generate_spec_and_assertions(
             SpecAttributeKind::AfterExpiryIf => generate_for_after_expiry_if(attr_tokens, item),
             SpecAttributeKind::Pure => generate_for_pure(attr_tokens, item),
             SpecAttributeKind::Trusted => generate_for_trusted(attr_tokens, item),
+            SpecAttributeKind::Decreases => generate_for_decreases(attr_tokens, item),
+            SpecAttributeKind::Bitvector => generate_for_bitvector(attr_tokens, item),
+            SpecAttributeKind::BitPrecise => generate_for_bit_precise(attr_tokens, item),
+            SpecAttributeKind::InState => generate_for_in_state(attr_tokens, item),
+            SpecAttributeKind::Complexity => generate_for_complexity(attr_tokens, item),
+            SpecAttributeKind::ViperImport => generate_for_viper_import(attr_tokens, item),
+            SpecAttributeKind::Lemma => generate_for_lemma(attr_tokens, item, false),
+            SpecAttributeKind::TrustedProof => generate_for_lemma(attr_tokens, item, true),
+            SpecAttributeKind::ThreadSafetyInvariant => Err(syn::Error::new(
+                item.span(),
+                "#[thread_safety_invariant] can only be attached to an impl block",
+            )),
         };
         let (new_items, new_attributes) = rewriting_result?;
         generated_items.extend(new_items);
         generated_attributes.extend(new_attributes);
     }
 
+    // The specifications of a bit-precise function are bit-precise as well.
+    if is_bitvector {
+        for generated_item in &mut generated_items {
+            if let syn::Item::Fn(item_fn) = generated_item {
+                item_fn.attrs.push(parse_quote_spanned! {item_fn.span()=>
+                    #[prusti::bitvector]
+                });
+            }
+        }
+    }
+    if is_bit_precise {
+        for generated_item in &mut generated_items {
+            if let syn::Item::Fn(item_fn) = generated_item {
+                item_fn.attrs.push(parse_quote_spanned! {item_fn.span()=>
+                    #[prusti::bit_precise]
+                });
+            }
+        }
+    }
+
     Ok((generated_items, generated_attributes))
 }
 
+/// Split a postcondition of the form `|<name>| <assertion>`, which binds the
+/// return value to `<name>` instead of `result`, into the name and the
+/// assertion.
+fn split_result_name(attr: TokenStream) -> (Option<syn::Ident>, TokenStream) {
+    let mut tokens: Vec<TokenTree> = attr.into_iter().collect();
+    if tokens.len() > 3 {
+        if let (TokenTree::Punct(open), TokenTree::Ident(name), TokenTree::Punct(close)) =
+            (&tokens[0], &tokens[1], &tokens[2])
+        {
+            if open.as_char() == '|' && close.as_char() == '|' {
+                let name = name.clone();
+                let assertion = tokens.split_off(3).into_iter().collect();
+                return (Some(name), assertion);
+            }
+        }
+    }
+    (None, tokens.into_iter().collect())
+}
+
+/// Rewrite a specification with a trailing `when = "<condition>"` case into
+/// the implication `(<condition>) ==> (<specification>)`. Specifications
+/// without such a case are returned unchanged.
+fn expand_spec_case(attr: TokenStream) -> syn::Result<TokenStream> {
+    let tokens: Vec<TokenTree> = attr.into_iter().collect();
+    let len = tokens.len();
+    if len >= 4 {
+        if let (
+            TokenTree::Punct(comma),
+            TokenTree::Ident(keyword),
+            TokenTree::Punct(eq),
+            TokenTree::Literal(literal),
+        ) = (&tokens[len - 4], &tokens[len - 3], &tokens[len - 2], &tokens[len - 1]) {
+            if comma.as_char() == ',' && keyword == "when" && eq.as_char() == '=' {
+                let literal: syn::LitStr = syn::parse2(literal.to_token_stream())?;
+                let condition: TokenStream = literal.parse()?;
+                let spec: TokenStream = tokens[..len - 4].iter().cloned().collect();
+                if spec.is_empty() {
+                    return Err(syn::Error::new(
+                        comma.span(),
+                        "expected a specification before the `when` case",
+                    ));
+                }
+                return Ok(quote_spanned! {literal.span()=> (#condition) ==> (#spec) });
+            }
+        }
+    }
+    Ok(tokens.into_iter().collect())
+}
+
 /// Generate spec items and attributes to typecheck the and later retrieve "requires" annotations.
 fn generate_for_requires(attr: TokenStream, item: &untyped::AnyFnItem) -> GeneratedResult {
     let mut rewriter = rewriter::AstRewriter::new();
     let spec_id = rewriter.generate_spec_id();
     let spec_id_str = spec_id.to_string();
-    let assertion = rewriter.parse_assertion(spec_id, attr)?;
+    let assertion = rewriter.parse_assertion(spec_id, expand_spec_case(attr)?)?;
     let spec_item = rewriter.generate_spec_item_fn(
         rewriter::SpecItemType::Precondition,
         spec_id,
@@ -123,12 +233,15 @@ fn generate_for_ensures(attr: TokenStream, item: &untyped::AnyFnItem) -> Generat
     let mut rewriter = rewriter::AstRewriter::new();
     let spec_id = rewriter.generate_spec_id();
     let spec_id_str = spec_id.to_string();
-    let assertion = rewriter.parse_assertion(spec_id, attr)?;
-    let spec_item = rewriter.generate_spec_item_fn(
+    let (result_name, attr) = split_result_name(attr);
+    let result_name = result_name.unwrap_or_else(|| syn::Ident::new("result", item.span()));
+    let assertion = rewriter.parse_assertion(spec_id, expand_spec_case(attr)?)?;
+    let spec_item = rewriter.generate_spec_item_fn_with_result(
         rewriter::SpecItemType::Postcondition,
         spec_id,
         assertion,
-        &item
+        &item,
+        &result_name,
     )?;
     Ok((
         vec![spec_item],
@@ -211,15 +324,149 @@ fn generate_for_after_expiry_if(attr: TokenStream, item: &untyped::AnyFnItem) ->
 }
 
 /// Generate spec items and attributes to typecheck and later retrieve "pure" annotations.
+///
+/// A pure trait method without a default body gets a spec-only provided
+/// method with the same signature, whose MIR gives the arguments of the
+/// abstract function that is used when the receiver is a type parameter.
+/// The provided method requires `Self: Sized`, so that it type-checks when
+/// `self` is taken by value or `Self` is returned, and it does not affect
+/// whether the trait can be made into an object.
 fn generate_for_pure(_attr: TokenStream, item: &untyped::AnyFnItem) -> GeneratedResult {
+    let mut spec_items = vec![];
+    if let untyped::AnyFnItem::TraitMethod(trait_method) = item {
+        if trait_method.default.is_none() {
+            let mut sig = trait_method.sig.clone();
+            sig.ident = format_ident!("prusti_pure_signature_{}", sig.ident);
+            sig.generics.make_where_clause().predicates.push(syn::parse_quote!(Self: Sized));
+            spec_items.push(parse_quote_spanned! {item.span()=>
+                #[allow(unused_variables, dead_code)]
+                #[doc(hidden)]
+                #[prusti::spec_only]
+                #sig {
+                    unimplemented!()
+                }
+            });
+        }
+    }
     Ok((
-        vec![],
+        spec_items,
         vec![parse_quote_spanned! {item.span()=>
             #[prusti::pure]
         }],
     ))
 }
 
+/// Generate spec items and attributes to typecheck and later retrieve "lemma"
+/// and "trusted_proof" annotations. A lemma is a pure function that returns
+/// `true` whenever its precondition holds, so that its calls instantiate its
+/// postcondition. The proof of a `#[trusted_proof]` lemma is not verified.
+fn generate_for_lemma(
+    _attr: TokenStream,
+    item: &untyped::AnyFnItem,
+    is_trusted: bool,
+) -> GeneratedResult {
+    let returns_bool = match &item.sig().output {
+        syn::ReturnType::Type(_, box syn::Type::Path(syn::TypePath { qself: None, path })) =>
+            path.is_ident("bool"),
+        _ => false,
+    };
+    if !returns_bool {
+        return Err(syn::Error::new(
+            item.sig().output.span(),
+            "a lemma must return `bool`",
+        ));
+    }
+    let mut attributes = vec![
+        parse_quote_spanned! {item.span()=>
+            #[prusti::pure]
+        },
+        parse_quote_spanned! {item.span()=>
+            #[prusti::lemma]
+        },
+    ];
+    if is_trusted {
+        attributes.push(parse_quote_spanned! {item.span()=>
+            #[prusti::trusted]
+        });
+    }
+    Ok((vec![], attributes))
+}
+
+/// Generate spec items and attributes to typecheck and later retrieve "bitvector" annotations.
+fn generate_for_bitvector(_attr: TokenStream, item: &untyped::AnyFnItem) -> GeneratedResult {
+    Ok((
+        vec![],
+        vec![parse_quote_spanned! {item.span()=>
+            #[prusti::bitvector]
+        }],
+    ))
+}
+
+/// Generate spec items and attributes to typecheck and later retrieve "bit_precise" annotations.
+fn generate_for_bit_precise(_attr: TokenStream, item: &untyped::AnyFnItem) -> GeneratedResult {
+    Ok((
+        vec![],
+        vec![parse_quote_spanned! {item.span()=>
+            #[prusti::bit_precise]
+        }],
+    ))
+}
+
+/// Generate spec items and attributes to typecheck and later retrieve "complexity" annotations.
+/// The bound is encoded as the assertion `credits() == bound`, which defines
+/// the number of credits available to the procedure.
+fn generate_for_complexity(attr: TokenStream, item: &untyped::AnyFnItem) -> GeneratedResult {
+    if attr.is_empty() {
+        return Err(syn::Error::new(
+            item.span(),
+            "a `complexity` annotation requires a bound",
+        ));
+    }
+    let mut rewriter = rewriter::AstRewriter::new();
+    let spec_id = rewriter.generate_spec_id();
+    let spec_id_str = spec_id.to_string();
+    let bound = quote_spanned! {attr.span()=> prusti_contracts::credits() == (#attr) };
+    let assertion = rewriter.parse_assertion(spec_id, bound)?;
+    let spec_item = rewriter.generate_spec_item_fn(
+        rewriter::SpecItemType::Complexity,
+        spec_id,
+        assertion,
+        &item
+    )?;
+    Ok((
+        vec![spec_item],
+        vec![parse_quote_spanned! {item.span()=>
+            #[prusti::complexity_spec_id_ref = #spec_id_str]
+        }],
+    ))
+}
+
+/// Generate the precondition and postcondition of an `#[in_state(...)]`
+/// annotation, which restricts a method to a state of the ghost state machine
+/// stored in a field of its receiver.
+fn generate_for_in_state(attr: TokenStream, item: &untyped::AnyFnItem) -> GeneratedResult {
+    if item.sig().receiver().is_none() {
+        return Err(syn::Error::new(
+            item.span(),
+            "#[in_state] can only be attached to a method with a receiver",
+        ));
+    }
+    let InState { field, from, to } = syn::parse2(attr)?;
+    let (mut generated_items, mut generated_attributes) = generate_for_requires(
+        quote_spanned! {from.span()=> self.#field.is(#from) },
+        item,
+    )?;
+    if let Some(to) = to {
+        let (new_items, new_attributes) = generate_for_ensures(
+            quote_spanned! {to.span()=> self.#field.is(#to) },
+            item,
+        )?;
+        generated_items.extend(new_items);
+        generated_attributes.extend(new_attributes);
+    }
+    Ok((generated_items, generated_attributes))
+}
+
 /// Generate spec items and attributes to typecheck and later retrieve "trusted" annotations.
 fn generate_for_trusted(_attr: TokenStream, item: &untyped::AnyFnItem) -> GeneratedResult {
     Ok((
@@ -230,6 +477,93 @@ fn generate_for_trusted(_attr: TokenStream, item: &untyped::AnyFnItem) -> Genera
     ))
 }
 
+/// Generate spec items and attributes to typecheck and later retrieve "viper_import" annotations.
+/// The annotated function is pure and trusted, and its calls are encoded as
+/// calls of the function with the given name of the given Viper file.
+fn generate_for_viper_import(attr: TokenStream, item: &untyped::AnyFnItem) -> GeneratedResult {
+    let ViperImport { file, name } = syn::parse2(attr)?;
+    Ok((
+        vec![],
+        vec![
+            parse_quote_spanned! {item.span()=>
+                #[prusti::pure]
+            },
+            parse_quote_spanned! {item.span()=>
+                #[prusti::trusted]
+            },
+            parse_quote_spanned! {item.span()=>
+                #[prusti::viper_import_file = #file]
+            },
+            parse_quote_spanned! {item.span()=>
+                #[prusti::viper_import_name = #name]
+            },
+        ],
+    ))
+}
+
+/// Generate spec items and attributes to typecheck and later retrieve "decreases" annotations.
+/// The termination measure is encoded as the assertion
+/// `termination_measure() == measure`.
+fn generate_for_decreases(attr: TokenStream, item: &untyped::AnyFnItem) -> GeneratedResult {
+    if attr.is_empty() {
+        return Err(syn::Error::new(
+            item.span(),
+            "a `decreases` annotation requires a termination measure",
+        ));
+    }
+    let mut rewriter = rewriter::AstRewriter::new();
+    let spec_id = rewriter.generate_spec_id();
+    let spec_id_str = spec_id.to_string();
+    let assertion = rewriter.parse_assertion(spec_id, termination_measure_equality(attr))?;
+    let spec_item = rewriter.generate_spec_item_fn(
+        rewriter::SpecItemType::Decreases,
+        spec_id,
+        assertion,
+        &item
+    )?;
+    Ok((
+        vec![spec_item],
+        vec![
+            parse_quote_spanned! {item.span()=>
+                #[prusti::decreases]
+            },
+            parse_quote_spanned! {item.span()=>
+                #[prusti::decreases_spec_id_ref = #spec_id_str]
+            },
+        ],
+    ))
+}
+
+/// The assertion that defines the termination measure `measure`, which must
+/// have an integer type.
+fn termination_measure_equality(measure: TokenStream) -> TokenStream {
+    quote_spanned! {measure.span()=>
+        prusti_contracts::termination_measure() == ((#measure) as i128)
+    }
+}
+
+pub fn body_variant(tokens: TokenStream) -> TokenStream {
+    if tokens.is_empty() {
+        return syn::Error::new(
+            Span::call_site(),
+            "a `body_variant` requires a termination measure",
+        ).to_compile_error();
+    }
+    let mut rewriter = rewriter::AstRewriter::new();
+    let spec_id = rewriter.generate_spec_id();
+    let variant = handle_result!(
+        rewriter.parse_assertion(spec_id, termination_measure_equality(tokens))
+    );
+    let check = rewriter.generate_spec_loop_variant(spec_id, variant);
+    let callsite_span = Span::call_site();
+    quote_spanned! {callsite_span=>
+        #[allow(unused_must_use, unused_variables)]
+        if false {
+            #check
+        }
+    }
+}
+
 pub fn body_invariant(tokens: TokenStream) -> TokenStream {
     let mut rewriter = rewriter::AstRewriter::new();
     let spec_id = rewriter.generate_spec_id();
@@ -244,6 +578,52 @@ pub fn body_invariant(tokens: TokenStream) -> TokenStream {
     }
 }
 
+/// Generate the statements of a `ghost! { ... }` block. They are kept when
+/// verifying (`drop_ghost` is false), so that they are type-checked and
+/// encoded like the rest of the function, and erased otherwise. The
+/// statements are not wrapped in a block, so that the ghost variables that
+/// they declare can be used by later ghost code and by the specifications.
+///
+/// The statements are surrounded by two spec-only closures, spanned at the
+/// first and the last token of the block, from which the verifier recovers
+/// the region of the ghost code to check that it does not modify the rest of
+/// the program.
+pub fn ghost(tokens: TokenStream, drop_ghost: bool) -> TokenStream {
+    let first_span = tokens.clone().into_iter().next().map(|token| token.span());
+    let last_span = tokens.clone().into_iter().last().map(|token| token.span());
+    let ghost_block: GhostBlock = handle_result!(syn::parse2(tokens));
+    if drop_ghost {
+        return TokenStream::new();
+    }
+    let (first_span, last_span) = match (first_span, last_span) {
+        (Some(first_span), Some(last_span)) => (first_span, last_span),
+        _ => return TokenStream::new(),
+    };
+    let stmts = ghost_block.stmts;
+    let callsite_span = Span::call_site();
+    let begin = quote_spanned! {first_span=>
+        #[prusti::spec_only]
+        #[prusti::ghost_begin]
+        || {};
+    };
+    let end = quote_spanned! {last_span=>
+        #[prusti::spec_only]
+        #[prusti::ghost_end]
+        || {};
+    };
+    quote_spanned! {callsite_span=>
+        #[allow(unused_must_use, unused_variables)]
+        if false {
+            #begin
+        }
+        #(#stmts)*
+        #[allow(unused_must_use, unused_variables)]
+        if false {
+            #end
+        }
+    }
+}
+
 /// Unlike the functions above, which are only called from
 /// prusti-contracts-internal, this function also needs to be called
 /// from prusti-contracts-impl, because we still need to parse the
@@ -332,18 +712,300 @@ pub fn closure(tokens: TokenStream, drop_spec: bool) -> TokenStream {
     }
 }
 
+/// Rewrite a `predicate!` definition into a pure function, whose body is
+/// given by the assertion, and a spec item to typecheck the assertion. Like
+/// `closure`, this is also called from prusti-contracts-impl, with `drop_spec`
+/// set, to keep only the function.
+pub fn predicate(tokens: TokenStream, drop_spec: bool) -> TokenStream {
+    let predicate: PredicateWithBody = handle_result!(syn::parse2(tokens));
+    let callsite_span = Span::call_site();
+    let PredicateWithBody { attrs, vis, sig, body } = predicate;
+    let item: syn::ItemFn = parse_quote_spanned! {callsite_span=>
+        #(#attrs)*
+        #[allow(dead_code)]
+        #vis #sig {
+            unimplemented!("predicates can only be used in specifications")
+        }
+    };
+
+    if drop_spec {
+        return quote_spanned! {callsite_span=> #item };
+    }
+
+    let mut rewriter = rewriter::AstRewriter::new();
+    let spec_id = rewriter.generate_spec_id();
+    let spec_id_str = spec_id.to_string();
+    let assertion = handle_result!(rewriter.parse_assertion(spec_id, body));
+    let item = untyped::AnyFnItem::Fn(item);
+    let spec_item = handle_result!(rewriter.generate_spec_item_fn(
+        rewriter::SpecItemType::Predicate,
+        spec_id,
+        assertion,
+        &item
+    ));
+    quote_spanned! {callsite_span=>
+        #spec_item
+        #[prusti::pure]
+        #[prusti::trusted]
+        #[prusti::pred_spec_id_ref = #spec_id_str]
+        #item
+    }
+}
+
+/// Generate the ghost state machine declared by a `state_machine!`
+/// invocation: an enum with one variant per state, and methods for inspecting
+/// the current state and for performing the declared transitions. The
+/// generated code only uses the public macros of `prusti_contracts`, so it
+/// is the same with and without Prusti.
+pub fn state_machine(tokens: TokenStream) -> TokenStream {
+    let machine: StateMachine = handle_result!(syn::parse2(tokens));
+    let callsite_span = Span::call_site();
+    let states = machine.states();
+    let StateMachine { attrs, vis, ident, transitions } = machine;
+    let is_arms = states.iter().map(|state| quote_spanned! {callsite_span=>
+        #ident::#state => match state {
+            #ident::#state => true,
+            _ => false,
+        },
+    });
+    let transition_arms = states.iter().map(|state| {
+        let targets = transitions.iter()
+            .find(|transitions| &transitions.from == state)
+            .map(|transitions| transitions.to.clone())
+            .unwrap_or_default();
+        if targets.is_empty() {
+            quote_spanned! {callsite_span=> #ident::#state => false, }
+        } else {
+            quote_spanned! {callsite_span=>
+                #ident::#state => match to {
+                    #( #ident::#targets )|* => true,
+                    _ => false,
+                },
+            }
+        }
+    });
+    quote_spanned! {callsite_span=>
+        #(#attrs)*
+        #[derive(Clone, Copy)]
+        #vis enum #ident {
+            #(#states),*
+        }
+
+        #[allow(dead_code)]
+        impl #ident {
+            /// Whether the state machine is in the state `state`.
+            #[prusti_contracts::pure]
+            pub fn is(&self, state: #ident) -> bool {
+                match self {
+                    #(#is_arms)*
+                }
+            }
+
+            /// Whether the state machine can transition from its current
+            /// state to the state `to`.
+            #[prusti_contracts::pure]
+            pub fn can_transition_to(&self, to: #ident) -> bool {
+                match self {
+                    #(#transition_arms)*
+                }
+            }
+
+            /// Perform a transition to the state `to`.
+            #[prusti_contracts::requires(self.can_transition_to(to))]
+            #[prusti_contracts::ensures(self.is(to))]
+            pub fn transition(&mut self, to: #ident) {
+                *self = to;
+            }
+        }
+    }
+}
+
+/// Generate a pure method of the type declared by `tokens` that checks the
+/// given invariant. The verifier finds it by its `prusti::type_invariant`
+/// attribute, and requires the invariant of the arguments of each call and
+/// of the value returned by each function. The invariant is an assertion, so
+/// it is given by the postconditions of the method instead of its body.
+pub fn invariant(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    let item: syn::Item = handle_result!(syn::parse2(tokens));
+    let (ident, generics) = match &item {
+        syn::Item::Struct(item_struct) => (&item_struct.ident, &item_struct.generics),
+        syn::Item::Enum(item_enum) => (&item_enum.ident, &item_enum.generics),
+        _ => {
+            return syn::Error::new(
+                item.span(),
+                "#[invariant] can only be attached to a struct or an enum",
+            ).to_compile_error();
+        }
+    };
+    let mut rewriter = rewriter::AstRewriter::new();
+    let spec_id = rewriter.generate_spec_id();
+    let invariant_name = format_ident!("prusti_type_invariant_{}", spec_id.to_string());
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let callsite_span = Span::call_site();
+    quote_spanned! {callsite_span=>
+        #item
+
+        #[allow(dead_code)]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #[prusti_contracts::pure]
+            #[trusted]
+            #[ensures(result ==> (#attr))]
+            #[ensures((#attr) ==> result)]
+            #[prusti::type_invariant]
+            fn #invariant_name(&self) -> bool {
+                unimplemented!()
+            }
+        }
+    }
+}
+
+/// The preconditions and postconditions that make `method` preserve the
+/// given thread-safety invariant of its impl block.
+fn thread_safety_invariant_attributes(
+    invariant: &TokenStream,
+    sig: &syn::Signature,
+) -> Vec<(SpecAttributeKind, TokenStream)> {
+    match sig.receiver() {
+        Some(syn::FnArg::Receiver(syn::Receiver { reference: Some(_), .. })) => vec![
+            (SpecAttributeKind::Requires, invariant.clone()),
+            (SpecAttributeKind::Ensures, invariant.clone()),
+        ],
+        Some(_) => vec![(SpecAttributeKind::Requires, invariant.clone())],
+        None => {
+            let returns_self = match &sig.output {
+                syn::ReturnType::Type(_, box syn::Type::Path(syn::TypePath { qself: None, path })) =>
+                    path.is_ident("Self"),
+                _ => false,
+            };
+            if returns_self {
+                vec![(SpecAttributeKind::Ensures, replace_self_with_result(invariant.clone()))]
+            } else {
+                vec![]
+            }
+        }
+    }
+}
+
+/// The names of the fields read as `self.<field>` in `tokens`, including the
+/// fields of tuple structs like `self.0`.
+fn collect_self_fields(tokens: TokenStream) -> Vec<String> {
+    let mut fields = vec![];
+    let tokens: Vec<_> = tokens.into_iter().collect();
+    for (index, token) in tokens.iter().enumerate() {
+        match (token, tokens.get(index + 1), tokens.get(index + 2)) {
+            (TokenTree::Ident(receiver), Some(TokenTree::Punct(dot)), Some(field))
+                if receiver == "self" && dot.as_char() == '.' => {
+                match field {
+                    TokenTree::Ident(field) => fields.push(field.to_string()),
+                    // `self.0.1` is lexed as the literal `0.1`.
+                    TokenTree::Literal(field) => fields.extend(
+                        field.to_string().split('.').next().map(str::to_string)
+                    ),
+                    _ => {}
+                }
+            }
+            (TokenTree::Group(group), _, _) => {
+                fields.extend(collect_self_fields(group.stream()));
+            }
+            _ => {}
+        }
+    }
+    fields
+}
+
+/// Replace every occurrence of `self` in `tokens` with `result`.
+fn replace_self_with_result(tokens: TokenStream) -> TokenStream {
+    tokens.into_iter().map(|token| match token {
+        TokenTree::Ident(ident) if ident == "self" => {
+            TokenTree::Ident(syn::Ident::new("result", ident.span()))
+        }
+        TokenTree::Group(group) => {
+            let mut new_group = proc_macro2::Group::new(
+                group.delimiter(),
+                replace_self_with_result(group.stream()),
+            );
+            new_group.set_span(group.span());
+            TokenTree::Group(new_group)
+        }
+        other => other,
+    }).collect()
+}
+
 pub fn refine_trait_spec(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
-    let mut impl_block: syn::ItemImpl = handle_result!(syn::parse2(tokens));
+    let impl_block: syn::ItemImpl = handle_result!(syn::parse2(tokens));
+    handle_result!(rewrite_impl_block(vec![], impl_block))
+}
+
+/// Rewrite the specification attributes of the methods of an impl block. The
+/// spec items are moved to a separate inherent impl block, so that they can
+/// also be generated for trait implementations.
+///
+/// The `#[requires]` and `#[ensures]` attributes of the impl block itself
+/// (`impl_attributes` and the ones still attached to it) are added to every
+/// method that has a receiver. A `#[thread_safety_invariant]` of the impl
+/// block is required and preserved by the methods with a receiver, and
+/// established by the methods that return `Self`.
+fn rewrite_impl_block(
+    mut impl_attributes: Vec<(SpecAttributeKind, TokenStream)>,
+    mut impl_block: syn::ItemImpl,
+) -> syn::Result<TokenStream> {
+    impl_attributes.extend(extract_prusti_attributes_from(&mut impl_block.attrs));
+    let mut thread_safety_invariants = vec![];
+    for (attr_kind, attr_tokens) in &impl_attributes {
+        match attr_kind {
+            SpecAttributeKind::Requires | SpecAttributeKind::Ensures => {}
+            SpecAttributeKind::ThreadSafetyInvariant if impl_block.trait_.is_none() => {
+                thread_safety_invariants.push(attr_tokens.clone());
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    impl_block.span(),
+                    "only #[requires], #[ensures] and, on inherent impl blocks, \
+                    #[thread_safety_invariant] can be attached to an impl block",
+                ));
+            }
+        }
+    }
+    impl_attributes.retain(|(attr_kind, _)| match attr_kind {
+        SpecAttributeKind::ThreadSafetyInvariant => false,
+        _ => true,
+    });
+    if !thread_safety_invariants.is_empty() {
+        let mut constrained_fields: Vec<String> = vec![];
+        for invariant in &thread_safety_invariants {
+            for field in collect_self_fields(invariant.clone()) {
+                if !constrained_fields.contains(&field) {
+                    constrained_fields.push(field);
+                }
+            }
+        }
+        let constrained_fields = constrained_fields.join(",");
+        impl_block.attrs.push(parse_quote_spanned! {impl_block.span()=>
+            #[prusti::thread_safety_invariant]
+        });
+        impl_block.attrs.push(parse_quote_spanned! {impl_block.span()=>
+            #[prusti::thread_safety_invariant_fields = #constrained_fields]
+        });
+    }
     let mut new_items = Vec::new();
     let mut generated_spec_items = Vec::new();
     for item in impl_block.items {
         match item {
             syn::ImplItem::Method(method) => {
+                let mut prusti_attributes = if method.sig.receiver().is_some() {
+                    impl_attributes.clone()
+                } else {
+                    vec![]
+                };
+                for invariant in &thread_safety_invariants {
+                    prusti_attributes.extend(
+                        thread_safety_invariant_attributes(invariant, &method.sig)
+                    );
+                }
                 let mut method_item = untyped::AnyFnItem::ImplMethod(method);
-                let prusti_attributes: Vec<_> = extract_prusti_attributes(&mut method_item).collect();
-                let (spec_items, generated_attributes) = handle_result!(
-                    generate_spec_and_assertions(prusti_attributes, &method_item)
-                );
+                prusti_attributes.extend(extract_prusti_attributes(&mut method_item));
+                let (spec_items, generated_attributes) =
+                    generate_spec_and_assertions(prusti_attributes, &method_item)?;
                 generated_spec_items.extend(spec_items.into_iter().map(|spec_item| {
                     match spec_item {
                         syn::Item::Fn(spec_item_fn) => {
@@ -364,7 +1026,7 @@ pub fn refine_trait_spec(_attr: TokenStream, tokens: TokenStream) -> TokenStream
                 };
                 new_items.push(new_item);
             }
-            _ => {}
+            other => new_items.push(other),
         }
     }
     impl_block.items = new_items;
@@ -379,10 +1041,10 @@ pub fn refine_trait_spec(_attr: TokenStream, tokens: TokenStream) -> TokenStream
         brace_token: impl_block.brace_token,
         items: generated_spec_items,
     };
-    quote_spanned! {impl_block.span()=>
+    Ok(quote_spanned! {impl_block.span()=>
         #spec_impl_block
         #impl_block
-    }
+    })
 }
 
 pub fn extern_spec(_attr: TokenStream, tokens:TokenStream) -> TokenStream {
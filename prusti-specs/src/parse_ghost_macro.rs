@@ -0,0 +1,121 @@
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::visit_mut::VisitMut;
+
+/// The content of a `ghost! { ... }` block: statements that are verified but
+/// erased from the compiled program.
+pub(crate) struct GhostBlock {
+    pub stmts: Vec<syn::Stmt>,
+}
+
+impl Parse for GhostBlock {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut stmts = syn::Block::parse_within(input)?;
+        let mut checker = ControlFlowChecker {
+            loop_labels: vec![],
+            closure_depth: 0,
+            error: None,
+        };
+        for stmt in &mut stmts {
+            checker.visit_stmt_mut(stmt);
+        }
+        match checker.error {
+            Some(error) => Err(error),
+            None => Ok(GhostBlock { stmts }),
+        }
+    }
+}
+
+/// Rejects the control flow that leaves a ghost block, because the erased
+/// program would not take it.
+struct ControlFlowChecker {
+    /// The labels of the loops of the ghost code that enclose the visited
+    /// expression, `None` for the loops without a label.
+    loop_labels: Vec<Option<String>>,
+    closure_depth: usize,
+    error: Option<syn::Error>,
+}
+
+impl ControlFlowChecker {
+    fn report(&mut self, span: proc_macro2::Span, what: &str) {
+        let error = syn::Error::new(
+            span,
+            format!("ghost code cannot {}, because it is erased from the compiled program", what),
+        );
+        match self.error {
+            Some(ref mut errors) => errors.combine(error),
+            None => self.error = Some(error),
+        }
+    }
+
+    fn visit_loop<F: FnOnce(&mut Self)>(&mut self, label: &Option<syn::Label>, visit: F) {
+        self.loop_labels.push(label.as_ref().map(|label| label.name.ident.to_string()));
+        visit(self);
+        self.loop_labels.pop();
+    }
+
+    /// Whether `break` or `continue` with the given label stays in the ghost code.
+    fn is_local_jump(&self, label: &Option<syn::Lifetime>) -> bool {
+        match label {
+            None => !self.loop_labels.is_empty(),
+            Some(label) => self.loop_labels.contains(&Some(label.ident.to_string())),
+        }
+    }
+}
+
+impl VisitMut for ControlFlowChecker {
+    fn visit_expr_return_mut(&mut self, expr: &mut syn::ExprReturn) {
+        if self.closure_depth == 0 {
+            self.report(expr.span(), "return from the function");
+        }
+        syn::visit_mut::visit_expr_return_mut(self, expr);
+    }
+
+    fn visit_expr_try_mut(&mut self, expr: &mut syn::ExprTry) {
+        if self.closure_depth == 0 {
+            self.report(expr.span(), "return from the function with `?`");
+        }
+        syn::visit_mut::visit_expr_try_mut(self, expr);
+    }
+
+    fn visit_expr_break_mut(&mut self, expr: &mut syn::ExprBreak) {
+        if !self.is_local_jump(&expr.label) {
+            self.report(expr.span(), "break out of a loop that encloses it");
+        }
+        syn::visit_mut::visit_expr_break_mut(self, expr);
+    }
+
+    fn visit_expr_continue_mut(&mut self, expr: &mut syn::ExprContinue) {
+        if !self.is_local_jump(&expr.label) {
+            self.report(expr.span(), "continue a loop that encloses it");
+        }
+        syn::visit_mut::visit_expr_continue_mut(self, expr);
+    }
+
+    fn visit_expr_loop_mut(&mut self, expr: &mut syn::ExprLoop) {
+        let label = expr.label.clone();
+        self.visit_loop(&label, |checker| syn::visit_mut::visit_expr_loop_mut(checker, expr));
+    }
+
+    fn visit_expr_while_mut(&mut self, expr: &mut syn::ExprWhile) {
+        let label = expr.label.clone();
+        self.visit_loop(&label, |checker| syn::visit_mut::visit_expr_while_mut(checker, expr));
+    }
+
+    fn visit_expr_for_loop_mut(&mut self, expr: &mut syn::ExprForLoop) {
+        let label = expr.label.clone();
+        self.visit_loop(&label, |checker| syn::visit_mut::visit_expr_for_loop_mut(checker, expr));
+    }
+
+    fn visit_expr_closure_mut(&mut self, expr: &mut syn::ExprClosure) {
+        let loop_labels = std::mem::take(&mut self.loop_labels);
+        self.closure_depth += 1;
+        syn::visit_mut::visit_expr_closure_mut(self, expr);
+        self.closure_depth -= 1;
+        self.loop_labels = loop_labels;
+    }
+
+    fn visit_item_mut(&mut self, _item: &mut syn::Item) {
+        // Nested items have their own control flow.
+    }
+}
@@ -0,0 +1,35 @@
+use syn::parse::{Parse, ParseStream};
+use syn::Token;
+
+/// The content of an `#[in_state(...)]` attribute: the ghost state in which
+/// the method can be called, optionally followed by the state in which the
+/// method leaves its receiver. The field that stores the state can be given
+/// as a prefix, and defaults to `state`.
+pub(crate) struct InState {
+    pub field: syn::Ident,
+    pub from: syn::Path,
+    pub to: Option<syn::Path>,
+}
+
+impl Parse for InState {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let field = if input.peek2(Token![:]) && !input.peek2(Token![::]) {
+            let field = input.parse()?;
+            input.parse::<Token![:]>()?;
+            field
+        } else {
+            syn::Ident::new("state", input.span())
+        };
+        let from = input.parse()?;
+        let to = if input.peek(Token![=>]) {
+            input.parse::<Token![=>]>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        if !input.is_empty() {
+            return Err(input.error("expected `<state>` or `<state> => <state>`"));
+        }
+        Ok(InState { field, from, to })
+    }
+}
@@ -0,0 +1,26 @@
+use proc_macro2::TokenStream;
+use syn::parse::{Parse, ParseStream};
+
+/// The content of a `predicate!` invocation: a function signature followed by
+/// an assertion that defines the predicate.
+pub(crate) struct PredicateWithBody {
+    pub attrs: Vec<syn::Attribute>,
+    pub vis: syn::Visibility,
+    pub sig: syn::Signature,
+    pub body: TokenStream,
+}
+
+impl Parse for PredicateWithBody {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        let sig: syn::Signature = input.parse()?;
+        let content;
+        syn::braced!(content in input);
+        let body = content.parse()?;
+        if !input.is_empty() {
+            return Err(input.error("a predicate definition must contain exactly one function"));
+        }
+        Ok(PredicateWithBody { attrs, vis, sig, body })
+    }
+}
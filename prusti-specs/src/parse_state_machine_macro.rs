@@ -0,0 +1,73 @@
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::Token;
+
+/// The content of a `state_machine!` invocation: an enum-like declaration
+/// whose variants list the states to which they can transition.
+pub(crate) struct StateMachine {
+    pub attrs: Vec<syn::Attribute>,
+    pub vis: syn::Visibility,
+    pub ident: syn::Ident,
+    pub transitions: Vec<StateTransitions>,
+}
+
+/// A state, followed by the states to which it can transition.
+pub(crate) struct StateTransitions {
+    pub from: syn::Ident,
+    pub to: Vec<syn::Ident>,
+}
+
+impl StateMachine {
+    /// All the states of the machine, in the order of their first occurrence.
+    pub fn states(&self) -> Vec<syn::Ident> {
+        let mut states: Vec<syn::Ident> = vec![];
+        for transitions in &self.transitions {
+            for state in std::iter::once(&transitions.from).chain(&transitions.to) {
+                if !states.contains(state) {
+                    states.push(state.clone());
+                }
+            }
+        }
+        states
+    }
+}
+
+impl Parse for StateMachine {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        input.parse::<Token![enum]>()?;
+        let ident = input.parse()?;
+        let content;
+        syn::braced!(content in input);
+        let transitions: Punctuated<StateTransitions, Token![,]> =
+            content.parse_terminated(StateTransitions::parse)?;
+        if !input.is_empty() {
+            return Err(input.error("a state machine definition must contain exactly one enum"));
+        }
+        let transitions: Vec<_> = transitions.into_iter().collect();
+        for (index, transitions_i) in transitions.iter().enumerate() {
+            if transitions[..index].iter().any(|other| other.from == transitions_i.from) {
+                return Err(syn::Error::new(
+                    transitions_i.from.span(),
+                    "the transitions of a state must be declared together",
+                ));
+            }
+        }
+        Ok(StateMachine { attrs, vis, ident, transitions })
+    }
+}
+
+impl Parse for StateTransitions {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let from = input.parse()?;
+        let mut to = vec![];
+        if input.peek(Token![=>]) {
+            input.parse::<Token![=>]>()?;
+            let targets: Punctuated<syn::Ident, Token![|]> =
+                Punctuated::parse_separated_nonempty(input)?;
+            to.extend(targets);
+        }
+        Ok(StateTransitions { from, to })
+    }
+}
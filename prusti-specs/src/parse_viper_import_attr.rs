@@ -0,0 +1,25 @@
+use syn::parse::{Parse, ParseStream};
+use syn::Token;
+
+/// The content of a `#[viper_import(...)]` attribute: the path of a Viper
+/// file, relative to the directory of the crate root, and the name of the
+/// Viper function of that file that implements the annotated function.
+pub(crate) struct ViperImport {
+    pub file: syn::LitStr,
+    pub name: syn::LitStr,
+}
+
+impl Parse for ViperImport {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.is_empty() {
+            return Err(input.error("expected `\"<file>\", \"<function name>\"`"));
+        }
+        let file = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let name = input.parse()?;
+        if !input.is_empty() {
+            return Err(input.error("expected `\"<file>\", \"<function name>\"`"));
+        }
+        Ok(ViperImport { file, name })
+    }
+}
@@ -14,6 +14,9 @@ pub(crate) struct AstRewriter {
 pub enum SpecItemType {
     Precondition,
     Postcondition,
+    Predicate,
+    Complexity,
+    Decreases,
 }
 
 impl std::fmt::Display for SpecItemType {
@@ -21,6 +24,9 @@ impl std::fmt::Display for SpecItemType {
         match self {
             SpecItemType::Precondition => write!(f, "pre"),
             SpecItemType::Postcondition => write!(f, "post"),
+            SpecItemType::Predicate => write!(f, "pred"),
+            SpecItemType::Complexity => write!(f, "complexity"),
+            SpecItemType::Decreases => write!(f, "decreases"),
         }
     }
 }
@@ -74,7 +80,7 @@ impl AstRewriter {
         }
         None
     }
-    fn generate_result_arg(&self, item: &untyped::AnyFnItem) -> syn::FnArg {
+    fn generate_result_arg(&self, item: &untyped::AnyFnItem, result_name: &syn::Ident) -> syn::FnArg {
         let item_span = item.span();
         let output_ty = match &item.sig().output {
             syn::ReturnType::Default => parse_quote_spanned!(item_span=> ()),
@@ -83,7 +89,7 @@ impl AstRewriter {
         let fn_arg = syn::FnArg::Typed(
             syn::PatType {
                 attrs: Vec::new(),
-                pat: box parse_quote_spanned!(item_span=> result),
+                pat: box parse_quote_spanned!(item_span=> #result_name),
                 colon_token: syn::Token![:](item.sig().output.span()),
                 ty: output_ty,
             }
@@ -91,9 +97,8 @@ impl AstRewriter {
         fn_arg
     }
 
-    /// Generate a dummy function for checking the given precondition or postcondition.
-    ///
-    /// `spec_type` should be either `"pre"` or `"post"`.
+    /// Generate a dummy function for checking the given precondition,
+    /// postcondition or predicate body.
     pub fn generate_spec_item_fn(
         &mut self,
         spec_type: SpecItemType,
@@ -101,10 +106,28 @@ impl AstRewriter {
         assertion: untyped::Assertion,
         item: &untyped::AnyFnItem,
     ) -> syn::Result<syn::Item> {
-        if let Some(span) = self.check_contains_keyword_in_params(item, "result") {
+        let result_name = syn::Ident::new("result", item.span());
+        self.generate_spec_item_fn_with_result(spec_type, spec_id, assertion, item, &result_name)
+    }
+
+    /// Like `generate_spec_item_fn`, but the return value of a postcondition
+    /// is bound to `result_name` instead of `result`.
+    pub fn generate_spec_item_fn_with_result(
+        &mut self,
+        spec_type: SpecItemType,
+        spec_id: untyped::SpecificationId,
+        assertion: untyped::Assertion,
+        item: &untyped::AnyFnItem,
+        result_name: &syn::Ident,
+    ) -> syn::Result<syn::Item> {
+        let result_name_str = result_name.to_string();
+        if let Some(span) = self.check_contains_keyword_in_params(item, &result_name_str) {
             return Err(syn::Error::new(
                 span,
-                "it is not allowed to use the keyword `result` as a function argument".to_string(),
+                format!(
+                    "it is not allowed to use the keyword `{}` as a function argument",
+                    result_name_str
+                ),
             ));
         }
         let item_span = item.span();
@@ -128,7 +151,7 @@ impl AstRewriter {
         spec_item.sig.generics = item.sig().generics.clone();
         spec_item.sig.inputs = item.sig().inputs.clone();
         if spec_type == SpecItemType::Postcondition {
-            let fn_arg = self.generate_result_arg(item);
+            let fn_arg = self.generate_result_arg(item, result_name);
             spec_item.sig.inputs.push(fn_arg);
         }
         Ok(syn::Item::Fn(spec_item))
@@ -139,6 +162,25 @@ impl AstRewriter {
         &mut self,
         spec_id: untyped::SpecificationId,
         assertion: untyped::Assertion,
+    ) -> TokenStream {
+        self.generate_loop_spec_closure(spec_id, assertion, format_ident!("loop_body_invariant_spec"))
+    }
+
+    /// Generate statements for checking the given loop variant, which is
+    /// encoded as an assertion `termination_measure() == measure`.
+    pub fn generate_spec_loop_variant(
+        &mut self,
+        spec_id: untyped::SpecificationId,
+        assertion: untyped::Assertion,
+    ) -> TokenStream {
+        self.generate_loop_spec_closure(spec_id, assertion, format_ident!("loop_body_variant_spec"))
+    }
+
+    fn generate_loop_spec_closure(
+        &mut self,
+        spec_id: untyped::SpecificationId,
+        assertion: untyped::Assertion,
+        spec_kind: syn::Ident,
     ) -> TokenStream {
         let mut statements = TokenStream::new();
         assertion.encode_type_check(&mut statements);
@@ -149,7 +191,7 @@ impl AstRewriter {
             #[allow(unused_must_use, unused_variables)]
             {
                 #[prusti::spec_only]
-                #[prusti::loop_body_invariant_spec]
+                #[prusti::#spec_kind]
                 #[prusti::spec_id = #spec_id_str]
                 #[prusti::assertion = #assertion_json]
                 || {
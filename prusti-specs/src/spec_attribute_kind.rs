@@ -9,6 +9,15 @@ pub enum SpecAttributeKind {
     AfterExpiryIf,
     Pure,
     Trusted,
+    Decreases,
+    ThreadSafetyInvariant,
+    Bitvector,
+    BitPrecise,
+    InState,
+    Complexity,
+    ViperImport,
+    Lemma,
+    TrustedProof,
 }
 
 impl TryFrom<String> for SpecAttributeKind {
@@ -22,6 +31,15 @@ impl TryFrom<String> for SpecAttributeKind {
             "after_expiry_if" => Ok(SpecAttributeKind::AfterExpiryIf),
             "pure" => Ok(SpecAttributeKind::Pure),
             "trusted" => Ok(SpecAttributeKind::Trusted),
+            "decreases" => Ok(SpecAttributeKind::Decreases),
+            "thread_safety_invariant" => Ok(SpecAttributeKind::ThreadSafetyInvariant),
+            "bitvector" => Ok(SpecAttributeKind::Bitvector),
+            "bit_precise" => Ok(SpecAttributeKind::BitPrecise),
+            "in_state" => Ok(SpecAttributeKind::InState),
+            "complexity" => Ok(SpecAttributeKind::Complexity),
+            "viper_import" => Ok(SpecAttributeKind::ViperImport),
+            "lemma" => Ok(SpecAttributeKind::Lemma),
+            "trusted_proof" => Ok(SpecAttributeKind::TrustedProof),
             _ => Err(name),
         }
     }
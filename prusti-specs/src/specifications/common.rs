@@ -17,6 +17,14 @@ pub enum SpecType {
     Postcondition,
     /// Loop invariant or struct invariant
     Invariant,
+    /// Body of a predicate.
+    Predicate,
+    /// Complexity bound of a procedure.
+    Complexity,
+    /// Termination measure of a procedure.
+    Decreases,
+    /// Termination measure of a loop.
+    Variant,
 }
 
 #[derive(Debug)]
@@ -53,6 +61,9 @@ pub enum SpecIdRef {
     Precondition(SpecificationId),
     Postcondition(SpecificationId),
     Pledge { lhs: Option<SpecificationId>, rhs: SpecificationId },
+    Predicate(SpecificationId),
+    Complexity(SpecificationId),
+    Decreases(SpecificationId),
 }
 
 impl Display for SpecificationId {
@@ -288,17 +299,20 @@ pub struct Specification<EID, ET, AT> {
 pub struct LoopSpecification<EID, ET, AT> {
     /// Loop invariant.
     pub invariant: Vec<Assertion<EID, ET, AT>>,
+    /// The termination measure given by a `body_variant!`, as an assertion
+    /// `termination_measure() == measure`.
+    pub variant: Option<Assertion<EID, ET, AT>>,
 }
 
 impl<EID, ET, AT> LoopSpecification<EID, ET, AT> {
     pub fn new(invariant: Vec<Assertion<EID, ET, AT>>) -> Self {
-        Self { invariant }
+        Self { invariant, variant: None }
     }
     pub fn empty() -> Self {
         Self::new(Vec::new())
     }
     pub fn is_empty(&self) -> bool {
-        self.invariant.is_empty()
+        self.invariant.is_empty() && self.variant.is_none()
     }
 }
 
@@ -311,6 +325,14 @@ pub struct ProcedureSpecification<EID, ET, AT> {
     pub posts: Vec<Assertion<EID, ET, AT>>,
     /// Pledges in the postcondition.
     pub pledges: Vec<Pledge<EID, ET, AT>>,
+    /// The body of a predicate defined with `predicate!`.
+    pub predicate_body: Option<Assertion<EID, ET, AT>>,
+    /// The number of credits available to the procedure, given by a
+    /// `#[complexity]` annotation as an assertion `credits() == bound`.
+    pub complexity: Option<Assertion<EID, ET, AT>>,
+    /// The termination measure given by a `#[decreases]` annotation, as an
+    /// assertion `termination_measure() == measure`.
+    pub decreases: Option<Assertion<EID, ET, AT>>,
 
     pub pure: bool,
     pub trusted: bool,
@@ -326,6 +348,9 @@ impl<EID, ET, AT> ProcedureSpecification<EID, ET, AT> {
             pres,
             posts,
             pledges,
+            predicate_body: None,
+            complexity: None,
+            decreases: None,
             pure: false,
             trusted: false,
         }
@@ -366,6 +391,9 @@ impl<EID: Clone + Debug, ET: Clone + Debug, AT: Clone + Debug> ProcedureSpecific
             pres,
             posts,
             pledges,
+            predicate_body: other.predicate_body.clone().or_else(|| self.predicate_body.clone()),
+            complexity: other.complexity.clone().or_else(|| self.complexity.clone()),
+            decreases: other.decreases.clone().or_else(|| self.decreases.clone()),
             pure: other.pure,
             trusted: other.trusted,
         }
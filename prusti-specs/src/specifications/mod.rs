@@ -13,6 +13,11 @@
 /// (`A` is an arbitrary Prusti assertion):
 /// `forall(|NAME1: TYPE1, NAME2: TYPE2, ...| A)`
 /// `forall(|NAME1: TYPE1, NAME2: TYPE2, ...| A, triggers=[(E, ...), ...])`
+/// `forall(|NAME1: TYPE1, NAME2: TYPE2, ...| triggers=[(E, ...), ...] A)`
+///
+/// Each trigger is a tuple of Rust expressions, or a single parenthesized
+/// expression such as `(f(x))`, and the quantifier is only instantiated for
+/// the terms that match one of its triggers.
 ///
 /// Prusti assertions can only be joined together by `&&` and `==>`, for example
 /// the following is not allowed, since `(E ==> E)` is a Prusti assertion:
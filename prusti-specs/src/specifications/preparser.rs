@@ -7,7 +7,7 @@ use std::collections::VecDeque;
 use std::mem;
 use syn::parse::{ParseStream, Parse};
 use syn::{self, Token, Error};
-use quote::quote;
+use quote::{quote, quote_spanned};
 
 use super::common;
 use crate::specifications::common::{ForAllVars, SpecEntailmentVars, TriggerSet, Trigger};
@@ -445,54 +445,45 @@ impl Parser {
                 })
             }
 
+            // parse triggers given before the body, if any
+            let mut trigger_set = TriggerSet(vec![]);
+            let mut has_triggers = false;
+            let mut lookahead = stream.clone();
+            if lookahead.check_and_consume_keyword("triggers")
+                && !lookahead.peek_operator("==")
+                && lookahead.check_and_consume_operator("=")
+            {
+                stream = lookahead;
+                let group = match stream.check_and_consume_block(Delimiter::Bracket) {
+                    Some(group) => group,
+                    None => {
+                        self.input.span = stream.span;
+                        return Err(self.error_expected_bracket());
+                    }
+                };
+                trigger_set = self.parse_trigger_set(TokenTree::Group(group).into())?;
+                has_triggers = true;
+            }
+
             // parse body
             let token_stream = stream.create_stream_until(",");
             let mut parser = Parser::from_token_stream(token_stream);
             let body = parser.extract_assertion()?;
 
-            // create triggers in case they are not present
-            let mut trigger_set = TriggerSet(vec![]);
-
-            // parse triggers (check if they are present at all)
+            // parse triggers given after the body, if any
             if stream.peek_operator(",") {
                 stream.check_and_consume_operator(",");
+                if has_triggers {
+                    self.input.span = stream.span;
+                    return Err(self.error_duplicate_triggers());
+                }
                 if !stream.check_and_consume_keyword("triggers") {
                     return Err(self.error_expected_triggers());
                 }
                 if !stream.check_and_consume_operator("=") {
                     return Err(self.error_expected_equals());
                 }
-                let token_stream = stream.create_stream();
-
-                let maybe_arr: Result<syn::ExprArray, Error> = syn::parse2(token_stream);
-                if let Err(err) = maybe_arr {
-                    self.input.span = err.span();
-                    return Err(self.error_expected_tuple());
-                }
-                let arr = maybe_arr.unwrap();
-                self.input.span = arr.span();
-
-                let mut vec_of_triggers = vec![];
-                for item in arr.elems {
-                    if let syn::Expr::Tuple(tuple) = item {
-                        vec_of_triggers.push(
-                            Trigger(tuple.elems
-                                .into_iter()
-                                .map(|x| ExpressionWithoutId {
-                                    id: (),
-                                    spec_id: common::SpecificationId::dummy(),
-                                    expr: x })
-                                .collect()
-                            )
-                        );
-                    }
-                    else {
-                        self.input.span = item.span();
-                        return Err(self.error_expected_tuple());
-                    }
-                }
-
-                trigger_set = TriggerSet(vec_of_triggers);
+                trigger_set = self.parse_trigger_set(stream.create_stream())?;
             }
 
             let conjunct = AssertionWithoutId {
@@ -517,6 +508,40 @@ impl Parser {
             return Err(self.error_expected_parenthesis());
         }
     }
+    /// Parse the array of triggers of a quantifier. Each trigger is a tuple of
+    /// Rust expressions, or a single parenthesized expression.
+    fn parse_trigger_set(&mut self, tokens: TokenStream) -> syn::Result<TriggerSet<(), syn::Expr>> {
+        let maybe_arr: Result<syn::ExprArray, Error> = syn::parse2(tokens);
+        if let Err(err) = maybe_arr {
+            self.input.span = err.span();
+            return Err(self.error_expected_tuple());
+        }
+        let arr = maybe_arr.unwrap();
+        self.input.span = arr.span();
+
+        let mut vec_of_triggers = vec![];
+        for item in arr.elems {
+            let terms: Vec<syn::Expr> = match item {
+                syn::Expr::Tuple(tuple) => tuple.elems.into_iter().collect(),
+                syn::Expr::Paren(paren) => vec![*paren.expr],
+                _ => {
+                    self.input.span = item.span();
+                    return Err(self.error_expected_tuple());
+                }
+            };
+            vec_of_triggers.push(
+                Trigger(terms
+                    .into_iter()
+                    .map(|x| ExpressionWithoutId {
+                        id: (),
+                        spec_id: common::SpecificationId::dummy(),
+                        expr: x })
+                    .collect()
+                )
+            );
+        }
+        Ok(TriggerSet(vec_of_triggers))
+    }
     fn resolve_spec_ent(&mut self) -> syn::Result<()> {
         // handles the case when there is no lhs of the |= operator
         if !self.expected_operator {
@@ -714,7 +739,30 @@ impl Parser {
         // build a conjunction off of the assertions parsed
         self.conjuncts_to_assertion()
     }
+    /// Rewrite `lhs === rhs` into `snapshot_equality(lhs, rhs)`. The snapshot
+    /// equality is only recognized at the top level of a Rust expression,
+    /// and has a lower precedence than all Rust operators.
+    fn rewrite_snapshot_equality(&mut self, tokens: TokenStream) -> syn::Result<TokenStream> {
+        let mut stream = ParserStream::from_token_stream(tokens.clone());
+        let mut lookahead = stream.clone();
+        while !lookahead.is_empty() && !lookahead.peek_operator("===") {
+            lookahead.pop();
+        }
+        if lookahead.is_empty() {
+            return Ok(tokens);
+        }
+        let lhs = stream.create_stream_until("===");
+        stream.check_and_consume_operator("===");
+        let operator_span = stream.span;
+        let rhs = stream.create_stream();
+        if lhs.is_empty() || rhs.is_empty() {
+            self.input.span = operator_span;
+            return Err(self.error_expected_snapshot_equality_operands());
+        }
+        Ok(quote_spanned! {operator_span=> snapshot_equality(#lhs, #rhs) })
+    }
     fn parse_rust_expression(&mut self, tokens: TokenStream) -> syn::Result<syn::Expr> {
+        let tokens = self.rewrite_snapshot_equality(tokens)?;
         let maybe_expr = syn::parse2(tokens.clone());
         if let Err(err) = maybe_expr {
             let mut stream = ParserStream::from_token_stream(tokens);
@@ -803,6 +851,9 @@ impl Parser {
         syn::Error::new(self.input.span,
                         "`==>` cannot be part of Rust expression")
     }
+    fn error_expected_snapshot_equality_operands(&self) -> syn::Error {
+        syn::Error::new(self.input.span, "expected an expression on both sides of `===`")
+    }
     fn error_expected_assertion(&self) -> syn::Error {
         syn::Error::new(self.input.span, "expected Prusti assertion")
     }
@@ -830,6 +881,9 @@ impl Parser {
     fn error_expected_equals(&self) -> syn::Error {
         syn::Error::new(self.input.span, "expected `=`")
     }
+    fn error_duplicate_triggers(&self) -> syn::Error {
+        syn::Error::new(self.input.span, "the triggers of a quantifier can only be given once")
+    }
     fn error_expected_tuple(&self) -> syn::Error {
         syn::Error::new(self.input.span, "`triggers` must be an array of tuples containing Rust expressions")
     }
@@ -12,6 +12,8 @@
 extern crate compiletest_rs;
 extern crate prusti_server;
 
+mod mutation;
+
 use compiletest_rs::{common::Mode, run_tests, Config};
 use prusti_server::ServerSideService;
 use std::{env, path::PathBuf};
@@ -124,6 +126,7 @@ fn run_verification(group_name: &str, filter: &Option<String>) {
         TemporaryEnvVar::set("PRUSTI_FULL_COMPILATION", "true"),
         TemporaryEnvVar::set("PRUSTI_ENCODE_UNSIGNED_NUM_CONSTRAINT", "true"),
         TemporaryEnvVar::set("PRUSTI_QUIET", "true"),
+        TemporaryEnvVar::set("PRUSTI_FORCE_VERIFY", "true"),
     );
 
     run_prusti_tests(group_name, filter, Some("-A warnings"));
@@ -145,6 +148,16 @@ fn run_verification_core_proof(group_name: &str, filter: &Option<String>) {
     run_verification(group_name, filter);
 }
 
+fn run_verification_mutations(group_name: &str, filter: &Option<String>) {
+    let _temporary_env_vars = (
+        TemporaryEnvVar::set("PRUSTI_ENCODE_UNSIGNED_NUM_CONSTRAINT", "true"),
+        TemporaryEnvVar::set("PRUSTI_QUIET", "true"),
+        TemporaryEnvVar::set("PRUSTI_FORCE_VERIFY", "true"),
+    );
+
+    mutation::run_mutation_tests(&find_prusti_rustc_path(), group_name, filter);
+}
+
 fn test_runner(_tests: &[&()]) {
     // Spawn server process as child (so it stays around until main function terminates)
     let server_address = ServerSideService::spawn_off_thread();
@@ -172,4 +185,11 @@ fn test_runner(_tests: &[&()]) {
     // Test the verifier with panic checks disabled (i.e. verify only the core proof).
     println!("[core_proof]");
     run_verification_core_proof("core_proof", &filter);
+
+    // Check that mutants of the passing tests are rejected. This is slow, and
+    // only enabled by `PRUSTI_TEST_MUTATIONS`.
+    if env::var("PRUSTI_TEST_MUTATIONS").is_ok() {
+        println!("[mutation]");
+        run_verification_mutations("verify", &filter);
+    }
 }
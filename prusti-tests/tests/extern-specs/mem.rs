@@ -0,0 +1,14 @@
+// External specifications loaded by the tests that set
+// `-Pextern_specs_dir=tests/extern-specs`.
+
+use prusti_contracts::*;
+
+#[extern_spec]
+mod std {
+    mod mem {
+        use prusti_contracts::*;
+
+        #[ensures(*a == old(*b) && *b == old(*a))]
+        pub fn swap(a: &mut i32, b: &mut i32);
+    }
+}
@@ -0,0 +1,291 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Mutation testing of the verifier. Each test that is expected to verify is
+//! mutated by weakening its specifications or by strengthening what its code
+//! has to establish, and each mutant is expected to be rejected. A mutant that
+//! verifies hints at a soundness bug, in which the encoding proves too much.
+//!
+//! Some mutations are guaranteed to be wrong (e.g. a postcondition `false` of
+//! a function that returns), and a surviving mutant fails the test run. Other
+//! mutations only usually are (e.g. dropping a conjunct of a precondition,
+//! which might be redundant), and their surviving mutants are only reported.
+//! A line with a `// mutation-ignore` comment is not mutated, and a test with
+//! a `// mutation-ignore-file` comment is skipped.
+//!
+//! A mutant is killed only if Prusti reports a verification error. Mutants
+//! that fail for another reason (e.g. a compilation error, an internal error
+//! or an unsupported feature) say nothing about soundness, and are reported
+//! separately.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// A change of a single line of a test.
+#[derive(Debug)]
+struct Mutant {
+    /// The index of the mutated line.
+    line: usize,
+    mutated_line: String,
+    operator: MutationOperator,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MutationOperator {
+    /// Replace a postcondition with `false`.
+    FalsePostcondition,
+    /// Negate the condition of an `assert!`.
+    NegateAssertion,
+    /// Drop a conjunct of a precondition.
+    DropPreconditionConjunct,
+    /// Increment an integer constant of a postcondition.
+    ChangePostconditionConstant,
+}
+
+impl MutationOperator {
+    /// Whether every mutant of this operator must be rejected.
+    fn must_be_rejected(self) -> bool {
+        match self {
+            MutationOperator::FalsePostcondition => true,
+            MutationOperator::NegateAssertion
+            | MutationOperator::DropPreconditionConjunct
+            | MutationOperator::ChangePostconditionConstant => false,
+        }
+    }
+}
+
+/// Generate the mutants of the lines of a test. Only the specifications and
+/// assertions written on a single line are mutated.
+fn generate_mutants(lines: &[&str]) -> Vec<Mutant> {
+    let mut mutants = vec![];
+    for (index, line) in lines.iter().enumerate() {
+        if line.contains("mutation-ignore") {
+            continue;
+        }
+        let trimmed = line.trim();
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let mut push = |mutated: String, operator| mutants.push(Mutant {
+            line: index,
+            mutated_line: format!("{}{}", indent, mutated),
+            operator,
+        });
+        if let Some(post) = strip_delimiters(trimmed, "#[ensures(", ")]") {
+            if !is_trusted_or_unreachable(lines, index) {
+                push("#[ensures(false)]".to_string(), MutationOperator::FalsePostcondition);
+            }
+            if let Some(mutated_post) = increment_first_integer(post) {
+                push(
+                    format!("#[ensures({})]", mutated_post),
+                    MutationOperator::ChangePostconditionConstant,
+                );
+            }
+        } else if let Some(pre) = strip_delimiters(trimmed, "#[requires(", ")]") {
+            let conjuncts = split_conjuncts(pre);
+            if conjuncts.len() > 1 {
+                for dropped in 0..conjuncts.len() {
+                    let remaining: Vec<_> = conjuncts.iter().enumerate()
+                        .filter(|(index, _)| *index != dropped)
+                        .map(|(_, conjunct)| conjunct.trim())
+                        .collect();
+                    push(
+                        format!("#[requires({})]", remaining.join(" && ")),
+                        MutationOperator::DropPreconditionConjunct,
+                    );
+                }
+            }
+        } else if let Some(condition) = strip_delimiters(trimmed, "assert!(", ");") {
+            push(format!("assert!(!({}));", condition), MutationOperator::NegateAssertion);
+        }
+    }
+    mutants
+}
+
+fn strip_delimiters<'a>(line: &'a str, prefix: &str, suffix: &str) -> Option<&'a str> {
+    // Drop a trailing comment, such as the annotation of an expected error.
+    let line = match line.find("//") {
+        Some(index) => line[..index].trim_end(),
+        None => line,
+    };
+    line.strip_prefix(prefix)?.strip_suffix(suffix)
+}
+
+/// Whether the function to which the attribute on `line` belongs is trusted,
+/// or cannot return, in which case any postcondition verifies.
+fn is_trusted_or_unreachable(lines: &[&str], line: usize) -> bool {
+    let attribute_lines = lines[line..].iter()
+        .take_while(|line| line.trim().starts_with("#[") || line.trim().starts_with("//"))
+        .chain(lines[..line].iter().rev().take_while(|line| line.trim().starts_with("#[")));
+    for attribute in attribute_lines {
+        let attribute = attribute.trim();
+        if attribute.starts_with("#[trusted") || attribute.starts_with("#[requires(false)]") {
+            return true;
+        }
+    }
+    let signature = lines[line..].iter()
+        .find(|line| !line.trim().starts_with("#[") && !line.trim().starts_with("//"));
+    signature.map_or(false, |signature| signature.contains("-> !"))
+}
+
+/// Split `expr` at its top-level `&&`.
+pub(crate) fn split_conjuncts(expr: &str) -> Vec<&str> {
+    let mut conjuncts = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    let bytes = expr.as_bytes();
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth -= 1,
+            // Disjunctions and implications bind weaker than `&&`.
+            b'|' if depth == 0 && bytes.get(index + 1) == Some(&b'|') => return vec![expr],
+            b'=' if depth == 0 && expr[index..].starts_with("==>") => return vec![expr],
+            b'&' if depth == 0 && bytes.get(index + 1) == Some(&b'&') => {
+                conjuncts.push(&expr[start..index]);
+                start = index + 2;
+                index += 1;
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+    conjuncts.push(&expr[start..]);
+    conjuncts
+}
+
+/// Increment the first integer literal of `expr` that is not part of an
+/// identifier.
+pub(crate) fn increment_first_integer(expr: &str) -> Option<String> {
+    let bytes = expr.as_bytes();
+    let mut index = 0;
+    while index < bytes.len() {
+        let is_start = bytes[index].is_ascii_digit()
+            && (index == 0 || !(bytes[index - 1].is_ascii_alphanumeric() || bytes[index - 1] == b'_'));
+        if is_start {
+            let end = expr[index..].find(|c: char| !c.is_ascii_digit())
+                .map_or(expr.len(), |length| index + length);
+            let value: u128 = expr[index..end].parse().ok()?;
+            return Some(format!("{}{}{}", &expr[..index], value + 1, &expr[end..]));
+        }
+        index += 1;
+    }
+    None
+}
+
+/// Whether the output of `prusti-rustc` reports a verification error, as
+/// opposed to e.g. a compilation error or an internal error.
+fn is_verification_failure(stderr: &str) -> bool {
+    stderr.contains("error: [Prusti: verification error]")
+}
+
+fn collect_tests(dir: &Path, filter: &Option<String>, tests: &mut Vec<PathBuf>) {
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .unwrap_or_else(|error| panic!("could not read {:?}: {}", dir, error))
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect_tests(&path, filter, tests);
+        } else if path.extension().map_or(false, |ext| ext == "rs") {
+            let matches_filter = filter.as_ref()
+                .map_or(true, |filter| path.to_string_lossy().contains(filter.as_str()));
+            if matches_filter {
+                tests.push(path);
+            }
+        }
+    }
+}
+
+/// The flags of the `// compile-flags:` header of a test.
+fn compile_flags(lines: &[&str]) -> Vec<String> {
+    lines.iter()
+        .filter_map(|line| line.trim().strip_prefix("// compile-flags:"))
+        .flat_map(|flags| flags.split_whitespace().map(|flag| flag.to_string()))
+        .collect()
+}
+
+/// Mutate the passing tests of `group_name`, and check that the mutants are
+/// rejected by `prusti_rustc`.
+pub fn run_mutation_tests(prusti_rustc: &Path, group_name: &str, filter: &Option<String>) {
+    let mut tests = vec![];
+    collect_tests(&["tests", group_name, "pass"].iter().collect::<PathBuf>(), filter, &mut tests);
+    let work_dir = std::env::temp_dir().join(format!("prusti-mutants-{}", std::process::id()));
+    fs::create_dir_all(&work_dir).unwrap();
+
+    let mut killed = 0;
+    let mut survivors = vec![];
+    let mut errored = vec![];
+    for test in &tests {
+        let source = fs::read_to_string(test).unwrap();
+        if source.contains("ignore-test") || source.contains("mutation-ignore-file") {
+            continue;
+        }
+        let lines: Vec<&str> = source.lines().collect();
+        let flags = compile_flags(&lines);
+        for mutant in generate_mutants(&lines) {
+            let mut mutated_lines = lines.clone();
+            mutated_lines[mutant.line] = &mutant.mutated_line;
+            let mutant_path = work_dir.join(test.file_name().unwrap());
+            fs::write(&mutant_path, mutated_lines.join("\n")).unwrap();
+            let output = Command::new(prusti_rustc)
+                .arg("--edition=2018")
+                .arg("-A")
+                .arg("warnings")
+                .args(&flags)
+                .arg(&mutant_path)
+                .arg("--out-dir")
+                .arg(&work_dir)
+                .output()
+                .expect("failed to execute prusti-rustc");
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if output.status.success() {
+                println!(
+                    "mutant survived ({:?}): {}:{}: {}",
+                    mutant.operator,
+                    test.display(),
+                    mutant.line + 1,
+                    mutant.mutated_line.trim(),
+                );
+                survivors.push((test.clone(), mutant));
+            } else if is_verification_failure(&stderr) {
+                killed += 1;
+            } else {
+                let first_error = stderr.lines()
+                    .find(|line| line.starts_with("error"))
+                    .unwrap_or("no error message")
+                    .to_string();
+                println!(
+                    "mutant failed without a verification error ({:?}): {}:{}: {}: {}",
+                    mutant.operator,
+                    test.display(),
+                    mutant.line + 1,
+                    mutant.mutated_line.trim(),
+                    first_error,
+                );
+                errored.push((test.clone(), mutant));
+            }
+        }
+    }
+    fs::remove_dir_all(&work_dir).ok();
+
+    println!(
+        "mutation testing: {} killed; {} survived ({} of which must be rejected); \
+        {} failed without a verification error",
+        killed,
+        survivors.len(),
+        survivors.iter().filter(|(_, mutant)| mutant.operator.must_be_rejected()).count(),
+        errored.len(),
+    );
+    let unsound: Vec<_> = survivors.iter()
+        .filter(|(_, mutant)| mutant.operator.must_be_rejected())
+        .map(|(test, mutant)| format!("{}:{}", test.display(), mutant.line + 1))
+        .collect();
+    assert!(unsound.is_empty(), "mutants that must be rejected verified: {:?}", unsound);
+}
@@ -0,0 +1,44 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Tests of the mutation operators of `mutation/mod.rs`. The mutation tests
+//! themselves only run with `PRUSTI_TEST_MUTATIONS`, while these tests run
+//! with the default test harness.
+
+#[allow(dead_code)]
+#[path = "mutation/mod.rs"]
+mod mutation;
+
+use mutation::{increment_first_integer, split_conjuncts};
+
+#[test]
+fn split_conjuncts_at_top_level() {
+    assert_eq!(split_conjuncts("a > 0 && b > 0"), vec!["a > 0 ", " b > 0"]);
+    assert_eq!(split_conjuncts("a && (b && c) && f(x && y)"), vec!["a ", " (b && c) ", " f(x && y)"]);
+    assert_eq!(split_conjuncts("v[i && j] && k"), vec!["v[i && j] ", " k"]);
+    assert_eq!(split_conjuncts("x > 0"), vec!["x > 0"]);
+}
+
+#[test]
+fn split_conjuncts_keeps_weaker_operators() {
+    assert_eq!(split_conjuncts("a && b || c"), vec!["a && b || c"]);
+    assert_eq!(split_conjuncts("a && b ==> c"), vec!["a && b ==> c"]);
+    assert_eq!(split_conjuncts("(a || b) && c"), vec!["(a || b) ", " c"]);
+}
+
+#[test]
+fn increment_first_integer_literal() {
+    assert_eq!(increment_first_integer("result == 41"), Some("result == 42".to_string()));
+    assert_eq!(increment_first_integer("x + 9 > 10"), Some("x + 10 > 10".to_string()));
+    assert_eq!(increment_first_integer("0"), Some("1".to_string()));
+}
+
+#[test]
+fn increment_first_integer_skips_identifiers() {
+    assert_eq!(increment_first_integer("x1 == y_2 + 3"), Some("x1 == y_2 + 4".to_string()));
+    assert_eq!(increment_first_integer("v2.len() > k"), None);
+    assert_eq!(increment_first_integer("result"), None);
+}
@@ -0,0 +1,13 @@
+use prusti_contracts::*;
+
+#[ensures(result == x * 2)] //~ ERROR postcondition might not hold
+fn double(x: u8) -> u8 {
+    x << 1
+}
+
+#[ensures(result < 256)]
+fn bounded(x: u8) -> u32 {
+    (x << 1) as u32
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use prusti_contracts::*;
+
+fn read_array(a: [u32; 4], i: usize) -> u32 {
+    a[i] //~ ERROR the array index may be out of bounds
+}
+
+fn write_slice(s: &mut [u32], i: usize) {
+    s[i] = 0; //~ ERROR the array index may be out of bounds
+}
+
+#[requires(i < s.len())]
+fn read_slice(s: &[u32], i: usize) -> u32 {
+    s[i] + s[i + 1] //~ ERROR the array index may be out of bounds
+}
+
+#[requires(i < 4)]
+fn read_array_in_bounds(a: [u32; 4], i: usize) -> u32 {
+    a[i]
+}
+
+fn main() {}
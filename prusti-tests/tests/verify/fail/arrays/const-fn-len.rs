@@ -0,0 +1,22 @@
+#![feature(const_generics, const_evaluatable_checked)]
+#![allow(incomplete_features)]
+
+use prusti_contracts::*;
+
+#[pure]
+const fn double(n: usize) -> usize {
+    n * 2
+}
+
+#[pure]
+#[requires(i <= double(N))]
+fn read<const N: usize>(a: &[u32; double(N)], i: usize) -> u32 {
+    a[i] //~ ERROR the array index may be out of bounds
+}
+
+#[ensures(result == double(x) + 1)] //~ ERROR postcondition might not hold
+fn twice(x: usize) -> usize {
+    x + x
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+#![feature(const_generics)]
+#![allow(incomplete_features)]
+
+use prusti_contracts::*;
+
+#[ensures(result == N)]
+fn size<const N: usize>() -> usize {
+    N
+}
+
+fn caller<const N: usize>() {
+    // The parameters of different functions are different values.
+    assert!(size::<5>() == N); //~ ERROR the asserted expression might not hold
+}
+
+fn main() {}
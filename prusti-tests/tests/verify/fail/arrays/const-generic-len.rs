@@ -0,0 +1,17 @@
+#![feature(const_generics, const_evaluatable_checked)]
+#![allow(incomplete_features)]
+
+use prusti_contracts::*;
+
+#[pure]
+#[requires(i <= N * 2)]
+fn read<const N: usize>(a: &[u32; N * 2], i: usize) -> u32 {
+    a[i] //~ ERROR the array index may be out of bounds
+}
+
+#[pure]
+fn first<const N: usize>(a: &[u32; N * 2]) -> u32 {
+    a[0] //~ ERROR the array index may be out of bounds
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+#![feature(const_generics)]
+#![allow(incomplete_features)]
+
+use prusti_contracts::*;
+
+struct Buf<const N: usize>([u8; N]);
+
+impl<const N: usize> Buf<N> {
+    #[pure]
+    #[requires(i <= N)]
+    fn get(&self, i: usize) -> u8 {
+        self.0[i] //~ ERROR the array index may be out of bounds
+    }
+
+    #[pure]
+    fn first(&self) -> u8 {
+        self.0[0] //~ ERROR the array index may be out of bounds
+    }
+}
+
+fn main() {}
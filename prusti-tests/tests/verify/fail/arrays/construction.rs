@@ -0,0 +1,15 @@
+use prusti_contracts::*;
+
+fn repeat() {
+    let buffer = [0u8; 8];
+    let [first, ..] = buffer;
+    assert!(first == 1); //~ ERROR the asserted expression might not hold
+}
+
+fn literal(x: i32) {
+    let values = [x, 2, 3];
+    let [_, _, c] = values;
+    assert!(c == x); //~ ERROR the asserted expression might not hold
+}
+
+fn main() {}
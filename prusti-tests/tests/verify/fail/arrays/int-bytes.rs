@@ -0,0 +1,13 @@
+use prusti_contracts::*;
+
+fn wrong_order() {
+    let [a, b, c, d] = 0x01020304u32.to_le_bytes();
+    assert!(a == 1); //~ ERROR the asserted expression might not hold
+}
+
+fn wrong_endianness() {
+    let x = u16::from_be_bytes([0x01, 0x02]);
+    assert!(x == 0x0201); //~ ERROR the asserted expression might not hold
+}
+
+fn main() {}
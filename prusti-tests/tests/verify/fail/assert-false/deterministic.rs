@@ -0,0 +1,15 @@
+// compile-flags: -Pdeterministic=true
+
+use prusti_contracts::*;
+
+#[ensures(result > a)] //~ ERROR postcondition might not hold
+fn increment(a: i32) -> i32 {
+    a
+}
+
+fn test(a: i32) {
+    assert!(increment(a) > a);
+    assert!(a > 0); //~ ERROR the asserted expression might not hold
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+// compile-flags: -Pbit_precise=true
+
+fn double_is_larger(x: u32) {
+    if x > 0 {
+        let y = x * 2;
+        assert!(y > x); //~ ERROR the asserted expression might not hold
+    }
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use prusti_contracts::*;
+
+#[bit_precise]
+fn increment_is_not_monotonic(x: u8) {
+    let y = x + 1;
+    assert!(y > x); //~ ERROR the asserted expression might not hold
+}
+
+#[bit_precise]
+fn negation_is_not_positive(x: i32) {
+    if x < 0 {
+        assert!(-x > 0); //~ ERROR the asserted expression might not hold
+    }
+}
+
+fn main() {}
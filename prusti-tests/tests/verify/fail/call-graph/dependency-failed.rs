@@ -0,0 +1,35 @@
+use prusti_contracts::*;
+
+#[ensures(result > 0)] //~ ERROR postcondition might not hold
+fn callee(x: i32) -> i32 {
+    x
+}
+
+// `caller` is reported as skipped because `callee` fails, but its own failure
+// is still an error: it is verified against the contract of `callee`.
+fn caller() { //~ WARNING this procedure is reported as skipped (dependency failed)
+    let y = callee(0);
+    assert!(y == 0); //~ ERROR the asserted expression might not hold
+}
+
+// Mutually recursive procedures that both fail are both reported.
+#[ensures(result > 0)] //~ ERROR postcondition might not hold
+fn ping(n: u32) -> u32 { //~ WARNING this procedure is reported as skipped (dependency failed)
+    if n == 0 { 0 } else { pong(n - 1) }
+}
+
+#[ensures(result > 0)] //~ ERROR postcondition might not hold
+fn pong(n: u32) -> u32 { //~ WARNING this procedure is reported as skipped (dependency failed)
+    if n == 0 { 0 } else { ping(n - 1) }
+}
+
+// A caller that verifies is also reported as skipped.
+fn verified_caller() { //~ WARNING this procedure is reported as skipped (dependency failed)
+    let _ = callee(1);
+}
+
+fn independent() {
+    assert!(false); //~ ERROR the asserted expression might not hold
+}
+
+fn main() {}
@@ -0,0 +1,33 @@
+use prusti_contracts::*;
+use std::cell::{Cell, RefCell};
+
+#[invariant(self.value <= 100)]
+#[derive(Clone, Copy)]
+struct Percentage {
+    value: u8,
+}
+
+fn set_too_large(cell: &Cell<Percentage>) {
+    cell.set(Percentage { value: 101 }); //~ ERROR type invariants might not hold for the arguments of the call
+}
+
+fn increment(cell: &Cell<Percentage>) {
+    let mut percentage = cell.get();
+    percentage.value += 1;
+    cell.set(percentage); //~ ERROR type invariants might not hold for the arguments of the call
+}
+
+fn increment_ref_cell(cell: &RefCell<Percentage>) {
+    let mut guard = cell.borrow_mut();
+    let percentage = &mut *guard; //~ ERROR obligation might not hold on borrow expiry
+    percentage.value += 1;
+}
+
+// The content of a cell is not tracked, only its invariant is known.
+fn read_back(cell: &Cell<Percentage>) {
+    cell.set(Percentage { value: 5 });
+    let percentage = cell.get();
+    assert!(percentage.value == 5); //~ ERROR the asserted expression might not hold
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+// compile-flags: -Pcheck=panics
+
+use prusti_contracts::*;
+
+#[ensures(result == 42)]
+fn wrong_postcondition() -> u32 {
+    0
+}
+
+#[requires(x > 0)]
+fn needs_positive(x: u32) {}
+
+fn panicking(x: u32) {
+    if x == 0 {
+        panic!(); //~ ERROR panic!(..) statement might be reachable
+    }
+}
+
+fn main() {
+    needs_positive(0); //~ ERROR precondition might not hold
+}
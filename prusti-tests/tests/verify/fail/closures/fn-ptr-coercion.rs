@@ -0,0 +1,24 @@
+use prusti_contracts::*;
+
+#[requires(callback |= |x: i32| [
+    requires(x > 0),
+    ensures(result > x)
+])]
+fn register(callback: fn(i32) -> i32) {}
+
+#[requires(x > 10)]
+#[ensures(result == x + 1)]
+fn increment_large(x: i32) -> i32 {
+    x + 1
+}
+
+#[requires(x > 0)]
+#[ensures(result == x)]
+fn identity(x: i32) -> i32 {
+    x
+}
+
+fn main() {
+    register(increment_large); //~ ERROR precondition might not hold
+    register(identity); //~ ERROR precondition might not hold
+}
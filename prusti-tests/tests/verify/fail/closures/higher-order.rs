@@ -0,0 +1,22 @@
+#![feature(stmt_expr_attributes)]
+
+use prusti_contracts::*;
+
+#[requires(f |= |x: i32| [
+    requires(x > 0),
+    ensures(result == x)
+])]
+fn apply<F: Fn(i32) -> i32>(f: F) -> i32 {
+    f(0) //~ ERROR the call of 'F' requires `x > 0`
+}
+
+#[requires(f |= |x: i32| [
+    requires(x > 0),
+    ensures(result == x)
+])]
+#[ensures(result == 2)] //~ ERROR postcondition might not hold
+fn apply_one<F: Fn(i32) -> i32>(f: F) -> i32 {
+    f(1)
+}
+
+fn main() {}
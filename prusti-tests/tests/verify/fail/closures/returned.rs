@@ -0,0 +1,19 @@
+#![feature(stmt_expr_attributes)]
+
+use prusti_contracts::*;
+
+#[ensures(result |= |x: i32| [requires(x >= 0), ensures(result == x + captured + 1)])] //~ ERROR postcondition might not hold
+fn make_adder(captured: i32) -> impl Fn(i32) -> i32 {
+    move |x: i32| x + captured
+}
+
+// The precondition of the closure is not implied.
+#[ensures(result |= |x: i32| [ensures(result == x * factor)])] //~ ERROR postcondition might not hold
+fn make_scaler(factor: i32) -> impl Fn(i32) -> i32 {
+    closure!(
+        requires(x <= 1000),
+        |x: i32| -> i32 { x * factor }
+    )
+}
+
+fn main() {}
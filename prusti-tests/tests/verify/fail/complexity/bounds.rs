@@ -0,0 +1,22 @@
+use prusti_contracts::*;
+
+#[complexity(n)]
+fn too_many_iterations(n: usize) {
+    let mut i = 0;
+    while i <= n { //~ ERROR the loop iteration might exceed the complexity bound of the function
+        body_invariant!(i <= n && credits() + i == n);
+        i += 1;
+    }
+}
+
+#[complexity(n)]
+fn exponential(n: usize) -> usize {
+    if n == 0 {
+        0
+    } else {
+        let a = exponential(n - 1);
+        exponential(n - 1) //~ ERROR the call might exceed the complexity bound of the function
+    }
+}
+
+fn main() {}
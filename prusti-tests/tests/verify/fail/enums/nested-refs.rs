@@ -0,0 +1,20 @@
+use prusti_contracts::*;
+
+enum Shape {
+    Circle(u32),
+    Empty,
+}
+
+#[pure]
+fn is_empty(shape: &&Shape) -> bool {
+    match shape {
+        Shape::Empty => true,
+        _ => false,
+    }
+}
+
+fn main() {
+    let shape = Shape::Circle(1);
+    let shape_ref = &shape;
+    assert!(is_empty(&shape_ref)); //~ ERROR the asserted expression might not hold
+}
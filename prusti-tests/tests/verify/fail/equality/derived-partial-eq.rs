@@ -0,0 +1,33 @@
+use prusti_contracts::*;
+
+#[derive(PartialEq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(PartialEq)]
+enum Shape {
+    Dot(Point),
+    Empty,
+}
+
+#[requires(a.x == b.x)]
+#[ensures(a == b)] //~ ERROR postcondition might not hold
+fn only_one_field(a: &Point, b: &Point) {}
+
+#[ensures(result == Shape::Dot(Point { x: 1, y: 2 }))] //~ ERROR postcondition might not hold
+fn make_dot() -> Shape {
+    Shape::Dot(Point { x: 1, y: 3 })
+}
+
+#[requires(a == b)]
+fn needs_same(a: &Point, b: &Point) {}
+
+fn different() {
+    let p = Point { x: 1, y: 2 };
+    let q = Point { x: 2, y: 2 };
+    needs_same(&p, &q); //~ ERROR precondition might not hold
+}
+
+fn main() {}
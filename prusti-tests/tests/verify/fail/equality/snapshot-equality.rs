@@ -0,0 +1,30 @@
+use prusti_contracts::*;
+
+#[derive(PartialEq, Eq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(PartialEq, Eq)]
+struct WithPtr {
+    ptr: *const i32,
+}
+
+#[requires(a === b)]
+fn needs_same(a: &Point, b: &Point) {}
+
+#[requires(a.x == b.x)]
+#[ensures(a === b)] //~ ERROR postcondition might not hold
+fn only_one_field(a: &Point, b: &Point) {}
+
+#[requires(a === b)] //~ ERROR snapshot equality of values of type
+fn pointers(a: &WithPtr, b: &WithPtr) {}
+
+fn different() {
+    let p = Point { x: 1, y: 2 };
+    let q = Point { x: 1, y: 3 };
+    needs_same(&p, &q); //~ ERROR precondition might not hold
+}
+
+fn main() {}
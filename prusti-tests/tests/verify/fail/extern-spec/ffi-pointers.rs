@@ -0,0 +1,48 @@
+// compile-flags: -Pcheck_raw_pointer_derefs=true
+
+#![feature(type_ascription)]
+#![allow(unused_variables)]
+#![allow(dead_code)]
+#![allow(unused_must_use)]
+
+extern crate prusti_contracts;
+use prusti_contracts::*;
+
+mod ffi {
+    extern "C" {
+        pub fn counter_get(counter: *const u32) -> u32;
+        pub fn counter_free(counter: *mut u32);
+    }
+}
+
+#[extern_spec]
+mod ffi {
+    extern "C" {
+        #[requires(ptr_valid(counter))]
+        #[ensures(ptr_valid(counter))]
+        #[ensures(result == *counter)]
+        pub fn counter_get(counter: *const u32) -> u32;
+
+        #[requires(ptr_valid(counter))]
+        pub fn counter_free(counter: *mut u32);
+    }
+}
+
+unsafe fn get_unchecked(counter: *const u32) -> u32 {
+    ffi::counter_get(counter) //~ ERROR precondition might not hold
+}
+
+#[requires(ptr_valid(counter))]
+unsafe fn use_after_free(counter: *mut u32) -> u32 {
+    ffi::counter_free(counter);
+    std::ptr::read(counter) //~ ERROR the dereferenced raw pointer might not be valid
+}
+
+#[requires(ptr_valid(p))]
+unsafe fn wrong_value(p: *mut u32) {
+    std::ptr::write(p, 7);
+    let value = std::ptr::read(p);
+    assert!(value == 8); //~ ERROR the asserted expression might not hold
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+#![feature(type_ascription)]
+#![allow(unused_variables)]
+#![allow(dead_code)]
+#![allow(unused_must_use)]
+
+extern crate prusti_contracts;
+use prusti_contracts::*;
+
+mod libc {
+    extern "C" {
+        pub fn abs(x: i32) -> i32;
+    }
+}
+
+#[extern_spec]
+mod libc {
+    extern "C" {
+        #[requires(x > -1000 && x < 1000)]
+        #[ensures(result >= 0)]
+        pub fn abs(x: i32) -> i32;
+    }
+}
+
+fn distance_to_zero(x: i32) -> i32 {
+    unsafe { libc::abs(x) } //~ ERROR precondition might not hold
+}
+
+fn main() {}
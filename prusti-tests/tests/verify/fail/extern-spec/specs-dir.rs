@@ -0,0 +1,12 @@
+// compile-flags: -Pextern_specs_dir=tests/extern-specs
+
+use prusti_contracts::*;
+
+fn main() {
+    let mut x = 5;
+    let mut y = 42;
+
+    std::mem::swap(&mut x, &mut y);
+
+    assert!(5 == x); //~ ERROR the asserted expression might not hold
+}
@@ -0,0 +1,17 @@
+// compile-flags: -Pfail_on=verification,unsupported
+
+// A failure that mentions a field named `timeout` is a verification failure,
+// not a timeout of the verifier, so it is still reported as an error.
+
+use prusti_contracts::*;
+
+struct Request {
+    timeout: u32,
+}
+
+#[ensures(result.timeout > 0)] //~ ERROR postcondition might not hold
+fn request_with_timeout(timeout: u32) -> Request {
+    Request { timeout }
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use prusti_contracts::*;
+
+#[ensures(result == x)] //~ ERROR postcondition might not hold
+fn add(x: f64, y: f64) -> f64 {
+    x + y
+}
+
+fn compare(x: f64) {
+    // Without `ENCODE_FLOATS_AS_REALS`, the order of floats is uninterpreted.
+    assert!(x <= x); //~ ERROR the asserted expression might not hold
+}
+
+fn reflexive(x: f64) {
+    // `x` may be NaN, which is not equal to itself.
+    assert!(x == x); //~ ERROR the asserted expression might not hold
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+use prusti_contracts::*;
+
+trait Measure {
+    #[pure]
+    #[ensures(result > 0)]
+    fn size(&self) -> i32;
+
+    #[pure]
+    #[requires(self.size() > limit)]
+    fn exceeds(&self, limit: i32) -> bool;
+}
+
+#[ensures(result > 1)] //~ ERROR postcondition might not hold
+fn size_of<T: Measure>(x: &T) -> i32 {
+    x.size()
+}
+
+fn exceeds_ten<T: Measure>(x: &T) -> bool {
+    x.exceeds(10) //~ ERROR precondition of pure function call might not hold
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+use prusti_contracts::*;
+
+fn ghost_return(x: u32) -> u32 {
+    ghost! {
+        if x == 0 {
+            return 1; //~ ERROR ghost code cannot return from the function
+        }
+    }
+    x
+}
+
+fn ghost_break() {
+    loop {
+        ghost! {
+            break; //~ ERROR ghost code cannot break out of a loop that encloses it
+        }
+    }
+}
+
+fn ghost_local_loop() {
+    ghost! {
+        'ghost: loop {
+            break 'ghost;
+        }
+    }
+}
+
+fn main() {}
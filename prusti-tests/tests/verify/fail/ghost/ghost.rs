@@ -0,0 +1,23 @@
+use prusti_contracts::*;
+
+fn wrong_ghost_counter(n: u32) {
+    let mut i = 0;
+    ghost! {
+        let mut steps = 0;
+    }
+    while i < n {
+        body_invariant!(i < n);
+        i += 1;
+    }
+    ghost! {
+        assert!(steps == n); //~ ERROR the asserted expression might not hold
+    }
+}
+
+fn ghost_division(x: u32) {
+    ghost! {
+        let _y = 10 / x; //~ ERROR attempt to divide by zero
+    }
+}
+
+fn main() {}
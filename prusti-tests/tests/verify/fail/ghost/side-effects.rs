@@ -0,0 +1,50 @@
+use prusti_contracts::*;
+
+fn increment(x: &mut u32) {
+    *x += 1;
+}
+
+#[pure]
+fn double(x: u32) -> u32 {
+    x * 2
+}
+
+#[requires(n < 10)]
+fn ghost_assign(n: u32) -> u32 {
+    let mut i = 0;
+    ghost! {
+        i = n; //~ ERROR ghost code cannot modify a variable that is not declared by ghost code
+    }
+    i
+}
+
+fn ghost_borrow() -> u32 {
+    let mut i = 0;
+    ghost! {
+        let r = &mut i; //~ ERROR ghost code cannot modify a variable that is not declared by ghost code
+        *r = 1;
+    }
+    i
+}
+
+fn ghost_impure_call() -> u32 {
+    ghost! {
+        let mut j = 0;
+        increment(&mut j); //~ ERROR use of impure function 'increment' is not allowed in ghost code
+    }
+    0
+}
+
+#[requires(n < 10)]
+fn ghost_pure_call(n: u32) {
+    ghost! {
+        let mut steps = double(n);
+        steps += 1;
+    }
+    ghost! {
+        steps -= 1;
+        assert!(steps == double(n));
+    }
+}
+
+fn main() {}
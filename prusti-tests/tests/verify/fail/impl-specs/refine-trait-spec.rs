@@ -0,0 +1,34 @@
+use prusti_contracts::*;
+
+trait Withdraw {
+    #[requires(amount <= 100)]
+    fn withdraw(&mut self, amount: u32);
+}
+
+trait Balance {
+    #[ensures(result <= 1000)] //~ ERROR the method's postcondition may not be a valid strengthening
+    fn balance(&self) -> u32;
+}
+
+struct Wallet {
+    balance: u32,
+}
+
+#[refine_trait_spec]
+#[requires(self.balance >= amount)] //~ ERROR the method's precondition may not be a valid weakening
+impl Withdraw for Wallet {
+    fn withdraw(&mut self, amount: u32) {
+        self.balance -= amount;
+    }
+}
+
+#[refine_trait_spec]
+#[ensures(result <= 2000)]
+impl Balance for Wallet {
+    #[trusted]
+    fn balance(&self) -> u32 {
+        self.balance
+    }
+}
+
+fn main() {}
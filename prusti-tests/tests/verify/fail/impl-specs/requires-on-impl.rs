@@ -0,0 +1,22 @@
+use prusti_contracts::*;
+
+struct Counter {
+    value: u32,
+    limit: u32,
+}
+
+#[requires(self.value < self.limit)]
+impl Counter {
+    fn new(limit: u32) -> Self {
+        Counter { value: 0, limit }
+    }
+
+    fn increment(&mut self) {
+        self.value += 1;
+    }
+}
+
+fn main() {
+    let mut counter = Counter::new(0);
+    counter.increment(); //~ ERROR precondition might not hold
+}
@@ -0,0 +1,39 @@
+use prusti_contracts::*;
+
+trait Source {
+    #[ensures(result >= 0)]
+    fn produce(&mut self) -> i32;
+}
+
+struct FromFn<F> {
+    f: F,
+}
+
+impl<F: Fn() -> i32> Source for FromFn<F> {
+    #[trusted]
+    #[ensures(result >= 0)]
+    fn produce(&mut self) -> i32 {
+        let value = (self.f)();
+        if value < 0 { 0 } else { value }
+    }
+}
+
+fn seven() -> i32 {
+    7
+}
+
+#[trusted]
+fn make_source() -> impl Source {
+    FromFn { f: seven }
+}
+
+fn make_untrusted_source() -> impl Source { //~ ERROR the hidden type of 'impl Source' cannot be encoded
+    FromFn { f: seven }
+}
+
+fn main() {
+    let mut source = make_source();
+    // The hidden type is not encoded, so the function item is not known.
+    let value = source.produce();
+    assert!(value == 7); //~ ERROR the asserted expression might not hold
+}
@@ -0,0 +1,43 @@
+use prusti_contracts::*;
+
+trait Counter {
+    #[pure]
+    #[ensures(result >= 0)]
+    fn value(&self) -> i32;
+
+    #[ensures(result >= 0)]
+    fn next(&mut self) -> i32;
+}
+
+struct Natural {
+    value: i32,
+}
+
+impl Counter for Natural {
+    #[pure]
+    #[ensures(result >= 0)]
+    fn value(&self) -> i32 {
+        if self.value < 0 { 0 } else { self.value }
+    }
+
+    #[ensures(result >= 0)]
+    fn next(&mut self) -> i32 {
+        if self.value < 0 {
+            self.value = 0;
+        }
+        self.value
+    }
+}
+
+// The contract of `Counter::value` does not tell that the value is positive.
+#[ensures(result.value() > 0)] //~ ERROR postcondition might not hold
+fn make(start: i32) -> impl Counter {
+    Natural { value: start }
+}
+
+fn main() {
+    let mut counter = make(5);
+    // Callers only learn what the contract of `make` states.
+    let value = counter.next();
+    assert!(value == 5); //~ ERROR the asserted expression might not hold
+}
@@ -0,0 +1,41 @@
+use prusti_contracts::*;
+
+#[pure]
+#[requires(n >= 0)]
+fn sum(n: i32) -> i32 {
+    if n == 0 { 0 } else { n + sum(n - 1) }
+}
+
+#[lemma]
+#[requires(n >= 0)]
+#[ensures(sum(n) > n)] //~ ERROR postcondition of pure function definition might not hold
+#[decreases(n)]
+fn wrong_lemma(n: i32) -> bool {
+    if n == 0 { true } else { wrong_lemma(n - 1) }
+}
+
+#[lemma]
+fn false_lemma(n: i32) -> bool { //~ ERROR the lemma might not return `true`
+    n > 10
+}
+
+#[lemma]
+#[ensures(false)]
+fn circular_lemma(n: i32) -> bool { //~ ERROR recursive lemma requires a #[decreases] annotation
+    circular_lemma(n)
+}
+
+#[lemma]
+#[requires(n > 0)]
+#[ensures(n >= 1)]
+fn positive_sum(n: i32) -> bool {
+    true
+}
+
+fn wrong_application(n: i32) {
+    ghost! {
+        positive_sum(n); //~ ERROR precondition of lemma might not hold
+    }
+}
+
+fn main() {}
@@ -0,0 +1,26 @@
+// compile-flags: -Pinfer_loop_invariants=true
+
+use prusti_contracts::*;
+
+fn not_monotonic(n: u32) {
+    let mut x: i32 = 5;
+    let mut i = 0;
+    while i < n {
+        x -= 1;
+        x += 2;
+        i += 1;
+    }
+    assert!(x >= 5); //~ ERROR might not hold
+}
+
+fn scaled(n: u32) {
+    let mut x: i32 = 1;
+    let mut i = 0;
+    while i < n {
+        x *= 2;
+        i += 1;
+    }
+    assert!(x >= 1); //~ ERROR might not hold
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+fn main() {
+    let mut a = 1;
+    let mut b = 2;
+    let mut refs = Vec::new();
+    refs.push(&mut a);
+    refs.push(&mut b);
+    // The values might have been changed through the collection.
+    drop(refs);
+    assert!(a == 1); //~ ERROR the asserted expression might not hold
+}
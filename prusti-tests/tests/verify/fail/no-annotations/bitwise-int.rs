@@ -0,0 +1,11 @@
+extern crate prusti_contracts;
+
+fn test_or(x: u32, y: u32) {
+    assert!(x | y == x + y); //~ ERROR the asserted expression might not hold
+}
+
+fn test_xor(x: i32) {
+    assert!(x ^ 1 == x - 1); //~ ERROR the asserted expression might not hold
+}
+
+fn main() {}
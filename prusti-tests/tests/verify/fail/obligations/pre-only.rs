@@ -0,0 +1,17 @@
+// compile-flags: -Pchecked_obligations=pre
+
+use prusti_contracts::*;
+
+#[requires(x > 0)]
+#[ensures(result == 0)]
+fn identity(x: u32) -> u32 {
+    x
+}
+
+fn panicking() {
+    panic!();
+}
+
+fn main() {
+    identity(0); //~ ERROR precondition might not hold
+}
@@ -0,0 +1,21 @@
+use prusti_contracts::*;
+
+#[ensures(result.unwrap() == x)] //~ ERROR unwrapping in pure function might panic, because the value might not be `Some`
+fn positive(x: i32) -> Option<i32> {
+    if x > 0 { Some(x) } else { None }
+}
+
+#[pure]
+fn get(value: Option<i32>) -> i32 {
+    value.unwrap() //~ ERROR unwrapping in pure function might panic, because the value might not be `Some`
+}
+
+fn unwrap_none(value: Option<i32>) -> i32 {
+    value.unwrap() //~ ERROR unwrapping might panic, because the value might not be `Some`
+}
+
+fn expect_err(value: Result<i32, i32>) -> i32 {
+    value.expect_err("no error") //~ ERROR unwrapping might panic, because the value might not be `Err`
+}
+
+fn main() {}
@@ -0,0 +1,26 @@
+#![feature(or_patterns)]
+
+use prusti_contracts::*;
+
+#[pure]
+fn is_small(x: Option<u32>) -> bool {
+    match x {
+        Some(1 | 2) => true,
+        _ => false,
+    }
+}
+
+fn guard(x: Option<i32>) -> i32 {
+    match x {
+        Some(y) if y > 0 => y,
+        Some(y) => {
+            assert!(y < 0); //~ ERROR the asserted expression might not hold
+            -y
+        }
+        None => 0,
+    }
+}
+
+fn main() {
+    assert!(is_small(Some(3))); //~ ERROR the asserted expression might not hold
+}
@@ -0,0 +1,26 @@
+use prusti_contracts::*;
+
+fn classify(x: u8) -> u8 {
+    match x {
+        0..=9 => {
+            assert!(x < 9); //~ ERROR the asserted expression might not hold
+            0
+        }
+        _ => {
+            assert!(x > 10); //~ ERROR the asserted expression might not hold
+            1
+        }
+    }
+}
+
+#[pure]
+fn is_lowercase(c: char) -> bool {
+    match c {
+        'a'..='z' => true,
+        _ => false,
+    }
+}
+
+fn main() {
+    assert!(is_lowercase('A')); //~ ERROR the asserted expression might not hold
+}
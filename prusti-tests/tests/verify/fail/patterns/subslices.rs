@@ -0,0 +1,10 @@
+use prusti_contracts::*;
+
+#[requires(items.len() == 3)]
+fn rest_of_slice(items: &[u32]) {
+    if let [_, rest @ ..] = items {
+        assert!(rest.len() == 3); //~ ERROR the asserted expression might not hold
+    }
+}
+
+fn main() {}
@@ -0,0 +1,43 @@
+use prusti_contracts::*;
+
+struct Buffer {
+    len: usize,
+    cap: usize,
+}
+
+predicate! {
+    fn has_room(buf: &Buffer) -> bool {
+        buf.len < buf.cap
+    }
+}
+
+predicate! {
+    fn in_range(x: i32, lo: i32, hi: i32) -> bool {
+        lo <= x && x <= hi
+    }
+}
+
+impl Buffer {
+    #[ensures(result.len == 0 && result.cap == cap)]
+    fn new(cap: usize) -> Self {
+        Buffer { len: 0, cap }
+    }
+
+    #[requires(has_room(self))]
+    #[ensures(self.len == old(self.len) + 1)]
+    fn push(&mut self) {
+        self.len += 1;
+    }
+}
+
+#[requires(in_range(x, 0, 10))]
+#[ensures(in_range(result, 0, 10))] //~ ERROR postcondition might not hold
+fn succ(x: i32) -> i32 {
+    x + 1
+}
+
+fn main() {
+    let mut buf = Buffer::new(1);
+    buf.push();
+    buf.push(); //~ ERROR precondition might not hold
+}
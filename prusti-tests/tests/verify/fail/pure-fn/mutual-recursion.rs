@@ -0,0 +1,13 @@
+use prusti_contracts::*;
+
+#[pure]
+fn is_even(n: u32) -> bool { //~ ERROR pure function is mutually recursive with 'is_odd'
+    if n == 0 { true } else { is_odd(n - 1) }
+}
+
+#[pure]
+fn is_odd(n: u32) -> bool { //~ ERROR pure function is mutually recursive with 'is_even'
+    if n == 0 { false } else { is_even(n - 1) }
+}
+
+fn main() {}
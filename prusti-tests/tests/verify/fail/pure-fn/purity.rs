@@ -0,0 +1,24 @@
+use prusti_contracts::*;
+
+static LIMIT: u32 = 10;
+
+fn impure(x: u32) -> u32 {
+    x
+}
+
+#[pure]
+fn reads_static() -> u32 {
+    LIMIT //~ ERROR reading the static 'LIMIT' is not allowed in a pure function
+}
+
+#[pure]
+fn calls_impure(x: u32) -> u32 {
+    impure(x) //~ ERROR use of impure function 'impure' is not allowed in a pure function
+}
+
+#[pure]
+fn calls_pointer(f: fn(u32) -> u32, x: u32) -> u32 {
+    f(x) //~ ERROR calling a function pointer is not allowed in a pure function
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use prusti_contracts::*;
+
+#[pure]
+fn unbox(x: u32) -> u32 {
+    let boxed = Box::new(x); //~ ERROR heap allocation is not allowed in a pure function
+    *boxed
+}
+
+fn main() {}
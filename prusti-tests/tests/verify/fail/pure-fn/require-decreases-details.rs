@@ -0,0 +1,14 @@
+// compile-flags: -Prequire_decreases=true
+
+// The tests are run with `--error-format json`, so the errors carry the
+// details of the Prusti errors in a note.
+
+use prusti_contracts::*;
+
+#[pure]
+fn sum(n: u32) -> u32 { //~ ERROR recursive pure function requires a #[decreases] annotation
+    //~^ NOTE "kind":"invalid-specification"
+    if n == 0 { 0 } else { n + sum(n - 1) }
+}
+
+fn main() {}
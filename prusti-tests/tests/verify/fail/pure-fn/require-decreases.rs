@@ -0,0 +1,10 @@
+// compile-flags: -Prequire_decreases=true
+
+use prusti_contracts::*;
+
+#[pure]
+fn sum(n: u32) -> u32 { //~ ERROR recursive pure function requires a #[decreases] annotation
+    if n == 0 { 0 } else { n + sum(n - 1) }
+}
+
+fn main() {}
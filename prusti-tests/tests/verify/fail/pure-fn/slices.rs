@@ -0,0 +1,23 @@
+use prusti_contracts::*;
+
+struct Item {
+    weight: u32,
+}
+
+#[pure]
+fn first_weight(items: &[Item]) -> u32 {
+    items[0].weight //~ ERROR the array index may be out of bounds
+}
+
+#[pure]
+#[requires(items.len() > 1)]
+fn second_weight(items: &[Item]) -> u32 {
+    items[1].weight
+}
+
+#[requires(items.len() > 1)]
+fn compare(items: &[Item]) {
+    assert!(second_weight(items) == 0); //~ ERROR the asserted expression might not hold
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use prusti_contracts::*;
+
+#[pure]
+fn first_byte(s: &str) -> u8 {
+    s.as_bytes()[0] //~ ERROR the array index may be out of bounds
+}
+
+#[pure]
+fn has_prefix(s: &str, prefix: &str) -> bool {
+    s.starts_with(prefix)
+}
+
+#[requires(has_prefix(input, prefix))]
+#[ensures(input.len() == prefix.len())] //~ ERROR postcondition might not hold
+fn prefix_len(input: &str, prefix: &str) {}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use prusti_contracts::*;
+
+#[ensures(result.len() == 0)] //~ ERROR postcondition might not hold
+fn pass_through(s: String) -> String {
+    s
+}
+
+#[requires(s.len() > 0)]
+#[ensures(s.is_empty())] //~ ERROR postcondition might not hold
+fn non_empty(s: &String) {}
+
+fn main() {}
@@ -35,4 +35,16 @@ pub fn test3() {
     assert!(fib(4) == 5);
 }
 
+#[requires(forall(|n: isize| triggers=[(dummy(n, n))] fib(n) == n))]
+pub fn test4() {
+    dummy(5, 5);
+    assert!(fib(5) == 5);
+}
+
+#[requires(forall(|n: isize| triggers=[(dummy(n, n))] fib(n) == n))]
+pub fn test5() {
+    // The quantifier is not instantiated without a matching trigger.
+    assert!(fib(5) == 5);   //~ ERROR the asserted expression might not hold
+}
+
 fn main() {}
@@ -0,0 +1,17 @@
+use prusti_contracts::*;
+use std::ptr;
+
+fn unrelated(p: *const u32, q: *const u32) {
+    assert!(p == q); //~ ERROR the asserted expression might not hold
+}
+
+#[ensures(result == 1)] //~ ERROR postcondition might not hold
+fn branch(p: *const u32, q: *const u32) -> u32 {
+    if ptr::eq(p, q) {
+        1
+    } else {
+        2
+    }
+}
+
+fn main() {}
@@ -0,0 +1,31 @@
+// compile-flags: -Pcheck_raw_pointer_derefs=true
+
+use prusti_contracts::*;
+
+unsafe fn read(p: *const u32) -> u32 {
+    *p //~ ERROR the dereferenced raw pointer might not be valid
+}
+
+unsafe fn is_zero(p: *const u32) -> bool {
+    match *p { //~ ERROR the dereferenced raw pointer might not be valid
+        0 => true,
+        _ => false,
+    }
+}
+
+#[requires(ptr_valid(p))]
+unsafe fn consume(p: *mut u32) {}
+
+#[requires(ptr_valid(p))]
+unsafe fn use_after_consume(p: *mut u32) {
+    consume(p);
+    *p = 1; //~ ERROR the dereferenced raw pointer might not be valid
+}
+
+#[requires(ptr_valid(p))]
+#[ensures(ptr_valid(p))]
+unsafe fn lose_validity(p: *mut u32) { //~ ERROR postcondition might not hold
+    consume(p);
+}
+
+fn main() {}
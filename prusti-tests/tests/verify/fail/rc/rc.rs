@@ -0,0 +1,21 @@
+use prusti_contracts::*;
+use std::rc::Rc;
+use std::sync::Arc;
+
+#[ensures(*result == 0)] //~ ERROR postcondition might not hold
+fn share(value: i32) -> Rc<i32> {
+    Rc::new(value)
+}
+
+fn clone_rc() {
+    let a = Rc::new(5);
+    let b = a.clone();
+    assert!(*b == 6); //~ ERROR the asserted expression might not hold
+}
+
+fn clone_arc(a: Arc<i32>) {
+    let b = a.clone();
+    assert!(*b > 0); //~ ERROR the asserted expression might not hold
+}
+
+fn main() {}
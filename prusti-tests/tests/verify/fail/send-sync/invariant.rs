@@ -0,0 +1,49 @@
+// compile-flags: -Pcheck_send_sync_impls=true
+
+use prusti_contracts::*;
+use std::cell::Cell;
+
+struct Slots {
+    used: usize,
+    capacity: usize,
+}
+
+#[thread_safety_invariant(self.used <= self.capacity)] //~ ERROR postcondition might not hold
+impl Slots {
+    fn new(capacity: usize) -> Self {
+        Slots { used: 0, capacity }
+    }
+
+    fn acquire(&mut self) {
+        self.used += 1;
+    }
+}
+
+unsafe impl Send for Slots {}
+
+struct Unchecked {
+    value: u32,
+}
+
+unsafe impl Sync for Unchecked {} //~ ERROR is not justified by a thread-safety invariant
+
+struct Trivial {
+    value: u32,
+}
+
+#[thread_safety_invariant(true)]
+impl Trivial {}
+
+unsafe impl Send for Trivial {} //~ ERROR does not constrain any of its fields
+
+struct Shared {
+    len: usize,
+    hits: Cell<usize>,
+}
+
+#[thread_safety_invariant(self.len > 0)]
+impl Shared {}
+
+unsafe impl Sync for Shared {} //~ ERROR the thread-safety invariant does not constrain the field `hits`
+
+fn main() {}
@@ -0,0 +1,28 @@
+use prusti_contracts::*;
+
+#[requires(x > 0)]
+#[requires(x < 100)]
+fn bounded(x: i32) -> i32 {
+    x
+}
+
+#[requires(y != 0)]
+fn non_zero(y: i32) -> i32 {
+    y
+}
+
+fn test_second_clause(a: i32) -> i32 {
+    bounded(5) + bounded(a) //~ ERROR precondition might not hold: the call of 'bounded' requires `x > 0`
+}
+
+#[requires(a > 0)]
+fn test_several_calls(a: i32) -> i32 {
+    bounded(a) + non_zero(a - 1) //~ ERROR precondition might not hold: the call of 'bounded' requires `x < 100`
+}
+
+#[requires(a > 0 && a < 100)]
+fn test_other_callee(a: i32) -> i32 {
+    bounded(a) + non_zero(a - 1) //~ ERROR precondition might not hold: the call of 'non_zero' requires `y != 0`
+}
+
+fn main() {}
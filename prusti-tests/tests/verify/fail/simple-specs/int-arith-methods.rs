@@ -0,0 +1,22 @@
+use prusti_contracts::*;
+
+fn test_wrapping() {
+    let x: u8 = 200;
+    assert!(x.wrapping_add(100) == 300); //~ ERROR the asserted expression might not hold
+}
+
+fn test_checked(x: u32, y: u32) {
+    assert!(x.checked_add(y).is_some()); //~ ERROR the asserted expression might not hold
+}
+
+fn test_overflowing(x: u8) {
+    let (_, overflow) = x.overflowing_add(1);
+    assert!(!overflow); //~ ERROR the asserted expression might not hold
+}
+
+#[ensures(result == x.saturating_sub(y))] //~ ERROR postcondition might not hold
+fn difference(x: u16, y: u16) -> u16 {
+    x.wrapping_sub(y)
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use prusti_contracts::*;
+
+#[ensures(|ret| ret == result + 1)] //~ ERROR postcondition might not hold
+fn next(result: u32) -> u32 {
+    result
+}
+
+fn main() {}
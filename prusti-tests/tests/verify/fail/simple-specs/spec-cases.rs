@@ -0,0 +1,17 @@
+use prusti_contracts::*;
+
+#[ensures(result == a, when = "a >= b")]
+#[ensures(result == a, when = "a < b")] //~ ERROR postcondition might not hold
+fn max(a: i32, b: i32) -> i32 {
+    if a >= b { a } else { b }
+}
+
+#[requires(divisor != 0, when = "dividend != 0")]
+fn safe_div(dividend: u32, divisor: u32) -> u32 {
+    if dividend == 0 { 0 } else { dividend / divisor }
+}
+
+fn main() {
+    safe_div(0, 0);
+    safe_div(4, 0); //~ ERROR precondition might not hold
+}
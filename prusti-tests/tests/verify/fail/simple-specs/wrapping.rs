@@ -0,0 +1,18 @@
+use prusti_contracts::*;
+
+#[trusted]
+#[ensures(result == wrap_add(a, b))]
+fn add(a: u8, b: u8) -> u8 {
+    a.wrapping_add(b)
+}
+
+fn test_add() {
+    assert!(add(200, 100) == 0); //~ ERROR the asserted expression might not hold
+}
+
+#[ensures(result == trunc::<i8, _>(x))] //~ ERROR postcondition might not hold
+fn to_signed(x: u8) -> i8 {
+    0
+}
+
+fn main() {}
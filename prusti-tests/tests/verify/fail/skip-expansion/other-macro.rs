@@ -0,0 +1,15 @@
+#![prusti::skip_expansion_of(other_macro)]
+
+use prusti_contracts::*;
+
+macro_rules! generate_panicking_fn {
+    ($name:ident) => {
+        fn $name() {
+            panic!(); //~ ERROR panic!(..) statement might be reachable
+        }
+    };
+}
+
+generate_panicking_fn!(generated);
+
+fn main() {}
@@ -0,0 +1,30 @@
+use prusti_contracts::*;
+
+state_machine! {
+    enum FileState {
+        Closed => Open,
+        Open => Closed | Eof,
+        Eof => Closed,
+    }
+}
+
+struct File {
+    state: FileState,
+}
+
+impl File {
+    #[ensures(result.state.is(FileState::Closed))]
+    fn new() -> Self {
+        File { state: FileState::Closed }
+    }
+
+    #[ensures(self.state.is(FileState::Open))]
+    fn open(&mut self) {
+        self.state.transition(FileState::Open); //~ ERROR precondition might not hold
+    }
+}
+
+fn main() {
+    let mut file = File::new();
+    file.state.transition(FileState::Eof); //~ ERROR precondition might not hold
+}
@@ -0,0 +1,37 @@
+use prusti_contracts::*;
+
+state_machine! {
+    enum ConnectionState {
+        Closed => Connected,
+        Connected => Authenticated | Closed,
+        Authenticated => Closed,
+    }
+}
+
+struct Connection {
+    state: ConnectionState,
+}
+
+impl Connection {
+    #[ensures(result.state.is(ConnectionState::Closed))]
+    fn new() -> Self {
+        Connection { state: ConnectionState::Closed }
+    }
+
+    #[in_state(ConnectionState::Closed => ConnectionState::Connected)]
+    fn connect(&mut self) {
+        self.state.transition(ConnectionState::Connected);
+    }
+
+    #[in_state(ConnectionState::Connected => ConnectionState::Authenticated)] //~ ERROR postcondition might not hold
+    fn login(&mut self) {}
+
+    #[in_state(ConnectionState::Authenticated)]
+    fn send(&self, _data: u32) {}
+}
+
+fn main() {
+    let mut connection = Connection::new();
+    connection.connect();
+    connection.send(1); //~ ERROR precondition might not hold
+}
@@ -0,0 +1,13 @@
+// compile-flags: -Phavoc_mutable_global_state=true
+
+use prusti_contracts::*;
+
+static mut COUNTER: u32 = 0;
+
+fn main() {
+    unsafe {
+        COUNTER = 5;
+        // Every access of the static yields an arbitrary value.
+        assert!(COUNTER == 5); //~ ERROR the asserted expression might not hold
+    }
+}
@@ -0,0 +1,25 @@
+use prusti_contracts::*;
+
+#[pure]
+#[decreases(n)]
+fn stuck(n: u32) -> u32 {
+    if n == 0 { 0 } else { stuck(n) } //~ ERROR the termination measure might not decrease at this recursive call
+}
+
+#[decreases(n)]
+fn count_up(n: u32) {
+    if n < 10 {
+        count_up(n + 1); //~ ERROR the termination measure might not decrease at this recursive call
+    }
+}
+
+fn loop_forever(n: usize) {
+    let mut i = 0;
+    while i < n {
+        body_invariant!(i < n);
+        body_variant!(n - i); //~ ERROR the loop variant might not decrease in this loop iteration
+        i += 0;
+    }
+}
+
+fn main() {}
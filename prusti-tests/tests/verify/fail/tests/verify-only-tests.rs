@@ -0,0 +1,14 @@
+// compile-flags: --test -Pverify_only_tests=true
+
+use prusti_contracts::*;
+
+#[ensures(result == x + 1)]
+fn inc(x: u32) -> u32 {
+    x + 1
+}
+
+#[test]
+fn test_inc() {
+    let y = inc(1);
+    assert!(y == 3); //~ ERROR the asserted expression might not hold
+}
@@ -0,0 +1,29 @@
+use prusti_contracts::*;
+
+trait Foo {
+    #[requires(val > 10)]
+    fn foo(&self, val: i32) -> i32;
+
+    #[pure]
+    #[requires(val >= 0)]
+    fn bar(&self, val: i32) -> i32;
+}
+
+struct Dummy { }
+
+#[refine_trait_spec]
+impl Foo for Dummy {
+    #[trusted]
+    #[requires(val > 100)] //~ ERROR the method's precondition may not be a valid weakening
+    fn foo(&self, val: i32) -> i32 {
+        val
+    }
+
+    #[pure]
+    #[requires(val >= 10)] //~ ERROR the method's precondition may not be a valid weakening
+    fn bar(&self, val: i32) -> i32 {
+        val
+    }
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+use prusti_contracts::*;
+
+trait Shape {
+    #[ensures(result >= 3)]
+    fn sides(&self) -> u32;
+}
+
+struct Square;
+
+impl Shape for Square {
+    fn sides(&self) -> u32 {
+        4
+    }
+}
+
+fn count(shape: &dyn Shape) -> u32 {
+    let n = shape.sides();
+    assert!(n == 4); //~ ERROR the asserted expression might not hold
+    n
+}
+
+fn boxed() {
+    let shape: Box<dyn Shape> = Box::new(Square);
+    let n = shape.sides();
+    assert!(n == 4); //~ ERROR the asserted expression might not hold
+}
+
+fn main() {}
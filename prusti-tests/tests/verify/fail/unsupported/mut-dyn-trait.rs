@@ -0,0 +1,29 @@
+use prusti_contracts::*;
+
+trait Counter {
+    fn increment(&mut self);
+}
+
+struct Simple {
+    value: u32,
+}
+
+impl Counter for Simple {
+    #[trusted]
+    fn increment(&mut self) {
+        self.value += 1;
+    }
+}
+
+fn increment_twice(counter: &mut dyn Counter) {
+    counter.increment();
+    counter.increment();
+}
+
+fn test() {
+    let mut simple = Simple { value: 0 };
+    increment_twice(&mut simple); //~ ERROR mutable references to trait objects are not supported
+    assert!(simple.value == 0);
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use prusti_contracts::*;
+
+static mut COUNTER: u32 = 0;
+
+fn increment() {
+    unsafe {
+        COUNTER += 1; //~ ERROR unsupported: mutable global state
+    }
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+struct Pair {
+    first: i32,
+    second: i32,
+}
+
+fn collect<'a>(pair: &'a mut Pair, fields: &mut Vec<&'a mut i32>) {
+    fields.push(&mut pair.first); //~ ERROR storing a mutable reference by calling
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use prusti_contracts::*;
+use std::cell::Cell;
+
+thread_local! {
+    static COUNTER: Cell<u32> = Cell::new(0);
+}
+
+#[trusted]
+fn get(counter: &Cell<u32>) -> u32 {
+    counter.get()
+}
+
+fn read() -> u32 {
+    COUNTER.with(get) //~ ERROR unsupported: mutable global state
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+use prusti_contracts::*;
+
+#[pure]
+fn get(v: &Vec<i32>, index: usize) -> i32 {
+    v[index] //~ ERROR the array index may be out of bounds
+}
+
+fn read_past_end(v: &Vec<i32>) -> i32 {
+    v[v.len()] //~ ERROR the array index may be out of bounds
+}
+
+fn remove_from_empty() {
+    let mut v: Vec<i32> = Vec::new();
+    v.remove(0); //~ ERROR the array index may be out of bounds
+}
+
+fn main() {
+    let mut v = Vec::new();
+    v.push(1);
+    v.push(2);
+    let last = v.pop();
+    assert!(last.is_some());
+    assert!(last.unwrap() == 1); //~ ERROR the asserted expression might not hold
+}
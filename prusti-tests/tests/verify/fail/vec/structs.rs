@@ -0,0 +1,15 @@
+use prusti_contracts::*;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn main() {
+    let mut v = Vec::new();
+    v.push(Point { x: 1, y: 2 });
+    v.push(Point { x: 3, y: 4 });
+    let last = v.pop().unwrap();
+    assert!(last.x == 1); //~ ERROR the asserted expression might not hold
+}
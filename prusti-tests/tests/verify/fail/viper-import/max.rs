@@ -0,0 +1,13 @@
+use prusti_contracts::*;
+
+#[viper_import("max.vpr", "max")]
+fn max(a: i32, b: i32) -> i32 {
+    if a > b { a } else { b }
+}
+
+#[ensures(result == a)] //~ ERROR postcondition might not hold
+fn test_call(a: i32, b: i32) -> i32 {
+    max(a, b)
+}
+
+fn main() {}
@@ -0,0 +1,44 @@
+#![feature(const_generics, const_evaluatable_checked)]
+#![allow(incomplete_features)]
+
+use prusti_contracts::*;
+
+#[pure]
+const fn double(n: usize) -> usize {
+    n * 2
+}
+
+const LEN: usize = double(4);
+
+#[pure]
+#[requires(i < double(N))]
+fn read<const N: usize>(a: &[u32; double(N)], i: usize) -> u32 {
+    a[i]
+}
+
+#[pure]
+fn last<const N: usize>(a: &[u32; double(N) + 1]) -> u32 {
+    a[double(N)]
+}
+
+#[pure]
+#[ensures(result == double(N) + LEN)]
+fn len<const N: usize>(_a: &[u32; double(N) + LEN]) -> usize {
+    double(N) + LEN
+}
+
+#[ensures(result == 8)]
+fn fixed_len(a: &[u32; LEN]) -> usize {
+    a.len()
+}
+
+#[requires(x < 100)]
+#[ensures(result == double(x))]
+fn twice(x: usize) -> usize {
+    x + x
+}
+
+fn main() {
+    let a = [0; LEN];
+    assert!(fixed_len(&a) == double(4));
+}
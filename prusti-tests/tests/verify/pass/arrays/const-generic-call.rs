@@ -0,0 +1,26 @@
+#![feature(const_generics)]
+#![allow(incomplete_features)]
+
+use prusti_contracts::*;
+
+#[ensures(result == N)]
+fn size<const N: usize>() -> usize {
+    N
+}
+
+#[pure]
+#[ensures(result == N + 1)]
+fn size_plus_one<const N: usize>() -> usize {
+    N + 1
+}
+
+#[ensures(result == M)]
+fn forward<const M: usize>() -> usize {
+    size::<M>()
+}
+
+fn main() {
+    assert!(size::<5>() == 5);
+    assert!(size_plus_one::<2>() == 3);
+    assert!(forward::<7>() == 7);
+}
@@ -0,0 +1,23 @@
+#![feature(const_generics, const_evaluatable_checked)]
+#![allow(incomplete_features)]
+
+use prusti_contracts::*;
+
+#[pure]
+#[requires(i < N * 2)]
+fn read<const N: usize>(a: &[u32; N * 2], i: usize) -> u32 {
+    a[i]
+}
+
+#[pure]
+fn last<const N: usize>(a: &[u32; N + 1]) -> u32 {
+    a[N]
+}
+
+#[pure]
+#[ensures(result == N * 2)]
+fn len<const N: usize>(_a: &[u32; N * 2]) -> usize {
+    N * 2
+}
+
+fn main() {}
@@ -0,0 +1,46 @@
+#![feature(const_generics)]
+#![allow(incomplete_features)]
+
+use prusti_contracts::*;
+
+struct Buf<const N: usize>([u8; N]);
+
+impl<const N: usize> Buf<N> {
+    #[pure]
+    fn capacity(&self) -> usize {
+        N
+    }
+
+    #[pure]
+    #[requires(i < N)]
+    fn get(&self, i: usize) -> u8 {
+        self.0[i]
+    }
+
+    #[pure]
+    #[requires(0 < N)]
+    fn first(&self) -> u8 {
+        self.0[0]
+    }
+}
+
+struct Pair<const N: usize> {
+    left: Buf<N>,
+    right: Buf<N>,
+}
+
+#[requires(i < N)]
+#[ensures(result == pair.left.get(i))]
+fn left<const N: usize>(pair: &Pair<N>, i: usize) -> u8 {
+    pair.left.get(i)
+}
+
+#[ensures(result == buf.capacity())]
+fn capacity<const N: usize>(buf: &Buf<N>) -> usize {
+    N
+}
+
+fn main() {
+    let _small = Buf([1, 2, 3]);
+    let _large = Buf([0; 16]);
+}
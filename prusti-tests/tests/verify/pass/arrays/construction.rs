@@ -0,0 +1,39 @@
+use prusti_contracts::*;
+
+fn repeat() {
+    let buffer = [0u8; 32];
+    let [first, .., last] = buffer;
+    assert!(first == 0);
+    assert!(last == 0);
+}
+
+fn literal(x: i32) {
+    let values = [x, 2, 3];
+    let [a, b, c] = values;
+    assert!(a == x);
+    assert!(b == 2);
+    assert!(c == 3);
+}
+
+fn copy() {
+    let values = [true, false];
+    let copied = values;
+    let [a, b] = copied;
+    assert!(a && !b);
+}
+
+#[requires(x > 0)]
+fn repeat_variable(x: i32) {
+    let values = [x; 4];
+    let [_, _, c, _] = values;
+    assert!(c > 0);
+}
+
+fn large() {
+    let buffer = [7u64; 65536];
+    let copied = buffer;
+    let [first, .., last] = copied;
+    assert!(first == 7 && last == 7);
+}
+
+fn main() {}
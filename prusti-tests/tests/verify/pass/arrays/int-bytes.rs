@@ -0,0 +1,25 @@
+use prusti_contracts::*;
+
+fn serialize(x: u32) -> [u8; 4] {
+    x.to_le_bytes()
+}
+
+#[ensures(result == x)]
+fn round_trip(x: u32) -> u32 {
+    let bytes = x.to_le_bytes();
+    u32::from_le_bytes(bytes)
+}
+
+fn main() {
+    let [a, b, c, d] = 0x01020304u32.to_le_bytes();
+    assert!(a == 4 && b == 3 && c == 2 && d == 1);
+    let [e, f] = 0x0102u16.to_be_bytes();
+    assert!(e == 1 && f == 2);
+    let x = i16::from_le_bytes([0xff, 0xff]);
+    assert!(x == -1);
+    let [g, h] = (-2i16).to_le_bytes();
+    assert!(g == 0xfe && h == 0xff);
+    let y = round_trip(7);
+    assert!(y == 7);
+    let _ = serialize(y);
+}
@@ -0,0 +1,30 @@
+use prusti_contracts::*;
+
+#[bit_precise]
+#[ensures(result == x)]
+fn add_wrapping_around(x: u8) -> u8 {
+    let y = x + 255;
+    y + 1
+}
+
+#[bit_precise]
+#[ensures(result == (x & 0x0f))]
+fn low_nibble(x: u32) -> u32 {
+    x & 0x0f
+}
+
+#[bit_precise]
+#[requires(min == -128 && big == 0x8000)]
+fn wrap_at_the_bounds(min: i8, big: u16) {
+    assert!(min - 1 == 127);
+    assert!(-min == min);
+    assert!(big * 2 == 0);
+}
+
+fn main() {
+    let x = add_wrapping_around(7);
+    assert!(x == 7);
+    let y = low_nibble(0x1234);
+    assert!(y == 4);
+    wrap_at_the_bounds(-128, 0x8000);
+}
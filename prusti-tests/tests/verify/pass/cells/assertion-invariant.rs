@@ -0,0 +1,30 @@
+use prusti_contracts::*;
+use std::cell::Cell;
+
+#[invariant(self.enabled ==> self.level > 0)]
+#[derive(Clone, Copy)]
+struct Setting {
+    enabled: bool,
+    level: u8,
+}
+
+fn enable(cell: &Cell<Setting>) {
+    let mut setting = cell.get();
+    if !setting.enabled {
+        setting.enabled = true;
+        setting.level = 1;
+    }
+    cell.set(setting);
+}
+
+#[ensures(result > 0)]
+fn enabled_level(cell: &Cell<Setting>) -> u8 {
+    let setting = cell.get();
+    if setting.enabled { setting.level } else { 1 }
+}
+
+fn main() {
+    let cell = Cell::new(Setting { enabled: false, level: 0 });
+    enable(&cell);
+    assert!(enabled_level(&cell) > 0);
+}
@@ -0,0 +1,43 @@
+use prusti_contracts::*;
+use std::cell::{Cell, RefCell};
+
+#[invariant(self.value <= 100)]
+#[derive(Clone, Copy)]
+struct Percentage {
+    value: u8,
+}
+
+fn cell() {
+    let cell = Cell::new(Percentage { value: 50 });
+    let percentage = cell.get();
+    assert!(percentage.value <= 100);
+    cell.set(Percentage { value: 100 });
+}
+
+fn increment(cell: &Cell<Percentage>) {
+    let mut percentage = cell.get();
+    if percentage.value < 100 {
+        percentage.value += 1;
+    }
+    cell.set(percentage);
+}
+
+fn increment_ref_cell(cell: &RefCell<Percentage>) {
+    let mut guard = cell.borrow_mut();
+    let percentage = &mut *guard;
+    if percentage.value < 100 {
+        percentage.value += 1;
+    }
+}
+
+#[ensures(result <= 100)]
+fn read_ref_cell(cell: &RefCell<Percentage>) -> u8 {
+    let guard = cell.borrow();
+    guard.value
+}
+
+fn main() {
+    let cell = RefCell::new(Percentage { value: 0 });
+    increment_ref_cell(&cell);
+    assert!(read_ref_cell(&cell) <= 100);
+}
@@ -0,0 +1,22 @@
+// compile-flags: -Pcheck=panics
+
+use prusti_contracts::*;
+
+#[ensures(result == 42)]
+fn wrong_postcondition() -> u32 {
+    0
+}
+
+#[ensures(result > 0)]
+fn positive() -> u32 {
+    1
+}
+
+#[requires(x > 0)]
+fn needs_positive(x: u32) {}
+
+fn main() {
+    wrong_postcondition();
+    // Postconditions are still assumed at call sites.
+    needs_positive(positive());
+}
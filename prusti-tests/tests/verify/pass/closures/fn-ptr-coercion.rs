@@ -0,0 +1,17 @@
+use prusti_contracts::*;
+
+#[requires(callback |= |x: i32| [
+    requires(x > 0),
+    ensures(result > x)
+])]
+fn register(callback: fn(i32) -> i32) {}
+
+#[requires(x >= 0)]
+#[ensures(result == x + 1)]
+fn increment(x: i32) -> i32 {
+    x + 1
+}
+
+fn main() {
+    register(increment);
+}
@@ -0,0 +1,37 @@
+#![feature(stmt_expr_attributes)]
+
+use prusti_contracts::*;
+
+#[requires(add |= |a: i32, b: i32| [
+    requires(a >= 0 && a <= 100),
+    requires(b >= 0 && b <= 100),
+    ensures(result == a + b)
+])]
+#[ensures(result == 16)]
+fn apply<F: Fn(i32, i32) -> i32>(add: F) -> i32 {
+    add(7, 9)
+}
+
+#[requires(step |= |x: u32| [
+    requires(x < 1000),
+    ensures(result > x)
+])]
+fn fold_twice<F: FnMut(u32) -> u32>(mut step: F) -> u32 {
+    let first = step(0);
+    if first < 1000 {
+        let second = step(first);
+        assert!(second > first);
+        second
+    } else {
+        first
+    }
+}
+
+fn main() {
+    let add = closure!(
+        requires(a >= 0 && b >= 0 && a <= 100 && b <= 100),
+        ensures(result == a + b),
+        |a: i32, b: i32| -> i32 { a + b }
+    );
+    assert!(apply(add) == 16);
+}
@@ -0,0 +1,30 @@
+#![feature(stmt_expr_attributes)]
+
+use prusti_contracts::*;
+
+#[ensures(result |= |x: i32| [
+    requires(x >= 0 && x <= 100),
+    ensures(result == x + captured)
+])]
+fn make_adder(captured: i32) -> impl Fn(i32) -> i32 {
+    move |x: i32| x + captured
+}
+
+#[requires(factor >= 1 && factor <= 10)]
+#[ensures(result |= |x: i32| [
+    requires(x >= 0 && x <= 1000),
+    ensures(result >= x),
+    ensures(result == x * factor)
+])]
+fn make_scaler(factor: i32) -> impl Fn(i32) -> i32 {
+    let scaled = factor;
+    closure!(
+        requires(x <= 1000),
+        |x: i32| -> i32 { x * scaled }
+    )
+}
+
+fn main() {
+    let add = make_adder(5);
+    let _sum = add(3);
+}
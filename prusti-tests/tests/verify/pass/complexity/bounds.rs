@@ -0,0 +1,36 @@
+use prusti_contracts::*;
+
+#[complexity(n)]
+fn linear_loop(n: usize) {
+    let mut i = 0;
+    while i < n {
+        body_invariant!(i < n && credits() + i == n);
+        i += 1;
+    }
+}
+
+#[complexity(n)]
+fn count_down(n: usize) -> usize {
+    if n == 0 {
+        0
+    } else {
+        count_down(n - 1)
+    }
+}
+
+#[complexity(n + 1)]
+fn loop_and_call(n: usize) {
+    let mut i = 0;
+    while i < n {
+        body_invariant!(i < n && credits() + i == n + 1);
+        i += 1;
+    }
+    count_down(0);
+}
+
+fn unbounded(n: usize) {
+    count_down(n);
+    count_down(n);
+}
+
+fn main() {}
@@ -0,0 +1,45 @@
+use prusti_contracts::*;
+
+struct Wrapper(i32);
+
+enum Value {
+    Int(i32),
+    Empty,
+}
+
+#[ensures(result.0 == x)]
+fn wrap(x: i32) -> Wrapper {
+    let f = Wrapper;
+    f(x)
+}
+
+#[pure]
+fn is_int(value: &Value) -> bool {
+    match value {
+        Value::Int(_) => true,
+        Value::Empty => false,
+    }
+}
+
+#[ensures(is_int(&result))]
+fn make_int(x: i32) -> Value {
+    let f = Value::Int;
+    f(x)
+}
+
+#[pure]
+fn pure_wrap(x: i32) -> i32 {
+    let f = Wrapper;
+    f(x).0
+}
+
+#[ensures(result == 5)]
+fn test_pure_wrap() -> i32 {
+    pure_wrap(5)
+}
+
+fn wrap_all(opt: Option<i32>) -> Option<Wrapper> {
+    opt.map(Wrapper)
+}
+
+fn main() {}
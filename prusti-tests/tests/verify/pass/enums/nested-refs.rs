@@ -0,0 +1,52 @@
+use prusti_contracts::*;
+
+#[derive(Clone, Copy)]
+enum Shape {
+    Circle(u32),
+    Square(u32),
+    Empty,
+}
+
+#[pure]
+fn is_empty(shape: &&Shape) -> bool {
+    match shape {
+        Shape::Empty => true,
+        _ => false,
+    }
+}
+
+#[pure]
+fn size(shape: &&Shape) -> u32 {
+    match shape {
+        Shape::Circle(r) => *r,
+        Shape::Square(s) => *s,
+        Shape::Empty => 0,
+    }
+}
+
+#[ensures(result == is_empty(&shape))]
+fn check_empty(shape: &Shape) -> bool {
+    let shape_ref = &shape;
+    match shape_ref {
+        Shape::Empty => true,
+        _ => false,
+    }
+}
+
+fn test_triple_ref(shape: &&&Shape) -> u32 {
+    match ***shape {
+        Shape::Circle(_) => 1,
+        Shape::Square(_) => 2,
+        Shape::Empty => 3,
+    }
+}
+
+fn main() {
+    let shape = Shape::Square(4);
+    let shape_ref = &shape;
+    assert!(!is_empty(&shape_ref));
+    assert!(size(&shape_ref) == 4);
+    assert!(!check_empty(&shape));
+    let empty = Shape::Empty;
+    assert!(check_empty(&empty));
+}
@@ -0,0 +1,41 @@
+use prusti_contracts::*;
+
+#[derive(PartialEq, Eq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+enum Shape {
+    Dot(Point),
+    Empty,
+}
+
+#[pure]
+fn is_dot(shape: &Box<Shape>) -> bool {
+    match **shape {
+        Shape::Dot(_) => true,
+        Shape::Empty => false,
+    }
+}
+
+#[requires(a == b)]
+#[ensures(result)]
+fn same_boxes(a: &Box<Point>, b: &Box<Point>) -> bool {
+    a == b
+}
+
+#[requires(a.x == b.x && a.y == b.y)]
+fn compare_contents(a: Box<Point>, b: Box<Point>) {
+    assert!(a == b);
+}
+
+#[requires(is_dot(&shape))]
+fn dot(shape: Box<Shape>) {
+    match *shape {
+        Shape::Dot(_) => {}
+        Shape::Empty => unreachable!(),
+    }
+}
+
+fn main() {}
@@ -0,0 +1,31 @@
+use prusti_contracts::*;
+
+#[derive(Clone,PartialEq,Eq)]
+struct A {
+    i: i32,
+}
+
+#[derive(PartialEq,Eq)]
+struct B {
+    a: Box<A>,
+}
+
+#[pure]
+fn get_value(_x: &A) -> i32 {
+    _x.i
+}
+
+#[requires(_x == _y)]
+#[ensures(result == 2*get_value(&_x.a))]
+fn test_eq_propagation(_x: &B, _y: &B) -> i32 {
+    get_value(&_x.a) + get_value(&_y.a)
+}
+
+#[requires(*_x == *_y)]
+#[ensures(result == 2*get_value(_x))]
+fn test_box_eq(_x: &Box<A>, _y: &Box<A>) -> i32 {
+    get_value(_x) + get_value(_y)
+}
+
+fn main() {
+}
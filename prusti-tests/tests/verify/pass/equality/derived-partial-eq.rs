@@ -0,0 +1,59 @@
+use prusti_contracts::*;
+
+#[derive(PartialEq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(PartialEq)]
+enum Shape {
+    Dot(Point),
+    Segment(Point, Point),
+    Empty,
+}
+
+#[derive(PartialEq)]
+struct Wrapper {
+    shape: Box<Shape>,
+}
+
+#[requires(a == b)]
+#[ensures(a.x == b.x && a.y == b.y)]
+fn same_point(a: &Point, b: &Point) {}
+
+#[requires(a.x == b.x && a.y == b.y)]
+#[ensures(a == b)]
+fn equal_fields(a: &Point, b: &Point) {}
+
+#[requires(a.x != b.x)]
+#[ensures(a != b)]
+fn different_points(a: &Point, b: &Point) {}
+
+#[ensures(result == Shape::Dot(Point { x: 1, y: 2 }))]
+fn make_dot() -> Shape {
+    Shape::Dot(Point { x: 1, y: 2 })
+}
+
+#[requires(a == b)]
+#[ensures(a.shape == b.shape)]
+fn same_wrapper(a: &Wrapper, b: &Wrapper) {}
+
+#[pure]
+fn is_origin(p: &Point) -> bool {
+    *p == Point { x: 0, y: 0 }
+}
+
+#[ensures(is_origin(&result))]
+fn origin() -> Point {
+    Point { x: 0, y: 0 }
+}
+
+fn main() {
+    let p = Point { x: 1, y: 2 };
+    let q = Point { x: 1, y: 2 };
+    equal_fields(&p, &q);
+    same_point(&p, &q);
+    let s = make_dot();
+    assert!(s != Shape::Empty);
+}
@@ -0,0 +1,43 @@
+use prusti_contracts::*;
+
+#[derive(PartialEq, Eq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(PartialEq, Eq)]
+struct Wrapper<'a> {
+    point: &'a Point,
+    boxed: Box<Point>,
+}
+
+#[requires(a === b)]
+fn needs_same(a: &Point, b: &Point) {}
+
+#[requires(a.x == b.x && a.y == b.y)]
+#[ensures(a === b)]
+fn same_fields(a: &Point, b: &Point) {}
+
+#[requires(a === b)]
+#[ensures(a.x == b.x)]
+fn same_boxes(a: &Box<Point>, b: &Box<Point>) {}
+
+// References and boxes are compared by their targets.
+#[requires(v.point.x == w.point.x && v.point.y == w.point.y)]
+#[requires(v.boxed.x == w.boxed.x && v.boxed.y == w.boxed.y)]
+#[ensures(v === w)]
+#[ensures(v.point === w.point)]
+fn same_wrappers(v: &Wrapper, w: &Wrapper) {}
+
+#[requires(x === y)]
+#[ensures(x == y)]
+fn same_ints(x: u32, y: u32) {}
+
+fn main() {
+    let p = Point { x: 1, y: 2 };
+    let q = Point { x: 1, y: 2 };
+    // Different addresses, same snapshot.
+    needs_same(&p, &q);
+    same_ints(3, 3);
+}
@@ -0,0 +1,55 @@
+// compile-flags: -Pcheck_raw_pointer_derefs=true
+
+#![feature(type_ascription)]
+#![allow(unused_variables)]
+#![allow(dead_code)]
+#![allow(unused_must_use)]
+
+extern crate prusti_contracts;
+use prusti_contracts::*;
+
+mod ffi {
+    extern "C" {
+        pub fn counter_get(counter: *const u32) -> u32;
+        pub fn counter_incr(counter: *mut u32);
+        pub fn counter_free(counter: *mut u32);
+    }
+}
+
+#[extern_spec]
+mod ffi {
+    extern "C" {
+        #[requires(ptr_valid(counter))]
+        #[ensures(ptr_valid(counter))]
+        #[ensures(result == *counter)]
+        pub fn counter_get(counter: *const u32) -> u32;
+
+        #[requires(ptr_valid(counter) && *counter < 100)]
+        #[ensures(ptr_valid(counter))]
+        #[ensures(*counter == old(*counter) + 1)]
+        pub fn counter_incr(counter: *mut u32);
+
+        #[requires(ptr_valid(counter))]
+        pub fn counter_free(counter: *mut u32);
+    }
+}
+
+#[requires(ptr_valid(counter) && *counter == 0)]
+unsafe fn count_twice(counter: *mut u32) -> u32 {
+    ffi::counter_incr(counter);
+    ffi::counter_incr(counter);
+    let value = ffi::counter_get(counter);
+    assert!(value == 2);
+    ffi::counter_free(counter);
+    value
+}
+
+#[requires(ptr_valid(p))]
+#[ensures(ptr_valid(p))]
+#[ensures(result == 7)]
+unsafe fn write_then_read(p: *mut u32) -> u32 {
+    std::ptr::write(p, 7);
+    std::ptr::read(p)
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+// compile-flags: -Pextern_specs_dir=tests/extern-specs
+
+use prusti_contracts::*;
+
+fn main() {
+    let mut x = 5;
+    let mut y = 42;
+
+    std::mem::swap(&mut x, &mut y);
+
+    assert!(42 == x);
+    assert!(5 == y);
+}
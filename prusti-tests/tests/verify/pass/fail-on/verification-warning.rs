@@ -0,0 +1,13 @@
+// compile-flags: -Pfail_on=timeout,unsupported
+
+// Verification failures are reported as warnings, so they do not make the
+// compilation fail.
+
+use prusti_contracts::*;
+
+#[ensures(result == 1)]
+fn wrong() -> i32 {
+    0
+}
+
+fn main() {}
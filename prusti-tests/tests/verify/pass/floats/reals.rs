@@ -0,0 +1,18 @@
+// compile-flags: -Pencode_floats_as_reals=true
+
+use prusti_contracts::*;
+
+#[ensures(result >= x && result >= y)]
+#[ensures(result == x || result == y)]
+fn max(x: f64, y: f64) -> f64 {
+    if x >= y { x } else { y }
+}
+
+fn identities(x: f32) {
+    assert!(x * 1.0 == x);
+    assert!(x * 0.0 == 0.0);
+    assert!(x + 0.0 == x);
+    assert!(!(x < x));
+}
+
+fn main() {}
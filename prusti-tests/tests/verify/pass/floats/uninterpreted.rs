@@ -0,0 +1,37 @@
+use prusti_contracts::*;
+
+// `==` on floats is not reflexive because of NaN, so the postconditions use
+// snapshot equality.
+
+struct Point {
+    x: f64,
+    y: f64,
+}
+
+#[pure]
+fn scale(x: f64, factor: f64) -> f64 {
+    x * factor
+}
+
+#[ensures(result === scale(x, 2.0))]
+fn double(x: f64) -> f64 {
+    x * 2.0
+}
+
+#[ensures(result === -(x + y))]
+fn negated_sum(x: f64, y: f64) -> f64 {
+    -(x + y)
+}
+
+#[ensures(result.x === p.y && result.y === p.x)]
+fn swap(p: &Point) -> Point {
+    Point { x: p.y, y: p.x }
+}
+
+fn literals() {
+    let x = 1.5f32;
+    assert!(x < 2.5);
+    assert!(x as f64 == 1.5);
+}
+
+fn main() {}
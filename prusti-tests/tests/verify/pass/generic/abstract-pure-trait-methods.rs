@@ -0,0 +1,83 @@
+use prusti_contracts::*;
+
+trait Measure {
+    #[pure]
+    #[ensures(result > 0)]
+    fn size(&self) -> i32;
+
+    #[pure]
+    #[requires(self.size() > limit)]
+    fn exceeds(&self, limit: i32) -> bool;
+}
+
+trait Token: Copy {
+    #[pure]
+    #[ensures(result > 0)]
+    fn weight(self) -> i32;
+
+    #[pure]
+    fn same(self) -> Self;
+}
+
+#[ensures(result > 0)]
+fn weight_of<T: Token>(x: T) -> i32 {
+    x.weight()
+}
+
+#[requires(x.size() < 100)]
+#[ensures(result > 0 && result < 100)]
+fn size_of<T: Measure>(x: &T) -> i32 {
+    x.size()
+}
+
+#[ensures(result > 1)]
+fn total_size<A: Measure, B: Measure>(a: &A, b: &B) -> i32 {
+    if a.size() < 1000 && b.size() < 1000 {
+        a.size() + b.size()
+    } else {
+        2
+    }
+}
+
+#[requires(x.size() > 10)]
+#[ensures(result == x.exceeds(10))]
+fn exceeds_ten<T: Measure>(x: &T) -> bool {
+    x.exceeds(10)
+}
+
+struct Unit;
+
+impl Measure for Unit {
+    #[pure]
+    #[ensures(result == 1)]
+    fn size(&self) -> i32 {
+        1
+    }
+
+    #[pure]
+    fn exceeds(&self, limit: i32) -> bool {
+        self.size() > limit
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Coin;
+
+impl Token for Coin {
+    #[pure]
+    #[ensures(result == 1)]
+    fn weight(self) -> i32 {
+        1
+    }
+
+    #[pure]
+    fn same(self) -> Self {
+        self
+    }
+}
+
+fn main() {
+    let unit = Unit;
+    assert!(size_of(&unit) > 0);
+    assert!(weight_of(Coin) > 0);
+}
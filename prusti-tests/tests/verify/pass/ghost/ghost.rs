@@ -0,0 +1,52 @@
+use prusti_contracts::*;
+
+#[pure]
+fn triangle(n: u32) -> u32 {
+    if n == 0 { 0 } else { n + triangle(n - 1) }
+}
+
+#[requires(n < 10)]
+fn count_steps(n: u32) -> u32 {
+    let mut i = 0;
+    ghost! {
+        let mut steps = 0;
+    }
+    while i < n {
+        body_invariant!(i < n && steps == i);
+        i += 1;
+        ghost! {
+            steps += 1;
+        }
+    }
+    ghost! {
+        assert!(steps == n);
+    }
+    i
+}
+
+#[requires(n < 100)]
+fn sum(n: u32) -> u32 {
+    let mut i = 0;
+    let mut total = 0;
+    ghost! {
+        let mut partial_sums = 0;
+        let mut steps = 0;
+        while steps < 3 {
+            body_invariant!(steps < 3);
+            partial_sums += steps;
+            steps += 1;
+        }
+        assert!(steps == 3);
+    }
+    while i < n {
+        body_invariant!(i < n && total == triangle(i));
+        i += 1;
+        total += i;
+    }
+    total
+}
+
+fn main() {
+    count_steps(5);
+    sum(10);
+}
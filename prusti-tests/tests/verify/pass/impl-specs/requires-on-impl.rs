@@ -0,0 +1,50 @@
+use prusti_contracts::*;
+
+struct Counter {
+    value: u32,
+    limit: u32,
+}
+
+#[requires(self.value < self.limit)]
+#[ensures(self.value <= self.limit)]
+impl Counter {
+    #[ensures(result.value == 0 && result.limit == limit)]
+    fn new(limit: u32) -> Self {
+        Counter { value: 0, limit }
+    }
+
+    #[pure]
+    fn get(&self) -> u32 {
+        self.value
+    }
+
+    #[ensures(self.value == old(self.value) + 1)]
+    fn increment(&mut self) {
+        self.value += 1;
+    }
+
+    #[requires(self.value > 0)]
+    fn decrement(&mut self) {
+        self.value -= 1;
+    }
+}
+
+trait Reset {
+    fn reset(&mut self);
+}
+
+#[refine_trait_spec]
+impl Reset for Counter {
+    #[ensures(self.value == 0)]
+    fn reset(&mut self) {
+        self.value = 0;
+    }
+}
+
+fn main() {
+    let mut counter = Counter::new(2);
+    counter.increment();
+    assert!(counter.value == 1);
+    counter.reset();
+    assert!(counter.value == 0);
+}
@@ -0,0 +1,44 @@
+use prusti_contracts::*;
+
+trait Source {
+    #[ensures(result >= 0)]
+    fn produce(&mut self) -> i32;
+}
+
+struct FromFn<F> {
+    f: F,
+}
+
+impl<F: Fn() -> i32> Source for FromFn<F> {
+    #[trusted]
+    #[ensures(result >= 0)]
+    fn produce(&mut self) -> i32 {
+        let value = (self.f)();
+        if value < 0 { 0 } else { value }
+    }
+}
+
+fn seven() -> i32 {
+    7
+}
+
+// The hidden type stores a function item, so the result is known only
+// through the contract of `Source`.
+#[trusted]
+fn make_source() -> impl Source {
+    FromFn { f: seven }
+}
+
+#[ensures(result >= 0)]
+fn produce_twice(source: &mut impl Source) -> i32 {
+    let first = source.produce();
+    let second = source.produce();
+    if first < second { first } else { second }
+}
+
+fn main() {
+    let mut source = make_source();
+    let value = source.produce();
+    assert!(value >= 0);
+    assert!(produce_twice(&mut source) >= 0);
+}
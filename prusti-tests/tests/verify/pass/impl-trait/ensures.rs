@@ -0,0 +1,52 @@
+use prusti_contracts::*;
+
+trait Counter {
+    #[pure]
+    #[ensures(result >= 0)]
+    fn value(&self) -> i32;
+
+    #[ensures(result >= 0)]
+    fn next(&mut self) -> i32;
+}
+
+struct Natural {
+    value: i32,
+}
+
+impl Counter for Natural {
+    #[pure]
+    #[ensures(result >= 0)]
+    fn value(&self) -> i32 {
+        if self.value < 0 { 0 } else { self.value }
+    }
+
+    #[ensures(result >= 0)]
+    fn next(&mut self) -> i32 {
+        if self.value < 0 {
+            self.value = 0;
+        } else if self.value < 1000 {
+            self.value += 1;
+        }
+        self.value
+    }
+}
+
+#[ensures(result.value() >= 0)]
+fn make(start: i32) -> impl Counter {
+    Natural { value: start }
+}
+
+#[requires(counter.value() >= 0)]
+#[ensures(result >= 0)]
+fn advance(mut counter: impl Counter) -> i32 {
+    counter.next()
+}
+
+fn main() {
+    let mut counter = make(5);
+    assert!(counter.value() >= 0);
+    let value = counter.next();
+    assert!(value >= 0);
+    let result = advance(make(-3));
+    assert!(result >= 0);
+}
@@ -0,0 +1,49 @@
+use prusti_contracts::*;
+
+#[pure]
+#[requires(n >= 0)]
+fn sum(n: i32) -> i32 {
+    if n == 0 { 0 } else { n + sum(n - 1) }
+}
+
+#[lemma]
+#[requires(n >= 0)]
+#[ensures(sum(n) >= n)]
+#[decreases(n)]
+fn sum_is_at_least(n: i32) -> bool {
+    if n == 0 { true } else { sum_is_at_least(n - 1) }
+}
+
+#[pure]
+fn combine(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+#[trusted_proof]
+#[ensures(combine(combine(a, b), c) == combine(a, combine(b, c)))]
+fn combine_assoc(a: i32, b: i32, c: i32) -> bool {
+    true
+}
+
+#[requires(n >= 0 && sum_is_at_least(n))]
+#[ensures(result >= n)]
+fn sum_in_spec(n: i32) -> i32 {
+    sum(n)
+}
+
+#[requires(n >= 0)]
+#[ensures(result >= n)]
+fn sum_in_ghost_code(n: i32) -> i32 {
+    ghost! {
+        sum_is_at_least(n);
+    }
+    sum(n)
+}
+
+#[ensures(combine_assoc(a, b, c))]
+#[ensures(result == combine(a, combine(b, c)))]
+fn reassociate(a: i32, b: i32, c: i32) -> i32 {
+    combine(combine(a, b), c)
+}
+
+fn main() {}
@@ -0,0 +1,46 @@
+// compile-flags: -Pinfer_loop_invariants=true
+
+use prusti_contracts::*;
+
+fn count_up(n: usize) -> usize {
+    let mut i = 0;
+    while i < n {
+        i += 1;
+    }
+    assert!(i == n);
+    i
+}
+
+fn count_down(n: u32) {
+    let mut i = n;
+    while i > 0 {
+        i -= 1;
+    }
+    assert!(i == 0);
+}
+
+fn accumulate(n: u32) {
+    let mut x: i32 = 5;
+    let mut y: i64 = 10;
+    let mut i = 0;
+    while i < n {
+        x += 2;
+        y -= 1;
+        i += 1;
+    }
+    assert!(x >= 5);
+    assert!(y <= 10);
+}
+
+fn with_explicit_invariant(n: u32) {
+    let mut i = 0;
+    let mut sum = 0;
+    while i < n {
+        body_invariant!(sum == 2 * i);
+        sum += 2;
+        i += 1;
+    }
+    assert!(sum == 2 * i);
+}
+
+fn main() {}
@@ -0,0 +1,33 @@
+use prusti_contracts::*;
+
+struct Pair {
+    first: i32,
+    second: i32,
+}
+
+impl Pair {
+    #[ensures(self.first == 0 && self.second == 0)]
+    fn reset(&mut self) {
+        let mut fields: Vec<&mut i32> = Vec::new();
+        fields.push(&mut self.first);
+        fields.push(&mut self.second);
+        drop(fields);
+        self.first = 0;
+        self.second = 0;
+    }
+}
+
+#[ensures(result == 3)]
+fn sum_after_collecting() -> i32 {
+    let mut a = 1;
+    let mut b = 2;
+    let mut refs = Vec::new();
+    refs.push(&mut a);
+    refs.push(&mut b);
+    drop(refs);
+    a = 1;
+    b = 2;
+    a + b
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+extern crate prusti_contracts;
+
+const READ: u32 = 1;
+const WRITE: u32 = 2;
+
+fn test_identities(x: u32, y: u32) {
+    assert!(x | 0 == x);
+    assert!(x ^ 0 == x);
+    assert!(x & 0 == 0);
+    assert!(x | x == x);
+    assert!(x & x == x);
+    assert!(x ^ x == 0);
+    assert!(x & y <= x);
+    assert!(x | y >= y);
+}
+
+fn test_flags(flags: u32) -> u32 {
+    let flags = flags | READ | WRITE;
+    assert!(flags >= WRITE);
+    flags ^ READ
+}
+
+fn test_signed(x: i64, y: i64) -> i64 {
+    assert!(x ^ 0 == x);
+    x & y
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+// compile-flags: -Pchecked_obligations=panics,post
+
+use prusti_contracts::*;
+
+#[requires(x > 0)]
+#[ensures(result == x)]
+fn identity(x: u32) -> u32 {
+    x
+}
+
+fn loop_with_wrong_invariant() {
+    let mut i = 0;
+    while i < 10 {
+        body_invariant!(i > 100);
+        i += 1;
+    }
+}
+
+fn main() {
+    identity(0);
+}
@@ -0,0 +1,35 @@
+use prusti_contracts::*;
+
+#[ensures(x > 0 ==> result.is_some() && result.unwrap() == x)]
+#[ensures(x <= 0 ==> result.is_none())]
+fn positive(x: i32) -> Option<i32> {
+    if x > 0 { Some(x) } else { None }
+}
+
+#[ensures(y != 0 ==> result.is_ok() && result.unwrap() == x / y)]
+#[ensures(y == 0 ==> result.is_err())]
+fn divide(x: i32, y: i32) -> Result<i32, ()> {
+    if y == 0 { Err(()) } else { Ok(x / y) }
+}
+
+#[pure]
+#[requires(value.is_some())]
+fn get(value: Option<i32>) -> i32 {
+    value.unwrap()
+}
+
+#[requires(value.is_some())]
+#[ensures(result == old(value.unwrap()))]
+fn unwrap_checked(value: Option<i32>) -> i32 {
+    value.unwrap()
+}
+
+fn main() {
+    let x = positive(5);
+    assert!(x.is_some());
+    assert!(x.unwrap() == 5);
+    assert!(get(positive(3)) == 3);
+    let y = divide(10, 2).expect("division by zero");
+    assert!(y == 5);
+    assert!(divide(1, 0).is_err());
+}
@@ -0,0 +1,47 @@
+// The pinned compiler does not parse `let ... else` yet. These are the forms
+// that it desugars to: a refutable binding whose else block diverges.
+
+use prusti_contracts::*;
+
+#[ensures(result >= 1)]
+fn early_return(opt: Option<u32>) -> u32 {
+    let x = match opt {
+        Some(x) => x,
+        None => return 1,
+    };
+    if x == 0 {
+        1
+    } else {
+        x
+    }
+}
+
+fn continue_else(values: Option<u32>) -> u32 {
+    let mut count = 0;
+    let mut i = 0;
+    while i < 10 {
+        body_invariant!(count <= i);
+        i += 1;
+        let x = if let Some(x) = values { x } else { continue };
+        if x > 0 {
+            count += 1;
+        }
+    }
+    count
+}
+
+fn loop_else(values: Option<(u32, u32)>) -> u32 {
+    let mut sum = 0;
+    let mut i = 0;
+    while i < 10 {
+        body_invariant!(sum <= i);
+        let (a, _b) = if let Some(pair) = values { pair } else { break };
+        if a > 0 {
+            sum += 1;
+        }
+        i += 1;
+    }
+    sum
+}
+
+fn main() {}
@@ -0,0 +1,68 @@
+#![feature(or_patterns)]
+
+use prusti_contracts::*;
+
+fn is_small(x: Option<u32>) -> bool {
+    match x {
+        Some(y @ (1 | 2)) => {
+            assert!(y == 1 || y == 2);
+            true
+        }
+        _ => false,
+    }
+}
+
+#[pure]
+fn is_extreme(x: Option<u32>) -> bool {
+    match x {
+        Some(0 | 100) | None => true,
+        Some(_) => false,
+    }
+}
+
+fn either(r: Result<u32, u32>) -> u32 {
+    match r {
+        Ok(x) | Err(x) if x > 10 => {
+            assert!(x > 10);
+            x
+        }
+        Ok(_) | Err(_) => 10,
+    }
+}
+
+fn guard(x: Option<i32>) -> i32 {
+    match x {
+        Some(y) if y > 0 => {
+            assert!(y > 0);
+            y
+        }
+        Some(y) => {
+            assert!(y <= 0);
+            -y
+        }
+        None => 0,
+    }
+}
+
+struct Pair {
+    first: u32,
+    second: u32,
+}
+
+fn ref_bindings(pair: Pair) -> u32 {
+    let Pair { ref first, second } = pair;
+    assert!(*first == pair.first);
+    *first + second
+}
+
+#[requires(pair.first < 100)]
+#[ensures(pair.first == old(pair.first) + 1)]
+fn ref_mut_bindings(pair: &mut Pair) {
+    let Pair { ref mut first, .. } = *pair;
+    *first += 1;
+}
+
+fn main() {
+    assert!(is_extreme(None));
+    assert!(!is_extreme(Some(50)));
+}
@@ -0,0 +1,66 @@
+use prusti_contracts::*;
+
+#[ensures(result < 3)]
+fn classify(x: u8) -> u8 {
+    match x {
+        0..=9 => {
+            assert!(x <= 9);
+            0
+        }
+        10..=99 => {
+            assert!(10 <= x && x <= 99);
+            1
+        }
+        _ => {
+            assert!(x >= 100);
+            2
+        }
+    }
+}
+
+fn signed(x: i32) -> i32 {
+    match x {
+        i32::MIN..=-1 => {
+            assert!(x < 0);
+            -1
+        }
+        0 => 0,
+        1..=i32::MAX => {
+            assert!(x > 0);
+            1
+        }
+    }
+}
+
+#[pure]
+#[ensures(result == (c >= 'a' && c <= 'z'))]
+fn is_lowercase(c: char) -> bool {
+    match c {
+        'a'..='z' => true,
+        _ => false,
+    }
+}
+
+#[pure]
+fn digit_value(c: char) -> u32 {
+    match c {
+        '0' => 0,
+        '1'..='9' => 1,
+        _ => 2,
+    }
+}
+
+fn char_ranges(c: char) {
+    match c {
+        'a'..='z' | 'A'..='Z' => assert!(c != ' '),
+        '0'..='9' => assert!(c >= '0' && c <= '9'),
+        _ => {}
+    }
+}
+
+fn main() {
+    assert!(is_lowercase('q'));
+    assert!(!is_lowercase('Q'));
+    assert!(digit_value('0') == 0);
+    assert!(digit_value('7') == 1);
+}
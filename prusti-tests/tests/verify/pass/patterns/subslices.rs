@@ -0,0 +1,29 @@
+use prusti_contracts::*;
+
+#[requires(items.len() == 3 && items[1] == 5)]
+fn rest_of_slice(items: &[u32]) {
+    match items {
+        [first, rest @ ..] => {
+            assert!(rest.len() == 2);
+            assert!(rest[0] == 5);
+        }
+        [] => unreachable!(),
+    }
+}
+
+#[requires(rest.len() > 0)]
+#[ensures(result == rest[0])]
+fn head(rest: &[u32]) -> u32 {
+    rest[0]
+}
+
+#[requires(items.len() == 4 && items[2] == 7)]
+fn pass_subslice(items: &[u32]) {
+    if let [_, middle @ .., _] = items {
+        assert!(middle.len() == 2);
+        let value = head(middle);
+        assert!(value == 7);
+    }
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use prusti_contracts::*;
+
+#[pure]
+#[decreases(n)]
+fn is_even(n: u32) -> bool {
+    if n == 0 { true } else { is_odd(n - 1) }
+}
+
+#[pure]
+#[decreases(n)]
+fn is_odd(n: u32) -> bool {
+    if n == 0 { false } else { is_even(n - 1) }
+}
+
+fn main() {}
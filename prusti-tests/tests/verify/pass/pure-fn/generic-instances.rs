@@ -0,0 +1,39 @@
+use prusti_contracts::*;
+
+pub struct Wrapper<T> {
+    value: T,
+    valid: bool,
+}
+
+#[pure]
+pub fn is_valid<T>(w: &Wrapper<T>) -> bool {
+    w.valid
+}
+
+#[pure]
+pub fn both_valid<T, U>(a: &Wrapper<T>, b: &Wrapper<U>) -> bool {
+    is_valid(a) && is_valid(b)
+}
+
+#[requires(is_valid(&w))]
+#[ensures(is_valid(&result))]
+pub fn keep_u32(w: Wrapper<u32>) -> Wrapper<u32> {
+    w
+}
+
+#[requires(is_valid(&w))]
+#[ensures(is_valid(&result))]
+pub fn keep_generic<T>(w: Wrapper<T>) -> Wrapper<T> {
+    w
+}
+
+#[requires(both_valid(&a, &b))]
+pub fn use_both(a: Wrapper<u32>, b: Wrapper<bool>) {
+    assert!(is_valid(&a));
+    assert!(is_valid(&b));
+    let a = keep_u32(a);
+    let b = keep_generic(b);
+    assert!(both_valid(&a, &b));
+}
+
+fn main() {}
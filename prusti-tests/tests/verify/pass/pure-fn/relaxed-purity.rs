@@ -0,0 +1,36 @@
+// compile-flags: -Prelaxed_purity=true
+
+use prusti_contracts::*;
+
+#[pure]
+fn unbox(x: u32) -> u32 {
+    let boxed = Box::new(x);
+    let moved = boxed;
+    *moved
+}
+
+#[pure]
+fn boxed_max(a: u32, b: u32) -> u32 {
+    let larger = if a > b { Box::new(a) } else { Box::new(b) };
+    *larger
+}
+
+#[pure]
+fn clone_box(x: u32) -> u32 {
+    let boxed = Box::new(x);
+    let cloned = boxed.clone();
+    *cloned.as_ref()
+}
+
+#[ensures(result == x)]
+fn client(x: u32) -> u32 {
+    unbox(x)
+}
+
+#[ensures(boxed_max(a, b) >= a && boxed_max(a, b) >= b)]
+fn max_client(a: u32, b: u32) {}
+
+#[ensures(clone_box(x) == x)]
+fn clone_client(x: u32) {}
+
+fn main() {}
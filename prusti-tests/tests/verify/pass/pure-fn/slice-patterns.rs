@@ -0,0 +1,67 @@
+use prusti_contracts::*;
+
+struct Item {
+    weight: u32,
+    value: u32,
+}
+
+#[pure]
+fn first_or_zero(items: &[u32]) -> u32 {
+    match items {
+        [first, ..] => *first,
+        [] => 0,
+    }
+}
+
+#[pure]
+#[requires(items.len() > 0)]
+fn last(items: &[u32]) -> u32 {
+    match items {
+        [.., last] => *last,
+        [] => unreachable!(),
+    }
+}
+
+#[pure]
+fn rest_len(items: &[Item]) -> usize {
+    match items {
+        [_, rest @ ..] => rest.len(),
+        [] => 0,
+    }
+}
+
+#[pure]
+fn second_value(items: &[Item]) -> u32 {
+    match items {
+        [first, rest @ ..] if rest.len() > 0 => first.weight + rest[0].value,
+        _ => 0,
+    }
+}
+
+#[requires(items.len() == 3)]
+#[ensures(result == 2)]
+fn test_rest_len(items: &[Item]) -> usize {
+    rest_len(items)
+}
+
+#[requires(items.len() == 1)]
+fn test_second_value(items: &[Item]) {
+    assert!(second_value(items) == 0);
+}
+
+#[requires(items.len() == 2 && items[1] == 5)]
+fn test_last(items: &[u32]) {
+    assert!(last(items) == 5);
+}
+
+#[requires(items.len() > 1 && items[0] == 7)]
+fn test_first(items: &[u32]) {
+    assert!(first_or_zero(items) == 7);
+}
+
+#[requires(items.len() == 0)]
+fn test_empty(items: &[u32]) {
+    assert!(first_or_zero(items) == 0);
+}
+
+fn main() {}
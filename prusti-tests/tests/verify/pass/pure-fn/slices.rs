@@ -0,0 +1,42 @@
+use prusti_contracts::*;
+
+struct Item {
+    weight: u32,
+    value: u32,
+}
+
+#[pure]
+#[requires(items.len() > 0)]
+fn first_weight(items: &[Item]) -> u32 {
+    items[0].weight
+}
+
+#[pure]
+#[requires(index < items.len())]
+fn value_at(items: &[Item], index: usize) -> u32 {
+    items[index].value
+}
+
+#[pure]
+#[requires(index < 3)]
+fn lookup(array: &[u32; 3], index: usize) -> u32 {
+    array[index]
+}
+
+#[requires(items.len() > 1)]
+#[ensures(result == first_weight(items))]
+fn get_first_weight(items: &[Item]) -> u32 {
+    first_weight(items)
+}
+
+#[requires(items.len() > 1)]
+fn compare(items: &[Item]) {
+    assert!(value_at(items, 1) == value_at(items, 1));
+}
+
+fn test_array() {
+    let array = [1, 2, 3];
+    assert!(lookup(&array, 1) == 2);
+}
+
+fn main() {}
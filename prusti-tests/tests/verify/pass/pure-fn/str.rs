@@ -0,0 +1,38 @@
+use prusti_contracts::*;
+
+#[pure]
+fn is_empty(s: &str) -> bool {
+    s.len() == 0
+}
+
+#[pure]
+#[requires(s.len() > 0)]
+fn first_byte(s: &str) -> u8 {
+    s.as_bytes()[0]
+}
+
+#[pure]
+fn has_prefix(s: &str, prefix: &str) -> bool {
+    s.starts_with(prefix)
+}
+
+#[pure]
+fn has_byte_prefix(bytes: &[u8], prefix: &[u8]) -> bool {
+    bytes.starts_with(prefix)
+}
+
+#[requires(!is_empty(input))]
+#[ensures(result == (first_byte(input) == 43))]
+fn is_plus_sign(input: &str) -> bool {
+    first_byte(input) == 43
+}
+
+#[requires(has_prefix(input, sign) && sign.len() > 0)]
+#[ensures(first_byte(input) == first_byte(sign))]
+fn starts_with_sign(input: &str, sign: &str) {}
+
+#[requires(has_prefix(input, prefix))]
+#[ensures(input.len() >= prefix.len())]
+fn prefix_len(input: &str, prefix: &str) {}
+
+fn main() {}
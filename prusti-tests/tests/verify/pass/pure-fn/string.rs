@@ -0,0 +1,26 @@
+use prusti_contracts::*;
+
+#[pure]
+fn byte_len(s: &String) -> usize {
+    s.len()
+}
+
+#[pure]
+fn same_len(a: &String, b: &str) -> bool {
+    a.len() == b.len()
+}
+
+#[requires(!s.is_empty())]
+#[ensures(byte_len(s) > 0)]
+fn non_empty(s: &String) {}
+
+#[ensures(result.len() == s.len())]
+fn pass_through(s: String) -> String {
+    s
+}
+
+#[ensures(same_len(s, s.as_str()))]
+#[ensures(s.as_bytes().len() == s.len())]
+fn views(s: &String) {}
+
+fn main() {}
@@ -0,0 +1,25 @@
+use prusti_contracts::*;
+
+#[pure]
+#[trusted]
+fn valid(_x: i32) -> bool {
+    true
+}
+
+#[pure]
+#[trusted]
+fn related(_x: i32, _y: i32) -> bool {
+    true
+}
+
+#[requires(valid(0))]
+#[requires(forall(|x: i32| triggers=[(valid(x))] valid(x) ==> valid(x + 1)))]
+#[ensures(valid(2))]
+fn prefix_triggers() {}
+
+#[requires(related(1, 2))]
+#[requires(forall(|x: i32, y: i32| related(x, y) ==> related(y, x), triggers=[(related(x, y),)]))]
+#[ensures(related(2, 1))]
+fn suffix_triggers() {}
+
+fn main() {}
@@ -0,0 +1,22 @@
+// compile-flags: -Pdeterministic=true
+
+use prusti_contracts::*;
+
+#[pure]
+fn max(a: i32, b: i32) -> i32 {
+    if a < b { b } else { a }
+}
+
+#[ensures(result >= a && result >= b)]
+fn max_of(a: i32, b: i32) -> i32 {
+    max(a, b)
+}
+
+#[ensures(result >= a && result >= b && result >= c)]
+fn max3(a: i32, b: i32, c: i32) -> i32 {
+    max_of(max_of(a, b), c)
+}
+
+fn main() {
+    assert!(max3(1, 2, 3) >= 3);
+}
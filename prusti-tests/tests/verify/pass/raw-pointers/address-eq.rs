@@ -0,0 +1,27 @@
+use prusti_contracts::*;
+use std::ptr;
+
+fn reflexive(p: *const u32) {
+    assert!(p == p);
+    assert!(ptr::eq(p, p));
+}
+
+fn symmetric(p: *const u32, q: *const u32) {
+    if p == q {
+        assert!(q == p);
+    }
+    if p != q {
+        assert!(!ptr::eq(q, p));
+    }
+}
+
+#[ensures(p == q ==> result == 1)]
+fn branch(p: *const u32, q: *const u32) -> u32 {
+    if ptr::eq(p, q) {
+        1
+    } else {
+        2
+    }
+}
+
+fn main() {}
@@ -0,0 +1,33 @@
+// compile-flags: -Pcheck_raw_pointer_derefs=true
+
+use prusti_contracts::*;
+
+#[requires(ptr_valid(p))]
+#[ensures(ptr_valid(p))]
+unsafe fn read(p: *const u32) -> u32 {
+    *p
+}
+
+#[requires(ptr_valid(p))]
+#[ensures(ptr_valid(p))]
+unsafe fn write(p: *mut u32, value: u32) {
+    *p = value;
+}
+
+#[requires(ptr_valid(p))]
+#[ensures(ptr_valid(p))]
+unsafe fn read_after_write(p: *mut u32) -> u32 {
+    write(p, 42);
+    *p
+}
+
+#[requires(ptr_valid(p))]
+unsafe fn consume(p: *mut u32) {}
+
+#[requires(ptr_valid(p))]
+unsafe fn write_then_consume(p: *mut u32) {
+    *p = 1;
+    consume(p);
+}
+
+fn main() {}
@@ -0,0 +1,50 @@
+use prusti_contracts::*;
+use std::rc::Rc;
+use std::sync::Arc;
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[ensures(*result == value)]
+fn share(value: i32) -> Rc<i32> {
+    Rc::new(value)
+}
+
+#[pure]
+fn get_x(p: &Rc<Point>) -> i32 {
+    p.x
+}
+
+#[requires(get_x(p) > 0)]
+#[ensures(result > 0)]
+fn positive_x(p: &Rc<Point>) -> i32 {
+    p.x
+}
+
+fn clone_rc() {
+    let a = share(5);
+    let b = a.clone();
+    assert!(*a == 5);
+    assert!(*b == 5);
+}
+
+fn shared_struct() {
+    let p = Rc::new(Point { x: 1, y: 2 });
+    let q = Rc::clone(&p);
+    assert!(get_x(&p) == 1);
+    assert!(get_x(&q) == 1);
+    assert!(q.y == 2);
+    let x = positive_x(&q);
+    assert!(x > 0);
+}
+
+fn shared_arc() {
+    let a = Arc::new(Point { x: 3, y: 4 });
+    let b = a.clone();
+    drop(a);
+    assert!(b.x + b.y == 7);
+}
+
+fn main() {}
@@ -0,0 +1,56 @@
+// compile-flags: -Pcheck_send_sync_impls=true
+
+use prusti_contracts::*;
+
+struct Slots {
+    used: usize,
+    capacity: usize,
+}
+
+#[thread_safety_invariant(self.used <= self.capacity)]
+impl Slots {
+    fn new(capacity: usize) -> Self {
+        Slots { used: 0, capacity }
+    }
+
+    #[ensures(result == (self.used < self.capacity))]
+    fn has_free(&self) -> bool {
+        self.used < self.capacity
+    }
+
+    fn acquire(&mut self) -> bool {
+        if self.used < self.capacity {
+            self.used += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn release(&mut self) {
+        if self.used > 0 {
+            self.used -= 1;
+        }
+    }
+}
+
+unsafe impl Send for Slots {}
+unsafe impl Sync for Slots {}
+
+struct Buffer {
+    data: *mut u8,
+    len: usize,
+}
+
+#[thread_safety_invariant(self.data.is_null() == (self.len == 0))]
+impl Buffer {}
+
+unsafe impl Send for Buffer {}
+
+fn main() {
+    let mut slots = Slots::new(1);
+    if slots.acquire() {
+        slots.release();
+    }
+    let _ = slots.has_free();
+}
@@ -0,0 +1,52 @@
+use prusti_contracts::*;
+
+fn test_wrapping() {
+    let x: u8 = 200;
+    assert!(x.wrapping_add(100) == 44);
+    let y: i8 = -128;
+    assert!(y.wrapping_sub(1) == 127);
+    assert!(x.wrapping_mul(2) == 144);
+}
+
+fn test_saturating(x: u8, y: i16) {
+    assert!(x.saturating_add(255) == 255);
+    assert!(x.saturating_sub(x) == 0);
+    let z = y.saturating_mul(2);
+    assert!(z >= -32768 && z <= 32767);
+    if y > 20000 {
+        assert!(z == 32767);
+    }
+}
+
+fn test_checked(x: u32, y: u32) {
+    match x.checked_add(y) {
+        Some(sum) => assert!(sum == x + y),
+        None => assert!(x > 1000 || y > 1000),
+    }
+    assert!(x.checked_sub(x).is_some());
+    let max: u32 = 4294967295;
+    assert!(max.checked_mul(2).is_none());
+}
+
+fn test_overflowing() {
+    let x: u8 = 255;
+    let (value, overflow) = x.overflowing_add(1);
+    assert!(value == 0);
+    assert!(overflow);
+    let (value, overflow) = x.overflowing_sub(5);
+    assert!(value == 250);
+    assert!(!overflow);
+}
+
+#[pure]
+#[ensures(result == x.wrapping_add(1))]
+fn successor(x: u8) -> u8 {
+    x.wrapping_add(1)
+}
+
+#[ensures(result == x.saturating_sub(y))]
+fn difference(x: u16, y: u16) -> u16 {
+    if x > y { x - y } else { 0 }
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use prusti_contracts::*;
+
+#[ensures(|ret| ret == result + 1)]
+fn next(result: u32) -> u32 {
+    result + 1
+}
+
+#[requires(x < 100)]
+#[ensures(|r| r > x)]
+#[ensures(result == x + 1)]
+fn succ(x: u32) -> u32 {
+    x + 1
+}
+
+fn main() {
+    let a = next(3);
+    assert!(a == 4);
+    let b = succ(a);
+    assert!(b == 5);
+}
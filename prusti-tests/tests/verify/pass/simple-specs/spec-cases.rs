@@ -0,0 +1,23 @@
+use prusti_contracts::*;
+
+#[ensures(result == a, when = "a >= b")]
+#[ensures(result == b, when = "a < b")]
+fn max(a: i32, b: i32) -> i32 {
+    if a >= b { a } else { b }
+}
+
+#[requires(divisor != 0, when = "dividend != 0")]
+#[ensures(result == 0, when = "dividend == 0")]
+fn safe_div(dividend: u32, divisor: u32) -> u32 {
+    if dividend == 0 { 0 } else { dividend / divisor }
+}
+
+fn main() {
+    let x = max(3, 7);
+    assert!(x == 7);
+    let y = max(9, 2);
+    assert!(y == 9);
+    let z = safe_div(0, 0);
+    assert!(z == 0);
+    safe_div(4, 2);
+}
@@ -0,0 +1,41 @@
+use prusti_contracts::*;
+
+#[trusted]
+#[ensures(result == wrap_add(a, b))]
+fn add(a: u8, b: u8) -> u8 {
+    a.wrapping_add(b)
+}
+
+#[trusted]
+#[ensures(result == wrap_sub(a, b))]
+fn sub(a: i8, b: i8) -> i8 {
+    a.wrapping_sub(b)
+}
+
+#[trusted]
+#[ensures(result == trunc::<u8, _>(x))]
+fn low_byte(x: u32) -> u8 {
+    x as u8
+}
+
+fn test_add() {
+    assert!(add(200, 100) == 44);
+    assert!(add(1, 2) == 3);
+}
+
+fn test_sub() {
+    assert!(sub(-128, 1) == 127);
+    assert!(sub(5, 7) == -2);
+}
+
+fn test_trunc() {
+    assert!(low_byte(0x1234) == 0x34);
+}
+
+#[pure]
+#[ensures(result == wrap_mul(a, b))]
+fn mul(a: u16, b: u16) -> u16 {
+    if a == 0 { 0 } else { wrap_mul(a, b) }
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+#![prusti::skip_expansion_of(generate_panicking_fn)]
+
+use prusti_contracts::*;
+
+macro_rules! generate_panicking_fn {
+    ($name:ident) => {
+        fn $name() {
+            panic!();
+        }
+    };
+}
+
+generate_panicking_fn!(generated);
+
+#[ensures(result == x + 1)]
+fn increment(x: u32) -> u32 {
+    x + 1
+}
+
+fn main() {}
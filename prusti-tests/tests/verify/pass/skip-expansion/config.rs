@@ -0,0 +1,16 @@
+// compile-flags: -Pskip_expansions_of=*_panicking_*
+
+use prusti_contracts::*;
+
+macro_rules! generate_panicking_fn {
+    ($name:ident) => {
+        fn $name(x: u32) -> u32 {
+            assert!(x > 0);
+            x - 1
+        }
+    };
+}
+
+generate_panicking_fn!(generated);
+
+fn main() {}
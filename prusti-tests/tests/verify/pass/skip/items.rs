@@ -0,0 +1,35 @@
+use prusti_contracts::*;
+
+#[prusti::skip]
+fn skipped_fn(x: u32) -> u32 {
+    assert!(x > 0);
+    x - 1
+}
+
+struct Counter {
+    value: u32,
+}
+
+#[prusti::skip]
+impl Counter {
+    fn decrement(&mut self) {
+        assert!(self.value > 0);
+        self.value -= 1;
+    }
+}
+
+#[prusti::skip]
+mod skipped_module {
+    pub fn unreachable() {
+        unreachable!();
+    }
+}
+
+fn caller() {
+    skipped_fn(0);
+    let mut counter = Counter { value: 0 };
+    counter.decrement();
+    skipped_module::unreachable();
+}
+
+fn main() {}
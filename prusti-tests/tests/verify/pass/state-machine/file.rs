@@ -0,0 +1,41 @@
+use prusti_contracts::*;
+
+state_machine! {
+    enum FileState {
+        Closed => Open,
+        Open => Closed | Eof,
+        Eof => Closed,
+    }
+}
+
+struct File {
+    state: FileState,
+}
+
+impl File {
+    #[ensures(result.state.is(FileState::Closed))]
+    fn new() -> Self {
+        File { state: FileState::Closed }
+    }
+
+    #[requires(self.state.is(FileState::Closed))]
+    #[ensures(self.state.is(FileState::Open))]
+    fn open(&mut self) {
+        self.state.transition(FileState::Open);
+    }
+
+    #[requires(self.state.is(FileState::Open) || self.state.is(FileState::Eof))]
+    #[ensures(self.state.is(FileState::Closed))]
+    fn close(&mut self) {
+        self.state.transition(FileState::Closed);
+    }
+}
+
+fn main() {
+    let mut file = File::new();
+    file.open();
+    file.close();
+    file.open();
+    file.state.transition(FileState::Eof);
+    file.close();
+}
@@ -0,0 +1,54 @@
+use prusti_contracts::*;
+
+state_machine! {
+    enum ConnectionState {
+        Closed => Connected,
+        Connected => Authenticated | Closed,
+        Authenticated => Closed,
+    }
+}
+
+struct Connection {
+    state: ConnectionState,
+}
+
+impl Connection {
+    #[ensures(result.state.is(ConnectionState::Closed))]
+    fn new() -> Self {
+        Connection { state: ConnectionState::Closed }
+    }
+
+    #[in_state(ConnectionState::Closed => ConnectionState::Connected)]
+    fn connect(&mut self) {
+        self.state.transition(ConnectionState::Connected);
+    }
+
+    #[in_state(ConnectionState::Connected => ConnectionState::Authenticated)]
+    fn login(&mut self) {
+        self.state.transition(ConnectionState::Authenticated);
+    }
+
+    #[in_state(ConnectionState::Authenticated)]
+    fn send(&self, _data: u32) {}
+}
+
+struct Builder {
+    phase: ConnectionState,
+}
+
+impl Builder {
+    #[in_state(phase: ConnectionState::Connected => ConnectionState::Closed)]
+    fn finish(&mut self) {
+        self.phase.transition(ConnectionState::Closed);
+    }
+}
+
+fn main() {
+    let mut connection = Connection::new();
+    connection.connect();
+    connection.login();
+    connection.send(1);
+    connection.send(2);
+    let mut builder = Builder { phase: ConnectionState::Connected };
+    builder.finish();
+}
@@ -0,0 +1,19 @@
+// compile-flags: -Phavoc_mutable_global_state=true
+
+use prusti_contracts::*;
+
+static mut COUNTER: u32 = 0;
+
+#[ensures(result <= 100)]
+fn read_bounded() -> u32 {
+    let value = unsafe { COUNTER };
+    if value > 100 { 100 } else { value }
+}
+
+fn write() {
+    unsafe {
+        COUNTER = 5;
+    }
+}
+
+fn main() {}
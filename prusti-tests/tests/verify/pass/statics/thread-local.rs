@@ -0,0 +1,16 @@
+// compile-flags: -Phavoc_mutable_global_state=true
+
+use prusti_contracts::*;
+
+thread_local! {
+    static LIMIT: u32 = 100;
+}
+
+#[trusted]
+fn read_limit(limit: &u32) -> u32 {
+    *limit
+}
+
+fn main() {
+    let _limit = LIMIT.with(read_limit);
+}
@@ -0,0 +1,46 @@
+use prusti_contracts::*;
+
+#[pure]
+#[decreases(n)]
+fn triangle(n: u32) -> u32 {
+    if n == 0 { 0 } else { n + triangle(n - 1) }
+}
+
+#[decreases(n)]
+fn count_down(n: u32) {
+    if n > 0 {
+        count_down(n - 1);
+    }
+}
+
+#[requires(a >= b)]
+#[decreases(a - b)]
+fn count_up(a: u32, b: u32) {
+    if b < a {
+        count_up(a, b + 1);
+    }
+}
+
+#[decreases(n)]
+fn is_even(n: u32) -> bool {
+    if n == 0 { true } else { is_odd(n - 1) }
+}
+
+#[decreases(n)]
+fn is_odd(n: u32) -> bool {
+    if n == 0 { false } else { is_even(n - 1) }
+}
+
+fn sum_up_to(n: usize) -> usize {
+    let mut i = 0;
+    let mut sum = 0;
+    while i < n {
+        body_invariant!(i < n);
+        body_variant!(n - i);
+        sum += 1;
+        i += 1;
+    }
+    sum
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+// compile-flags: --test -Pverify_only_tests=true
+
+use prusti_contracts::*;
+
+#[ensures(result == x + 1)]
+fn inc(x: u32) -> u32 {
+    x + 1
+}
+
+// Not verified, because it is not a test.
+fn might_panic(x: u32) {
+    assert!(x == 0);
+}
+
+#[test]
+fn test_inc() {
+    let y = inc(1);
+    assert!(y == 2);
+}
@@ -0,0 +1,35 @@
+use prusti_contracts::*;
+
+trait Foo {
+    #[requires(val > 100)]
+    #[ensures(result > 0)]
+    fn foo(&self, val: i32) -> i32;
+
+    #[pure]
+    #[requires(val >= 0)]
+    #[ensures(result >= 0)]
+    fn bar(&self, val: i32) -> i32;
+}
+
+struct Dummy { }
+
+#[refine_trait_spec]
+impl Foo for Dummy {
+    #[trusted]
+    #[requires(val > 12)]
+    #[ensures(result > 10)]
+    fn foo(&self, val: i32) -> i32 {
+        val
+    }
+
+    #[pure]
+    #[ensures(result == val)]
+    fn bar(&self, val: i32) -> i32 {
+        val
+    }
+}
+
+fn main() {
+    let d = Dummy {};
+    assert!(d.foo(42) > 10);
+}
@@ -0,0 +1,34 @@
+use prusti_contracts::*;
+
+trait Shape {
+    #[ensures(result >= 3)]
+    fn sides(&self) -> u32;
+}
+
+struct Square;
+
+impl Shape for Square {
+    fn sides(&self) -> u32 {
+        4
+    }
+}
+
+fn count(shape: &dyn Shape) -> u32 {
+    let n = shape.sides();
+    assert!(n >= 3);
+    n
+}
+
+fn borrowed() {
+    let square = Square;
+    let n = count(&square);
+    assert!(n >= 3);
+}
+
+fn boxed() {
+    let shape: Box<dyn Shape> = Box::new(Square);
+    let n = shape.sides();
+    assert!(n >= 3);
+}
+
+fn main() {}
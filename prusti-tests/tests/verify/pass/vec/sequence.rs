@@ -0,0 +1,42 @@
+use prusti_contracts::*;
+
+#[requires(v.len() > 0)]
+#[ensures(result == v[0])]
+fn first(v: &Vec<i32>) -> i32 {
+    v[0]
+}
+
+#[requires(v.len() < 100)]
+#[ensures(v.len() == old(v.len()) + 1)]
+#[ensures(v[v.len() - 1] == value)]
+#[ensures(forall(|i: usize| i < old(v.len()) ==> v[i] == old(v[i])))]
+fn append(v: &mut Vec<i32>, value: i32) {
+    v.push(value);
+}
+
+#[requires(!v.is_empty())]
+#[ensures(v.len() == old(v.len()) - 1)]
+#[ensures(result == old(v[v.len() - 1]))]
+fn take_last(v: &mut Vec<i32>) -> i32 {
+    v.pop().unwrap()
+}
+
+fn main() {
+    let mut v = Vec::new();
+    assert!(v.is_empty());
+    assert!(v.pop().is_none());
+    v.push(1);
+    v.push(2);
+    v.push(3);
+    assert!(v.len() == 3);
+    assert!(v[0] == 1 && v[2] == 3);
+    assert!(first(&v) == 1);
+    v.insert(1, 5);
+    assert!(v[0] == 1 && v[1] == 5 && v[2] == 2 && v[3] == 3);
+    let removed = v.remove(0);
+    assert!(removed == 1);
+    assert!(v.len() == 3 && v[0] == 5);
+    assert!(take_last(&mut v) == 3);
+    append(&mut v, 7);
+    assert!(v[2] == 7);
+}
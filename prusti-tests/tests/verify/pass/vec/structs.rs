@@ -0,0 +1,22 @@
+use prusti_contracts::*;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn main() {
+    let mut v = Vec::new();
+    v.push(Point { x: 1, y: 2 });
+    v.push(Point { x: 3, y: 4 });
+    v.insert(1, Point { x: 5, y: 6 });
+    assert!(v.len() == 3);
+    let last = v.pop().unwrap();
+    assert!(last.x == 3 && last.y == 4);
+    let first = v.remove(0);
+    assert!(first.x == 1 && first.y == 2);
+    let middle = v[0];
+    assert!(middle.x == 5);
+    assert!(v.len() == 1);
+}
@@ -0,0 +1,23 @@
+use prusti_contracts::*;
+
+#[viper_import("max.vpr", "max")]
+fn max(a: i32, b: i32) -> i32 {
+    if a > b { a } else { b }
+}
+
+#[ensures(result >= a && result >= b)]
+fn test_call(a: i32, b: i32) -> i32 {
+    max(a, b)
+}
+
+#[pure]
+#[ensures(result == a || result == b)]
+fn test_pure_call(a: i32, b: i32) -> i32 {
+    max(a, b)
+}
+
+#[requires(max(a, b) == a)]
+#[ensures(a >= b)]
+fn test_spec(a: i32, b: i32) {}
+
+fn main() {}
@@ -0,0 +1,32 @@
+// compile-flags: -Pwhole_program=true
+
+use prusti_contracts::*;
+
+trait Shape {
+    fn area(&self) -> u32;
+}
+
+struct Square {
+    side: u32,
+}
+
+impl Shape for Square {
+    #[ensures(result == self.side * self.side)]
+    fn area(&self) -> u32 {
+        self.side * self.side
+    }
+}
+
+// `Square` is the only implementation of `Shape`, so the call uses the
+// contract of `Square::area`.
+#[ensures(result == shape.side * shape.side)]
+fn area_of_square(shape: &Square) -> u32 {
+    let dynamic: &dyn Shape = shape;
+    dynamic.area()
+}
+
+fn main() {
+    let square = Square { side: 3 };
+    let area = area_of_square(&square);
+    assert!(area == 9);
+}
@@ -0,0 +1,14 @@
+// compile-flags: -Pcounterexample=true
+
+use prusti_contracts::*;
+
+struct Counter {
+    count: u32,
+}
+
+#[requires(a == 3 && b.count == 4)]
+fn test(a: u32, b: &Counter) {
+    assert!(a == b.count);
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+// compile-flags: -Pcounterexample=true
+
+use prusti_contracts::*;
+
+#[requires(n == 2)]
+fn test(n: u32) {
+    let mut i = 0;
+    while i < n {
+        body_invariant!(i < 1);
+        i += 1;
+    }
+}
+
+fn main() {}
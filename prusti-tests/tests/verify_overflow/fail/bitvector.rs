@@ -0,0 +1,14 @@
+use prusti_contracts::*;
+
+#[bitvector]
+fn shift_overflow(x: u8, shift: u32) -> u8 {
+    x << shift //~ ERROR assertion might fail with "attempt to shift left with overflow"
+}
+
+#[bitvector]
+fn wrong_mask() {
+    let a: u8 = 0b1100;
+    assert!(a & 0b0110 == 0b0110); //~ ERROR the asserted expression might not hold
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use prusti_contracts::*;
+
+const FLAG: u32 = 2;
+
+#[ensures(result)] //~ ERROR postcondition might not hold
+fn has_flag(x: u32) -> bool {
+    (x & FLAG) as u8 != 0
+}
+
+fn main() {}
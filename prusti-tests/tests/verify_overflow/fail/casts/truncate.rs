@@ -0,0 +1,13 @@
+// compile-flags: -Ptruncate_casts=true
+
+use prusti_contracts::*;
+
+#[ensures(result as u32 == x)] //~ ERROR postcondition might not hold
+fn lossy(x: u32) -> u8 {
+    x as u8
+}
+
+fn main() {
+    let a: i32 = -1;
+    assert!(a as u8 == 0); //~ ERROR the asserted expression might not hold
+}
@@ -0,0 +1,23 @@
+// rustc-env:PRUSTI_CONFIG=tests/module-settings/Prusti.toml
+
+mod unchecked {
+    pub fn add(x: u8, y: u8) -> u8 {
+        x + y
+    }
+
+    // The addition wraps around instead of being assumed not to overflow.
+    pub fn add_is_monotonic(x: u8, y: u8) -> u8 {
+        let sum = x + y;
+        assert!(sum >= x); //~ ERROR the asserted expression might not hold
+        sum
+    }
+}
+
+// The module `uncheckedness` is not contained in `unchecked`.
+mod uncheckedness {
+    pub fn add(x: u8, y: u8) -> u8 {
+        x + y //~ ERROR assertion might fail with "attempt to add with overflow"
+    }
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use prusti_contracts::*;
+
+#[requires(x >= -128)]
+fn negate_i8(x: i8) -> i8 {
+    -x //~ ERROR negate with overflow
+}
+
+#[bit_precise]
+fn negate_wrapping(x: i16) -> i16 {
+    -x //~ ERROR negate with overflow
+}
+
+fn main() {}
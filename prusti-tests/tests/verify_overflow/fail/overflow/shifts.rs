@@ -0,0 +1,12 @@
+use prusti_contracts::*;
+
+fn shift_by_too_much(x: u32, amount: u32) -> u32 {
+    x >> amount //~ ERROR assertion might fail with "attempt to shift right with overflow"
+}
+
+#[ensures(result == x * 16)] //~ ERROR postcondition might not hold
+fn shift_out_bits(x: u8) -> u8 {
+    x << 4
+}
+
+fn main() {}
@@ -0,0 +1,35 @@
+use prusti_contracts::*;
+
+#[bitvector]
+#[ensures(result == (x & 0xff))]
+#[ensures(result <= 255)]
+fn low_byte(x: u32) -> u32 {
+    x & 0xff
+}
+
+#[bitvector]
+#[requires(shift < 8)]
+fn rotate_left(x: u8, shift: u32) -> u8 {
+    if shift == 0 { x } else { (x << shift) | (x >> (8 - shift)) }
+}
+
+#[bitvector]
+fn bits() {
+    let a: u8 = 0b1100;
+    let b: u8 = 0b1010;
+    assert!(a & b == 0b1000);
+    assert!(a | b == 0b1110);
+    assert!(a ^ b == 0b0110);
+    assert!(a << 4 == 0b1100_0000);
+    assert!(a >> 2 == 0b11);
+    let c: i8 = -8;
+    assert!(c >> 1 == -4);
+    assert!(c & 0x0f == 8);
+}
+
+fn main() {
+    let x = low_byte(0x1234);
+    assert!(x == 0x34);
+    let y = rotate_left(0b1000_0001, 1);
+    bits();
+}
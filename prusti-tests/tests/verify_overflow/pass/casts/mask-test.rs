@@ -0,0 +1,29 @@
+use prusti_contracts::*;
+
+const FLAG_A: u32 = 1;
+const FLAG_B: u32 = 4;
+const FLAG_HIGH: u32 = 256;
+
+#[ensures(result == (x % 2 == 1))]
+fn has_flag_a(x: u32) -> bool {
+    (x & FLAG_A) != 0
+}
+
+#[requires(x == 5)]
+#[ensures(result)]
+fn has_flag_b(x: u32) -> bool {
+    (x & FLAG_B) as u8 != 0
+}
+
+#[ensures(!result)]
+fn truncated_flag(x: u32) -> bool {
+    (x & FLAG_HIGH) as u8 != 0
+}
+
+#[requires(x >= 0)]
+#[ensures(result == ((x / 4) % 2 == 1))]
+fn signed_flag(x: i32) -> bool {
+    (x & 4) != 0
+}
+
+fn main() {}
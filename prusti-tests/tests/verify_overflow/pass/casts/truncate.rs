@@ -0,0 +1,24 @@
+// compile-flags: -Ptruncate_casts=true
+
+use prusti_contracts::*;
+
+#[ensures(result <= 255)]
+fn low_byte(x: u64) -> u32 {
+    (x as u8) as u32
+}
+
+#[ensures(x < 256 ==> result as u32 == x)]
+fn small(x: u32) -> u8 {
+    x as u8
+}
+
+fn main() {
+    let a: u32 = 300;
+    assert!(a as u8 == 44);
+    let b: i32 = -1;
+    assert!(b as u32 == 4294967295);
+    let c: u16 = 200;
+    assert!(c as i8 == -56);
+    let d: i64 = -129;
+    assert!(d as i8 == 127);
+}
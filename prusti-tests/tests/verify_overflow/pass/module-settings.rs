@@ -0,0 +1,29 @@
+// rustc-env:PRUSTI_CONFIG=tests/module-settings/Prusti.toml
+
+mod unchecked {
+    // Overflows are not checked in this module, its arithmetic wraps around.
+    pub fn add(x: u8, y: u8) -> u8 {
+        x + y
+    }
+
+    pub mod inner {
+        pub fn mul(x: u32, y: u32) -> u32 {
+            x * y
+        }
+    }
+}
+
+mod checked {
+    use prusti_contracts::*;
+
+    #[requires(x <= 100 && y <= 100)]
+    pub fn add(x: u8, y: u8) -> u8 {
+        x + y
+    }
+}
+
+fn main() {
+    unchecked::add(1, 2);
+    unchecked::inner::mul(3, 4);
+    checked::add(5, 6);
+}
@@ -0,0 +1,31 @@
+use prusti_contracts::*;
+
+#[pure]
+#[requires(x > i32::MIN)]
+fn negate_i32(x: i32) -> i32 {
+    -x
+}
+
+#[requires(x != i64::MIN)]
+#[ensures(result == -x)]
+fn negate_i64(x: i64) -> i64 {
+    -x
+}
+
+#[requires(x >= 0)]
+#[ensures(result <= 0)]
+fn negate_non_negative(x: isize) -> isize {
+    -x
+}
+
+#[bit_precise]
+#[requires(x > i8::MIN)]
+#[ensures(result == -x)]
+fn negate_i8(x: i8) -> i8 {
+    -x
+}
+
+fn main() {
+    assert!(negate_i32(5) == -5);
+    assert!(negate_i64(i64::MAX) == i64::MIN + 1);
+}
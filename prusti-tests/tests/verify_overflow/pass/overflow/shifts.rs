@@ -0,0 +1,24 @@
+use prusti_contracts::*;
+
+#[requires(x < 16)]
+#[ensures(result == x * 16)]
+fn shift_left(x: u8) -> u8 {
+    x << 4
+}
+
+#[ensures(result == (x % 16) * 16)]
+fn shift_out_bits(x: u8) -> u8 {
+    x << 4
+}
+
+#[requires(amount < 32)]
+fn shift_right(x: u32, amount: u32) -> u32 {
+    x >> amount
+}
+
+#[ensures(result == x / 4)]
+fn divide_by_four(x: u32) -> u32 {
+    x >> 2
+}
+
+fn main() {}
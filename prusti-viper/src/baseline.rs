@@ -0,0 +1,58 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The verification failures that are accepted by the baseline file given by
+//! `config::baseline()`. Errors of accepted items are reported as warnings.
+
+use std::collections::HashSet;
+use std::fs;
+use serde::Deserialize;
+use prusti_common::config;
+
+#[derive(Deserialize)]
+struct BaselineFailure {
+    crate_name: String,
+    item: String,
+}
+
+#[derive(Deserialize)]
+struct BaselineFile {
+    failures: Vec<BaselineFailure>,
+}
+
+#[derive(Default)]
+pub struct Baseline {
+    /// The absolute names of the items of the crate whose failures are accepted.
+    items: HashSet<String>,
+}
+
+impl Baseline {
+    /// Load the accepted failures of the given crate. A missing baseline file
+    /// accepts no failure.
+    pub fn load(crate_name: &str) -> Result<Self, String> {
+        let path = match config::baseline() {
+            Some(path) => path,
+            None => return Ok(Baseline::default()),
+        };
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => return Ok(Baseline::default()),
+        };
+        let file: BaselineFile = serde_json::from_str(&content)
+            .map_err(|error| format!("invalid baseline file '{}': {}", path, error))?;
+        Ok(Baseline {
+            items: file.failures
+                .into_iter()
+                .filter(|failure| failure.crate_name == crate_name)
+                .map(|failure| failure.item)
+                .collect(),
+        })
+    }
+
+    pub fn accepts(&self, item: &str) -> bool {
+        self.items.contains(item)
+    }
+}
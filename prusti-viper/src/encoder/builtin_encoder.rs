@@ -6,6 +6,7 @@
 
 use crate::encoder::snapshot;
 use prusti_common::{vir, vir::WithIdentifier};
+use rustc_middle::mir;
 
 #[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
 pub enum BuiltinMethodKind {
@@ -21,12 +22,34 @@ pub enum BuiltinFunctionKind {
     /// type
     Undefined(vir::Type),
 }
+/// How the arithmetic methods of the integer types of the standard library,
+/// e.g. `wrapping_add` or `checked_mul`, handle overflows.
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub enum IntArithMode {
+    /// The result wraps around the range of the type.
+    Wrapping,
+    /// The result is `None` on overflow.
+    Checked,
+    /// The result is clamped to the range of the type.
+    Saturating,
+    /// The result is the wrapped value and whether an overflow happened.
+    Overflowing,
+}
+
 #[derive(Clone, Debug, Hash, Eq, PartialEq)]
 pub enum BuiltinDomainKind {
     Nat,
     Primitive,
+    Address,
+    /// The sequences of values of the given type, e.g. the snapshots of the
+    /// elements of a vector.
+    Seq(vir::Type),
 }
 
+/// The name of the domain of the relation that tells whether two pointers
+/// have the same address.
+const ADDRESS_DOMAIN_NAME: &str = "AddressDomain";
+
 pub struct BuiltinEncoder {}
 
 impl BuiltinEncoder {
@@ -99,6 +122,127 @@ impl BuiltinEncoder {
         match kind {
             BuiltinDomainKind::Nat => self.encode_nat_builtin_domain(),
             BuiltinDomainKind::Primitive => self.encode_primitive_builtin_domain(),
+            BuiltinDomainKind::Address => self.encode_address_builtin_domain(),
+            BuiltinDomainKind::Seq(elem_type) => self.encode_seq_builtin_domain(elem_type),
+        }
+    }
+
+    /// The name of the domain of the sequences of values of `elem_type`.
+    pub fn encode_seq_domain_name(&self, elem_type: &vir::Type) -> String {
+        let elem_name = match elem_type {
+            vir::Type::Int => "Int",
+            vir::Type::Bool => "Bool",
+            vir::Type::TypedRef(name) | vir::Type::Domain(name) => name.as_str(),
+        };
+        format!("Seq${}", elem_name)
+    }
+
+    /// The function that returns the length of a sequence of `elem_type`.
+    pub fn encode_seq_len_func(&self, elem_type: &vir::Type) -> vir::DomainFunc {
+        let domain_name = self.encode_seq_domain_name(elem_type);
+        vir::DomainFunc {
+            name: format!("{}$len", domain_name),
+            formal_args: vec![vir::LocalVar::new("seq", vir::Type::Domain(domain_name.clone()))],
+            return_type: vir::Type::Int,
+            unique: false,
+            domain_name,
+        }
+    }
+
+    /// The function that returns the element at an index of a sequence of
+    /// `elem_type`. It is only meaningful for the indices within the length.
+    pub fn encode_seq_lookup_func(&self, elem_type: &vir::Type) -> vir::DomainFunc {
+        let domain_name = self.encode_seq_domain_name(elem_type);
+        vir::DomainFunc {
+            name: format!("{}$lookup", domain_name),
+            formal_args: vec![
+                vir::LocalVar::new("seq", vir::Type::Domain(domain_name.clone())),
+                vir::LocalVar::new("index", vir::Type::Int),
+            ],
+            return_type: elem_type.clone(),
+            unique: false,
+            domain_name,
+        }
+    }
+
+    /// A sequence is only described by its length and its elements, so that
+    /// the contracts of the operations that change it relate the elements
+    /// before and after the operation.
+    fn encode_seq_builtin_domain(&self, elem_type: vir::Type) -> vir::Domain {
+        let domain_name = self.encode_seq_domain_name(&elem_type);
+        let len_func = self.encode_seq_len_func(&elem_type);
+        let seq = vir::LocalVar::new("seq", vir::Type::Domain(domain_name.clone()));
+        let len = vir::Expr::domain_func_app(len_func.clone(), vec![vir::Expr::local(seq.clone())]);
+        let non_negative_len = vir::Expr::forall(
+            vec![seq],
+            vec![vir::Trigger::new(vec![len.clone()])],
+            vir::Expr::ge_cmp(len, 0.into()),
+        );
+        vir::Domain {
+            name: domain_name.clone(),
+            functions: vec![len_func, self.encode_seq_lookup_func(&elem_type)],
+            axioms: vec![vir::DomainAxiom {
+                name: format!("{}$non_negative_len", domain_name),
+                expr: non_negative_len,
+                domain_name,
+            }],
+            type_vars: vec![],
+        }
+    }
+
+    /// The uninterpreted function that tells whether two pointers, given by
+    /// the Viper references that they point to, have the same address.
+    pub fn encode_address_eq_func(&self) -> vir::DomainFunc {
+        vir::DomainFunc {
+            name: "address_eq".to_owned(),
+            formal_args: vec![
+                vir::LocalVar::new("left", vir::Type::TypedRef("".to_string())),
+                vir::LocalVar::new("right", vir::Type::TypedRef("".to_string())),
+            ],
+            return_type: vir::Type::Bool,
+            unique: false,
+            domain_name: ADDRESS_DOMAIN_NAME.to_owned(),
+        }
+    }
+
+    /// The address equality of pointers is not derived from the Viper
+    /// references that they point to, because the encoding does not preserve
+    /// the identity of all memory locations. It is only axiomatized to be
+    /// reflexive and symmetric, which is sound for any model of the memory.
+    fn encode_address_builtin_domain(&self) -> vir::Domain {
+        let address_eq = self.encode_address_eq_func();
+        let left = vir::LocalVar::new("left", vir::Type::TypedRef("".to_string()));
+        let right = vir::LocalVar::new("right", vir::Type::TypedRef("".to_string()));
+        let app = |left: &vir::LocalVar, right: &vir::LocalVar| vir::Expr::domain_func_app(
+            address_eq.clone(),
+            vec![vir::Expr::local(left.clone()), vir::Expr::local(right.clone())],
+        );
+        let reflexivity = vir::Expr::forall(
+            vec![left.clone()],
+            vec![vir::Trigger::new(vec![app(&left, &left)])],
+            app(&left, &left),
+        );
+        let symmetry = vir::Expr::forall(
+            vec![left.clone(), right.clone()],
+            vec![vir::Trigger::new(vec![app(&left, &right)])],
+            vir::Expr::eq_cmp(app(&left, &right), app(&right, &left)),
+        );
+        vir::Domain {
+            name: ADDRESS_DOMAIN_NAME.to_owned(),
+            functions: vec![address_eq.clone()],
+            axioms: vec![
+                vir::DomainAxiom {
+                    name: "address_eq$reflexivity".to_owned(),
+                    expr: reflexivity,
+                    domain_name: ADDRESS_DOMAIN_NAME.to_owned(),
+                },
+                vir::DomainAxiom {
+                    name: "address_eq$symmetry".to_owned(),
+                    expr: symmetry,
+                    domain_name: ADDRESS_DOMAIN_NAME.to_owned(),
+                },
+            ],
+            type_vars: vec![],
         }
     }
 
@@ -156,3 +300,31 @@ impl BuiltinEncoder {
         }
     }
 }
+
+/// The arithmetic method of an integer type that is called by `proc_name`,
+/// as its mode and its operation, e.g. `(Checked, Add)` for
+/// `u32::checked_add`. These methods are encoded with their exact semantics.
+pub fn get_int_arith_method(proc_name: &str) -> Option<(IntArithMode, mir::BinOp)> {
+    if !proc_name.starts_with("core::num::<impl ") && !proc_name.starts_with("std::num::<impl ") {
+        return None;
+    }
+    let method = proc_name.rsplit("::").next()?;
+    let (mode, op) = if let Some(op) = method.strip_prefix("wrapping_") {
+        (IntArithMode::Wrapping, op)
+    } else if let Some(op) = method.strip_prefix("checked_") {
+        (IntArithMode::Checked, op)
+    } else if let Some(op) = method.strip_prefix("saturating_") {
+        (IntArithMode::Saturating, op)
+    } else if let Some(op) = method.strip_prefix("overflowing_") {
+        (IntArithMode::Overflowing, op)
+    } else {
+        return None;
+    };
+    let op = match op {
+        "add" => mir::BinOp::Add,
+        "sub" => mir::BinOp::Sub,
+        "mul" => mir::BinOp::Mul,
+        _ => return None,
+    };
+    Some((mode, op))
+}
@@ -10,13 +10,13 @@ use crate::encoder::builtin_encoder::BuiltinEncoder;
 use crate::encoder::builtin_encoder::BuiltinFunctionKind;
 use crate::encoder::builtin_encoder::BuiltinMethodKind;
 use crate::encoder::builtin_encoder::BuiltinDomainKind;
-use crate::encoder::errors::{ErrorCtxt, ErrorManager, SpannedEncodingError, EncodingError, WithSpan, RunIfErr};
+use crate::encoder::errors::{ErrorCtxt, ErrorManager, SpannedEncodingError, EncodingError, EncodingErrorKind, WithSpan, RunIfErr};
 use crate::encoder::foldunfold;
 use crate::encoder::places;
 use crate::encoder::procedure_encoder::ProcedureEncoder;
 use crate::encoder::pure_function_encoder::PureFunctionEncoder;
 use crate::encoder::stub_function_encoder::StubFunctionEncoder;
-use crate::encoder::spec_encoder::encode_spec_assertion;
+use crate::encoder::spec_encoder::{encode_spec_assertion, encode_closure_call_contract};
 use crate::encoder::snapshot_encoder::{Snapshot, SnapshotEncoder};
 use crate::encoder::type_encoder::{
     compute_discriminant_values, compute_discriminant_bounds, TypeEncoder};
@@ -26,11 +26,11 @@ use prusti_common::vir;
 use prusti_common::vir::{WithIdentifier, ExprIterator};
 use prusti_common::config;
 use prusti_common::report::log;
-use prusti_interface::data::ProcedureDefId;
+use prusti_interface::data::{ProcedureDefId, ProcedureReport, ProcedureStatus};
 use prusti_interface::environment::Environment;
 use prusti_interface::specs::typed;
 use prusti_interface::specs::typed::SpecificationId;
-use prusti_interface::utils::{has_spec_only_attr, read_prusti_attrs};
+use prusti_interface::utils::{has_prusti_attr, has_spec_only_attr, read_prusti_attr, read_prusti_attrs};
 use prusti_interface::PrustiError;
 use prusti_specs::specifications::common::SpecIdRef;
 use rustc_hir as hir;
@@ -39,28 +39,44 @@ use rustc_hir::def_id::DefId;
 use rustc_middle::mir;
 // use rustc::mir::interpret::GlobalId;
 use rustc_middle::ty;
+use rustc_middle::ty::TypeFoldable;
+use rustc_middle::ty::subst::Subst;
 use std::cell::{RefCell, RefMut};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
 use std::io::Write;
 use std::mem;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 // use syntax::ast;
 use rustc_ast::ast;
 // use viper;
+use crate::encoder::refinement_check_encoder::RefinementCheckEncoder;
 use crate::encoder::stub_procedure_encoder::StubProcedureEncoder;
 use std::ops::AddAssign;
 use std::convert::TryInto;
 use std::borrow::Borrow;
 use crate::encoder::specs_closures_collector::SpecsClosuresCollector;
 use crate::encoder::memory_eq_encoder::MemoryEqEncoder;
-use rustc_span::MultiSpan;
+use rustc_span::{MultiSpan, Symbol};
 use crate::encoder::name_interner::NameInterner;
+use crate::encoder::viper_name_map::{ViperDeclarationKind, ViperNameMap, ViperNameOrigin};
 use crate::encoder::utils::transpose;
 use crate::encoder::errors::EncodingResult;
 use crate::encoder::errors::SpannedEncodingResult;
 use crate::encoder::snapshot;
+use crate::encoder::float_encoder;
 
 const SNAPSHOT_MIRROR_DOMAIN: &str = "$SnapshotMirrors$";
 
+/// The token predicate that grants the permission to dereference a raw
+/// pointer, given its address.
+const RAW_POINTER_VALIDITY_PREDICATE: &str = "RawPtrValid$";
+
+/// An instantiation of a generic pure function, given by the types that
+/// substitute its generic parameters (including those of the parent item).
+type PureFunctionInstance<'tcx> = (ProcedureDefId, Vec<ty::Ty<'tcx>>);
+
 pub struct Encoder<'v, 'tcx: 'v> {
     env: &'v Environment<'tcx>,
     def_spec: &'v typed::DefSpecificationMap<'tcx>,
@@ -72,11 +88,14 @@ pub struct Encoder<'v, 'tcx: 'v> {
     builtin_methods: RefCell<HashMap<BuiltinMethodKind, vir::BodylessMethod>>,
     builtin_functions: RefCell<HashMap<BuiltinFunctionKind, vir::Function>>,
     procedures: RefCell<HashMap<ProcedureDefId, vir::CfgMethod>>,
-    pure_function_bodies: RefCell<HashMap<(ProcedureDefId, String), vir::Expr>>,
-    pure_functions: RefCell<HashMap<(ProcedureDefId, String), vir::Function>>,
+    pure_function_bodies: RefCell<HashMap<PureFunctionInstance<'tcx>, vir::Expr>>,
+    pure_functions: RefCell<HashMap<PureFunctionInstance<'tcx>, vir::Function>>,
+    /// The instantiations of pure functions that have been queued for
+    /// encoding, each of which is encoded only once.
+    queued_pure_functions: RefCell<HashSet<PureFunctionInstance<'tcx>>>,
     /// Stub pure functions. Generated when an impure Rust function is invoked
     /// where a pure function is required.
-    stub_pure_functions: RefCell<HashMap<(ProcedureDefId, String), vir::Function>>,
+    stub_pure_functions: RefCell<HashMap<PureFunctionInstance<'tcx>, vir::Function>>,
     spec_functions: RefCell<HashMap<ProcedureDefId, Vec<vir::Function>>>,
     type_predicate_names: RefCell<HashMap<ty::TyKind<'tcx>, String>>,
     type_invariant_names: RefCell<HashMap<ty::TyKind<'tcx>, String>>,
@@ -87,6 +106,26 @@ pub struct Encoder<'v, 'tcx: 'v> {
     type_tags: RefCell<HashMap<String, vir::Function>>,
     type_discriminant_funcs: RefCell<HashMap<String, vir::Function>>,
     type_cast_functions: RefCell<HashMap<(ty::Ty<'tcx>, ty::Ty<'tcx>), vir::Function>>,
+    bitwise_op_functions: RefCell<HashMap<(mir::BinOp, ty::Ty<'tcx>), vir::Function>>,
+    /// The functions that read the length and the elements of slices.
+    slice_functions: RefCell<HashMap<String, vir::Function>>,
+    /// The functions that encode the values of const generic parameters.
+    const_param_functions: RefCell<HashMap<String, vir::Function>>,
+    /// Whether the `Float` domain is used, and the bits of the float literals.
+    uses_float_domain: RefCell<bool>,
+    float_literals: RefCell<BTreeSet<u64>>,
+    /// Whether the validity of raw pointers is used.
+    uses_raw_pointer_validity: RefCell<bool>,
+    /// Whether pointers are compared by address.
+    uses_address_eq: RefCell<bool>,
+    /// The element types of the sequence domains, by the name of the domain.
+    seq_domains: RefCell<BTreeMap<String, vir::Type>>,
+    /// For each procedure, the ghost variables that record the initial value
+    /// of its arguments, with the Rust expression that they correspond to.
+    counterexample_vars: RefCell<HashMap<ProcedureDefId, Vec<(String, String)>>>,
+    /// For the position of each loop invariant check, the ghost variables
+    /// that record the state at the loop head, with their Rust expression.
+    loop_head_counterexample_vars: RefCell<HashMap<u64, Vec<(String, String)>>>,
     memory_eq_encoder: RefCell<MemoryEqEncoder>,
     fields: RefCell<HashMap<String, vir::Field>>,
     snapshots: RefCell<HashMap<String, Box<Snapshot>>>, // maps predicate names to snapshots
@@ -97,9 +136,23 @@ pub struct Encoder<'v, 'tcx: 'v> {
     vir_program_before_foldunfold_writer: RefCell<Box<Write>>,
     vir_program_before_viper_writer: RefCell<Box<Write>>,
     pub typaram_repl: RefCell<Vec<HashMap<ty::Ty<'tcx>, ty::Ty<'tcx>>>>,
+    /// The encoded const generic arguments of the called functions, like
+    /// `typaram_repl`.
+    pub const_param_repl: RefCell<Vec<HashMap<ty::ParamConst, vir::Expr>>>,
+    /// The items whose body is being encoded, which own the const generic
+    /// parameters that are not replaced by `const_param_repl`.
+    const_param_owners: RefCell<Vec<DefId>>,
     encoding_errors_counter: RefCell<usize>,
+    procedure_reports: RefCell<Vec<ProcedureReport>>,
     name_interner: RefCell<NameInterner>,
+    /// Maps the Viper identifiers of items to the items that they encode.
+    viper_item_names: RefCell<HashMap<String, DefId>>,
     axiomatized_function_domain: RefCell<vir::Domain>,
+    /// The index of the group of mutually recursive procedures with a
+    /// `#[decreases]` annotation that contains a procedure.
+    termination_components: RefCell<HashMap<ProcedureDefId, usize>>,
+    /// The sources of the Viper files imported with `#[viper_import]`.
+    viper_imports: RefCell<BTreeMap<PathBuf, String>>,
 }
 
 impl<'v, 'tcx> Encoder<'v, 'tcx> {
@@ -143,6 +196,7 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             procedures: RefCell::new(HashMap::new()),
             pure_function_bodies: RefCell::new(HashMap::new()),
             pure_functions: RefCell::new(HashMap::new()),
+            queued_pure_functions: RefCell::new(HashSet::new()),
             stub_pure_functions: RefCell::new(HashMap::new()),
             spec_functions: RefCell::new(HashMap::new()),
             type_predicate_names: RefCell::new(HashMap::new()),
@@ -154,6 +208,16 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             type_tags: RefCell::new(HashMap::new()),
             type_discriminant_funcs: RefCell::new(HashMap::new()),
             type_cast_functions: RefCell::new(HashMap::new()),
+            bitwise_op_functions: RefCell::new(HashMap::new()),
+            slice_functions: RefCell::new(HashMap::new()),
+            const_param_functions: RefCell::new(HashMap::new()),
+            uses_float_domain: RefCell::new(false),
+            float_literals: RefCell::new(BTreeSet::new()),
+            uses_raw_pointer_validity: RefCell::new(false),
+            uses_address_eq: RefCell::new(false),
+            seq_domains: RefCell::new(BTreeMap::new()),
+            counterexample_vars: RefCell::new(HashMap::new()),
+            loop_head_counterexample_vars: RefCell::new(HashMap::new()),
             memory_eq_encoder: RefCell::new(MemoryEqEncoder::new()),
             fields: RefCell::new(HashMap::new()),
             closures_collector: RefCell::new(SpecsClosuresCollector::new()),
@@ -161,12 +225,18 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             vir_program_before_foldunfold_writer,
             vir_program_before_viper_writer,
             typaram_repl: RefCell::new(Vec::new()),
+            const_param_repl: RefCell::new(Vec::new()),
+            const_param_owners: RefCell::new(Vec::new()),
             snapshots: RefCell::new(HashMap::new()),
             type_snapshots: RefCell::new(HashMap::new()),
             snap_mirror_funcs: RefCell::new(HashMap::new()),
             encoding_errors_counter: RefCell::new(0),
+            procedure_reports: RefCell::new(vec![]),
             name_interner: RefCell::new(NameInterner::new()),
+            viper_item_names: RefCell::new(HashMap::new()),
             axiomatized_function_domain: RefCell::new(axiomatized_functions_domain),
+            termination_components: RefCell::new(HashMap::new()),
+            viper_imports: RefCell::new(BTreeMap::new()),
         }
     }
 
@@ -224,6 +294,7 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             methods: self.get_used_viper_methods(),
             functions: self.get_used_viper_functions(),
             viper_predicates: self.get_used_viper_predicates(),
+            viper_imports: self.viper_imports.borrow().values().cloned().collect(),
         }
     }
 
@@ -240,6 +311,13 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         *self.encoding_errors_counter.borrow()
     }
 
+    /// Take the reports of the procedures processed by `process_encoding_queue`.
+    /// The status of an encoded procedure is `Verified` until the verifier
+    /// attributes an error to it.
+    pub fn take_procedure_reports(&self) -> Vec<ProcedureReport> {
+        mem::take(&mut *self.procedure_reports.borrow_mut())
+    }
+
     pub fn get_used_viper_domains(&self) -> Vec<vir::Domain> {
         let mirrors: Vec<_> = self
             .snap_mirror_funcs
@@ -264,6 +342,23 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             });
         }
 
+        if *self.uses_float_domain.borrow() {
+            domains.push(float_encoder::encode_float_domain(
+                &self.float_literals.borrow(),
+                config::encode_floats_as_reals(),
+            ));
+        }
+
+        if *self.uses_address_eq.borrow() {
+            domains.push(BuiltinEncoder::new().encode_builtin_domain(BuiltinDomainKind::Address));
+        }
+
+        for elem_type in self.seq_domains.borrow().values() {
+            domains.push(
+                BuiltinEncoder::new().encode_builtin_domain(BuiltinDomainKind::Seq(elem_type.clone()))
+            );
+        }
+
         if config::enable_purification_optimization() {
             domains.push(self.axiomatized_function_domain.borrow().clone());
             let builtin_encoder =  BuiltinEncoder::new();
@@ -275,7 +370,7 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         domains
     }
 
-    pub fn encode_axiomatized_pure_function(&self, f: &vir::Function) {
+    pub fn encode_axiomatized_pure_function(&self, f: &vir::Function) -> EncodingResult<()> {
         let snapshots: &HashMap<String, Box<Snapshot>> = &self.snapshots.borrow();
         let domain_name = self.axiomatized_function_domain.borrow().name.clone();
 
@@ -311,7 +406,9 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             .pres
             .iter()
             .cloned()
-            .map(|p| vir::ExprFolder::fold(&mut purifier, p))
+            .map(|p| purifier.purify(p))
+            .collect::<EncodingResult<Vec<_>>>()?
+            .into_iter()
             .conjoin();
         let post_conds: vir::Expr = f
             .posts
@@ -323,12 +420,14 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
                     // Skip the last post condition as it is only there to clarify the relation between the result of this function and snapshots
                     None
                 } else {
-                    Some(vir::ExprFolder::fold(&mut purifier, p))
+                    Some(purifier.purify(p))
                 }
             })
+            .collect::<EncodingResult<Vec<_>>>()?
+            .into_iter()
             .conjoin();
 
-        let function_body = vir::ExprFolder::fold(&mut purifier, f.body.clone().unwrap());
+        let function_body = purifier.purify(f.body.clone().unwrap())?;
 
         let function_identiry = vir::Expr::eq_cmp(function_call_with_succ.clone(), function_body);
 
@@ -399,6 +498,7 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             .borrow_mut()
             .axioms
             .push(nat_da);
+        Ok(())
     }
 
     fn get_used_viper_fields(&self) -> Vec<vir::Field> {
@@ -430,6 +530,15 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         for function in self.type_cast_functions.borrow().values() {
             functions.push(function.clone());
         }
+        for function in self.bitwise_op_functions.borrow().values() {
+            functions.push(function.clone());
+        }
+        for function in self.slice_functions.borrow().values() {
+            functions.push(function.clone());
+        }
+        for function in self.const_param_functions.borrow().values() {
+            functions.push(function.clone());
+        }
         functions.extend(
             self.memory_eq_encoder.borrow().get_encoded_functions()
         );
@@ -459,6 +568,13 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             },
         ));
 
+        if *self.uses_raw_pointer_validity.borrow() {
+            predicates.push(vir::Predicate::Bodyless(
+                RAW_POINTER_VALIDITY_PREDICATE.to_string(),
+                vir::LocalVar::new("address", vir::Type::TypedRef("".to_string())),
+            ));
+        }
+
         predicates.sort_by_key(|f| f.get_identifier());
         predicates
     }
@@ -475,6 +591,16 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         self.procedures.borrow().values().cloned().collect()
     }
 
+    /// The procedures that are encoded as a method of the Viper program,
+    /// keyed by the name of their method.
+    pub fn get_encoded_procedures(&self) -> HashMap<String, ProcedureDefId> {
+        self.procedures
+            .borrow()
+            .iter()
+            .map(|(&def_id, method)| (method.name(), def_id))
+            .collect()
+    }
+
     pub fn get_single_closure_instantiation(
         &self,
         closure_def_id: DefId,
@@ -506,14 +632,33 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         Some(spec.expect_procedure().clone())
     }
 
-    /// Get a local wrapper `DefId` for functions that have external specs.
+    /// Get a local wrapper `DefId` for functions that have external specs,
+    /// and the spec-only signature of pure trait methods without a body.
     /// Return the original `DefId` for everything else.
     fn get_wrapper_def_id(&self, def_id: DefId) -> DefId {
         self.def_spec.extern_specs.get(&def_id)
             .map(|local_id| local_id.to_def_id())
+            .or_else(|| self.get_pure_signature_def_id(def_id))
             .unwrap_or(def_id)
     }
 
+    /// The spec-only method generated for a pure trait method without a
+    /// default body, which gives the MIR of its signature. Such a method is
+    /// encoded as an abstract function that has only the contract of the
+    /// trait, so that its calls on a type parameter can be verified once for
+    /// all implementations.
+    fn get_pure_signature_def_id(&self, def_id: DefId) -> Option<DefId> {
+        let tcx = self.env.tcx();
+        let trait_id = tcx.trait_of_item(def_id)?;
+        let item = tcx.associated_item(def_id);
+        if item.kind != ty::AssocKind::Fn || item.defaultness.has_value() {
+            return None;
+        }
+        let signature_name = format!("prusti_pure_signature_{}", item.ident);
+        self.env.get_assoc_item(trait_id, Symbol::intern(&signature_name))
+            .map(|signature| signature.def_id)
+    }
+
     fn get_procedure_contract(&self, proc_def_id: ProcedureDefId)
         -> EncodingResult<ProcedureContractMirDef<'tcx>>
     {
@@ -570,6 +715,7 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         // }
 
         if let Some(ty) = self_ty {
+            let ty = self.devirtualize_type(ty);
             if let Some(id) = self.env().tcx().trait_of_item(proc_def_id) {
                 let proc_name = self.env().tcx().item_name(proc_def_id);
                 let procs = self.env().get_trait_method_decl_for_type(ty, id, proc_name);
@@ -602,10 +748,12 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
     /// a primitive types.
     /// For composed data structures, the base expression is returned.
     pub fn encode_value_expr(&self, base: vir::Expr, ty: ty::Ty<'tcx>) -> vir::Expr {
+        let ty = self.reveal_opaque_type(ty);
         match ty.kind() {
             ty::TyKind::Adt(_, _)
-            | ty::TyKind::Tuple(_) => {
-                base // don't use a field for tuples and ADTs
+            | ty::TyKind::Tuple(_)
+            | ty::TyKind::Opaque(_, _) => {
+                base // don't use a field for tuples, ADTs and abstract opaque types
             }
             _ => {
                 let value_field = self.encode_value_field(ty);
@@ -653,7 +801,7 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
     pub fn encode_struct_field(&self, field_name: &str, ty: ty::Ty<'tcx>)
         -> EncodingResult<vir::Field>
     {
-        let viper_field_name = format!("f${}", field_name);
+        let viper_field_name = format!("{}{}", snapshot::STRUCT_FIELD_PREFIX, field_name);
         self.encode_raw_ref_field(viper_field_name, ty)
     }
 
@@ -677,6 +825,61 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         field
     }
 
+    /// Encode that the enum `base` of type `ty` is of the variant named
+    /// `variant_name`, e.g. that an `Option` is `Some`.
+    pub fn encode_variant_guard(
+        &self,
+        base: vir::Expr,
+        ty: ty::Ty<'tcx>,
+        variant_name: &str,
+    ) -> vir::Expr {
+        let tcx = self.env().tcx();
+        let adt_def = match ty.kind() {
+            ty::TyKind::Adt(adt_def, _) if adt_def.is_enum() => adt_def,
+            _ => unreachable!("{:?} is not an enum", ty),
+        };
+        let variant_index = adt_def.variants
+            .iter()
+            .position(|variant_def| variant_def.ident.as_str() == variant_name)
+            .unwrap_or_else(|| unreachable!("{:?} has no variant {}", ty, variant_name));
+        let discriminant_values = compute_discriminant_values(adt_def, tcx);
+        vir::Expr::eq_cmp(
+            base.field(self.encode_discriminant_field()),
+            discriminant_values[variant_index].into(),
+        )
+    }
+
+    /// Encode the place of the single value contained by the variant named
+    /// `variant_name` of the enum `base` of type `ty`, e.g. the value of a
+    /// `Some`. Returns the place and the type of the value.
+    pub fn encode_variant_payload(
+        &self,
+        base: vir::Expr,
+        ty: ty::Ty<'tcx>,
+        variant_name: &str,
+    ) -> EncodingResult<(vir::Expr, ty::Ty<'tcx>)> {
+        let tcx = self.env().tcx();
+        let (adt_def, substs) = match ty.kind() {
+            ty::TyKind::Adt(adt_def, substs) if adt_def.is_enum() => (adt_def, substs),
+            _ => unreachable!("{:?} is not an enum", ty),
+        };
+        let variant_def = adt_def.variants
+            .iter()
+            .find(|variant_def| variant_def.ident.as_str() == variant_name)
+            .unwrap_or_else(|| unreachable!("{:?} has no variant {}", ty, variant_name));
+        if variant_def.fields.len() != 1 {
+            return Err(EncodingError::internal(format!(
+                "the variant {} of {:?} does not contain a single value",
+                variant_name,
+                ty,
+            )));
+        }
+        let field = &variant_def.fields[0];
+        let field_ty = field.ty(tcx, substs);
+        let encoded_field = self.encode_struct_field(&field.ident.as_str(), field_ty)?;
+        Ok((base.variant(variant_name).field(encoded_field), field_ty))
+    }
+
     pub fn encode_discriminant_func_app(
         &self,
         place: vir::Expr,
@@ -821,6 +1024,380 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         Ok(function_name)
     }
 
+    /// Encode the type of the values of `f32` and `f64`.
+    pub fn encode_float_type(&self) -> vir::Type {
+        *self.uses_float_domain.borrow_mut() = true;
+        float_encoder::float_type()
+    }
+
+    pub fn encode_float_literal(&self, value: f64) -> vir::Expr {
+        *self.uses_float_domain.borrow_mut() = true;
+        let bits = float_encoder::encode_literal_bits(value);
+        self.float_literals.borrow_mut().insert(bits);
+        float_encoder::encode_literal(bits)
+    }
+
+    /// Encode the permission to dereference the raw pointer whose address is
+    /// `address`. A read requires some of it, a write all of it. The
+    /// permission is a token: the permission to the pointee is tracked as for
+    /// references, because the fold-unfold algorithm cannot recover from a
+    /// missing permission, so the token guards every dereference instead.
+    pub fn encode_raw_pointer_validity(&self, address: vir::Expr, perm: vir::PermAmount)
+        -> vir::Expr
+    {
+        *self.uses_raw_pointer_validity.borrow_mut() = true;
+        vir::Expr::predicate_access_predicate(RAW_POINTER_VALIDITY_PREDICATE, address, perm)
+    }
+
+    /// Encode whether the pointers to the Viper references `left` and `right`
+    /// have the same address.
+    pub fn encode_address_eq(&self, left: vir::Expr, right: vir::Expr) -> vir::Expr {
+        *self.uses_address_eq.borrow_mut() = true;
+        let address_eq = BuiltinEncoder::new().encode_address_eq_func();
+        vir::Expr::domain_func_app(address_eq, vec![left, right])
+    }
+
+    /// The type of the sequences of values of `elem_type`.
+    pub fn encode_seq_type(&self, elem_type: &vir::Type) -> vir::Type {
+        let domain_name = BuiltinEncoder::new().encode_seq_domain_name(elem_type);
+        self.seq_domains
+            .borrow_mut()
+            .entry(domain_name.clone())
+            .or_insert_with(|| elem_type.clone());
+        vir::Type::Domain(domain_name)
+    }
+
+    /// Encode the length of the sequence `seq` of values of `elem_type`.
+    pub fn encode_seq_len(&self, seq: vir::Expr, elem_type: &vir::Type) -> vir::Expr {
+        self.encode_seq_type(elem_type);
+        let len_func = BuiltinEncoder::new().encode_seq_len_func(elem_type);
+        vir::Expr::domain_func_app(len_func, vec![seq])
+    }
+
+    /// Encode the element at `index` of the sequence `seq` of values of
+    /// `elem_type`.
+    pub fn encode_seq_lookup(&self, seq: vir::Expr, index: vir::Expr, elem_type: &vir::Type)
+        -> vir::Expr
+    {
+        self.encode_seq_type(elem_type);
+        let lookup_func = BuiltinEncoder::new().encode_seq_lookup_func(elem_type);
+        vir::Expr::domain_func_app(lookup_func, vec![seq, index])
+    }
+
+    /// Record that the Viper variable `var_name` of the procedure `proc_def_id`
+    /// holds the value of the Rust expression `rust_expr`.
+    pub fn register_counterexample_var(
+        &self,
+        proc_def_id: ProcedureDefId,
+        var_name: String,
+        rust_expr: String,
+    ) {
+        self.counterexample_vars
+            .borrow_mut()
+            .entry(proc_def_id)
+            .or_insert_with(Vec::new)
+            .push((var_name, rust_expr));
+    }
+
+    /// Record that the Viper variable `var_name` holds the value of the Rust
+    /// expression `rust_expr` at the loop head, when the loop invariant check
+    /// at `pos_id` is performed.
+    pub fn register_loop_head_counterexample_var(
+        &self,
+        pos_id: u64,
+        var_name: String,
+        rust_expr: String,
+    ) {
+        self.loop_head_counterexample_vars
+            .borrow_mut()
+            .entry(pos_id)
+            .or_insert_with(Vec::new)
+            .push((var_name, rust_expr));
+    }
+
+    /// Describe the values that `counterexample` assigns to the registered
+    /// variables of `proc_def_id`, e.g. `x = 255, y = 1`. If the failure is a
+    /// loop invariant check at `pos_id`, the values at the loop head follow,
+    /// e.g. `x = 255; at the loop head: i = 3`.
+    pub fn describe_counterexample(
+        &self,
+        proc_def_id: ProcedureDefId,
+        pos_id: Option<u64>,
+        counterexample: &viper::Counterexample,
+    ) -> Option<String> {
+        let describe = |vars: &[(String, String)]| {
+            let values: Vec<_> = vars
+                .iter()
+                .filter_map(|(var_name, rust_expr)| {
+                    counterexample.get(var_name)
+                        .map(|value| format!("{} = {}", rust_expr, value))
+                })
+                .collect();
+            if values.is_empty() {
+                None
+            } else {
+                Some(values.join(", "))
+            }
+        };
+        let arg_values = self.counterexample_vars
+            .borrow()
+            .get(&proc_def_id)
+            .and_then(|vars| describe(vars));
+        let loop_head_values = pos_id
+            .and_then(|pos_id| {
+                self.loop_head_counterexample_vars
+                    .borrow()
+                    .get(&pos_id)
+                    .and_then(|vars| describe(vars))
+            })
+            .map(|values| format!("at the loop head: {}", values));
+        match (arg_values, loop_head_values) {
+            (Some(arg_values), Some(loop_head_values)) => {
+                Some(format!("{}; {}", arg_values, loop_head_values))
+            }
+            (arg_values, loop_head_values) => arg_values.or(loop_head_values),
+        }
+    }
+
+    /// Encode the use of an uninterpreted function that models a bitwise
+    /// operation (`&`, `|` or `^`) on integers of type `ty`. The function is
+    /// axiomatized only by some common identities and by the bounds of its
+    /// result, which is enough for typical flag manipulations.
+    pub fn encode_bitwise_op_function_use(&self, op: mir::BinOp, ty: ty::Ty<'tcx>)
+        -> EncodingResult<String>
+    {
+        trace!("encode_bitwise_op_function_use(op={:?}, ty={:?})", op, ty);
+        let op_name = match op {
+            mir::BinOp::BitAnd => "bitand",
+            mir::BinOp::BitOr => "bitor",
+            mir::BinOp::BitXor => "bitxor",
+            _ => unreachable!("{:?} is not a bitwise operation", op),
+        };
+        let function_name = format!("builtin${}${}", op_name, ty);
+        if !self.bitwise_op_functions.borrow().contains_key(&(op, ty)) {
+            let left = vir::LocalVar::new("left", vir::Type::Int);
+            let right = vir::LocalVar::new("right", vir::Type::Int);
+            let result = vir::LocalVar::new("__result", vir::Type::Int);
+            let (left, right, result): (vir::Expr, vir::Expr, vir::Expr) =
+                (left.into(), right.into(), result.into());
+            let implies = |guard: vir::Expr, expr: vir::Expr| vir::Expr::implies(guard, expr);
+            let is_zero = |expr: &vir::Expr| vir::Expr::eq_cmp(expr.clone(), 0.into());
+            let are_equal = vir::Expr::eq_cmp(left.clone(), right.clone());
+            let are_non_negative = vir::Expr::and(
+                vir::Expr::ge_cmp(left.clone(), 0.into()),
+                vir::Expr::ge_cmp(right.clone(), 0.into()),
+            );
+            let result_is = |expr: vir::Expr| vir::Expr::eq_cmp(result.clone(), expr);
+            let mut posts = match op {
+                mir::BinOp::BitAnd => vec![
+                    implies(are_equal, result_is(left.clone())),
+                    implies(is_zero(&left), result_is(0.into())),
+                    implies(is_zero(&right), result_is(0.into())),
+                    implies(are_non_negative, vir::Expr::and(
+                        vir::Expr::ge_cmp(result.clone(), 0.into()),
+                        vir::Expr::and(
+                            vir::Expr::le_cmp(result.clone(), left.clone()),
+                            vir::Expr::le_cmp(result.clone(), right.clone()),
+                        ),
+                    )),
+                ],
+                mir::BinOp::BitOr => vec![
+                    implies(are_equal, result_is(left.clone())),
+                    implies(is_zero(&left), result_is(right.clone())),
+                    implies(is_zero(&right), result_is(left.clone())),
+                    implies(are_non_negative, vir::Expr::and(
+                        vir::Expr::and(
+                            vir::Expr::ge_cmp(result.clone(), left.clone()),
+                            vir::Expr::ge_cmp(result.clone(), right.clone()),
+                        ),
+                        vir::Expr::le_cmp(
+                            result.clone(),
+                            vir::Expr::add(left.clone(), right.clone()),
+                        ),
+                    )),
+                ],
+                _ => vec![
+                    implies(are_equal, result_is(0.into())),
+                    implies(is_zero(&left), result_is(right.clone())),
+                    implies(is_zero(&right), result_is(left.clone())),
+                    implies(are_non_negative, vir::Expr::and(
+                        vir::Expr::ge_cmp(result.clone(), 0.into()),
+                        vir::Expr::le_cmp(
+                            result.clone(),
+                            vir::Expr::add(left.clone(), right.clone()),
+                        ),
+                    )),
+                ],
+            };
+            // The operations preserve the range of the integer type.
+            let operand_bounds = self.encode_type_bounds(&left, ty)
+                .into_iter()
+                .chain(self.encode_type_bounds(&right, ty))
+                .conjoin();
+            for bound in self.encode_type_bounds(&result, ty) {
+                posts.push(implies(operand_bounds.clone(), bound));
+            }
+            let function = vir::Function {
+                name: function_name.clone(),
+                formal_args: vec![
+                    vir::LocalVar::new("left", vir::Type::Int),
+                    vir::LocalVar::new("right", vir::Type::Int),
+                ],
+                return_type: vir::Type::Int,
+                pres: vec![],
+                posts,
+                body: None,
+            };
+            self.bitwise_op_functions.borrow_mut().insert((op, ty), function);
+        }
+        Ok(function_name)
+    }
+
+    /// Encode the application of the function that returns the length of
+    /// `slice`, a place of the slice type `slice_ty`. Together with the
+    /// lookup functions, the length is the snapshot of the slice, which is
+    /// encoded as an abstract predicate.
+    pub fn encode_slice_len_func_app(&self, slice: vir::Expr, slice_ty: ty::Ty<'tcx>)
+        -> EncodingResult<vir::Expr>
+    {
+        trace!("encode_slice_len_func_app(slice={}, slice_ty={:?})", slice, slice_ty);
+        let predicate_name = self.encode_type_predicate_use(slice_ty)?;
+        let function_name = format!("builtin$len${}", predicate_name);
+        let self_var = vir::LocalVar::new("self", vir::Type::TypedRef(predicate_name.clone()));
+        if !self.slice_functions.borrow().contains_key(&function_name) {
+            let result = vir::LocalVar::new("__result", vir::Type::Int);
+            let mut posts = vec![vir::Expr::ge_cmp(result.clone().into(), 0.into())];
+            // The length of an array is given by its type.
+            if let ty::TyKind::Array(..) = slice_ty.kind() {
+                posts.push(vir::Expr::eq_cmp(result.into(), self.encode_array_len_expr(slice_ty)?));
+            }
+            let function = vir::Function {
+                name: function_name.clone(),
+                formal_args: vec![self_var.clone()],
+                return_type: vir::Type::Int,
+                pres: vec![vir::Expr::predicate_access_predicate(
+                    predicate_name,
+                    self_var.clone().into(),
+                    vir::PermAmount::Read,
+                )],
+                posts,
+                body: None,
+            };
+            self.slice_functions.borrow_mut().insert(function_name.clone(), function);
+        }
+        Ok(vir::Expr::func_app(
+            function_name,
+            vec![slice],
+            vec![self_var],
+            vir::Type::Int,
+            vir::Position::default(),
+        ))
+    }
+
+    /// Encode the application of a function that reads the value of
+    /// `slice[index].f1.f2...`, where `slice` is a place of the slice type
+    /// `slice_ty`, `fields` are the names of the (nested) struct fields that
+    /// are read from the element and `value_ty` is the primitive type of the
+    /// value.
+    pub fn encode_slice_lookup_func_app(
+        &self,
+        slice: vir::Expr,
+        slice_ty: ty::Ty<'tcx>,
+        index: vir::Expr,
+        fields: &[String],
+        value_ty: ty::Ty<'tcx>,
+    ) -> EncodingResult<vir::Expr> {
+        trace!(
+            "encode_slice_lookup_func_app(slice={}, slice_ty={:?}, index={}, fields={:?})",
+            slice, slice_ty, index, fields
+        );
+        let predicate_name = self.encode_type_predicate_use(slice_ty)?;
+        let mut function_name = format!("builtin$lookup${}", predicate_name);
+        for field in fields {
+            function_name.push_str("$");
+            function_name.push_str(field);
+        }
+        let return_type = self.encode_value_type(value_ty)?;
+        let self_var = vir::LocalVar::new("self", vir::Type::TypedRef(predicate_name.clone()));
+        let index_var = vir::LocalVar::new("index", vir::Type::Int);
+        if !self.slice_functions.borrow().contains_key(&function_name) {
+            let len = self.encode_slice_len_func_app(self_var.clone().into(), slice_ty)?;
+            let index_expr: vir::Expr = index_var.clone().into();
+            let result = vir::LocalVar::new("__result", return_type.clone());
+            let function = vir::Function {
+                name: function_name.clone(),
+                formal_args: vec![self_var.clone(), index_var.clone()],
+                return_type: return_type.clone(),
+                pres: vec![
+                    vir::Expr::predicate_access_predicate(
+                        predicate_name,
+                        self_var.clone().into(),
+                        vir::PermAmount::Read,
+                    ),
+                    vir::Expr::le_cmp(0.into(), index_expr.clone()),
+                    vir::Expr::lt_cmp(index_expr, len),
+                ],
+                posts: self.encode_type_bounds(&result.into(), value_ty),
+                body: None,
+            };
+            self.slice_functions.borrow_mut().insert(function_name.clone(), function);
+        }
+        Ok(vir::Expr::func_app(
+            function_name,
+            vec![slice, index],
+            vec![self_var, index_var],
+            return_type,
+            vir::Position::default(),
+        ))
+    }
+
+    /// Encode that the slice `prefix` is a prefix of the slice `slice`, where
+    /// both are of type `slice_ty`. String slices are compared byte by byte.
+    pub fn encode_slice_starts_with(
+        &self,
+        slice: vir::Expr,
+        prefix: vir::Expr,
+        slice_ty: ty::Ty<'tcx>,
+    ) -> EncodingResult<vir::Expr> {
+        let elem_ty = match slice_ty.kind() {
+            ty::TyKind::Slice(elem_ty) => *elem_ty,
+            ty::TyKind::Str => self.env().tcx().types.u8,
+            _ => unreachable!("{:?} is not a slice", slice_ty),
+        };
+        match elem_ty.kind() {
+            ty::TyKind::Bool
+            | ty::TyKind::Int(_)
+            | ty::TyKind::Uint(_) => {}
+            _ => return Err(EncodingError::unsupported(
+                "only slices of primitive types can be compared in pure code"
+            )),
+        }
+        let index_var = vir::LocalVar::new("index", vir::Type::Int);
+        let index: vir::Expr = index_var.clone().into();
+        let slice_elem = self.encode_slice_lookup_func_app(
+            slice.clone(), slice_ty, index.clone(), &[], elem_ty
+        )?;
+        let prefix_elem = self.encode_slice_lookup_func_app(
+            prefix.clone(), slice_ty, index.clone(), &[], elem_ty
+        )?;
+        let prefix_len = self.encode_slice_len_func_app(prefix, slice_ty)?;
+        let slice_len = self.encode_slice_len_func_app(slice, slice_ty)?;
+        Ok(vir::Expr::and(
+            vir::Expr::le_cmp(prefix_len.clone(), slice_len),
+            vir::Expr::forall(
+                vec![index_var],
+                vec![vir::Trigger::new(vec![slice_elem.clone()])],
+                vir::Expr::implies(
+                    vir::Expr::and(
+                        vir::Expr::le_cmp(0.into(), index.clone()),
+                        vir::Expr::lt_cmp(index, prefix_len),
+                    ),
+                    vir::Expr::eq_cmp(slice_elem, prefix_elem),
+                ),
+            ),
+        ))
+    }
+
     pub fn encode_procedure(&self, def_id: ProcedureDefId) -> SpannedEncodingResult<vir::CfgMethod> {
         debug!("encode_procedure({:?})", def_id);
         assert!(
@@ -837,7 +1414,10 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             self.closures_collector.borrow_mut().collect(self.env, def_id.expect_local());
             let procedure = self.env.get_procedure(def_id);
             let proc_encoder = ProcedureEncoder::new(self, &procedure)?;
-            let method = match proc_encoder.encode() {
+            self.const_param_owners.borrow_mut().push(def_id);
+            let encoding_result = proc_encoder.encode();
+            self.const_param_owners.borrow_mut().pop();
+            let method = match encoding_result {
                 Ok(result) => result,
                 Err(error) => {
                     self.register_encoding_error(error);
@@ -863,6 +1443,36 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         Ok(self.procedures.borrow()[&def_id].clone())
     }
 
+    /// Encodes the check that the contract of `def_id` refines the contract
+    /// of the trait method that it implements. This is needed for trusted and
+    /// pure implementations, because `encode_procedure` performs the check
+    /// only while it verifies the body of a procedure.
+    pub fn encode_refinement_check(&self, def_id: ProcedureDefId) -> SpannedEncodingResult<()> {
+        debug!("encode_refinement_check({:?})", def_id);
+        let tcx = self.env.tcx();
+        let trait_method_def_id = tcx.impl_of_method(def_id)
+            .and_then(|impl_id| tcx.trait_id_of_impl(impl_id))
+            .and_then(|trait_id| self.env.get_assoc_item(trait_id, tcx.item_name(def_id)))
+            .map(|assoc_item| assoc_item.def_id);
+        let trait_method_def_id = match trait_method_def_id {
+            Some(trait_method_def_id) => trait_method_def_id,
+            None => return Ok(()),
+        };
+        // Without own specifications the implementation inherits the contract
+        // of the trait, which trivially refines it.
+        let has_own_specs = self.get_procedure_specs(def_id).map_or(false, |specs| {
+            !specs.pres.is_empty() || !specs.posts.is_empty()
+        });
+        if !has_own_specs || self.procedures.borrow().contains_key(&def_id) {
+            return Ok(());
+        }
+        let procedure = self.env.get_procedure(def_id);
+        let method = RefinementCheckEncoder::new(self, &procedure, trait_method_def_id).encode()?;
+        self.log_vir_program_before_viper(method.to_string());
+        self.procedures.borrow_mut().insert(def_id, method);
+        Ok(())
+    }
+
     pub fn encode_value_or_ref_type(&self, ty: ty::Ty<'tcx>)
         -> EncodingResult<vir::Type>
     {
@@ -897,11 +1507,293 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         type_encoder.encode_type()
     }
 
+    /// The number of elements of the array type `ty`. Fails if the length
+    /// depends on const generic parameters, see `encode_array_len_expr`.
+    pub fn get_array_len(&self, ty: ty::Ty<'tcx>) -> EncodingResult<u64> {
+        self.eval_array_len(ty).ok_or_else(|| EncodingError::unsupported(format!(
+            "the array type '{:?}' has a length that depends on const generic parameters",
+            ty,
+        )))
+    }
+
+    /// The number of elements of the array type `ty`, if it can be evaluated.
+    pub fn eval_array_len(&self, ty: ty::Ty<'tcx>) -> Option<u64> {
+        let type_encoder = TypeEncoder::new(self, ty);
+        type_encoder.get_array_len()
+    }
+
+    /// Encode the number of elements of the array type `ty`, which may be
+    /// computed from const generic parameters, like `[u8; N * 2]`.
+    pub fn encode_array_len_expr(&self, ty: ty::Ty<'tcx>) -> EncodingResult<vir::Expr> {
+        match ty.kind() {
+            ty::TyKind::Array(_, size) => self.encode_symbolic_const(size),
+            ref x => unreachable!("{:?}", x),
+        }
+    }
+
+    /// Encode the value of the constant `value`. A constant that can not be
+    /// evaluated because it depends on const generic parameters is encoded
+    /// from its expression, which may use integer literals, constants, const
+    /// generic parameters, arithmetic operators and calls of `#[pure] const fn`.
+    pub fn encode_symbolic_const(&self, value: &ty::Const<'tcx>) -> EncodingResult<vir::Expr> {
+        let tcx = self.env().tcx();
+        if let ty::ConstKind::Unevaluated(def, substs, None) = value.val {
+            let param_env = tcx.param_env(def.did);
+            let is_evaluable = tcx.const_eval_resolve(param_env, def, substs, None, None).is_ok();
+            if let (false, Some(local_def_id)) = (is_evaluable, def.did.as_local()) {
+                let hir_id = tcx.hir().local_def_id_to_hir_id(local_def_id);
+                let body = tcx.hir().body(tcx.hir().body_owned_by(hir_id));
+                return self.encode_symbolic_const_expr(&body.value, substs);
+            }
+        }
+        self.encode_const_expr(value.ty, &value.val)
+    }
+
+    /// Encode the expression `expr` of a constant whose generic arguments are
+    /// `substs`.
+    fn encode_symbolic_const_expr(
+        &self,
+        expr: &hir::Expr<'tcx>,
+        substs: ty::subst::SubstsRef<'tcx>,
+    ) -> EncodingResult<vir::Expr> {
+        let tcx = self.env().tcx();
+        match expr.kind {
+            hir::ExprKind::Block(block, _) if block.stmts.is_empty() && block.expr.is_some() => {
+                self.encode_symbolic_const_expr(block.expr.unwrap(), substs)
+            }
+            hir::ExprKind::Lit(ref lit) => match lit.node {
+                ast::LitKind::Int(value, _) => Ok(value.into()),
+                _ => Err(EncodingError::unsupported(format!(
+                    "the literal '{:?}' in a constant expression is not supported",
+                    lit.node,
+                ))),
+            },
+            hir::ExprKind::Path(hir::QPath::Resolved(None, path)) => match path.res {
+                hir::def::Res::Def(hir::def::DefKind::ConstParam, param_def_id) => {
+                    let owner = tcx.parent(param_def_id).unwrap();
+                    let index = tcx.generics_of(owner).param_def_id_to_index[&param_def_id];
+                    self.encode_symbolic_const(substs.const_at(index as usize))
+                }
+                hir::def::Res::Def(hir::def::DefKind::Const, const_def_id) => {
+                    let value = tcx.const_eval_poly(const_def_id).map_err(|_| {
+                        EncodingError::unsupported(format!(
+                            "the constant '{}' could not be evaluated",
+                            tcx.def_path_str(const_def_id),
+                        ))
+                    })?;
+                    self.encode_const_expr(tcx.type_of(const_def_id), &ty::ConstKind::Value(value))
+                }
+                _ => Err(EncodingError::unsupported(format!(
+                    "the path '{:?}' in a constant expression is not supported",
+                    path.res,
+                ))),
+            },
+            hir::ExprKind::Binary(op, left, right) => {
+                let left = self.encode_symbolic_const_expr(left, substs)?;
+                let right = self.encode_symbolic_const_expr(right, substs)?;
+                Ok(match op.node {
+                    hir::BinOpKind::Add => vir::Expr::add(left, right),
+                    hir::BinOpKind::Sub => vir::Expr::sub(left, right),
+                    hir::BinOpKind::Mul => vir::Expr::mul(left, right),
+                    hir::BinOpKind::Div => vir::Expr::div(left, right),
+                    hir::BinOpKind::Rem => vir::Expr::modulo(left, right),
+                    _ => return Err(EncodingError::unsupported(format!(
+                        "the operator '{:?}' in a constant expression is not supported",
+                        op.node,
+                    ))),
+                })
+            }
+            // The value of a call of a pure `const fn` is the application of its
+            // pure function, which is the value computed by the const evaluator.
+            hir::ExprKind::Call(callee, args) => {
+                let fn_def_id = match callee.kind {
+                    hir::ExprKind::Path(hir::QPath::Resolved(None, path)) => match path.res {
+                        hir::def::Res::Def(hir::def::DefKind::Fn, def_id)
+                        | hir::def::Res::Def(hir::def::DefKind::AssocFn, def_id)
+                            if tcx.is_const_fn_raw(def_id) && self.is_pure(def_id) => Some(def_id),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                let fn_def_id = fn_def_id.ok_or_else(|| EncodingError::unsupported(
+                    "only calls of #[pure] const functions are supported in constant expressions"
+                ))?;
+                let (function_name, return_type) = self.encode_pure_function_use(fn_def_id)?;
+                let fn_sig = tcx.fn_sig(fn_def_id).skip_binder();
+                let mut encoded_args = vec![];
+                let mut formal_args = vec![];
+                for (i, (arg, arg_ty)) in args.iter().zip(fn_sig.inputs()).enumerate() {
+                    encoded_args.push(self.encode_symbolic_const_expr(arg, substs)?);
+                    formal_args.push(vir::LocalVar::new(
+                        format!("x{}", i),
+                        self.encode_value_type(arg_ty)?,
+                    ));
+                }
+                Ok(vir::Expr::func_app(
+                    function_name,
+                    encoded_args,
+                    formal_args,
+                    return_type,
+                    vir::Position::default(),
+                ))
+            }
+            _ => Err(EncodingError::unsupported(format!(
+                "the constant expression '{:?}' is not supported",
+                expr.kind,
+            ))),
+        }
+    }
+
+    /// The const generic parameters of `def_id`, including the ones of its
+    /// parent items, with their types.
+    fn const_params(&self, def_id: DefId) -> Vec<(ty::ParamConst, ty::Ty<'tcx>)> {
+        ty::List::identity_for_item(self.env.tcx(), def_id)
+            .iter()
+            .filter_map(|arg| match arg.unpack() {
+                ty::subst::GenericArgKind::Const(value) => match value.val {
+                    ty::ConstKind::Param(param) => Some((param, value.ty)),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Encode the const generic arguments `substs` of a call of `def_id`, by
+    /// the parameter that they replace.
+    pub fn encode_const_param_frame(&self, def_id: DefId, substs: &[ty::subst::GenericArg<'tcx>])
+        -> EncodingResult<HashMap<ty::ParamConst, vir::Expr>>
+    {
+        let own_substs = ty::List::identity_for_item(self.env.tcx(), def_id);
+        let mut frame = HashMap::new();
+        for (kind1, kind2) in own_substs.iter().zip(substs.iter()) {
+            if let (
+                ty::subst::GenericArgKind::Const(param_const),
+                ty::subst::GenericArgKind::Const(arg_const),
+            ) = (kind1.unpack(), kind2.unpack()) {
+                if let ty::ConstKind::Param(param) = param_const.val {
+                    frame.insert(param, self.encode_const_expr(arg_const.ty, &arg_const.val)?);
+                }
+            }
+        }
+        Ok(frame)
+    }
+
+    /// The formal arguments of the pure function `def_id` that hold the
+    /// values of its const generic parameters, after the ones of the MIR.
+    pub fn encode_const_param_formal_args(&self, def_id: DefId) -> EncodingResult<Vec<vir::LocalVar>> {
+        self.const_params(def_id)
+            .into_iter()
+            .map(|(param, ty)| Ok(vir::LocalVar::new(
+                format!("_const${}", param.name),
+                self.encode_value_type(ty)?,
+            )))
+            .collect()
+    }
+
+    /// The values of the const generic parameters of the called pure function
+    /// `def_id`, which are passed after the arguments of the MIR.
+    pub fn encode_const_param_args(&self, def_id: DefId) -> EncodingResult<Vec<vir::Expr>> {
+        self.const_params(def_id)
+            .into_iter()
+            .map(|(param, ty)| self.encode_const_param_func_app(param, ty))
+            .collect()
+    }
+
+    /// Encode the value of the const generic parameter `param` of type `ty`.
+    /// The parameter of a called function is replaced by the argument of the
+    /// call, and the one of a pure function by its formal argument.
+    /// Otherwise, the parameter belongs to the procedure that is being
+    /// encoded, and its value is the application of a function without
+    /// arguments that is specific to the parameter of that procedure. The
+    /// function is uninterpreted, so the value is only known to be within the
+    /// bounds of `ty`.
+    pub fn encode_const_param_func_app(&self, param: ty::ParamConst, ty: ty::Ty<'tcx>)
+        -> EncodingResult<vir::Expr>
+    {
+        // FIXME: like `typaram_repl`, this does not distinguish the parameters
+        // of different items that have the same name and index.
+        let value = self.const_param_repl
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|frame| frame.get(&param).cloned());
+        if let Some(value) = value {
+            return Ok(value);
+        }
+        let tcx = self.env().tcx();
+        let param_def = self.const_param_owners.borrow().last().and_then(|&owner| {
+            let generics = tcx.generics_of(owner);
+            let index = param.index as usize;
+            if index < generics.count() {
+                Some(generics.param_at(index, tcx))
+            } else {
+                None
+            }
+        });
+        let param_def_id = match param_def {
+            Some(param_def) if param_def.name == param.name => param_def.def_id,
+            _ => return Err(EncodingError::unsupported(format!(
+                "the const generic parameter '{}' is not supported here",
+                param.name,
+            ))),
+        };
+        let return_type = self.encode_value_type(ty)?;
+        let function_name = format!(
+            "const_param${}${}",
+            self.encode_item_name(param_def_id),
+            self.encode_type_predicate_use(ty)?,
+        );
+        if !self.const_param_functions.borrow().contains_key(&function_name) {
+            let result = vir::LocalVar::new("__result", return_type.clone());
+            let function = vir::Function {
+                name: function_name.clone(),
+                formal_args: vec![],
+                return_type: return_type.clone(),
+                pres: vec![],
+                posts: self.encode_type_bounds(&result.into(), ty),
+                body: None,
+            };
+            self.const_param_functions.borrow_mut().insert(function_name.clone(), function);
+        }
+        Ok(vir::Expr::func_app(
+            function_name,
+            vec![],
+            vec![],
+            return_type,
+            vir::Position::default(),
+        ))
+    }
+
     pub fn encode_type_bounds(&self, var: &vir::Expr, ty: ty::Ty<'tcx>) -> Vec<vir::Expr> {
         let type_encoder = TypeEncoder::new(self, ty);
         type_encoder.encode_bounds(var)
     }
 
+    /// Encode the `requires` and `ensures` clauses, with their spans, that the specification
+    /// entailments of the precondition `assertion` attach to a call of a closure of type
+    /// `closure_ty`. See `spec_encoder::encode_closure_call_contract`.
+    pub fn encode_closure_call_contract(
+        &self,
+        assertion: &typed::Assertion<'tcx>,
+        closure_ty: ty::Ty<'tcx>,
+        mir: &mir::Body<'tcx>,
+        target_args: &[vir::Expr],
+        call_args: &[vir::Expr],
+        call_result: &vir::Expr,
+    ) -> SpannedEncodingResult<(Vec<(vir::Expr, MultiSpan)>, Vec<(vir::Expr, MultiSpan)>)> {
+        trace!("encode_closure_call_contract {:?} {:?}", assertion, closure_ty);
+        encode_closure_call_contract(
+            self,
+            assertion,
+            closure_ty,
+            mir,
+            target_args,
+            call_args,
+            call_result,
+        )
+    }
+
     pub fn encode_assertion(
         &self,
         assertion: &typed::Assertion<'tcx>,
@@ -969,7 +1861,7 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
     pub fn encode_snapshot(&self, ty: ty::Ty<'tcx>)
         -> EncodingResult<Box<Snapshot>>
     {
-        let ty = self.dereference_ty(ty);
+        let ty = self.dereference_ty(self.reveal_opaque_type(ty));
         let predicate_name = self.encode_type_predicate_use(ty)
             .expect("failed to encode unsupported type");
         if !self.snapshots.borrow().contains_key(&predicate_name) {
@@ -978,25 +1870,49 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
                 predicate_name.to_string()
             );
             let snapshot = encoder.encode()?;
+            // A box shares the snapshot type of its content, which must stay
+            // associated with the predicate of the content.
             self.type_snapshots
                 .borrow_mut()
-                .insert(
-                    snapshot.get_type().name().to_string(),
-                    predicate_name.to_string()
-                );
+                .entry(snapshot.get_type().name().to_string())
+                .or_insert_with(|| predicate_name.to_string());
             self.snapshots
                 .borrow_mut()
                 .insert(predicate_name.to_string(), box snapshot);
             if config::enable_purification_optimization() {
                 if let Some(domain) = &self.snapshots.borrow()[&predicate_name].snap_domain {
-                    self.encode_axiomatized_pure_function(&domain.equals_func);
-                    self.encode_axiomatized_pure_function(&domain.not_equals_func);
+                    self.encode_axiomatized_pure_function(&domain.equals_func)?;
+                    self.encode_axiomatized_pure_function(&domain.not_equals_func)?;
                 }
             }
         }
         Ok(self.snapshots.borrow()[&predicate_name].clone())
     }
 
+    /// Encode `lhs === rhs`, where `lhs` and `rhs` are either values or
+    /// places of type `ty`. The snapshots of references and boxes are the
+    /// snapshots of their targets, so addresses are never compared.
+    pub fn encode_snapshot_equality(
+        &self,
+        lhs: vir::Expr,
+        rhs: vir::Expr,
+        ty: ty::Ty<'tcx>,
+    ) -> EncodingResult<vir::Expr> {
+        let snapshot = self.encode_snapshot(ty)?;
+        if !snapshot.supports_equality() {
+            return Err(EncodingError::unsupported(format!(
+                "snapshot equality of values of type '{}' is not supported, because their \
+                snapshot does not only depend on their content",
+                self.dereference_ty(ty),
+            )));
+        }
+        let encode_snapshot = |expr: vir::Expr| match expr.get_type() {
+            vir::Type::TypedRef(_) => snapshot.snap_call(expr),
+            _ => expr,
+        };
+        Ok(vir::Expr::eq_cmp(encode_snapshot(lhs), encode_snapshot(rhs)))
+    }
+
     fn dereference_ty(&self, ty: ty::Ty<'tcx>) -> ty::Ty<'tcx> {
         match ty.kind() {
             ty::TyKind::Ref(_, ref val_ty, _) => self.dereference_ty(val_ty),
@@ -1006,7 +1922,18 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
 
     /// Checks whether the given type implements structural equality
     /// by either being a primitive type or by deriving the Eq trait.
+    /// Structs and enums that derive only `PartialEq` also have a structural
+    /// equality if the types of all their fields do, in which case `==` is
+    /// encoded as the equality of their snapshots.
     pub fn has_structural_eq_impl(&self, ty: ty::Ty<'tcx>) -> bool {
+        self.has_structural_eq_impl_rec(ty, &mut vec![])
+    }
+
+    fn has_structural_eq_impl_rec(
+        &self,
+        ty: ty::Ty<'tcx>,
+        visited: &mut Vec<ty::Ty<'tcx>>,
+    ) -> bool {
         let ty = self.dereference_ty(ty);
         match ty.kind() {
             ty::TyKind::Bool
@@ -1016,8 +1943,30 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             | ty::TyKind::Tuple(_)
             | ty::TyKind::Never
             | ty::TyKind::Param(_) => true,
-            ty::TyKind::Adt(_, _) => {
-                self.env().tcx().has_structural_eq_impls(ty)
+            ty::TyKind::Adt(adt_def, substs) => {
+                let tcx = self.env().tcx();
+                if tcx.has_structural_eq_impls(ty) || visited.contains(&ty) {
+                    return true;
+                }
+                // `Rc` and `Arc` compare their content, like `Box`.
+                if let Some(pointee_ty) = self.get_rc_pointee_ty(ty) {
+                    return self.has_structural_eq_impl_rec(pointee_ty, visited);
+                }
+                // `Box` compares its content, without a derived implementation.
+                if adt_def.is_box() {
+                    return self.has_structural_eq_impl_rec(substs.type_at(0), visited);
+                }
+                let is_derived = tcx.lang_items().structural_peq_trait()
+                    .map_or(false, |trait_def_id| self.env().type_implements_trait(ty, trait_def_id));
+                if !is_derived {
+                    return false;
+                }
+                visited.push(ty);
+                let fields_have_structural_eq = adt_def.all_fields().all(|field| {
+                    self.has_structural_eq_impl_rec(field.ty(tcx, substs), visited)
+                });
+                visited.pop();
+                fields_have_structural_eq
             }
             _ => false,
         }
@@ -1112,6 +2061,9 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
     ) -> EncodingResult<vir::Expr> {
         trace!("encode_const_expr {:?}", value);
         let opt_scalar_value = match value {
+            ty::ConstKind::Param(param) => {
+                return self.encode_const_param_func_app(*param, ty);
+            }
             ty::ConstKind::Value(ref const_value) => {
                 const_value
                     .try_to_scalar()
@@ -1149,10 +2101,28 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             ty::TyKind::Uint(ast::UintTy::U64) => scalar_value.to_u64().unwrap().into(),
             ty::TyKind::Uint(ast::UintTy::U128) => scalar_value.to_u128().unwrap().into(),
             ty::TyKind::Uint(ast::UintTy::Usize) => scalar_value.to_machine_usize(&self.env().tcx()).unwrap().into(),
+            ty::TyKind::Float(ast::FloatTy::F32) => {
+                let bits = scalar_value.to_bits(rustc_target::abi::Size::from_bits(32)).unwrap();
+                self.encode_float_literal(f32::from_bits(bits as u32) as f64)
+            }
+            ty::TyKind::Float(ast::FloatTy::F64) => {
+                let bits = scalar_value.to_bits(rustc_target::abi::Size::from_bits(64)).unwrap();
+                self.encode_float_literal(f64::from_bits(bits as u64))
+            }
             ty::TyKind::FnDef(def_id, _) => {
-                self.encode_spec_funcs(*def_id)?;
+                // Constructors of tuple structs and enum variants have no
+                // contract, and no MIR body from which to encode it.
+                if !self.env().tcx().is_constructor(*def_id) {
+                    self.encode_spec_funcs(*def_id)?;
+                }
                 vir::Expr::Const(vir::Const::FnPtr, vir::Position::default())
             }
+            ty::TyKind::Ref(..) | ty::TyKind::RawPtr(..) => {
+                return Err(EncodingError::unsupported(
+                    "constants of reference types, such as the accesses of statics, \
+                    are not supported here"
+                ));
+            }
             ref x => unimplemented!("{:?}", x),
         };
         debug!("encode_const_expr {:?} --> {:?}", value, expr);
@@ -1190,7 +2160,9 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
                 .map(|s| s.name.to_ident_string())
                 .unwrap_or(self.env.get_item_name(def_id))
         ));
-        self.intern_viper_identifier(full_name, short_name)
+        let name = self.intern_viper_identifier(full_name, short_name);
+        self.viper_item_names.borrow_mut().insert(name.clone(), def_id);
+        name
     }
 
     pub fn encode_invariant_func_app(
@@ -1227,9 +2199,7 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
     pub fn encode_pure_function_body(&self, proc_def_id: ProcedureDefId)
         -> SpannedEncodingResult<vir::Expr>
     {
-        let mir_span = self.env.tcx().def_span(proc_def_id);
-        let substs_key = self.type_substitution_key().with_span(mir_span)?;
-        let key = (proc_def_id, substs_key);
+        let key = self.pure_function_instance(proc_def_id);
         if !self.pure_function_bodies.borrow().contains_key(&key) {
             let procedure = self.env.get_procedure(proc_def_id);
             let pure_function_encoder = PureFunctionEncoder::new(
@@ -1268,16 +2238,31 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             }
             tymap_stack.push(tymap);
         }
+        // The const generic parameters are the formal arguments of the function.
+        let const_param_formal_args = self.encode_const_param_formal_args(proc_def_id)
+            .with_span(self.env.get_item_span(proc_def_id))
+            .run_if_err(|| { self.typaram_repl.borrow_mut().pop(); })?;
+        let const_param_frame = self.const_params(proc_def_id)
+            .into_iter()
+            .zip(const_param_formal_args)
+            .map(|((param, _), formal_arg)| (param, vir::Expr::local(formal_arg)))
+            .collect();
+        self.const_param_repl.borrow_mut().push(const_param_frame);
         let cleanup = || {
             // FIXME: this is a hack to support generics. See issue #187.
             let mut tymap_stack = self.typaram_repl.borrow_mut();
             tymap_stack.pop();
+            self.const_param_repl.borrow_mut().pop();
         };
 
-        // FIXME: Using substitutions as a key is most likely wrong.
-        let mir_span = self.env.tcx().def_span(proc_def_id);
-        let substs_key = self.type_substitution_key().with_span(mir_span).run_if_err(cleanup)?;
-        let key = (proc_def_id, substs_key);
+        // The Viper function of an imported pure function is declared in the
+        // imported file.
+        if self.encode_viper_import(proc_def_id).run_if_err(cleanup)?.is_some() {
+            cleanup();
+            return Ok(());
+        }
+
+        let key = self.pure_function_instance(proc_def_id);
 
         if !self.pure_functions.borrow().contains_key(&key) {
             trace!("not encoded: {:?}", key);
@@ -1285,7 +2270,14 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             let procedure = self.env.get_procedure(wrapper_def_id);
             let pure_function_encoder =
                 PureFunctionEncoder::new(self, proc_def_id, procedure.get_mir(), false);
-            let function = if self.is_trusted(proc_def_id) {
+            let predicate_body = self.get_procedure_specs(proc_def_id)
+                .and_then(|spec| spec.predicate_body);
+            let function = if let Some(predicate_body) = predicate_body {
+                pure_function_encoder.encode_predicate_function(&predicate_body)
+                    .run_if_err(cleanup)?
+            } else if self.is_trusted(proc_def_id)
+                || self.get_pure_signature_def_id(proc_def_id).is_some()
+            {
                 pure_function_encoder.encode_bodyless_function()
                     .run_if_err(cleanup)?
             } else {
@@ -1304,7 +2296,9 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
                     let ty = local_decl.ty;
                     self.encode_snapshot(ty).with_span(procedure.get_span()).run_if_err(cleanup)?;
                 }
-                self.encode_axiomatized_pure_function(&function);
+                self.encode_axiomatized_pure_function(&function)
+                    .with_span(procedure.get_span())
+                    .run_if_err(cleanup)?;
             }
 
             self.log_vir_program_before_viper(function.to_string());
@@ -1457,12 +2451,40 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
 
         self.queue_pure_function_encoding(proc_def_id);
 
+        let function_name = match self.encode_viper_import(proc_def_id)? {
+            Some(imported_function_name) => imported_function_name,
+            None => pure_function_encoder.encode_function_name(),
+        };
         Ok((
-            pure_function_encoder.encode_function_name(),
+            function_name,
             pure_function_encoder.encode_function_return_type()?,
         ))
     }
 
+    /// The name of the Viper function that implements a pure function
+    /// imported with `#[viper_import]`, if any. The Viper file that declares
+    /// it, given relative to the directory of the crate root, is imported the
+    /// first time that it is used.
+    fn encode_viper_import(&self, proc_def_id: ProcedureDefId) -> SpannedEncodingResult<Option<String>> {
+        let attrs = self.env.tcx().get_attrs(proc_def_id);
+        let (file, function_name) = match (
+            read_prusti_attr("viper_import_file", attrs),
+            read_prusti_attr("viper_import_name", attrs),
+        ) {
+            (Some(file), Some(function_name)) => (file, function_name),
+            _ => return Ok(None),
+        };
+        let path = self.env.source_path().with_file_name(file);
+        if !self.viper_imports.borrow().contains_key(&path) {
+            let source = fs::read_to_string(&path).map_err(|error| SpannedEncodingError::incorrect(
+                format!("cannot read the imported Viper file '{}': {}", path.display(), error),
+                self.env.get_item_span(proc_def_id),
+            ))?;
+            self.viper_imports.borrow_mut().insert(path, source);
+        }
+        Ok(Some(function_name))
+    }
+
     /// Encode the use (call) of either a comparison (equality or disequality)
     /// returning the name of the encoded function and its type.
     /// If the comparison is not supported, a stub function will be encoded
@@ -1502,11 +2524,10 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         let body = self.env.external_mir(proc_def_id);
         let stub_encoder = StubFunctionEncoder::new(self, proc_def_id, &body);
 
-        let substs_key = self.type_substitution_key().with_span(body.span)?;
-        let key = (proc_def_id, substs_key);
+        let key = self.pure_function_instance(proc_def_id);
 
         // If we haven't seen this particular stub before, generate and insert it.
-        if !self.pure_functions.borrow().contains_key(&key) {
+        if !self.stub_pure_functions.borrow().contains_key(&key) {
             let function = stub_encoder.encode_function()?;
 
             self.log_vir_program_before_viper(function.to_string());
@@ -1520,14 +2541,31 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
     }
 
     pub fn queue_procedure_encoding(&self, proc_def_id: ProcedureDefId) {
-        self.encoding_queue
-            .borrow_mut()
-            .push((proc_def_id, Vec::new()));
+        if self.is_pure(proc_def_id) {
+            self.queue_pure_function_encoding(proc_def_id);
+        } else {
+            self.encoding_queue
+                .borrow_mut()
+                .push((proc_def_id, Vec::new()));
+        }
     }
 
     pub fn queue_pure_function_encoding(&self, proc_def_id: ProcedureDefId) {
-        let substs = self.current_tymap().into_iter().collect();
-        self.encoding_queue.borrow_mut().push((proc_def_id, substs));
+        let key = self.pure_function_instance(proc_def_id);
+        if self.queued_pure_functions.borrow_mut().insert(key) {
+            let substs = self.current_tymap().into_iter().collect();
+            self.encoding_queue.borrow_mut().push((proc_def_id, substs));
+        }
+    }
+
+    /// The instantiation of the pure function `proc_def_id` under the current
+    /// type substitution. Uses of the same instantiation share its encoding.
+    fn pure_function_instance(&self, proc_def_id: ProcedureDefId) -> PureFunctionInstance<'tcx> {
+        let substs = ty::List::identity_for_item(self.env.tcx(), proc_def_id)
+            .types()
+            .map(|ty| self.resolve_typaram(ty))
+            .collect();
+        (proc_def_id, substs)
     }
 
     pub fn process_encoding_queue(&mut self) {
@@ -1536,15 +2574,18 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             let (proc_def_id, substs) = self.encoding_queue.borrow_mut().pop().unwrap();
             let proc_name = self.env.get_absolute_item_name(proc_def_id);
             let proc_def_path = self.env.get_item_def_path(proc_def_id);
+            let _module_scope = config::enter_module(&proc_name);
             let wrapper_def_id = self.get_wrapper_def_id(proc_def_id);
             let proc_span = self.env.get_item_span(wrapper_def_id);
             info!(
                 "Encoding: {} from {:?} ({})",
                 proc_name, proc_span, proc_def_path
             );
+            let start = Instant::now();
             let is_pure_function = self.is_pure(proc_def_id);
-            if is_pure_function {
-                self.encode_pure_function_def(proc_def_id, substs);
+            let encoding_result = if is_pure_function {
+                self.encode_pure_function_def(proc_def_id, substs)
+                    .and_then(|()| self.encode_registered_refinement_check(proc_def_id))
             } else {
                 assert!(substs.is_empty());
                 if self.is_trusted(proc_def_id) {
@@ -1552,28 +2593,288 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
                         "Trusted procedure will not be encoded or verified: {:?}",
                         proc_def_id
                     );
+                    self.encode_registered_refinement_check(proc_def_id)
                 } else {
-                    if let Err(error) = self.encode_procedure(proc_def_id) {
-                        self.register_encoding_error(error);
+                    let result = self.encode_procedure(proc_def_id).map(|_| ());
+                    if let Err(ref error) = result {
+                        self.register_encoding_error(error.clone());
                         debug!("Error encoding function: {:?}", proc_def_id);
                     }
+                    result
                 }
-            }
+            };
+            let status = match encoding_result {
+                Err(ref error) => match error.kind() {
+                    EncodingErrorKind::Unsupported(_) => ProcedureStatus::Unsupported,
+                    _ => ProcedureStatus::Failed,
+                },
+                Ok(()) if self.is_trusted(proc_def_id) => ProcedureStatus::Trusted,
+                Ok(()) => ProcedureStatus::Verified,
+            };
+            self.procedure_reports.borrow_mut().push(ProcedureReport {
+                def_id: proc_def_id,
+                span: proc_span,
+                status,
+                encoding_time: start.elapsed(),
+                verification_time: Duration::default(),
+            });
         }
     }
 
+    fn encode_registered_refinement_check(&self, def_id: ProcedureDefId) -> SpannedEncodingResult<()> {
+        let result = self.encode_refinement_check(def_id);
+        if let Err(ref error) = result {
+            self.register_encoding_error(error.clone());
+            debug!("Error encoding refinement check: {:?}", def_id);
+        }
+        result
+    }
+
     pub fn is_trusted(&self, def_id: ProcedureDefId) -> bool {
         let result = self.def_spec.get(&def_id).map_or(false, |spec| spec.expect_procedure().trusted);
         trace!("is_trusted {:?} = {}", def_id, result);
         result
     }
 
+    /// In whole-program mode, a trait object whose trait has a single
+    /// implementation is encoded as the implementing type.
+    pub fn devirtualize_type(&self, ty: ty::Ty<'tcx>) -> ty::Ty<'tcx> {
+        if !config::whole_program() {
+            return ty;
+        }
+        if let ty::TyKind::Dynamic(predicates, _) = ty.kind() {
+            if let Some(trait_def_id) = predicates.principal_def_id() {
+                let tcx = self.env().tcx();
+                let impls: Vec<_> = tcx.all_impls(trait_def_id).collect();
+                if let [impl_def_id] = impls[..] {
+                    let impl_ty = tcx.type_of(impl_def_id);
+                    if !impl_ty.needs_subst() {
+                        trace!("devirtualize_type({:?}) = {:?}", ty, impl_ty);
+                        return impl_ty;
+                    }
+                }
+            }
+        }
+        ty
+    }
+
+    /// An opaque `impl Trait` type is encoded as its hidden type, so that the
+    /// values of functions returning `impl Trait` have snapshots and can be
+    /// used in specifications. Callers can still only use the trait methods,
+    /// and thus only learn what the contract states about the result.
+    ///
+    /// If the hidden type cannot be encoded (e.g. an iterator adapter that
+    /// stores a function item), the opaque type is kept. It is then encoded
+    /// as an abstract predicate, like a type parameter, and its values are
+    /// known only through the contracts of the traits that bound it.
+    pub fn reveal_opaque_type(&self, ty: ty::Ty<'tcx>) -> ty::Ty<'tcx> {
+        let tcx = self.env().tcx();
+        let mut ty = ty;
+        while let ty::TyKind::Opaque(def_id, substs) = ty.kind() {
+            let hidden_ty = tcx.type_of(*def_id).subst(tcx, substs);
+            if !self.is_encodable_hidden_type(hidden_ty, &mut vec![]) {
+                trace!("reveal_opaque_type: {:?} is encoded by its bounds", ty);
+                break;
+            }
+            ty = hidden_ty;
+            trace!("reveal_opaque_type: revealed {:?}", ty);
+        }
+        ty
+    }
+
+    /// Can all the values that make up a value of the hidden type `ty` of an
+    /// opaque type be encoded? `visited` are the ADTs that are being checked,
+    /// which are assumed to be encodable to support recursive types.
+    fn is_encodable_hidden_type(&self, ty: ty::Ty<'tcx>, visited: &mut Vec<ty::Ty<'tcx>>) -> bool {
+        if visited.contains(&ty) {
+            return true;
+        }
+        let tcx = self.env().tcx();
+        match ty.kind() {
+            ty::TyKind::Bool
+            | ty::TyKind::Int(_)
+            | ty::TyKind::Uint(_)
+            | ty::TyKind::Float(_)
+            | ty::TyKind::Char
+            | ty::TyKind::Str
+            | ty::TyKind::Never
+            | ty::TyKind::Param(_)
+            | ty::TyKind::Dynamic(..)
+            | ty::TyKind::FnPtr(..)
+            // The state of a closure is not encoded.
+            | ty::TyKind::Closure(..) => true,
+
+            ty::TyKind::Ref(_, elem_ty, _)
+            | ty::TyKind::RawPtr(ty::TypeAndMut { ty: elem_ty, .. })
+            | ty::TyKind::Slice(elem_ty)
+            | ty::TyKind::Array(elem_ty, _) => self.is_encodable_hidden_type(elem_ty, visited),
+
+            ty::TyKind::Tuple(elems) => elems
+                .types()
+                .all(|elem_ty| self.is_encodable_hidden_type(elem_ty, visited)),
+
+            ty::TyKind::Adt(adt_def, substs) => {
+                visited.push(ty);
+                let is_encodable = adt_def.all_fields()
+                    .all(|field| self.is_encodable_hidden_type(field.ty(tcx, substs), visited));
+                visited.pop();
+                is_encodable
+            }
+
+            ty::TyKind::Opaque(def_id, substs) => {
+                let hidden_ty = tcx.type_of(*def_id).subst(tcx, substs);
+                self.is_encodable_hidden_type(hidden_ty, visited)
+            }
+
+            _ => false,
+        }
+    }
+
+    /// A `String` is encoded as the slice of its UTF-8 bytes, like `str`, and
+    /// a `Vec<T>` as the slice `[T]` of its elements, so that the functions on
+    /// slices also apply to them.
+    pub fn as_slice_type(&self, ty: ty::Ty<'tcx>) -> ty::Ty<'tcx> {
+        let tcx = self.env().tcx();
+        if let Some(elem_ty) = self.get_vec_elem_ty(ty) {
+            return tcx.mk_slice(elem_ty);
+        }
+        match ty.kind() {
+            ty::TyKind::Adt(adt_def, _)
+                if tcx.def_path_str(adt_def.did) == "std::string::String" =>
+            {
+                tcx.mk_slice(tcx.types.u8)
+            }
+            _ => ty,
+        }
+    }
+
+    /// The type of the elements of `ty`, if `ty` is a `Vec`.
+    pub fn get_vec_elem_ty(&self, ty: ty::Ty<'tcx>) -> Option<ty::Ty<'tcx>> {
+        let tcx = self.env().tcx();
+        match ty.kind() {
+            ty::TyKind::Adt(adt_def, substs)
+                if tcx.def_path_str(adt_def.did) == "std::vec::Vec" => Some(substs.type_at(0)),
+            _ => None,
+        }
+    }
+
+    /// The type of the content of `ty`, if `ty` is a reference-counted pointer
+    /// (`Rc` or `Arc`). Such a pointer gives only shared access to its content,
+    /// which is encoded like the content of a box.
+    pub fn get_rc_pointee_ty(&self, ty: ty::Ty<'tcx>) -> Option<ty::Ty<'tcx>> {
+        let tcx = self.env().tcx();
+        match ty.kind() {
+            ty::TyKind::Adt(adt_def, substs) => match tcx.def_path_str(adt_def.did).as_str() {
+                "std::rc::Rc" | "std::sync::Arc" => Some(substs.type_at(0)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Is `ty` a cell of `std::cell`, or one of the guards returned by
+    /// borrowing a `RefCell`? The content of such a type can change while it
+    /// is shared, so it is not tracked: every access yields a value of which
+    /// only the type invariant is known, and every update has to establish
+    /// the type invariant.
+    pub fn is_cell_type(&self, ty: ty::Ty<'tcx>) -> bool {
+        let tcx = self.env().tcx();
+        match ty.kind() {
+            ty::TyKind::Adt(adt_def, _) => matches!(
+                tcx.def_path_str(adt_def.did).as_str(),
+                "std::cell::Cell"
+                | "std::cell::RefCell"
+                | "std::cell::UnsafeCell"
+                | "std::cell::Ref"
+                | "std::cell::RefMut"
+            ),
+            _ => false,
+        }
+    }
+
+    /// The methods that encode the `#[invariant]`s of the ADT `adt_did`. They
+    /// are generated in an inherent impl block of the ADT.
+    pub fn get_type_invariants(&self, adt_did: DefId) -> Vec<ProcedureDefId> {
+        let tcx = self.env().tcx();
+        tcx.inherent_impls(adt_did)
+            .iter()
+            .flat_map(|impl_def_id| tcx.associated_items(*impl_def_id).in_definition_order())
+            .filter(|item| {
+                item.kind == ty::AssocKind::Fn
+                    && has_prusti_attr(tcx.get_attrs(item.def_id), "type_invariant")
+            })
+            .map(|item| item.def_id)
+            .collect()
+    }
+
+    /// Is `ty` a reference or a box that points to a trait object?
+    pub fn is_trait_object_pointer(&self, ty: ty::Ty<'tcx>) -> bool {
+        ty.builtin_deref(true).map_or(false, |pointee| pointee.ty.is_trait())
+    }
+
+    /// Is the cast of `src_ty` to `dst_ty` the coercion of a reference to a
+    /// trait object that is encoded as the type of the referenced value?
+    pub fn is_devirtualized_unsize(&self, src_ty: ty::Ty<'tcx>, dst_ty: ty::Ty<'tcx>) -> bool {
+        match (src_ty.kind(), dst_ty.kind()) {
+            (ty::TyKind::Ref(_, src_pointee, _), ty::TyKind::Ref(_, dst_pointee, _)) => {
+                dst_pointee.is_trait() && self.devirtualize_type(dst_pointee) == *src_pointee
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the procedure is a lemma, declared with `#[lemma]` or
+    /// `#[trusted_proof]`. Lemmas are pure functions that return `true`.
+    pub fn is_lemma(&self, def_id: ProcedureDefId) -> bool {
+        self.env.has_prusti_attribute(def_id, "lemma")
+    }
+
     pub fn is_pure(&self, def_id: ProcedureDefId) -> bool {
         let result = self.def_spec.get(&def_id).map_or(false, |spec| spec.expect_procedure().pure);
         trace!("is_pure {:?} = {}", def_id, result);
         result
     }
 
+    /// Register the groups of mutually recursive procedures with a
+    /// `#[decreases]` annotation, whose termination measures are checked at
+    /// the calls within each group.
+    pub fn register_termination_components(&self, components: Vec<Vec<ProcedureDefId>>) {
+        let mut termination_components = self.termination_components.borrow_mut();
+        for (index, component) in components.into_iter().enumerate() {
+            for def_id in component {
+                termination_components.insert(def_id, index);
+            }
+        }
+    }
+
+    /// Should the call of `callee` from `caller` decrease the termination
+    /// measure of `caller`?
+    pub fn is_termination_checked_call(
+        &self,
+        caller: ProcedureDefId,
+        callee: ProcedureDefId,
+    ) -> bool {
+        let termination_components = self.termination_components.borrow();
+        match (termination_components.get(&caller), termination_components.get(&callee)) {
+            (Some(caller_component), Some(callee_component)) => {
+                caller_component == callee_component
+            }
+            _ => false,
+        }
+    }
+
+    /// Encode the termination measure of the pure function `def_id`, if it
+    /// has a `#[decreases]` annotation, over the formal arguments of the
+    /// encoded function.
+    pub fn encode_pure_termination_measure(&self, def_id: ProcedureDefId)
+        -> SpannedEncodingResult<Option<(Vec<vir::LocalVar>, vir::Expr)>>
+    {
+        let wrapper_def_id = self.get_wrapper_def_id(def_id);
+        let procedure = self.env.get_procedure(wrapper_def_id);
+        PureFunctionEncoder::new(self, def_id, procedure.get_mir(), false)
+            .encode_termination_measure()
+    }
+
     pub fn has_extern_spec(&self, def_id: ProcedureDefId) -> bool {
         // FIXME: eventually, procedure specs (the entries in def_spec) should
         // have an `is_extern_spec` field. For now, due to the way we handle
@@ -1635,19 +2936,6 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             .collect::<Result<_, _>>()
     }
 
-    /// TODO: This is a hack, it generates a String that can be used for uniquely identifying this
-    /// type substitution.
-    pub fn type_substitution_key(&self) -> EncodingResult<String> {
-        let mut substs: Vec<_> = self
-            .type_substitution_strings()?
-            .into_iter()
-            .filter(|(typ, subst)| typ != subst)
-            .map(|(typ, subst)| format!("({},{})", typ, subst))
-            .collect();
-        substs.sort();
-        Ok(substs.join(";"))
-    }
-
     pub fn encode_spec_func_name(&self, def_id: ProcedureDefId, kind: SpecFunctionKind) -> String {
         let kind_name = match kind {
             SpecFunctionKind::Pre => "pre",
@@ -1668,7 +2956,61 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
                     .unwrap_or(self.env.get_item_name(def_id))
             )
         );
-        self.intern_viper_identifier(full_name, short_name)
+        let name = self.intern_viper_identifier(full_name, short_name);
+        self.viper_item_names.borrow_mut().insert(name.clone(), def_id);
+        name
+    }
+
+    /// Map the identifiers of the methods, functions, predicates and
+    /// domains of `program` to the Rust items and types that they encode.
+    /// The identifiers of builtin declarations are not mapped.
+    pub fn encode_viper_name_map(&self, program: &vir::Program) -> ViperNameMap {
+        let tcx = self.env.tcx();
+        let source_map = tcx.sess.source_map();
+        let item_names = self.viper_item_names.borrow();
+        let predicate_types = self.predicate_types.borrow();
+        let item_origin = |kind, name: &str| item_names.get(name).map(|&def_id| ViperNameOrigin {
+            kind,
+            rust: self.env.get_absolute_item_name(def_id),
+            span: Some(source_map.span_to_string(self.env.get_item_span(def_id))),
+        });
+        let type_origin = |kind, predicate_name: &str| {
+            predicate_types.get(predicate_name).map(|ty| ViperNameOrigin {
+                kind,
+                rust: ty.to_string(),
+                span: match ty.kind() {
+                    ty::TyKind::Adt(adt_def, _) => {
+                        Some(source_map.span_to_string(tcx.def_span(adt_def.did)))
+                    }
+                    _ => None,
+                },
+            })
+        };
+        let mut name_map = ViperNameMap::default();
+        let methods = program.methods.iter()
+            .map(|method| (ViperDeclarationKind::Method, method.name()));
+        let functions = program.functions.iter()
+            .map(|function| (ViperDeclarationKind::Function, function.name.clone()));
+        for (kind, name) in methods.chain(functions) {
+            if let Some(origin) = item_origin(kind, &name) {
+                name_map.insert(name, origin);
+            }
+        }
+        for predicate in &program.viper_predicates {
+            let name = predicate.name();
+            if let Some(origin) = type_origin(ViperDeclarationKind::Predicate, name) {
+                name_map.insert(name.to_string(), origin);
+            }
+        }
+        for domain in &program.domains {
+            let predicate_name = domain.name.strip_prefix(snapshot::SNAPSHOT_DOMAIN_PREFIX);
+            let origin = predicate_name
+                .and_then(|predicate_name| type_origin(ViperDeclarationKind::Domain, predicate_name));
+            if let Some(origin) = origin {
+                name_map.insert(domain.name.clone(), origin);
+            }
+        }
+        name_map
     }
 
     pub fn intern_viper_identifier<S: AsRef<str>>(&self, full_name: S, short_name: S) -> String {
@@ -1703,3 +3045,38 @@ fn encode_identifier(ident: String) -> String {
         .replace(" ", "$space$")
 }
 
+
+/// The variant of `Option` or `Result` that is tested by the method
+/// `func_proc_name`, e.g. `Some` for `Option::is_some`.
+pub fn get_tested_variant(func_proc_name: &str) -> Option<&'static str> {
+    match func_proc_name {
+        "std::option::Option::<T>::is_some" => Some("Some"),
+        "std::option::Option::<T>::is_none" => Some("None"),
+        "std::result::Result::<T, E>::is_ok" => Some("Ok"),
+        "std::result::Result::<T, E>::is_err" => Some("Err"),
+        _ => None,
+    }
+}
+
+/// The name of the method of `Vec` that is called by `func_proc_name`, e.g.
+/// `push` for `Vec::push`. The methods are defined with or without the
+/// allocator parameter of `Vec`.
+pub fn get_vec_method(func_proc_name: &str) -> Option<&str> {
+    func_proc_name.strip_prefix("std::vec::Vec::<T>::")
+        .or_else(|| func_proc_name.strip_prefix("std::vec::Vec::<T, A>::"))
+}
+
+/// The variant of `Option` or `Result` whose value is returned by the method
+/// `func_proc_name`, which panics on the other variants, e.g. `Some` for
+/// `Option::unwrap`.
+pub fn get_unwrapped_variant(func_proc_name: &str) -> Option<&'static str> {
+    match func_proc_name {
+        "std::option::Option::<T>::unwrap"
+        | "std::option::Option::<T>::expect" => Some("Some"),
+        "std::result::Result::<T, E>::unwrap"
+        | "std::result::Result::<T, E>::expect" => Some("Ok"),
+        "std::result::Result::<T, E>::unwrap_err"
+        | "std::result::Result::<T, E>::expect_err" => Some("Err"),
+        _ => None,
+    }
+}
@@ -4,8 +4,9 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use prusti_common::vir::Position;
+use prusti_common::vir::{self, Position};
 use std::collections::HashMap;
+use std::panic::Location;
 use rustc_span::source_map::SourceMap;
 use rustc_span::MultiSpan;
 use viper::VerificationError;
@@ -27,6 +28,8 @@ pub enum PanicCause {
     Unreachable,
     /// Caused by an unimplemented!()
     Unimplemented,
+    /// Caused by unwrapping an `Option` or a `Result` that is not of the given variant
+    Unwrap(String),
 }
 
 /// In case of verification error, this enum will contain additional information
@@ -36,7 +39,13 @@ pub enum ErrorCtxt {
     /// A Viper `assert false` that encodes a Rust panic
     Panic(PanicCause),
     /// A Viper `exhale expr` that encodes the call of a Rust procedure with precondition `expr`
-    ExhaleMethodPrecondition,
+    /// Arguments: the absolute path of the callee and, if `expr` is a single `requires` clause,
+    /// its source text and span
+    ExhaleMethodPrecondition(String, Option<(String, MultiSpan)>),
+    /// A Viper `assert expr` that encodes the call of a Rust procedure whose arguments have to
+    /// satisfy their type invariants
+    /// Arguments: the absolute path of the callee
+    AssertMethodPreconditionTypeInvariants(String),
     /// A Viper `assert expr` that encodes the call of a Rust procedure with precondition `expr`
     AssertMethodPostcondition,
     /// A Viper `assert expr` that encodes the call of a Rust procedure with precondition `expr`
@@ -52,6 +61,9 @@ pub enum ErrorCtxt {
     /// A Viper `assert false` that encodes the failure (panic) of an `assert` Rust terminator
     /// Arguments: the message of the Rust assertion
     AssertTerminator(String),
+    /// A Viper `assert expr`, or an unreachable function call in a pure
+    /// function, that checks that an index of an array or slice is in bounds
+    BoundsCheck,
     /// A Viper `assert false` that encodes an `abort` Rust terminator
     AbortTerminator,
     /// A Viper `assert false` that encodes an `unreachable` Rust terminator
@@ -68,6 +80,10 @@ pub enum ErrorCtxt {
     StubPureFunctionCall,
     /// An expression that encodes the value range of the result of a pure function
     PureFunctionPostconditionValueRangeOfResult,
+    /// The postcondition `result == true` of a lemma
+    LemmaResult,
+    /// A Viper `assert` of the call of a lemma, which instantiates its postcondition
+    LemmaApplication,
     /// A Viper function with `false` precondition that encodes the failure (panic) of an
     /// `assert` Rust terminator in a Rust pure function.
     /// Arguments: the message of the Rust assertion
@@ -94,6 +110,70 @@ pub enum ErrorCtxt {
     TypeCast,
     /// A Viper `assert false` that encodes an unsupported feature
     Unsupported(String),
+    /// A Viper `assert expr` that checks that a loop iteration can be paid
+    /// with the credits given by a `#[complexity]` annotation
+    AssertLoopIterationCredits,
+    /// A Viper `assert expr` that checks that a call can be paid with the
+    /// credits given by a `#[complexity]` annotation
+    AssertCallCredits,
+    /// A Viper `assert expr`, or the precondition of a call in a pure
+    /// function, that checks that the termination measure given by a
+    /// `#[decreases]` annotation decreases at a recursive call
+    AssertCallTerminationMeasure,
+    /// A Viper `assert expr` that checks that the termination measure given
+    /// by a `body_variant!` decreases in each loop iteration
+    AssertLoopVariant,
+    /// A Viper `assert acc(RawPtrValid$(address))` that checks that a raw
+    /// pointer is valid when it is dereferenced
+    AssertRawPointerValidity,
+}
+
+impl ErrorCtxt {
+    /// The name of the variant, e.g. `ExhaleMethodPostcondition`.
+    pub fn name(&self) -> String {
+        let description = format!("{:?}", self);
+        match description.find('(') {
+            Some(index) => description[..index].to_string(),
+            None => description,
+        }
+    }
+
+    /// The kind of specification whose failure the error context describes,
+    /// if any.
+    pub fn spec_kind(&self) -> Option<&'static str> {
+        match self {
+            ErrorCtxt::ExhaleMethodPrecondition(..)
+            | ErrorCtxt::AssertMethodPreconditionTypeInvariants(_)
+            | ErrorCtxt::AssertMethodPreconditionWeakening(_) => Some("precondition"),
+            ErrorCtxt::AssertMethodPostcondition
+            | ErrorCtxt::AssertMethodPostconditionTypeInvariants
+            | ErrorCtxt::ExhaleMethodPostcondition
+            | ErrorCtxt::PackageMagicWandForPostcondition
+            | ErrorCtxt::AssertMethodPostconditionStrengthening(_)
+            | ErrorCtxt::PureFunctionPostconditionValueRangeOfResult => Some("postcondition"),
+            ErrorCtxt::ApplyMagicWandOnExpiry => Some("pledge"),
+            ErrorCtxt::ExhaleLoopInvariantOnEntry
+            | ErrorCtxt::ExhaleLoopInvariantAfterIteration
+            | ErrorCtxt::AssertLoopInvariantOnEntry
+            | ErrorCtxt::AssertLoopInvariantAfterIteration => Some("loop-invariant"),
+            ErrorCtxt::LemmaResult | ErrorCtxt::LemmaApplication => Some("lemma"),
+            ErrorCtxt::AssertLoopIterationCredits
+            | ErrorCtxt::AssertCallCredits => Some("complexity"),
+            ErrorCtxt::AssertCallTerminationMeasure
+            | ErrorCtxt::AssertLoopVariant => Some("termination"),
+            _ => None,
+        }
+    }
+}
+
+/// Where a position was registered, to trace a verification error back to the
+/// encoding that produced it.
+#[derive(Clone, Debug)]
+struct Provenance {
+    /// The location in the source code of Prusti that registered the position
+    registered_by: &'static Location<'static>,
+    /// The MIR statement or terminator that was being encoded, if any
+    mir_context: Option<String>,
 }
 
 /// The error manager
@@ -102,6 +182,8 @@ pub struct ErrorManager<'tcx> {
     codemap: &'tcx SourceMap,
     source_span: HashMap<u64, MultiSpan>,
     error_contexts: HashMap<u64, ErrorCtxt>,
+    provenances: HashMap<u64, Provenance>,
+    mir_context: Option<String>,
     next_pos_id: u64,
 }
 
@@ -112,21 +194,35 @@ impl<'tcx> ErrorManager<'tcx>
             codemap,
             source_span: HashMap::new(),
             error_contexts: HashMap::new(),
+            provenances: HashMap::new(),
+            mir_context: None,
             next_pos_id: 1,
         }
     }
 
+    /// Set the description of the MIR statement or terminator whose encoding
+    /// registers the next positions.
+    pub fn set_mir_context(&mut self, mir_context: Option<String>) {
+        self.mir_context = mir_context;
+    }
+
+    #[track_caller]
     pub fn register<T: Into<MultiSpan>>(&mut self, span: T, error_ctxt: ErrorCtxt) -> Position {
         let pos = self.register_span(span);
         self.register_error(&pos, error_ctxt);
         pos
     }
 
+    #[track_caller]
     pub fn register_span<T: Into<MultiSpan>>(&mut self, span: T) -> Position {
         let span = span.into();
         let pos_id = self.next_pos_id;
         self.next_pos_id += 1;
         debug!("Register position {:?} at span {:?}", pos_id, span);
+        self.provenances.insert(pos_id, Provenance {
+            registered_by: Location::caller(),
+            mir_context: self.mir_context.clone(),
+        });
         let pos = if let Some(primary_span) = span.primary_span() {
             let lines_info_res = self
                 .codemap
@@ -155,6 +251,51 @@ impl<'tcx> ErrorManager<'tcx>
         self.error_contexts.insert(pos.id(), error_ctxt);
     }
 
+    /// Describe, as a tree, how the positions of `ver_error` were produced:
+    /// the failing VIR statement or expression of `program`, the error
+    /// context and the encoder code that registered the position, the MIR
+    /// statement being encoded and the span.
+    pub fn describe_provenance(
+        &self,
+        ver_error: &VerificationError,
+        program: &vir::Program,
+    ) -> String {
+        let mut tree = format!("provenance of the Viper error '{}'\n", ver_error.full_id);
+        tree.push_str(&format!("└─ Viper: {}\n", ver_error.message));
+        let positions = [("failing position", &ver_error.pos_id), ("reason", &ver_error.reason_pos_id)];
+        for (kind, opt_pos_id) in positions.iter() {
+            let pos_id = match opt_pos_id.as_ref().and_then(|pos_id| pos_id.parse().ok()) {
+                Some(pos_id) => pos_id,
+                None => continue,
+            };
+            let mut levels = vec![format!("{} {}", kind, pos_id)];
+            levels.push(format!(
+                "VIR: {}",
+                program.find_position(pos_id).unwrap_or_else(|| "<not found>".to_string()),
+            ));
+            if let Some(error_ctxt) = self.error_contexts.get(&pos_id) {
+                levels.push(format!("error context: {:?}", error_ctxt));
+            }
+            if let Some(provenance) = self.provenances.get(&pos_id) {
+                levels.push(format!("encoding: registered by {}", provenance.registered_by));
+                if let Some(ref mir_context) = provenance.mir_context {
+                    levels.push(format!("MIR: {}", mir_context));
+                }
+            }
+            if let Some(span) = self.source_span.get(&pos_id) {
+                let spans: Vec<_> = span.primary_spans()
+                    .iter()
+                    .map(|span| self.codemap.span_to_string(*span))
+                    .collect();
+                levels.push(format!("span: {}", spans.join(", ")));
+            }
+            for (depth, level) in levels.into_iter().enumerate() {
+                tree.push_str(&format!("{}└─ {}\n", "   ".repeat(depth + 1), level));
+            }
+        }
+        tree
+    }
+
     pub fn translate_verification_error(&self, ver_error: &VerificationError) -> PrustiError {
         debug!("Verification error: {:?}", ver_error);
         let opt_pos_id: Option<u64> = match ver_error.pos_id {
@@ -202,6 +343,18 @@ impl<'tcx> ErrorManager<'tcx>
             res
         });
 
+        if ver_error.is_timeout() {
+            let error_span = opt_error_span.or(opt_cause_span).cloned()
+                .unwrap_or_else(|| MultiSpan::new());
+            return PrustiError::timeout(
+                format!("the verifier timed out: {}", ver_error.message),
+                error_span
+            ).set_help(
+                "Try increasing the timeout by setting the configuration parameter \
+                ASSERT_TIMEOUT to a larger value."
+            );
+        }
+
         let (error_span, error_ctxt) = if let Some(error_ctxt) = opt_error_ctxt {
             debug_assert!(opt_error_span.is_some());
             let error_span = opt_error_span.cloned().unwrap_or_else(|| MultiSpan::new());
@@ -244,7 +397,7 @@ impl<'tcx> ErrorManager<'tcx>
             }
         };
 
-        match (ver_error.full_id.as_str(), error_ctxt) {
+        let prusti_error = match (ver_error.full_id.as_str(), error_ctxt) {
             ("assert.failed:assertion.false", ErrorCtxt::Panic(PanicCause::Generic)) => {
                 PrustiError::verification("statement might panic", error_span)
                     .set_failing_assertion(opt_cause_span)
@@ -271,11 +424,25 @@ impl<'tcx> ErrorManager<'tcx>
                     .set_failing_assertion(opt_cause_span)
             }
 
+            ("assert.failed:assertion.false", ErrorCtxt::Panic(PanicCause::Unwrap(ref variant))) => {
+                PrustiError::verification(
+                    format!("unwrapping might panic, because the value might not be `{}`", variant),
+                    error_span
+                ).set_failing_assertion(opt_cause_span)
+            }
+
             ("assert.failed:assertion.false", ErrorCtxt::AssertTerminator(ref message)) => {
                 PrustiError::verification(format!("assertion might fail with \"{}\"", message), error_span)
                     .set_failing_assertion(opt_cause_span)
             }
 
+            ("assert.failed:assertion.false", ErrorCtxt::BoundsCheck) |
+            ("application.precondition:assertion.false", ErrorCtxt::BoundsCheck) => {
+                PrustiError::verification("the array index may be out of bounds", error_span)
+                    .set_failing_assertion(opt_cause_span)
+                    .set_help("The index must be smaller than the length of the array or slice.")
+            }
+
             ("assert.failed:assertion.false", ErrorCtxt::AbortTerminator) => {
                 PrustiError::verification("statement might abort", error_span)
                     .set_failing_assertion(opt_cause_span)
@@ -289,19 +456,30 @@ impl<'tcx> ErrorManager<'tcx>
                     .set_help("This might be a bug in the Rust compiler.")
             }
 
-            ("assert.failed:assertion.false", ErrorCtxt::ExhaleMethodPrecondition) => {
-                PrustiError::verification("precondition might not hold.", error_span)
-                    .set_failing_assertion(opt_cause_span)
+            ("assert.failed:assertion.false", ErrorCtxt::ExhaleMethodPrecondition(ref callee, Some((ref clause, ref clause_span)))) => {
+                PrustiError::verification(
+                    format!("precondition might not hold: the call of '{}' requires `{}`.", callee, clause),
+                    error_span
+                ).set_failing_assertion(opt_cause_span.or(Some(clause_span)))
             }
 
-            ("fold.failed:assertion.false", ErrorCtxt::ExhaleMethodPrecondition) => {
+            ("assert.failed:assertion.false", ErrorCtxt::ExhaleMethodPrecondition(ref callee, _)) |
+            ("assert.failed:insufficient.permission", ErrorCtxt::ExhaleMethodPrecondition(ref callee, _)) => {
                 PrustiError::verification(
-                    "implicit type invariant expected by the function call might not hold.",
+                    format!("precondition might not hold for the call of '{}'.", callee),
                     error_span
                 ).set_failing_assertion(opt_cause_span)
             }
 
-            ("assert.failed:assertion.false", ErrorCtxt::ExhaleMethodPostcondition) => {
+            ("fold.failed:assertion.false", ErrorCtxt::ExhaleMethodPrecondition(ref callee, _)) => {
+                PrustiError::verification(
+                    format!("implicit type invariant expected by the call of '{}' might not hold.", callee),
+                    error_span
+                ).set_failing_assertion(opt_cause_span)
+            }
+
+            ("assert.failed:assertion.false", ErrorCtxt::ExhaleMethodPostcondition) |
+            ("assert.failed:insufficient.permission", ErrorCtxt::ExhaleMethodPostcondition) => {
                 PrustiError::verification("postcondition might not hold.", error_span)
                     .push_primary_span(opt_cause_span)
             }
@@ -309,6 +487,7 @@ impl<'tcx> ErrorManager<'tcx>
             ("assert.failed:assertion.false", ErrorCtxt::ExhaleLoopInvariantOnEntry) => {
                 PrustiError::verification("loop invariant might not hold in the first loop iteration.", error_span)
                     .push_primary_span(opt_cause_span)
+                    .set_help("This is the establishment of the invariant: it must hold when the loop is reached for the first time.")
             }
 
             ("fold.failed:assertion.false", ErrorCtxt::ExhaleLoopInvariantOnEntry) => {
@@ -321,6 +500,7 @@ impl<'tcx> ErrorManager<'tcx>
             ("assert.failed:assertion.false", ErrorCtxt::AssertLoopInvariantOnEntry) => {
                 PrustiError::verification("loop invariant might not hold in the first loop iteration.", error_span)
                     .push_primary_span(opt_cause_span)
+                    .set_help("This is the establishment of the invariant: it must hold when the loop is reached for the first time.")
             }
 
             ("assert.failed:assertion.false", ErrorCtxt::ExhaleLoopInvariantAfterIteration) => {
@@ -328,6 +508,7 @@ impl<'tcx> ErrorManager<'tcx>
                     "loop invariant might not hold after a loop iteration that preserves the loop condition.",
                     error_span
                 ).push_primary_span(opt_cause_span)
+                    .set_help("This is the preservation of the invariant: it must hold again after an arbitrary iteration that starts in a state satisfying the invariant.")
             }
 
             ("assert.failed:assertion.false", ErrorCtxt::AssertLoopInvariantAfterIteration) => {
@@ -335,6 +516,7 @@ impl<'tcx> ErrorManager<'tcx>
                     "loop invariant might not hold after a loop iteration that preserves the loop condition.",
                     error_span
                 ).push_primary_span(opt_cause_span)
+                    .set_help("This is the preservation of the invariant: it must hold again after an arbitrary iteration that starts in a state satisfying the invariant.")
             }
 
             ("application.precondition:assertion.false", ErrorCtxt::PureFunctionCall) => {
@@ -344,6 +526,20 @@ impl<'tcx> ErrorManager<'tcx>
                 ).set_failing_assertion(opt_cause_span)
             }
 
+            ("application.precondition:assertion.false", ErrorCtxt::LemmaApplication) => {
+                PrustiError::verification(
+                    "precondition of lemma might not hold.",
+                    error_span
+                ).set_failing_assertion(opt_cause_span)
+            }
+
+            ("postcondition.violated:assertion.false", ErrorCtxt::LemmaResult) => {
+                PrustiError::verification(
+                    "the lemma might not return `true`",
+                    error_span
+                ).push_primary_span(opt_cause_span)
+            }
+
             ("application.precondition:assertion.false", ErrorCtxt::StubPureFunctionCall) => {
                 PrustiError::incorrect(
                     "use of impure function might be reachable.",
@@ -415,6 +611,16 @@ impl<'tcx> ErrorManager<'tcx>
                 ).push_primary_span(opt_cause_span)
             }
 
+            (
+                "application.precondition:assertion.false",
+                ErrorCtxt::PanicInPureFunction(PanicCause::Unwrap(ref variant)),
+            ) => {
+                PrustiError::verification(
+                    format!("unwrapping in pure function might panic, because the value might not be `{}`", variant),
+                    error_span
+                ).push_primary_span(opt_cause_span)
+            }
+
             ("postcondition.violated:assertion.false", ErrorCtxt::PureFunctionDefinition) |
             ("postcondition.violated:assertion.false", ErrorCtxt::PureFunctionCall) |
             ("postcondition.violated:assertion.false", ErrorCtxt::GenericExpression) => {
@@ -461,6 +667,16 @@ impl<'tcx> ErrorManager<'tcx>
                 ).set_failing_assertion(opt_cause_span)
             },
 
+            (
+                "assert.failed:assertion.false",
+                ErrorCtxt::AssertMethodPreconditionTypeInvariants(ref callee),
+            ) => {
+                PrustiError::verification(
+                    format!("type invariants might not hold for the arguments of the call of '{}'.", callee),
+                    error_span
+                ).set_failing_assertion(opt_cause_span)
+            }
+
             ("fold.failed:assertion.false", ErrorCtxt::PackageMagicWandForPostcondition) |
             ("fold.failed:assertion.false", ErrorCtxt::AssertMethodPostconditionTypeInvariants) => {
                 PrustiError::verification(
@@ -483,6 +699,48 @@ impl<'tcx> ErrorManager<'tcx>
                     .set_help("The implemented method's postcondition should imply the trait's postcondition.")
             }
 
+            ("assert.failed:assertion.false", ErrorCtxt::AssertLoopIterationCredits) => {
+                PrustiError::verification(
+                    "the loop iteration might exceed the complexity bound of the function.",
+                    error_span
+                ).set_help("Each loop iteration consumes one credit.")
+            }
+
+            ("assert.failed:assertion.false", ErrorCtxt::AssertCallCredits) => {
+                PrustiError::verification(
+                    "the call might exceed the complexity bound of the function.",
+                    error_span
+                ).set_help("Each call consumes one credit plus the complexity bound of the callee.")
+            }
+
+            ("assert.failed:assertion.false", ErrorCtxt::AssertCallTerminationMeasure) |
+            ("application.precondition:assertion.false", ErrorCtxt::AssertCallTerminationMeasure) => {
+                PrustiError::verification(
+                    "the termination measure might not decrease at this recursive call.",
+                    error_span
+                ).set_help(
+                    "The measure of the callee must be non-negative and smaller than the \
+                    measure of the caller."
+                )
+            }
+
+            ("assert.failed:assertion.false", ErrorCtxt::AssertLoopVariant) => {
+                PrustiError::verification(
+                    "the loop variant might not decrease in this loop iteration.",
+                    error_span
+                ).set_help(
+                    "At the end of each iteration, the variant must be non-negative and smaller \
+                    than at its start."
+                )
+            }
+
+            ("assert.failed:insufficient.permission", ErrorCtxt::AssertRawPointerValidity) => {
+                PrustiError::verification(
+                    "the dereferenced raw pointer might not be valid.",
+                    error_span
+                ).set_help("The validity of a raw pointer can be required with `ptr_valid`.")
+            }
+
             ("assert.failed:assertion.false", ErrorCtxt::Unsupported(ref reason)) => {
                 PrustiError::unsupported(
                     format!("an unsupported Rust feature might be reachable: {}.", reason),
@@ -525,6 +783,7 @@ impl<'tcx> ErrorManager<'tcx>
                     ASSERT_TIMEOUT to a larger value."
                 )
             }
-        }
+        };
+        prusti_error.set_error_ctxt(error_ctxt.name(), error_ctxt.spec_kind())
     }
 }
@@ -0,0 +1,309 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Encoding of the values of `f32` and `f64` as elements of the `Float`
+//! domain. The operations on floats are uninterpreted functions of the
+//! domain. With `ENCODE_FLOATS_AS_REALS`, the domain is axiomatized as an
+//! ordered field, which approximates floats by real numbers.
+//!
+//! Both `f32` and `f64` values are elements of the same domain. A literal is
+//! identified by the bits of its value converted to `f64`, which is exact.
+
+use crate::encoder::errors::{EncodingError, EncodingResult};
+use crate::encoder::snapshot;
+use prusti_common::vir;
+use rustc_middle::mir;
+use std::collections::BTreeSet;
+
+pub const FLOAT_DOMAIN_NAME: &str = "Float";
+
+pub fn float_type() -> vir::Type {
+    vir::Type::Domain(FLOAT_DOMAIN_NAME.to_string())
+}
+
+/// The bits that identify the literal `value`. Both zeros are the same
+/// literal, because they compare equal.
+pub fn encode_literal_bits(value: f64) -> u64 {
+    if value == 0.0 {
+        0.0f64.to_bits()
+    } else {
+        value.to_bits()
+    }
+}
+
+fn float_arg(name: &str) -> vir::LocalVar {
+    vir::LocalVar::new(name, float_type())
+}
+
+fn float_func(name: &str, formal_args: Vec<vir::LocalVar>, return_type: vir::Type) -> vir::DomainFunc {
+    vir::DomainFunc {
+        name: format!("float${}", name),
+        formal_args,
+        return_type,
+        unique: false,
+        domain_name: FLOAT_DOMAIN_NAME.to_string(),
+    }
+}
+
+fn literal_func() -> vir::DomainFunc {
+    float_func("lit", vec![vir::LocalVar::new("bits", vir::Type::Int)], float_type())
+}
+
+fn neg_func() -> vir::DomainFunc {
+    float_func("neg", vec![float_arg("arg")], float_type())
+}
+
+fn bin_op_func(name: &str, return_type: vir::Type) -> vir::DomainFunc {
+    float_func(name, vec![float_arg("left"), float_arg("right")], return_type)
+}
+
+fn arith_funcs() -> Vec<vir::DomainFunc> {
+    ["add", "sub", "mul", "div"]
+        .iter()
+        .map(|name| bin_op_func(name, float_type()))
+        .collect()
+}
+
+fn cmp_funcs() -> Vec<vir::DomainFunc> {
+    ["eq", "lt", "le"]
+        .iter()
+        .map(|name| bin_op_func(name, vir::Type::Bool))
+        .collect()
+}
+
+fn apply(func: vir::DomainFunc, args: Vec<vir::Expr>) -> vir::Expr {
+    vir::Expr::domain_func_app(func, args)
+}
+
+fn add(left: vir::Expr, right: vir::Expr) -> vir::Expr {
+    apply(bin_op_func("add", float_type()), vec![left, right])
+}
+
+fn eq(left: vir::Expr, right: vir::Expr) -> vir::Expr {
+    apply(bin_op_func("eq", vir::Type::Bool), vec![left, right])
+}
+
+fn lt(left: vir::Expr, right: vir::Expr) -> vir::Expr {
+    apply(bin_op_func("lt", vir::Type::Bool), vec![left, right])
+}
+
+/// Encode the float literal whose bits are given by `encode_literal_bits`.
+pub fn encode_literal(bits: u64) -> vir::Expr {
+    apply(literal_func(), vec![bits.into()])
+}
+
+pub fn encode_neg(arg: vir::Expr) -> vir::Expr {
+    apply(neg_func(), vec![arg])
+}
+
+/// Encode a binary operation on floats. Equality is the uninterpreted
+/// `float$eq`, which is not reflexive because of NaN.
+pub fn encode_bin_op(
+    op: mir::BinOp,
+    left: vir::Expr,
+    right: vir::Expr,
+) -> EncodingResult<vir::Expr> {
+    let arith = |name: &str| apply(bin_op_func(name, float_type()), vec![left.clone(), right.clone()]);
+    let cmp = |name: &str, left: &vir::Expr, right: &vir::Expr| {
+        apply(bin_op_func(name, vir::Type::Bool), vec![left.clone(), right.clone()])
+    };
+    Ok(match op {
+        mir::BinOp::Eq => cmp("eq", &left, &right),
+        mir::BinOp::Ne => vir::Expr::not(cmp("eq", &left, &right)),
+        mir::BinOp::Lt => cmp("lt", &left, &right),
+        mir::BinOp::Le => cmp("le", &left, &right),
+        mir::BinOp::Gt => cmp("lt", &right, &left),
+        mir::BinOp::Ge => cmp("le", &right, &left),
+        mir::BinOp::Add => arith("add"),
+        mir::BinOp::Sub => arith("sub"),
+        mir::BinOp::Mul => arith("mul"),
+        mir::BinOp::Div => arith("div"),
+        unsupported_op => {
+            return Err(EncodingError::unsupported(format!(
+                "operation '{:?}' on floats is not supported",
+                unsupported_op
+            )))
+        }
+    })
+}
+
+/// Encode the `Float` domain. `literals` are the bits of the literals used by
+/// the program, whose order is always axiomatized.
+pub fn encode_float_domain(literals: &BTreeSet<u64>, as_reals: bool) -> vir::Domain {
+    let valid_func = snapshot::valid_func_for_type(&float_type());
+    let mut functions = vec![literal_func(), neg_func(), valid_func.clone()];
+    functions.extend(arith_funcs());
+    functions.extend(cmp_funcs());
+
+    let axiom = |name: &str, expr: vir::Expr| vir::DomainAxiom {
+        name: format!("float${}", name),
+        expr,
+        domain_name: FLOAT_DOMAIN_NAME.to_string(),
+    };
+    let (a, b, c) = (float_arg("a"), float_arg("b"), float_arg("c"));
+    let (ea, eb, ec) = (
+        vir::Expr::local(a.clone()),
+        vir::Expr::local(b.clone()),
+        vir::Expr::local(c.clone()),
+    );
+
+    let valid_app = apply(valid_func, vec![ea.clone()]);
+    let mut axioms = vec![
+        axiom("valid", vir::Expr::forall(
+            vec![a.clone()],
+            vec![vir::Trigger::new(vec![valid_app.clone()])],
+            valid_app,
+        )),
+        axiom("lt_irreflexive", vir::Expr::forall(
+            vec![a.clone()],
+            vec![vir::Trigger::new(vec![lt(ea.clone(), ea.clone())])],
+            vir::Expr::not(lt(ea.clone(), ea.clone())),
+        )),
+        // Floats that compare equal are the same element, but an element
+        // is not equal to itself if it is NaN.
+        axiom("eq_same", vir::Expr::forall(
+            vec![a.clone(), b.clone()],
+            vec![vir::Trigger::new(vec![eq(ea.clone(), eb.clone())])],
+            vir::Expr::implies(
+                eq(ea.clone(), eb.clone()),
+                vir::Expr::eq_cmp(ea.clone(), eb.clone()),
+            ),
+        )),
+    ];
+
+    for &bits in literals {
+        if !f64::from_bits(bits).is_nan() {
+            axioms.push(axiom(
+                &format!("lit_eq_{}", bits),
+                eq(encode_literal(bits), encode_literal(bits)),
+            ));
+        }
+    }
+
+    // The order of the literals, which is the same for floats and reals. All
+    // the pairs are axiomatized, because the order is not known to be
+    // transitive unless floats are encoded as reals.
+    let finite_literals: Vec<_> = literals
+        .iter()
+        .cloned()
+        .filter(|&bits| f64::from_bits(bits).is_finite())
+        .collect();
+    for &smaller in &finite_literals {
+        for &larger in &finite_literals {
+            if f64::from_bits(smaller) < f64::from_bits(larger) {
+                axioms.push(axiom(
+                    &format!("lit_lt_{}_{}", smaller, larger),
+                    lt(encode_literal(smaller), encode_literal(larger)),
+                ));
+            }
+        }
+    }
+
+    if as_reals {
+        let zero = encode_literal(encode_literal_bits(0.0));
+        let one = encode_literal(encode_literal_bits(1.0));
+        let app = |name: &str, left: &vir::Expr, right: &vir::Expr| {
+            apply(bin_op_func(name, float_type()), vec![left.clone(), right.clone()])
+        };
+        let le_app = apply(bin_op_func("le", vir::Type::Bool), vec![ea.clone(), eb.clone()]);
+        let trigger = |exprs: Vec<vir::Expr>| vec![vir::Trigger::new(exprs)];
+        axioms.extend(vec![
+            axiom("eq_reflexive", vir::Expr::forall(
+                vec![a.clone()],
+                trigger(vec![eq(ea.clone(), ea.clone())]),
+                eq(ea.clone(), ea.clone()),
+            )),
+            axiom("lt_transitive", vir::Expr::forall(
+                vec![a.clone(), b.clone(), c.clone()],
+                trigger(vec![lt(ea.clone(), eb.clone()), lt(eb.clone(), ec.clone())]),
+                vir::Expr::implies(
+                    vir::Expr::and(lt(ea.clone(), eb.clone()), lt(eb.clone(), ec.clone())),
+                    lt(ea.clone(), ec.clone()),
+                ),
+            )),
+            axiom("lt_total", vir::Expr::forall(
+                vec![a.clone(), b.clone()],
+                trigger(vec![lt(ea.clone(), eb.clone())]),
+                vir::Expr::or(
+                    lt(ea.clone(), eb.clone()),
+                    vir::Expr::or(
+                        eq(ea.clone(), eb.clone()),
+                        lt(eb.clone(), ea.clone()),
+                    ),
+                ),
+            )),
+            axiom("le_def", vir::Expr::forall(
+                vec![a.clone(), b.clone()],
+                trigger(vec![le_app.clone()]),
+                vir::Expr::eq_cmp(
+                    le_app,
+                    vir::Expr::or(
+                        lt(ea.clone(), eb.clone()),
+                        eq(ea.clone(), eb.clone()),
+                    ),
+                ),
+            )),
+            axiom("add_commutative", vir::Expr::forall(
+                vec![a.clone(), b.clone()],
+                trigger(vec![app("add", &ea, &eb)]),
+                vir::Expr::eq_cmp(app("add", &ea, &eb), app("add", &eb, &ea)),
+            )),
+            axiom("mul_commutative", vir::Expr::forall(
+                vec![a.clone(), b.clone()],
+                trigger(vec![app("mul", &ea, &eb)]),
+                vir::Expr::eq_cmp(app("mul", &ea, &eb), app("mul", &eb, &ea)),
+            )),
+            axiom("add_zero", vir::Expr::forall(
+                vec![a.clone()],
+                trigger(vec![app("add", &ea, &zero)]),
+                vir::Expr::eq_cmp(app("add", &ea, &zero), ea.clone()),
+            )),
+            axiom("mul_zero", vir::Expr::forall(
+                vec![a.clone()],
+                trigger(vec![app("mul", &ea, &zero)]),
+                vir::Expr::eq_cmp(app("mul", &ea, &zero), zero.clone()),
+            )),
+            axiom("mul_one", vir::Expr::forall(
+                vec![a.clone()],
+                trigger(vec![app("mul", &ea, &one)]),
+                vir::Expr::eq_cmp(app("mul", &ea, &one), ea.clone()),
+            )),
+            axiom("sub_def", vir::Expr::forall(
+                vec![a.clone(), b.clone()],
+                trigger(vec![app("sub", &ea, &eb)]),
+                vir::Expr::eq_cmp(add(app("sub", &ea, &eb), eb.clone()), ea.clone()),
+            )),
+            axiom("neg_def", vir::Expr::forall(
+                vec![a.clone()],
+                trigger(vec![encode_neg(ea.clone())]),
+                vir::Expr::eq_cmp(app("sub", &zero, &ea), encode_neg(ea.clone())),
+            )),
+            axiom("div_def", vir::Expr::forall(
+                vec![a.clone(), b.clone()],
+                trigger(vec![app("div", &ea, &eb)]),
+                vir::Expr::implies(
+                    vir::Expr::ne_cmp(eb.clone(), zero.clone()),
+                    vir::Expr::eq_cmp(app("mul", &app("div", &ea, &eb), &eb), ea.clone()),
+                ),
+            )),
+            axiom("add_monotonic", vir::Expr::forall(
+                vec![a.clone(), b.clone(), c.clone()],
+                trigger(vec![lt(ea.clone(), eb.clone()), app("add", &ea, &ec)]),
+                vir::Expr::implies(
+                    lt(ea.clone(), eb.clone()),
+                    lt(app("add", &ea, &ec), app("add", &eb, &ec)),
+                ),
+            )),
+        ]);
+    }
+
+    vir::Domain {
+        name: FLOAT_DOMAIN_NAME.to_string(),
+        functions,
+        axioms,
+        type_vars: vec![],
+    }
+}
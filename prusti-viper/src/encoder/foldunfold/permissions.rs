@@ -204,6 +204,14 @@ impl RequiredPermissionsGetter for vir::Expr {
 
             vir::Expr::LabelledOld(_label, _expr, _) => HashSet::new(),
 
+            // Bodyless predicates, like the validity of raw pointers, are
+            // tokens that are not tracked by the fold-unfold algorithm.
+            vir::Expr::PredicateAccessPredicate(name, box arg, _perm_amount, _)
+                if !predicates.contains_key(name) =>
+            {
+                arg.get_required_permissions(predicates)
+            }
+
             vir::Expr::PredicateAccessPredicate(_, box place, _perm_amount, _) => {
                 debug_assert!(place.is_place());
                 let epsilon = PermAmount::Read;
@@ -395,6 +403,9 @@ impl ExprPermissionsGetter for vir::Expr {
                 perm_difference(body.get_permissions(predicates), vars_places)
             }
 
+            vir::Expr::PredicateAccessPredicate(ref name, _, _, _)
+                if !predicates.contains_key(name) => HashSet::new(),
+
             vir::Expr::PredicateAccessPredicate(_, box ref arg, perm_amount, _) => {
                 let opt_perm = if arg.is_place() {
                     Some(match arg.get_label() {
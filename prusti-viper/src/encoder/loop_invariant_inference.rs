@@ -0,0 +1,290 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Inference of the invariants of simple numeric loops that have no
+//! `body_invariant!`. Only facts that are inductive by construction are
+//! inferred, so that an inferred invariant can never fail:
+//! - the guard of the loop, which holds whenever the body is entered, if it
+//!   compares integer variables and constants;
+//! - the monotonicity of counters and accumulators: an integer variable that
+//!   the loop only increases (decreases) by non-negative values is at least
+//!   (at most) its value when the invariant is reached for the first time.
+
+use crate::encoder::mir_encoder::{MirEncoder, PlaceEncoder};
+use prusti_common::vir;
+use prusti_interface::environment::BasicBlockIndex;
+use rustc_middle::mir;
+use rustc_middle::ty;
+use std::collections::HashSet;
+
+/// A fact that holds whenever a loop reaches its invariant.
+#[derive(Debug)]
+pub enum InferredInvariant<'tcx> {
+    /// The loop guard `left op right`, negated if the loop continues when
+    /// the comparison is false.
+    Guard {
+        op: mir::BinOp,
+        left: mir::Operand<'tcx>,
+        right: mir::Operand<'tcx>,
+        negated: bool,
+    },
+    /// The variable `local` only increases, or only decreases, in the loop.
+    Monotonic {
+        local: mir::Local,
+        increasing: bool,
+    },
+}
+
+/// Infer the invariants of the loop with the blocks `loop_body`. The
+/// invariant is reached right after `guard_block`, if any, which evaluates
+/// the guard of the loop.
+pub fn infer_loop_invariants<'p, 'v: 'p, 'tcx: 'v>(
+    mir_encoder: &MirEncoder<'p, 'v, 'tcx>,
+    mir: &mir::Body<'tcx>,
+    loop_body: &[BasicBlockIndex],
+    guard_block: Option<BasicBlockIndex>,
+) -> Vec<InferredInvariant<'tcx>> {
+    let inference = LoopInvariantInference {
+        mir_encoder,
+        mir,
+        loop_body: loop_body.iter().cloned().collect(),
+    };
+    let mut invariants = vec![];
+    if let Some(guard) = guard_block.and_then(|block| inference.infer_guard(block)) {
+        invariants.push(guard);
+    }
+    invariants.extend(inference.infer_monotonic_variables());
+    invariants
+}
+
+struct LoopInvariantInference<'a, 'p: 'a, 'v: 'p, 'tcx: 'v> {
+    mir_encoder: &'a MirEncoder<'p, 'v, 'tcx>,
+    mir: &'a mir::Body<'tcx>,
+    loop_body: HashSet<BasicBlockIndex>,
+}
+
+impl<'a, 'p: 'a, 'v: 'p, 'tcx: 'v> LoopInvariantInference<'a, 'p, 'v, 'tcx> {
+    fn is_integer(&self, local: mir::Local) -> bool {
+        matches!(
+            self.mir_encoder.get_local_ty(local).kind(),
+            ty::TyKind::Int(_) | ty::TyKind::Uint(_)
+        )
+    }
+
+    /// The statements of the loop body.
+    fn loop_statements(&self) -> impl Iterator<Item = &'a mir::Statement<'tcx>> + '_ {
+        let mir = self.mir;
+        let mut blocks: Vec<_> = self.loop_body.iter().cloned().collect();
+        blocks.sort();
+        blocks.into_iter().flat_map(move |bb| mir[bb].statements.iter())
+    }
+
+    /// The right-hand sides of the assignments to `local` in the loop body.
+    fn loop_assignments(&self, local: mir::Local) -> Vec<&'a mir::Rvalue<'tcx>> {
+        self.loop_statements()
+            .filter_map(|stmt| match stmt.kind {
+                mir::StatementKind::Assign(box (ref place, ref rvalue))
+                    if place.as_local() == Some(local) => Some(rvalue),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn infer_guard(&self, guard_block: BasicBlockIndex) -> Option<InferredInvariant<'tcx>> {
+        let block = &self.mir[guard_block];
+        let (discr, targets) = match block.terminator().kind {
+            mir::TerminatorKind::SwitchInt { ref discr, switch_ty, ref targets }
+                if switch_ty.is_bool() => (discr, targets),
+            _ => return None,
+        };
+        let discr_local = discr.place()?.as_local()?;
+        let false_target = targets.iter().find(|&(value, _)| value == 0)?.1;
+        let negated = match (
+            self.loop_body.contains(&false_target),
+            self.loop_body.contains(&targets.otherwise()),
+        ) {
+            (false, true) => false,
+            (true, false) => true,
+            _ => return None,
+        };
+        // The comparison must be the last assignment of the discriminant.
+        let (index, op, left, right) = block.statements.iter().enumerate().rev()
+            .find_map(|(index, stmt)| match stmt.kind {
+                mir::StatementKind::Assign(box (ref place, ref rvalue))
+                    if place.as_local() == Some(discr_local) => Some((index, rvalue)),
+                _ => None,
+            })
+            .and_then(|(index, rvalue)| match rvalue {
+                mir::Rvalue::BinaryOp(op, left, right) => Some((index, *op, left, right)),
+                _ => None,
+            })?;
+        match op {
+            mir::BinOp::Lt | mir::BinOp::Le | mir::BinOp::Gt | mir::BinOp::Ge
+            | mir::BinOp::Eq | mir::BinOp::Ne => {}
+            _ => return None,
+        }
+        let left = self.resolve_guard_operand(guard_block, index, left)?;
+        let right = self.resolve_guard_operand(guard_block, index, right)?;
+        Some(InferredInvariant::Guard { op, left, right, negated })
+    }
+
+    /// Resolve an operand of the comparison at `index` of the guard block to
+    /// a constant or to an integer variable that keeps its value until the
+    /// end of the block.
+    fn resolve_guard_operand(
+        &self,
+        guard_block: BasicBlockIndex,
+        index: usize,
+        operand: &mir::Operand<'tcx>,
+    ) -> Option<mir::Operand<'tcx>> {
+        let place = match operand {
+            mir::Operand::Constant(_) => return Some(operand.clone()),
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => place,
+        };
+        let mut local = place.as_local()?;
+        let statements = &self.mir[guard_block].statements;
+        // Follow the copies into temporaries that precede the comparison.
+        while self.mir.local_kind(local) == mir::LocalKind::Temp {
+            local = statements[..index].iter().rev()
+                .find_map(|stmt| match stmt.kind {
+                    mir::StatementKind::Assign(box (ref lhs, ref rvalue))
+                        if lhs.as_local() == Some(local) => Some(rvalue),
+                    _ => None,
+                })
+                .and_then(|rvalue| match rvalue {
+                    mir::Rvalue::Use(mir::Operand::Copy(place))
+                    | mir::Rvalue::Use(mir::Operand::Move(place)) => place.as_local(),
+                    _ => None,
+                })?;
+        }
+        let is_assigned = statements.iter().any(|stmt| matches!(
+            stmt.kind,
+            mir::StatementKind::Assign(box (ref lhs, _)) if lhs.local == local
+        ));
+        if !self.is_integer(local) || is_assigned {
+            return None;
+        }
+        Some(mir::Operand::Copy(local.into()))
+    }
+
+    fn infer_monotonic_variables(&self) -> Vec<InferredInvariant<'tcx>> {
+        let mut candidates: Vec<mir::Local> = self.loop_statements()
+            .filter_map(|stmt| match stmt.kind {
+                mir::StatementKind::Assign(box (ref place, _)) => place.as_local(),
+                _ => None,
+            })
+            .filter(|&local| {
+                self.mir.local_kind(local) != mir::LocalKind::Temp && self.is_integer(local)
+            })
+            .collect();
+        candidates.sort();
+        candidates.dedup();
+        candidates.into_iter()
+            .filter(|&local| !self.is_borrowed(local) && !self.is_call_destination(local))
+            .filter_map(|local| {
+                let mut directions = self.loop_assignments(local).into_iter()
+                    .map(|rvalue| self.update_direction(local, rvalue));
+                let increasing = directions.next()??;
+                if directions.all(|direction| direction == Some(increasing)) {
+                    Some(InferredInvariant::Monotonic { local, increasing })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Whether `local` is borrowed anywhere in the procedure, so that it could
+    /// be modified through a reference.
+    fn is_borrowed(&self, local: mir::Local) -> bool {
+        self.mir.basic_blocks().iter()
+            .flat_map(|block| block.statements.iter())
+            .any(|stmt| matches!(
+                stmt.kind,
+                mir::StatementKind::Assign(box (_, mir::Rvalue::Ref(_, _, ref place)))
+                | mir::StatementKind::Assign(box (_, mir::Rvalue::AddressOf(_, ref place)))
+                    if place.local == local
+            ))
+    }
+
+    fn is_call_destination(&self, local: mir::Local) -> bool {
+        self.loop_body.iter().any(|&bb| matches!(
+            self.mir[bb].terminator().kind,
+            mir::TerminatorKind::Call { destination: Some((ref place, _)), .. }
+                if place.local == local
+        ))
+    }
+
+    /// Whether the assignment `local = rvalue` increases (`Some(true)`) or
+    /// decreases (`Some(false)`) the variable by a non-negative value.
+    fn update_direction(&self, local: mir::Local, rvalue: &mir::Rvalue<'tcx>) -> Option<bool> {
+        let (op, left, right) = match rvalue {
+            mir::Rvalue::BinaryOp(op, left, right) => (*op, left, right),
+            // `local = move (tmp.0)`, where `tmp` is the result of a checked operation.
+            mir::Rvalue::Use(mir::Operand::Move(place)) | mir::Rvalue::Use(mir::Operand::Copy(place))
+                if place.projection.len() == 1 =>
+            {
+                match self.unique_loop_assignment(place.local)? {
+                    mir::Rvalue::CheckedBinaryOp(op, left, right) => (*op, left, right),
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+        match op {
+            mir::BinOp::Add if self.is_operand_of(local, left) && self.is_non_negative(right) => {
+                Some(true)
+            }
+            mir::BinOp::Add if self.is_operand_of(local, right) && self.is_non_negative(left) => {
+                Some(true)
+            }
+            mir::BinOp::Sub if self.is_operand_of(local, left) && self.is_non_negative(right) => {
+                Some(false)
+            }
+            _ => None,
+        }
+    }
+
+    fn unique_loop_assignment(&self, local: mir::Local) -> Option<&'a mir::Rvalue<'tcx>> {
+        let assignments = self.loop_assignments(local);
+        if assignments.len() == 1 {
+            Some(assignments[0])
+        } else {
+            None
+        }
+    }
+
+    /// Whether `operand` reads `local`, directly or through a temporary.
+    fn is_operand_of(&self, local: mir::Local, operand: &mir::Operand<'tcx>) -> bool {
+        let operand_local = match operand.place().and_then(|place| place.as_local()) {
+            Some(operand_local) => operand_local,
+            None => return false,
+        };
+        if operand_local == local {
+            return true;
+        }
+        self.mir.local_kind(operand_local) == mir::LocalKind::Temp
+            && matches!(
+                self.unique_loop_assignment(operand_local),
+                Some(mir::Rvalue::Use(mir::Operand::Copy(place)))
+                | Some(mir::Rvalue::Use(mir::Operand::Move(place)))
+                    if place.as_local() == Some(local)
+            )
+    }
+
+    fn is_non_negative(&self, operand: &mir::Operand<'tcx>) -> bool {
+        if let ty::TyKind::Uint(_) = self.mir_encoder.get_operand_ty(operand).kind() {
+            return true;
+        }
+        match operand {
+            mir::Operand::Constant(_) => matches!(
+                self.mir_encoder.encode_operand_expr(operand),
+                Ok(vir::Expr::Const(vir::Const::Int(value), _)) if value >= 0
+            ),
+            _ => false,
+        }
+    }
+}
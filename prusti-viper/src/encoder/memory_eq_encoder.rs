@@ -132,12 +132,25 @@ impl MemoryEqEncoder {
             ty::TyKind::Bool
             | ty::TyKind::Int(_)
             | ty::TyKind::Uint(_)
+            | ty::TyKind::Float(_)
             | ty::TyKind::Char => {
                 let field = encoder.encode_value_field(self_ty);
                 let first_field = first.clone().field(field.clone());
                 let second_field = second.clone().field(field);
                 Some(vir::Expr::eq_cmp(first_field, second_field))
             }
+            ty::TyKind::Adt(_, _) if encoder.get_rc_pointee_ty(self_ty).is_some() => {
+                // Reference-counted pointers are equal if their contents are.
+                let pointee_ty = encoder.get_rc_pointee_ty(self_ty).unwrap();
+                let content_field = encoder.encode_dereference_field(pointee_ty)?;
+                Some(self.encode_memory_eq_func_app(
+                    encoder,
+                    first.clone().field(content_field.clone()),
+                    second.clone().field(content_field),
+                    pointee_ty,
+                    vir::Position::default(),
+                )?)
+            }
             ty::TyKind::Adt(adt_def, subst) if !adt_def.is_box() => {
                 // TODO: If adt_def contains fields of unsupported type,
                 // we should return None.
@@ -4,13 +4,14 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use crate::encoder::builtin_encoder::BuiltinFunctionKind;
+use crate::encoder::builtin_encoder::{BuiltinFunctionKind, IntArithMode};
 use crate::encoder::errors::{
     ErrorCtxt, PanicCause, SpannedEncodingError, EncodingError, WithSpan,
     SpannedEncodingResult, EncodingResult
 };
 use crate::encoder::Encoder;
-use prusti_common::vir;
+use crate::encoder::float_encoder;
+use prusti_common::vir::{self, ExprIterator};
 use prusti_common::config;
 use rustc_hir::def_id::DefId;
 use rustc_middle::{mir, ty};
@@ -22,6 +23,12 @@ use std::collections::HashMap;
 
 pub static PRECONDITION_LABEL: &'static str = "pre";
 pub static WAND_LHS_LABEL: &'static str = "lhs";
+/// The ghost variable that counts the credits left to a procedure with a
+/// `#[complexity]` annotation.
+pub static CREDITS_VAR_NAME: &'static str = "_credits";
+/// The ghost variable that stands for the termination measure of a
+/// `#[decreases]` annotation or of a `body_variant!`.
+pub static MEASURE_VAR_NAME: &'static str = "_measure";
 
 pub trait PlaceEncoder<'v, 'tcx: 'v> {
 
@@ -107,6 +114,7 @@ pub trait PlaceEncoder<'v, 'tcx: 'v> {
                     ty::TyKind::Bool
                     | ty::TyKind::Int(_)
                     | ty::TyKind::Uint(_)
+                    | ty::TyKind::Float(_)
                     | ty::TyKind::RawPtr(_)
                     | ty::TyKind::Ref(_, _, _) => {
                         panic!("Type {:?} has no fields", base_ty)
@@ -209,6 +217,25 @@ pub trait PlaceEncoder<'v, 'tcx: 'v> {
                 (encoded_base, base_ty, Some(variant_index.into()))
             }
 
+            mir::ProjectionElem::ConstantIndex { .. } => {
+                // The elements of arrays and slices are not places of the
+                // encoding: they are read with the lookup functions of the
+                // array or slice type.
+                return Err(EncodingError::unsupported(
+                    "constant indexing of arrays and slices is only supported when \
+                    reading elements of a primitive type"
+                ));
+            }
+
+            mir::ProjectionElem::Subslice { .. } => {
+                // The subslices of slice patterns are encoded where they are
+                // borrowed: as views of the sliced place in pure functions,
+                // and as copies of it in procedures.
+                return Err(EncodingError::unsupported(
+                    "subslices are only supported when they are borrowed by slice patterns"
+                ));
+            }
+
             x => unimplemented!("{:?}", x),
         })
     }
@@ -257,6 +284,24 @@ pub trait PlaceEncoder<'v, 'tcx: 'v> {
         })
     }
 
+    /// Dereference the encoded place until its type is neither a reference
+    /// nor a box. This normalizes places whose content is accessed through
+    /// (nested) references or boxes, such as the discriminant of a
+    /// `&Box<Enum>`: boxes have the snapshot of their content, so only the
+    /// dereference fields of the heap remain to be inserted.
+    fn encode_auto_deref(
+        &self,
+        encoded_place: vir::Expr,
+        place_ty: ty::Ty<'tcx>,
+    ) -> EncodingResult<(vir::Expr, ty::Ty<'tcx>)> {
+        let mut result = (encoded_place, place_ty);
+        while result.1.is_box() || matches!(result.1.kind(), ty::TyKind::Ref(..)) {
+            let (encoded_deref, deref_ty, _) = self.encode_deref(result.0, result.1)?;
+            result = (encoded_deref, deref_ty);
+        }
+        Ok(result)
+    }
+
     fn can_be_dereferenced(&self, base_ty: ty::Ty<'tcx>) -> bool {
         trace!("can_be_dereferenced {}", base_ty);
         match base_ty.kind() {
@@ -308,7 +353,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> PlaceEncoder<'v, 'tcx> for FakeMirEncoder<'p, 'v, 'tc
     }
 
     fn get_local_ty(&self, local: mir::Local) -> ty::Ty<'tcx> {
-        self.tys[local]
+        self.encoder.reveal_opaque_type(self.tys[local])
     }
 
     fn get_local_span(&self, _local: mir::Local) -> Span {
@@ -331,7 +376,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> PlaceEncoder<'v, 'tcx> for MirEncoder<'p, 'v, 'tcx> {
     }
 
     fn get_local_ty(&self, local: mir::Local) -> ty::Ty<'tcx> {
-        self.mir.local_decls[local].ty
+        self.encoder.reveal_opaque_type(self.mir.local_decls[local].ty)
     }
 
     fn get_local_span(&self, local: mir::Local) -> Span {
@@ -410,6 +455,50 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
         })
     }
 
+    /// Encode the Viper reference that the raw pointer `operand` points to.
+    pub fn encode_pointer_operand_address(
+        &self,
+        operand: &mir::Operand<'tcx>,
+    ) -> EncodingResult<vir::Expr> {
+        let pointee_ty = match self.get_operand_ty(operand).kind() {
+            ty::TyKind::RawPtr(ty::TypeAndMut { ty, .. }) => *ty,
+            ref x => unreachable!("{:?} is not a raw pointer", x),
+        };
+        match operand {
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => {
+                let (encoded_place, _, _) = self.encode_place(place)?;
+                let address_field = self.encoder.encode_dereference_field(pointee_ty)?;
+                Ok(encoded_place.field(address_field))
+            }
+            mir::Operand::Constant(_) => Err(EncodingError::unsupported(
+                "comparisons with constant raw pointers are not supported"
+            )),
+        }
+    }
+
+    /// Encode the comparison of two raw pointers by their address. Whether
+    /// two addresses are equal is an uninterpreted relation, which is only
+    /// known to be reflexive and symmetric.
+    pub fn encode_pointer_comparison(
+        &self,
+        op: mir::BinOp,
+        left: &mir::Operand<'tcx>,
+        right: &mir::Operand<'tcx>,
+    ) -> EncodingResult<vir::Expr> {
+        let address_eq = self.encoder.encode_address_eq(
+            self.encode_pointer_operand_address(left)?,
+            self.encode_pointer_operand_address(right)?,
+        );
+        match op {
+            mir::BinOp::Eq => Ok(address_eq),
+            mir::BinOp::Ne => Ok(vir::Expr::not(address_eq)),
+            _ => Err(EncodingError::unsupported(format!(
+                "operation '{:?}' on raw pointers is not supported",
+                op,
+            ))),
+        }
+    }
+
     pub fn get_operand_ty(&self, operand: &mir::Operand<'tcx>) -> ty::Ty<'tcx> {
         debug!("Get operand ty {:?}", operand);
         // match operand {
@@ -419,7 +508,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
         //     }
         //     &mir::Operand::Constant(box mir::Constant { ty, .. }) => ty,
         // }
-        operand.ty(self.mir, self.encoder.env().tcx())
+        self.encoder.reveal_opaque_type(operand.ty(self.mir, self.encoder.env().tcx()))
     }
 
     /// Returns an `vir::Type` that corresponds to the type of the value of the operand
@@ -451,6 +540,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
         right: vir::Expr,
         ty: ty::Ty<'tcx>,
     ) -> EncodingResult<vir::Expr> {
+        if ty.is_floating_point() {
+            return float_encoder::encode_bin_op(op, left, right);
+        }
         let is_bool = ty.kind() == &ty::TyKind::Bool;
         Ok(match op {
             mir::BinOp::Eq => vir::Expr::eq_cmp(left, right),
@@ -459,6 +551,12 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
             mir::BinOp::Ge => vir::Expr::ge_cmp(left, right),
             mir::BinOp::Lt => vir::Expr::lt_cmp(left, right),
             mir::BinOp::Le => vir::Expr::le_cmp(left, right),
+            mir::BinOp::Add |
+            mir::BinOp::Sub |
+            mir::BinOp::Mul if ty.is_integral() && self.has_wrapping_arith() => {
+                let result = self.encode_unbounded_arith_op(op, left, right);
+                return self.encode_wrapped_int(result, ty);
+            }
             mir::BinOp::Add => vir::Expr::add(left, right),
             mir::BinOp::Sub => vir::Expr::sub(left, right),
             mir::BinOp::Rem => vir::Expr::rem(left, right),
@@ -469,11 +567,29 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
             mir::BinOp::BitXor if is_bool => vir::Expr::xor(left, right),
             mir::BinOp::BitAnd |
             mir::BinOp::BitOr |
-            mir::BinOp::BitXor => {
-                return Err(EncodingError::unsupported(
-                    "bitwise operations on non-boolean types are not supported"
-                ))
+            mir::BinOp::BitXor |
+            mir::BinOp::Shl |
+            mir::BinOp::Shr if self.is_bitvector() => {
+                return self.encode_bit_precise_op(op, left, right, ty);
             }
+            mir::BinOp::BitAnd => {
+                // Bit flag tests such as `x & FLAG != 0` mask with a constant,
+                // which is encoded precisely by extracting the masked bits.
+                // Other conjunctions are encoded with an uninterpreted function.
+                let masked = match (get_mask(&left), get_mask(&right)) {
+                    (_, Some(mask)) => encode_bit_mask(left.clone(), mask),
+                    (Some(mask), _) => encode_bit_mask(right.clone(), mask),
+                    _ => None,
+                };
+                match masked {
+                    Some(expr) => expr,
+                    None => self.encode_bitwise_op_function_app(op, left, right, ty)?,
+                }
+            }
+            mir::BinOp::BitOr |
+            mir::BinOp::BitXor => self.encode_bitwise_op_function_app(op, left, right, ty)?,
+            mir::BinOp::Shl |
+            mir::BinOp::Shr => self.encode_shift(op, left, right, ty)?,
             unsupported_op => {
                 return Err(EncodingError::unsupported(format!(
                     "operation '{:?}' is not supported",
@@ -483,13 +599,252 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
         })
     }
 
-    pub fn encode_unary_op_expr(&self, op: mir::UnOp, expr: vir::Expr) -> vir::Expr {
+    /// Encode a bitwise operation on integers as the application of an
+    /// uninterpreted function, which is axiomatized by common identities.
+    fn encode_bitwise_op_function_app(
+        &self,
+        op: mir::BinOp,
+        left: vir::Expr,
+        right: vir::Expr,
+        ty: ty::Ty<'tcx>,
+    ) -> EncodingResult<vir::Expr> {
+        if !ty.is_integral() {
+            return Err(EncodingError::unsupported(
+                "bitwise operations on non-boolean types are not supported"
+            ));
+        }
+        let function_name = self.encoder.encode_bitwise_op_function_use(op, ty)?;
+        Ok(vir::Expr::func_app(
+            function_name,
+            vec![left, right],
+            vec![
+                vir::LocalVar::new("left", vir::Type::Int),
+                vir::LocalVar::new("right", vir::Type::Int),
+            ],
+            vir::Type::Int,
+            vir::Position::default(),
+        ))
+    }
+
+    /// Whether the procedure is marked with `#[bitvector]`, or with
+    /// `#[bit_precise]`, which implies it. The bitwise operations on integers
+    /// are then encoded precisely, bit by bit. The integers are still encoded
+    /// as unbounded integers, so no conversion is needed when they are passed
+    /// to or returned from other procedures.
+    fn is_bitvector(&self) -> bool {
+        self.encoder.env().has_prusti_attribute(self.def_id, "bitvector")
+            || self.is_bit_precise()
+    }
+
+    /// Whether the procedure is marked with `#[bit_precise]` (or the
+    /// `BIT_PRECISE` flag is set), in which case its integers behave like
+    /// machine integers: additions, subtractions, multiplications and
+    /// negations wrap around the range of the integer type, and bitwise
+    /// operations are encoded as with `#[bitvector]`. Overflows are still
+    /// reported if `CHECK_OVERFLOWS` is enabled.
+    fn is_bit_precise(&self) -> bool {
+        config::bit_precise()
+            || self.encoder.env().has_prusti_attribute(self.def_id, "bit_precise")
+    }
+
+    /// Whether additions, subtractions, multiplications and negations wrap
+    /// around the range of the integer type: with `#[bit_precise]`, or in a
+    /// module whose overflow checks are disabled while the ranges of the
+    /// integer types are encoded.
+    fn has_wrapping_arith(&self) -> bool {
+        self.is_bit_precise() || (config::integer_bounds() && !config::check_overflows())
+    }
+
+    /// The number of bits of an integer type.
+    fn get_int_bit_width(&self, ty: ty::Ty<'tcx>) -> Option<u64> {
+        let pointer_width = self.encoder.env().tcx().data_layout.pointer_size.bits();
+        match ty.kind() {
+            ty::TyKind::Int(int_ty) => Some(int_ty.bit_width().unwrap_or(pointer_width)),
+            ty::TyKind::Uint(uint_ty) => Some(uint_ty.bit_width().unwrap_or(pointer_width)),
+            _ => None,
+        }
+    }
+
+    /// Wrap an integer around the range of the integer type `ty`, as done by
+    /// the wrapping arithmetic operations of Rust.
+    pub fn encode_wrapped_int(&self, value: vir::Expr, ty: ty::Ty<'tcx>) -> EncodingResult<vir::Expr> {
+        let bits = self.get_int_bit_width(ty).ok_or_else(|| EncodingError::unsupported(
+            format!("wrapping arithmetic on type '{:?}' is not supported", ty)
+        ))?;
+        let unsigned = vir::Expr::modulo(value, encode_power_of_two(bits));
+        Ok(if ty.is_signed() {
+            vir::Expr::ite(
+                vir::Expr::ge_cmp(unsigned.clone(), encode_power_of_two(bits - 1)),
+                vir::Expr::sub(unsigned.clone(), encode_power_of_two(bits)),
+                unsigned,
+            )
+        } else {
+            unsigned
+        })
+    }
+
+    /// Encode the arithmetic method of the integer type `ty` with the mode
+    /// `mode` (e.g. `saturating_add`) as its resulting value and whether the
+    /// operation overflowed. The value of a checked operation is only
+    /// meaningful if it did not overflow.
+    pub fn encode_int_arith_method(
+        &self,
+        mode: IntArithMode,
+        op: mir::BinOp,
+        left: vir::Expr,
+        right: vir::Expr,
+        ty: ty::Ty<'tcx>,
+    ) -> EncodingResult<(vir::Expr, vir::Expr)> {
+        let bits = self.get_int_bit_width(ty).ok_or_else(|| EncodingError::unsupported(
+            format!("arithmetic methods of type '{:?}' are not supported", ty)
+        ))?;
+        let (min, max): (vir::Expr, vir::Expr) = if ty.is_signed() {
+            (
+                vir::Expr::minus(encode_power_of_two(bits - 1)),
+                vir::Expr::sub(encode_power_of_two(bits - 1), 1.into()),
+            )
+        } else {
+            (0.into(), vir::Expr::sub(encode_power_of_two(bits), 1.into()))
+        };
+        let result = self.encode_unbounded_arith_op(op, left, right);
+        let overflow = vir::Expr::or(
+            vir::Expr::lt_cmp(result.clone(), min.clone()),
+            vir::Expr::gt_cmp(result.clone(), max.clone()),
+        );
+        let value = match mode {
+            IntArithMode::Wrapping | IntArithMode::Overflowing => {
+                self.encode_wrapped_int(result, ty)?
+            }
+            IntArithMode::Checked => result,
+            IntArithMode::Saturating => vir::Expr::ite(
+                vir::Expr::lt_cmp(result.clone(), min.clone()),
+                min,
+                vir::Expr::ite(vir::Expr::gt_cmp(result.clone(), max.clone()), max, result),
+            ),
+        };
+        Ok((value, overflow))
+    }
+
+    /// Encode a bitwise operation or a shift on integers of type `ty`, in
+    /// terms of the bits of the operands in two's complement.
+    fn encode_bit_precise_op(
+        &self,
+        op: mir::BinOp,
+        left: vir::Expr,
+        right: vir::Expr,
+        ty: ty::Ty<'tcx>,
+    ) -> EncodingResult<vir::Expr> {
+        let bits = self.get_int_bit_width(ty).ok_or_else(|| EncodingError::unsupported(
+            format!("bitwise operations on type '{:?}' are not supported", ty)
+        ))?;
+        let wrap = |value: vir::Expr| -> vir::Expr {
+            self.encode_wrapped_int(value, ty).unwrap()
+        };
+        Ok(match op {
+            mir::BinOp::Shl | mir::BinOp::Shr => {
+                return self.encode_shift(op, left, right, ty);
+            }
+            _ => {
+                let bit = |value: &vir::Expr, index: u64| vir::Expr::eq_cmp(
+                    vir::Expr::modulo(
+                        vir::Expr::div(value.clone(), encode_power_of_two(index)),
+                        2.into(),
+                    ),
+                    1.into(),
+                );
+                let sum = (0..bits).fold(0.into(), |sum, index| {
+                    let (left_bit, right_bit) = (bit(&left, index), bit(&right, index));
+                    let is_set = match op {
+                        mir::BinOp::BitAnd => vir::Expr::and(left_bit, right_bit),
+                        mir::BinOp::BitOr => vir::Expr::or(left_bit, right_bit),
+                        _ => vir::Expr::xor(left_bit, right_bit),
+                    };
+                    vir::Expr::add(
+                        sum,
+                        vir::Expr::ite(is_set, encode_power_of_two(index), 0.into()),
+                    )
+                });
+                wrap(sum)
+            }
+        })
+    }
+
+    /// Encode a shift of `left` by `right` bits. As in Rust, the bits shifted
+    /// out of a left shift are discarded, i.e. the result wraps around the
+    /// range of the integer type `ty`. Only the shift amount can overflow
+    /// (see `encode_bin_op_check`).
+    fn encode_shift(
+        &self,
+        op: mir::BinOp,
+        left: vir::Expr,
+        right: vir::Expr,
+        ty: ty::Ty<'tcx>,
+    ) -> EncodingResult<vir::Expr> {
+        let bits = self.get_int_bit_width(ty).ok_or_else(|| EncodingError::unsupported(
+            format!("shift operations on type '{:?}' are not supported", ty)
+        ))?;
+        let shift = |amount: u64| -> vir::Expr {
+            match op {
+                mir::BinOp::Shl => {
+                    let shifted = vir::Expr::mul(left.clone(), encode_power_of_two(amount));
+                    self.encode_wrapped_int(shifted, ty).unwrap()
+                }
+                // Viper's division rounds towards negative infinity, which
+                // matches the arithmetic right shift of signed integers.
+                _ => vir::Expr::div(left.clone(), encode_power_of_two(amount)),
+            }
+        };
+        Ok(if let Some(amount) = get_mask(&right) {
+            shift(amount.min(bits as u128 - 1) as u64)
+        } else {
+            // Select the shift amount among all the possible ones.
+            (0..bits).rev().fold(0.into(), |rest, amount| vir::Expr::ite(
+                vir::Expr::eq_cmp(right.clone(), amount.into()),
+                shift(amount),
+                rest,
+            ))
+        })
+    }
+
+    /// Encode an addition, subtraction or multiplication of unbounded integers.
+    fn encode_unbounded_arith_op(&self, op: mir::BinOp, left: vir::Expr, right: vir::Expr) -> vir::Expr {
+        match op {
+            mir::BinOp::Add => vir::Expr::add(left, right),
+            mir::BinOp::Sub => vir::Expr::sub(left, right),
+            mir::BinOp::Mul => vir::Expr::mul(left, right),
+            _ => unreachable!("{:?}", op),
+        }
+    }
+
+    pub fn encode_unary_op_expr(&self, op: mir::UnOp, expr: vir::Expr, ty: ty::Ty<'tcx>) -> vir::Expr {
         match op {
             mir::UnOp::Not => vir::Expr::not(expr),
+            mir::UnOp::Neg if ty.is_floating_point() => float_encoder::encode_neg(expr),
+            mir::UnOp::Neg if ty.is_integral() && self.has_wrapping_arith() => {
+                self.encode_wrapped_int(vir::Expr::minus(expr), ty).unwrap()
+            }
             mir::UnOp::Neg => vir::Expr::minus(expr),
         }
     }
 
+    /// The condition under which the unary operation overflows, if overflows
+    /// are checked and the operation can overflow: negating the minimum of a
+    /// signed integer type.
+    pub fn encode_unary_op_check(
+        &self,
+        op: mir::UnOp,
+        expr: vir::Expr,
+        ty: ty::Ty<'tcx>,
+    ) -> Option<vir::Expr> {
+        match (op, ty.kind()) {
+            (mir::UnOp::Neg, ty::TyKind::Int(_)) if config::check_overflows() => {
+                let bits = self.get_int_bit_width(ty)?;
+                Some(vir::Expr::eq_cmp(expr, vir::Expr::minus(encode_power_of_two(bits - 1))))
+            }
+            _ => None,
+        }
+    }
+
     /// Returns `true` is an overflow happened
     pub fn encode_bin_op_check(
         &self,
@@ -501,74 +856,87 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
         if !op.is_checkable() || !config::check_overflows() {
             return Ok(false.into())
         } else {
-            let result = self.encode_bin_op_expr(op, left.clone(), right.clone(), ty)?;
-
             Ok(match op {
-                mir::BinOp::Add | mir::BinOp::Mul | mir::BinOp::Sub => match ty.kind() {
-                    // Unsigned
-                    ty::TyKind::Uint(ast::UintTy::U8) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::u8::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::u8::MAX.into()),
-                    ),
-                    ty::TyKind::Uint(ast::UintTy::U16) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::u16::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::u16::MAX.into()),
-                    ),
-                    ty::TyKind::Uint(ast::UintTy::U32) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::u32::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::u32::MAX.into()),
-                    ),
-                    ty::TyKind::Uint(ast::UintTy::U64) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::u64::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::u64::MAX.into()),
-                    ),
-                    ty::TyKind::Uint(ast::UintTy::U128) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::u128::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::u128::MAX.into()),
-                    ),
-                    ty::TyKind::Uint(ast::UintTy::Usize) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::usize::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::usize::MAX.into()),
-                    ),
-                    // Signed
-                    ty::TyKind::Int(ast::IntTy::I8) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::i8::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::i8::MAX.into()),
-                    ),
-                    ty::TyKind::Int(ast::IntTy::I16) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::i16::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::i16::MIN.into()),
-                    ),
-                    ty::TyKind::Int(ast::IntTy::I32) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::i32::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::i32::MAX.into()),
-                    ),
-                    ty::TyKind::Int(ast::IntTy::I64) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::i64::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::i64::MAX.into()),
-                    ),
-                    ty::TyKind::Int(ast::IntTy::I128) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::i128::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::i128::MAX.into()),
-                    ),
-                    ty::TyKind::Int(ast::IntTy::Isize) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::isize::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::isize::MAX.into()),
-                    ),
+                mir::BinOp::Add | mir::BinOp::Mul | mir::BinOp::Sub => {
+                    // The overflow is detected on the result of the operation
+                    // on unbounded integers, before it wraps around.
+                    let result = self.encode_unbounded_arith_op(op, left, right);
+                    match ty.kind() {
+                        // Unsigned
+                        ty::TyKind::Uint(ast::UintTy::U8) => vir::Expr::or(
+                            vir::Expr::lt_cmp(result.clone(), std::u8::MIN.into()),
+                            vir::Expr::gt_cmp(result, std::u8::MAX.into()),
+                        ),
+                        ty::TyKind::Uint(ast::UintTy::U16) => vir::Expr::or(
+                            vir::Expr::lt_cmp(result.clone(), std::u16::MIN.into()),
+                            vir::Expr::gt_cmp(result, std::u16::MAX.into()),
+                        ),
+                        ty::TyKind::Uint(ast::UintTy::U32) => vir::Expr::or(
+                            vir::Expr::lt_cmp(result.clone(), std::u32::MIN.into()),
+                            vir::Expr::gt_cmp(result, std::u32::MAX.into()),
+                        ),
+                        ty::TyKind::Uint(ast::UintTy::U64) => vir::Expr::or(
+                            vir::Expr::lt_cmp(result.clone(), std::u64::MIN.into()),
+                            vir::Expr::gt_cmp(result, std::u64::MAX.into()),
+                        ),
+                        ty::TyKind::Uint(ast::UintTy::U128) => vir::Expr::or(
+                            vir::Expr::lt_cmp(result.clone(), std::u128::MIN.into()),
+                            vir::Expr::gt_cmp(result, std::u128::MAX.into()),
+                        ),
+                        ty::TyKind::Uint(ast::UintTy::Usize) => vir::Expr::or(
+                            vir::Expr::lt_cmp(result.clone(), std::usize::MIN.into()),
+                            vir::Expr::gt_cmp(result, std::usize::MAX.into()),
+                        ),
+                        // Signed
+                        ty::TyKind::Int(ast::IntTy::I8) => vir::Expr::or(
+                            vir::Expr::lt_cmp(result.clone(), std::i8::MIN.into()),
+                            vir::Expr::gt_cmp(result, std::i8::MAX.into()),
+                        ),
+                        ty::TyKind::Int(ast::IntTy::I16) => vir::Expr::or(
+                            vir::Expr::lt_cmp(result.clone(), std::i16::MIN.into()),
+                            vir::Expr::gt_cmp(result, std::i16::MIN.into()),
+                        ),
+                        ty::TyKind::Int(ast::IntTy::I32) => vir::Expr::or(
+                            vir::Expr::lt_cmp(result.clone(), std::i32::MIN.into()),
+                            vir::Expr::gt_cmp(result, std::i32::MAX.into()),
+                        ),
+                        ty::TyKind::Int(ast::IntTy::I64) => vir::Expr::or(
+                            vir::Expr::lt_cmp(result.clone(), std::i64::MIN.into()),
+                            vir::Expr::gt_cmp(result, std::i64::MAX.into()),
+                        ),
+                        ty::TyKind::Int(ast::IntTy::I128) => vir::Expr::or(
+                            vir::Expr::lt_cmp(result.clone(), std::i128::MIN.into()),
+                            vir::Expr::gt_cmp(result, std::i128::MAX.into()),
+                        ),
+                        ty::TyKind::Int(ast::IntTy::Isize) => vir::Expr::or(
+                            vir::Expr::lt_cmp(result.clone(), std::isize::MIN.into()),
+                            vir::Expr::gt_cmp(result, std::isize::MAX.into()),
+                        ),
 
-                    _ => {
-                        return Err(EncodingError::unsupported(format!(
-                            "overflow checks are unsupported for operation '{:?}' on type '{:?}'",
-                            op,
-                            ty,
-                        )));
+                        _ => {
+                            return Err(EncodingError::unsupported(format!(
+                                "overflow checks are unsupported for operation '{:?}' on type '{:?}'",
+                                op,
+                                ty,
+                            )));
+                        }
                     }
-                },
+                }
 
                 mir::BinOp::Shl | mir::BinOp::Shr => {
-                    return Err(EncodingError::unsupported(
-                        "overflow checks on a shift operation are unsupported",
-                    ));
+                    // The shift overflows if the shift amount is not smaller
+                    // than the number of bits of the shifted value.
+                    let bits = self.get_int_bit_width(ty).ok_or_else(|| {
+                        EncodingError::unsupported(format!(
+                            "overflow checks are unsupported for operation '{:?}' on type '{:?}'",
+                            op,
+                            ty,
+                        ))
+                    })?;
+                    vir::Expr::or(
+                        vir::Expr::lt_cmp(right.clone(), 0.into()),
+                        vir::Expr::ge_cmp(right, bits.into()),
+                    )
                 }
 
                 _ => unreachable!("{:?}", op),
@@ -584,6 +952,10 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
     ) -> SpannedEncodingResult<vir::Expr> {
         let src_ty = self.get_operand_ty(operand);
 
+        if let Some(encoded_val) = self.encode_masked_narrowing_cast(operand, dst_ty, span)? {
+            return Ok(encoded_val);
+        }
+
         let encoded_val = match (src_ty.kind(), dst_ty.kind()) {
             // Numeric casts that cannot fail
             | (ty::TyKind::Char, ty::TyKind::Char)
@@ -638,7 +1010,10 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
             | (ty::TyKind::Uint(_), ty::TyKind::Uint(_))
             => {
                 let encoded_operand = self.encode_operand_expr(operand).with_span(span)?;
-                if config::check_overflows() {
+                if config::truncate_casts() && dst_ty.is_integral() {
+                    // Keep the lower bits of the value, as Rust does
+                    self.encode_wrapped_int(encoded_operand, dst_ty).with_span(span)?
+                } else if config::check_overflows() {
                     // Check the cast
                     let function_name = self.encoder.encode_cast_function_use(src_ty, dst_ty)
                         .with_span(span)?;
@@ -665,6 +1040,12 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
                 }
             }
 
+            // Float casts that do not round. Both float types are encoded
+            // with the same domain, in which such a cast is the identity.
+            | (ty::TyKind::Float(ast::FloatTy::F32), ty::TyKind::Float(_))
+            | (ty::TyKind::Float(ast::FloatTy::F64), ty::TyKind::Float(ast::FloatTy::F64))
+            => self.encode_operand_expr(operand).with_span(span)?,
+
             _ => {
                 return Err(SpannedEncodingError::unsupported(
                     format!(
@@ -680,6 +1061,50 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
         Ok(encoded_val)
     }
 
+    /// Encode a narrowing cast to an unsigned type of the result of masking
+    /// with a constant, as in `(x & FLAG) as u8`. The masked value is not
+    /// negative, so the cast keeps its lower bits. Returns `None` if the cast
+    /// does not match this pattern.
+    fn encode_masked_narrowing_cast(
+        &self,
+        operand: &mir::Operand<'tcx>,
+        dst_ty: ty::Ty<'tcx>,
+        span: Span,
+    ) -> SpannedEncodingResult<Option<vir::Expr>> {
+        let local = match operand {
+            mir::Operand::Move(place) | mir::Operand::Copy(place) => place.as_local(),
+            mir::Operand::Constant(_) => None,
+        };
+        let dst_bits = match dst_ty.kind() {
+            ty::TyKind::Uint(uint_ty) => uint_ty.bit_width(),
+            _ => None,
+        };
+        let (local, dst_bits) = match (local, dst_bits) {
+            (Some(local), Some(dst_bits)) if dst_bits < 128 => (local, dst_bits),
+            _ => return Ok(None),
+        };
+        let is_masked = self.mir.basic_blocks().iter()
+            .flat_map(|bb_data| &bb_data.statements)
+            .any(|stmt| match stmt.kind {
+                mir::StatementKind::Assign(box (
+                    ref place,
+                    mir::Rvalue::BinaryOp(mir::BinOp::BitAnd, ref left, ref right),
+                )) if place.as_local() == Some(local) => {
+                    let is_constant = |operand: &mir::Operand| {
+                        matches!(operand, mir::Operand::Constant(_))
+                    };
+                    is_constant(left) || is_constant(right)
+                }
+                _ => false,
+            });
+        if !is_masked || !self.get_operand_ty(operand).is_integral() {
+            return Ok(None);
+        }
+        trace!("encode_masked_narrowing_cast({:?}, {:?})", operand, dst_ty);
+        let encoded_operand = self.encode_operand_expr(operand).with_span(span)?;
+        Ok(Some(vir::Expr::rem(encoded_operand, (1u128 << dst_bits).into())))
+    }
+
     pub fn encode_operand_place(
         &self,
         operand: &mir::Operand<'tcx>,
@@ -762,3 +1187,54 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
         }
     }
 }
+
+/// The description of the failure of an assert terminator. Unlike
+/// `AssertKind::description`, this also supports bounds checks.
+pub fn describe_assert_message(msg: &mir::AssertMessage) -> &'static str {
+    match msg {
+        mir::AssertKind::BoundsCheck { .. } => "index out of bounds",
+        _ => msg.description(),
+    }
+}
+
+/// Encode `2^exponent`, which might not fit in a `u128`.
+fn encode_power_of_two(exponent: u64) -> vir::Expr {
+    if exponent < 128 {
+        (1u128 << exponent).into()
+    } else {
+        vir::Expr::mul((1u128 << 127).into(), encode_power_of_two(exponent - 127))
+    }
+}
+
+/// The maximum number of set bits of a constant mask for which `x & mask` is
+/// encoded by extracting each bit of `x`.
+const MAX_MASK_BITS: u32 = 8;
+
+/// The value of `expr` if it is a non-negative integer constant.
+fn get_mask(expr: &vir::Expr) -> Option<u128> {
+    match expr {
+        vir::Expr::Const(vir::Const::Int(value), _) if *value >= 0 => Some(*value as u128),
+        vir::Expr::Const(vir::Const::BigInt(value), _) => value.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Encode `value & mask` as the sum of the bits of `value` that are set in
+/// `mask`. The bit `k` of `value` is `(value / 2^k) % 2`, also for negative
+/// values in two's complement, because Viper's integer division and modulo
+/// are Euclidean.
+fn encode_bit_mask(value: vir::Expr, mask: u128) -> Option<vir::Expr> {
+    if mask.count_ones() > MAX_MASK_BITS {
+        return None;
+    }
+    let bits = (0..128)
+        .filter(|bit| mask & (1u128 << bit) != 0)
+        .map(|bit| {
+            let power: vir::Expr = (1u128 << bit).into();
+            vir::Expr::mul(
+                vir::Expr::rem(vir::Expr::div(value.clone(), power.clone()), 2.into()),
+                power,
+            )
+        });
+    Some(bits.fold(0.into(), |sum, bit| vir::Expr::add(sum, bit)))
+}
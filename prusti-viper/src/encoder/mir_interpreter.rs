@@ -285,6 +285,12 @@ impl MultiExprBackwardInterpreterState {
         }
     }
 
+    pub fn map_exprs<F: FnMut(vir::Expr) -> vir::Expr>(&mut self, mut f: F) {
+        for expr in &mut self.exprs {
+            *expr = f(expr.clone());
+        }
+    }
+
     pub fn use_place(&self, sub_target: &vir::Expr) -> bool {
         trace!("use_place {:?}", sub_target);
         self.exprs.iter().any(|expr| expr.find(sub_target))
@@ -11,9 +11,11 @@ mod builtin_encoder;
 mod specs_closures_collector;
 mod encoder;
 mod errors;
+mod float_encoder;
 mod foldunfold;
 mod initialisation;
 mod loop_encoder;
+mod loop_invariant_inference;
 mod mir_encoder;
 mod mir_successor;
 mod mir_interpreter;
@@ -22,13 +24,17 @@ mod name_interner;
 mod places;
 mod procedure_encoder;
 mod pure_function_encoder;
+mod refinement_check_encoder;
 mod snapshot_encoder;
 mod snapshot_spec_patcher;
 mod spec_encoder;
 mod spec_function_encoder;
 pub use spec_function_encoder::SpecFunctionKind;
+mod std_specs;
 mod stub_function_encoder;
 mod stub_procedure_encoder;
+mod termination;
 mod type_encoder;
 mod utils;
+mod viper_name_map;
 mod snapshot;
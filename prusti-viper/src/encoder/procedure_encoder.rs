@@ -5,7 +5,7 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use crate::encoder::borrows::ProcedureContract;
-use crate::encoder::builtin_encoder::BuiltinMethodKind;
+use crate::encoder::builtin_encoder::{BuiltinMethodKind, IntArithMode, get_int_arith_method};
 use crate::encoder::errors::{
     SpannedEncodingError, ErrorCtxt, PanicCause, EncodingError, WithSpan, RunIfErr,
     EncodingResult, SpannedEncodingResult
@@ -13,12 +13,17 @@ use crate::encoder::errors::{
 use crate::encoder::foldunfold;
 use crate::encoder::initialisation::InitInfo;
 use crate::encoder::loop_encoder::{LoopEncoder, LoopEncoderError};
-use crate::encoder::mir_encoder::{MirEncoder, FakeMirEncoder, PlaceEncoder};
-use crate::encoder::mir_encoder::PRECONDITION_LABEL;
+use crate::encoder::loop_invariant_inference::{self, InferredInvariant};
+use crate::encoder::mir_encoder::{MirEncoder, FakeMirEncoder, PlaceEncoder, describe_assert_message};
+use crate::encoder::mir_encoder::{PRECONDITION_LABEL, CREDITS_VAR_NAME};
 use crate::encoder::mir_successor::MirSuccessor;
 use crate::encoder::places::{Local, LocalVariableManager, Place};
 use crate::encoder::Encoder;
+use crate::encoder::encoder::{get_tested_variant, get_unwrapped_variant, get_vec_method};
+use crate::encoder::std_specs::{VecCall, VecSpec, VEC_METHODS};
+use crate::encoder::snapshot;
 use crate::encoder::snapshot_spec_patcher::SnapshotSpecPatcher;
+use crate::encoder::termination;
 use prusti_common::{
     config,
     report::log,
@@ -77,6 +82,8 @@ pub struct ProcedureEncoder<'p, 'v: 'p, 'tcx: 'v> {
     auxiliary_local_vars: HashMap<String, vir::Type>,
     mir_encoder: MirEncoder<'p, 'v, 'tcx>,
     check_panics: bool,
+    /// The kinds of obligations that are checked.
+    obligations: config::Obligations,
     check_foldunfold_state: bool,
     polonius_info: Option<PoloniusInfo<'p, 'tcx>>,
     procedure_contract: Option<ProcedureContract<'tcx>>,
@@ -103,6 +110,18 @@ pub struct ProcedureEncoder<'p, 'v: 'p, 'tcx: 'v> {
     old_ghost_vars: HashMap<String, vir::Type>,
     /// For each loop head, the block at whose end the loop invariant holds
     cached_loop_invariant_block: HashMap<BasicBlockIndex, BasicBlockIndex>,
+    /// For each loop head without a `body_invariant!`, the inferred invariant
+    /// and the statements that record the values it refers to on entry.
+    inferred_loop_invariants: HashMap<BasicBlockIndex, (Vec<vir::Expr>, Vec<vir::Stmt>)>,
+    /// The types of the function items that were coerced to the function
+    /// pointers stored in the given locals.
+    reified_fn_pointers: HashMap<mir::Local, ty::Ty<'tcx>>,
+    /// The ghost variable that counts the credits left, if the procedure has
+    /// a `#[complexity]` annotation.
+    credits_var: Option<vir::LocalVar>,
+    /// The ghost variable that stores the termination measure on entry, if
+    /// the procedure has a `#[decreases]` annotation.
+    measure_var: Option<vir::LocalVar>,
 }
 
 impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
@@ -143,6 +162,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             auxiliary_local_vars: HashMap::new(),
             mir_encoder: mir_encoder,
             check_panics: config::check_panics(),
+            obligations: config::checked_obligations(),
             check_foldunfold_state: config::check_foldunfold_state(),
             polonius_info: None,
             procedure_contract: None,
@@ -157,6 +177,10 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             old_to_ghost_var: HashMap::new(),
             old_ghost_vars: HashMap::new(),
             cached_loop_invariant_block: HashMap::new(),
+            inferred_loop_invariants: HashMap::new(),
+            reified_fn_pointers: HashMap::new(),
+            credits_var: None,
+            measure_var: None,
         })
     }
 
@@ -232,6 +256,20 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         trace!("Encode procedure {}", self.cfg_method.name());
         let mir_span = self.mir.span;
 
+        // The body would have to convert its result to the abstract encoding
+        // of an `impl Trait` type whose hidden type cannot be encoded.
+        let return_ty = self.mir.return_ty();
+        if let ty::TyKind::Opaque(..) = self.encoder.reveal_opaque_type(return_ty).kind() {
+            return Err(SpannedEncodingError::unsupported(
+                format!(
+                    "the hidden type of '{}' cannot be encoded, so the body of the function \
+                    has to be #[trusted]",
+                    return_ty,
+                ),
+                mir_span,
+            ));
+        }
+
         // Retrieve the contract
         self.procedure_contract = Some(
             self.encoder
@@ -327,6 +365,18 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             }
         }
 
+        // Declare the credits of a procedure with a complexity bound
+        if self.procedure_contract().specification.expect_procedure().complexity.is_some() {
+            let credits_var = vir::LocalVar::new(CREDITS_VAR_NAME, vir::Type::Int);
+            self.cfg_method.add_local_var(&credits_var.name, vir::Type::Int);
+            self.credits_var = Some(credits_var);
+        }
+
+        // Declare the termination measure of a procedure with a `#[decreases]` annotation
+        if self.procedure_contract().specification.expect_procedure().decreases.is_some() {
+            self.measure_var = Some(self.cfg_method.add_fresh_local_var(vir::Type::Int));
+        }
+
         // Declare the formal return
         for local in self.mir.local_decls.indices().take(1) {
             let name = self.mir_encoder.encode_local_var_name(local);
@@ -730,6 +780,13 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         heads.push(Some(inv_pre_block));
         self.cfg_method
             .set_successor(inv_pre_block, vir::Successor::Goto(inv_post_block));
+        if config::infer_loop_invariants() && self.get_loop_spec_blocks(loop_head).is_empty() {
+            // Without `body_invariant!`, the invariant holds right after the guard
+            let guard_block = opt_loop_guard_switch
+                .filter(|&guard_switch| guard_switch == before_invariant_block);
+            self.infer_loop_invariant(loop_head, &loop_body, guard_block)
+                .with_span(self.get_loop_span(loop_head))?;
+        }
         {
             let stmts =
                 self.encode_loop_invariant_exhale_stmts(loop_head, before_invariant_block, false)?;
@@ -741,6 +798,29 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 self.encode_loop_invariant_inhale_stmts(loop_head, before_invariant_block, false)?;
             self.cfg_method.add_stmts(inv_post_block, stmts);
         }
+        // Each iteration passes the invariant once, and pays for itself
+        // after it. The credits are havocked with the other local variables.
+        if let Some(credits_var) = self.credits_var.clone() {
+            let pos = self.encoder.error_manager().register(
+                self.get_loop_span(loop_head),
+                ErrorCtxt::AssertLoopIterationCredits,
+            );
+            let stmts = self.encode_credits_payment(credits_var, 1.into(), pos);
+            self.cfg_method.add_stmts(inv_post_block, stmts);
+        }
+        // Record the loop variant at the start of the iteration
+        let loop_variant = self.encode_loop_variant(loop_head, before_invariant_block)?;
+        let variant_var = match loop_variant {
+            Some((ref variant, _)) if self.obligations.termination => {
+                let variant_var = self.cfg_method.add_fresh_local_var(vir::Type::Int);
+                self.cfg_method.add_stmt(
+                    inv_post_block,
+                    vir::Stmt::Assign(variant_var.clone().into(), variant.clone(), vir::AssignKind::Copy),
+                );
+                Some(variant_var)
+            }
+            _ => None,
+        };
 
         // Encode the last B2 group (start - G - B1 - invariant - *B2* - G - B1 - end)
         let (last_b2_head, last_b2_edges) = self.encode_blocks_group(
@@ -779,6 +859,20 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 loop_label_prefix
             ))],
         );
+        if let (Some((variant, variant_span)), Some(variant_var)) = (loop_variant, variant_var) {
+            let pos = self
+                .encoder
+                .error_manager()
+                .register(variant_span, ErrorCtxt::AssertLoopVariant);
+            self.cfg_method.add_stmt(
+                end_body_block,
+                vir::Stmt::Assert(
+                    termination::measure_decreases(variant, variant_var.into()),
+                    vir::FoldingBehaviour::Expr,
+                    pos,
+                ),
+            );
+        }
         {
             let stmts = self.encode_loop_invariant_exhale_stmts(
                 loop_head,
@@ -1034,13 +1128,20 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         let index = location.statement_index;
         let stmts_succ_res = if index < bb_data.statements.len() {
             let mir_stmt = &bb_data.statements[index];
+            self.encoder.error_manager().set_mir_context(
+                Some(format!("{:?}: {:?}", location, mir_stmt))
+            );
             self.encode_statement(mir_stmt, location)
                 .map(|stmts| (stmts, None))
         } else {
             let mir_term = bb_data.terminator();
+            self.encoder.error_manager().set_mir_context(
+                Some(format!("{:?}: {:?}", location, mir_term.kind))
+            );
             self.encode_terminator(mir_term, location)
                 .map(|(stmts, succ)| (stmts, Some(succ)))
         };
+        self.encoder.error_manager().set_mir_context(None);
 
         // Intercept encoding error caused by an unsupported feature
         match stmts_succ_res {
@@ -1081,6 +1182,55 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         }
     }
 
+    /// Encode the checks that the raw pointers dereferenced by `places` are
+    /// valid, that is, that the permission given by `ptr_valid` is held. A
+    /// write through a pointer requires the full permission.
+    fn encode_raw_pointer_deref_checks(
+        &self,
+        places: Vec<(mir::Place<'tcx>, bool)>,
+        span: Span,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        let tcx = self.encoder.env().tcx();
+        let mut stmts = vec![];
+        for (place, is_mutating) in places {
+            for (index, elem) in place.projection.iter().enumerate() {
+                if elem != mir::ProjectionElem::Deref {
+                    continue;
+                }
+                let base = mir::Place {
+                    local: place.local,
+                    projection: tcx.intern_place_elems(&place.projection[..index]),
+                };
+                let target_ty = match base.ty(self.mir, tcx).ty.kind() {
+                    ty::TyKind::RawPtr(ty::TypeAndMut { ty, .. }) => *ty,
+                    _ => continue,
+                };
+                let (encoded_base, _, _) = self.mir_encoder.encode_place(&base)
+                    .with_span(span)?;
+                let address_field = self.encoder.encode_dereference_field(target_ty)
+                    .with_span(span)?;
+                // Only the innermost dereference writes to the pointee.
+                let perm = if is_mutating && index + 1 == place.projection.len() {
+                    vir::PermAmount::Write
+                } else {
+                    vir::PermAmount::Read
+                };
+                let pos = self.encoder
+                    .error_manager()
+                    .register(span, ErrorCtxt::AssertRawPointerValidity);
+                stmts.push(vir::Stmt::Assert(
+                    self.encoder.encode_raw_pointer_validity(
+                        encoded_base.field(address_field),
+                        perm,
+                    ),
+                    vir::FoldingBehaviour::Expr,
+                    pos,
+                ));
+            }
+        }
+        Ok(stmts)
+    }
+
     /// Note: it's better to call `encode_statement_at` instead of this method.
     fn encode_statement(
         &mut self,
@@ -1094,6 +1244,15 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
 
         let mut stmts = vec![vir::Stmt::comment(format!("[mir] {:?}", stmt))];
 
+        if config::check_raw_pointer_derefs() {
+            let mut collector = AccessedPlaceCollector { places: vec![] };
+            mir::visit::Visitor::visit_statement(&mut collector, stmt, location);
+            stmts.extend(self.encode_raw_pointer_deref_checks(
+                collector.places,
+                stmt.source_info.span,
+            )?);
+        }
+
         let encoding_stmts = match stmt.kind {
             mir::StatementKind::StorageLive(..)
             | mir::StatementKind::StorageDead(..)
@@ -1107,7 +1266,10 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 let (encoded_lhs, ty, _) = self.mir_encoder.encode_place(lhs).unwrap();
                 match rhs {
                     &mir::Rvalue::Use(ref operand) => {
-                        self.encode_assign_operand(&encoded_lhs, operand, location)?
+                        match self.encode_assign_array_elem(&encoded_lhs, ty, operand, location)? {
+                            Some(stmts) => stmts,
+                            None => self.encode_assign_operand(&encoded_lhs, operand, location)?,
+                        }
                     }
                     &mir::Rvalue::Aggregate(ref aggregate, ref operands) => {
                         self.encode_assign_aggregate(
@@ -1163,6 +1325,16 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                             ty
                         )?
                     }
+                    &mir::Rvalue::Ref(ref _region, mir_borrow_kind, ref place)
+                        if is_subslice(place) =>
+                    {
+                        self.encode_assign_subslice_ref(
+                            mir_borrow_kind,
+                            place,
+                            location,
+                            encoded_lhs,
+                        )?
+                    }
                     &mir::Rvalue::Ref(ref _region, mir_borrow_kind, ref place) => {
                         self.encode_assign_ref(
                             mir_borrow_kind,
@@ -1182,6 +1354,59 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                             stmt.source_info.span,
                         )?
                     }
+                    &mir::Rvalue::Cast(
+                        mir::CastKind::Pointer(ty::adjustment::PointerCast::Unsize),
+                        ref operand,
+                        dst_ty,
+                    ) if self.encoder.is_devirtualized_unsize(
+                        self.mir_encoder.get_operand_ty(operand),
+                        dst_ty,
+                    ) => {
+                        // The trait object is encoded as the referenced value.
+                        self.encode_assign_operand(&encoded_lhs, operand, location)?
+                    }
+                    &mir::Rvalue::Cast(
+                        mir::CastKind::Pointer(ty::adjustment::PointerCast::Unsize),
+                        ref operand,
+                        dst_ty,
+                    ) if self.encoder.is_trait_object_pointer(dst_ty) => {
+                        // The writes through a mutable trait object would not
+                        // reach the value that it borrows.
+                        let is_mutable = dst_ty.builtin_deref(true)
+                            .map_or(false, |pointee| pointee.mutbl == Mutability::Mut);
+                        if is_mutable {
+                            return Err(SpannedEncodingError::unsupported(
+                                "mutable references to trait objects are not supported",
+                                stmt.source_info.span,
+                            ));
+                        }
+                        // The trait object is a fresh abstract value, whose
+                        // methods are verified against the contracts of the
+                        // trait.
+                        self.encode_havoc_and_allocation(&encoded_lhs)
+                    }
+                    &mir::Rvalue::Cast(
+                        mir::CastKind::Pointer(ty::adjustment::PointerCast::ReifyFnPointer),
+                        ref operand,
+                        _,
+                    ) => {
+                        // The value of a function pointer is not encoded. We
+                        // only remember the function, to check its contract
+                        // when the pointer is passed to a procedure.
+                        let fn_ty = self.mir_encoder.get_operand_ty(operand);
+                        if let (Some(local), ty::TyKind::FnDef(def_id, _)) =
+                            (lhs.as_local(), fn_ty.kind())
+                        {
+                            if !self.encoder.env().tcx().is_constructor(*def_id) {
+                                self.encoder.encode_spec_funcs(*def_id)?;
+                            }
+                            self.reified_fn_pointers.insert(local, fn_ty);
+                        }
+                        vec![]
+                    }
+                    &mir::Rvalue::Repeat(ref operand, _) => {
+                        self.encode_assign_repeat(&encoded_lhs, ty, operand, location)?
+                    }
                     &mir::Rvalue::Len(..) => {
                         return Err(SpannedEncodingError::unsupported(
                             "obtaining the length of an array is unsupported",
@@ -1223,6 +1448,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         let (all_active_loans, _) = self.polonius_info().get_all_active_loans(location);
         let mut relevant_active_loan_places = vec![];
         for loan in &all_active_loans {
+            if self.is_subslice_loan(*loan) {
+                continue;
+            }
             let opt_places = self.polonius_info().get_loan_places(loan)
                 .map_err(EncodingError::from)
                 .with_span(span)?;
@@ -1248,6 +1476,17 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         }
     }
 
+    /// Is `loan` created by the borrow of the subslice of a slice pattern?
+    fn is_subslice_loan(&self, loan: facts::Loan) -> bool {
+        match self.polonius_info().get_loan_places(&loan) {
+            Ok(Some(loan_places)) => matches!(
+                loan_places.source,
+                mir::Rvalue::Ref(_, _, ref place) if is_subslice(place)
+            ),
+            _ => false,
+        }
+    }
+
     /// Encode the lhs and the rhs of the assignment that create the loan
     fn encode_loan_places(&self, loan_places: &LoanPlaces<'tcx>) -> (vir::Expr, vir::Expr, bool) {
         debug!("encode_loan_places '{:?}'", loan_places);
@@ -1634,6 +1873,11 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             zombie_loans
         );
         let mut stmts: Vec<vir::Stmt> = vec![];
+        // The subslices of slice patterns are copies, see
+        // `encode_assign_subslice_ref`.
+        let loans: Vec<_> = loans.into_iter()
+            .filter(|loan| !self.is_subslice_loan(*loan))
+            .collect();
         if loans.len() > 0 {
             let vir_reborrowing_dag =
                 self.construct_vir_reborrowing_dag(&loans, &zombie_loans, location, end_location)?;
@@ -1679,6 +1923,14 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         let mut stmts: Vec<vir::Stmt> = vec![vir::Stmt::comment(format!("[mir] {:?}", term.kind))];
         let span = self.mir_encoder.get_span_of_location(location);
 
+        // The operands of calls, switches and assertions can dereference
+        // raw pointers too.
+        if config::check_raw_pointer_derefs() {
+            let mut collector = AccessedPlaceCollector { places: vec![] };
+            mir::visit::Visitor::visit_terminator(&mut collector, term, location);
+            stmts.extend(self.encode_raw_pointer_deref_checks(collector.places, span)?);
+        }
+
         let result = match term.kind {
             TerminatorKind::Return => {
                 // Package magic wands, if there is any
@@ -1790,8 +2042,15 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 };
 
                 if kill_default_target {
-                    // Use the last conditional target as default. We could also assume or assert
-                    // that the switch is exhaustive and never hits the default.
+                    // Use the last conditional target as default. Since the switch is
+                    // exhaustive, we assume that one of the guards holds, which keeps the
+                    // guard of the last target (e.g. a matched range) in its branch.
+                    if !cfg_targets.is_empty() {
+                        stmts.push(vir::Stmt::Inhale(
+                            cfg_targets.iter().map(|(guard, _)| guard.clone()).disjoin(),
+                            vir::FoldingBehaviour::None,
+                        ));
+                    }
                     let last_target = cfg_targets.pop().unwrap();
                     (stmts, MirSuccessor::GotoSwitch(cfg_targets, last_target.1))
                 } else {
@@ -1899,6 +2158,11 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                     let own_substs =
                         ty::List::identity_for_item(self.encoder.env().tcx(), def_id);
 
+                    // The const generic arguments are encoded in the context
+                    // of the caller, before entering the one of the callee.
+                    let const_param_frame = self.encoder.encode_const_param_frame(def_id, substs)
+                        .with_span(term.source_info.span)?;
+                    self.encoder.const_param_repl.borrow_mut().push(const_param_frame);
                     {
                         // FIXME: this is a hack to support generics. See issue #187.
                         let mut tymap_stack = self.encoder.typaram_repl.borrow_mut();
@@ -1913,12 +2177,27 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                                 tymap.insert(ty1, ty2);
                             }
                         }
+                        // Function pointers passed as arguments are replaced
+                        // by the function that they point to, so that the
+                        // specification entailments of the callee's contract
+                        // are checked against the contract of that function.
+                        for arg in args {
+                            if let mir::Operand::Copy(place) | mir::Operand::Move(place) = arg {
+                                let fn_ty = place.as_local()
+                                    .and_then(|local| self.reified_fn_pointers.get(&local));
+                                if let Some(&fn_ty) = fn_ty {
+                                    let fn_ptr_ty = self.mir_encoder.get_operand_ty(arg);
+                                    tymap.insert(fn_ptr_ty, fn_ty);
+                                }
+                            }
+                        }
                         tymap_stack.push(tymap);
                     }
                     let cleanup = |this: &ProcedureEncoder| {
                         // FIXME: this is a hack to support generics. See issue #187.
                         let mut tymap_stack = this.encoder.typaram_repl.borrow_mut();
                         tymap_stack.pop();
+                        this.encoder.const_param_repl.borrow_mut().pop();
                     };
 
                     match full_func_proc_name {
@@ -1943,7 +2222,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                                     ErrorCtxt::Panic(panic_cause)
                                 );
 
-                            if self.check_panics {
+                            if self.check_panics && self.obligations.panics {
                                 stmts.push(vir::Stmt::comment(format!(
                                     "Rust panic - {}",
                                     panic_message
@@ -1958,14 +2237,19 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                             }
                         }
 
-                        "std::boxed::Box::<T>::new" => {
-                            // This is the initialization of a box
+                        "std::boxed::Box::<T>::new"
+                        | "std::rc::Rc::<T>::new"
+                        | "std::sync::Arc::<T>::new" => {
+                            // This is the initialization of a box, or of a
+                            // reference-counted pointer, which is encoded
+                            // like a box.
                             // args[0]: value to put in the box
                             assert_eq!(args.len(), 1);
 
                             let (ref target_place, _) = destination.as_ref().unwrap();
                             let (dst, dest_ty, _) = self.mir_encoder.encode_place(target_place).unwrap();
-                            let boxed_ty = dest_ty.boxed_ty();
+                            let boxed_ty = self.encoder.get_rc_pointee_ty(dest_ty)
+                                .unwrap_or_else(|| dest_ty.boxed_ty());
                             let ref_field = self.encoder.encode_dereference_field(boxed_ty)
                                 .with_span(span)
                                 .run_if_err(|| cleanup(&self))?;
@@ -1994,6 +2278,104 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                             );
                         }
 
+                        "std::clone::Clone::clone" |
+                        "core::clone::Clone::clone"
+                            if self.encoder.get_rc_pointee_ty(substs.type_at(0)).is_some()
+                        => {
+                            debug!("Encoding call of Clone::clone on a reference-counted pointer");
+                            stmts.extend(
+                                self.encode_rc_clone_call(
+                                    location,
+                                    term.source_info.span,
+                                    args,
+                                    destination,
+                                ).run_if_err(|| cleanup(&self))?
+                            );
+                        }
+
+                        "std::ops::Deref::deref" |
+                        "core::ops::Deref::deref"
+                            if self.encoder.get_rc_pointee_ty(substs.type_at(0)).is_some()
+                        => {
+                            debug!("Encoding call of Deref::deref on a reference-counted pointer");
+                            stmts.extend(
+                                self.encode_rc_deref_call(
+                                    location,
+                                    term.source_info.span,
+                                    args,
+                                    destination,
+                                    def_id,
+                                    self_ty,
+                                ).run_if_err(|| cleanup(&self))?
+                            );
+                        }
+
+                        _ if get_vec_method(full_func_proc_name)
+                            .map_or(false, |method| VEC_METHODS.contains(&method))
+                        => {
+                            debug!("Encoding call of a method of Vec");
+                            stmts.extend(
+                                self.encode_vec_call(
+                                    location,
+                                    term.source_info.span,
+                                    args,
+                                    destination,
+                                    def_id,
+                                    self_ty,
+                                    get_vec_method(full_func_proc_name).unwrap(),
+                                ).run_if_err(|| cleanup(&self))?
+                            );
+                        }
+
+                        "std::ops::Index::index" |
+                        "core::ops::Index::index"
+                            if self.encoder.get_vec_elem_ty(substs.type_at(0)).is_some()
+                                && matches!(substs.type_at(1).kind(), ty::TyKind::Uint(_))
+                        => {
+                            debug!("Encoding call of Index::index on a Vec");
+                            stmts.extend(
+                                self.encode_vec_call(
+                                    location,
+                                    term.source_info.span,
+                                    args,
+                                    destination,
+                                    def_id,
+                                    self_ty,
+                                    "index",
+                                ).run_if_err(|| cleanup(&self))?
+                            );
+                        }
+
+                        _ if get_tested_variant(full_func_proc_name).is_some() => {
+                            debug!("Encoding call of a variant test of Option or Result");
+                            stmts.extend(
+                                self.encode_variant_test_call(
+                                    location,
+                                    term.source_info.span,
+                                    args,
+                                    destination,
+                                    def_id,
+                                    self_ty,
+                                    get_tested_variant(full_func_proc_name).unwrap(),
+                                ).run_if_err(|| cleanup(&self))?
+                            );
+                        }
+
+                        _ if get_unwrapped_variant(full_func_proc_name).is_some() => {
+                            debug!("Encoding call of an unwrap of Option or Result");
+                            stmts.extend(
+                                self.encode_unwrap_call(
+                                    location,
+                                    term.source_info.span,
+                                    args,
+                                    destination,
+                                    def_id,
+                                    self_ty,
+                                    get_unwrapped_variant(full_func_proc_name).unwrap(),
+                                ).run_if_err(|| cleanup(&self))?
+                            );
+                        }
+
                         "std::cmp::PartialEq::eq" |
                         "core::cmp::PartialEq::eq"
                             if args.len() == 2 &&
@@ -2034,8 +2416,72 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                             );
                         }
 
-                        "std::ops::Fn::call" => {
-                            let cl_type: ty::Ty = substs[0].expect_ty();
+                        _ if int_bytes_conversion(full_func_proc_name).is_some() => {
+                            let (to_bytes, little_endian) =
+                                int_bytes_conversion(full_func_proc_name).unwrap();
+                            stmts.extend(
+                                self.encode_int_bytes_conversion(
+                                    args,
+                                    destination,
+                                    location,
+                                    to_bytes,
+                                    little_endian,
+                                ).run_if_err(|| cleanup(&self))?
+                            );
+                        }
+
+                        "std::ptr::eq" | "core::ptr::eq" => {
+                            debug!("Encoding call of ptr::eq");
+                            stmts.extend(
+                                self.encode_ptr_eq_call(
+                                    location,
+                                    term.source_info.span,
+                                    args,
+                                    destination,
+                                    def_id,
+                                    self_ty,
+                                ).run_if_err(|| cleanup(&self))?
+                            );
+                        }
+
+                        "std::ptr::read" | "core::ptr::read"
+                        | "std::ptr::write" | "core::ptr::write" => {
+                            debug!("Encoding call of ptr::read or ptr::write");
+                            stmts.extend(
+                                self.encode_ptr_read_write_call(
+                                    location,
+                                    term.source_info.span,
+                                    args,
+                                    destination,
+                                    def_id,
+                                    self_ty,
+                                    full_func_proc_name.ends_with("::write"),
+                                ).run_if_err(|| cleanup(&self))?
+                            );
+                        }
+
+                        _ if get_int_arith_method(full_func_proc_name).is_some() => {
+                            debug!("Encoding call of an arithmetic method of an integer");
+                            let (mode, op) = get_int_arith_method(full_func_proc_name).unwrap();
+                            stmts.extend(
+                                self.encode_int_arith_call(
+                                    location,
+                                    term.source_info.span,
+                                    args,
+                                    destination,
+                                    def_id,
+                                    self_ty,
+                                    mode,
+                                    op,
+                                ).run_if_err(|| cleanup(&self))?
+                            );
+                        }
+
+                        "std::ops::Fn::call"
+                        | "std::ops::FnMut::call_mut"
+                        | "std::ops::FnOnce::call_once" => {
+                            // A closure returned as `impl Fn(..)` is called with its own contract.
+                            let cl_type: ty::Ty = self.encoder.reveal_opaque_type(substs[0].expect_ty());
                             match cl_type.kind() {
                                 ty::TyKind::Closure(cl_def_id, _) => {
                                     debug!("Encoding call to closure {:?} with func {:?}", cl_def_id, func_const_val);
@@ -2049,10 +2495,36 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                                     )?);
                                 }
 
-                                _ => unreachable!()
+                                ty::TyKind::Param(_) => {
+                                    debug!("Encoding call to closure of type {:?}", cl_type);
+                                    stmts.extend(
+                                        self.encode_closure_param_call(
+                                            location,
+                                            term.source_info.span,
+                                            args,
+                                            destination,
+                                            cl_type,
+                                        ).run_if_err(|| cleanup(&self))?
+                                    );
+                                }
+
+                                _ => {
+                                    cleanup(&self);
+                                    return Err(SpannedEncodingError::unsupported(
+                                        format!("calling a value of type '{}' is not supported", cl_type),
+                                        term.source_info.span,
+                                    ));
+                                }
                             }
                         }
 
+                        _ if self.encoder.env().tcx().is_constructor(def_id) => {
+                            stmts.extend(
+                                self.encode_constructor_call(def_id, args, destination, location)
+                                    .run_if_err(|| cleanup(&self))?
+                            );
+                        }
+
                         _ => {
                             let is_pure_function = self.encoder.is_pure(def_id);
                             if is_pure_function {
@@ -2109,6 +2581,33 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 }
             }
 
+            TerminatorKind::Call {
+                func: mir::Operand::Move(ref place),
+                ref args,
+                ref destination,
+                ..
+            }
+            | TerminatorKind::Call {
+                func: mir::Operand::Copy(ref place),
+                ref args,
+                ref destination,
+                ..
+            } if self.is_constructor_place(place) => {
+                // The constructor is stored in a local variable, e.g. in
+                // `let f = Some; f(x)`.
+                let def_id = match self.mir_encoder.get_local_ty(place.local).kind() {
+                    ty::TyKind::FnDef(def_id, _) => *def_id,
+                    _ => unreachable!(),
+                };
+                stmts.extend(
+                    self.encode_constructor_call(def_id, args, destination, location)?
+                );
+                match destination {
+                    &Some((_, target)) => (stmts, MirSuccessor::Goto(target)),
+                    None => (stmts, MirSuccessor::Kill),
+                }
+            }
+
             TerminatorKind::Call { .. } => {
                 // Other kind of calls?
                 unimplemented!();
@@ -2143,15 +2642,26 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 // Check or assume the assertion
                 stmts.push(vir::Stmt::comment(format!(
                     "Rust assertion: {}",
-                    msg.description()
+                    describe_assert_message(msg)
                 )));
-                if self.check_panics {
+                let is_checked = match msg {
+                    mir::AssertKind::BoundsCheck { .. } => self.obligations.bounds,
+                    mir::AssertKind::Overflow(..)
+                    | mir::AssertKind::OverflowNeg(..) => self.obligations.overflow,
+                    _ => self.obligations.panics,
+                };
+                if self.check_panics && is_checked {
                     stmts.push(vir::Stmt::Assert(
                         viper_guard,
                         vir::FoldingBehaviour::Stmt,
                         self.encoder.error_manager().register(
                             term.source_info.span,
-                            ErrorCtxt::AssertTerminator(msg.description().to_string()),
+                            match msg {
+                                mir::AssertKind::BoundsCheck { .. } => ErrorCtxt::BoundsCheck,
+                                _ => ErrorCtxt::AssertTerminator(
+                                    describe_assert_message(msg).to_string()
+                                ),
+                            },
                         ),
                     ));
                 } else {
@@ -2179,7 +2689,26 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         destination: &Option<(mir::Place<'tcx>, BasicBlockIndex)>,
         bin_op: vir::BinOpKind,
     ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
-        let arg_ty = self.mir_encoder.get_operand_ty(&args[0]);
+        let mut arg_ty = self.mir_encoder.get_operand_ty(&args[0]);
+        let mut lhs = self.mir_encoder.encode_operand_expr(&args[0])
+            .with_span(call_site_span)?;
+        let mut rhs = self.mir_encoder.encode_operand_expr(&args[1])
+            .with_span(call_site_span)?;
+        if let ty::TyKind::Ref(_, pointee_ty, _) = arg_ty.kind() {
+            if pointee_ty.is_box() {
+                // Boxes are compared by their content, which has the same
+                // snapshot, without going through the snapshot of the box.
+                let (deref_lhs, content_ty) = self.mir_encoder
+                    .encode_auto_deref(lhs, arg_ty)
+                    .with_span(call_site_span)?;
+                let (deref_rhs, _) = self.mir_encoder
+                    .encode_auto_deref(rhs, arg_ty)
+                    .with_span(call_site_span)?;
+                lhs = deref_lhs;
+                rhs = deref_rhs;
+                arg_ty = content_ty;
+            }
+        }
 
         let snapshot_res = self.encoder.encode_snapshot(&arg_ty);
         if snapshot_res.is_ok() && snapshot_res.as_ref().unwrap().supports_equality() {
@@ -2189,11 +2718,6 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 .error_manager()
                 .register(call_site_span, ErrorCtxt::PureFunctionCall);
 
-            let lhs = self.mir_encoder.encode_operand_expr(&args[0])
-                .with_span(call_site_span)?;
-            let rhs = self.mir_encoder.encode_operand_expr(&args[1])
-                .with_span(call_site_span)?;
-
             let expr = match bin_op {
                 vir::BinOpKind::EqCmp => snapshot.encode_equals(lhs, rhs, pos),
                 vir::BinOpKind::NeCmp => snapshot.encode_not_equals(lhs, rhs, pos),
@@ -2232,43 +2756,662 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         }
     }
 
-    /// Encode an edge of the MIR graph
-    fn encode_edge_block(
+    /// Encode the call of `Clone::clone` on a reference-counted pointer. The
+    /// content of the pointer is never mutated, so sharing it is modeled by
+    /// allocating a new content that is equal to the content of the original.
+    fn encode_rc_clone_call(
         &mut self,
-        source: BasicBlockIndex,
-        destination: BasicBlockIndex,
-        force_block: bool,
-    ) -> SpannedEncodingResult<Option<CfgBlockIndex>> {
-        let source_loc = mir::Location {
-            block: source,
-            statement_index: self.mir[source].statements.len(),
-        };
-        let destination_loc = mir::Location {
-            block: destination,
-            statement_index: 0,
-        };
-        let stmts = self.encode_expiring_borrows_between(source_loc, destination_loc)?;
+        location: mir::Location,
+        call_site_span: Span,
+        args: &[mir::Operand<'tcx>],
+        destination: &Option<(mir::Place<'tcx>, BasicBlockIndex)>,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        // args[0]: a shared reference to the pointer to clone
+        assert_eq!(args.len(), 1);
+        let (ref target_place, _) = destination.as_ref().unwrap();
+        let (dst, dest_ty, _) = self.mir_encoder.encode_place(target_place)
+            .with_span(call_site_span)?;
+        let pointee_ty = self.encoder.get_rc_pointee_ty(dest_ty).unwrap();
+        let ref_field = self.encoder.encode_dereference_field(pointee_ty)
+            .with_span(call_site_span)?;
+        let src_content = self.mir_encoder.encode_operand_expr(&args[0])
+            .with_span(call_site_span)?
+            .field(ref_field.clone());
+        let dst_content = dst.clone().field(ref_field.clone());
 
-        if force_block || !stmts.is_empty() {
-            let edge_label = self.cfg_method.get_fresh_label_name();
-            let edge_block = self.cfg_method.add_block(
-                &edge_label,
-                vec![
-                    vir::Stmt::comment(format!("========== {} ==========", edge_label)),
-                    vir::Stmt::comment(format!("MIR edge {:?} --> {:?}", source, destination)),
-                ],
-            );
-            if !stmts.is_empty() {
-                self.cfg_method
-                    .add_stmt(edge_block, vir::Stmt::comment("Expire borrows"));
-                self.cfg_method.add_stmts(edge_block, stmts);
-            }
-            Ok(Some(edge_block))
+        let mut stmts = self.prepare_assign_target(
+            dst,
+            ref_field,
+            location,
+            vir::AssignKind::Move,
+        )?;
+        stmts.extend(self.encode_havoc_and_allocation(&dst_content));
+        let eq = self.encoder.encode_memory_eq_func_app(
+            src_content,
+            dst_content,
+            pointee_ty,
+            vir::Position::default(),
+            call_site_span.into(),
+        );
+        stmts.push(vir::Stmt::Inhale(eq, vir::FoldingBehaviour::Stmt));
+        Ok(stmts)
+    }
+
+    /// Encode the call of `Deref::deref` on a reference-counted pointer like
+    /// any other call, and inhale that the returned shared reference points
+    /// to a value equal to the content of the pointer.
+    fn encode_rc_deref_call(
+        &mut self,
+        location: mir::Location,
+        call_site_span: Span,
+        args: &[mir::Operand<'tcx>],
+        destination: &Option<(mir::Place<'tcx>, BasicBlockIndex)>,
+        called_def_id: ProcedureDefId,
+        self_ty: Option<&'tcx ty::TyS<'tcx>>,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        // args[0]: a shared reference to the pointer to dereference
+        assert_eq!(args.len(), 1);
+        let mut stmts = self.encode_impure_function_call(
+            location,
+            call_site_span,
+            args,
+            destination,
+            called_def_id,
+            self_ty,
+        )?;
+        let (ref target_place, _) = destination.as_ref().unwrap();
+        let (dst, dest_ty, _) = self.mir_encoder.encode_place(target_place)
+            .with_span(call_site_span)?;
+        let pointee_ty = match dest_ty.kind() {
+            ty::TyKind::Ref(_, pointee_ty, _) => *pointee_ty,
+            _ => unreachable!("Deref::deref returned {:?}", dest_ty),
+        };
+        let ref_field = self.encoder.encode_dereference_field(pointee_ty)
+            .with_span(call_site_span)?;
+        // The permissions of the argument were moved to the state before
+        // the call, in which the pointer is read.
+        let pre_label = self.label_after_location[&location].clone();
+        let rc_content = self.mir_encoder.encode_operand_expr(&args[0])
+            .with_span(call_site_span)?
+            .field(ref_field)
+            .old(pre_label);
+        let target_content = self.encoder.encode_value_expr(dst, dest_ty);
+        let eq = self.encoder.encode_memory_eq_func_app(
+            rc_content,
+            target_content,
+            pointee_ty,
+            vir::Position::default(),
+            call_site_span.into(),
+        );
+        stmts.push(vir::Stmt::Inhale(eq, vir::FoldingBehaviour::Expr));
+        Ok(stmts)
+    }
+
+    /// Encode the call of a method that tests whether an `Option` or a
+    /// `Result` is of the variant `variant_name` like any other call, and
+    /// inhale that the result is the outcome of the test.
+    fn encode_variant_test_call(
+        &mut self,
+        location: mir::Location,
+        call_site_span: Span,
+        args: &[mir::Operand<'tcx>],
+        destination: &Option<(mir::Place<'tcx>, BasicBlockIndex)>,
+        called_def_id: ProcedureDefId,
+        self_ty: Option<&'tcx ty::TyS<'tcx>>,
+        variant_name: &str,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        // args[0]: a shared reference to the tested value
+        assert_eq!(args.len(), 1);
+        let enum_ty = match self.mir_encoder.get_operand_ty(&args[0]).kind() {
+            ty::TyKind::Ref(_, enum_ty, _) => *enum_ty,
+            _ => unreachable!(),
+        };
+        let mut stmts = self.encode_impure_function_call(
+            location,
+            call_site_span,
+            args,
+            destination,
+            called_def_id,
+            self_ty,
+        )?;
+        let (ref target_place, _) = destination.as_ref().unwrap();
+        let (dst, dest_ty, _) = self.mir_encoder.encode_place(target_place)
+            .with_span(call_site_span)?;
+        // The permissions of the argument were moved to the state before
+        // the call, in which the value is tested.
+        let pre_label = self.label_after_location[&location].clone();
+        let tested_value = self.mir_encoder.encode_operand_expr(&args[0])
+            .with_span(call_site_span)?;
+        let guard = self.encoder.encode_variant_guard(tested_value, enum_ty, variant_name);
+        stmts.push(vir::Stmt::Inhale(
+            vir::Expr::eq_cmp(
+                self.encoder.encode_value_expr(dst, dest_ty),
+                guard.old(pre_label),
+            ),
+            vir::FoldingBehaviour::Expr,
+        ));
+        Ok(stmts)
+    }
+
+    /// Encode the call of a method that unwraps the value of the variant
+    /// `variant_name` of an `Option` or a `Result`: assert that the argument
+    /// is of that variant, encode the call like any other call, and inhale
+    /// that the result is equal to the value of the variant.
+    fn encode_unwrap_call(
+        &mut self,
+        location: mir::Location,
+        call_site_span: Span,
+        args: &[mir::Operand<'tcx>],
+        destination: &Option<(mir::Place<'tcx>, BasicBlockIndex)>,
+        called_def_id: ProcedureDefId,
+        self_ty: Option<&'tcx ty::TyS<'tcx>>,
+        variant_name: &str,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        // args[0]: the unwrapped value
+        // args[1]: the message of `expect`
+        let enum_ty = self.mir_encoder.get_operand_ty(&args[0]);
+        let unwrapped_value = self.mir_encoder.encode_operand_expr(&args[0])
+            .with_span(call_site_span)?;
+        let pos = self.encoder.error_manager().register(
+            call_site_span,
+            ErrorCtxt::Panic(PanicCause::Unwrap(variant_name.to_string())),
+        );
+        let guard = self.encoder.encode_variant_guard(unwrapped_value.clone(), enum_ty, variant_name);
+        // The call returns only if the argument is of the variant.
+        let mut stmts = if self.check_panics && self.obligations.panics {
+            vec![vir::Stmt::Assert(guard, vir::FoldingBehaviour::Expr, pos)]
+        } else {
+            vec![vir::Stmt::Inhale(guard, vir::FoldingBehaviour::Expr)]
+        };
+        stmts.extend(self.encode_impure_function_call(
+            location,
+            call_site_span,
+            args,
+            destination,
+            called_def_id,
+            self_ty,
+        )?);
+        let (ref target_place, _) = destination.as_ref().unwrap();
+        let (dst, _, _) = self.mir_encoder.encode_place(target_place)
+            .with_span(call_site_span)?;
+        // The argument was moved into the call, so its value is read in the
+        // state before the call.
+        let pre_label = self.label_after_location[&location].clone();
+        let (payload, payload_ty) = self.encoder
+            .encode_variant_payload(unwrapped_value, enum_ty, variant_name)
+            .with_span(call_site_span)?;
+        let eq = self.encoder.encode_memory_eq_func_app(
+            payload.old(pre_label),
+            dst,
+            payload_ty,
+            vir::Position::default(),
+            call_site_span.into(),
+        );
+        stmts.push(vir::Stmt::Inhale(eq, vir::FoldingBehaviour::Expr));
+        Ok(stmts)
+    }
+
+    /// Encode the call of `ptr::eq` like any other call, and inhale that the
+    /// result tells whether the two pointers have the same address.
+    fn encode_ptr_eq_call(
+        &mut self,
+        location: mir::Location,
+        call_site_span: Span,
+        args: &[mir::Operand<'tcx>],
+        destination: &Option<(mir::Place<'tcx>, BasicBlockIndex)>,
+        called_def_id: ProcedureDefId,
+        self_ty: Option<&'tcx ty::TyS<'tcx>>,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        // args[0]: the left pointer
+        // args[1]: the right pointer
+        assert_eq!(args.len(), 2);
+        let address_eq = self.mir_encoder
+            .encode_pointer_comparison(mir::BinOp::Eq, &args[0], &args[1])
+            .with_span(call_site_span)?;
+        let mut stmts = self.encode_impure_function_call(
+            location,
+            call_site_span,
+            args,
+            destination,
+            called_def_id,
+            self_ty,
+        )?;
+        let (ref target_place, _) = destination.as_ref().unwrap();
+        let (dst, dest_ty, _) = self.mir_encoder.encode_place(target_place)
+            .with_span(call_site_span)?;
+        // The pointers were moved into the call, so they are read in the
+        // state before the call.
+        let pre_label = self.label_after_location[&location].clone();
+        stmts.push(vir::Stmt::Inhale(
+            vir::Expr::eq_cmp(
+                self.encoder.encode_value_expr(dst, dest_ty),
+                address_eq.old(pre_label),
+            ),
+            vir::FoldingBehaviour::Expr,
+        ));
+        Ok(stmts)
+    }
+
+    /// Encode a call of `ptr::read` or `ptr::write` on a raw pointer to a
+    /// value of a primitive type like a dereference of the pointer: the
+    /// pointer has to be valid (see `ptr_valid`), and the call reads or
+    /// writes the value that it points to. The calls on pointers to other
+    /// types are encoded like any other call.
+    fn encode_ptr_read_write_call(
+        &mut self,
+        location: mir::Location,
+        call_site_span: Span,
+        args: &[mir::Operand<'tcx>],
+        destination: &Option<(mir::Place<'tcx>, BasicBlockIndex)>,
+        called_def_id: ProcedureDefId,
+        self_ty: Option<&'tcx ty::TyS<'tcx>>,
+        is_write: bool,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        // args[0]: the pointer
+        // args[1]: the written value, for `ptr::write`
+        let pointee_ty = match self.mir_encoder.get_operand_ty(&args[0]).kind() {
+            ty::TyKind::RawPtr(ty::TypeAndMut { ty, .. }) => *ty,
+            ref x => unreachable!("{:?} is not a raw pointer", x),
+        };
+        let is_primitive = matches!(
+            pointee_ty.kind(),
+            ty::TyKind::Bool | ty::TyKind::Int(_) | ty::TyKind::Uint(_) | ty::TyKind::Char
+        );
+        if !is_primitive {
+            return self.encode_impure_function_call(
+                location,
+                call_site_span,
+                args,
+                destination,
+                called_def_id,
+                self_ty,
+            );
+        }
+        let pointee = self.mir_encoder.encode_pointer_operand_address(&args[0])
+            .with_span(call_site_span)?;
+        let mut stmts = vec![];
+        if config::check_raw_pointer_derefs() {
+            let pos = self.encoder
+                .error_manager()
+                .register(call_site_span, ErrorCtxt::AssertRawPointerValidity);
+            let perm = if is_write { vir::PermAmount::Write } else { vir::PermAmount::Read };
+            stmts.push(vir::Stmt::Assert(
+                self.encoder.encode_raw_pointer_validity(pointee.clone(), perm),
+                vir::FoldingBehaviour::Expr,
+                pos,
+            ));
+        }
+        let (ref target_place, _) = destination.as_ref().unwrap();
+        let (dst, _, _) = self.mir_encoder.encode_place(target_place)
+            .with_span(call_site_span)?;
+        if is_write {
+            let value = self.mir_encoder.encode_operand_expr(&args[1])
+                .with_span(call_site_span)?;
+            stmts.extend(self.encode_copy_value_assign(pointee, value, pointee_ty, location)?);
+            stmts.extend(self.encode_havoc_and_allocation(&dst));
+        } else {
+            let value = self.encoder.encode_value_expr(pointee, pointee_ty);
+            stmts.extend(self.encode_copy_value_assign(dst, value, pointee_ty, location)?);
+        }
+        Ok(stmts)
+    }
+
+    /// Encode the call of a wrapping, checked, saturating or overflowing
+    /// arithmetic method of an integer type like any other call, and inhale
+    /// that the result is the exact result of the method.
+    fn encode_int_arith_call(
+        &mut self,
+        location: mir::Location,
+        call_site_span: Span,
+        args: &[mir::Operand<'tcx>],
+        destination: &Option<(mir::Place<'tcx>, BasicBlockIndex)>,
+        called_def_id: ProcedureDefId,
+        self_ty: Option<&'tcx ty::TyS<'tcx>>,
+        mode: IntArithMode,
+        op: mir::BinOp,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        // args[0]: the left operand
+        // args[1]: the right operand
+        assert_eq!(args.len(), 2);
+        let int_ty = self.mir_encoder.get_operand_ty(&args[0]);
+        let encoded_left = self.mir_encoder.encode_operand_expr(&args[0])
+            .with_span(call_site_span)?;
+        let encoded_right = self.mir_encoder.encode_operand_expr(&args[1])
+            .with_span(call_site_span)?;
+        let mut stmts = self.encode_impure_function_call(
+            location,
+            call_site_span,
+            args,
+            destination,
+            called_def_id,
+            self_ty,
+        )?;
+        let (ref target_place, _) = destination.as_ref().unwrap();
+        let (dst, dest_ty, _) = self.mir_encoder.encode_place(target_place)
+            .with_span(call_site_span)?;
+        // The operands were moved into the call, so they are read in the
+        // state before the call.
+        let pre_label = self.label_after_location[&location].clone();
+        let (value, overflow) = self.mir_encoder
+            .encode_int_arith_method(mode, op, encoded_left, encoded_right, int_ty)
+            .with_span(call_site_span)?;
+        let (value, overflow) = (value.old(&pre_label), overflow.old(&pre_label));
+        let value_field = |ty| self.encoder.encode_value_field(ty);
+        let spec = match mode {
+            IntArithMode::Wrapping | IntArithMode::Saturating => {
+                vir::Expr::eq_cmp(self.encoder.encode_value_expr(dst, dest_ty), value)
+            }
+            IntArithMode::Checked => {
+                let is_some = self.encoder.encode_variant_guard(dst.clone(), dest_ty, "Some");
+                let (payload, payload_ty) = self.encoder
+                    .encode_variant_payload(dst, dest_ty, "Some")
+                    .with_span(call_site_span)?;
+                vir::Expr::and(
+                    vir::Expr::eq_cmp(is_some.clone(), vir::Expr::not(overflow)),
+                    vir::Expr::implies(
+                        is_some,
+                        vir::Expr::eq_cmp(payload.field(value_field(payload_ty)), value),
+                    ),
+                )
+            }
+            IntArithMode::Overflowing => {
+                let field_types = if let ty::TyKind::Tuple(ref types) = dest_ty.kind() {
+                    types
+                } else {
+                    unreachable!()
+                };
+                let (value_ty, check_ty) = (field_types[0].expect_ty(), field_types[1].expect_ty());
+                let value_place = dst.clone().field(
+                    self.encoder.encode_raw_ref_field("tuple_0".to_string(), value_ty)
+                        .with_span(call_site_span)?
+                );
+                let check_place = dst.field(
+                    self.encoder.encode_raw_ref_field("tuple_1".to_string(), check_ty)
+                        .with_span(call_site_span)?
+                );
+                vir::Expr::and(
+                    vir::Expr::eq_cmp(value_place.field(value_field(value_ty)), value),
+                    vir::Expr::eq_cmp(check_place.field(value_field(check_ty)), overflow),
+                )
+            }
+        };
+        stmts.push(vir::Stmt::Inhale(spec, vir::FoldingBehaviour::Expr));
+        Ok(stmts)
+    }
+
+    /// Encode the call of the method `method` of a `Vec` like any other call,
+    /// with the built-in contract of `std_specs`. Indexing, inserting and
+    /// removing check that the index is within the bounds of the vector.
+    fn encode_vec_call(
+        &mut self,
+        location: mir::Location,
+        call_site_span: Span,
+        args: &[mir::Operand<'tcx>],
+        destination: &Option<(mir::Place<'tcx>, BasicBlockIndex)>,
+        called_def_id: ProcedureDefId,
+        self_ty: Option<&'tcx ty::TyS<'tcx>>,
+        method: &str,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        // args[0]: a reference to the vector, except for the constructors
+        // args[1]: the index or the element, if any
+        // args[2]: the element of `insert`
+        let (ref target_place, _) = destination.as_ref().unwrap();
+        let (dst, dest_ty, _) = self.mir_encoder.encode_place(target_place)
+            .with_span(call_site_span)?;
+        let is_constructor = matches!(method, "new" | "with_capacity");
+        let vec_ty = if is_constructor {
+            dest_ty
+        } else {
+            match self.mir_encoder.get_operand_ty(&args[0]).kind() {
+                ty::TyKind::Ref(_, vec_ty, _) => *vec_ty,
+                _ => unreachable!(),
+            }
+        };
+        let elem_ty = self.encoder.get_vec_elem_ty(vec_ty).unwrap();
+        let vec_snapshot = self.encoder.encode_snapshot(vec_ty).with_span(call_site_span)?;
+        let elem_snapshot = self.encoder.encode_snapshot(elem_ty).with_span(call_site_span)?;
+        // Without a snapshot of the elements, the snapshot of the vector is
+        // not a sequence.
+        if vec_snapshot.snap_domain.is_some() {
+            return Err(SpannedEncodingError::unsupported(
+                format!("the elements of a Vec of type '{}' cannot be modelled", elem_ty),
+                call_site_span,
+            ));
+        }
+        let vec_spec = VecSpec::new(self.encoder, elem_snapshot.get_type());
+        let is_primitive = matches!(
+            elem_ty.kind(),
+            ty::TyKind::Bool | ty::TyKind::Int(_) | ty::TyKind::Uint(_)
+        );
+        let encoder = self.encoder;
+        // The value of an element stored in `place`.
+        let elem_value = |place: vir::Expr| if is_primitive {
+            encoder.encode_value_expr(place, elem_ty)
+        } else {
+            elem_snapshot.snap_call(place)
+        };
+        // The value of an element passed as an operand, which is encoded as a
+        // value if the element is primitive and as a place otherwise.
+        let elem_operand_value = |expr: vir::Expr| if is_primitive {
+            expr
+        } else {
+            elem_snapshot.snap_call(expr)
+        };
+
+        // The call returns only if the index is within the bounds.
+        let mut stmts = vec![];
+        let vec = if is_constructor {
+            None
+        } else {
+            Some(self.mir_encoder.encode_operand_expr(&args[0]).with_span(call_site_span)?)
+        };
+        let index = match method {
+            "index" | "insert" | "remove" => {
+                let index = self.mir_encoder.encode_operand_expr(&args[1])
+                    .with_span(call_site_span)?;
+                let in_bounds = vec_spec.precondition(
+                    method,
+                    vec_snapshot.snap_call(vec.clone().unwrap()),
+                    index.clone(),
+                ).unwrap();
+                if self.check_panics && self.obligations.bounds {
+                    let pos = self.encoder.error_manager().register(
+                        call_site_span,
+                        ErrorCtxt::BoundsCheck,
+                    );
+                    stmts.push(vir::Stmt::Assert(in_bounds, vir::FoldingBehaviour::Expr, pos));
+                } else {
+                    stmts.push(vir::Stmt::Inhale(in_bounds, vir::FoldingBehaviour::Expr));
+                }
+                Some(index)
+            }
+            _ => None,
+        };
+        stmts.extend(self.encode_impure_function_call(
+            location,
+            call_site_span,
+            args,
+            destination,
+            called_def_id,
+            self_ty,
+        )?);
+
+        // The arguments are read in the state before the call.
+        let pre_label = self.label_after_location[&location].clone();
+        let old_vec = vec.clone().map(|vec| vec_snapshot.snap_call(vec).old(&pre_label));
+        let value = match method {
+            "push" => Some(&args[1]),
+            "insert" => Some(&args[2]),
+            _ => None,
+        }.map(|operand| -> SpannedEncodingResult<_> {
+            let value = self.mir_encoder.encode_operand_expr(operand)
+                .with_span(call_site_span)?;
+            Ok(elem_operand_value(value).old(&pre_label))
+        }).transpose()?;
+        let result = match method {
+            "len" | "is_empty" => Some(self.encoder.encode_value_expr(dst.clone(), dest_ty)),
+            "index" => Some(elem_value(dst.clone().field(
+                self.encoder.encode_dereference_field(elem_ty).with_span(call_site_span)?
+            ))),
+            "remove" => Some(elem_value(dst.clone())),
+            _ => None,
+        };
+        let popped = if method == "pop" {
+            let is_some = self.encoder.encode_variant_guard(dst.clone(), dest_ty, "Some");
+            let (payload, _) = self.encoder
+                .encode_variant_payload(dst.clone(), dest_ty, "Some")
+                .with_span(call_site_span)?;
+            Some((is_some, elem_value(payload)))
+        } else {
+            None
+        };
+        let call = VecCall {
+            vec: match method {
+                "new" | "with_capacity" => vec_snapshot.snap_call(dst),
+                "len" | "is_empty" | "index" => old_vec.clone().unwrap(),
+                _ => vec_snapshot.snap_call(vec.unwrap()),
+            },
+            old_vec,
+            index: index.map(|index| index.old(&pre_label)),
+            value,
+            result,
+            popped,
+        };
+        stmts.extend(vec_spec.postcondition(method, &call).into_iter().map(|fact| {
+            vir::Stmt::Inhale(fact, vir::FoldingBehaviour::Expr)
+        }));
+        Ok(stmts)
+    }
+
+    /// Encode an edge of the MIR graph
+    fn encode_edge_block(
+        &mut self,
+        source: BasicBlockIndex,
+        destination: BasicBlockIndex,
+        force_block: bool,
+    ) -> SpannedEncodingResult<Option<CfgBlockIndex>> {
+        let source_loc = mir::Location {
+            block: source,
+            statement_index: self.mir[source].statements.len(),
+        };
+        let destination_loc = mir::Location {
+            block: destination,
+            statement_index: 0,
+        };
+        let stmts = self.encode_expiring_borrows_between(source_loc, destination_loc)?;
+
+        if force_block || !stmts.is_empty() {
+            let edge_label = self.cfg_method.get_fresh_label_name();
+            let edge_block = self.cfg_method.add_block(
+                &edge_label,
+                vec![
+                    vir::Stmt::comment(format!("========== {} ==========", edge_label)),
+                    vir::Stmt::comment(format!("MIR edge {:?} --> {:?}", source, destination)),
+                ],
+            );
+            if !stmts.is_empty() {
+                self.cfg_method
+                    .add_stmt(edge_block, vir::Stmt::comment("Expire borrows"));
+                self.cfg_method.add_stmts(edge_block, stmts);
+            }
+            Ok(Some(edge_block))
         } else {
             Ok(None)
         }
     }
 
+    /// Encode a call of a closure whose type is a type parameter of the procedure, e.g. `f(x)`
+    /// with `f: F` and `F: Fn(i32) -> i32`. The contract of the call is given by the
+    /// specification entailments `f |= |x| [requires(..), ensures(..)]` of the precondition of
+    /// the procedure, which are checked by its callers against the closure that they pass: the
+    /// `requires` clauses are asserted before the call and the `ensures` clauses are assumed
+    /// after it. Without an entailment, the result of the call is arbitrary.
+    fn encode_closure_param_call(
+        &mut self,
+        location: mir::Location,
+        call_site_span: Span,
+        args: &[mir::Operand<'tcx>],
+        destination: &Option<(mir::Place<'tcx>, BasicBlockIndex)>,
+        cl_type: ty::Ty<'tcx>,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        // The arguments of the closure are tupled up in the second argument of the call.
+        assert_eq!(args.len(), 2);
+        let arg_tuple_ty = self.mir_encoder.get_operand_ty(&args[1]);
+        let arg_tuple = self.mir_encoder.encode_operand_place(&args[1])
+            .with_span(call_site_span)?;
+        let mut call_args = vec![];
+        if let (ty::TyKind::Tuple(substs), Some(arg_tuple)) = (arg_tuple_ty.kind(), arg_tuple) {
+            for (field_num, ty) in substs.iter().enumerate() {
+                let arg_ty = ty.expect_ty();
+                let field = self.encoder
+                    .encode_raw_ref_field(format!("tuple_{}", field_num), arg_ty)
+                    .with_span(call_site_span)?;
+                call_args.push(
+                    arg_tuple.clone().field(field).field(self.encoder.encode_value_field(arg_ty))
+                );
+            }
+        } else {
+            return Err(SpannedEncodingError::unsupported(
+                "the arguments of a closure call must be a tuple stored in a local variable",
+                call_site_span,
+            ));
+        }
+        let call_result = self.encode_pure_function_call_lhs_value(destination)
+            .with_span(call_site_span)?;
+
+        let contract = self.procedure_contract();
+        let encoded_args: Vec<vir::Expr> = contract
+            .args
+            .iter()
+            .map(|local| self.encode_prusti_local(*local).into())
+            .collect();
+        let mut pres = vec![];
+        let mut posts = vec![];
+        for assertion in contract.functional_precondition() {
+            let (assertion_pres, assertion_posts) = self.encoder.encode_closure_call_contract(
+                assertion,
+                cl_type,
+                self.mir,
+                &encoded_args,
+                &call_args,
+                &call_result,
+            )?;
+            pres.extend(assertion_pres);
+            posts.extend(assertion_posts);
+        }
+
+        let mut stmts = vec![];
+        let callee_name = format!("{}", cl_type);
+        if self.obligations.pre {
+            for (clause, clause_spans) in pres {
+                let clause_pos = self.encoder.error_manager().register(
+                    call_site_span,
+                    ErrorCtxt::ExhaleMethodPrecondition(
+                        callee_name.clone(),
+                        self.clause_source_text(&clause_spans)
+                            .map(|clause_text| (clause_text, clause_spans)),
+                    ),
+                );
+                stmts.push(vir::Stmt::Assert(
+                    clause,
+                    vir::FoldingBehaviour::Expr,
+                    clause_pos,
+                ));
+            }
+        } else {
+            stmts.push(vir::Stmt::comment("The precondition will not be checked"));
+        }
+
+        let (call_stmts, label) = self.encode_pure_function_call_site(
+            location,
+            destination,
+            posts.into_iter().map(|(clause, _)| clause).conjoin(),
+        );
+        stmts.extend(call_stmts);
+        self.encode_transfer_args_permissions(location, args, &mut stmts, label)?;
+        Ok(stmts)
+    }
+
     fn encode_impure_function_call(
         &mut self,
         location: mir::Location,
@@ -2472,7 +3615,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             expr
         };
 
-        let procedure_contract = {
+        let mut procedure_contract = {
             self.encoder.get_procedure_contract_for_call(
                 self_ty,
                 called_def_id,
@@ -2480,6 +3623,15 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 target_local,
             ).with_span(call_site_span)?
         };
+        if !self.encoder.env().tcx().is_closure(called_def_id) {
+            let stored_refs = self.encode_stored_references(
+                called_def_id,
+                mir_args,
+                &arguments,
+                call_site_span,
+            )?;
+            procedure_contract.returned_refs.extend(stored_refs);
+        }
 
         // Store a label for the pre state
         let pre_label = self.cfg_method.get_fresh_label_name();
@@ -2499,20 +3651,90 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             _, // We don't care about verifying that the weakening is valid,
                // since it isn't the task of the caller
         ) = self.encode_precondition_expr(&procedure_contract, None)?;
-        let pos = self
-            .encoder
-            .error_manager()
-            .register(call_site_span, ErrorCtxt::ExhaleMethodPrecondition);
-        stmts.push(vir::Stmt::Assert(
-            replace_fake_exprs(pre_func_spec),
-            vir::FoldingBehaviour::Stmt, // TODO: Should be Expr.
-            pos,
-        ));
+        let callee_name = self.encoder.env().get_absolute_item_name(called_def_id);
+        let pos = self.encoder.error_manager().register(
+            call_site_span,
+            ErrorCtxt::ExhaleMethodPrecondition(callee_name.clone(), None),
+        );
+        if self.obligations.pre {
+            // Each clause is asserted separately, to report the failing one
+            for (clause, clause_spans) in pre_func_spec {
+                let clause_pos = match self.clause_source_text(&clause_spans) {
+                    Some(clause_text) => self.encoder.error_manager().register(
+                        call_site_span,
+                        ErrorCtxt::ExhaleMethodPrecondition(
+                            callee_name.clone(),
+                            Some((clause_text, clause_spans)),
+                        ),
+                    ),
+                    None => pos,
+                };
+                stmts.push(vir::Stmt::Assert(
+                    replace_fake_exprs(clause),
+                    vir::FoldingBehaviour::Stmt, // TODO: Should be Expr.
+                    clause_pos,
+                ));
+            }
+        } else {
+            stmts.push(vir::Stmt::comment("The precondition will not be checked"));
+            stmts.push(vir::Stmt::Inhale(
+                replace_fake_exprs(pre_func_spec.into_iter().map(|(clause, _)| clause).conjoin()),
+                vir::FoldingBehaviour::Stmt,
+            ));
+        }
+        let invs_pos = self.encoder.error_manager().register(
+            call_site_span,
+            ErrorCtxt::AssertMethodPreconditionTypeInvariants(callee_name.clone()),
+        );
         stmts.push(vir::Stmt::Assert(
             replace_fake_exprs(pre_invs_spec),
             vir::FoldingBehaviour::Stmt,
-            pos,
+            invs_pos,
         ));
+
+        // Pay for the call, if both procedures have a complexity bound
+        let callee_has_bound = procedure_contract.specification
+            .expect_procedure()
+            .complexity
+            .is_some();
+        if let (Some(credits_var), true) = (self.credits_var.clone(), callee_has_bound) {
+            let callee_credits = self.cfg_method.add_fresh_local_var(vir::Type::Int);
+            let callee_bound = self.encode_complexity_bound(
+                &procedure_contract,
+                callee_credits.clone().into(),
+            )?.unwrap();
+            stmts.push(vir::Stmt::Inhale(
+                replace_fake_exprs(callee_bound),
+                vir::FoldingBehaviour::Expr,
+            ));
+            let credits_pos = self
+                .encoder
+                .error_manager()
+                .register(call_site_span, ErrorCtxt::AssertCallCredits);
+            stmts.extend(self.encode_credits_payment(
+                credits_var,
+                vir::Expr::add(callee_credits.into(), 1.into()),
+                credits_pos,
+            ));
+        }
+
+        // Check that a recursive call decreases the termination measure
+        let is_recursive_call = self.encoder
+            .is_termination_checked_call(self.proc_def_id, called_def_id);
+        if let (Some(measure_var), true) = (self.measure_var.clone(), is_recursive_call) {
+            let callee_measure = self.encode_termination_measure(&procedure_contract)?.unwrap();
+            let measure_pos = self
+                .encoder
+                .error_manager()
+                .register(call_site_span, ErrorCtxt::AssertCallTerminationMeasure);
+            stmts.push(vir::Stmt::Assert(
+                replace_fake_exprs(
+                    termination::measure_decreases(callee_measure, measure_var.into())
+                ),
+                vir::FoldingBehaviour::Expr,
+                measure_pos,
+            ));
+        }
         let pre_perm_spec = replace_fake_exprs(pre_type_spec.clone());
         assert!(!pos.is_default());
         stmts.push(vir::Stmt::Exhale(
@@ -2645,16 +3867,59 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 .with_span(call_site_span)?;
             arg_exprs.push(arg_expr);
         }
+        arg_exprs.extend(
+            self.encoder.encode_const_param_args(called_def_id).with_span(call_site_span)?
+        );
+
+        let mut stmts = vec![];
+        if self.encoder.is_lemma(called_def_id) {
+            // Applying a lemma checks its precondition and instantiates its
+            // postcondition for the arguments of the call.
+            let pos = self
+                .encoder
+                .error_manager()
+                .register(call_site_span, ErrorCtxt::LemmaApplication);
+            let lemma_call = vir::Expr::func_app(
+                function_name.clone(),
+                arg_exprs.clone(),
+                self.encode_pure_function_call_formal_args(args, called_def_id, call_site_span)?,
+                return_type.clone(),
+                pos,
+            );
+            stmts.push(vir::Stmt::Assert(lemma_call, vir::FoldingBehaviour::Expr, pos));
+        }
 
-        self.encode_specified_pure_function_call(
+        stmts.extend(self.encode_specified_pure_function_call(
             location,
             call_site_span,
             args,
             destination,
+            called_def_id,
             function_name,
             arg_exprs,
             return_type,
-        )
+        )?);
+        Ok(stmts)
+    }
+
+    fn encode_pure_function_call_formal_args(
+        &self,
+        args: &[mir::Operand<'tcx>],
+        called_def_id: ProcedureDefId,
+        call_site_span: Span,
+    ) -> SpannedEncodingResult<Vec<vir::LocalVar>> {
+        let mut formal_args: Vec<vir::LocalVar> = args.iter()
+            .enumerate()
+            .map(|(i, arg)| {
+                self.mir_encoder.encode_operand_expr_type(arg)
+                    .map(|ty| vir::LocalVar::new(format!("x{}", i), ty))
+            })
+            .collect::<Result<_, _>>()
+            .with_span(call_site_span)?;
+        formal_args.extend(
+            self.encoder.encode_const_param_formal_args(called_def_id).with_span(call_site_span)?
+        );
+        Ok(formal_args)
     }
 
     fn encode_specified_pure_function_call(
@@ -2663,19 +3928,16 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         call_site_span: Span,
         args: &[mir::Operand<'tcx>],
         destination: &Option<(mir::Place<'tcx>, BasicBlockIndex)>,
+        called_def_id: ProcedureDefId,
         function_name: String,
         arg_exprs: Vec<Expr>,
         return_type: Type,
     ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
-        let formal_args: Vec<vir::LocalVar> = args
-            .iter()
-            .enumerate()
-            .map(|(i, arg)| {
-                self.mir_encoder.encode_operand_expr_type(arg)
-                    .map(|ty| vir::LocalVar::new(format!("x{}", i), ty))
-            })
-            .collect::<Result<_, _>>()
-            .with_span(call_site_span)?;
+        let formal_args = self.encode_pure_function_call_formal_args(
+            args,
+            called_def_id,
+            call_site_span,
+        )?;
 
         let pos = self
             .encoder
@@ -2777,6 +4039,111 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         (stmts, label)
     }
 
+    /// Find the mutable references that a call stores in a collection, e.g. `refs.push(&mut x)`
+    /// with `refs: Vec<&mut i32>`, and return their targets as places whose permission is given
+    /// back to the caller with an unknown value. The caller cannot access the referenced memory
+    /// before the collection is dropped, so it cannot observe the changes done through the
+    /// collection. This is only sound if the collection is a local variable, which does not
+    /// outlive the procedure; otherwise, an error explains the aliasing.
+    fn encode_stored_references(
+        &self,
+        called_def_id: ProcedureDefId,
+        mir_args: &[mir::Operand<'tcx>],
+        arguments: &[Local],
+        call_site_span: Span,
+    ) -> SpannedEncodingResult<Vec<(Place<'tcx>, Mutability)>> {
+        let tcx = self.encoder.env().tcx();
+        let is_mut_ref = |ty: ty::Ty<'tcx>| {
+            matches!(ty.kind(), ty::TyKind::Ref(_, _, Mutability::Mut))
+        };
+        // The collections in which a reference can be stored.
+        let collection_locals: Vec<mir::Local> = mir_args.iter()
+            .filter_map(|arg| {
+                let arg_ty = self.mir_encoder.get_operand_ty(arg);
+                match arg_ty.kind() {
+                    ty::TyKind::Ref(_, collection_ty, Mutability::Mut) => {
+                        let stores_mut_refs = collection_ty.walk().any(|inner| {
+                            matches!(inner.unpack(), ty::subst::GenericArgKind::Type(inner_ty) if is_mut_ref(inner_ty))
+                        });
+                        if stores_mut_refs {
+                            arg.place().and_then(|place| place.as_local())
+                        } else {
+                            None
+                        }
+                    }
+                    _ => None,
+                }
+            })
+            .collect();
+        if collection_locals.is_empty() {
+            return Ok(vec![]);
+        }
+        // The mutable references moved into a parameter whose type is generic.
+        let formal_tys = tcx.fn_sig(called_def_id).skip_binder().inputs();
+        let mut stored_refs = vec![];
+        for (index, (arg, formal_ty)) in mir_args.iter().zip(formal_tys.iter()).enumerate() {
+            let is_stored = matches!(arg, mir::Operand::Move(_))
+                && is_mut_ref(self.mir_encoder.get_operand_ty(arg))
+                && matches!(formal_ty.kind(), ty::TyKind::Param(_));
+            if !is_stored {
+                continue;
+            }
+            for &collection_local in &collection_locals {
+                let is_local_collection = match self.get_borrowed_place(collection_local) {
+                    Some(place) => {
+                        !place.is_indirect()
+                            && place.local != mir::RETURN_PLACE
+                            && !self.mir.args_iter().any(|arg| arg == place.local)
+                    }
+                    None => false,
+                };
+                if !is_local_collection {
+                    return Err(SpannedEncodingError::unsupported(
+                        format!(
+                            "storing a mutable reference by calling '{}' is only supported if the \
+                            collection is a local variable, because the collection aliases the \
+                            referenced memory until it is dropped",
+                            tcx.def_path_str(called_def_id),
+                        ),
+                        call_site_span,
+                    ));
+                }
+            }
+            let callee_arg = mir::Local::from_usize(index + 1);
+            stored_refs.push((
+                Place::SubstitutedPlace {
+                    substituted_root: arguments[index],
+                    place: tcx.mk_place_deref(callee_arg.into()),
+                },
+                Mutability::Mut,
+            ));
+        }
+        Ok(stored_refs)
+    }
+
+    /// The place that is borrowed to obtain the reference stored in `local`, if `local` is only
+    /// assigned by a borrow.
+    fn get_borrowed_place(&self, local: mir::Local) -> Option<mir::Place<'tcx>> {
+        let mut borrowed_places = vec![];
+        for bb_data in self.mir.basic_blocks() {
+            for stmt in &bb_data.statements {
+                if let mir::StatementKind::Assign(box (lhs, rhs)) = &stmt.kind {
+                    if lhs.as_local() == Some(local) {
+                        match rhs {
+                            mir::Rvalue::Ref(_, _, place) => borrowed_places.push(*place),
+                            _ => return None,
+                        }
+                    }
+                }
+            }
+        }
+        if borrowed_places.len() == 1 {
+            borrowed_places.pop()
+        } else {
+            None
+        }
+    }
+
     // Transfer the permissions for the arguments used in the call
     fn encode_transfer_args_permissions(
         &mut self,
@@ -2880,7 +4247,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
     /// Encode the precondition with three expressions:
     /// - one for the type encoding
     /// - one for the type invariants
-    /// - one for the functional specification.
+    /// - one for each clause of the functional specification, with its span.
     fn encode_precondition_expr(
         &self,
         contract: &ProcedureContract<'tcx>,
@@ -2889,7 +4256,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         vir::Expr,
         Vec<vir::Expr>,
         vir::Expr,
-        vir::Expr,
+        Vec<(vir::Expr, MultiSpan)>,
         Option<vir::Expr>,
     )> {
         let borrow_infos = &contract.borrow_infos;
@@ -2939,29 +4306,14 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             };
         }
 
-        let mut func_spec: Vec<vir::Expr> = vec![];
-
         // Encode functional specification
         let encoded_args: Vec<vir::Expr> = contract
             .args
             .iter()
             .map(|local| self.encode_prusti_local(*local).into())
             .collect();
+        let func_spec = self.encode_functional_precondition_clauses(contract)?;
         let func_precondition = contract.functional_precondition();
-        for assertion in func_precondition {
-            // FIXME
-            let value = self.encoder.encode_assertion(
-                &assertion,
-                &self.mir,
-                None,
-                &encoded_args,
-                None,
-                false,
-                None,
-                ErrorCtxt::GenericExpression,
-            )?;
-            func_spec.push(value);
-        }
         let precondition_spans = MultiSpan::from_spans(
             func_precondition
                 .iter()
@@ -3001,20 +4353,55 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             type_spec.into_iter().conjoin(),
             mandatory_type_spec,
             invs_spec.into_iter().conjoin(),
-            func_spec
-                .into_iter()
-                .map(|spec|
-                    SnapshotSpecPatcher::new(self.encoder)
-                        .patch_spec(spec)
-                        .with_span(precondition_spans.clone())
-                )
-                .collect::<SpannedEncodingResult<Vec<_>>>()?
-                .into_iter()
-                .conjoin(),
+            func_spec,
             precondition_weakening,
         ))
     }
 
+    /// Encode each `requires` clause of `contract`, together with its span.
+    fn encode_functional_precondition_clauses(
+        &self,
+        contract: &ProcedureContract<'tcx>,
+    ) -> SpannedEncodingResult<Vec<(vir::Expr, MultiSpan)>> {
+        let encoded_args: Vec<vir::Expr> = contract
+            .args
+            .iter()
+            .map(|local| self.encode_prusti_local(*local).into())
+            .collect();
+        let mut clauses = vec![];
+        for assertion in contract.functional_precondition() {
+            // FIXME
+            let value = self.encoder.encode_assertion(
+                &assertion,
+                &self.mir,
+                None,
+                &encoded_args,
+                None,
+                false,
+                None,
+                ErrorCtxt::GenericExpression,
+            )?;
+            let spans = MultiSpan::from_spans(
+                typed::Spanned::get_spans(assertion, &self.mir, self.encoder.env().tcx())
+            );
+            let value = SnapshotSpecPatcher::new(self.encoder)
+                .patch_spec(value)
+                .with_span(spans.clone())?;
+            clauses.push((value, spans));
+        }
+        Ok(clauses)
+    }
+
+    /// The source text of a `requires` clause whose spans are `spans`.
+    fn clause_source_text(&self, spans: &MultiSpan) -> Option<String> {
+        let primary_spans = spans.primary_spans();
+        let first = primary_spans.first()?;
+        let clause_span = primary_spans[1..]
+            .iter()
+            .fold(*first, |span, other| span.to(*other));
+        self.encoder.env().tcx().sess.source_map().span_to_snippet(clause_span).ok()
+    }
+
     /// Encode precondition inhale on the definition side.
     fn encode_preconditions(
         &mut self,
@@ -3054,8 +4441,31 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         }
         self.cfg_method.add_stmt(
             start_cfg_block,
-            vir::Stmt::Inhale(func_spec, vir::FoldingBehaviour::Expr),
+            vir::Stmt::Inhale(
+                func_spec.into_iter().map(|(clause, _)| clause).conjoin(),
+                vir::FoldingBehaviour::Expr,
+            ),
         );
+        if let Some(credits_var) = self.credits_var.clone() {
+            let bound = self.encode_complexity_bound(
+                self.procedure_contract(),
+                credits_var.into(),
+            )?.unwrap();
+            self.cfg_method.add_stmt(
+                start_cfg_block,
+                vir::Stmt::Inhale(bound, vir::FoldingBehaviour::Expr),
+            );
+        }
+        if let Some(measure_var) = self.measure_var.clone() {
+            let measure = self.encode_termination_measure(self.procedure_contract())?.unwrap();
+            self.cfg_method.add_stmt(
+                start_cfg_block,
+                vir::Stmt::Assign(measure_var.into(), measure, vir::AssignKind::Copy),
+            );
+        }
+        if config::counterexample() {
+            self.encode_counterexample_snapshots(start_cfg_block)?;
+        }
         self.cfg_method.add_stmt(
             start_cfg_block,
             vir::Stmt::Label(PRECONDITION_LABEL.to_string()),
@@ -3063,6 +4473,245 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         Ok(())
     }
 
+    /// Record the initial values of the primitive arguments, and of the
+    /// primitive data reachable from the arguments, in ghost variables. The
+    /// values of these variables in the model of a failure are the reported
+    /// counterexample.
+    fn encode_counterexample_snapshots(
+        &mut self,
+        start_cfg_block: CfgBlockIndex,
+    ) -> SpannedEncodingResult<()> {
+        let mut arg_names = self.local_debug_names();
+        for local in self.mir.args_iter() {
+            let rust_name = match arg_names.remove(&local) {
+                Some(rust_name) => rust_name,
+                None => continue,
+            };
+            let span = self.mir_encoder.get_local_span(local);
+            let encoded_local = self.mir_encoder.encode_local(local)?;
+            let mut snapshots = vec![];
+            self.collect_counterexample_snapshots(
+                encoded_local.into(),
+                self.mir_encoder.get_local_ty(local),
+                rust_name,
+                0,
+                &mut snapshots,
+            ).with_span(span)?;
+            for (place, rust_expr) in snapshots {
+                let var_name: String = format!("_cex${}", place)
+                    .chars()
+                    .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '$' })
+                    .collect();
+                let var = vir::LocalVar::new(var_name.clone(), place.get_type().clone());
+                self.cfg_method.add_local_var(&var.name, var.typ.clone());
+                self.cfg_method.add_stmt(
+                    start_cfg_block,
+                    vir::Stmt::Assign(vir::Expr::local(var), place, vir::AssignKind::Copy),
+                );
+                self.encoder.register_counterexample_var(self.proc_def_id, var_name, rust_expr);
+            }
+        }
+        Ok(())
+    }
+
+    /// The Rust names of the MIR locals that correspond to user variables.
+    fn local_debug_names(&self) -> HashMap<mir::Local, String> {
+        let mut names = HashMap::new();
+        for info in &self.mir.var_debug_info {
+            if let mir::VarDebugInfoContents::Place(place) = info.value {
+                if let Some(local) = place.as_local() {
+                    names.insert(local, info.name.to_string());
+                }
+            }
+        }
+        names
+    }
+
+    /// Record the values of the primitive places read by the loop invariant
+    /// `func_spec` in fresh ghost variables, which are registered for the
+    /// invariant checks at `positions`. The values of these variables in the
+    /// model of a failing check are the state at the loop head.
+    fn encode_loop_head_counterexample_snapshots(
+        &mut self,
+        func_spec: &[vir::Expr],
+        positions: &[vir::Position],
+    ) -> Vec<vir::Stmt> {
+        let mut collector = PrimitivePlaceCollector { places: vec![] };
+        for expr in func_spec {
+            vir::ExprWalker::walk(&mut collector, expr);
+        }
+        let local_names: HashMap<String, String> = self.local_debug_names()
+            .into_iter()
+            .map(|(local, name)| (self.mir_encoder.encode_local_var_name(local), name))
+            .collect();
+        let mut stmts = vec![];
+        for place in collector.places {
+            let rust_expr = match describe_counterexample_place(&place, &local_names) {
+                Some(rust_expr) => rust_expr,
+                None => continue,
+            };
+            let var = self.cfg_method.add_fresh_local_var(place.get_type().clone());
+            for pos in positions {
+                self.encoder.register_loop_head_counterexample_var(
+                    pos.id(),
+                    var.name.clone(),
+                    rust_expr.clone(),
+                );
+            }
+            stmts.push(vir::Stmt::Assign(vir::Expr::local(var), place, vir::AssignKind::Copy));
+        }
+        stmts
+    }
+
+    /// Collect the places of primitive type that are reachable from `place`
+    /// by following at most `COUNTEREXAMPLE_DEPTH` fields and references,
+    /// together with the corresponding Rust expression.
+    fn collect_counterexample_snapshots(
+        &self,
+        place: vir::Expr,
+        ty: ty::Ty<'tcx>,
+        rust_expr: String,
+        depth: usize,
+        snapshots: &mut Vec<(vir::Expr, String)>,
+    ) -> EncodingResult<()> {
+        const COUNTEREXAMPLE_DEPTH: usize = 2;
+        match ty.kind() {
+            ty::TyKind::Bool
+            | ty::TyKind::Int(_)
+            | ty::TyKind::Uint(_)
+            | ty::TyKind::Char => {
+                let value_field = self.encoder.encode_value_field(ty);
+                snapshots.push((place.field(value_field), rust_expr));
+            }
+            _ if depth >= COUNTEREXAMPLE_DEPTH => {}
+            ty::TyKind::Ref(_, pointee_ty, _) => {
+                let deref_field = self.encoder.encode_dereference_field(*pointee_ty)?;
+                // The fields of a struct are accessed through auto-deref
+                let deref_expr = if pointee_ty.is_adt() {
+                    rust_expr
+                } else {
+                    format!("*{}", rust_expr)
+                };
+                self.collect_counterexample_snapshots(
+                    place.field(deref_field),
+                    *pointee_ty,
+                    deref_expr,
+                    depth + 1,
+                    snapshots,
+                )?;
+            }
+            ty::TyKind::Adt(adt_def, substs) if adt_def.is_struct() => {
+                let tcx = self.encoder.env().tcx();
+                for field in &adt_def.non_enum_variant().fields {
+                    let field_name = field.ident.to_string();
+                    let field_ty = field.ty(tcx, substs);
+                    let encoded_field = self.encoder.encode_struct_field(&field_name, field_ty)?;
+                    self.collect_counterexample_snapshots(
+                        place.clone().field(encoded_field),
+                        field_ty,
+                        format!("{}.{}", rust_expr, field_name),
+                        depth + 1,
+                        snapshots,
+                    )?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Encode the `#[complexity]` annotation of `contract`, if any, as an
+    /// assertion that defines `credits` to be the bound of the procedure.
+    fn encode_complexity_bound(
+        &self,
+        contract: &ProcedureContract<'tcx>,
+        credits: vir::Expr,
+    ) -> SpannedEncodingResult<Option<vir::Expr>> {
+        let complexity = match contract.specification.expect_procedure().complexity {
+            Some(ref complexity) => complexity,
+            None => return Ok(None),
+        };
+        let encoded_args: Vec<vir::Expr> = contract
+            .args
+            .iter()
+            .map(|local| self.encode_prusti_local(*local).into())
+            .collect();
+        let spans = MultiSpan::from_spans(
+            typed::Spanned::get_spans(complexity, &self.mir, self.encoder.env().tcx())
+        );
+        let bound = self.encoder.encode_assertion(
+            complexity,
+            &self.mir,
+            None,
+            &encoded_args,
+            None,
+            false,
+            None,
+            ErrorCtxt::GenericExpression,
+        )?;
+        let bound = SnapshotSpecPatcher::new(self.encoder)
+            .patch_spec(bound)
+            .with_span(spans)?;
+        let credits_place = vir::Expr::local(vir::LocalVar::new(CREDITS_VAR_NAME, vir::Type::Int));
+        Ok(Some(bound.replace_place(&credits_place, &credits)))
+    }
+
+    /// Encode the termination measure of the `#[decreases]` annotation of
+    /// `contract`, if any.
+    fn encode_termination_measure(
+        &self,
+        contract: &ProcedureContract<'tcx>,
+    ) -> SpannedEncodingResult<Option<vir::Expr>> {
+        let decreases = match contract.specification.expect_procedure().decreases {
+            Some(ref decreases) => decreases,
+            None => return Ok(None),
+        };
+        let encoded_args: Vec<vir::Expr> = contract
+            .args
+            .iter()
+            .map(|local| self.encode_prusti_local(*local).into())
+            .collect();
+        let spans = MultiSpan::from_spans(
+            typed::Spanned::get_spans(decreases, &self.mir, self.encoder.env().tcx())
+        );
+        let assertion = self.encoder.encode_assertion(
+            decreases,
+            &self.mir,
+            None,
+            &encoded_args,
+            None,
+            false,
+            None,
+            ErrorCtxt::GenericExpression,
+        )?;
+        let assertion = SnapshotSpecPatcher::new(self.encoder)
+            .patch_spec(assertion)
+            .with_span(spans.clone())?;
+        Ok(Some(termination::extract_measure(assertion).with_span(spans)?))
+    }
+
+    /// Check that `cost` credits are left, and consume them.
+    fn encode_credits_payment(
+        &self,
+        credits_var: vir::LocalVar,
+        cost: vir::Expr,
+        pos: vir::Position,
+    ) -> Vec<vir::Stmt> {
+        let credits: vir::Expr = credits_var.into();
+        vec![
+            vir::Stmt::Assert(
+                vir::Expr::ge_cmp(credits.clone(), cost.clone()),
+                vir::FoldingBehaviour::Expr,
+                pos,
+            ),
+            vir::Stmt::Assign(
+                credits.clone(),
+                vir::Expr::sub(credits, cost),
+                vir::AssignKind::Copy,
+            ),
+        ]
+    }
+
     /// Encode the magic wand used in the postcondition with its
     /// functional specification. Returns (lhs, rhs).
     fn encode_postcondition_magic_wand(
@@ -3783,7 +5432,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             return_cfg_block,
             vir::Stmt::comment("Assert possible strengthening"),
         );
-        if let Some(strengthening_spec) = strengthening_spec {
+        let check_postconditions = self.obligations.post;
+        if let (Some(strengthening_spec), true) = (strengthening_spec, check_postconditions) {
             let patched_strengthening_spec =
                 self.replace_old_places_with_ghost_vars(None, strengthening_spec);
             let pos = patched_strengthening_spec.pos();
@@ -3802,11 +5452,13 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             .encoder
             .error_manager()
             .register(self.mir.span, ErrorCtxt::AssertMethodPostcondition);
-        let patched_func_spec = self.replace_old_places_with_ghost_vars(None, func_spec);
-        self.cfg_method.add_stmt(
-            return_cfg_block,
-            vir::Stmt::Assert(patched_func_spec, vir::FoldingBehaviour::Expr, func_pos),
-        );
+        if check_postconditions {
+            let patched_func_spec = self.replace_old_places_with_ghost_vars(None, func_spec);
+            self.cfg_method.add_stmt(
+                return_cfg_block,
+                vir::Stmt::Assert(patched_func_spec, vir::FoldingBehaviour::Expr, func_pos),
+            );
+        }
 
         // Assert type invariants
         self.cfg_method.add_stmt(
@@ -4150,10 +5802,118 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 encoded_specs.push(encoded_spec.set_default_pos(spec_pos));
                 encoded_spec_spans.extend(spec_spans);
             }
-            trace!("encoded_specs: {:?}", encoded_specs);
+            trace!("encoded_specs: {:?}", encoded_specs);
+        }
+
+        if specs.is_empty() {
+            if let Some((inferred_specs, _)) = self.inferred_loop_invariants.get(&loop_head) {
+                let loop_span = self.get_loop_span(loop_head);
+                let spec_pos = self.encoder.error_manager().register_span(loop_span);
+                encoded_specs.extend(
+                    inferred_specs.iter().map(|spec| spec.clone().set_default_pos(spec_pos))
+                );
+                encoded_spec_spans.push(loop_span);
+            }
+        }
+
+        Ok((encoded_specs, MultiSpan::from_spans(encoded_spec_spans)))
+    }
+
+    /// Encode the termination measure given by the `body_variant!` of a loop,
+    /// if any.
+    fn encode_loop_variant(
+        &self,
+        loop_head: BasicBlockIndex,
+        loop_inv_block: BasicBlockIndex,
+    ) -> SpannedEncodingResult<Option<(vir::Expr, MultiSpan)>> {
+        let mut variant = None;
+        for bbi in self.get_loop_spec_blocks(loop_head) {
+            for stmt in &self.mir.basic_blocks()[bbi].statements {
+                if let mir::StatementKind::Assign(box (
+                    _,
+                    mir::Rvalue::Aggregate(box mir::AggregateKind::Closure(cl_def_id, _), _),
+                )) = stmt.kind {
+                    variant = variant.or(self.encoder.get_loop_specs(cl_def_id).unwrap().variant);
+                }
+            }
+        }
+        let variant = match variant {
+            Some(variant) => variant,
+            None => return Ok(None),
+        };
+        let encoded_args: Vec<vir::Expr> = self
+            .mir
+            .args_iter()
+            .map(|local| self.mir_encoder.encode_local(local).map(|l| l.into()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let spans = MultiSpan::from_spans(
+            typed::Spanned::get_spans(&variant, &self.mir, self.encoder.env().tcx())
+        );
+        let assertion = self.encoder.encode_assertion(
+            &variant,
+            &self.mir,
+            Some(PRECONDITION_LABEL),
+            &encoded_args,
+            None,
+            false,
+            Some(loop_inv_block),
+            ErrorCtxt::GenericExpression,
+        )?;
+        let measure = termination::extract_measure(assertion).with_span(spans.clone())?;
+        Ok(Some((measure, spans)))
+    }
+
+    /// Infer the invariant of a loop without `body_invariant!`, see the
+    /// `loop_invariant_inference` module.
+    fn infer_loop_invariant(
+        &mut self,
+        loop_head: BasicBlockIndex,
+        loop_body: &[BasicBlockIndex],
+        guard_block: Option<BasicBlockIndex>,
+    ) -> EncodingResult<()> {
+        let invariants = loop_invariant_inference::infer_loop_invariants(
+            &self.mir_encoder,
+            self.mir,
+            loop_body,
+            guard_block,
+        );
+        debug!("inferred loop invariants of {:?}: {:?}", loop_head, invariants);
+        let mut specs = vec![];
+        let mut entry_stmts = vec![];
+        for invariant in invariants {
+            match invariant {
+                InferredInvariant::Guard { op, left, right, negated } => {
+                    let guard = self.mir_encoder.encode_bin_op_expr(
+                        op,
+                        self.mir_encoder.encode_operand_expr(&left)?,
+                        self.mir_encoder.encode_operand_expr(&right)?,
+                        self.mir_encoder.get_operand_ty(&left),
+                    )?;
+                    specs.push(if negated { vir::Expr::not(guard) } else { guard });
+                }
+                InferredInvariant::Monotonic { local, increasing } => {
+                    let value = self.mir_encoder.encode_operand_expr(
+                        &mir::Operand::Copy(local.into())
+                    )?;
+                    let entry_value: vir::Expr =
+                        self.cfg_method.add_fresh_local_var(vir::Type::Int).into();
+                    entry_stmts.push(vir::Stmt::Assign(
+                        entry_value.clone(),
+                        value.clone(),
+                        vir::AssignKind::Copy,
+                    ));
+                    specs.push(if increasing {
+                        vir::Expr::ge_cmp(value, entry_value)
+                    } else {
+                        vir::Expr::le_cmp(value, entry_value)
+                    });
+                }
+            }
         }
-
-        Ok((encoded_specs, MultiSpan::from_spans(encoded_spec_spans)))
+        if !specs.is_empty() {
+            self.inferred_loop_invariants.insert(loop_head, (specs, entry_stmts));
+        }
+        Ok(())
     }
 
     fn encode_loop_invariant_exhale_stmts(
@@ -4213,6 +5973,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                     vir::AssignKind::Ghost,
                 ));
             }
+            if let Some((_, entry_stmts)) = self.inferred_loop_invariants.get(&loop_head) {
+                stmts.extend(entry_stmts.iter().cloned());
+            }
         }
         assert!(!assert_pos.is_default());
         let obtain_predicates = permissions.iter().map(|p| {
@@ -4220,11 +5983,18 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         });
         stmts.extend(obtain_predicates);
 
-        stmts.push(vir::Stmt::Assert(
-            func_spec.into_iter().conjoin(),
-            vir::FoldingBehaviour::Expr,
-            assert_pos,
-        ));
+        if config::counterexample() {
+            stmts.extend(
+                self.encode_loop_head_counterexample_snapshots(&func_spec, &[assert_pos, exhale_pos])
+            );
+        }
+        if self.obligations.invariants {
+            stmts.push(vir::Stmt::Assert(
+                func_spec.into_iter().conjoin(),
+                vir::FoldingBehaviour::Expr,
+                assert_pos,
+            ));
+        }
         let equalities_expr = equalities.into_iter().conjoin();
         stmts.push(vir::Stmt::Assert(
             equalities_expr,
@@ -4485,6 +6255,10 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 stmts
             }
 
+            mir::Operand::Constant(ref constant) if self.is_global_state_access(constant) => {
+                self.encode_assign_global_state(lhs, constant, location)?
+            }
+
             mir::Operand::Constant(box mir::Constant {
                 literal: ty::Const { ty, val }, ..
             }) => {
@@ -4581,6 +6355,11 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             right
         );
         let span = self.mir_encoder.get_span_of_location(location);
+        if self.mir_encoder.get_operand_ty(left).is_unsafe_ptr() {
+            let encoded_value = self.mir_encoder.encode_pointer_comparison(op, left, right)
+                .with_span(span)?;
+            return self.encode_copy_value_assign(encoded_lhs, encoded_value, ty, location);
+        }
         let encoded_left = self.mir_encoder.encode_operand_expr(left)
             .with_span(span)?;
         let encoded_right = self.mir_encoder.encode_operand_expr(right)
@@ -4720,9 +6499,27 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             .with_span(
                 self.mir_encoder.get_span_of_location(location)
             )?;
-        let encoded_value = self.mir_encoder.encode_unary_op_expr(op, encoded_val);
+        let mut stmts = vec![];
+        // The MIR usually asserts that there is no overflow before the
+        // operation, but not if it was built without overflow checks.
+        let opt_overflow = self.mir_encoder.encode_unary_op_check(op, encoded_val.clone(), ty);
+        if let Some(overflow) = opt_overflow {
+            if self.check_panics && self.obligations.overflow {
+                let span = self.mir_encoder.get_span_of_location(location);
+                stmts.push(vir::Stmt::Assert(
+                    vir::Expr::not(overflow),
+                    vir::FoldingBehaviour::Stmt,
+                    self.encoder.error_manager().register(
+                        span,
+                        ErrorCtxt::AssertTerminator("attempt to negate with overflow".to_string()),
+                    ),
+                ));
+            }
+        }
+        let encoded_value = self.mir_encoder.encode_unary_op_expr(op, encoded_val, ty);
         // Initialize `lhs.field`
-        self.encode_copy_value_assign(encoded_lhs, encoded_value, ty, location)
+        stmts.extend(self.encode_copy_value_assign(encoded_lhs, encoded_value, ty, location)?);
+        Ok(stmts)
     }
 
     fn encode_assign_nullary_op(
@@ -4777,6 +6574,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             location
         );
         let (encoded_src, src_ty, _) = self.mir_encoder.encode_place(src).unwrap(); // will panic if attempting to encode unsupported type
+        let (encoded_src, src_ty) = self.mir_encoder
+            .encode_auto_deref(encoded_src, src_ty)
+            .with_span(self.mir_encoder.get_span_of_location(location))?;
         let stmts = match src_ty.kind() {
             ty::TyKind::Adt(ref adt_def, _) if !adt_def.is_box() => {
                 let num_variants = adt_def.variants.len();
@@ -4839,12 +6639,17 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             place,
             location
         );
+        if mir_borrow_kind == mir::BorrowKind::Shallow {
+            // The shallow borrows of match guards are only used by `FakeRead`
+            // statements and have no loan, so there is nothing to encode.
+            return Ok(vec![]);
+        }
         let (encoded_value, _, _) = self.mir_encoder.encode_place(place).unwrap(); // will panic if attempting to encode unsupported type
         let loan = self.polonius_info().get_loan_at_location(location);
         let vir_assign_kind = match mir_borrow_kind {
             mir::BorrowKind::Shared => vir::AssignKind::SharedBorrow(loan.into()),
             mir::BorrowKind::Unique => unimplemented!(),
-            mir::BorrowKind::Shallow => unimplemented!(),
+            mir::BorrowKind::Shallow => unreachable!(),
             mir::BorrowKind::Mut { .. } => vir::AssignKind::MutableBorrow(loan.into()),
         };
         // Initialize ref_var.ref_field
@@ -4868,6 +6673,106 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         Ok(stmts)
     }
 
+    /// Encode the shared borrow of the subslice `place` bound by a slice
+    /// pattern like `[first, rest @ ..]`. The subslice is a fresh copy of the
+    /// sliced elements, which can not change while they are borrowed, so its
+    /// loan restores nothing. Only the elements of a primitive type are
+    /// tracked.
+    fn encode_assign_subslice_ref(
+        &mut self,
+        mir_borrow_kind: mir::BorrowKind,
+        place: &mir::Place<'tcx>,
+        location: mir::Location,
+        encoded_lhs: vir::Expr,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        let span = self.mir_encoder.get_span_of_location(location);
+        match mir_borrow_kind {
+            // The shallow borrows of match guards have no loan.
+            mir::BorrowKind::Shallow => return Ok(vec![]),
+            mir::BorrowKind::Shared => {}
+            _ => return Err(SpannedEncodingError::unsupported(
+                "mutable borrows of the subslices bound by slice patterns are not supported",
+                span,
+            )),
+        }
+        let (from, to, from_end, base_projection) = match place.projection.split_last() {
+            Some((&mir::ProjectionElem::Subslice { from, to, from_end }, base_projection)) => {
+                (from, to, from_end, base_projection)
+            }
+            _ => unreachable!(),
+        };
+        let tcx = self.encoder.env().tcx();
+        let base = mir::Place {
+            local: place.local,
+            projection: tcx.intern_place_elems(base_projection),
+        };
+        let (encoded_base, base_ty, _) = self.mir_encoder.encode_place(&base).with_span(span)?;
+        let subslice_ty = place.ty(self.mir, tcx).ty;
+        let elem_ty = match base_ty.kind() {
+            ty::TyKind::Slice(elem_ty) | ty::TyKind::Array(elem_ty, _) => *elem_ty,
+            ref x => unreachable!("{:?}", x),
+        };
+        let subslice = encoded_lhs.clone().field(
+            self.encoder.encode_dereference_field(subslice_ty).with_span(span)?
+        );
+        let mut stmts = self.encode_havoc_and_allocation(&encoded_lhs);
+
+        // The subslice of a slice is counted from its end, the one of an
+        // array from its start.
+        let subslice_len = if from_end {
+            let base_len = self.encoder.encode_slice_len_func_app(encoded_base.clone(), base_ty)
+                .with_span(span)?;
+            vir::Expr::sub(base_len, (from + to).into())
+        } else {
+            (to - from).into()
+        };
+        let len = match subslice_ty.kind() {
+            ty::TyKind::Slice(_) => self.encoder
+                .encode_slice_len_func_app(subslice.clone(), subslice_ty)
+                .with_span(span)?,
+            _ => self.encoder.encode_array_len_expr(subslice_ty).with_span(span)?,
+        };
+        stmts.push(vir::Stmt::Inhale(
+            vir::Expr::eq_cmp(len.clone(), subslice_len),
+            vir::FoldingBehaviour::Expr,
+        ));
+
+        if matches!(
+            elem_ty.kind(),
+            ty::TyKind::Bool | ty::TyKind::Int(_) | ty::TyKind::Uint(_) | ty::TyKind::Char
+        ) {
+            let i_var = vir::LocalVar::new("i", vir::Type::Int);
+            let i: vir::Expr = i_var.clone().into();
+            let elem = self.encoder
+                .encode_slice_lookup_func_app(subslice, subslice_ty, i.clone(), &[], elem_ty)
+                .with_span(span)?;
+            let base_elem = self.encoder
+                .encode_slice_lookup_func_app(
+                    encoded_base,
+                    base_ty,
+                    vir::Expr::add(i.clone(), from.into()),
+                    &[],
+                    elem_ty,
+                )
+                .with_span(span)?;
+            stmts.push(vir::Stmt::Inhale(
+                vir::Expr::forall(
+                    vec![i_var],
+                    vec![vir::Trigger::new(vec![elem.clone()])],
+                    vir::Expr::implies(
+                        vir::Expr::and(
+                            vir::Expr::le_cmp(0.into(), i.clone()),
+                            vir::Expr::lt_cmp(i, len),
+                        ),
+                        vir::Expr::eq_cmp(elem, base_elem),
+                    ),
+                ),
+                vir::FoldingBehaviour::Expr,
+            ));
+        }
+        Ok(stmts)
+    }
+
     fn encode_cast(
         &mut self,
         operand: &mir::Operand<'tcx>,
@@ -4935,6 +6840,67 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         stmts
     }
 
+    /// Is `constant` an access of global state: the address of a static, or a
+    /// key of `thread_local!`?
+    fn is_global_state_access(&self, constant: &mir::Constant<'tcx>) -> bool {
+        let tcx = self.encoder.env().tcx();
+        constant.check_static_ptr(tcx).is_some()
+            || constant.literal.ty.walk().any(|arg| match arg.unpack() {
+                ty::subst::GenericArgKind::Type(ty) => is_local_key(tcx, ty),
+                _ => false,
+            })
+    }
+
+    /// Encode the assignment of the access `constant` of global state to
+    /// `lhs`. Every access yields a fresh location with an arbitrary content,
+    /// so even the content of an immutable static is not known to be the same
+    /// at two accesses. Mutable global state is reported as unsupported,
+    /// unless `HAVOC_MUTABLE_GLOBAL_STATE` enables this trusted model for it.
+    fn encode_assign_global_state(
+        &mut self,
+        lhs: &vir::Expr,
+        constant: &mir::Constant<'tcx>,
+        location: mir::Location,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        let tcx = self.encoder.env().tcx();
+        let span = self.mir_encoder.get_span_of_location(location);
+        let is_mutable = match constant.check_static_ptr(tcx) {
+            Some(static_def_id) => {
+                tcx.is_mutable_static(static_def_id)
+                    || tcx.is_thread_local_static(static_def_id)
+            }
+            // A key of `thread_local!`.
+            None => true,
+        };
+        if is_mutable && !config::havoc_mutable_global_state() {
+            return Err(SpannedEncodingError::unsupported(
+                "unsupported: mutable global state (`static mut` or `thread_local!`); \
+                set `PRUSTI_HAVOC_MUTABLE_GLOBAL_STATE=true` (or pass \
+                `-Phavoc_mutable_global_state=true`) to model each access as \
+                returning an arbitrary value",
+                span,
+            ));
+        }
+        let pointee_ty = match constant.literal.ty.kind() {
+            ty::TyKind::Ref(_, pointee_ty, _)
+            | ty::TyKind::RawPtr(ty::TypeAndMut { ty: pointee_ty, .. }) => *pointee_ty,
+            _ => return Err(SpannedEncodingError::unsupported(
+                format!("constants of type '{:?}' are not supported", constant.literal.ty),
+                span,
+            )),
+        };
+        let ref_field = self.encoder.encode_dereference_field(pointee_ty)
+            .with_span(span)?;
+        let mut stmts = self.prepare_assign_target(
+            lhs.clone(),
+            ref_field.clone(),
+            location,
+            vir::AssignKind::Move,
+        )?;
+        stmts.extend(self.encode_havoc_and_allocation(&lhs.clone().field(ref_field)));
+        Ok(stmts)
+    }
+
     /// Prepare the ``dst`` to be copy target:
     ///
     /// 1.  Havoc and allocate if it is not yet allocated.
@@ -5046,11 +7012,168 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         dst: vir::Expr,
         elems: ty::subst::SubstsRef<'tcx>,
         location: mir::Location,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        let fields = elems
+            .iter()
+            .enumerate()
+            .map(|(field_num, arg)| (format!("tuple_{}", field_num), arg.expect_ty()))
+            .collect();
+        self.encode_deep_copy_fields(src, dst, fields, location)
+    }
+
+    /// Copy the array `src` to `dst`. The elements of a primitive type are
+    /// copied with the lookup functions of the array, while the other arrays
+    /// are only known to be equal.
+    fn encode_deep_copy_array(
+        &mut self,
+        src: vir::Expr,
+        dst: vir::Expr,
+        self_ty: ty::Ty<'tcx>,
+        location: mir::Location,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        let span = self.mir_encoder.get_span_of_location(location);
+        let mut stmts = self.encode_havoc_and_allocation(&dst);
+        let elems_eq = self.encode_array_elems_eq(
+            &dst,
+            self_ty,
+            |this, index| this.encode_array_lookup(src.clone(), self_ty, index, span),
+            span,
+        )?;
+        let eq = match elems_eq {
+            Some(elems_eq) => elems_eq,
+            None => self.encoder.encode_memory_eq_func_app(
+                src,
+                dst,
+                self_ty,
+                vir::Position::default(),
+                span.into(),
+            ),
+        };
+        stmts.push(vir::Stmt::Inhale(eq, vir::FoldingBehaviour::Expr));
+        Ok(stmts)
+    }
+
+    /// Encode the value of the element at `index` of the array `array`, read
+    /// with the lookup function of the array type `array_ty`. Returns `None`
+    /// if the elements are not of a primitive type, because only the values
+    /// of those are tracked.
+    fn encode_array_lookup(
+        &self,
+        array: vir::Expr,
+        array_ty: ty::Ty<'tcx>,
+        index: vir::Expr,
+        span: Span,
+    ) -> SpannedEncodingResult<Option<vir::Expr>> {
+        let elem_ty = match array_ty.kind() {
+            ty::TyKind::Array(elem_ty, _) => *elem_ty,
+            ref x => unreachable!("{:?}", x),
+        };
+        match elem_ty.kind() {
+            ty::TyKind::Bool | ty::TyKind::Int(_) | ty::TyKind::Uint(_) | ty::TyKind::Char => {}
+            _ => return Ok(None),
+        }
+        self.encoder
+            .encode_slice_lookup_func_app(array, array_ty, index, &[], elem_ty)
+            .map(Some)
+            .with_span(span)
+    }
+
+    /// Encode `forall i :: 0 <= i < len ==> array[i] == value(i)`, where `len`
+    /// is the length of the array type `array_ty` and `value` is encoded by
+    /// `encode_value`. Returns `None` if the elements of the array are not
+    /// tracked, see `encode_array_lookup`.
+    fn encode_array_elems_eq<F>(
+        &self,
+        array: &vir::Expr,
+        array_ty: ty::Ty<'tcx>,
+        encode_value: F,
+        span: Span,
+    ) -> SpannedEncodingResult<Option<vir::Expr>>
+        where F: Fn(&Self, vir::Expr) -> SpannedEncodingResult<Option<vir::Expr>>
+    {
+        let i_var = vir::LocalVar::new("i", vir::Type::Int);
+        let i: vir::Expr = i_var.clone().into();
+        let elem = match self.encode_array_lookup(array.clone(), array_ty, i.clone(), span)? {
+            Some(elem) => elem,
+            None => return Ok(None),
+        };
+        let value = match encode_value(self, i.clone())? {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        let len = self.encoder.encode_array_len_expr(array_ty).with_span(span)?;
+        Ok(Some(vir::Expr::forall(
+            vec![i_var],
+            vec![vir::Trigger::new(vec![elem.clone()])],
+            vir::Expr::implies(
+                vir::Expr::and(
+                    vir::Expr::le_cmp(0.into(), i.clone()),
+                    vir::Expr::lt_cmp(i, len),
+                ),
+                vir::Expr::eq_cmp(elem, value),
+            ),
+        )))
+    }
+
+    /// Encode the assignment of `operand` if it reads an element of an array
+    /// by a constant index, as done by array patterns like `let [a, b] = ab;`.
+    /// The element is read with the lookup function of the array. Returns
+    /// `None` if `operand` does not read an element of an array.
+    fn encode_assign_array_elem(
+        &mut self,
+        encoded_lhs: &vir::Expr,
+        ty: ty::Ty<'tcx>,
+        operand: &mir::Operand<'tcx>,
+        location: mir::Location,
+    ) -> SpannedEncodingResult<Option<Vec<vir::Stmt>>> {
+        let span = self.mir_encoder.get_span_of_location(location);
+        let place = match operand {
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => place,
+            mir::Operand::Constant(_) => return Ok(None),
+        };
+        let (offset, from_end, base_projection) = match place.projection.split_last() {
+            Some((&mir::ProjectionElem::ConstantIndex { offset, from_end, .. }, base_projection)) => {
+                (offset, from_end, base_projection)
+            }
+            _ => return Ok(None),
+        };
+        let tcx = self.encoder.env().tcx();
+        let base = mir::Place {
+            local: place.local,
+            projection: tcx.intern_place_elems(base_projection),
+        };
+        let (encoded_base, base_ty, _) = self.mir_encoder.encode_place(&base).with_span(span)?;
+        if !matches!(base_ty.kind(), ty::TyKind::Array(..)) {
+            return Ok(None);
+        }
+        let index = if from_end {
+            let len = self.encoder.encode_array_len_expr(base_ty).with_span(span)?;
+            vir::Expr::sub(len, offset.into())
+        } else {
+            offset.into()
+        };
+        match self.encode_array_lookup(encoded_base, base_ty, index, span)? {
+            Some(value) => Ok(Some(
+                self.encode_copy_value_assign(encoded_lhs.clone(), value, ty, location)?
+            )),
+            None => Err(SpannedEncodingError::unsupported(
+                "only the elements of a primitive type can be read from arrays",
+                span,
+            )),
+        }
+    }
+
+    /// Copy the given fields of `src`, each of which is encoded as a
+    /// reference, to `dst`.
+    fn encode_deep_copy_fields(
+        &mut self,
+        src: vir::Expr,
+        dst: vir::Expr,
+        fields: Vec<(String, ty::Ty<'tcx>)>,
+        location: mir::Location,
     ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
         let mut stmts = self.encode_havoc(&dst);
-        for (field_num, arg) in elems.iter().enumerate() {
-            let ty = arg.expect_ty();
-            let field_name = format!("tuple_{}", field_num);
+        for (field_name, ty) in fields {
             let field = self.encoder
                 .encode_raw_ref_field(field_name, ty)
                 .with_span(
@@ -5086,6 +7209,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             ty::TyKind::Bool
             | ty::TyKind::Int(_)
             | ty::TyKind::Uint(_)
+            | ty::TyKind::Float(_)
             | ty::TyKind::Char => {
                 self.encode_copy_primitive_value(src, dst, self_ty, location)?
             }
@@ -5095,6 +7219,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             ty::TyKind::Tuple(elems) => {
                 self.encode_deep_copy_tuple(src, dst, elems, location)?
             }
+            ty::TyKind::Array(..) => {
+                self.encode_deep_copy_array(src, dst, self_ty, location)?
+            }
             ty::TyKind::Param(_) => {
                 let mut stmts = self.encode_havoc_and_allocation(&dst.clone());
                 let eq = self.encoder.encode_memory_eq_func_app(
@@ -5119,6 +7246,41 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         Ok(stmts)
     }
 
+    /// Is `place` a local variable that stores the constructor of a tuple
+    /// struct or of an enum variant?
+    fn is_constructor_place(&self, place: &mir::Place<'tcx>) -> bool {
+        place.projection.is_empty() && match self.mir_encoder.get_local_ty(place.local).kind() {
+            ty::TyKind::FnDef(def_id, _) => self.encoder.env().tcx().is_constructor(*def_id),
+            _ => false,
+        }
+    }
+
+    /// Encode a call of the constructor `def_id` of a tuple struct or of an
+    /// enum variant, which is used as a function value (e.g. `opt.map(Some)`),
+    /// as the construction of the corresponding aggregate.
+    fn encode_constructor_call(
+        &mut self,
+        def_id: ProcedureDefId,
+        args: &Vec<mir::Operand<'tcx>>,
+        destination: &Option<(mir::Place<'tcx>, BasicBlockIndex)>,
+        location: mir::Location,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        debug!("Encoding call of constructor {:?}", def_id);
+        let (ref target_place, _) = destination.as_ref().unwrap();
+        let (dst, dest_ty, _) = self.mir_encoder.encode_place(target_place).unwrap();
+        let aggregate = match dest_ty.kind() {
+            ty::TyKind::Adt(adt_def, adt_substs) => mir::AggregateKind::Adt(
+                adt_def,
+                adt_def.variant_index_with_ctor_id(def_id),
+                adt_substs,
+                None,
+                None,
+            ),
+            _ => unreachable!("constructor of type {:?}", dest_ty),
+        };
+        self.encode_assign_aggregate(&dst, dest_ty, &aggregate, args, location)
+    }
+
     fn encode_assign_aggregate(
         &mut self,
         dst: &vir::Expr,
@@ -5220,11 +7382,18 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 // let f = closure!(...);
             }
 
-            &mir::AggregateKind::Array(..) => {
-                return Err(SpannedEncodingError::unsupported(
-                    "construction of arrays is not supported",
-                    span
-                ));
+            &mir::AggregateKind::Array(_) => {
+                for (index, operand) in operands.iter().enumerate() {
+                    let elem = self.encode_array_lookup(dst.clone(), ty, index.into(), span)?;
+                    if let Some(elem) = elem {
+                        let value = self.mir_encoder.encode_operand_expr(operand)
+                            .with_span(span)?;
+                        stmts.push(vir::Stmt::Inhale(
+                            vir::Expr::eq_cmp(elem, value),
+                            vir::FoldingBehaviour::Expr,
+                        ));
+                    }
+                }
             }
 
             &mir::AggregateKind::Generator(..) => {
@@ -5238,6 +7407,114 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         Ok(stmts)
     }
 
+    /// Encode an array repeat expression `[operand; N]`, by stating that
+    /// each element of the array has the value of `operand`.
+    fn encode_assign_repeat(
+        &mut self,
+        dst: &vir::Expr,
+        ty: ty::Ty<'tcx>,
+        operand: &mir::Operand<'tcx>,
+        location: mir::Location,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        debug!("[enter] encode_assign_repeat({:?}, {:?})", ty, operand);
+        let span = self.mir_encoder.get_span_of_location(location);
+        let mut stmts = self.encode_havoc_and_allocation(dst);
+        let value = self.mir_encoder.encode_operand_expr(operand).with_span(span)?;
+        let elems_eq = self.encode_array_elems_eq(
+            dst,
+            ty,
+            |_, _| Ok(Some(value.clone())),
+            span,
+        )?;
+        if let Some(elems_eq) = elems_eq {
+            stmts.push(vir::Stmt::Inhale(elems_eq, vir::FoldingBehaviour::Expr));
+        }
+        Ok(stmts)
+    }
+
+    /// Encode a call of `to_le_bytes`, `to_be_bytes`, `from_le_bytes` or
+    /// `from_be_bytes` of an integer type, by relating each byte of the
+    /// array to the digit of the integer in base 256. Negative integers are
+    /// represented in two's complement.
+    fn encode_int_bytes_conversion(
+        &mut self,
+        args: &[mir::Operand<'tcx>],
+        destination: &Option<(mir::Place<'tcx>, mir::BasicBlock)>,
+        location: mir::Location,
+        to_bytes: bool,
+        little_endian: bool,
+    ) -> SpannedEncodingResult<Vec<vir::Stmt>> {
+        let span = self.mir_encoder.get_span_of_location(location);
+        assert_eq!(args.len(), 1);
+        let (target_place, _) = destination.as_ref().unwrap();
+        let (dst, dst_ty, _) = self.mir_encoder.encode_place(target_place).with_span(span)?;
+        let (int_ty, array_ty) = if to_bytes {
+            (self.mir_encoder.get_operand_ty(&args[0]), dst_ty)
+        } else {
+            (dst_ty, self.mir_encoder.get_operand_ty(&args[0]))
+        };
+        let len = self.encoder.get_array_len(array_ty).with_span(span)?;
+        // The weight of the byte stored at `index` of the array.
+        let byte_weight = |index: u64| -> vir::Expr {
+            let digit = if little_endian { index } else { len - 1 - index };
+            (1u128 << (8 * digit)).into()
+        };
+        let lookup_byte = |this: &Self, bytes: &vir::Expr, index: u64| -> SpannedEncodingResult<_> {
+            Ok(this.encode_array_lookup(bytes.clone(), array_ty, index.into(), span)?.unwrap())
+        };
+        let mut stmts = vec![];
+        if to_bytes {
+            let value = self.mir_encoder.encode_operand_expr(&args[0]).with_span(span)?;
+            stmts.extend(self.encode_havoc_and_allocation(&dst));
+            for index in 0..len {
+                let byte = vir::Expr::modulo(
+                    vir::Expr::div(value.clone(), byte_weight(index)),
+                    256.into(),
+                );
+                stmts.push(vir::Stmt::Inhale(
+                    vir::Expr::eq_cmp(lookup_byte(self, &dst, index)?, byte),
+                    vir::FoldingBehaviour::Expr,
+                ));
+            }
+        } else {
+            let bytes = match &args[0] {
+                mir::Operand::Copy(place) | mir::Operand::Move(place) => {
+                    self.mir_encoder.encode_place(place).with_span(span)?.0
+                }
+                mir::Operand::Constant(_) => {
+                    return Err(SpannedEncodingError::unsupported(
+                        "conversion of constant byte arrays is not supported",
+                        span,
+                    ));
+                }
+            };
+            let mut value: vir::Expr = 0.into();
+            for index in 0..len {
+                let byte = lookup_byte(self, &bytes, index)?;
+                value = vir::Expr::add(value, vir::Expr::mul(byte, byte_weight(index)));
+            }
+            if int_ty.is_signed() {
+                let bits = 8 * len;
+                let modulus: vir::Expr = if bits < 128 {
+                    (1u128 << bits).into()
+                } else {
+                    // 2^128 does not fit in a `u128`.
+                    vir::Expr::Const(
+                        vir::Const::BigInt("340282366920938463463374607431768211456".to_string()),
+                        vir::Position::default(),
+                    )
+                };
+                value = vir::Expr::ite(
+                    vir::Expr::ge_cmp(value.clone(), (1u128 << (bits - 1)).into()),
+                    vir::Expr::sub(value.clone(), modulus),
+                    value,
+                );
+            }
+            stmts.extend(self.encode_copy_value_assign(dst, value, int_ty, location)?);
+        }
+        Ok(stmts)
+    }
+
     fn check_vir(&self) -> SpannedEncodingResult<()> {
         if self.cfg_method.has_loops() {
             return Err(SpannedEncodingError::internal(
@@ -5271,6 +7548,123 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
     }
 }
 
+/// Whether `proc_name` is a conversion between an integer and its bytes that
+/// is encoded as a builtin, as `(to_bytes, little_endian)`.
+fn int_bytes_conversion(proc_name: &str) -> Option<(bool, bool)> {
+    if !proc_name.starts_with("core::num::<impl ") && !proc_name.starts_with("std::num::<impl ") {
+        return None;
+    }
+    match proc_name.rsplit("::").next()? {
+        "to_le_bytes" => Some((true, true)),
+        "to_be_bytes" => Some((true, false)),
+        "from_le_bytes" => Some((false, true)),
+        "from_be_bytes" => Some((false, false)),
+        _ => None,
+    }
+}
+
+/// Is `place` the subslice bound by a slice pattern like `[first, rest @ ..]`?
+fn is_subslice(place: &mir::Place) -> bool {
+    matches!(place.projection.last(), Some(mir::ProjectionElem::Subslice { .. }))
+}
+
+/// Collects the places that are accessed by a statement or a terminator,
+/// excluding the places whose address is only taken, and whether they are
+/// written.
+struct AccessedPlaceCollector<'tcx> {
+    places: Vec<(mir::Place<'tcx>, bool)>,
+}
+
+impl<'tcx> mir::visit::Visitor<'tcx> for AccessedPlaceCollector<'tcx> {
+    fn visit_place(
+        &mut self,
+        place: &mir::Place<'tcx>,
+        context: mir::visit::PlaceContext,
+        _location: mir::Location,
+    ) {
+        use rustc_middle::mir::visit::PlaceContext::*;
+        match context {
+            NonMutatingUse(mir::visit::NonMutatingUseContext::AddressOf)
+            | MutatingUse(mir::visit::MutatingUseContext::AddressOf)
+            | NonUse(_) => {}
+            MutatingUse(_) => self.places.push((*place, true)),
+            NonMutatingUse(_) => self.places.push((*place, false)),
+        }
+    }
+}
+
+/// Collects the places of primitive type that an expression reads in the
+/// current state, i.e. outside of `old[..]` expressions and quantifiers.
+struct PrimitivePlaceCollector {
+    places: Vec<vir::Expr>,
+}
+
+impl vir::ExprWalker for PrimitivePlaceCollector {
+    fn walk_field(&mut self, receiver: &vir::Expr, field: &vir::Field, pos: &vir::Position) {
+        let is_value_field = match field.typ {
+            vir::Type::Int | vir::Type::Bool => true,
+            _ => false,
+        };
+        if is_value_field && receiver.is_place() && receiver.get_base().typ.is_ref() {
+            let place = vir::Expr::Field(box receiver.clone(), field.clone(), *pos);
+            if !self.places.contains(&place) {
+                self.places.push(place);
+            }
+        }
+        self.walk(receiver);
+    }
+
+    fn walk_labelled_old(&mut self, _label: &str, _body: &vir::Expr, _pos: &vir::Position) {}
+
+    fn walk_forall(
+        &mut self,
+        _vars: &Vec<vir::LocalVar>,
+        _triggers: &Vec<vir::Trigger>,
+        _body: &vir::Expr,
+        _pos: &vir::Position,
+    ) {}
+}
+
+/// The Rust expression of the primitive `place`, whose base is one of the
+/// locals in `local_names`. References to structs are auto-dereferenced.
+fn describe_counterexample_place(
+    place: &vir::Expr,
+    local_names: &HashMap<String, String>,
+) -> Option<String> {
+    let (base, components) = place.explode_place();
+    let mut rust_expr = match base {
+        vir::Expr::Local(ref var, _) => local_names.get(&var.name)?.clone(),
+        _ => return None,
+    };
+    let mut derefs = 0;
+    for component in components {
+        let field = match component {
+            vir::PlaceComponent::Field(field, _) => field,
+            vir::PlaceComponent::Variant(..) => return None,
+        };
+        if field.name == "val_ref" {
+            derefs += 1;
+        } else if let Some(field_name) = field.name.strip_prefix(snapshot::STRUCT_FIELD_PREFIX) {
+            rust_expr = format!("{}.{}", rust_expr, field_name);
+            derefs = 0;
+        } else if let Some(index) = field.name.strip_prefix("tuple_") {
+            rust_expr = format!("{}.{}", rust_expr, index);
+            derefs = 0;
+        } else if !field.name.starts_with("val_") {
+            return None;
+        }
+    }
+    Some(format!("{}{}", "*".repeat(derefs), rust_expr))
+}
+
 fn convert_loans_to_borrows(loans: &Vec<facts::Loan>) -> Vec<Borrow> {
     loans.iter().map(|l| l.into()).collect()
 }
+
+/// Whether `ty` is the type of the keys declared by `thread_local!`.
+fn is_local_key<'tcx>(tcx: ty::TyCtxt<'tcx>, ty: ty::Ty<'tcx>) -> bool {
+    match ty.kind() {
+        ty::TyKind::Adt(adt_def, _) => tcx.def_path_str(adt_def.did) == "std::thread::LocalKey",
+        _ => false,
+    }
+}
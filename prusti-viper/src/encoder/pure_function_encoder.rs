@@ -5,17 +5,19 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use crate::encoder::borrows::{compute_procedure_contract, ProcedureContract};
-use crate::encoder::builtin_encoder::BuiltinFunctionKind;
+use crate::encoder::builtin_encoder::{BuiltinFunctionKind, IntArithMode, get_int_arith_method};
 use crate::encoder::errors::{PanicCause, RunIfErr};
 use crate::encoder::errors::{SpannedEncodingError, ErrorCtxt, WithSpan};
 use crate::encoder::foldunfold;
-use crate::encoder::mir_encoder::{MirEncoder, PlaceEncoder};
-use crate::encoder::mir_encoder::{PRECONDITION_LABEL, WAND_LHS_LABEL};
+use crate::encoder::mir_encoder::{MirEncoder, PlaceEncoder, describe_assert_message};
+use crate::encoder::mir_encoder::{PRECONDITION_LABEL, WAND_LHS_LABEL, CREDITS_VAR_NAME, MEASURE_VAR_NAME};
 use crate::encoder::mir_interpreter::{
     run_backward_interpretation, BackwardMirInterpreter, MultiExprBackwardInterpreterState,
 };
 use crate::encoder::Encoder;
+use crate::encoder::encoder::{get_tested_variant, get_unwrapped_variant};
 use crate::encoder::snapshot_spec_patcher::SnapshotSpecPatcher;
+use crate::encoder::termination;
 use prusti_common::vir;
 use prusti_common::vir::ExprIterator;
 use prusti_common::config;
@@ -27,8 +29,8 @@ use rustc_middle::ty;
 use std::collections::HashMap;
 use log::{debug, trace};
 use prusti_interface::PrustiError;
-use rustc_span::Span;
-use crate::encoder::errors::EncodingResult;
+use rustc_span::{MultiSpan, Span};
+use crate::encoder::errors::{EncodingError, EncodingResult};
 use crate::encoder::errors::SpannedEncodingResult;
 
 pub struct PureFunctionEncoder<'p, 'v: 'p, 'tcx: 'v> {
@@ -135,6 +137,72 @@ impl<'p, 'v: 'p, 'tcx: 'v> PureFunctionEncoder<'p, 'v, 'tcx> {
         self.encode_function_given_body(None)
     }
 
+    /// Encode a predicate defined with `predicate!`, whose body is given by
+    /// the assertion instead of the MIR of the function.
+    pub fn encode_predicate_function(&self, predicate_body: &typed::Assertion<'tcx>)
+        -> SpannedEncodingResult<vir::Function>
+    {
+        let function_name = self.encode_function_name();
+        debug!("Encode predicate {}", function_name);
+
+        let encoded_args: Vec<vir::Expr> = self.mir
+            .args_iter()
+            .map(|local| self.encode_local(local).map(|l| l.into()))
+            .collect::<Result<_, _>>()?;
+        let body_expr = self.encoder.encode_assertion(
+            predicate_body,
+            &self.mir,
+            None,
+            &encoded_args,
+            None,
+            true,
+            None,
+            ErrorCtxt::GenericExpression,
+        )?;
+        let body_expr = SnapshotSpecPatcher::new(self.encoder)
+            .patch_spec(body_expr)
+            .with_span(self.mir.span)?;
+        self.encode_function_given_body(Some(body_expr))
+    }
+
+    /// Encode the termination measure of the function, if it has a
+    /// `#[decreases]` annotation, over its formal arguments.
+    pub fn encode_termination_measure(&self)
+        -> SpannedEncodingResult<Option<(Vec<vir::LocalVar>, vir::Expr)>>
+    {
+        let contract = self.encoder
+            .get_procedure_contract_for_def(self.proc_def_id)
+            .with_span(self.mir.span)?;
+        let decreases = match contract.specification.expect_procedure().decreases {
+            Some(ref decreases) => decreases,
+            None => return Ok(None),
+        };
+        let formal_args: Vec<vir::LocalVar> = contract
+            .args
+            .iter()
+            .map(|local| self.encode_local(local.clone().into()))
+            .collect::<Result<_, _>>()?;
+        let encoded_args: Vec<vir::Expr> = formal_args.iter().cloned().map(vir::Expr::local).collect();
+        let span = MultiSpan::from_spans(
+            typed::Spanned::get_spans(decreases, &self.mir, self.encoder.env().tcx())
+        );
+        let assertion = self.encoder.encode_assertion(
+            decreases,
+            &self.mir,
+            None,
+            &encoded_args,
+            None,
+            true,
+            None,
+            ErrorCtxt::GenericExpression,
+        )?;
+        let assertion = SnapshotSpecPatcher::new(self.encoder)
+            .patch_spec(assertion)
+            .with_span(span.clone())?;
+        let measure = termination::extract_measure(assertion).with_span(span)?;
+        Ok(Some((formal_args, measure)))
+    }
+
     // Private
 
     fn encode_function_given_body(&self, body: Option<vir::Expr>)
@@ -176,6 +244,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> PureFunctionEncoder<'p, 'v, 'tcx> {
             let var_type = var_type.patch(&subst_strings);
             formal_args.push(vir::LocalVar::new(var_name, var_type))
         };
+        formal_args.extend(
+            self.encoder.encode_const_param_formal_args(self.proc_def_id).with_span(self.mir.span)?
+        );
         let return_type = self.encode_function_return_type()?;
 
         let res_value_range_pos = self.encoder.error_manager().register(
@@ -184,7 +255,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> PureFunctionEncoder<'p, 'v, 'tcx> {
         );
         let pure_fn_return_variable = vir::LocalVar::new("__result", return_type.clone());
         // Add value range of the arguments and return value to the pre/postconditions
-        if config::check_overflows() {
+        if config::integer_bounds() {
             let return_bounds: Vec<_> = self
                 .encoder
                 .encode_type_bounds(
@@ -216,6 +287,20 @@ impl<'p, 'v: 'p, 'tcx: 'v> PureFunctionEncoder<'p, 'v, 'tcx> {
             }
         }
 
+        // The calls of a lemma instantiate its postcondition, and are `true`.
+        if self.encoder.is_lemma(self.proc_def_id) {
+            let lemma_result_pos = self.encoder.error_manager().register(
+                self.mir.span,
+                ErrorCtxt::LemmaResult,
+            );
+            postcondition.push(
+                vir::Expr::eq_cmp(
+                    vir::Expr::local(vir::LocalVar::new("__result", vir::Type::Bool)),
+                    true.into(),
+                ).set_default_pos(lemma_result_pos)
+            );
+        }
+
         debug_assert!(
             !postcondition.iter().any(|p| p.pos().is_default()),
             "Some postcondition has no position: {:?}",
@@ -402,6 +487,7 @@ pub(super) struct PureFunctionBackwardInterpreter<'p, 'v: 'p, 'tcx: 'v> {
     encoder: &'p Encoder<'v, 'tcx>,
     mir: &'p mir::Body<'tcx>,
     mir_encoder: MirEncoder<'p, 'v, 'tcx>,
+    def_id: DefId,
     /// True if the encoder is currently encoding an assertion and not a pure function body. This
     /// flag is used to distinguish when assert terminators should be translated into `false` and
     /// when to a undefined function calls. This distinction allows overflow checks to be checked
@@ -423,6 +509,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> PureFunctionBackwardInterpreter<'p, 'v, 'tcx> {
             encoder,
             mir,
             mir_encoder: MirEncoder::new(encoder, mir, def_id),
+            def_id,
             is_encoding_assertion,
         }
     }
@@ -430,6 +517,354 @@ impl<'p, 'v: 'p, 'tcx: 'v> PureFunctionBackwardInterpreter<'p, 'v, 'tcx> {
     pub(super) fn mir_encoder(&self) -> &MirEncoder<'p, 'v, 'tcx> {
         &self.mir_encoder
     }
+
+    /// The condition under which the call of `callee_def_id` with `args`
+    /// decreases the termination measure of the function being encoded.
+    fn encode_termination_check(&self, callee_def_id: DefId, args: &[vir::Expr])
+        -> SpannedEncodingResult<vir::Expr>
+    {
+        let (_, measure) = self.encoder.encode_pure_termination_measure(self.def_id)?.unwrap();
+        let (callee_formal_args, callee_measure) = self.encoder
+            .encode_pure_termination_measure(callee_def_id)?
+            .unwrap();
+        Ok(termination::measure_decreases(
+            termination::instantiate_measure(callee_measure, &callee_formal_args, args),
+            measure,
+        ))
+    }
+
+    /// The type of the content of `ty`, if `ty` is a box or a
+    /// reference-counted pointer, which are encoded by their content.
+    fn get_owned_pointee_ty(&self, ty: ty::Ty<'tcx>) -> Option<ty::Ty<'tcx>> {
+        if ty.is_box() {
+            Some(ty.boxed_ty())
+        } else {
+            self.encoder.get_rc_pointee_ty(ty)
+        }
+    }
+
+    /// Is `ty` a box of a `Copy` value, whose clone is equal to it?
+    fn is_box_of_copy(&self, ty: ty::Ty<'tcx>, span: Span) -> bool {
+        let tcx = self.encoder.env().tcx();
+        ty.is_box()
+            && ty.boxed_ty().is_copy_modulo_regions(tcx.at(span), ty::ParamEnv::reveal_all())
+    }
+
+    /// Is `local` a local variable that stores the constructor of a tuple
+    /// struct or of an enum variant?
+    fn is_constructor(&self, local: mir::Local) -> bool {
+        match self.mir_encoder.get_local_ty(local).kind() {
+            ty::TyKind::FnDef(def_id, _) => self.encoder.env().tcx().is_constructor(*def_id),
+            _ => false,
+        }
+    }
+
+    /// Encode the value read from a place that indexes a slice or an array
+    /// with a variable, e.g. `(*items)[i].weight`. Slices and arrays are read
+    /// through uninterpreted functions of their abstract predicate. Returns
+    /// `None` if the place does not index.
+    fn encode_indexed_place_value(&self, place: &mir::Place<'tcx>)
+        -> EncodingResult<Option<vir::Expr>>
+    {
+        let tcx = self.encoder.env().tcx();
+        let index_position = match self.find_index_projection(place) {
+            Some(index_position) => index_position,
+            None => return Ok(None),
+        };
+        let base = mir::Place {
+            local: place.local,
+            projection: tcx.intern_place_elems(&place.projection[..index_position]),
+        };
+        let (encoded_base, base_ty, _) = self.mir_encoder.encode_place(&base)?;
+        let encoded_index = match place.projection[index_position] {
+            mir::ProjectionElem::Index(index_local) => self.mir_encoder
+                .encode_operand_expr(&mir::Operand::Copy(index_local.into()))?,
+            mir::ProjectionElem::ConstantIndex { offset, from_end, .. } => {
+                self.encode_constant_slice_index(encoded_base.clone(), base_ty, offset, from_end)?
+            }
+            _ => unreachable!(),
+        };
+        let value_ty = place.ty(self.mir, tcx).ty;
+        match value_ty.kind() {
+            ty::TyKind::Bool
+            | ty::TyKind::Int(_)
+            | ty::TyKind::Uint(_) => {}
+            _ => return Err(EncodingError::unsupported(
+                "only values of primitive types can be read from slices and arrays in pure code"
+            )),
+        }
+        match base_ty.kind() {
+            ty::TyKind::Slice(elem_ty) | ty::TyKind::Array(elem_ty, _) => {
+                let mut fields = vec![];
+                let mut field_base_ty = *elem_ty;
+                for elem in &place.projection[index_position + 1..] {
+                    match (elem, field_base_ty.kind()) {
+                        (mir::ProjectionElem::Field(field, field_ty), ty::TyKind::Adt(adt_def, _))
+                            if adt_def.is_struct() => {
+                            let field_def = &adt_def.non_enum_variant().fields[field.index()];
+                            fields.push(field_def.ident.to_string());
+                            field_base_ty = *field_ty;
+                        }
+                        (mir::ProjectionElem::Field(field, field_ty), ty::TyKind::Tuple(_)) => {
+                            fields.push(format!("tuple_{}", field.index()));
+                            field_base_ty = *field_ty;
+                        }
+                        _ => return Err(EncodingError::unsupported(format!(
+                            "reading '{:?}' from the elements of a slice is not supported",
+                            elem,
+                        ))),
+                    }
+                }
+                Ok(Some(self.encoder.encode_slice_lookup_func_app(
+                    encoded_base,
+                    base_ty,
+                    encoded_index,
+                    &fields,
+                    value_ty,
+                )?))
+            }
+            _ => Err(EncodingError::unsupported(format!(
+                "indexing of type '{:?}' is not supported",
+                base_ty,
+            ))),
+        }
+    }
+
+    /// Find the projection of `place` that indexes a slice or an array with a
+    /// variable or with a constant, as done by slice patterns like
+    /// `[first, ..]`.
+    fn find_index_projection(&self, place: &mir::Place<'tcx>) -> Option<usize> {
+        place.projection.iter().position(|elem| matches!(
+            elem,
+            mir::ProjectionElem::Index(_) | mir::ProjectionElem::ConstantIndex { .. }
+        ))
+    }
+
+    /// Encode the index `offset` of the slice `encoded_slice`, counted from the
+    /// end if `from_end`.
+    fn encode_constant_slice_index(
+        &self,
+        encoded_slice: vir::Expr,
+        slice_ty: ty::Ty<'tcx>,
+        offset: u64,
+        from_end: bool,
+    ) -> EncodingResult<vir::Expr> {
+        Ok(if from_end {
+            let len = self.encoder.encode_slice_len_func_app(encoded_slice, slice_ty)?;
+            vir::Expr::sub(len, offset.into())
+        } else {
+            offset.into()
+        })
+    }
+
+    /// Encode the borrow `encoded_lhs = &place` of a slice pattern, where
+    /// `place` is an element or a subslice of a slice. The borrowed part is
+    /// not a place of the encoding, so the reads through `encoded_lhs` are
+    /// replaced by reads of the slice:
+    /// - the primitive fields of a borrowed element `slice[i]` are read with
+    ///   the lookup functions of the slice;
+    /// - a borrowed subslice `slice[from..len - to]` has the length
+    ///   `len - from - to` and its element `j` is the element `from + j` of
+    ///   the slice.
+    /// Returns `false` if `place` is not part of a slice.
+    fn substitute_slice_pattern_ref(
+        &self,
+        state: &mut MultiExprBackwardInterpreterState,
+        encoded_lhs: &vir::Expr,
+        place: &mir::Place<'tcx>,
+    ) -> EncodingResult<bool> {
+        let tcx = self.encoder.env().tcx();
+        let (last_elem, base_projection) = match place.projection.split_last() {
+            Some(split) => split,
+            None => return Ok(false),
+        };
+        let base = mir::Place {
+            local: place.local,
+            projection: tcx.intern_place_elems(base_projection),
+        };
+        let base_ty = base.ty(self.mir, tcx).ty;
+        let elem_ty = match base_ty.kind() {
+            ty::TyKind::Slice(elem_ty) => *elem_ty,
+            _ => return Ok(false),
+        };
+        let (encoded_base, _, _) = match last_elem {
+            mir::ProjectionElem::ConstantIndex { .. }
+            | mir::ProjectionElem::Subslice { .. } => self.mir_encoder.encode_place(&base)?,
+            _ => return Ok(false),
+        };
+        let encoded_deref = encoded_lhs.clone()
+            .field(self.encoder.encode_dereference_field(place.ty(self.mir, tcx).ty)?);
+        match *last_elem {
+            mir::ProjectionElem::ConstantIndex { offset, from_end, .. } => {
+                let index = self.encode_constant_slice_index(
+                    encoded_base.clone(), base_ty, offset, from_end
+                )?;
+                let mut leaves = vec![];
+                self.collect_primitive_leaves(encoded_deref, elem_ty, vec![], &mut leaves)?;
+                for (leaf_place, fields, leaf_ty) in leaves {
+                    let value = self.encoder.encode_slice_lookup_func_app(
+                        encoded_base.clone(), base_ty, index.clone(), &fields, leaf_ty
+                    )?;
+                    state.substitute_value(&leaf_place, value);
+                }
+                if state.use_place(encoded_lhs) {
+                    return Err(EncodingError::unsupported(
+                        "only the primitive fields of the elements bound by a slice pattern \
+                        can be read in pure functions"
+                    ));
+                }
+            }
+            mir::ProjectionElem::Subslice { from, to, from_end } => {
+                debug_assert!(from_end, "the subslice of a slice is counted from its end");
+                let base_len = self.encoder.encode_slice_len_func_app(
+                    encoded_base.clone(), base_ty
+                )?;
+                let mut replacer = SubsliceAccessReplacer {
+                    subslice: encoded_deref,
+                    slice: encoded_base,
+                    len_function: match base_len {
+                        vir::Expr::FuncApp(ref name, ..) => name.clone(),
+                        _ => unreachable!(),
+                    },
+                    len: vir::Expr::sub(base_len, (from + to).into()),
+                    from: from.into(),
+                    unsupported_use: false,
+                };
+                state.map_exprs(|expr| vir::ExprFolder::fold(&mut replacer, expr));
+                if replacer.unsupported_use || state.use_place(encoded_lhs) {
+                    return Err(EncodingError::unsupported(
+                        "the subslices bound by a slice pattern can only be indexed and \
+                        measured in pure functions"
+                    ));
+                }
+            }
+            _ => unreachable!(),
+        }
+        Ok(true)
+    }
+
+    /// Collect the places of the primitive values that are reachable from
+    /// `place` of type `ty` through struct and tuple fields, with the names
+    /// of the fields as used by the lookup functions of slices.
+    fn collect_primitive_leaves(
+        &self,
+        place: vir::Expr,
+        ty: ty::Ty<'tcx>,
+        fields: Vec<String>,
+        leaves: &mut Vec<(vir::Expr, Vec<String>, ty::Ty<'tcx>)>,
+    ) -> EncodingResult<()> {
+        let tcx = self.encoder.env().tcx();
+        match ty.kind() {
+            ty::TyKind::Bool
+            | ty::TyKind::Int(_)
+            | ty::TyKind::Uint(_) => {
+                let value_place = place.field(self.encoder.encode_value_field(ty));
+                leaves.push((value_place, fields, ty));
+            }
+            ty::TyKind::Adt(adt_def, subst) if adt_def.is_struct() => {
+                for field_def in &adt_def.non_enum_variant().fields {
+                    let field_name = field_def.ident.to_string();
+                    let field_ty = field_def.ty(tcx, subst);
+                    let encoded_field = self.encoder.encode_struct_field(&field_name, field_ty)?;
+                    let mut field_path = fields.clone();
+                    field_path.push(field_name);
+                    self.collect_primitive_leaves(
+                        place.clone().field(encoded_field), field_ty, field_path, leaves
+                    )?;
+                }
+            }
+            ty::TyKind::Tuple(elems) => {
+                for (field_num, field_ty) in elems.iter().enumerate() {
+                    let field_name = format!("tuple_{}", field_num);
+                    let field_ty = field_ty.expect_ty();
+                    let encoded_field = self.encoder
+                        .encode_raw_ref_field(field_name.clone(), field_ty)?;
+                    let mut field_path = fields.clone();
+                    field_path.push(field_name);
+                    self.collect_primitive_leaves(
+                        place.clone().field(encoded_field), field_ty, field_path, leaves
+                    )?;
+                }
+            }
+            // Other values can not be read from slices in pure code.
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Encode the length of the slice or array at `place`.
+    fn encode_len(&self, place: &mir::Place<'tcx>) -> EncodingResult<vir::Expr> {
+        let (encoded_place, place_ty, _) = self.mir_encoder.encode_place(place)?;
+        match place_ty.kind() {
+            ty::TyKind::Slice(..) => self.encoder.encode_slice_len_func_app(encoded_place, place_ty),
+            ty::TyKind::Array(..) => match self.encoder.eval_array_len(place_ty) {
+                Some(len) => Ok(len.into()),
+                None => self.encoder.encode_slice_len_func_app(encoded_place, place_ty),
+            },
+            _ => Err(EncodingError::unsupported(format!(
+                "the length of type '{:?}' is not supported",
+                place_ty,
+            ))),
+        }
+    }
+
+    /// Substitute in `state` the fields of `encoded_lhs` with the operands of
+    /// the construction of the variant `variant_index` of `adt_def`.
+    fn substitute_adt_aggregate(
+        &self,
+        state: &mut MultiExprBackwardInterpreterState,
+        encoded_lhs: &vir::Expr,
+        adt_def: &ty::AdtDef,
+        variant_index: rustc_target::abi::VariantIdx,
+        subst: ty::subst::SubstsRef<'tcx>,
+        operands: &[mir::Operand<'tcx>],
+        span: Span,
+    ) -> SpannedEncodingResult<()> {
+        let num_variants = adt_def.variants.len();
+        let variant_def = &adt_def.variants[variant_index];
+        let mut encoded_lhs_variant = encoded_lhs.clone();
+        if num_variants != 1 {
+            let discr_field = self.encoder.encode_discriminant_field();
+            state.substitute_value(
+                &encoded_lhs.clone().field(discr_field),
+                variant_index.index().into(),
+            );
+            encoded_lhs_variant =
+                encoded_lhs_variant.variant(&variant_def.ident.as_str());
+        }
+        for (field_index, field) in variant_def.fields.iter().enumerate() {
+            let operand = &operands[field_index];
+            let field_name = &field.ident.as_str();
+            let tcx = self.encoder.env().tcx();
+            let field_ty = field.ty(tcx, subst);
+            let encoded_field = self.encoder
+                    .encode_struct_field(field_name, field_ty)
+                    .with_span(span)?;
+
+            let field_place =
+                encoded_lhs_variant.clone().field(encoded_field);
+
+            let encoded_operand = self.mir_encoder.encode_operand_place(operand)
+                .with_span(span)?;
+            match encoded_operand {
+                Some(encoded_rhs) => {
+                    // Substitute a place
+                    state.substitute_place(&field_place, encoded_rhs);
+                }
+                None => {
+                    // Substitute a place of a value with an expression
+                    let rhs_expr =
+                        self.mir_encoder.encode_operand_expr(operand)
+                            .with_span(span)?;
+                    state.substitute_value(
+                        &self.encoder.encode_value_expr(field_place, field_ty),
+                        rhs_expr,
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
@@ -557,7 +992,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                             }
                         }
 
-                        ty::TyKind::Int(_) | ty::TyKind::Uint(_) => {
+                        ty::TyKind::Int(_) | ty::TyKind::Uint(_) | ty::TyKind::Char => {
                             vir::Expr::eq_cmp(
                                 discr_val.clone().into(),
                                 self.encoder.encode_int_cast(value, switch_ty),
@@ -639,6 +1074,11 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                     let own_substs =
                         ty::List::identity_for_item(self.encoder.env().tcx(), def_id);
 
+                    // The const generic arguments are encoded in the context
+                    // of the caller, before entering the one of the callee.
+                    let const_param_frame = self.encoder.encode_const_param_frame(def_id, substs)
+                        .with_span(span)?;
+                    self.encoder.const_param_repl.borrow_mut().push(const_param_frame);
                     {
                         // FIXME: this is a hack to support generics. See issue #187.
                         let mut tymap_stack = self.encoder.typaram_repl.borrow_mut();
@@ -659,6 +1099,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                         // FIXME: this is a hack to support generics. See issue #187.
                         let mut tymap_stack = self.encoder.typaram_repl.borrow_mut();
                         tymap_stack.pop();
+                        self.encoder.const_param_repl.borrow_mut().pop();
                     };
 
                     let state = if destination.is_some() {
@@ -667,9 +1108,17 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                             .with_span(span)
                             .run_if_err(cleanup)?;
                         let lhs_value = self.encoder.encode_value_expr(encoded_lhs.clone(), ty);
+                        // Raw pointers have no value field, so they are
+                        // passed as places, from which their address is read.
                         let encoded_args: Vec<vir::Expr> = args
                             .iter()
-                            .map(|arg| self.mir_encoder.encode_operand_expr(arg))
+                            .map(|arg| if self.mir_encoder.get_operand_ty(arg).is_unsafe_ptr() {
+                                self.mir_encoder.encode_operand_place(arg)?.ok_or_else(|| {
+                                    EncodingError::unsupported("constant raw pointers are not supported")
+                                })
+                            } else {
+                                self.mir_encoder.encode_operand_expr(arg)
+                            })
                             .collect::<Result<_, _>>()
                             .with_span(span)?;
 
@@ -685,6 +1134,352 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                                 state
                             }
 
+                            "prusti_contracts::wrap_add"
+                            | "prusti_contracts::wrap_sub"
+                            | "prusti_contracts::wrap_mul"
+                            | "prusti_contracts::trunc" => {
+                                trace!("Encoding modular arithmetic {:?}", full_func_proc_name);
+                                let value = match full_func_proc_name {
+                                    "prusti_contracts::wrap_add" => vir::Expr::add(
+                                        encoded_args[0].clone(),
+                                        encoded_args[1].clone(),
+                                    ),
+                                    "prusti_contracts::wrap_sub" => vir::Expr::sub(
+                                        encoded_args[0].clone(),
+                                        encoded_args[1].clone(),
+                                    ),
+                                    "prusti_contracts::wrap_mul" => vir::Expr::mul(
+                                        encoded_args[0].clone(),
+                                        encoded_args[1].clone(),
+                                    ),
+                                    _ => encoded_args[0].clone(),
+                                };
+                                let encoded_rhs = self.mir_encoder
+                                    .encode_wrapped_int(value, ty)
+                                    .with_span(span)
+                                    .run_if_err(cleanup)?;
+                                let mut state = states[&target_block].clone();
+                                state.substitute_value(&lhs_value, encoded_rhs);
+                                state
+                            }
+
+                            _ if matches!(
+                                get_int_arith_method(full_func_proc_name),
+                                Some((IntArithMode::Wrapping, _))
+                                | Some((IntArithMode::Saturating, _))
+                            ) => {
+                                trace!("Encoding arithmetic method {:?}", full_func_proc_name);
+                                let (mode, op) = get_int_arith_method(full_func_proc_name).unwrap();
+                                let (encoded_rhs, _) = self.mir_encoder
+                                    .encode_int_arith_method(
+                                        mode,
+                                        op,
+                                        encoded_args[0].clone(),
+                                        encoded_args[1].clone(),
+                                        ty,
+                                    )
+                                    .with_span(span)
+                                    .run_if_err(cleanup)?;
+                                let mut state = states[&target_block].clone();
+                                state.substitute_value(&lhs_value, encoded_rhs);
+                                state
+                            }
+
+                            "core::slice::<impl [T]>::len"
+                            | "core::str::<impl str>::len"
+                            | "std::string::String::len"
+                            | "std::vec::Vec::<T>::len"
+                            | "std::vec::Vec::<T, A>::len"
+                            | "core::slice::<impl [T]>::is_empty"
+                            | "core::str::<impl str>::is_empty"
+                            | "std::string::String::is_empty"
+                            | "std::vec::Vec::<T>::is_empty"
+                            | "std::vec::Vec::<T, A>::is_empty" => {
+                                let slice_ty = match self.mir_encoder.get_operand_ty(&args[0]).kind() {
+                                    ty::TyKind::Ref(_, slice_ty, _) => *slice_ty,
+                                    _ => unreachable!(),
+                                };
+                                let encoded_len = self.encoder
+                                    .encode_slice_len_func_app(encoded_args[0].clone(), slice_ty)
+                                    .with_span(span)
+                                    .run_if_err(cleanup)?;
+                                let encoded_rhs = if full_func_proc_name.ends_with("::is_empty") {
+                                    vir::Expr::eq_cmp(encoded_len, 0.into())
+                                } else {
+                                    encoded_len
+                                };
+                                let mut state = states[&target_block].clone();
+                                state.substitute_value(&lhs_value, encoded_rhs);
+                                state
+                            }
+
+                            // Indexing a vector reads its elements with the
+                            // lookup functions of slices, which are only
+                            // defined within the bounds of the vector.
+                            "std::ops::Index::index"
+                            | "core::ops::Index::index"
+                                if self.encoder.get_vec_elem_ty(substs.type_at(0)).is_some()
+                                    && matches!(substs.type_at(1).kind(), ty::TyKind::Uint(_))
+                            => {
+                                let vec_ty = substs.type_at(0);
+                                let elem_ty = self.encoder.get_vec_elem_ty(vec_ty).unwrap();
+                                let pos = self.encoder.error_manager().register(
+                                    term.source_info.span,
+                                    ErrorCtxt::BoundsCheck,
+                                );
+                                let encoded_deref = encoded_lhs.clone().field(
+                                    self.encoder.encode_dereference_field(elem_ty)
+                                        .with_span(span)
+                                        .run_if_err(cleanup)?
+                                );
+                                let mut leaves = vec![];
+                                self.collect_primitive_leaves(encoded_deref, elem_ty, vec![], &mut leaves)
+                                    .with_span(span)
+                                    .run_if_err(cleanup)?;
+                                let mut state = states[&target_block].clone();
+                                for (leaf_place, fields, leaf_ty) in leaves {
+                                    let value = self.encoder.encode_slice_lookup_func_app(
+                                        encoded_args[0].clone(),
+                                        vec_ty,
+                                        encoded_args[1].clone(),
+                                        &fields,
+                                        leaf_ty,
+                                    ).with_span(span).run_if_err(cleanup)?;
+                                    state.substitute_value(&leaf_place, value.set_pos(pos));
+                                }
+                                if state.use_place(&encoded_lhs) {
+                                    return Err(EncodingError::unsupported(
+                                        "only the primitive fields of the elements of a vector \
+                                        can be read in pure functions"
+                                    )).with_span(span).run_if_err(cleanup);
+                                }
+                                state
+                            }
+
+                            // Strings and string slices have the same encoding
+                            // as their bytes.
+                            "core::str::<impl str>::as_bytes"
+                            | "std::string::String::as_bytes"
+                            | "std::string::String::as_str" => {
+                                let mut state = states[&target_block].clone();
+                                state.substitute_value(&lhs_value, encoded_args[0].clone());
+                                state
+                            }
+
+                            "core::str::<impl str>::starts_with"
+                            | "core::slice::<impl [T]>::starts_with" => {
+                                let slice_ty = match self.mir_encoder.get_operand_ty(&args[0]).kind() {
+                                    ty::TyKind::Ref(_, slice_ty, _) => *slice_ty,
+                                    _ => unreachable!(),
+                                };
+                                let prefix_ty = self.mir_encoder.get_operand_ty(&args[1]);
+                                let is_supported_prefix = match prefix_ty.kind() {
+                                    ty::TyKind::Ref(_, prefix_ty, _) => *prefix_ty == slice_ty,
+                                    _ => false,
+                                };
+                                let encoded_rhs = if is_supported_prefix {
+                                    self.encoder.encode_slice_starts_with(
+                                        encoded_args[0].clone(),
+                                        encoded_args[1].clone(),
+                                        slice_ty,
+                                    )
+                                } else {
+                                    Err(EncodingError::unsupported(format!(
+                                        "checking for a prefix of type '{:?}' is not supported",
+                                        prefix_ty,
+                                    )))
+                                }.with_span(span).run_if_err(cleanup)?;
+                                let mut state = states[&target_block].clone();
+                                state.substitute_value(&lhs_value, encoded_rhs);
+                                state
+                            }
+
+                            "prusti_contracts::credits" => {
+                                let encoded_rhs = vir::Expr::local(
+                                    vir::LocalVar::new(CREDITS_VAR_NAME, vir::Type::Int)
+                                );
+                                let mut state = states[&target_block].clone();
+                                state.substitute_value(&lhs_value, encoded_rhs);
+                                state
+                            }
+
+                            "prusti_contracts::termination_measure" => {
+                                let encoded_rhs = vir::Expr::local(
+                                    vir::LocalVar::new(MEASURE_VAR_NAME, vir::Type::Int)
+                                );
+                                let mut state = states[&target_block].clone();
+                                state.substitute_value(&lhs_value, encoded_rhs);
+                                state
+                            }
+
+                            "prusti_contracts::ptr_valid" => {
+                                assert_eq!(args.len(), 1);
+                                let ptr_ty = self.mir_encoder.get_operand_ty(&args[0]);
+                                let target_ty = match ptr_ty.kind() {
+                                    ty::TyKind::RawPtr(ty::TypeAndMut { ty, .. }) => *ty,
+                                    _ => unreachable!("ptr_valid of non-pointer type {:?}", ptr_ty),
+                                };
+                                let address_field = self.encoder
+                                    .encode_dereference_field(target_ty)
+                                    .with_span(span)
+                                    .run_if_err(cleanup)?;
+                                let encoded_rhs = self.encoder.encode_raw_pointer_validity(
+                                    encoded_args[0].clone().field(address_field),
+                                    vir::PermAmount::Write,
+                                );
+                                let mut state = states[&target_block].clone();
+                                state.substitute_value(&lhs_value, encoded_rhs);
+                                state
+                            }
+
+                            "std::ptr::eq" | "core::ptr::eq" => {
+                                assert_eq!(args.len(), 2);
+                                let encoded_rhs = self.mir_encoder
+                                    .encode_pointer_comparison(mir::BinOp::Eq, &args[0], &args[1])
+                                    .with_span(span)
+                                    .run_if_err(cleanup)?;
+                                let mut state = states[&target_block].clone();
+                                state.substitute_value(&lhs_value, encoded_rhs);
+                                state
+                            }
+
+                            "prusti_contracts::snapshot_equality" => {
+                                assert_eq!(args.len(), 2);
+                                let arg_ty = self.mir_encoder.get_operand_ty(&args[0]);
+                                let encoded_rhs = self.encoder.encode_snapshot_equality(
+                                    encoded_args[0].clone(),
+                                    encoded_args[1].clone(),
+                                    arg_ty,
+                                ).with_span(span).run_if_err(cleanup)?;
+                                let mut state = states[&target_block].clone();
+                                state.substitute_value(&lhs_value, encoded_rhs);
+                                state
+                            }
+
+                            // With relaxed purity, a box is encoded by the
+                            // value that it contains, and so is a
+                            // reference-counted pointer.
+                            "std::boxed::Box::<T>::new"
+                            | "std::rc::Rc::<T>::new"
+                            | "std::sync::Arc::<T>::new" if config::relaxed_purity() => {
+                                assert_eq!(args.len(), 1);
+                                let boxed_ty = self.encoder.get_rc_pointee_ty(ty)
+                                    .unwrap_or_else(|| ty.boxed_ty());
+                                let content_field = self.encoder
+                                    .encode_dereference_field(boxed_ty)
+                                    .with_span(span)
+                                    .run_if_err(cleanup)?;
+                                let content_value = self.encoder.encode_value_expr(
+                                    encoded_lhs.clone().field(content_field),
+                                    boxed_ty,
+                                );
+                                let mut state = states[&target_block].clone();
+                                state.substitute_value(&content_value, encoded_args[0].clone());
+                                state
+                            }
+
+                            // The content of a reference-counted pointer is
+                            // never mutated, so a clone is equal to the
+                            // original and a dereference reads the content.
+                            // So is the clone of a box of a `Copy` value.
+                            "std::clone::Clone::clone"
+                            | "core::clone::Clone::clone"
+                                if self.encoder.get_rc_pointee_ty(substs.type_at(0)).is_some()
+                                    || self.is_box_of_copy(substs.type_at(0), span) =>
+                            {
+                                assert_eq!(args.len(), 1);
+                                let mut state = states[&target_block].clone();
+                                state.substitute_value(&lhs_value, encoded_args[0].clone());
+                                state
+                            }
+
+                            "std::ops::Deref::deref"
+                            | "core::ops::Deref::deref"
+                            | "std::convert::AsRef::as_ref"
+                            | "core::convert::AsRef::as_ref"
+                            | "std::borrow::Borrow::borrow"
+                            | "core::borrow::Borrow::borrow"
+                                if self.get_owned_pointee_ty(substs.type_at(0)).is_some() =>
+                            {
+                                assert_eq!(args.len(), 1);
+                                let pointee_ty = self.get_owned_pointee_ty(substs.type_at(0))
+                                    .unwrap();
+                                let content_field = self.encoder
+                                    .encode_dereference_field(pointee_ty)
+                                    .with_span(span)
+                                    .run_if_err(cleanup)?;
+                                let mut state = states[&target_block].clone();
+                                state.substitute_value(
+                                    &lhs_value,
+                                    encoded_args[0].clone().field(content_field),
+                                );
+                                state
+                            }
+
+                            // Testing the variant of an `Option` or a
+                            // `Result` reads its discriminant.
+                            _ if get_tested_variant(full_func_proc_name).is_some() => {
+                                assert_eq!(args.len(), 1);
+                                let variant_name = get_tested_variant(full_func_proc_name).unwrap();
+                                let enum_ty = match self.mir_encoder.get_operand_ty(&args[0]).kind() {
+                                    ty::TyKind::Ref(_, enum_ty, _) => *enum_ty,
+                                    _ => unreachable!(),
+                                };
+                                let encoded_rhs = self.encoder.encode_variant_guard(
+                                    encoded_args[0].clone(),
+                                    enum_ty,
+                                    variant_name,
+                                );
+                                let mut state = states[&target_block].clone();
+                                state.substitute_value(&lhs_value, encoded_rhs);
+                                state
+                            }
+
+                            // Unwrapping an `Option` or a `Result` reads the
+                            // value of the variant, and is a partial function
+                            // that is only defined on that variant.
+                            _ if get_unwrapped_variant(full_func_proc_name).is_some() => {
+                                let variant_name = get_unwrapped_variant(full_func_proc_name).unwrap();
+                                let enum_ty = self.mir_encoder.get_operand_ty(&args[0]);
+                                let guard = self.encoder.encode_variant_guard(
+                                    encoded_args[0].clone(),
+                                    enum_ty,
+                                    variant_name,
+                                );
+                                let (payload, payload_ty) = self.encoder
+                                    .encode_variant_payload(encoded_args[0].clone(), enum_ty, variant_name)
+                                    .with_span(span)
+                                    .run_if_err(cleanup)?;
+                                let payload_value = self.encoder.encode_value_expr(payload, payload_ty);
+                                let payload_type = self.encoder
+                                    .encode_value_or_ref_type(payload_ty)
+                                    .with_span(span)
+                                    .run_if_err(cleanup)?;
+                                let pos = self.encoder.error_manager().register(
+                                    term.source_info.span,
+                                    ErrorCtxt::PanicInPureFunction(
+                                        PanicCause::Unwrap(variant_name.to_string())
+                                    ),
+                                );
+                                let unreachable_name = self.encoder.encode_builtin_function_use(
+                                    BuiltinFunctionKind::Unreachable(payload_type.clone()),
+                                );
+                                let encoded_rhs = vir::Expr::ite(
+                                    guard,
+                                    payload_value,
+                                    vir::Expr::func_app(
+                                        unreachable_name,
+                                        vec![],
+                                        vec![],
+                                        payload_type,
+                                        pos,
+                                    ),
+                                );
+                                let mut state = states[&target_block].clone();
+                                state.substitute_value(&lhs_value, encoded_rhs);
+                                state
+                            }
+
                             "prusti_contracts::before_expiry" => {
                                 trace!("Encoding before_expiry expression {:?}", args[0]);
                                 assert_eq!(args.len(), 1);
@@ -695,6 +1490,25 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                                 state.substitute_value(&lhs_value, encoded_rhs);
                                 state
                             }
+                            // call of the constructor of a tuple struct or of an enum variant
+                            _ if self.encoder.env().tcx().is_constructor(def_id) => {
+                                trace!("Encoding call of constructor {:?}", def_id);
+                                let mut state = states[&target_block].clone();
+                                if let ty::TyKind::Adt(adt_def, adt_substs) = ty.kind() {
+                                    self.substitute_adt_aggregate(
+                                        &mut state,
+                                        &encoded_lhs,
+                                        adt_def,
+                                        adt_def.variant_index_with_ctor_id(def_id),
+                                        adt_substs,
+                                        args,
+                                        term.source_info.span,
+                                    ).run_if_err(cleanup)?;
+                                } else {
+                                    unreachable!("constructor of type {:?}", ty);
+                                }
+                                state
+                            }
                             // simple function call
                             _ => {
                                 let mut is_cmp_call = false;
@@ -771,13 +1585,62 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                                     .encoder
                                     .error_manager()
                                     .register(term.source_info.span, err_ctxt);
+                                // A recursive call must decrease the termination measure,
+                                // otherwise the result is unreachable.
+                                let termination_check = if is_pure_function
+                                    && !self.is_encoding_assertion
+                                    && self.encoder.is_termination_checked_call(self.def_id, def_id)
+                                {
+                                    Some(
+                                        self.encode_termination_check(def_id, &encoded_args)
+                                            .run_if_err(cleanup)?
+                                    )
+                                } else {
+                                    None
+                                };
+                                let mut encoded_args = encoded_args;
+                                let mut formal_args = formal_args;
+                                if is_pure_function {
+                                    encoded_args.extend(
+                                        self.encoder.encode_const_param_args(def_id)
+                                            .with_span(span)
+                                            .run_if_err(cleanup)?
+                                    );
+                                    formal_args.extend(
+                                        self.encoder.encode_const_param_formal_args(def_id)
+                                            .with_span(span)
+                                            .run_if_err(cleanup)?
+                                    );
+                                }
                                 let encoded_rhs = vir::Expr::func_app(
                                     function_name,
                                     encoded_args,
                                     formal_args,
-                                    return_type,
+                                    return_type.clone(),
                                     pos,
                                 );
+                                let encoded_rhs = if let Some(check) = termination_check {
+                                    let check_pos = self.encoder.error_manager().register(
+                                        term.source_info.span,
+                                        ErrorCtxt::AssertCallTerminationMeasure,
+                                    );
+                                    let unreachable_name = self.encoder.encode_builtin_function_use(
+                                        BuiltinFunctionKind::Unreachable(return_type.clone()),
+                                    );
+                                    vir::Expr::ite(
+                                        check,
+                                        encoded_rhs,
+                                        vir::Expr::func_app(
+                                            unreachable_name,
+                                            vec![],
+                                            vec![],
+                                            return_type,
+                                            check_pos,
+                                        ),
+                                    )
+                                } else {
+                                    encoded_rhs
+                                };
                                 let mut state = states[&target_block].clone();
                                 state.substitute_value(&lhs_value, encoded_rhs);
                                 state
@@ -821,6 +1684,43 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                 }
             }
 
+            TerminatorKind::Call {
+                func: mir::Operand::Move(ref place),
+                ref args,
+                destination: Some((ref lhs_place, target_block)),
+                ..
+            }
+            | TerminatorKind::Call {
+                func: mir::Operand::Copy(ref place),
+                ref args,
+                destination: Some((ref lhs_place, target_block)),
+                ..
+            } if place.projection.is_empty() && self.is_constructor(place.local) => {
+                // The constructor is stored in a local variable, e.g. in
+                // `let f = Some; f(x)`.
+                let def_id = match self.mir_encoder.get_local_ty(place.local).kind() {
+                    ty::TyKind::FnDef(def_id, _) => *def_id,
+                    _ => unreachable!(),
+                };
+                let (encoded_lhs, ty, _) = self.mir_encoder.encode_place(lhs_place)
+                    .with_span(span)?;
+                let mut state = states[&target_block].clone();
+                if let ty::TyKind::Adt(adt_def, adt_substs) = ty.kind() {
+                    self.substitute_adt_aggregate(
+                        &mut state,
+                        &encoded_lhs,
+                        adt_def,
+                        adt_def.variant_index_with_ctor_id(def_id),
+                        adt_substs,
+                        args,
+                        term.source_info.span,
+                    )?;
+                } else {
+                    unreachable!("constructor of type {:?}", ty);
+                }
+                state
+            }
+
             TerminatorKind::Call { .. } => {
                 // Other kind of calls?
                 unimplemented!();
@@ -843,7 +1743,12 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
 
                 let pos = self.encoder.error_manager().register(
                     term.source_info.span,
-                    ErrorCtxt::PureFunctionAssertTerminator(msg.description().to_string()),
+                    match msg {
+                        mir::AssertKind::BoundsCheck { .. } => ErrorCtxt::BoundsCheck,
+                        _ => ErrorCtxt::PureFunctionAssertTerminator(
+                            describe_assert_message(msg).to_string()
+                        ),
+                    },
                 );
 
                 MultiExprBackwardInterpreterState::new(
@@ -909,6 +1814,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                     ty::TyKind::Bool
                     | ty::TyKind::Int(..)
                     | ty::TyKind::Uint(..)
+                    | ty::TyKind::Float(..)
                     | ty::TyKind::RawPtr(..)
                     | ty::TyKind::Ref(..) => Some(
                         self.encoder.encode_value_expr(
@@ -923,6 +1829,16 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                 };
 
                 match rhs {
+                    &mir::Rvalue::Use(mir::Operand::Copy(ref place))
+                    | &mir::Rvalue::Use(mir::Operand::Move(ref place))
+                        if self.find_index_projection(place).is_some() => {
+                        let encoded_rhs = self.encode_indexed_place_value(place)
+                            .with_span(span)?
+                            .unwrap();
+                        // Substitute a place of a value with the read element
+                        state.substitute_value(&opt_lhs_value_place.unwrap(), encoded_rhs);
+                    }
+
                     &mir::Rvalue::Use(ref operand) => {
                         let opt_encoded_rhs = self.mir_encoder.encode_operand_place(operand)
                             .with_span(span)?;
@@ -984,55 +1900,30 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                             }
 
                             &mir::AggregateKind::Adt(adt_def, variant_index, subst, _, _) => {
-                                let num_variants = adt_def.variants.len();
-                                let variant_def = &adt_def.variants[variant_index];
-                                let mut encoded_lhs_variant = encoded_lhs.clone();
-                                if num_variants != 1 {
-                                    let discr_field = self.encoder.encode_discriminant_field();
-                                    state.substitute_value(
-                                        &encoded_lhs.clone().field(discr_field),
-                                        variant_index.index().into(),
-                                    );
-                                    encoded_lhs_variant =
-                                        encoded_lhs_variant.variant(&variant_def.ident.as_str());
-                                }
-                                for (field_index, field) in variant_def.fields.iter().enumerate() {
-                                    let operand = &operands[field_index];
-                                    let field_name = &field.ident.as_str();
-                                    let tcx = self.encoder.env().tcx();
-                                    let field_ty = field.ty(tcx, subst);
-                                    let encoded_field = self.encoder
-                                            .encode_struct_field(field_name, field_ty)
-                                            .with_span(span)?;
-
-                                    let field_place =
-                                        encoded_lhs_variant.clone().field(encoded_field);
-
-                                    let encoded_operand = self.mir_encoder.encode_operand_place(operand)
-                                        .with_span(span)?;
-                                    match encoded_operand {
-                                        Some(encoded_rhs) => {
-                                            // Substitute a place
-                                            state.substitute_place(&field_place, encoded_rhs);
-                                        }
-                                        None => {
-                                            // Substitute a place of a value with an expression
-                                            let rhs_expr =
-                                                self.mir_encoder.encode_operand_expr(operand)
-                                                    .with_span(span)?;
-                                            state.substitute_value(
-                                                &self.encoder.encode_value_expr(field_place, field_ty),
-                                                rhs_expr,
-                                            );
-                                        }
-                                    }
-                                }
+                                self.substitute_adt_aggregate(
+                                    state,
+                                    &encoded_lhs,
+                                    adt_def,
+                                    variant_index,
+                                    subst,
+                                    operands,
+                                    span,
+                                )?;
                             }
 
                             ref x => unimplemented!("{:?}", x),
                         }
                     }
 
+                    &mir::Rvalue::BinaryOp(op, ref left, ref right)
+                        if self.mir_encoder.get_operand_ty(left).is_unsafe_ptr() =>
+                    {
+                        let encoded_value = self.mir_encoder
+                            .encode_pointer_comparison(op, left, right)
+                            .with_span(span)?;
+                        state.substitute_value(&opt_lhs_value_place.unwrap(), encoded_value);
+                    }
+
                     &mir::Rvalue::BinaryOp(op, ref left, ref right) => {
                         let encoded_left = self.mir_encoder.encode_operand_expr(left)
                             .with_span(span)?;
@@ -1107,7 +1998,33 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                     &mir::Rvalue::UnaryOp(op, ref operand) => {
                         let encoded_val = self.mir_encoder.encode_operand_expr(operand)
                             .with_span(span)?;
-                        let encoded_value = self.mir_encoder.encode_unary_op_expr(op, encoded_val);
+                        let opt_overflow = if self.is_encoding_assertion {
+                            // The assertion of the MIR already makes the
+                            // specification false on an overflow.
+                            None
+                        } else {
+                            self.mir_encoder.encode_unary_op_check(op, encoded_val.clone(), ty)
+                        };
+                        let mut encoded_value = self.mir_encoder.encode_unary_op_expr(op, encoded_val, ty);
+                        if let Some(overflow) = opt_overflow {
+                            // An overflow makes the result unreachable, like
+                            // the failure of an assertion.
+                            let pos = self.encoder.error_manager().register(
+                                span,
+                                ErrorCtxt::PureFunctionAssertTerminator(
+                                    "attempt to negate with overflow".to_string()
+                                ),
+                            );
+                            let encoded_type = self.encoder.encode_value_type(ty).with_span(span)?;
+                            let function_name = self.encoder.encode_builtin_function_use(
+                                BuiltinFunctionKind::Unreachable(encoded_type.clone()),
+                            );
+                            encoded_value = vir::Expr::ite(
+                                overflow,
+                                vir::Expr::func_app(function_name, vec![], vec![], encoded_type, pos),
+                                encoded_value,
+                            );
+                        }
 
                         // Substitute a place of a value with an expression
                         state.substitute_value(&opt_lhs_value_place.unwrap(), encoded_value);
@@ -1117,6 +2034,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
 
                     &mir::Rvalue::Discriminant(ref src) => {
                         let (encoded_src, src_ty, _) = self.mir_encoder.encode_place(src).unwrap();
+                        let (encoded_src, src_ty) = self.mir_encoder
+                            .encode_auto_deref(encoded_src, src_ty)
+                            .with_span(stmt.source_info.span)?;
                         match src_ty.kind() {
                             ty::TyKind::Adt(ref adt_def, _) if !adt_def.is_box() => {
                                 let num_variants = adt_def.variants.len();
@@ -1154,6 +2074,14 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                         }
                     }
 
+                    &mir::Rvalue::Ref(_, mir::BorrowKind::Unique, ref place)
+                    | &mir::Rvalue::Ref(_, mir::BorrowKind::Mut { .. }, ref place)
+                    | &mir::Rvalue::Ref(_, mir::BorrowKind::Shared, ref place)
+                        if self.substitute_slice_pattern_ref(state, &encoded_lhs, place)
+                            .with_span(span)? => {
+                        // The element or subslice of a slice pattern
+                    }
+
                     &mir::Rvalue::Ref(_, mir::BorrowKind::Unique, ref place)
                     | &mir::Rvalue::Ref(_, mir::BorrowKind::Mut { .. }, ref place)
                     | &mir::Rvalue::Ref(_, mir::BorrowKind::Shared, ref place) => {
@@ -1175,6 +2103,11 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                         state.substitute_place(&encoded_lhs, encoded_ref);
                     }
 
+                    &mir::Rvalue::Ref(_, mir::BorrowKind::Shallow, _) => {
+                        // The shallow borrows of match guards are only used
+                        // by `FakeRead` statements.
+                    }
+
                     &mir::Rvalue::Cast(mir::CastKind::Misc, ref operand, dst_ty) => {
                         let encoded_val = self.mir_encoder
                             .encode_cast_expr(operand, dst_ty, stmt.source_info.span)?;
@@ -1183,6 +2116,11 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                         state.substitute_value(&opt_lhs_value_place.unwrap(), encoded_val);
                     }
 
+                    &mir::Rvalue::Len(ref place) => {
+                        let encoded_len = self.encode_len(place).with_span(span)?;
+                        state.substitute_value(&opt_lhs_value_place.unwrap(), encoded_len);
+                    }
+
                     ref rhs => {
                         unimplemented!("encoding of '{:?}'", rhs);
                     }
@@ -1195,3 +2133,48 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
         Ok(())
     }
 }
+
+/// Replaces the length and the lookups of a subslice of a slice pattern by
+/// those of the sliced slice.
+struct SubsliceAccessReplacer {
+    /// The encoded subslice.
+    subslice: vir::Expr,
+    /// The encoded slice.
+    slice: vir::Expr,
+    /// The name of the length function of the slices.
+    len_function: String,
+    /// The length of the subslice.
+    len: vir::Expr,
+    /// The index of the slice at which the subslice starts.
+    from: vir::Expr,
+    /// Whether the subslice is used by a function that is not a length or
+    /// lookup function.
+    unsupported_use: bool,
+}
+
+impl vir::ExprFolder for SubsliceAccessReplacer {
+    fn fold_func_app(
+        &mut self,
+        name: String,
+        args: Vec<vir::Expr>,
+        formal_args: Vec<vir::LocalVar>,
+        return_type: vir::Type,
+        pos: vir::Position,
+    ) -> vir::Expr {
+        let args: Vec<_> = args.into_iter().map(|arg| self.fold(arg)).collect();
+        if args.first() != Some(&self.subslice) {
+            return vir::Expr::FuncApp(name, args, formal_args, return_type, pos);
+        }
+        if name == self.len_function {
+            self.len.clone()
+        } else if name.starts_with("builtin$lookup$") {
+            let index = vir::Expr::add(self.from.clone(), args[1].clone());
+            vir::Expr::FuncApp(
+                name, vec![self.slice.clone(), index], formal_args, return_type, pos
+            )
+        } else {
+            self.unsupported_use = true;
+            vir::Expr::FuncApp(name, args, formal_args, return_type, pos)
+        }
+    }
+}
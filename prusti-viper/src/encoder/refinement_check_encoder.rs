@@ -0,0 +1,248 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::encoder::errors::{ErrorCtxt, SpannedEncodingError, SpannedEncodingResult, WithSpan};
+use crate::encoder::foldunfold;
+use crate::encoder::mir_encoder::{MirEncoder, PlaceEncoder, PRECONDITION_LABEL};
+use crate::encoder::Encoder;
+use prusti_common::vir::{self, fixes::fix_ghost_vars, ExprIterator, Successor};
+use prusti_interface::environment::Procedure;
+use prusti_interface::specs::typed;
+use rustc_hir::def_id::DefId;
+use rustc_middle::mir;
+use rustc_middle::ty;
+use rustc_index::vec::Idx;
+use rustc_span::MultiSpan;
+use std::collections::HashMap;
+use ::log::trace;
+
+/// Encodes the check that the contract of a method that implements a trait
+/// method refines the contract of the trait method: the precondition of the
+/// trait has to imply the precondition of the implementation, and the
+/// postcondition of the implementation has to imply the postcondition of the
+/// trait. Callers that only know the trait rely on its contract.
+///
+/// The check is encoded as a method without body, which is verified for the
+/// implementations whose body is not verified against the contract of the
+/// trait, i.e. trusted and pure methods. A non-trusted procedure performs the
+/// same check while its body is verified.
+pub struct RefinementCheckEncoder<'p, 'v: 'p, 'tcx: 'v> {
+    encoder: &'p Encoder<'v, 'tcx>,
+    mir: &'p mir::Body<'tcx>,
+    mir_encoder: MirEncoder<'p, 'v, 'tcx>,
+    def_id: DefId,
+    trait_method_def_id: DefId,
+    procedure: &'p Procedure<'v, 'tcx>,
+}
+
+impl<'p, 'v: 'p, 'tcx: 'v> RefinementCheckEncoder<'p, 'v, 'tcx> {
+    pub fn new(
+        encoder: &'p Encoder<'v, 'tcx>,
+        procedure: &'p Procedure<'v, 'tcx>,
+        trait_method_def_id: DefId,
+    ) -> Self {
+        let def_id = procedure.get_id();
+        trace!("RefinementCheckEncoder constructor: {:?}", def_id);
+        let mir = procedure.get_mir();
+        RefinementCheckEncoder {
+            encoder,
+            mir,
+            mir_encoder: MirEncoder::new(encoder, mir, def_id),
+            def_id,
+            trait_method_def_id,
+            procedure,
+        }
+    }
+
+    pub fn encode(self) -> SpannedEncodingResult<vir::CfgMethod> {
+        trace!("Encode refinement check for procedure {}", self.procedure.get_def_path());
+        let span = self.procedure.get_span();
+        let impl_contract = self.encoder.get_procedure_contract_for_def(self.def_id)
+            .with_span(span)?;
+        let trait_contract = self.encoder.get_procedure_contract_for_def(self.trait_method_def_id)
+            .with_span(span)?;
+
+        let mut cfg_method = vir::CfgMethod::new(
+            // method name
+            format!("refinement_check${}", self.encoder.encode_item_name(self.def_id)),
+            // formal args
+            self.mir.arg_count,
+            // formal returns
+            vec![],
+            // local vars
+            vec![],
+            // reserved labels
+            vec![PRECONDITION_LABEL.to_string()],
+        );
+        let encoded_return = self.encode_local(mir::RETURN_PLACE)?;
+        cfg_method.add_formal_return(&encoded_return.name, encoded_return.typ.clone());
+        let mut encoded_args = vec![];
+        for arg_index in 1..=self.mir.arg_count {
+            let encoded_arg = self.encode_local(mir::Local::new(arg_index))?;
+            cfg_method.add_local_var(&encoded_arg.name, encoded_arg.typ.clone());
+            encoded_args.push(vir::Expr::local(encoded_arg));
+        }
+        let encoded_return = vir::Expr::local(encoded_return);
+
+        let mut stmts = vec![
+            vir::Stmt::comment("========== refinement check =========="),
+            vir::Stmt::comment(format!("Def path: {:?}", self.procedure.get_def_path())),
+        ];
+
+        // The state of a caller that establishes the precondition of the trait.
+        let mut arg_perms = vec![];
+        let mut havocked_targets = vec![];
+        for (arg_index, encoded_arg) in encoded_args.iter().enumerate() {
+            let arg_ty = self.mir.local_decls[mir::Local::new(arg_index + 1)].ty;
+            match arg_ty.kind() {
+                ty::TyKind::Ref(_, target_ty, mutability) => {
+                    let target = encoded_arg.clone().field(
+                        self.encoder.encode_dereference_field(*target_ty).with_span(span)?
+                    );
+                    let target_perm = vir::Expr::pred_permission(
+                        target.clone(),
+                        match *mutability {
+                            mir::Mutability::Mut => vir::PermAmount::Write,
+                            mir::Mutability::Not => vir::PermAmount::Read,
+                        },
+                    ).unwrap();
+                    arg_perms.push(vir::Expr::acc_permission(target, vir::PermAmount::Write));
+                    arg_perms.push(target_perm.clone());
+                    if *mutability == mir::Mutability::Mut {
+                        havocked_targets.push(target_perm);
+                    }
+                }
+                _ => arg_perms.push(
+                    self.mir_encoder
+                        .encode_place_predicate_permission(encoded_arg.clone(), vir::PermAmount::Write)
+                        .unwrap()
+                ),
+            }
+        }
+        stmts.push(vir::Stmt::Inhale(arg_perms.into_iter().conjoin(), vir::FoldingBehaviour::Stmt));
+        let trait_pre = self.encode_conjunction(
+            trait_contract.functional_precondition(),
+            None,
+            &encoded_args,
+            None,
+            ErrorCtxt::GenericExpression,
+        )?;
+        stmts.push(vir::Stmt::Inhale(trait_pre, vir::FoldingBehaviour::Expr));
+        let impl_pres = impl_contract.functional_precondition();
+        if !impl_pres.is_empty() {
+            let impl_pre = self.encode_conjunction(
+                impl_pres,
+                None,
+                &encoded_args,
+                None,
+                ErrorCtxt::AssertMethodPreconditionWeakening(self.get_spans(impl_pres)),
+            )?;
+            let pos = impl_pre.pos();
+            stmts.push(vir::Stmt::Assert(impl_pre, vir::FoldingBehaviour::Expr, pos));
+        }
+        stmts.push(vir::Stmt::Label(PRECONDITION_LABEL.to_string()));
+
+        // The state after any execution of the implementation.
+        for target_perm in havocked_targets {
+            stmts.push(vir::Stmt::Exhale(target_perm.clone(), vir::Position::default()));
+            stmts.push(vir::Stmt::Inhale(target_perm, vir::FoldingBehaviour::Stmt));
+        }
+        stmts.push(vir::Stmt::Inhale(
+            self.mir_encoder
+                .encode_place_predicate_permission(encoded_return.clone(), vir::PermAmount::Write)
+                .unwrap(),
+            vir::FoldingBehaviour::Stmt,
+        ));
+        let impl_posts = impl_contract.functional_postcondition();
+        if !impl_posts.is_empty() {
+            let impl_post = self.encode_conjunction(
+                impl_posts,
+                Some(PRECONDITION_LABEL),
+                &encoded_args,
+                Some(&encoded_return),
+                ErrorCtxt::GenericExpression,
+            )?;
+            stmts.push(vir::Stmt::Inhale(impl_post, vir::FoldingBehaviour::Expr));
+            let trait_post = self.encode_conjunction(
+                trait_contract.functional_postcondition(),
+                Some(PRECONDITION_LABEL),
+                &encoded_args,
+                Some(&encoded_return),
+                ErrorCtxt::AssertMethodPostconditionStrengthening(self.get_spans(impl_posts)),
+            )?;
+            let pos = trait_post.pos();
+            stmts.push(vir::Stmt::Assert(trait_post, vir::FoldingBehaviour::Expr, pos));
+        }
+
+        let check_block = cfg_method.add_block("check", stmts);
+        cfg_method.set_successor(check_block, Successor::Return);
+
+        let method_pos = self.encoder.error_manager().register(span, ErrorCtxt::Unexpected);
+        let method_with_fold_unfold = foldunfold::add_fold_unfold(
+            self.encoder,
+            cfg_method,
+            &HashMap::new(),
+            &HashMap::new(),
+            method_pos,
+        ).map_err(|foldunfold_error| {
+            SpannedEncodingError::internal(
+                format!(
+                    "generating fold-unfold Viper statements failed ({:?})",
+                    foldunfold_error
+                ),
+                span,
+            )
+        })?;
+        Ok(fix_ghost_vars(method_with_fold_unfold))
+    }
+
+    fn encode_local(&self, local: mir::Local) -> SpannedEncodingResult<vir::LocalVar> {
+        let type_name = self.encoder
+            .encode_type_predicate_use(self.mir_encoder.get_local_ty(local))
+            .with_span(self.procedure.get_span())?;
+        Ok(vir::LocalVar::new(
+            self.mir_encoder.encode_local_var_name(local),
+            vir::Type::TypedRef(type_name),
+        ))
+    }
+
+    /// Encode the conjunction of the clauses `assertions` of a contract.
+    fn encode_conjunction(
+        &self,
+        assertions: &[typed::Assertion<'tcx>],
+        pre_label: Option<&str>,
+        encoded_args: &[vir::Expr],
+        encoded_return: Option<&vir::Expr>,
+        error: ErrorCtxt,
+    ) -> SpannedEncodingResult<vir::Expr> {
+        let conjunction = typed::Assertion {
+            kind: box typed::AssertionKind::And(assertions.to_vec()),
+        };
+        self.encoder.encode_assertion(
+            &conjunction,
+            self.mir,
+            pre_label,
+            encoded_args,
+            encoded_return,
+            false,
+            None,
+            error,
+        )
+    }
+
+    fn get_spans(&self, assertions: &[typed::Assertion<'tcx>]) -> MultiSpan {
+        MultiSpan::from_spans(
+            assertions
+                .iter()
+                .flat_map(|assertion| typed::Spanned::get_spans(
+                    assertion,
+                    self.mir,
+                    self.encoder.env().tcx(),
+                ))
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,331 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Property-based tests of the purifier and of the expression optimizations.
+//! Random well-typed expressions over integer and boolean places are
+//! transformed, and the results must be well-typed and evaluate to the same
+//! value as the original expressions under random valuations of the places.
+//! Permissions are assumed to be held, so access predicates evaluate to true.
+
+use super::ExprPurifier;
+use prusti_common::vir::{
+    self,
+    optimizations::{folding::FoldingOptimizer, functions::Simplifier},
+    BinOpKind, Const, Expr, Field, LocalVar, PermAmount, Type, UnaryOpKind,
+};
+use std::collections::HashMap;
+
+const EXPRESSIONS: usize = 500;
+const VALUATIONS: usize = 5;
+const MAX_DEPTH: u32 = 5;
+const INT_PLACES: &[&str] = &["_1", "_2", "_3"];
+const BOOL_PLACES: &[&str] = &["_4", "_5"];
+const INT_LOCALS: &[&str] = &["n"];
+
+/// A xorshift generator, so that failures are reproducible from the seed.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    fn choose<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len() as u64) as usize]
+    }
+
+    fn small_int(&mut self) -> i64 {
+        self.below(11) as i64 - 5
+    }
+}
+
+fn place(name: &str, predicate: &str) -> Expr {
+    Expr::local(LocalVar::new(name, Type::TypedRef(predicate.to_string())))
+}
+
+fn int_place(name: &str) -> Expr {
+    place(name, "i32").field(Field::new("val_int", Type::Int))
+}
+
+fn bool_place(name: &str) -> Expr {
+    place(name, "bool").field(Field::new("val_bool", Type::Bool))
+}
+
+fn gen_int(rng: &mut Rng, depth: u32) -> Expr {
+    if depth == 0 {
+        return match rng.below(3) {
+            0 => Expr::Const(Const::Int(rng.small_int()), vir::Position::default()),
+            1 => int_place(rng.choose(INT_PLACES)),
+            _ => Expr::local(LocalVar::new(rng.choose(INT_LOCALS), Type::Int)),
+        };
+    }
+    match rng.below(7) {
+        0 => Expr::add(gen_int(rng, depth - 1), gen_int(rng, depth - 1)),
+        1 => Expr::sub(gen_int(rng, depth - 1), gen_int(rng, depth - 1)),
+        2 => Expr::mul(gen_int(rng, depth - 1), gen_int(rng, depth - 1)),
+        3 => Expr::minus(gen_int(rng, depth - 1)),
+        4 => Expr::ite(gen_bool(rng, depth - 1), gen_int(rng, depth - 1), gen_int(rng, depth - 1)),
+        5 => Expr::unfolding(
+            "i32".to_string(),
+            vec![place(rng.choose(INT_PLACES), "i32")],
+            gen_int(rng, depth - 1),
+            PermAmount::Read,
+            None,
+        ),
+        _ => gen_int(rng, 0),
+    }
+}
+
+fn gen_bool(rng: &mut Rng, depth: u32) -> Expr {
+    if depth == 0 {
+        return match rng.below(2) {
+            0 => (rng.below(2) == 0).into(),
+            _ => bool_place(rng.choose(BOOL_PLACES)),
+        };
+    }
+    match rng.below(9) {
+        0 => Expr::not(gen_bool(rng, depth - 1)),
+        1 => Expr::and(gen_bool(rng, depth - 1), gen_bool(rng, depth - 1)),
+        2 => Expr::or(gen_bool(rng, depth - 1), gen_bool(rng, depth - 1)),
+        3 => Expr::implies(gen_bool(rng, depth - 1), gen_bool(rng, depth - 1)),
+        4 => {
+            let kind = [
+                BinOpKind::EqCmp, BinOpKind::NeCmp, BinOpKind::LtCmp,
+                BinOpKind::LeCmp, BinOpKind::GtCmp, BinOpKind::GeCmp,
+            ][rng.below(6) as usize];
+            Expr::BinOp(
+                kind,
+                box gen_int(rng, depth - 1),
+                box gen_int(rng, depth - 1),
+                vir::Position::default(),
+            )
+        }
+        5 => Expr::eq_cmp(gen_bool(rng, depth - 1), gen_bool(rng, depth - 1)),
+        6 => Expr::ite(gen_bool(rng, depth - 1), gen_bool(rng, depth - 1), gen_bool(rng, depth - 1)),
+        7 => Expr::unfolding(
+            "bool".to_string(),
+            vec![place(rng.choose(BOOL_PLACES), "bool")],
+            gen_bool(rng, depth - 1),
+            PermAmount::Read,
+            None,
+        ),
+        _ => gen_bool(rng, 0),
+    }
+}
+
+/// An assertion: a conjunction of access predicates to places and of a
+/// boolean expression, as found in the pre- and postconditions of functions.
+fn gen_assertion(rng: &mut Rng) -> Expr {
+    let mut assertion = gen_bool(rng, rng.below(MAX_DEPTH as u64 + 1) as u32);
+    for _ in 0..rng.below(3) {
+        let access = if rng.below(2) == 0 {
+            let name = rng.choose(INT_PLACES);
+            Expr::predicate_access_predicate("i32", place(name, "i32"), PermAmount::Read)
+        } else {
+            Expr::field_access_predicate(bool_place(rng.choose(BOOL_PLACES)), PermAmount::Read)
+        };
+        assertion = Expr::and(access, assertion);
+    }
+    assertion
+}
+
+/// The type of a well-typed expression of the fragment of the generator.
+fn type_of(expr: &Expr) -> Result<Type, String> {
+    let expect = |expr: &Expr, expected: &Type| -> Result<(), String> {
+        let actual = type_of(expr)?;
+        if &actual == expected {
+            Ok(())
+        } else {
+            Err(format!("'{}' has type {} instead of {}", expr, actual, expected))
+        }
+    };
+    match expr {
+        Expr::Local(var, _) => Ok(var.typ.clone()),
+        Expr::Field(base, field, _) => match type_of(base)? {
+            Type::TypedRef(_) => Ok(field.typ.clone()),
+            typ => Err(format!("field access '{}' on a value of type {}", expr, typ)),
+        },
+        Expr::Const(Const::Int(_), _) => Ok(Type::Int),
+        Expr::Const(Const::Bool(_), _) => Ok(Type::Bool),
+        Expr::UnaryOp(UnaryOpKind::Not, arg, _) => expect(arg, &Type::Bool).map(|_| Type::Bool),
+        Expr::UnaryOp(UnaryOpKind::Minus, arg, _) => expect(arg, &Type::Int).map(|_| Type::Int),
+        Expr::BinOp(kind, left, right, _) => {
+            let (operand_type, result_type) = match kind {
+                BinOpKind::Add | BinOpKind::Sub | BinOpKind::Mul
+                | BinOpKind::Div | BinOpKind::Mod => (Some(Type::Int), Type::Int),
+                BinOpKind::LtCmp | BinOpKind::LeCmp
+                | BinOpKind::GtCmp | BinOpKind::GeCmp => (Some(Type::Int), Type::Bool),
+                BinOpKind::And | BinOpKind::Or | BinOpKind::Implies => (Some(Type::Bool), Type::Bool),
+                BinOpKind::EqCmp | BinOpKind::NeCmp => (None, Type::Bool),
+            };
+            let operand_type = match operand_type {
+                Some(typ) => typ,
+                None => type_of(left)?,
+            };
+            expect(left, &operand_type)?;
+            expect(right, &operand_type)?;
+            Ok(result_type)
+        }
+        Expr::Cond(guard, then_expr, else_expr, _) => {
+            expect(guard, &Type::Bool)?;
+            let typ = type_of(then_expr)?;
+            expect(else_expr, &typ)?;
+            Ok(typ)
+        }
+        Expr::Unfolding(_, args, body, _, _, _) => {
+            for arg in args {
+                type_of(arg)?;
+            }
+            type_of(body)
+        }
+        Expr::PredicateAccessPredicate(_, arg, _, _) => type_of(arg).map(|_| Type::Bool),
+        Expr::FieldAccessPredicate(place, _, _) => type_of(place).map(|_| Type::Bool),
+        _ => Err(format!("unexpected expression '{}'", expr)),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Value {
+    Int(i128),
+    Bool(bool),
+}
+
+/// The values of the places, and of the local variables that replace them
+/// once purified.
+type Valuation = HashMap<String, Value>;
+
+fn gen_valuation(rng: &mut Rng) -> Valuation {
+    let mut valuation = Valuation::new();
+    for name in INT_PLACES.iter().chain(INT_LOCALS) {
+        valuation.insert(name.to_string(), Value::Int(rng.small_int() as i128));
+    }
+    for name in BOOL_PLACES {
+        valuation.insert(name.to_string(), Value::Bool(rng.below(2) == 0));
+    }
+    valuation
+}
+
+/// Evaluate `expr`, or return `None` if an arithmetic operation overflows.
+fn eval(expr: &Expr, valuation: &Valuation) -> Option<Value> {
+    let int = |expr: &Expr| match eval(expr, valuation)? {
+        Value::Int(value) => Some(value),
+        value => unreachable!("'{}' evaluates to {:?}", expr, value),
+    };
+    let boolean = |expr: &Expr| match eval(expr, valuation)? {
+        Value::Bool(value) => Some(value),
+        value => unreachable!("'{}' evaluates to {:?}", expr, value),
+    };
+    Some(match expr {
+        Expr::Local(var, _) => valuation[&var.name],
+        Expr::Field(box Expr::Local(var, _), _, _) => valuation[&var.name],
+        Expr::Const(Const::Int(value), _) => Value::Int(*value as i128),
+        Expr::Const(Const::Bool(value), _) => Value::Bool(*value),
+        Expr::UnaryOp(UnaryOpKind::Not, arg, _) => Value::Bool(!boolean(arg)?),
+        Expr::UnaryOp(UnaryOpKind::Minus, arg, _) => Value::Int(int(arg)?.checked_neg()?),
+        Expr::BinOp(kind, left, right, _) => match kind {
+            BinOpKind::Add => Value::Int(int(left)?.checked_add(int(right)?)?),
+            BinOpKind::Sub => Value::Int(int(left)?.checked_sub(int(right)?)?),
+            BinOpKind::Mul => Value::Int(int(left)?.checked_mul(int(right)?)?),
+            BinOpKind::LtCmp => Value::Bool(int(left)? < int(right)?),
+            BinOpKind::LeCmp => Value::Bool(int(left)? <= int(right)?),
+            BinOpKind::GtCmp => Value::Bool(int(left)? > int(right)?),
+            BinOpKind::GeCmp => Value::Bool(int(left)? >= int(right)?),
+            BinOpKind::EqCmp => Value::Bool(eval(left, valuation)? == eval(right, valuation)?),
+            BinOpKind::NeCmp => Value::Bool(eval(left, valuation)? != eval(right, valuation)?),
+            // Both operands are evaluated, because the transformations may
+            // reorder them.
+            BinOpKind::And => Value::Bool(boolean(left)? & boolean(right)?),
+            BinOpKind::Or => Value::Bool(boolean(left)? | boolean(right)?),
+            BinOpKind::Implies => Value::Bool(!boolean(left)? | boolean(right)?),
+            BinOpKind::Div | BinOpKind::Mod => unreachable!("not generated"),
+        },
+        Expr::Cond(guard, then_expr, else_expr, _) => {
+            let then_value = eval(then_expr, valuation)?;
+            let else_value = eval(else_expr, valuation)?;
+            if boolean(guard)? { then_value } else { else_value }
+        }
+        Expr::Unfolding(_, _, body, _, _, _) => eval(body, valuation)?,
+        Expr::PredicateAccessPredicate(..) | Expr::FieldAccessPredicate(..) => Value::Bool(true),
+        _ => unreachable!("unexpected expression '{}'", expr),
+    })
+}
+
+/// Check that `transformed` is well-typed, with the type of `original`, and
+/// that it has the same value under random valuations.
+fn check_equivalent(
+    rng: &mut Rng,
+    transformation: &str,
+    original: &Expr,
+    transformed: &Expr,
+) {
+    let original_type = type_of(original).unwrap();
+    let transformed_type = type_of(transformed).unwrap_or_else(|error| {
+        panic!("{} of '{}' is ill-typed: {}", transformation, original, error)
+    });
+    assert_eq!(
+        original_type, transformed_type,
+        "{} of '{}' changed its type: '{}'", transformation, original, transformed,
+    );
+    for _ in 0..VALUATIONS {
+        let valuation = gen_valuation(rng);
+        if let Some(value) = eval(original, &valuation) {
+            assert_eq!(
+                Some(value), eval(transformed, &valuation),
+                "{} of '{}' changed its value under {:?}: '{}'",
+                transformation, original, valuation, transformed,
+            );
+        }
+    }
+}
+
+fn for_each_assertion<F: FnMut(&mut Rng, Expr)>(seed: u64, mut check: F) {
+    let mut rng = Rng(seed);
+    for _ in 0..EXPRESSIONS {
+        let assertion = gen_assertion(&mut rng);
+        type_of(&assertion).unwrap_or_else(|error| {
+            panic!("generated an ill-typed assertion '{}': {}", assertion, error)
+        });
+        check(&mut rng, assertion);
+    }
+}
+
+#[test]
+fn fuzz_purifier() {
+    let snapshots = HashMap::new();
+    for_each_assertion(0x5eed_0001, |rng, assertion| {
+        let mut purifier = ExprPurifier {
+            snapshots: &snapshots,
+            self_function: true.into(),
+        };
+        let purified = purifier.purify(assertion.clone()).unwrap_or_else(|error| {
+            panic!("failed to purify '{}': {:?}", assertion, error)
+        });
+        check_equivalent(rng, "purification", &assertion, &purified);
+    });
+}
+
+#[test]
+fn fuzz_simplifier() {
+    for_each_assertion(0x5eed_0002, |rng, assertion| {
+        let simplified = assertion.clone().simplify();
+        check_equivalent(rng, "simplification", &assertion, &simplified);
+    });
+}
+
+#[test]
+fn fuzz_folding_optimizer() {
+    for_each_assertion(0x5eed_0003, |rng, assertion| {
+        let optimized = assertion.clone().optimize();
+        check_equivalent(rng, "folding optimization", &assertion, &optimized);
+    });
+}
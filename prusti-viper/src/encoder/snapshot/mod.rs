@@ -10,9 +10,41 @@ use vir::Type;
 
 mod purifier;
 mod fixer;
+#[cfg(test)]
+mod fuzz;
 
 pub const NAT_DOMAIN_NAME: &str = "$Nat$";
 pub const AXIOMATIZED_FUNCTION_DOMAIN_NAME: &str = "$AxiomatizedFunctions$";
+/// Prefix of the domain that encodes the snapshots of a predicate.
+pub const SNAPSHOT_DOMAIN_PREFIX: &str = "Snap$";
+/// Name of the domain function that returns the variant index of an enum snapshot.
+pub const SNAPSHOT_VARIANT: &str = "variant$";
+/// Prefix of the Viper fields that encode the fields of Rust structs.
+pub const STRUCT_FIELD_PREFIX: &str = "f$";
+
+/// Returns the name of the snapshot domain of the predicate `predicate_name`.
+pub fn encode_domain_name(predicate_name: &str) -> String {
+    format!("{}{}", SNAPSHOT_DOMAIN_PREFIX, predicate_name)
+}
+
+/// Returns the `variant$` function of the snapshot domain `domain_name`.
+pub fn encode_variant_func(domain_name: String) -> vir::DomainFunc {
+    let snap_type = vir::Type::Domain(domain_name.clone());
+    let arg = vir::LocalVar::new("self", snap_type);
+    vir::DomainFunc {
+        name: SNAPSHOT_VARIANT.to_string(),
+        formal_args: vec![arg],
+        return_type: vir::Type::Int,
+        unique: false,
+        domain_name,
+    }
+}
+
+/// Returns the name of the Rust struct field that is encoded by the Viper
+/// field `field`, or `None` if `field` does not encode a struct field.
+pub fn decode_struct_field_name(field: &vir::Field) -> Option<&str> {
+    field.name.strip_prefix(STRUCT_FIELD_PREFIX)
+}
 
 pub fn encode_field_domain_func(
     field_type: vir::Type,
@@ -143,39 +175,20 @@ pub fn encode_axiomatized_function(
     df
 }
 
-fn unbox(name: String) -> String {
-    let start = "m_alloc$$boxed$$Box$_beg_$";
-    let end = "$_sep_$m_alloc$$alloc$$Global$_beg_$_end_$_end_";
-    if !name.ends_with(end) {
-        return name;
-    }
-
-    if !name.starts_with(start) {
-        return name;
-    }
-
-    let remaining = name.len() - start.len() - end.len();
-
-    return name.chars().skip(start.len()).take(remaining).collect();
-}
-
 pub fn translate_type(t: Type, snapshots: &HashMap<String, Box<Snapshot>>) -> Type {
     match t {
         Type::TypedRef(name) => match name.as_str() {
             "i32" | "usize" | "u32" => Type::Int,
             "bool" => Type::Bool,
             _ => {
-                let name = unbox(name);
-                let domain_name = snapshots
+                // Boxes have the same snapshot type as their content.
+                snapshots
                     .get(&name)
-                    .and_then(|snap| snap.domain())
-                    .map(|domain| domain.name)
+                    .map(|snap| snap.get_type())
                     .expect(&format!(
-                        "No matching domain for '{}' in '{:?}'",
+                        "No matching snapshot for '{}' in '{:?}'",
                         name, snapshots
-                    ));
-
-                vir::Type::Domain(domain_name)
+                    ))
             }
         },
         o @ _ => o,
@@ -219,10 +232,15 @@ pub fn get_zero_func() -> vir::DomainFunc {
 mod tests {
     use super::*;
     #[test]
-    fn test_unbox() {
-        let res = unbox("m_alloc$$boxed$$Box$_beg_$m_len_lookup$$List$_beg_$_end_$_sep_$m_alloc$$alloc$$Global$_beg_$_end_$_end_".to_string());
-        assert_eq!(res, "m_len_lookup$$List$_beg_$_end_".to_string());
+    fn test_encode_domain_name() {
+        assert_eq!(encode_domain_name("m_Foo"), "Snap$m_Foo".to_string());
+    }
 
-        assert_eq!(unbox("u32".to_string()), "u32".to_string());
+    #[test]
+    fn test_decode_struct_field_name() {
+        let field = vir::Field::new("f$f$x", vir::Type::Int);
+        assert_eq!(decode_struct_field_name(&field), Some("f$x"));
+        let field = vir::Field::new("val_int", vir::Type::Int);
+        assert_eq!(decode_struct_field_name(&field), None);
     }
 }
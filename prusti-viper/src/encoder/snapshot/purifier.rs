@@ -1,10 +1,12 @@
 use crate::encoder::{
+    errors::{EncodingError, EncodingResult},
     snapshot,
-    snapshot_encoder::{Snapshot, SnapshotEncoder},
+    snapshot_encoder::{self, Snapshot, SnapshotEncoder},
 };
 use log::{debug, info, trace, warn};
 use prusti_common::vir::{
-    self, Expr, ExprFolder, Field, LocalVar, PermAmount, Position, Type, WithIdentifier,
+    self, EnumVariantIndex, Expr, FallibleExprFolder, Field, LocalVar, PermAmount, Position, Type,
+    WithIdentifier,
 };
 use std::collections::HashMap;
 
@@ -13,8 +15,37 @@ pub struct ExprPurifier<'a> {
     pub self_function: vir::Expr,
 }
 
-impl<'a> ExprFolder for ExprPurifier<'a> {
-    fn fold_unfolding(
+impl<'a> ExprPurifier<'a> {
+    /// Purifies the given expression. Fails if this would require dropping
+    /// a permission that cannot be soundly dropped.
+    pub fn purify(&mut self, expr: Expr) -> EncodingResult<Expr> {
+        self.fallible_fold(expr)
+    }
+
+    /// A permission can be dropped only if it is about a simple place, whose
+    /// value is fully captured by its snapshot. Permissions about other
+    /// expressions (e.g. old expressions) would be silently lost.
+    fn drop_access(&self, access: Expr) -> EncodingResult<Expr> {
+        let is_droppable = match access {
+            Expr::PredicateAccessPredicate(_, ref arg, _, _) => arg.is_simple_place(),
+            Expr::FieldAccessPredicate(ref receiver, _, _) => receiver.is_simple_place(),
+            _ => unreachable!(),
+        };
+        if is_droppable {
+            Ok(true.into())
+        } else {
+            Err(EncodingError::unsupported(format!(
+                "the purification optimization cannot drop the permission '{}'",
+                access
+            )))
+        }
+    }
+}
+
+impl<'a> FallibleExprFolder for ExprPurifier<'a> {
+    type Error = EncodingError;
+
+    fn fallible_fold_unfolding(
         &mut self,
         name: String,
         args: Vec<Expr>,
@@ -22,68 +53,69 @@ impl<'a> ExprFolder for ExprPurifier<'a> {
         perm: PermAmount,
         variant: vir::MaybeEnumVariantIndex,
         pos: Position,
-    ) -> Expr {
-        *self.fold_boxed(expr)
+    ) -> EncodingResult<Expr> {
+        Ok(*self.fallible_fold_boxed(expr)?)
     }
 
-    fn fold_variant(&mut self, base: Box<Expr>, variant: Field, p: Position) -> Expr {
-        *self.fold_boxed(base)
+    fn fallible_fold_variant(
+        &mut self,
+        base: Box<Expr>,
+        variant: Field,
+        p: Position,
+    ) -> EncodingResult<Expr> {
+        Ok(*self.fallible_fold_boxed(base)?)
     }
 
-    fn fold_field(&mut self, receiver: Box<Expr>, field: Field, pos: Position) -> Expr {
-        let receiver_type: Type = receiver.get_type().clone();
-        if let Type::TypedRef(receiver_domain) = receiver_type {
-            let mut receiver_domain = format!("Snap${}", receiver_domain); //FIXME this should come from a constant
-
-            let variant_name = if let Expr::Variant(base, var, _) = *receiver.clone() {
-                warn!("fold_field with base: {:?} variant: {:?}", base, var);
-                let name: String = var.name.chars().skip(5).collect(); //TODO this is probably not the best way to get the name of the variant
-                receiver_domain = receiver_domain[..receiver_domain.len() - name.len()].to_string();
-
-                Some(name)
-            } else {
-                None
-            };
-            let inner = self.fold_boxed(receiver);
-            let field_name = field.name.to_string();
-            match field_name.as_str() {
-                "val_bool" | "val_int" | "val_ref" => *inner,
-                "discriminant" => {
-                    let domain_name = receiver_domain;
-                    let snap_type = vir::Type::Domain(domain_name.to_string());
-                    let arg = vir::LocalVar::new("self", snap_type);
-                    let domain_func = vir::DomainFunc {
-                        name: "variant$".to_string(), //TODO use constant
-                        formal_args: vec![arg],
-                        return_type: vir::Type::Int,
-                        unique: false,
-                        domain_name: domain_name.to_string(),
-                    };
-
-                    vir::Expr::DomainFuncApp(domain_func, vec![*inner], pos)
-                }
-                _ => {
-                    let field_name: String = field_name.chars().skip(2).collect();
-                    let field_type = field.typ.clone();
-                    let purified_field_type = super::translate_type(field_type, &self.snapshots);
-
-                    let domain_func = super::encode_field_domain_func(
-                        purified_field_type,
-                        field_name,
-                        receiver_domain,
-                        variant_name, //TODO
-                    );
-
-                    vir::Expr::DomainFuncApp(domain_func, vec![*inner], pos)
-                }
+    fn fallible_fold_field(
+        &mut self,
+        receiver: Box<Expr>,
+        field: Field,
+        pos: Position,
+    ) -> EncodingResult<Expr> {
+        // The snapshot domain is determined by the predicate of the enum or
+        // struct, and not by the type of the variant.
+        let (predicate_type, variant_name) = match *receiver {
+            Expr::Variant(ref base, ref variant, _) => {
+                let variant_index: EnumVariantIndex = variant.into();
+                (
+                    base.get_type().clone(),
+                    Some(variant_index.get_variant_name().to_string()),
+                )
             }
+            _ => (receiver.get_type().clone(), None),
+        };
+        let receiver_domain = if let Type::TypedRef(predicate_name) = predicate_type {
+            snapshot::encode_domain_name(&predicate_name)
         } else {
             unreachable!();
-        }
+        };
+        let inner = self.fallible_fold_boxed(receiver)?;
+        Ok(match field.name.as_str() {
+            "val_bool" | "val_int" | "val_float" | "val_ref" => *inner,
+            "discriminant" => {
+                let domain_func = snapshot::encode_variant_func(receiver_domain);
+                vir::Expr::DomainFuncApp(domain_func, vec![*inner], pos)
+            }
+            _ => {
+                let field_name = snapshot::decode_struct_field_name(&field)
+                    .unwrap_or_else(|| unreachable!("unexpected field: {}", field.name))
+                    .to_string();
+                let purified_field_type = super::translate_type(field.typ, &self.snapshots);
+
+                let domain_func = super::encode_field_domain_func(
+                    purified_field_type,
+                    field_name,
+                    receiver_domain,
+                    variant_name,
+                );
+
+                vir::Expr::DomainFuncApp(domain_func, vec![*inner], pos)
+            }
+        })
     }
 
-    fn fold_local(&mut self, v: LocalVar, p: Position) -> Expr {
-        if v.name == "__result" {
+    fn fallible_fold_local(&mut self, v: LocalVar, p: Position) -> EncodingResult<Expr> {
+        Ok(if v.name == "__result" {
             self.self_function.clone()
         } else {
             Expr::Local(
@@ -93,30 +125,45 @@ impl<'a> ExprFolder for ExprPurifier<'a> {
                 },
                 p,
             )
-        }
+        })
     }
 
-    fn fold_func_app(
+    fn fallible_fold_func_app(
         &mut self,
         name: String,
         mut args: Vec<Expr>,
         formal_args: Vec<LocalVar>,
         return_type: Type,
         pos: Position
-    ) -> Expr {
-        match name.as_str() {
+    ) -> EncodingResult<Expr> {
+        Ok(match name.as_str() {
             "builtin$unreach_int" => Expr::FuncApp(
                 name,
-                args.into_iter().map(|e| self.fold(e)).collect(),
+                args.into_iter()
+                    .map(|e| self.fallible_fold(e))
+                    .collect::<Result<_, _>>()?,
                 formal_args,
                 return_type,
                 pos,
             ),
+            snapshot_encoder::SNAPSHOT_EQUALS | snapshot_encoder::SNAPSHOT_NOT_EQUALS => {
+                // A call of `PartialEq::eq` or `PartialEq::ne` on a type with a
+                // structural equality. Once purified, both arguments are
+                // snapshots, which are equal iff the values are.
+                assert_eq!(args.len(), 2, "A comparison must have exactly two arguments.");
+                let right = self.fallible_fold(args.pop().unwrap())?;
+                let left = self.fallible_fold(args.pop().unwrap())?;
+                if name == snapshot_encoder::SNAPSHOT_EQUALS {
+                    Expr::eq_cmp(left, right)
+                } else {
+                    Expr::ne_cmp(left, right)
+                }
+            }
             "snap$"  =>{
                 // This is a snapshot function. Just drop it and use its argument.
                 // FIXME: We should have a proper way of discovering this.
                 assert_eq!(args.len(), 1, "The snapshot function must contain only a single argument.");
-                self.fold(args.pop().unwrap())
+                self.fallible_fold(args.pop().unwrap())?
             }
             _ => {
                 let ident_name = vir::compute_identifier(&name, &formal_args, &return_type);
@@ -128,30 +175,87 @@ impl<'a> ExprFolder for ExprPurifier<'a> {
                     &self.snapshots,
                 );
 
-                let mut folded_args: Vec<Expr> = args.into_iter().map(|e| self.fold(e)).collect();
+                let mut folded_args: Vec<Expr> = args.into_iter()
+                    .map(|e| self.fallible_fold(e))
+                    .collect::<Result<_, _>>()?;
                 folded_args.push(snapshot::encode_nat_argument().into());
                 Expr::DomainFuncApp(df, folded_args, pos)
             }
-        }
+        })
     }
 
-    fn fold_predicate_access_predicate(
+    fn fallible_fold_predicate_access_predicate(
         &mut self,
         name: String,
         arg: Box<Expr>,
         perm_amount: PermAmount,
         pos: Position,
-    ) -> Expr {
-        true.into()
+    ) -> EncodingResult<Expr> {
+        self.drop_access(Expr::PredicateAccessPredicate(name, arg, perm_amount, pos))
     }
 
-    fn fold_field_access_predicate(
+    fn fallible_fold_field_access_predicate(
         &mut self,
         receiver: Box<Expr>,
         perm_amount: PermAmount,
         pos: Position,
-    ) -> Expr {
-        true.into()
+    ) -> EncodingResult<Expr> {
+        self.drop_access(Expr::FieldAccessPredicate(receiver, perm_amount, pos))
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int_local(name: &str) -> Expr {
+        Expr::local(LocalVar::new(name, Type::TypedRef("i32".to_string())))
     }
 
+    fn val_int(base: Expr) -> Expr {
+        base.field(Field::new("val_int", Type::Int))
+    }
+
+    fn purify(expr: Expr) -> EncodingResult<Expr> {
+        let snapshots = HashMap::new();
+        let mut purifier = ExprPurifier {
+            snapshots: &snapshots,
+            self_function: true.into(),
+        };
+        purifier.purify(expr)
+    }
+
+    #[test]
+    fn test_drop_access_to_place() {
+        let expr = Expr::and(
+            Expr::predicate_access_predicate("i32", int_local("_1"), PermAmount::Read),
+            Expr::and(
+                Expr::field_access_predicate(val_int(int_local("_1")), PermAmount::Read),
+                Expr::gt_cmp(val_int(int_local("_1")), 0.into()),
+            ),
+        );
+        let expected = Expr::and(
+            true.into(),
+            Expr::and(
+                true.into(),
+                Expr::gt_cmp(Expr::local(LocalVar::new("_1", Type::Int)), 0.into()),
+            ),
+        );
+        assert_eq!(purify(expr).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_reject_access_to_old_place() {
+        let old_local = Expr::LabelledOld(
+            "pre".to_string(),
+            box int_local("_1"),
+            Position::default(),
+        );
+        let expr = Expr::and(
+            Expr::predicate_access_predicate("i32", old_local, PermAmount::Read),
+            Expr::gt_cmp(val_int(int_local("_1")), 0.into()),
+        );
+        assert!(purify(expr).is_err());
+    }
 }
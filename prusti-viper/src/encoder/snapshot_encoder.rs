@@ -4,10 +4,23 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+//! Encoding of the snapshot of a type, which is the mathematical value of an
+//! instance of the type.
+//!
+//! Snapshots are structural and ignore addresses: the snapshot of a
+//! reference or of a box is the snapshot of the value that it points to, so
+//! two instances have the same snapshot iff their fields (or, for enums, their
+//! variants and fields) have the same snapshots. Equality of snapshots is
+//! supported only for the types whose snapshot is defined by their content;
+//! types that contain e.g. raw pointers get an opaque snapshot, which does not
+//! support equality. With the purification optimization, the field functions of
+//! a domain are complemented by extensionality axioms, so that two snapshots
+//! that agree on all fields are known to be equal.
+
 use prusti_common::vir;
 use crate::encoder::Encoder;
 use rustc_middle::ty;
-use prusti_common::vir::{PermAmount, EnumVariantIndex};
+use prusti_common::vir::{PermAmount, EnumVariantIndex, ExprIterator};
 use log::warn;
 use crate::encoder::errors::{EncodingError, EncodingResult, SpannedEncodingResult};
 use std::borrow::Borrow;
@@ -17,9 +30,7 @@ use rustc_target::abi::Integer;
 use ::log::{info, debug, trace};
 use crate::encoder::snapshot;
 
-const SNAPSHOT_DOMAIN_PREFIX: &str = "Snap$";
 const SNAPSHOT_CONS: &str = "cons$";
-const SNAPSHOT_VARIANT: &str = "variant$";
 const SNAPSHOT_GET: &str = "snap$";
 pub const SNAPSHOT_EQUALS: &str = "equals$";
 pub const SNAPSHOT_NOT_EQUALS: &str = "not_equals$";
@@ -155,6 +166,15 @@ impl<'p, 'v, 'r: 'v, 'a: 'r, 'tcx: 'a> SnapshotEncoder<'p, 'v, 'tcx> {
     }
 
     pub fn encode(&self) -> EncodingResult<Snapshot> {
+        if let Some(boxed_ty) = get_pointee_ty(self.encoder, self.ty) {
+            let boxed_snapshot = self.encoder.encode_snapshot(boxed_ty)?;
+            return self.encode_box(boxed_ty, &boxed_snapshot);
+        }
+
+        if let Some(elem_ty) = self.get_seq_elem_ty() {
+            return self.encode_seq(elem_ty);
+        }
+
         if !self.is_supported() {
             return Ok(self.encode_generic(false)?); // fallback solution
         }
@@ -162,6 +182,7 @@ impl<'p, 'v, 'r: 'v, 'a: 'r, 'tcx: 'a> SnapshotEncoder<'p, 'v, 'tcx> {
         Ok(match &self.ty.kind() {
             ty::TyKind::Int(_)
             | ty::TyKind::Uint(_)
+            | ty::TyKind::Float(_)
             | ty::TyKind::Char
             | ty::TyKind::Bool => {
                 self.encode_primitive(
@@ -174,7 +195,7 @@ impl<'p, 'v, 'r: 'v, 'a: 'r, 'tcx: 'a> SnapshotEncoder<'p, 'v, 'tcx> {
             ty::TyKind::Tuple(_) => {
                 self.encode_tuple()?
             }
-            ty::TyKind::Adt(adt_def, subst) if !adt_def.is_box() => {
+            ty::TyKind::Adt(adt_def, subst) => {
                 if adt_def.is_struct() || adt_def.is_enum() {
                     SnapshotAdtEncoder::new(
                         &self,
@@ -185,24 +206,6 @@ impl<'p, 'v, 'r: 'v, 'a: 'r, 'tcx: 'a> SnapshotEncoder<'p, 'v, 'tcx> {
                     unreachable!()
                 }
             }
-            ty::TyKind::Adt(adt_def, _) if adt_def.is_box() => { //TODO this breaks for lots of boxes. e.g. Box<Box<T>> or Box<i32>
-                let boxed_ty = self.ty.boxed_ty();
-                warn!("enocding boxed ty {:?}", boxed_ty);
-                match boxed_ty.kind() {
-                    ty::TyKind::Adt(adt_def, subst) => {
-                        if adt_def.is_struct() || adt_def.is_enum() {
-                            SnapshotAdtEncoder::new(
-                                &self,
-                                adt_def,
-                                subst,
-                            ).encode_snapshot()?
-                        } else {
-                            unreachable!()
-                        }
-                    }
-                    x => Err(EncodingError::unsupported(format!("{:?}", x)))?
-                }
-            }
             x => Err(EncodingError::unsupported(format!("{:?}", x)))?
         })
     }
@@ -222,6 +225,7 @@ impl<'p, 'v, 'r: 'v, 'a: 'r, 'tcx: 'a> SnapshotEncoder<'p, 'v, 'tcx> {
         match ty.kind() {
             ty::TyKind::Int(_)
             | ty::TyKind::Uint(_)
+            | ty::TyKind::Float(_)
             | ty::TyKind::Char
             | ty::TyKind::Bool
             | ty::TyKind::Param(_) => {
@@ -232,7 +236,16 @@ impl<'p, 'v, 'r: 'v, 'a: 'r, 'tcx: 'a> SnapshotEncoder<'p, 'v, 'tcx> {
                 self.is_ty_supported(ty, parent_boxes)
             }
 
-            ty::TyKind::Adt(adt_def, subst) if !adt_def.is_box() => {
+            // The content of a cell is not tracked, so it has no value.
+            ty::TyKind::Adt(_, _) if self.encoder.is_cell_type(ty) => false,
+            ty::TyKind::Adt(_, _) if get_pointee_ty(self.encoder, ty).is_some() => {
+                parent_boxes.push(ty);
+                let boxed_ty = get_pointee_ty(self.encoder, ty).unwrap();
+                let is = self.is_ty_supported(boxed_ty, parent_boxes);
+                parent_boxes.pop();
+                is
+            }
+            ty::TyKind::Adt(adt_def, subst) => {
                 let tcx = self.encoder.env().tcx();
                 for variant in &adt_def.variants {
                     for field in &variant.fields {
@@ -244,12 +257,6 @@ impl<'p, 'v, 'r: 'v, 'a: 'r, 'tcx: 'a> SnapshotEncoder<'p, 'v, 'tcx> {
                 }
                 true
             }
-            ty::TyKind::Adt(adt_def, subst) if adt_def.is_box() => {
-                parent_boxes.push(ty);
-                let is = self.is_ty_supported(ty.boxed_ty(), parent_boxes);
-                parent_boxes.pop();
-                is
-            }
             ty::TyKind::Tuple(elems) => {
                 for field_ty in *elems {
                     if !self.is_ty_supported(field_ty.expect_ty(), parent_boxes) {
@@ -262,6 +269,69 @@ impl<'p, 'v, 'r: 'v, 'a: 'r, 'tcx: 'a> SnapshotEncoder<'p, 'v, 'tcx> {
         }
     }
 
+    /// The type of the elements of a slice or of a vector.
+    fn get_seq_elem_ty(&self) -> Option<ty::Ty<'tcx>> {
+        match self.ty.kind() {
+            ty::TyKind::Slice(elem_ty) => Some(*elem_ty),
+            _ => self.encoder.get_vec_elem_ty(self.ty),
+        }
+    }
+
+    /// The snapshot of a slice or of a vector is the sequence of the
+    /// snapshots of its elements. Its length, and its elements if they are
+    /// primitive, agree with the functions that read the slice in the rest of
+    /// the encoding. Without a snapshot of the elements, the snapshot is
+    /// opaque.
+    fn encode_seq(&self, elem_ty: ty::Ty<'tcx>) -> EncodingResult<Snapshot> {
+        let elem_type = match self.encoder.encode_snapshot(elem_ty) {
+            Ok(elem_snapshot) => elem_snapshot.get_type(),
+            Err(_) => return self.encode_generic(false),
+        };
+        let seq_type = self.encoder.encode_seq_type(&elem_type);
+        let arg = self.encode_arg_local(SNAPSHOT_ARG);
+        let result: vir::Expr = vir::LocalVar::new("__result", seq_type.clone()).into();
+        let len = self.encoder.encode_seq_len(result.clone(), &elem_type);
+        let mut posts = vec![vir::Expr::eq_cmp(
+            len.clone(),
+            self.encoder.encode_slice_len_func_app(arg.clone(), self.ty)?,
+        )];
+        if let ty::TyKind::Bool | ty::TyKind::Int(_) | ty::TyKind::Uint(_) = elem_ty.kind() {
+            let index_var = vir::LocalVar::new("index", vir::Type::Int);
+            let index: vir::Expr = index_var.clone().into();
+            let elem = self.encoder.encode_seq_lookup(result, index.clone(), &elem_type);
+            let slice_elem = self.encoder.encode_slice_lookup_func_app(
+                arg, self.ty, index.clone(), &[], elem_ty,
+            )?;
+            posts.push(vir::Expr::forall(
+                vec![index_var],
+                vec![
+                    vir::Trigger::new(vec![elem.clone()]),
+                    vir::Trigger::new(vec![slice_elem.clone()]),
+                ],
+                vir::Expr::implies(
+                    vir::Expr::and(
+                        vir::Expr::le_cmp(0.into(), index.clone()),
+                        vir::Expr::lt_cmp(index, len),
+                    ),
+                    vir::Expr::eq_cmp(elem, slice_elem),
+                ),
+            ));
+        }
+        Ok(Snapshot {
+            predicate_name: self.predicate_name.clone(),
+            snap_func: vir::Function {
+                name: SNAPSHOT_GET.to_string(),
+                formal_args: vec![self.encode_arg_var(SNAPSHOT_ARG)],
+                return_type: seq_type,
+                pres: vec![self.encode_predicate_access(self.encode_arg_local(SNAPSHOT_ARG))],
+                posts,
+                body: None,
+            },
+            snap_domain: None,
+            is_equality_supported: false,
+        })
+    }
+
     fn encode_primitive(&self, field: vir::Field)
         -> EncodingResult<Snapshot>
     {
@@ -273,6 +343,27 @@ impl<'p, 'v, 'r: 'v, 'a: 'r, 'tcx: 'a> SnapshotEncoder<'p, 'v, 'tcx> {
         })
     }
 
+    /// A box has the same snapshot as its content, so that specifications do
+    /// not observe the indirection. Only the snapshot function, which reads
+    /// through the box, is specific to the box. The same holds for
+    /// reference-counted pointers.
+    fn encode_box(
+        &self,
+        boxed_ty: ty::Ty<'tcx>,
+        boxed_snapshot: &Snapshot,
+    ) -> EncodingResult<Snapshot> {
+        let deref_field = self.encoder.encode_dereference_field(boxed_ty)?;
+        let body = boxed_snapshot.snap_call(
+            self.encode_arg_field(self.encode_arg_local(SNAPSHOT_ARG), deref_field)
+        );
+        Ok(Snapshot {
+            predicate_name: self.predicate_name.clone(),
+            snap_func: self.encode_snap_func(boxed_snapshot.get_type(), body),
+            snap_domain: None,
+            is_equality_supported: boxed_snapshot.supports_equality(),
+        })
+    }
+
     fn encode_generic(&self, is_equality_supported: bool) -> EncodingResult<Snapshot> {
         let snap_domain = self.encode_snap_domain()?;
         Ok(Snapshot {
@@ -426,11 +517,7 @@ impl<'p, 'v, 'r: 'v, 'a: 'r, 'tcx: 'a> SnapshotEncoder<'p, 'v, 'tcx> {
     }
 
     fn encode_domain_name(&self) -> String {
-        format!(
-            "{}{}",
-            SNAPSHOT_DOMAIN_PREFIX,
-            self.predicate_name,
-        )
+        snapshot::encode_domain_name(&self.predicate_name)
     }
 
     fn encode_domain_cons(&self)
@@ -645,37 +732,34 @@ impl<'p, 'v, 'r: 'v, 'a: 'r, 'tcx: 'a> SnapshotEncoder<'p, 'v, 'tcx> {
         field: vir::Field,
         field_ty: ty::Ty<'tcx>,
     ) -> EncodingResult<vir::Expr> {
-        match field_ty.kind() {
-            ty::TyKind::Adt(adt_def, _) if adt_def.is_box() => {
-                //TODO this breaks for lots of boxes. e.g. Box<Box<T>> or Box<i32>
-                let boxed_ty = field_ty.boxed_ty();
-                match boxed_ty.kind() {
-                    ty::TyKind::Adt(adt_def, subst) => {
-                        if adt_def.is_struct() || adt_def.is_enum() {
-                            let predicate_name = self.encoder.encode_type_predicate_use(boxed_ty)?;
-                            let field_snapshot_encoder = SnapshotEncoder::new(
-                                self.encoder,
-                                boxed_ty,
-                                predicate_name.to_string(),
-                            );
-
-                            let field_sae =
-                                SnapshotAdtEncoder::new(&field_snapshot_encoder, adt_def, subst);
-                            Ok(vir::Expr::func_app(
-                                SNAPSHOT_GET.to_string(),
-                                vec![self.encode_arg_field(location, field)],
-                                vec![field_snapshot_encoder.encode_arg_var(SNAPSHOT_ARG)],
-                                field_sae.get_snapshot_type(boxed_ty)?,
-                                vir::Position::default(),
-                            ))
-                        } else {
-                            unreachable!()
-                        }
+        match get_pointee_ty(self.encoder, field_ty) {
+            Some(boxed_ty) => {
+                // Boxes are transparent: take the snapshot of the boxed value.
+                let box_place = self.encode_arg_field(location, field);
+                let deref_field = self.encoder.encode_dereference_field(boxed_ty)?;
+                let boxed_snap = match boxed_ty.kind() {
+                    ty::TyKind::Adt(_, _) if get_pointee_ty(self.encoder, boxed_ty).is_none() => {
+                        // Do not encode the snapshot of the boxed type here,
+                        // because this would not terminate for recursive types.
+                        let predicate_name = self.encoder.encode_type_predicate_use(boxed_ty)?;
+                        let boxed_snapshot_encoder = SnapshotEncoder::new(
+                            self.encoder,
+                            boxed_ty,
+                            predicate_name,
+                        );
+                        vir::Expr::func_app(
+                            SNAPSHOT_GET.to_string(),
+                            vec![box_place.clone().field(deref_field)],
+                            vec![boxed_snapshot_encoder.encode_arg_var(SNAPSHOT_ARG)],
+                            vir::Type::Domain(boxed_snapshot_encoder.encode_domain_name()),
+                            vir::Position::default(),
+                        )
                     }
-                    x => Err(EncodingError::unsupported(format!("{:?}", x))),
-                }
+                    _ => self.encode_snap_arg(box_place.clone(), deref_field, boxed_ty)?,
+                };
+                Ok(vir::Expr::wrap_in_unfolding(box_place, boxed_snap))
             }
-            _ => {
+            None => {
                 let snapshot = self.encoder.encode_snapshot(field_ty)?;
                 let encoded_arg = self.dereference_expr_with_unfolding(
                     self.encode_arg_field(location, field)
@@ -1027,6 +1111,71 @@ impl<'s, 'v: 's, 'tcx: 'v> SnapshotAdtEncoder<'s, 'v, 'tcx> {
         })
     }
 
+    /// Encodes the axiom that two snapshots of `variant` (or of the struct)
+    /// that agree on all fields are equal. Returns `None` for a struct
+    /// without fields, for which the axiom would have no trigger.
+    fn encode_extensionality_axiom(
+        &self,
+        variant: Option<&ty::VariantDef>,
+    ) -> EncodingResult<Option<vir::DomainAxiom>> {
+        let domain_name = self.snapshot_encoder.encode_domain_name();
+        let domain_type = vir::Type::Domain(domain_name.clone());
+        let left = vir::Expr::local(vir::LocalVar::new(SNAPSHOT_LEFT, domain_type.clone()));
+        let right = vir::Expr::local(vir::LocalVar::new(SNAPSHOT_RIGHT, domain_type.clone()));
+
+        let fields: Vec<&ty::FieldDef> = if let Some(v) = variant {
+            v.fields.iter().collect()
+        } else {
+            self.adt_def.all_fields().collect()
+        };
+        let variant_name = variant
+            .map(|v| v.ident.name.to_ident_string())
+            .unwrap_or("".to_string());
+
+        let mut conjuncts = vec![];
+        let mut trigger_terms = vec![];
+        if let Some(v) = variant {
+            let variant_func = self.encode_variant_func();
+            let variant_index: vir::Expr = self.variant_index(Some(v)).into();
+            let left_variant = vir::Expr::domain_func_app(variant_func.clone(), vec![left.clone()]);
+            let right_variant = vir::Expr::domain_func_app(variant_func, vec![right.clone()]);
+            conjuncts.push(vir::Expr::eq_cmp(left_variant.clone(), variant_index.clone()));
+            conjuncts.push(vir::Expr::eq_cmp(right_variant.clone(), variant_index));
+            if fields.is_empty() {
+                trigger_terms = vec![left_variant, right_variant];
+            }
+        } else if fields.is_empty() {
+            return Ok(None);
+        }
+        for field in fields {
+            let field_func = self.encode_field_func(field, Some(variant_name.clone()))?;
+            let left_field = vir::Expr::domain_func_app(field_func.clone(), vec![left.clone()]);
+            let right_field = vir::Expr::domain_func_app(field_func, vec![right.clone()]);
+            if trigger_terms.is_empty() {
+                trigger_terms = vec![left_field.clone(), right_field.clone()];
+            }
+            conjuncts.push(vir::Expr::eq_cmp(left_field, right_field));
+        }
+
+        let axiom_body = vir::Expr::forall(
+            vec![
+                vir::LocalVar::new(SNAPSHOT_LEFT, domain_type.clone()),
+                vir::LocalVar::new(SNAPSHOT_RIGHT, domain_type),
+            ],
+            vec![vir::Trigger::new(trigger_terms)],
+            vir::Expr::implies(
+                conjuncts.into_iter().conjoin(),
+                vir::Expr::eq_cmp(left, right),
+            ),
+        );
+
+        Ok(Some(vir::DomainAxiom {
+            name: format!("{}${}$extensionality$axiom", domain_name, variant_name),
+            expr: axiom_body,
+            domain_name,
+        }))
+    }
+
     /// For a given variant returns the index at which the constructor for this variant is in `self.encode_constructors`
     fn variant_index(&self, variant: Option<&ty::VariantDef>) -> usize {
         if let Some(v) = variant {
@@ -1057,6 +1206,9 @@ impl<'s, 'v: 's, 'tcx: 'v> SnapshotAdtEncoder<'s, 'v, 'tcx> {
                     funcs.push(self.encode_field_func(field, None)?);
                     axioms.push(self.encode_field_axiom(field, None)?);
                 }
+                if let Some(axiom) = self.encode_extensionality_axiom(None)? {
+                    axioms.push(axiom);
+                }
             }
             ty::AdtKind::Enum => {
                 for variant in &self.adt_def.variants {
@@ -1066,6 +1218,9 @@ impl<'s, 'v: 's, 'tcx: 'v> SnapshotAdtEncoder<'s, 'v, 'tcx> {
                         funcs.push(self.encode_field_func(field, Some(variant_name.clone()))?);
                         axioms.push(self.encode_field_axiom(field, Some(variant))?);
                     }
+                    if let Some(axiom) = self.encode_extensionality_axiom(Some(variant))? {
+                        axioms.push(axiom);
+                    }
                 }
             }
             x => {
@@ -1092,16 +1247,7 @@ impl<'s, 'v: 's, 'tcx: 'v> SnapshotAdtEncoder<'s, 'v, 'tcx> {
 
     fn encode_variant_func(&self) -> vir::DomainFunc
     {
-        let domain_name = self.snapshot_encoder.encode_domain_name();
-        let snap_type = vir::Type::Domain(domain_name.to_string());
-        let arg = vir::LocalVar::new("self", snap_type);
-        vir::DomainFunc {
-            name: SNAPSHOT_VARIANT.to_string(),
-            formal_args: vec![arg],
-            return_type: vir::Type::Int,
-            unique: false,
-            domain_name: domain_name.to_string(),
-        }
+        snapshot::encode_variant_func(self.snapshot_encoder.encode_domain_name())
     }
 
     fn encode_axiom_variants(
@@ -1173,37 +1319,10 @@ impl<'s, 'v: 's, 'tcx: 'v> SnapshotAdtEncoder<'s, 'v, 'tcx> {
         */
 
         let res = match ty.kind() {
-            ty::TyKind::Adt(adt_def, _) if adt_def.is_box() => {
-                let boxed_ty = ty.boxed_ty();
-
-                match boxed_ty.kind() {
-                    ty::TyKind::Adt(adt_def, subst) => {
-
-                        if adt_def.is_struct() || adt_def.is_enum() {
-                            let predicate_name = self.snapshot_encoder.encoder.encode_type_predicate_use(&boxed_ty)?;
-
-                            let snapshot_encoder = SnapshotEncoder::new(
-                                self.snapshot_encoder.encoder,
-                                boxed_ty,
-                                predicate_name.to_string()
-                            );
-
-
-                            let domain_name = snapshot_encoder.encode_domain_name();
-                            Ok(vir::Type::Domain(domain_name))
-
-                        } else {
-                            unreachable!()
-                        }
-                    }
-                    _ => {
-                        let snapshot = self.snapshot_encoder.encoder.encode_snapshot(&ty)?;
-                        Ok(snapshot.get_type())
-                    }
-                }
-
+            ty::TyKind::Adt(_, _) if get_pointee_ty(self.snapshot_encoder.encoder, ty).is_some() => {
+                self.get_snapshot_type(get_pointee_ty(self.snapshot_encoder.encoder, ty).unwrap())
             }
-            ty::TyKind::Adt(adt_def, _) if !adt_def.is_box() => {
+            ty::TyKind::Adt(_, _) => {
                 let predicate_name = self.snapshot_encoder.encoder.encode_type_predicate_use(&ty)?;
 
                 let snapshot_encoder = SnapshotEncoder::new(
@@ -1415,3 +1534,13 @@ impl<'s, 'v: 's, 'tcx: 'v> SnapshotAdtEncoder<'s, 'v, 'tcx> {
         }
     }
 }
+
+/// The content of `ty`, if `ty` is a box or a reference-counted pointer.
+/// Their snapshot is the snapshot of their content.
+fn get_pointee_ty<'tcx>(encoder: &Encoder<'_, 'tcx>, ty: ty::Ty<'tcx>) -> Option<ty::Ty<'tcx>> {
+    if ty.is_box() {
+        Some(ty.boxed_ty())
+    } else {
+        encoder.get_rc_pointee_ty(ty)
+    }
+}
@@ -14,6 +14,7 @@ use crate::encoder::mir_interpreter::{
     MultiExprBackwardInterpreterState,
 };
 use crate::encoder::pure_function_encoder::PureFunctionBackwardInterpreter;
+use crate::encoder::specs_closures_collector::find_single_closure_instantiation;
 use crate::encoder::Encoder;
 use prusti_common::config;
 use crate::encoder::SpecFunctionKind;
@@ -75,6 +76,36 @@ pub fn encode_spec_assertion<'v, 'tcx: 'v>(
     Ok(encoded_assertion)
 }
 
+/// Encode the contract of a call of a closure of type `closure_ty`, as given by the specification
+/// entailments `f |= |args| [requires(..), ensures(..)]` of the precondition `assertion` of the
+/// calling procedure, where `f` has type `closure_ty`.
+///
+/// Arguments:
+/// * `mir`: the MIR of the calling procedure.
+/// * `target_args`: the expression to be used to encode the arguments of the calling procedure.
+/// * `call_args`: the values of the arguments of the call.
+/// * `call_result`: the value of the result of the call.
+///
+/// The result contains the `requires` and the `ensures` clauses of the entailments, each with
+/// its span. Only the entailments that are conjuncts of the precondition are taken into account.
+pub fn encode_closure_call_contract<'v, 'tcx: 'v>(
+    encoder: &Encoder<'v, 'tcx>,
+    assertion: &typed::Assertion<'tcx>,
+    closure_ty: ty::Ty<'tcx>,
+    mir: &mir::Body<'tcx>,
+    target_args: &[vir::Expr],
+    call_args: &[vir::Expr],
+    call_result: &vir::Expr,
+) -> SpannedEncodingResult<(Vec<(vir::Expr, rustc_span::MultiSpan)>, Vec<(vir::Expr, rustc_span::MultiSpan)>)> {
+    let spec_encoder = SpecEncoder::new(encoder, "", target_args, None, false, None);
+    let mut pres = vec![];
+    let mut posts = vec![];
+    spec_encoder.encode_closure_call_contract(
+        assertion, closure_ty, mir, call_args, call_result, &mut pres, &mut posts
+    )?;
+    Ok((pres, posts))
+}
+
 struct SpecEncoder<'p, 'v: 'p, 'tcx: 'v> {
     encoder: &'p Encoder<'v, 'tcx>,
     /// The label to encode `old(..)` expressions
@@ -124,6 +155,86 @@ impl<'p, 'v: 'p, 'tcx: 'v> SpecEncoder<'p, 'v, 'tcx> {
         vir::LocalVar::new(var_name, snapshot.get_type())
     }
 
+    /// Collect in `pres` and `posts` the contract of a call of a closure of type `closure_ty`.
+    /// See `encode_closure_call_contract`.
+    fn encode_closure_call_contract(
+        &self,
+        assertion: &typed::Assertion<'tcx>,
+        closure_ty: ty::Ty<'tcx>,
+        mir: &mir::Body<'tcx>,
+        call_args: &[vir::Expr],
+        call_result: &vir::Expr,
+        pres: &mut Vec<(vir::Expr, rustc_span::MultiSpan)>,
+        posts: &mut Vec<(vir::Expr, rustc_span::MultiSpan)>,
+    ) -> SpannedEncodingResult<()> {
+        let tcx = self.encoder.env().tcx();
+        match assertion.kind {
+            box typed::AssertionKind::And(ref assertions) => {
+                for assertion in assertions {
+                    self.encode_closure_call_contract(
+                        assertion, closure_ty, mir, call_args, call_result, pres, posts
+                    )?;
+                }
+            }
+            box typed::AssertionKind::SpecEntailment {
+                ref closure,
+                arg_binders: ref vars,
+                pres: ref entailment_pres,
+                posts: ref entailment_posts,
+            } => {
+                let closure_mir = self.encoder.env().local_mir(closure.expr);
+                if closure_mir.local_decls[mir::RETURN_PLACE].ty != closure_ty {
+                    return Ok(());
+                }
+                let span = rustc_span::MultiSpan::from_spans(
+                    typed::Spanned::get_spans(assertion, mir, tcx)
+                );
+                let is_integer = |ty: ty::Ty<'tcx>| {
+                    matches!(ty.kind(), ty::TyKind::Int(..) | ty::TyKind::Uint(..))
+                };
+                if !vars.args.iter().all(|(_, arg_ty)| is_integer(arg_ty)) || !is_integer(vars.result.1) {
+                    return Err(SpannedEncodingError::unsupported(
+                        "only closures with integer arguments and result can be called",
+                        span,
+                    ));
+                }
+                debug_assert_eq!(vars.args.len(), call_args.len());
+                // The arguments of the closure are bound with different ids in the `requires`
+                // and in the `ensures` clauses; see the encoding of the entailment above.
+                let pre_id = format!("{}_{}", vars.spec_id, vars.pre_id);
+                let post_id = format!("{}_{}", vars.spec_id, vars.post_id);
+                for pre in entailment_pres {
+                    let mut encoded_pre = self.encode_assertion(pre)?;
+                    for ((arg, arg_ty), call_arg) in vars.args.iter().zip(call_args) {
+                        let encoded_arg = self.encode_forall_arg(*arg, arg_ty, &pre_id);
+                        encoded_pre = encoded_pre.replace_place(&encoded_arg.into(), call_arg);
+                    }
+                    pres.push((
+                        encoded_pre,
+                        rustc_span::MultiSpan::from_spans(typed::Spanned::get_spans(pre, mir, tcx)),
+                    ));
+                }
+                let result_var = mir::Local::from_usize(vars.args.len() + 2);
+                let encoded_result = self.encode_forall_arg(result_var, vars.result.1, &post_id);
+                for post in entailment_posts {
+                    let mut encoded_post = self.encode_assertion(post)?;
+                    for ((arg, arg_ty), call_arg) in vars.args.iter().zip(call_args) {
+                        let encoded_arg = self.encode_forall_arg(*arg, arg_ty, &post_id);
+                        encoded_post = encoded_post.replace_place(&encoded_arg.into(), call_arg);
+                    }
+                    encoded_post = encoded_post.replace_place(&encoded_result.clone().into(), call_result);
+                    posts.push((
+                        encoded_post,
+                        rustc_span::MultiSpan::from_spans(typed::Spanned::get_spans(post, mir, tcx)),
+                    ));
+                }
+            }
+            // The other assertions do not unconditionally specify the closure.
+            _ => {}
+        }
+        Ok(())
+    }
+
     fn encode_trigger(
         &self,
         trigger: &typed::Trigger,
@@ -226,7 +337,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> SpecEncoder<'p, 'v, 'tcx> {
                 let mut bounds = Vec::new();
                 for (arg, ty) in &vars.vars {
                     let encoded_arg = self.encode_forall_arg(*arg, ty, &format!("{}_{}", vars.spec_id, vars.id));
-                    if config::check_overflows() {
+                    if config::integer_bounds() {
                         bounds.extend(self.encoder.encode_type_bounds(&encoded_arg.clone().into(), ty));
                     } else if config::encode_unsigned_num_constraint() {
                         if let ty::TyKind::Uint(_) = ty.kind() {
@@ -370,6 +481,14 @@ impl<'p, 'v: 'p, 'tcx: 'v> SpecEncoder<'p, 'v, 'tcx> {
                         }
                         _ => unreachable!()
                     }
+                } else if let ty::TyKind::Closure(def_id, _) = self.encoder.reveal_opaque_type(ty).kind() {
+                    self.encode_returned_closure_entailment(
+                        *def_id,
+                        vars,
+                        pres,
+                        posts,
+                        tcx.def_span(closure.expr),
+                    )?
                 } else {
                     // TODO
                     vir::Expr::Const(vir::Const::Bool(true), vir::Position::default())
@@ -378,6 +497,134 @@ impl<'p, 'v: 'p, 'tcx: 'v> SpecEncoder<'p, 'v, 'tcx> {
         })
     }
 
+    /// Encode a specification entailment `result |= |x| [requires(..), ensures(..)]` about a
+    /// closure that is created and returned by the procedure whose postcondition is being
+    /// encoded, e.g. `fn make(captured: i32) -> impl Fn(i32) -> i32`.
+    ///
+    /// The `ensures` clauses are checked against the body of the closure, which is encoded as a
+    /// pure expression and translated to the state of the procedure at the beginning, so that
+    /// the clauses can constrain the captured state by referring to the arguments of the
+    /// procedure. The `requires` clauses have to imply the precondition of the closure.
+    fn encode_returned_closure_entailment(
+        &self,
+        closure_def_id: DefId,
+        vars: &typed::SpecEntailmentVars<'tcx>,
+        pres: &[typed::Assertion<'tcx>],
+        posts: &[typed::Assertion<'tcx>],
+        span: rustc_span::Span,
+    ) -> SpannedEncodingResult<vir::Expr> {
+        let is_integer = |ty: ty::Ty<'tcx>| {
+            matches!(ty.kind(), ty::TyKind::Int(..) | ty::TyKind::Uint(..))
+        };
+        if !vars.args.iter().all(|(_, arg_ty)| is_integer(arg_ty)) || !is_integer(vars.result.1) {
+            return Err(SpannedEncodingError::unsupported(
+                "only returned closures with integer arguments and result can be specified",
+                span,
+            ));
+        }
+        let instantiation = find_single_closure_instantiation(self.encoder.env(), closure_def_id)
+            .ok_or_else(|| SpannedEncodingError::unsupported(
+                "the returned closure must be created exactly once by the procedure",
+                span,
+            ))?;
+        let pre_id = format!("{}_{}", vars.spec_id, vars.pre_id);
+        let post_id = format!("{}_{}", vars.spec_id, vars.post_id);
+        let qvars_pre: Vec<vir::LocalVar> = vars.args.iter()
+            .map(|(arg, arg_ty)| self.encode_forall_arg(*arg, arg_ty, &pre_id))
+            .collect();
+        let qvars_post: Vec<vir::LocalVar> = vars.args.iter()
+            .map(|(arg, arg_ty)| self.encode_forall_arg(*arg, arg_ty, &post_id))
+            .collect();
+        let encoded_pres = pres.iter()
+            .map(|pre| self.encode_assertion(pre))
+            .collect::<Result<Vec<vir::Expr>, _>>()?
+            .into_iter()
+            .conjoin();
+        let mut conjuncts = vec![];
+
+        // The `requires` clauses have to imply the precondition of the closure, if it has one.
+        if !self.encoder.encode_spec_funcs(closure_def_id)?.is_empty() {
+            let sf_pre_name = self.encoder.encode_spec_func_name(
+                closure_def_id,
+                SpecFunctionKind::Pre,
+            );
+            conjuncts.push(vir::Expr::forall(
+                qvars_pre.clone(),
+                vec![],
+                vir::Expr::implies(
+                    encoded_pres.clone(),
+                    vir::Expr::func_app(
+                        sf_pre_name,
+                        qvars_pre.iter().cloned().map(vir::Expr::local).collect(),
+                        (0 .. vars.args.len())
+                            .map(|i| vir::LocalVar::new(format!("_{}", i), vir::Type::Int))
+                            .collect(),
+                        vir::Type::Bool,
+                        vir::Position::default(),
+                    ),
+                ),
+            ));
+        }
+
+        // The value returned by the closure, with its arguments renamed to the quantified
+        // variables before translating its captured state to the definition site.
+        let closure_mir = self.encoder.env().local_mir(closure_def_id.expect_local());
+        let closure_mir_encoder = MirEncoder::new(self.encoder, &closure_mir, closure_def_id);
+        let mut body = self.encoder.encode_pure_function_body(closure_def_id)?;
+        for (local, qvar) in closure_mir.args_iter().skip(1).zip(qvars_post.iter()) {
+            let local_ty = closure_mir.local_decls[local].ty;
+            let encoded_local = closure_mir_encoder.encode_local(local).with_span(span)?;
+            let value_field = self.encoder.encode_value_field(local_ty);
+            body = body.replace_place(
+                &vir::Expr::local(encoded_local).field(value_field),
+                &qvar.clone().into(),
+            );
+        }
+        let (outer_body, outer_def_id, outer_location) =
+            self.translate_expr_to_instantiation_site(body, closure_def_id, instantiation)?;
+        let mut outer_body = self.translate_expr_to_state(
+            outer_body,
+            outer_def_id,
+            outer_location,
+            mir::START_BLOCK,
+        )?;
+
+        // Use the provided `target_args` for the arguments of the procedure.
+        let outer_mir = self.encoder.env().local_mir(outer_def_id.expect_local());
+        let outer_mir_encoder = MirEncoder::new(self.encoder, &outer_mir, outer_def_id);
+        for (local, target_arg) in outer_mir.args_iter().zip(self.target_args.iter()) {
+            let local_ty = outer_mir.local_decls[local].ty;
+            let encoded_local = outer_mir_encoder.encode_local(local).with_span(span)?;
+            let local_place: vir::Expr = if self.targets_are_values {
+                self.encoder.encode_value_expr(vir::Expr::local(encoded_local), local_ty)
+            } else {
+                encoded_local.into()
+            };
+            outer_body = outer_body.replace_place(&local_place, target_arg);
+        }
+
+        // The `ensures` clauses have to hold for the value returned by the closure.
+        let result_var = mir::Local::from_usize(vars.args.len() + 2);
+        let encoded_result = self.encode_forall_arg(result_var, vars.result.1, &post_id);
+        let encoded_posts = posts.iter()
+            .map(|post| self.encode_assertion(post))
+            .collect::<Result<Vec<vir::Expr>, _>>()?
+            .into_iter()
+            .conjoin()
+            .replace_place(&encoded_result.into(), &outer_body);
+        let post_pres = qvars_pre.iter()
+            .zip(qvars_post.iter())
+            .fold(encoded_pres, |pre, (qvar_pre, qvar_post)| {
+                pre.replace_place(&qvar_pre.clone().into(), &qvar_post.clone().into())
+            });
+        conjuncts.push(vir::Expr::forall(
+            qvars_post,
+            vec![],
+            vir::Expr::implies(post_pres, encoded_posts),
+        ));
+        Ok(conjuncts.into_iter().conjoin())
+    }
+
     /// Translate an expression `expr` from a closure identified by `def_id` to its definition site.
     ///
     /// During the translation:
@@ -395,21 +642,31 @@ impl<'p, 'v: 'p, 'tcx: 'v> SpecEncoder<'p, 'v, 'tcx> {
         inner_def_id: DefId,
     ) -> SpannedEncodingResult<(vir::Expr, DefId, mir::Location)> {
         debug!("translate_expr_to_closure_def_site {} {:?}", expr, inner_def_id);
+        let instantiation = self.encoder.get_single_closure_instantiation(
+            inner_def_id
+        ).expect(
+            &format!("cannot find definition site for closure {:?}", inner_def_id)
+        );
+        self.translate_expr_to_instantiation_site(expr, inner_def_id, instantiation)
+    }
+
+    /// Like `translate_expr_to_closure_def_site`, but for a given instantiation of the closure.
+    fn translate_expr_to_instantiation_site(
+        &self,
+        expr: vir::Expr,
+        inner_def_id: DefId,
+        instantiation: (DefId, mir::Location, Vec<mir::Operand<'tcx>>, Vec<ty::Ty<'tcx>>),
+    ) -> SpannedEncodingResult<(vir::Expr, DefId, mir::Location)> {
         let inner_mir = self.encoder.env().local_mir(inner_def_id.expect_local());
         let inner_mir_encoder = MirEncoder::new(self.encoder, &inner_mir, inner_def_id);
         let inner_attrs = self.encoder.env().tcx().get_attrs(inner_def_id);
 
-        let opt_instantiation = self.encoder.get_single_closure_instantiation(
-            inner_def_id
-        );
         let (
             outer_def_id,
             outer_location,
             captured_operands,
             captured_operand_tys,
-        ) = opt_instantiation.expect(
-            &format!("cannot find definition site for closure {:?}", inner_def_id)
-        );
+        ) = instantiation;
         let outer_mir = self.encoder.env().local_mir(outer_def_id.expect_local());
         let outer_mir_encoder = MirEncoder::new(self.encoder, &outer_mir, outer_def_id);
         let outer_span = outer_mir_encoder.get_span_of_location(outer_location);
@@ -498,6 +755,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> SpecEncoder<'p, 'v, 'tcx> {
                 let encoded_arg_value = match local_arg.ty.kind() {
                     ty::TyKind::Uint(_) |
                     ty::TyKind::Int(_) |
+                    ty::TyKind::Float(_) |
                     ty::TyKind::Bool |
                     ty::TyKind::Char => {
                         let value_field = self.encoder.encode_value_field(local_arg.ty);
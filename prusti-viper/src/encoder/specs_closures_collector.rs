@@ -57,40 +57,12 @@ impl<'tcx> SpecsClosuresCollector<'tcx> {
         } else {
             self.visited.insert(def_id);
         }
-        let tcx = env.tcx();
-        let mir = env.local_mir(def_id);
-        for (bb_index, bb_data) in mir.basic_blocks().iter_enumerated() {
-            for (stmt_index, stmt) in bb_data.statements.iter().enumerate() {
-                if let mir::StatementKind::Assign(
-                    box (
-                        _,
-                        mir::Rvalue::Aggregate(
-                            box mir::AggregateKind::Closure(cl_def_id, _),
-                            ref operands,
-                        ),
-                    )
-                ) = stmt.kind {
-                    // Skip closures that are not annotated with `prusti::spec_only`.
-                    if !env.has_prusti_attribute(cl_def_id, "spec_only") {
-                        continue;
-                    }
-                    trace!("Found closure instantiation at {:?}", stmt);
-                    let operand_tys = operands.iter().map(
-                        |operand| operand.ty(&*mir, tcx)
-                    ).collect();
-                    let instantiations =
-                        self.instantiations.entry(cl_def_id).or_insert(vec![]);
-                    instantiations.push((
-                        def_id.to_def_id(),
-                        mir::Location {
-                            block: bb_index,
-                            statement_index: stmt_index,
-                        },
-                        operands.clone(),
-                        operand_tys
-                    ));
-                }
+        for (cl_def_id, instantiation) in closure_instantiations(env, def_id) {
+            // Skip closures that are not annotated with `prusti::spec_only`.
+            if !env.has_prusti_attribute(cl_def_id, "spec_only") {
+                continue;
             }
+            self.instantiations.entry(cl_def_id).or_insert(vec![]).push(instantiation);
         }
     }
 
@@ -116,3 +88,62 @@ impl<'tcx> SpecsClosuresCollector<'tcx> {
         })
     }
 }
+
+/// Find the single instantiation of a closure, which does not need to be annotated with
+/// `prusti::spec_only`, in the procedure in which the closure is defined.
+pub fn find_single_closure_instantiation<'tcx>(env: &Environment<'tcx>, closure_def_id: DefId)
+    -> Option<(DefId, mir::Location, Vec<mir::Operand<'tcx>>, Vec<ty::Ty<'tcx>>)>
+{
+    let parent_def_id = env.tcx().parent(closure_def_id)?.as_local()?;
+    let mut instantiations: Vec<_> = closure_instantiations(env, parent_def_id)
+        .into_iter()
+        .filter(|(cl_def_id, _)| *cl_def_id == closure_def_id)
+        .map(|(_, instantiation)| instantiation)
+        .collect();
+    if instantiations.len() == 1 {
+        instantiations.pop()
+    } else {
+        None
+    }
+}
+
+/// Collect the instantiations of all closures in a given procedure.
+fn closure_instantiations<'tcx>(env: &Environment<'tcx>, def_id: LocalDefId) -> Vec<(
+    DefId,
+    (DefId, mir::Location, Vec<mir::Operand<'tcx>>, Vec<ty::Ty<'tcx>>),
+)> {
+    let tcx = env.tcx();
+    let mir = env.local_mir(def_id);
+    let mut instantiations = vec![];
+    for (bb_index, bb_data) in mir.basic_blocks().iter_enumerated() {
+        for (stmt_index, stmt) in bb_data.statements.iter().enumerate() {
+            if let mir::StatementKind::Assign(
+                box (
+                    _,
+                    mir::Rvalue::Aggregate(
+                        box mir::AggregateKind::Closure(cl_def_id, _),
+                        ref operands,
+                    ),
+                )
+            ) = stmt.kind {
+                trace!("Found closure instantiation at {:?}", stmt);
+                let operand_tys = operands.iter().map(
+                    |operand| operand.ty(&*mir, tcx)
+                ).collect();
+                instantiations.push((
+                    cl_def_id,
+                    (
+                        def_id.to_def_id(),
+                        mir::Location {
+                            block: bb_index,
+                            statement_index: stmt_index,
+                        },
+                        operands.clone(),
+                        operand_tys,
+                    ),
+                ));
+            }
+        }
+    }
+    instantiations
+}
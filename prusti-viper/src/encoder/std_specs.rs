@@ -0,0 +1,140 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The built-in specifications of the standard library. A `Vec<T>` is
+//! modelled by its snapshot, which is the sequence of the snapshots of its
+//! elements, and the contracts of its methods relate the sequence before and
+//! after the call.
+
+use crate::encoder::Encoder;
+use prusti_common::vir;
+
+/// The methods of `Vec` that have a built-in contract, besides `index`.
+pub const VEC_METHODS: &[&str] = &[
+    "new", "with_capacity", "len", "is_empty", "push", "pop", "insert", "remove",
+];
+
+/// The encoded arguments and result of a call of a method of `Vec`. The
+/// vectors and elements are given by their snapshots, and the arguments are
+/// evaluated in the state before the call.
+pub struct VecCall {
+    /// The vector before the call, except for the constructors.
+    pub old_vec: Option<vir::Expr>,
+    /// The vector after the call.
+    pub vec: vir::Expr,
+    /// The index argument of `index`, `insert` and `remove`.
+    pub index: Option<vir::Expr>,
+    /// The element argument of `push` and `insert`.
+    pub value: Option<vir::Expr>,
+    /// The result of `len`, `is_empty`, `index` and `remove`.
+    pub result: Option<vir::Expr>,
+    /// Whether the result of `pop` is `Some`, and its payload.
+    pub popped: Option<(vir::Expr, vir::Expr)>,
+}
+
+/// The contracts of the methods of `Vec<T>`, where `elem_type` is the type
+/// of the snapshots of `T`.
+pub struct VecSpec<'p, 'v: 'p, 'tcx: 'v> {
+    encoder: &'p Encoder<'v, 'tcx>,
+    elem_type: vir::Type,
+}
+
+impl<'p, 'v: 'p, 'tcx: 'v> VecSpec<'p, 'v, 'tcx> {
+    pub fn new(encoder: &'p Encoder<'v, 'tcx>, elem_type: vir::Type) -> Self {
+        VecSpec { encoder, elem_type }
+    }
+
+    fn len(&self, vec: vir::Expr) -> vir::Expr {
+        self.encoder.encode_seq_len(vec, &self.elem_type)
+    }
+
+    fn lookup(&self, vec: vir::Expr, index: vir::Expr) -> vir::Expr {
+        self.encoder.encode_seq_lookup(vec, index, &self.elem_type)
+    }
+
+    /// The precondition of `method` called on `vec`: the index has to be
+    /// within the bounds of the vector, or at its end for `insert`.
+    pub fn precondition(&self, method: &str, vec: vir::Expr, index: vir::Expr) -> Option<vir::Expr> {
+        let len = self.len(vec);
+        let upper_bound = match method {
+            "insert" => vir::Expr::le_cmp(index.clone(), len),
+            "index" | "remove" => vir::Expr::lt_cmp(index.clone(), len),
+            _ => return None,
+        };
+        Some(vir::Expr::and(vir::Expr::le_cmp(0.into(), index), upper_bound))
+    }
+
+    /// The elements of `call.vec` in `from..to` are the elements of
+    /// `call.old_vec` at the index shifted by `offset`.
+    fn shifted_elems(&self, call: &VecCall, from: vir::Expr, to: vir::Expr, offset: i64) -> vir::Expr {
+        let i_var = vir::LocalVar::new("i", vir::Type::Int);
+        let i: vir::Expr = i_var.clone().into();
+        let elem = self.lookup(call.vec.clone(), i.clone());
+        let old_index = if offset == 0 {
+            i.clone()
+        } else {
+            vir::Expr::add(i.clone(), offset.into())
+        };
+        let old_elem = self.lookup(call.old_vec.clone().unwrap(), old_index);
+        vir::Expr::forall(
+            vec![i_var],
+            vec![vir::Trigger::new(vec![elem.clone()])],
+            vir::Expr::implies(
+                vir::Expr::and(vir::Expr::le_cmp(from, i.clone()), vir::Expr::lt_cmp(i, to)),
+                vir::Expr::eq_cmp(elem, old_elem),
+            ),
+        )
+    }
+
+    /// The postcondition of `method`.
+    pub fn postcondition(&self, method: &str, call: &VecCall) -> Vec<vir::Expr> {
+        let len = self.len(call.vec.clone());
+        let old_len = || self.len(call.old_vec.clone().unwrap());
+        let index = || call.index.clone().unwrap();
+        let result = || call.result.clone().unwrap();
+        match method {
+            "new" | "with_capacity" => vec![vir::Expr::eq_cmp(len, 0.into())],
+            "len" => vec![vir::Expr::eq_cmp(result(), old_len())],
+            "is_empty" => vec![vir::Expr::eq_cmp(result(), vir::Expr::eq_cmp(old_len(), 0.into()))],
+            "index" => vec![vir::Expr::eq_cmp(result(), self.lookup(call.old_vec.clone().unwrap(), index()))],
+            "push" => vec![
+                vir::Expr::eq_cmp(len, vir::Expr::add(old_len(), 1.into())),
+                vir::Expr::eq_cmp(self.lookup(call.vec.clone(), old_len()), call.value.clone().unwrap()),
+                self.shifted_elems(call, 0.into(), old_len(), 0),
+            ],
+            "pop" => {
+                let (is_some, payload) = call.popped.clone().unwrap();
+                let was_empty = vir::Expr::eq_cmp(old_len(), 0.into());
+                let last = vir::Expr::sub(old_len(), 1.into());
+                vec![
+                    vir::Expr::eq_cmp(is_some, vir::Expr::not(was_empty.clone())),
+                    vir::Expr::implies(was_empty.clone(), vir::Expr::eq_cmp(len.clone(), 0.into())),
+                    vir::Expr::implies(
+                        vir::Expr::not(was_empty),
+                        vir::Expr::and(
+                            vir::Expr::eq_cmp(len.clone(), last.clone()),
+                            vir::Expr::eq_cmp(payload, self.lookup(call.old_vec.clone().unwrap(), last)),
+                        ),
+                    ),
+                    self.shifted_elems(call, 0.into(), len, 0),
+                ]
+            }
+            "insert" => vec![
+                vir::Expr::eq_cmp(len.clone(), vir::Expr::add(old_len(), 1.into())),
+                vir::Expr::eq_cmp(self.lookup(call.vec.clone(), index()), call.value.clone().unwrap()),
+                self.shifted_elems(call, 0.into(), index(), 0),
+                self.shifted_elems(call, vir::Expr::add(index(), 1.into()), len, -1),
+            ],
+            "remove" => vec![
+                vir::Expr::eq_cmp(len.clone(), vir::Expr::sub(old_len(), 1.into())),
+                vir::Expr::eq_cmp(result(), self.lookup(call.old_vec.clone().unwrap(), index())),
+                self.shifted_elems(call, 0.into(), index(), 0),
+                self.shifted_elems(call, index(), len, 1),
+            ],
+            _ => unreachable!("{} is not a modelled method of Vec", method),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Helpers for checking the termination measures given by `#[decreases]`
+//! annotations and by `body_variant!`. A measure `m` is encoded as the
+//! assertion `_measure == m`, and must be non-negative and strictly smaller
+//! than the measure of the caller at each recursive call, and than the
+//! measure at the start of the iteration at each back edge of a loop.
+
+use crate::encoder::errors::{EncodingError, EncodingResult};
+use crate::encoder::mir_encoder::MEASURE_VAR_NAME;
+use prusti_common::vir;
+use prusti_common::vir::{ExprFolder, default_fold_expr};
+
+/// The measure `m` of an encoded assertion `_measure == m`.
+pub fn extract_measure(assertion: vir::Expr) -> EncodingResult<vir::Expr> {
+    match assertion {
+        vir::Expr::BinOp(vir::BinOpKind::EqCmp, box vir::Expr::Local(var, _), box measure, _)
+            if var.name == MEASURE_VAR_NAME => Ok(measure),
+        _ => Err(EncodingError::unsupported(
+            "the termination measure must be an integer expression",
+        )),
+    }
+}
+
+/// The condition under which `measure` is smaller than `bound` in the
+/// well-founded order of the termination measures.
+pub fn measure_decreases(measure: vir::Expr, bound: vir::Expr) -> vir::Expr {
+    vir::Expr::and(
+        vir::Expr::le_cmp(0.into(), measure.clone()),
+        vir::Expr::lt_cmp(measure, bound),
+    )
+}
+
+/// Instantiate the termination measure of a function, given over its formal
+/// arguments, with the arguments of a call.
+pub fn instantiate_measure(
+    measure: vir::Expr,
+    formal_args: &[vir::LocalVar],
+    args: &[vir::Expr],
+) -> vir::Expr {
+    struct ArgReplacer<'a> {
+        formal_args: &'a [vir::LocalVar],
+        args: &'a [vir::Expr],
+    }
+    impl<'a> ExprFolder for ArgReplacer<'a> {
+        fn fold_local(&mut self, var: vir::LocalVar, pos: vir::Position) -> vir::Expr {
+            match self.formal_args.iter().position(|formal_arg| formal_arg == &var) {
+                Some(index) => self.args[index].clone(),
+                None => vir::Expr::Local(var, pos),
+            }
+        }
+    }
+    debug_assert_eq!(formal_args.len(), args.len());
+    ArgReplacer { formal_args, args }.fold(measure)
+}
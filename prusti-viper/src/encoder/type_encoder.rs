@@ -13,6 +13,8 @@ use prusti_common::config;
 // use prusti_interface::specifications::*;
 // use rustc::middle::const_val::ConstVal;
 use rustc_middle::ty;
+use rustc_middle::ty::TypeFoldable;
+use rustc_span::DUMMY_SP;
 use rustc_target::abi;
 use rustc_middle::ty::layout::IntegerExt;
 // use rustc_data_structures::indexed_vec::Idx;
@@ -34,6 +36,8 @@ pub struct TypeEncoder<'p, 'v: 'p, 'tcx: 'v> {
 
 impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
     pub fn new(encoder: &'p Encoder<'v, 'tcx>, ty: ty::Ty<'tcx>) -> Self {
+        let ty = encoder.reveal_opaque_type(ty);
+        let ty = encoder.as_slice_type(encoder.devirtualize_type(ty));
         TypeEncoder { encoder, ty }
     }
 
@@ -43,6 +47,7 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
             ty::TyKind::Bool
             | ty::TyKind::Int(_)
             | ty::TyKind::Uint(_)
+            | ty::TyKind::Float(_)
             | ty::TyKind::Char
             | ty::TyKind::Ref(_, _, _)
             | ty::TyKind::Adt(_, _)
@@ -111,6 +116,8 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                 vir::Type::Int
             }
 
+            ty::TyKind::Float(_) => self.encoder.encode_float_type(),
+
             ty::TyKind::Ref(_, ref ty, _) => {
                 let type_name = self.encoder.encode_type_predicate_use(ty)?;
                 vir::Type::TypedRef(type_name)
@@ -127,6 +134,12 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                 ));
             }
 
+            ty::TyKind::Slice(_) | ty::TyKind::Str => {
+                return Err(EncodingError::unsupported(
+                    "slices and strings are only supported behind references"
+                ));
+            }
+
             ref x => unimplemented!("{:?}", x),
         })
     }
@@ -144,6 +157,11 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                 Ok(vir::Type::TypedRef(type_name))
             },
 
+            ty::TyKind::Opaque(_, _) => {
+                let type_name = self.encoder.encode_type_predicate_use(self.ty)?;
+                Ok(vir::Type::TypedRef(type_name))
+            }
+
             _ => self.encode_value_type(),
         }
     }
@@ -157,6 +175,10 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                 vir::Field::new("val_int", vir::Type::Int)
             }
 
+            ty::TyKind::Float(_) => {
+                vir::Field::new("val_float", self.encoder.encode_float_type())
+            }
+
             ty::TyKind::Ref(_, ref ty, _) => {
                 let type_name = self.encoder.encode_type_predicate_use(ty)?;
                 vir::Field::new("val_ref", vir::Type::TypedRef(type_name))
@@ -167,7 +189,8 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
             ty::TyKind::Adt(_, _)
             | ty::TyKind::Tuple(_)
             | ty::TyKind::Closure(_, _)
-            | ty::TyKind::FnDef(_, _) => {
+            | ty::TyKind::FnDef(_, _)
+            | ty::TyKind::Opaque(_, _) => {
                 let type_name = self.encoder.encode_type_predicate_use(self.ty)?;
                 vir::Field::new("val_ref", vir::Type::TypedRef(type_name))
             }
@@ -209,11 +232,38 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
             ty::TyKind::Char => {
                 Some((0.into(), std::char::MAX.into()))
             }
-            ty::TyKind::Bool | ty::TyKind::Ref(_, _, _) => None,
+            ty::TyKind::Bool | ty::TyKind::Float(_) | ty::TyKind::Ref(_, _, _) => None,
             ref x => unreachable!("{:?}", x),
         }
     }
 
+    /// The number of elements of an array type.
+    pub fn get_array_len(&self) -> Option<u64> {
+        let size = match self.ty.kind() {
+            ty::TyKind::Array(_, size) => size,
+            ref x => unreachable!("{:?}", x),
+        };
+        let scalar_size = match size.val {
+            ty::ConstKind::Value(ref value) => {
+                value.try_to_bits(
+                    rustc_target::abi::Size::from_bits(64)
+                )
+            },
+            ty::ConstKind::Unevaluated(def, ref substs, promoted) => {
+                let tcx = self.encoder.env().tcx();
+                let param_env = tcx.param_env(def.did);
+                tcx.const_eval_resolve(param_env, def, substs, promoted, None)
+                    .ok()
+                    .and_then(|const_value| const_value.try_to_bits(
+                        rustc_target::abi::Size::from_bits(64)
+                    ))
+            }
+            // The length depends on const generic parameters.
+            _ => None,
+        };
+        scalar_size.map(|size| size as u64)
+    }
+
     pub fn encode_bounds(self, var: &vir::Expr) -> Vec<vir::Expr> {
         if let Some((lower, upper)) = self.get_integer_bounds() {
             vec![
@@ -231,7 +281,7 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
         let typ = vir::Type::TypedRef(predicate_name.clone());
 
         Ok(match self.ty.kind() {
-            ty::TyKind::Bool => vec![vir::Predicate::new_primitive_value(
+            ty::TyKind::Bool | ty::TyKind::Float(_) => vec![vir::Predicate::new_primitive_value(
                 typ,
                 self.encoder.encode_value_field(self.ty),
                 None,
@@ -239,7 +289,7 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
             )],
 
             ty::TyKind::Int(_) | ty::TyKind::Uint(_) | ty::TyKind::Char => {
-                let bounds = if config::check_overflows() {
+                let bounds = if config::integer_bounds() {
                     self.get_integer_bounds()
                 } else {
                     None
@@ -276,6 +326,39 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                 vec![vir::Predicate::new_struct(typ, fields)]
             }
 
+            // The elements of an array are read like the ones of a slice,
+            // with the length and lookup functions of its type.
+            ty::TyKind::Array(_, _) => {
+                vec![vir::Predicate::new_abstract(typ)]
+            }
+
+            // The content of a cell is not tracked, see `Encoder::is_cell_type`.
+            ty::TyKind::Adt(_, _) if self.encoder.is_cell_type(self.ty) => {
+                vec![vir::Predicate::new_abstract(typ)]
+            }
+
+            // The content of a reference-counted pointer can only be read, so
+            // sharing it is indistinguishable from copying it: each pointer
+            // owns its content, like a box, and cloning a pointer duplicates
+            // the content. Interior mutability would break this model.
+            ty::TyKind::Adt(_, _) if self.encoder.get_rc_pointee_ty(self.ty).is_some() => {
+                let pointee_ty = self.encoder.get_rc_pointee_ty(self.ty).unwrap();
+                let tcx = self.encoder.env().tcx();
+                if !pointee_ty.needs_subst()
+                    && !pointee_ty.is_freeze(tcx.at(DUMMY_SP), ty::ParamEnv::reveal_all())
+                {
+                    return Err(EncodingError::unsupported(format!(
+                        "reference-counted pointers to values with interior mutability \
+                        (such as '{:?}') are not supported",
+                        pointee_ty,
+                    )));
+                }
+                vec![vir::Predicate::new_struct(
+                    typ,
+                    vec![self.encoder.encode_dereference_field(pointee_ty)?],
+                )]
+            }
+
             ty::TyKind::Adt(adt_def, subst) if !adt_def.is_box() => {
                 let num_variants = adt_def.variants.len();
                 let tcx = self.encoder.env().tcx();
@@ -393,6 +476,23 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                 }
             }
 
+            ty::TyKind::FnPtr(..) => {
+                // The value of a function pointer is not encoded.
+                vec![vir::Predicate::new_struct(typ, vec![])]
+            }
+
+            ty::TyKind::Dynamic(..) => {
+                // A trait object is known only through the contracts of the
+                // methods of its trait.
+                vec![vir::Predicate::new_abstract(typ)]
+            }
+
+            ty::TyKind::Opaque(..) => {
+                // An opaque type whose hidden type cannot be encoded is known
+                // only through the contracts of the traits that bound it.
+                vec![vir::Predicate::new_abstract(typ)]
+            }
+
             ref ty_variant => {
                 debug!("Encoding of type '{:?}' is incomplete", ty_variant);
                 vec![vir::Predicate::new_abstract(typ)]
@@ -412,16 +512,42 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                 // makes generics "less fragile"
                 composed_name.push("_sep_".to_string());
             }
-            if let ty::subst::GenericArgKind::Type(ty) = kind.unpack() {
-                composed_name.push(
-                    self.encoder.encode_type_predicate_use(ty)?
-                )
+            match kind.unpack() {
+                ty::subst::GenericArgKind::Type(ty) => {
+                    composed_name.push(
+                        self.encoder.encode_type_predicate_use(ty)?
+                    )
+                }
+                ty::subst::GenericArgKind::Const(value) => {
+                    composed_name.push(self.encode_const_arg(value))
+                }
+                ty::subst::GenericArgKind::Lifetime(_) => {}
             }
         }
         composed_name.push("_end_".to_string()); // makes generics "less fragile"
         Ok(composed_name.join("$"))
     }
 
+    /// The string that identifies the const generic argument `value`, like
+    /// the length of an array, in the name of a predicate. A const generic
+    /// parameter is named like a type parameter.
+    fn encode_const_arg(&self, value: &ty::Const<'tcx>) -> String {
+        if let ty::ConstKind::Param(param) = value.val {
+            return format!("__CONSTPARAM__${}$__", param.name.as_str());
+        }
+        let tcx = self.encoder.env().tcx();
+        match value.try_eval_bits(tcx, ty::ParamEnv::reveal_all(), value.ty) {
+            Some(bits) => bits.to_string(),
+            None => {
+                // The hash of an interned constant depends on its address, so
+                // the constant is identified by its printed form instead.
+                let mut s = DefaultHasher::new();
+                format!("{:?}", value).hash(&mut s);
+                format!("sym${}", s.finish())
+            }
+        }
+    }
+
     pub fn encode_predicate_use(self) -> EncodingResult<String> {
         debug!("Encode type predicate name '{:?}'", self.ty);
 
@@ -444,6 +570,9 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
 
             ty::TyKind::Char => "char".to_string(),
 
+            ty::TyKind::Float(ast::FloatTy::F32) => "f32".to_string(),
+            ty::TyKind::Float(ast::FloatTy::F64) => "f64".to_string(),
+
             ty::TyKind::RawPtr(ty::TypeAndMut { ref ty, .. }) => {
                 format!("raw_ref${}", self.encoder.encode_type_predicate_use(ty)?)
             }
@@ -474,31 +603,22 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
 
             ty::TyKind::Never => "never".to_string(),
 
-            ty::TyKind::Str => "str".to_string(),
+            // A string slice is encoded as the slice of its UTF-8 bytes, so
+            // that `str::as_bytes` does not change the encoded value.
+            ty::TyKind::Str => {
+                let tcx = self.encoder.env().tcx();
+                self.encoder.encode_type_predicate_use(tcx.mk_slice(tcx.types.u8))?
+            }
 
             ty::TyKind::Array(elem_ty, size) => {
-                let scalar_size = match size.val {
-                    ty::ConstKind::Value(ref value) => {
-                        value.try_to_bits(
-                            rustc_target::abi::Size::from_bits(64)
-                        ).unwrap()
-                    },
-                    ty::ConstKind::Unevaluated(def, ref substs, promoted) => {
-                        let tcx = self.encoder.env().tcx();
-                        let param_env = tcx.param_env(def.did);
-                        tcx.const_eval_resolve(param_env, def, substs, promoted, None)
-                            .ok()
-                            .and_then(|const_value| const_value.try_to_bits(
-                                rustc_target::abi::Size::from_bits(64)
-                            ))
-                            .unwrap()
-                    }
-                    x => unimplemented!("{:?}", x),
+                let len = match self.get_array_len() {
+                    Some(len) => len.to_string(),
+                    None => self.encode_const_arg(size),
                 };
                 format!(
                     "array${}${}",
                     self.encoder.encode_type_predicate_use(elem_ty)?,
-                    scalar_size
+                    len
                 )
             }
 
@@ -536,8 +656,21 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                 composed_name.join("$")
             }
 
-            ty::TyKind::Dynamic(..) => {
-                "unsupported$dynamic".to_string()
+            ty::TyKind::Dynamic(predicates, _) => {
+                match predicates.principal_def_id() {
+                    Some(trait_def_id) => {
+                        format!("dyn${}", self.encoder.encode_item_name(trait_def_id))
+                    }
+                    None => "dyn$".to_string(),
+                }
+            }
+
+            ty::TyKind::Opaque(def_id, substs) => {
+                format!(
+                    "opaque${}${}",
+                    self.encoder.encode_item_name(*def_id),
+                    self.encode_substs(substs)?,
+                )
             }
 
             ty::TyKind::FnPtr(..) => {
@@ -577,7 +710,11 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                 ])
             }
 
-            ty::TyKind::Adt(ref adt_def, ref subst) if !adt_def.is_box() => {
+            ty::TyKind::Adt(ref adt_def, ref subst)
+                if !adt_def.is_box()
+                    && self.encoder.get_rc_pointee_ty(self.ty).is_none()
+                    && !self.encoder.is_cell_type(self.ty) =>
+            {
                 if self.is_supported_struct_type(adt_def, subst) {
                     let own_substs =
                         ty::List::identity_for_item(self.encoder.env().tcx(), adt_def.did);
@@ -683,6 +820,23 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                         // TODO: https://gitlab.inf.ethz.ch/OU-PMUELLER/prusti-dev/issues/201
                     }
 
+                    // The invariants written by the user with `#[invariant]`
+                    for invariant_def_id in self.encoder.get_type_invariants(adt_def.did) {
+                        let (function_name, return_type) =
+                            self.encoder.encode_pure_function_use(invariant_def_id)?;
+                        let mut args = vec![self_local_var.clone().into()];
+                        args.extend(self.encoder.encode_const_param_args(invariant_def_id)?);
+                        let mut formal_args = vec![vir::LocalVar::new("_1", self_local_var.typ.clone())];
+                        formal_args.extend(self.encoder.encode_const_param_formal_args(invariant_def_id)?);
+                        exprs.push(vir::Expr::func_app(
+                            function_name,
+                            args,
+                            formal_args,
+                            return_type,
+                            vir::Position::default(),
+                        ));
+                    }
+
                     Some(exprs)
                 } else {
                     // TODO: https://gitlab.inf.ethz.ch/OU-PMUELLER/prusti-dev/issues/201
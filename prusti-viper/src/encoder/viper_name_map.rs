@@ -0,0 +1,48 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The map from the identifiers of a dumped Viper program to the Rust items
+//! and types that they encode, dumped next to the program with
+//! `DUMP_VIPER_PROGRAM`.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// The kind of the Viper declaration that an identifier names.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ViperDeclarationKind {
+    Method,
+    Function,
+    Predicate,
+    Domain,
+}
+
+/// The Rust origin of a Viper identifier.
+#[derive(Serialize, Clone, Debug)]
+pub struct ViperNameOrigin {
+    pub kind: ViperDeclarationKind,
+    /// The path of the Rust item, or the Rust type of a predicate or domain.
+    pub rust: String,
+    /// The location of the Rust item in the source code, if any.
+    pub span: Option<String>,
+}
+
+/// The origins of the identifiers of a Viper program, sorted by identifier.
+#[derive(Serialize, Default, Debug)]
+pub struct ViperNameMap {
+    names: BTreeMap<String, ViperNameOrigin>,
+}
+
+impl ViperNameMap {
+    pub fn insert(&mut self, viper_name: String, origin: ViperNameOrigin) {
+        self.names.insert(viper_name, origin);
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
@@ -23,9 +23,13 @@ extern crate rustc_index;
 extern crate rustc_ast;
 extern crate rustc_target;
 extern crate rustc_attr;
+extern crate rustc_errors;
 #[macro_use]
 extern crate lazy_static;
 
+mod baseline;
 pub mod encoder;
 mod utils;
+mod verification_cache;
 pub mod verifier;
+pub mod vir_passes;
@@ -0,0 +1,148 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The persistent cache of the procedures that were verified successfully,
+//! stored in the directory given by `config::cache_path()`. A procedure is
+//! identified by the hash of its Viper method, of the declarations shared by
+//! the methods of the program (which contain the encoded specifications), of
+//! the imported Viper sources, of the backend (its name, arguments and
+//! installed version, see `ViperBackendConfig::cache_key`) and of the
+//! installed Prusti. Failures are never cached, because their diagnostics
+//! depend on the whole program.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use prusti_common::config;
+use prusti_common::verification_service::{CacheStats, FileFingerprint, ViperBackendConfig};
+use prusti_common::vir;
+
+#[derive(Default, Deserialize, Serialize)]
+struct CacheFile {
+    /// The key of the backend that verified the procedures.
+    #[serde(default)]
+    backend: u64,
+    verified: Vec<u64>,
+}
+
+#[derive(Default)]
+pub struct VerificationCache {
+    /// The file of the cache, or `None` if the cache is disabled.
+    path: Option<PathBuf>,
+    /// The key of the backend of this run.
+    backend: u64,
+    /// The keys of the procedures that were verified by an earlier run.
+    verified: HashSet<u64>,
+    /// The keys of the procedures of this run that are verified.
+    updated: HashSet<u64>,
+    stats: CacheStats,
+}
+
+impl VerificationCache {
+    /// Load the cache of the given crate. A missing or unreadable cache file
+    /// is an empty cache, and so is a cache of another backend.
+    pub fn load(crate_name: &str, backend_config: &ViperBackendConfig) -> Self {
+        let cache_path = config::cache_path();
+        if cache_path.is_empty() {
+            return VerificationCache::default();
+        }
+        let backend = backend_config.cache_key();
+        let path = Path::new(&cache_path).join(format!("{}.json", crate_name));
+        let file: CacheFile = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        let mut stats = CacheStats::default();
+        let verified = if file.backend == backend {
+            file.verified.into_iter().collect()
+        } else {
+            stats.invalidated = file.verified.len();
+            HashSet::new()
+        };
+        VerificationCache {
+            path: Some(path),
+            backend,
+            verified,
+            updated: HashSet::new(),
+            stats,
+        }
+    }
+
+    /// Whether the procedure with the given key was verified by an earlier
+    /// run. With `FORCE_VERIFY`, no procedure is considered verified.
+    pub fn is_verified(&mut self, key: u64) -> bool {
+        let is_verified = !config::force_verify() && self.verified.contains(&key);
+        if is_verified {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+        }
+        is_verified
+    }
+
+    /// Record that the procedure with the given key is verified.
+    pub fn set_verified(&mut self, key: u64) {
+        self.updated.insert(key);
+    }
+
+    /// The lookups of this run.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Store the procedures that are verified by this run, which replace the
+    /// ones of the earlier runs.
+    pub fn save(&self) -> Result<(), String> {
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return Ok(()),
+        };
+        let mut verified: Vec<_> = self.updated.iter().cloned().collect();
+        verified.sort_unstable();
+        let content = serde_json::to_string(&CacheFile { backend: self.backend, verified })
+            .map_err(|error| error.to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|error| {
+                format!("could not create the cache directory '{}': {}", dir.display(), error)
+            })?;
+        }
+        fs::write(path, content).map_err(|error| {
+            format!("could not write the cache file '{}': {}", path.display(), error)
+        })
+    }
+}
+
+/// The hash of everything, except the methods, that the verification of a
+/// method of `program` depends on.
+pub fn hash_program_context(program: &vir::Program, backend_config: &ViperBackendConfig) -> u64 {
+    // The declarations are sorted, because their order is not deterministic.
+    let mut declarations: Vec<String> = program.domains.iter().map(|d| d.to_string())
+        .chain(program.fields.iter().map(|f| f.to_string()))
+        .chain(program.builtin_methods.iter().map(|m| m.to_string()))
+        .chain(program.functions.iter().map(|f| f.to_string()))
+        .chain(program.viper_predicates.iter().map(|p| p.to_string()))
+        .chain(program.viper_imports.iter().cloned())
+        .collect();
+    declarations.sort_unstable();
+    let mut hasher = DefaultHasher::new();
+    declarations.hash(&mut hasher);
+    backend_config.cache_key().hash(&mut hasher);
+    // A new version of Prusti may encode the same method differently.
+    env::current_exe().ok().map(|exe| FileFingerprint::of(&exe)).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The key of `method`, given the hash of the context of its program.
+pub fn procedure_key(program_context_hash: u64, method: &vir::CfgMethod) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    program_context_hash.hash(&mut hasher);
+    method.to_string().hash(&mut hasher);
+    hasher.finish()
+}
@@ -6,24 +6,38 @@
 
 use prusti_common::vir::{self, optimizations, ToViper, ToViperDecl};
 use prusti_common::{
-    config, report::log, verification_context::VerifierBuilder, verification_service::*, Stopwatch,
+    config,
+    report::{log, user},
+    verification_context::VerifierBuilder,
+    verification_service::*,
+    Stopwatch,
 };
 use crate::encoder::Encoder;
 // use prusti_filter::validators::Validator;
 use prusti_interface::data::VerificationResult;
 use prusti_interface::data::VerificationTask;
-use prusti_interface::environment::Environment;
+use prusti_interface::data::{ProcedureDefId, ProcedureReport, ProcedureStatus};
+use crate::baseline::Baseline;
+use crate::verification_cache::{self, VerificationCache};
+use crate::vir_passes;
+use prusti_interface::environment::{check_ghost_code, check_purity, CallGraph, Environment};
 use prusti_interface::PrustiError;
 // use prusti_interface::specifications::TypedSpecificationMap;
-use std::time::Instant;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 use viper::{self, VerificationBackend, Viper};
 use std::path::PathBuf;
 use std::fs::{create_dir_all, canonicalize};
 use std::ffi::OsString;
 use prusti_interface::specs::typed;
 use ::log::{info, debug, error};
-use prusti_server::{PrustiServerConnection, ServerSideService, VerifierRunner};
+use prusti_server::{
+    merge_verification_results, verify_in_parallel, PrustiServerConnection, ServerSideService,
+    VerifierRunner,
+};
+use std::sync::Arc;
 use rustc_span::DUMMY_SP;
+use rustc_errors::Applicability;
 
 // /// A verifier builder is an object that lives entire program's
 // /// lifetime, has no mutable state, and is responsible for constructing
@@ -139,6 +153,8 @@ where
 {
     env: &'v Environment<'tcx>,
     encoder: Encoder<'v, 'tcx>,
+    procedure_reports: Vec<ProcedureReport>,
+    cache_stats: CacheStats,
 }
 
 impl<'v, 'tcx> Verifier<'v, 'tcx> {
@@ -149,7 +165,182 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
         Verifier {
             env,
             encoder: Encoder::new(env, def_spec),
+            procedure_reports: vec![],
+            cache_stats: CacheStats::default(),
+        }
+    }
+
+    /// The per-procedure outcome of the last call to `verify`.
+    pub fn procedure_reports(&self) -> &[ProcedureReport] {
+        &self.procedure_reports
+    }
+
+    /// The lookups in the persistent cache of the last call to `verify`.
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache_stats
+    }
+
+    /// Mark the procedures that contain the span of the error as failed.
+    /// Returns the procedures to which the error has been attributed.
+    fn attribute_error(&mut self, error: &PrustiError) -> Vec<ProcedureDefId> {
+        let error_span = match error.span().primary_span() {
+            Some(span) => span,
+            None => return vec![],
+        };
+        let mut procedures = vec![];
+        for report in &mut self.procedure_reports {
+            if report.span.contains(error_span) {
+                report.status = ProcedureStatus::Failed;
+                procedures.push(report.def_id);
+            }
+        }
+        procedures
+    }
+
+    /// Emit an error. Returns whether it was emitted as an error, and not as
+    /// a warning.
+    fn emit_error(&self, error: PrustiError) -> bool {
+        let is_error = error.is_error();
+        error.emit(self.env);
+        is_error
+    }
+
+    /// Mark all procedures that were not already classified as failed.
+    fn fail_all_procedures(&mut self) {
+        for report in &mut self.procedure_reports {
+            if report.status == ProcedureStatus::Verified {
+                report.status = ProcedureStatus::Failed;
+            }
+        }
+    }
+
+    /// Report the recursive pure functions that lack a `#[decreases]`
+    /// annotation, and return them. Their encoding might be unsound or might
+    /// not terminate, so they are not encoded. A recursive lemma is a proof
+    /// by induction, which is only sound if its termination is checked, so
+    /// lemmas are rejected even if they are only self-recursive.
+    fn check_pure_recursion(&mut self, call_graph: &CallGraph) -> HashSet<ProcedureDefId> {
+        let mut rejected = HashSet::new();
+        let check_termination = config::checked_obligations().termination;
+        let components = call_graph.recursive_components(|def_id| {
+            self.encoder.is_pure(def_id) && !self.encoder.is_trusted(def_id)
+        });
+        for component in components {
+            for &proc_id in &component {
+                let is_lemma = self.encoder.is_lemma(proc_id);
+                let is_exempt = !check_termination
+                    || component.len() == 1 && !config::require_decreases();
+                if !is_lemma && is_exempt {
+                    continue;
+                }
+                let has_decreases = self.env.has_prusti_attribute(proc_id, "decreases");
+                if has_decreases && check_termination {
+                    continue;
+                }
+                let proc_span = self.env.get_item_span(proc_id);
+                let error = if has_decreases {
+                    PrustiError::incorrect(
+                        "recursive lemma requires its termination to be checked",
+                        proc_span.into(),
+                    ).set_help(
+                        "Include `termination` in CHECKED_OBLIGATIONS, otherwise the \
+                        #[decreases] annotation of the lemma is not checked."
+                    )
+                } else {
+                    let others: Vec<_> = component.iter()
+                        .filter(|&&other| other != proc_id)
+                        .map(|&other| format!("'{}'", self.env.get_absolute_item_name(other)))
+                        .collect();
+                    let message = if !others.is_empty() {
+                        format!(
+                            "pure function is mutually recursive with {} and requires a \
+                            #[decreases] annotation",
+                            others.join(", "),
+                        )
+                    } else if is_lemma {
+                        "recursive lemma requires a #[decreases] annotation".to_string()
+                    } else {
+                        "recursive pure function requires a #[decreases] annotation".to_string()
+                    };
+                    PrustiError::incorrect(message, proc_span.into())
+                        .set_help(
+                            "Add #[decreases(...)] with a termination measure that decreases \
+                            at each recursive call."
+                        )
+                        .set_suggestion(
+                            "add a termination measure",
+                            proc_span.shrink_to_lo(),
+                            "#[decreases(...)] ",
+                            Applicability::HasPlaceholders,
+                        )
+                };
+                error.set_method(self.env.get_absolute_item_name(proc_id)).emit(self.env);
+                rejected.insert(proc_id);
+                self.procedure_reports.push(ProcedureReport {
+                    def_id: proc_id,
+                    span: proc_span,
+                    status: ProcedureStatus::Failed,
+                    encoding_time: Duration::default(),
+                    verification_time: Duration::default(),
+                });
+            }
+        }
+        rejected
+    }
+
+    /// Check the bodies of the pure functions of the task before encoding
+    /// them, rejecting those that contain impure constructs.
+    fn check_pure_bodies(&mut self, task: &VerificationTask) -> HashSet<ProcedureDefId> {
+        let mut rejected = HashSet::new();
+        for &proc_id in &task.procedures {
+            if !self.encoder.is_pure(proc_id) || self.encoder.is_trusted(proc_id) {
+                continue;
+            }
+            let errors = check_purity(self.env, proc_id, |def_id| self.encoder.is_pure(def_id));
+            if errors.is_empty() {
+                continue;
+            }
+            for error in errors {
+                error.emit(self.env);
+            }
+            rejected.insert(proc_id);
+            self.procedure_reports.push(ProcedureReport {
+                def_id: proc_id,
+                span: self.env.get_item_span(proc_id),
+                status: ProcedureStatus::Failed,
+                encoding_time: Duration::default(),
+                verification_time: Duration::default(),
+            });
         }
+        rejected
+    }
+
+    /// Check the ghost code of the impure procedures of the task before
+    /// encoding them, rejecting those whose ghost code modifies the rest of
+    /// the program.
+    fn check_ghost_bodies(&mut self, task: &VerificationTask) -> HashSet<ProcedureDefId> {
+        let mut rejected = HashSet::new();
+        for &proc_id in &task.procedures {
+            if self.encoder.is_pure(proc_id) || self.encoder.is_trusted(proc_id) {
+                continue;
+            }
+            let errors = check_ghost_code(self.env, proc_id, |def_id| self.encoder.is_pure(def_id));
+            if errors.is_empty() {
+                continue;
+            }
+            for error in errors {
+                error.emit(self.env);
+            }
+            rejected.insert(proc_id);
+            self.procedure_reports.push(ProcedureReport {
+                def_id: proc_id,
+                span: self.env.get_item_span(proc_id),
+                status: ProcedureStatus::Failed,
+                encoding_time: Duration::default(),
+                verification_time: Duration::default(),
+            });
+        }
+        rejected
     }
 
     pub fn verify(&mut self, task: &VerificationTask) -> VerificationResult {
@@ -158,6 +349,11 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
             task.procedures.len()
         );
 
+        let baseline = Baseline::load(&self.env.crate_name()).unwrap_or_else(|message| {
+            PrustiError::incorrect(message, DUMMY_SP.into()).emit(self.env);
+            Baseline::default()
+        });
+
         let mut stopwatch = Stopwatch::start("prusti-viper", "encoding to Viper");
 
         // Dump the configuration
@@ -235,14 +431,63 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
         //     skipped_functions_count,
         // );
 
-        for &proc_id in task.procedures.iter().rev() {
-            // FIXME: Use the loop above.
+        // Encode callees before their callers, and skip the callers of
+        // procedures whose encoding failed: their errors would only be a
+        // confusing consequence of the failure of the callee.
+        let call_graph = CallGraph::new(self.env, &task.procedures);
+        let mut failed_procedures = self.check_pure_bodies(task);
+        failed_procedures.extend(self.check_ghost_bodies(task));
+        failed_procedures.extend(self.check_pure_recursion(&call_graph));
+        if config::checked_obligations().termination {
+            // The termination measures are checked at the calls between the
+            // procedures with a `#[decreases]` annotation of a recursive group.
+            let components = call_graph.recursive_components(|def_id| {
+                !self.encoder.is_trusted(def_id) && self.env.has_prusti_attribute(def_id, "decreases")
+            });
+            self.encoder.register_termination_components(components);
+        }
+        let has_early_errors = !failed_procedures.is_empty();
+        for &proc_id in call_graph.topological_order() {
+            if failed_procedures.contains(&proc_id) {
+                continue;
+            }
+            let failed_callee = call_graph.callees(proc_id)
+                .find(|callee| failed_procedures.contains(callee));
+            if let Some(callee) = failed_callee {
+                let proc_span = self.env.get_item_span(proc_id);
+                PrustiError::warning(
+                    format!(
+                        "verification of this procedure is skipped, because the encoding of the \
+                        called procedure '{}' failed",
+                        self.env.get_absolute_item_name(callee),
+                    ),
+                    proc_span.into(),
+                ).emit(self.env);
+                failed_procedures.insert(proc_id);
+                self.procedure_reports.push(ProcedureReport {
+                    def_id: proc_id,
+                    span: proc_span,
+                    status: ProcedureStatus::DependencyFailed,
+                    encoding_time: Duration::default(),
+                    verification_time: Duration::default(),
+                });
+                continue;
+            }
             self.encoder.queue_procedure_encoding(proc_id);
+            self.encoder.process_encoding_queue();
+            for report in self.encoder.take_procedure_reports() {
+                if report.status == ProcedureStatus::Failed {
+                    failed_procedures.insert(report.def_id);
+                }
+                self.procedure_reports.push(report);
+            }
         }
-        self.encoder.process_encoding_queue();
 
         let encoding_errors_count = self.encoder.count_encoding_errors();
         let mut program = self.encoder.get_viper_program();
+        if config::deterministic() {
+            program.sort_declarations();
+        }
 
         if config::simplify_encoding() {
             stopwatch.start_next("optimizing Viper program");
@@ -250,6 +495,66 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
             program = program.optimized(&source_file_name);
         }
 
+        if vir_passes::has_vir_passes() {
+            stopwatch.start_next("running VIR passes");
+            program = vir_passes::run_vir_passes(program);
+        }
+
+        // The optimizations and the passes may reorder the declarations.
+        if config::deterministic() {
+            program.sort_declarations();
+        }
+
+        // Relate the identifiers of the dumped Viper programs to the source
+        let viper_name_map = if config::dump_viper_program() {
+            Some(self.encoder.encode_viper_name_map(&program))
+        } else {
+            None
+        };
+
+        if config::dump_vir_items() {
+            log::report(
+                "vir_items",
+                format!("{}.json", self.env.crate_name()),
+                serde_json::to_string_pretty(&program.item_encodings()).unwrap(),
+            );
+        }
+
+        if config::encode_only() {
+            stopwatch.finish();
+            return if encoding_errors_count > 0 || has_early_errors {
+                VerificationResult::Failure
+            } else {
+                VerificationResult::Success
+            };
+        }
+
+        // Remove the methods of the procedures that are unchanged since they
+        // were verified successfully.
+        let backend_config = ViperBackendConfig::default();
+        let mut cache = VerificationCache::load(&self.env.crate_name(), &backend_config);
+        let program_context_hash = verification_cache::hash_program_context(&program, &backend_config);
+        let encoded_procedures = self.encoder.get_encoded_procedures();
+        let mut procedure_keys = vec![];
+        let mut cached_procedures_count = 0;
+        program.methods.retain(|method| {
+            let key = verification_cache::procedure_key(program_context_hash, method);
+            if let Some(&proc_id) = encoded_procedures.get(&method.name()) {
+                procedure_keys.push((proc_id, key));
+            }
+            let is_cached = cache.is_verified(key);
+            if is_cached {
+                cached_procedures_count += 1;
+            }
+            !is_cached
+        });
+        if cached_procedures_count > 0 {
+            info!(
+                "Skipping {} procedures that are unchanged since they were verified",
+                cached_procedures_count
+            );
+        }
+
         stopwatch.start_next("verifying Viper program");
         let source_path = self.env.source_path();
         let program_name = source_path
@@ -258,9 +563,29 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
             .to_str()
             .unwrap()
             .to_owned();
-        let verification_result: viper::VerificationResult = if let Some(server_address) =
-            config::server_address()
+        if let Some(viper_name_map) = viper_name_map {
+            log::report(
+                "viper_program",
+                format!("{}.names.json", program_name),
+                viper_name_map.to_json(),
+            );
+        }
+        // The program is needed to trace the provenance of the errors
+        let traced_program = config::trace_provenance().map(|_| program.clone());
+
+        // With several jobs, the methods are verified as separate programs.
+        // So are they if a module overrides the timeout of its methods.
+        let jobs = if program.methods.len() > 1 { config::jobs() } else { 1 };
+        let split = program.methods.len() > 1 && (jobs > 1 || config::has_module_timeouts());
+        let verified_methods: Vec<String> = program.methods.iter().map(|method| method.name()).collect();
+        // The time spent verifying each method, if it is verified separately.
+        let mut method_times: HashMap<String, Duration> = HashMap::new();
+        let verification_start = Instant::now();
+        let verification_result: viper::VerificationResult = if program.methods.is_empty()
+            && cached_procedures_count > 0
         {
+            viper::VerificationResult::Success()
+        } else if let Some(server_address) = config::server_address() {
             let server_address = if server_address == "MOCK" {
                 ServerSideService::spawn_off_thread().to_string()
             } else {
@@ -274,22 +599,72 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
                 )
             });
 
-            let request = VerificationRequest {
-                program,
-                program_name,
-                backend_config: Default::default(),
-            };
-            service.verify(request)
+            if split {
+                let requests = split_program(program, &program_name)
+                    .into_iter()
+                    .map(|(program_name, program)| VerificationRequest {
+                        backend_config: self.backend_config_of(&program, &encoded_procedures),
+                        program,
+                        program_name,
+                        crate_name: self.env.crate_name(),
+                    })
+                    .collect();
+                merge_verification_results(service.verify_batch(requests))
+            } else {
+                let request = VerificationRequest {
+                    program,
+                    program_name,
+                    crate_name: self.env.crate_name(),
+                    backend_config,
+                };
+                service.verify(request)
+            }
         } else {
             let mut stopwatch = Stopwatch::start("prusti-viper", "JVM startup");
             let verifier_builder = VerifierBuilder::new();
             stopwatch.start_next("running verifier");
-            VerifierRunner::with_default_configured_runner(&verifier_builder, |runner| {
-                runner.verify(program, program_name.as_str())
-            })
+            if split {
+                // The programs are verified by groups of the same backend
+                // configuration, which differ only in module timeouts.
+                let verifier_builder = Arc::new(verifier_builder);
+                let mut groups: Vec<(ViperBackendConfig, Vec<(String, vir::Program)>)> = vec![];
+                for (name, program) in split_program(program, &program_name) {
+                    let config = self.backend_config_of(&program, &encoded_procedures);
+                    match groups.iter_mut().find(|(group_config, _)| *group_config == config) {
+                        Some((_, programs)) => programs.push((name, program)),
+                        None => groups.push((config, vec![(name, program)])),
+                    }
+                }
+                let mut results = vec![];
+                for (config, programs) in groups {
+                    let method_names: Vec<String> = programs.iter()
+                        .map(|(_, program)| program.methods[0].name())
+                        .collect();
+                    let timed_results =
+                        verify_in_parallel(verifier_builder.clone(), &config, programs, jobs);
+                    for (method_name, (result, time)) in method_names.into_iter().zip(timed_results) {
+                        method_times.insert(method_name, time);
+                        results.push(result);
+                    }
+                }
+                merge_verification_results(results)
+            } else {
+                VerifierRunner::with_default_configured_runner(&verifier_builder, |runner| {
+                    runner.verify(program, program_name.as_str())
+                })
+            }
         };
 
         stopwatch.finish();
+        let verification_time = verification_start.elapsed();
+        for method_name in &verified_methods {
+            if let Some(&proc_id) = encoded_procedures.get(method_name) {
+                let time = method_times.get(method_name).cloned().unwrap_or(verification_time);
+                for report in self.procedure_reports.iter_mut().filter(|report| report.def_id == proc_id) {
+                    report.verification_time = time;
+                }
+            }
+        }
 
         let verification_errors = match verification_result {
             viper::VerificationResult::Success() => vec![],
@@ -301,29 +676,157 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
                         format!("consistency error: {}", e), DUMMY_SP.into()
                     ).emit(self.env)
                 });
+                self.fail_all_procedures();
                 return VerificationResult::Failure;
             }
             viper::VerificationResult::JavaException(exception) => {
                 error!("Java exception: {}", exception.get_stack_trace());
-                PrustiError::internal(
-                    format!("{}", exception), DUMMY_SP.into()
-                ).emit(self.env);
-                return VerificationResult::Failure;
+                let message = format!("{}", exception);
+                let prusti_error = PrustiError::internal(message, DUMMY_SP.into());
+                self.fail_all_procedures();
+                return if self.emit_error(prusti_error) {
+                    VerificationResult::Failure
+                } else {
+                    VerificationResult::Success
+                };
             }
         };
 
-        if encoding_errors_count == 0 && verification_errors.is_empty() {
-            VerificationResult::Success
-        } else {
-            let error_manager = self.encoder.error_manager();
+        let mut verification_errors = verification_errors;
+        if config::deterministic() {
+            verification_errors.sort_by(|a, b| {
+                (&a.pos_id, &a.full_id, &a.message).cmp(&(&b.pos_id, &b.full_id, &b.message))
+            });
+        }
+        let mut attributed_errors = vec![];
+        for verification_error in verification_errors {
+            debug!("Verification error: {:?}", verification_error);
+            let mut prusti_error = self.encoder.error_manager()
+                .translate_verification_error(&verification_error);
+            debug!("Prusti error: {:?}", prusti_error);
+            if let (Some(filter), Some(program)) = (config::trace_provenance(), &traced_program) {
+                if filter == "all" || prusti_error.message().contains(&filter) {
+                    user::message(
+                        self.encoder.error_manager().describe_provenance(&verification_error, program)
+                    );
+                }
+            }
+            let procedures = self.attribute_error(&prusti_error);
+            if let (Some(counterexample), &[proc_id]) =
+                (&verification_error.counterexample, &procedures[..])
+            {
+                debug!("Counterexample: {:?}", counterexample);
+                let pos_id = verification_error.pos_id.as_ref()
+                    .and_then(|pos_id| pos_id.parse().ok());
+                if let Some(description) =
+                    self.encoder.describe_counterexample(proc_id, pos_id, counterexample)
+                {
+                    prusti_error = prusti_error.set_counterexample(description);
+                }
+            }
+            if let &[proc_id] = &procedures[..] {
+                prusti_error = prusti_error.set_method(self.env.get_absolute_item_name(proc_id));
+            }
+            attributed_errors.push((prusti_error, procedures));
+        }
+
+        // The callers of procedures that failed are reported as skipped
+        // (dependency failed). Their own errors are still reported, because
+        // their verification only relies on the contract of the callee.
+        let failing_procedures: HashSet<_> = self.procedure_reports.iter()
+            .filter(|report| report.status == ProcedureStatus::Failed)
+            .map(|report| report.def_id)
+            .collect();
+        for report in &mut self.procedure_reports {
+            if report.status != ProcedureStatus::Verified && report.status != ProcedureStatus::Failed {
+                continue;
+            }
+            let failed_callee = call_graph.callees(report.def_id)
+                .find(|callee| failing_procedures.contains(callee));
+            if let Some(callee) = failed_callee {
+                PrustiError::warning(
+                    format!(
+                        "this procedure is reported as skipped (dependency failed), because \
+                        the called procedure '{}' failed",
+                        self.env.get_absolute_item_name(callee),
+                    ),
+                    report.span.into(),
+                ).emit(self.env);
+                report.status = ProcedureStatus::DependencyFailed;
+            }
+        }
 
-            for verification_error in verification_errors {
-                debug!("Verification error: {:?}", verification_error);
-                let prusti_error = error_manager.translate_verification_error(&verification_error);
-                debug!("Prusti error: {:?}", prusti_error);
-                prusti_error.emit(self.env);
+        // An error that is not attributed to a method might come from the
+        // declarations shared by all methods, in which case no procedure is
+        // recorded as verified.
+        let has_shared_errors = attributed_errors.iter().any(|(_, procedures)| {
+            !procedures.iter().any(|proc_id| procedure_keys.iter().any(|(id, _)| id == proc_id))
+        });
+        if !has_shared_errors {
+            for report in &self.procedure_reports {
+                if report.status != ProcedureStatus::Verified {
+                    continue;
+                }
+                for &(_, key) in procedure_keys.iter().filter(|(id, _)| *id == report.def_id) {
+                    cache.set_verified(key);
+                }
             }
+        }
+        if let Err(message) = cache.save() {
+            PrustiError::warning(message, DUMMY_SP.into()).emit(self.env);
+        }
+        self.cache_stats = cache.stats();
+        if config::cache_stats() {
+            user::message(format!(
+                "verification cache of {}: {}",
+                self.env.crate_name(),
+                self.cache_stats,
+            ));
+        }
+
+        // Errors reported as warnings (e.g. because of `FAIL_ON` or of the
+        // baseline) do not make the verification fail.
+        let mut has_errors = encoding_errors_count > 0 || has_early_errors;
+        for (mut prusti_error, procedures) in attributed_errors {
+            if procedures.is_empty() {
+                debug!("Verification error not attributed to any procedure");
+            } else if procedures.iter().all(|&proc_id| {
+                baseline.accepts(&self.env.get_absolute_item_name(proc_id))
+            }) {
+                prusti_error.set_warning();
+                prusti_error = prusti_error.set_help("This failure is accepted by the baseline.");
+            }
+            has_errors |= self.emit_error(prusti_error);
+        }
+        if has_errors {
             VerificationResult::Failure
+        } else {
+            VerificationResult::Success
         }
     }
+
+    /// The backend configuration of a program of `split_program`, with the
+    /// settings of the module of its method (see `config::enter_module`).
+    fn backend_config_of(
+        &self,
+        program: &vir::Program,
+        encoded_procedures: &HashMap<String, ProcedureDefId>,
+    ) -> ViperBackendConfig {
+        let _module_scope = encoded_procedures
+            .get(&program.methods[0].name())
+            .map(|&proc_id| config::enter_module(&self.env.get_absolute_item_name(proc_id)));
+        ViperBackendConfig::default()
+    }
+}
+
+/// Split `program` into one program per method, named after the method.
+fn split_program(program: vir::Program, program_name: &str) -> Vec<(String, vir::Program)> {
+    program
+        .split_methods()
+        .into_iter()
+        .map(|program| {
+            let name = format!("{}.{}", program_name, program.methods[0].name());
+            (name, program)
+        })
+        .collect()
 }
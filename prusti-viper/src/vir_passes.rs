@@ -0,0 +1,44 @@
+// © 2021, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A registration point for external VIR passes, which run on the encoded
+//! program of a crate after Prusti's optimizations and before verification.
+//! A pass can, for example, inject additional assertions or instrumentation.
+
+use log::info;
+use prusti_common::vir;
+use std::sync::RwLock;
+
+/// A transformation of the encoded Viper program.
+pub trait VirPass: Send + Sync {
+    /// The name of the pass, used in the logs.
+    fn name(&self) -> &str;
+
+    /// Transform the program of a crate.
+    fn run(&self, program: vir::Program) -> vir::Program;
+}
+
+lazy_static! {
+    static ref VIR_PASSES: RwLock<Vec<Box<dyn VirPass>>> = RwLock::new(vec![]);
+}
+
+/// Register a pass that runs on the programs of all the crates verified
+/// afterwards by this process. Passes run in the order of registration.
+pub fn register_vir_pass(pass: Box<dyn VirPass>) {
+    VIR_PASSES.write().unwrap().push(pass);
+}
+
+pub(crate) fn has_vir_passes() -> bool {
+    !VIR_PASSES.read().unwrap().is_empty()
+}
+
+pub(crate) fn run_vir_passes(mut program: vir::Program) -> vir::Program {
+    for pass in VIR_PASSES.read().unwrap().iter() {
+        info!("Running VIR pass '{}'", pass.name());
+        program = pass.run(program);
+    }
+    program
+}
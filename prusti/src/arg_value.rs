@@ -30,6 +30,18 @@ pub fn arg_value<'a, T: Deref<Target = str>>(
     None
 }
 
+/// Remove the command-line option `find_arg` from `args`, returning its value. The parameter is
+/// assumed to be either `--arg=value` or `--arg value`.
+pub fn take_arg_value(args: &mut Vec<String>, find_arg: &str) -> Option<String> {
+    let index = args.iter().position(|arg| arg.splitn(2, '=').next() == Some(find_arg))?;
+    let arg = args.remove(index);
+    match arg.splitn(2, '=').nth(1) {
+        Some(value) => Some(value.to_string()),
+        None if index < args.len() => Some(args.remove(index)),
+        None => None,
+    }
+}
+
 #[test]
 fn test_arg_value() {
     let args = &["--bar=bar", "--foobar", "123", "--foo"];
@@ -43,3 +55,17 @@ fn test_arg_value() {
     assert_eq!(arg_value(args, "--foobar", |p| p == "123"), Some("123"));
     assert_eq!(arg_value(args, "--foo", |_| true), None);
 }
+
+#[test]
+fn test_take_arg_value() {
+    let to_args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+    let mut args = to_args(&["--bar=bar", "--foobar", "123", "--foo"]);
+    assert_eq!(take_arg_value(&mut args, "--baz"), None);
+    assert_eq!(take_arg_value(&mut args, "--bar"), Some("bar".to_string()));
+    assert_eq!(args, to_args(&["--foobar", "123", "--foo"]));
+    assert_eq!(take_arg_value(&mut args, "--foobar"), Some("123".to_string()));
+    assert_eq!(args, to_args(&["--foo"]));
+    assert_eq!(take_arg_value(&mut args, "--foo"), None);
+    assert!(args.is_empty());
+}
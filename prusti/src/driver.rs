@@ -28,17 +28,15 @@ extern crate smallvec;
 extern crate regex;
 extern crate prusti_common;
 
-mod callbacks;
-mod verifier;
 mod arg_value;
 
-use std::{env, panic, borrow::Cow, path::PathBuf};
+use std::{env, panic, borrow::Cow};
 use prusti_common::report::user;
 use lazy_static::lazy_static;
-use callbacks::PrustiCompilerCalls;
+use prusti_facade::{DefaultCallbacks, PrustiConfig};
 use rustc_middle::ty::TyCtxt;
 use prusti_common::config;
-use arg_value::arg_value;
+use arg_value::{arg_value, take_arg_value};
 
 /// Link to report Prusti bugs
 const BUG_REPORT_URL: &str = "https://github.com/viperproject/prusti-dev/issues/new";
@@ -122,6 +120,28 @@ fn main() {
     // have been filtered out.
     let mut rustc_args = config::get_filtered_args();
 
+    // With `--message-format=json`, rustc emits its diagnostics, and thus the Prusti errors, as
+    // JSON. The diagnostics are also JSON if `--error-format=json` is given directly, e.g. by
+    // `cargo --message-format=json`.
+    match take_arg_value(&mut rustc_args, "--message-format") {
+        Some(format) if format == "json" => {
+            if arg_value(&rustc_args, "--error-format", |_| true).is_none() {
+                rustc_args.push("--error-format=json".to_owned());
+            }
+        }
+        Some(format) if format == "human" => {}
+        Some(format) => {
+            eprintln!("error: unknown message format '{}', expected 'human' or 'json'", format);
+            std::process::exit(1);
+        }
+        None => {}
+    }
+    // `PRUSTI_MESSAGE_FORMAT=json` has the same effect.
+    if config::json_messages() && arg_value(&rustc_args, "--error-format", |_| true).is_none() {
+        rustc_args.push("--error-format=json".to_owned());
+    }
+    let json_errors = arg_value(&rustc_args, "--error-format", |val| val == "json").is_some();
+
     // If the environment asks us to actually be rustc, or if lints have been disabled, then
     // run `rustc` instead of Prusti.
     let prusti_be_rustc = config::be_rustc();
@@ -133,8 +153,8 @@ fn main() {
     lazy_static::initialize(&ICE_HOOK);
     init_loggers();
 
-    let exit_code = rustc_driver::catch_with_exit_code(move || {
-
+    // The banner would mix with the JSON diagnostics.
+    if !config::json_messages() && !json_errors {
         user::message(format!(
             "{}\n{}\n{}\n\n{}\n\n",
             r"  __          __        __  ___             ",
@@ -142,42 +162,12 @@ fn main() {
             r" |      /\   |  \ \__/ .__/  |       /\   | ",
             get_prusti_version_info(),
         ));
+    }
 
-        env::set_var("POLONIUS_ALGORITHM", "Naive");
-        rustc_args.push("-Zborrowck=mir".to_owned());
-        rustc_args.push("-Zpolonius".to_owned());
-        rustc_args.push("-Znll-facts".to_owned());
-        rustc_args.push(format!(
-            "-Znll-facts-dir={}",
-            PathBuf::from(config::log_dir()).join("nll-facts").to_str()
-                .expect("failed to configure nll-facts-dir")
-        ));
-        rustc_args.push("-Zidentify-regions".to_owned());
-        rustc_args.push(format!(
-            "-Zdump-mir-dir={}",
-            PathBuf::from(config::log_dir()).join("mir").to_str()
-                .expect("failed to configure dump-mir-dir")
-        ));
-        rustc_args.push("-Zdump-mir=renumber".to_owned());
-        rustc_args.push("-Zalways-encode-mir".to_owned());
-        rustc_args.push("-Zcrate-attr=feature(register_tool)".to_owned());
-        rustc_args.push("-Zcrate-attr=register_tool(prusti)".to_owned());
-        rustc_args.push("--cfg=prusti".to_owned());
-
-        if config::check_overflows() {
-            // Some crates might have a `overflow-checks = false` in their `Cargo.toml` to
-            // disable integer overflow checks, but we want to ignore that.
-            rustc_args.push("-Zforce-overflow-checks=yes".to_owned());
-        }
-
-        if config::dump_debug_info() {
-            rustc_args.push("-Zdump-mir=all".to_owned());
-            rustc_args.push("-Zdump-mir-graphviz".to_owned());
-        }
-
-        let mut callbacks = PrustiCompilerCalls::default();
-
-        rustc_driver::RunCompiler::new(&rustc_args, &mut callbacks).run()
-    });
-    std::process::exit(exit_code)
+    let mut prusti_config = PrustiConfig::new(rustc_args);
+    if json_errors {
+        prusti_config = prusti_config.set("message_format", "json");
+    }
+    let results = prusti_facade::verify_crate(prusti_config, &mut DefaultCallbacks);
+    std::process::exit(results.exit_code)
 }
@@ -97,6 +97,7 @@ fn main() {
             // Scala
             java_class!("scala.Some", vec![
                 constructor!(),
+                method!("get"),
             ]),
             java_class!("scala.None$", vec![
                 object_getter!(),
@@ -515,6 +516,11 @@ fn main() {
             ]),
             java_class!("viper.silver.ast.Program", vec![
                 constructor!(),
+                method!("domains"),
+                method!("fields"),
+                method!("functions"),
+                method!("predicates"),
+                method!("methods"),
             ]),
             java_class!("viper.silver.ast.RangeSeq", vec![
                 constructor!(),
@@ -595,6 +601,22 @@ fn main() {
             java_class!("viper.silver.ast.WildcardPerm", vec![
                 constructor!(),
             ]),
+            java_class!("viper.silver.parser.FastParser", vec![
+                constructor!(),
+                method!("parse", "(Ljava/lang/String;Ljava/nio/file/Path;Lscala/Option;)Lviper/silver/parser/PProgram;"),
+            ]),
+            java_class!("viper.silver.parser.PProgram", vec![
+                method!("errors"),
+            ]),
+            java_class!("viper.silver.parser.Resolver", vec![
+                constructor!(),
+                method!("run"),
+                method!("messages"),
+            ]),
+            java_class!("viper.silver.parser.Translator", vec![
+                constructor!(),
+                method!("translate"),
+            ]),
             java_class!("viper.silver.verifier.AbortedExceptionally", vec![
                 constructor!(),
                 method!("cause"),
@@ -613,6 +635,10 @@ fn main() {
                 method!("fullId"),
                 method!("reason"),
                 method!("readableMessage", "()Ljava/lang/String;"),
+                method!("counterexample"),
+            ]),
+            java_class!("viper.silver.verifier.Counterexample", vec![
+                method!("model"),
             ]),
             java_class!("viper.silver.verifier.ErrorReason", vec![
                 method!("id"),
@@ -41,6 +41,27 @@ impl<'a> AstFactory<'a> {
         )
     }
 
+    /// Build a program with the declarations of both `program` and `other`.
+    pub fn merge_programs(&self, program: Program<'a>, other: Program<'a>) -> Program<'a> {
+        let program_wrapper = ast::Program::with(self.env);
+        let merge = |members: &dyn Fn(JObject<'a>) -> jni::errors::Result<JObject<'a>>| {
+            let mut merged = self.jni.seq_to_vec(self.jni.unwrap_result(members(program.to_jobject())));
+            merged.extend(self.jni.seq_to_vec(self.jni.unwrap_result(members(other.to_jobject()))));
+            self.jni.new_seq(&merged)
+        };
+        build_ast_node!(
+            self,
+            Program,
+            ast::Program,
+            merge(&|p| program_wrapper.call_domains(p)),
+            merge(&|p| program_wrapper.call_fields(p)),
+            merge(&|p| program_wrapper.call_functions(p)),
+            merge(&|p| program_wrapper.call_predicates(p)),
+            merge(&|p| program_wrapper.call_methods(p)),
+            self.jni.new_seq(&[])
+        )
+    }
+
     pub fn field(&self, name: &str, typ: Type) -> Field<'a> {
         build_ast_node!(
             self,
@@ -5,9 +5,10 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use ast_factory::Program;
-use jni::objects::JObject;
+use jni::objects::{JObject, JValue};
 use jni::JNIEnv;
 use jni_utils::JniUtils;
+use viper_sys::wrappers::java;
 use viper_sys::wrappers::viper::*;
 use JavaException;
 
@@ -46,6 +47,122 @@ impl<'a> AstUtils<'a> {
         )
     }
 
+    /// Parse and typecheck the standalone Viper program `source`. On failure,
+    /// returns the messages of the parser or of the type checker.
+    pub fn parse_program(&self, source: &str) -> Result<Program<'a>, Vec<String>> {
+        let parser_wrapper = silver::parser::FastParser::with(self.env);
+        let parser = self.jni.unwrap_result(parser_wrapper.new());
+        let path = self.jni.unwrap_result(
+            java::nio::file::Paths::with(self.env)
+                .call_get(self.jni.new_string(""), self.jni.new_object_array(0)),
+        );
+        let parsed_program = self.jni.unwrap_result(parser_wrapper.call_parse(
+            parser,
+            self.jni.new_string(source),
+            path,
+            self.jni.new_option(None),
+        ));
+        let parse_errors = self.jni.seq_to_vec(self.jni.unwrap_result(
+            silver::parser::PProgram::with(self.env).call_errors(parsed_program),
+        ));
+        if !parse_errors.is_empty() {
+            return Err(parse_errors.into_iter().map(|e| self.jni.to_string(e)).collect());
+        }
+
+        let resolver_wrapper = silver::parser::Resolver::with(self.env);
+        let resolver = self.jni.unwrap_result(resolver_wrapper.new(parsed_program));
+        let resolved_program = self.jni.unwrap_result(resolver_wrapper.call_run(resolver));
+        if !self.jni.is_instance_of(resolved_program, "scala/Some") {
+            let messages = self.jni.unwrap_result(resolver_wrapper.call_messages(resolver));
+            return Err(self.jni.seq_to_vec(messages).into_iter()
+                .map(|m| self.jni.to_string(m))
+                .collect());
+        }
+        let resolved_program = self.jni.unwrap_result(
+            viper_sys::wrappers::scala::Some::with(self.env).call_get(resolved_program),
+        );
+
+        let translator_wrapper = silver::parser::Translator::with(self.env);
+        let translator = self.jni.unwrap_result(translator_wrapper.new(resolved_program));
+        let program = self.jni.unwrap_result(translator_wrapper.call_translate(translator));
+        if !self.jni.is_instance_of(program, "scala/Some") {
+            return Err(vec!["the Viper program could not be translated".to_string()]);
+        }
+        Ok(Program::new(self.jni.unwrap_result(
+            viper_sys::wrappers::scala::Some::with(self.env).call_get(program),
+        )))
+    }
+
+    /// Convert the program to the JSON form of its Silver AST. Each node is
+    /// an object with the name of its case class and its arguments, without
+    /// the positions, so that the program can be consumed without parsing it.
+    pub fn to_json(&self, program: Program<'a>) -> serde_json::Value {
+        self.node_to_json(program.to_jobject())
+    }
+
+    fn node_to_json(&self, object: JObject<'a>) -> serde_json::Value {
+        use serde_json::Value;
+        if object.is_null() {
+            return Value::Null;
+        }
+        // A program has too many nodes to keep a local reference to each.
+        self.jni.unwrap_result(self.env.push_local_frame(16));
+        let value = if self.jni.is_instance_of(object, "java/lang/String") {
+            Value::String(self.jni.get_string(object))
+        } else if self.jni.is_instance_of(object, "java/lang/Boolean") {
+            Value::Bool(self.jni.to_string(object) == "true")
+        } else if self.jni.is_instance_of(object, "scala/math/BigInt") {
+            let number = self.jni.to_string(object);
+            number.parse::<i64>().map(Value::from).unwrap_or(Value::String(number))
+        } else if self.jni.is_instance_of(object, "scala/None$") {
+            Value::Null
+        } else if self.jni.is_instance_of(object, "scala/Some") {
+            self.node_to_json(self.jni.unwrap_result(
+                viper_sys::wrappers::scala::Some::with(self.env).call_get(object),
+            ))
+        } else if self.jni.is_instance_of(object, "scala/collection/Iterable") {
+            // Sequences, sets and maps, whose entries are tuples.
+            let seq = self.jni.unwrap_result(
+                self.env
+                    .call_method(object, "toSeq", "()Lscala/collection/Seq;", &[])
+                    .and_then(|value| value.l()),
+            );
+            Value::Array(
+                self.jni.seq_to_vec(seq).into_iter().map(|item| self.node_to_json(item)).collect(),
+            )
+        } else if self.jni.is_instance_of(object, "scala/Product") {
+            let name = self.jni.get_string(self.jni.unwrap_result(
+                self.env
+                    .call_method(object, "productPrefix", "()Ljava/lang/String;", &[])
+                    .and_then(|value| value.l()),
+            ));
+            let arity = self.jni.unwrap_result(
+                self.env
+                    .call_method(object, "productArity", "()I", &[])
+                    .and_then(|value| value.i()),
+            );
+            let args = (0..arity)
+                .map(|index| {
+                    self.node_to_json(self.jni.unwrap_result(
+                        self.env
+                            .call_method(
+                                object,
+                                "productElement",
+                                "(I)Ljava/lang/Object;",
+                                &[JValue::Int(index)],
+                            )
+                            .and_then(|value| value.l()),
+                    ))
+                })
+                .collect();
+            serde_json::json!({ "node": name, "args": Value::Array(args) })
+        } else {
+            Value::String(self.jni.to_string(object))
+        };
+        self.jni.unwrap_result(self.env.pop_local_frame(JObject::null()));
+        value
+    }
+
     pub fn to_string(&self, program: Program<'a>) -> String {
         self.jni.to_string(program.to_jobject())
     }
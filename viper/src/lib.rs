@@ -16,6 +16,7 @@ extern crate uuid;
 extern crate viper_sys;
 #[macro_use]
 extern crate serde;
+extern crate serde_json;
 
 mod ast_factory;
 mod ast_utils;
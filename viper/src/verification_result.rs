@@ -19,12 +19,18 @@ pub enum VerificationResult {
     JavaException(JavaException),
 }
 
+/// The `full_id` of the error that reports that the backend timed out.
+pub const TIMEOUT_ERROR_ID: &str = "timeout.occurred";
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct VerificationError {
     pub full_id: String,
     pub pos_id: Option<String>,
     pub reason_pos_id: Option<String>,
     pub message: String,
+    /// The model of the failure, if the backend has been asked for one.
+    #[serde(default)]
+    pub counterexample: Option<Counterexample>,
 }
 
 impl VerificationError {
@@ -33,12 +39,57 @@ impl VerificationError {
         pos_id: Option<String>,
         reason_pos_id: Option<String>,
         message: String,
+        counterexample: Option<Counterexample>,
     ) -> Self {
         VerificationError {
             full_id,
             pos_id,
             reason_pos_id,
             message,
+            counterexample,
         }
     }
+
+    /// Whether the error reports that the backend timed out, rather than a
+    /// failing assertion.
+    pub fn is_timeout(&self) -> bool {
+        self.full_id == TIMEOUT_ERROR_ID
+    }
+}
+
+/// The values that the backend assigned to the variables of the failing
+/// Viper method.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Counterexample {
+    pub entries: Vec<(String, String)>,
+}
+
+impl Counterexample {
+    /// Parse the textual representation of a Viper model, which has one
+    /// `name -> value` entry per line. Entries whose value is not a single
+    /// line (e.g. the interpretation of a function) are skipped.
+    pub fn from_model(model: &str) -> Self {
+        let entries = model
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(2, " -> ");
+                let name = parts.next()?.trim();
+                let value = parts.next()?.trim();
+                if name.is_empty() || value.is_empty() || value.contains('{') {
+                    None
+                } else {
+                    Some((name.to_string(), value.to_string()))
+                }
+            })
+            .collect();
+        Counterexample { entries }
+    }
+
+    /// The value of the variable `name`, if the model has one.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(entry_name, _)| entry_name == name)
+            .map(|(_, value)| value.as_str())
+    }
 }
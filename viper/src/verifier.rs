@@ -14,7 +14,9 @@ use jni_utils::JniUtils;
 use std::marker::PhantomData;
 use std::path::PathBuf;
 use verification_backend::VerificationBackend;
+use verification_result::Counterexample;
 use verification_result::VerificationError;
+use verification_result::TIMEOUT_ERROR_ID;
 use verification_result::VerificationResult;
 use viper_sys::wrappers::viper::*;
 
@@ -178,6 +180,21 @@ impl<'a> Verifier<'a, state::Started> {
                     .is_instance_of(viper_error, "viper/silver/verifier/VerificationError");
 
                 if !is_verification_error {
+                    let is_timeout = self
+                        .jni
+                        .is_instance_of(viper_error, "viper/silver/verifier/TimeoutOccurred");
+
+                    if is_timeout {
+                        errors.push(VerificationError::new(
+                            TIMEOUT_ERROR_ID.to_string(),
+                            None,
+                            None,
+                            self.jni.to_string(viper_error),
+                            None,
+                        ));
+                        continue;
+                    }
+
                     let is_aborted_exceptionally = self
                         .jni
                         .is_instance_of(viper_error, "viper/silver/verifier/AbortedExceptionally");
@@ -259,11 +276,14 @@ impl<'a> Verifier<'a, state::Started> {
                         None
                     };
 
+                let counterexample = self.extract_counterexample(viper_error);
+
                 errors.push(VerificationError::new(
                     error_full_id,
                     pos_id,
                     reason_pos_id,
                     message,
+                    counterexample,
                 ))
             }
 
@@ -272,4 +292,22 @@ impl<'a> Verifier<'a, state::Started> {
             VerificationResult::Success()
         }
     }
+
+    /// The counterexample attached to the error, which is only present if
+    /// the backend has been asked to produce one.
+    fn extract_counterexample(&self, viper_error: JObject<'a>) -> Option<Counterexample> {
+        let opt_counterexample = self.jni.unwrap_result(
+            silver::verifier::VerificationError::with(self.env).call_counterexample(viper_error),
+        );
+        if !self.jni.is_instance_of(opt_counterexample, "scala/Some") {
+            return None;
+        }
+        let counterexample = self
+            .jni
+            .unwrap_result(viper_sys::wrappers::scala::Some::with(self.env).call_get(opt_counterexample));
+        let model = self.jni.unwrap_result(
+            silver::verifier::Counterexample::with(self.env).call_model(counterexample),
+        );
+        Some(Counterexample::from_model(&self.jni.to_string(model)))
+    }
 }
@@ -0,0 +1,44 @@
+#[macro_use]
+extern crate lazy_static;
+extern crate viper;
+
+use viper::*;
+
+lazy_static! {
+    static ref VIPER: Viper = Viper::new();
+}
+
+#[test]
+fn program_to_json() {
+    let verification_context: VerificationContext = VIPER.new_verification_context();
+    let ast = verification_context.new_ast_factory();
+    let ast_utils = verification_context.new_ast_utils();
+
+    let method = ast.method(
+        "foobar",
+        &[ast.local_var_decl("arg", ast.int_type())],
+        &[ast.local_var_decl("res", ast.int_type())],
+        &[],
+        &[],
+        Some(ast.seqn(
+            &[ast.local_var_assign(
+                ast.local_var("res", ast.int_type()),
+                ast.add(ast.local_var("arg", ast.int_type()), ast.int_lit(1)),
+            )],
+            &[],
+        )),
+    );
+
+    let program = ast.program(&[], &[], &[], &[], &[method]);
+
+    let json = ast_utils.to_json(program);
+
+    assert_eq!(json["node"], "Program");
+    let methods = &json["args"][4];
+    assert_eq!(methods[0]["node"], "Method");
+    assert_eq!(methods[0]["args"][0], "foobar");
+    let serialized = json.to_string();
+    assert!(serialized.contains(r#"{"args":["arg",{"args":[],"node":"Int"}],"node":"LocalVarDecl"}"#));
+    assert!(serialized.contains(r#""node":"Add""#));
+    assert!(serialized.contains(r#"{"args":[1],"node":"IntLit"}"#));
+}